@@ -29,4 +29,25 @@ benchmark list-1.1 {list of six items} {
     list this that theother foo bar quux
 }
 
+# An argument for the eval benchmarks, as might be passed to a callback.
+set callbackArg "some callback data"
+
+benchmark eval-1.1 {eval a command built as a list} {
+    eval [list ident $::callbackArg]
+}
+
+benchmark eval-1.2 {eval the equivalent command built as a string} {
+    eval "ident {$::callbackArg}"
+}
+
+# A string of 1000 comma-separated items, for the string benchmarks.
+set bigstring ""
+for {set i 0} {$i < 1000} {incr i} {
+    append bigstring "item$i,"
+}
+
+benchmark string-1.1 {split and join a large string} {
+    join [split $::bigstring ,] ,
+} 100
+
 pdump
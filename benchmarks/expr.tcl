@@ -0,0 +1,35 @@
+# Benchmarks for expression evaluation: counting loops whose conditions and
+# bodies are expressions.
+pclear
+
+proc count_while {n} {
+    set i 0
+    while {$i < $n} {
+        set i [expr {$i + 1}]
+    }
+}
+
+benchmark expr-1.1 {count to 1000 in a while loop} {
+    count_while 1000
+}
+
+proc count_for {n} {
+    for {set i 0} {$i < $n} {incr i} {}
+}
+
+benchmark expr-1.2 {count to 1000 in a for loop} {
+    count_for 1000
+}
+
+proc count_compound {n} {
+    set i 0
+    set k 0
+    while {$i < $n && ($k % 7 != 3 || $i >= 0)} {
+        incr i
+        set k [expr {($i * 3 + 1) % 11}]
+    }
+}
+
+benchmark expr-1.3 {count to 1000 with compound expressions} {
+    count_compound 1000
+}
@@ -0,0 +1,79 @@
+# Benchmarks for variable access: tight loops over procedure locals and globals.
+pclear
+
+proc loop_incr {n} {
+    set i 0
+    while {$i < $n} {
+        incr i
+    }
+}
+
+benchmark var-1.1 {incr a local 100 times in a while loop} {
+    loop_incr 100
+}
+
+proc loop_locals {n} {
+    set a 1
+    set b 2
+    set c 3
+    set d 4
+    set e 5
+    for {set i 0} {$i < $n} {incr i} {
+        set sum [expr {$a + $b + $c + $d + $e}]
+    }
+}
+
+benchmark var-1.2 {read five locals 100 times in a for loop} {
+    loop_locals 100
+}
+
+proc loop_global {n} {
+    global counter
+    for {set i 0} {$i < $n} {incr i} {
+        incr counter
+    }
+}
+
+set counter 0
+
+benchmark var-1.3 {incr an upvar'd global 100 times in a for loop} {
+    loop_global 100
+}
+
+proc loop_counter {n} {
+    set counter 0
+    for {set i 0} {$i < $n} {incr i} {
+        incr counter
+    }
+}
+
+benchmark var-1.5 {incr a local counter 100 times in a for loop} {
+    loop_counter 100
+}
+
+benchmark var-1.4 {set a global array element} {
+    set ::arr(key) 5
+}
+
+proc loop_array_set {n} {
+    for {set i 0} {$i < $n} {incr i} {
+        set a($i) $i
+    }
+}
+
+benchmark var-2.1 {set 100 array elements in a for loop} {
+    loop_array_set 100
+}
+
+proc loop_array_get {n} {
+    for {set i 0} {$i < $n} {incr i} {
+        set a($i) $i
+    }
+    for {set i 0} {$i < $n} {incr i} {
+        set a($i) [expr {$a($i) + 1}]
+    }
+}
+
+benchmark var-2.2 {set and then update 100 array elements} {
+    loop_array_get 100
+}
@@ -5,8 +5,10 @@ fn main() {
     // FIRST, get the command line arguments.
     let args: Vec<String> = env::args().collect();
 
-    // NEXT, create and initialize the interpreter.
+    // NEXT, create and initialize the interpreter.  The shell's scripts own the process,
+    // so the env() array is linked to its environment.
     let mut interp = Interp::new();
+    interp.link_env();
 
     // NOTE: commands can be added to the interpreter here.
 
@@ -18,6 +20,9 @@ fn main() {
             "bench" => {
                 molt_shell::benchmark(&mut interp, &args[2..]);
             }
+            "debug" => {
+                molt_shell::debug(&mut interp, &args[2..]);
+            }
             "shell" => {
                 if args.len() == 2 {
                     println!("Molt {}", env!("CARGO_PKG_VERSION"));
@@ -54,6 +59,8 @@ fn print_help() {
     println!();
     println!("  help                          -- This help");
     println!("  shell [<script>] [args...]    -- The Molt shell");
+    println!("  debug [-break <command>...] <script> [args...]");
+    println!("                                -- The Molt debugger");
     println!("  test  [<script>] [args...]    -- The Molt test harness");
     println!("  bench [<script>] [args...]    -- The Molt benchmark tool");
     println!();
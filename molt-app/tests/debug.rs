@@ -0,0 +1,83 @@
+//! Runs `moltsh debug` on a script, with the debugger commands on standard input.
+
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+const SCRIPT: &str = "\
+proc count {start {step 1} args} {
+    set n $start
+    incr n $step
+    return $n
+}
+set total [count 5 2 x y]
+puts \"total $total\"
+";
+
+/// Debugs the script, saved in a file with the given name, with a breakpoint on `count`,
+/// returning whether the script succeeded and the debugger's output.
+fn debug(name: &str, commands: &str) -> (bool, String) {
+    let path = std::env::temp_dir().join(format!("{}-{}.tcl", name, std::process::id()));
+    std::fs::write(&path, SCRIPT).unwrap();
+
+    let mut child = Command::new(env!("CARGO_BIN_EXE_moltsh"))
+        .arg("debug")
+        .arg("-break")
+        .arg("count")
+        .arg(&path)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(commands.as_bytes())
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    (
+        output.status.success(),
+        String::from_utf8(output.stdout).unwrap(),
+    )
+}
+
+#[test]
+fn test_prompt_commands() {
+    let (ok, output) = debug(
+        "molt-debug-prompt",
+        "locals\nstep\nlocals\nwhere\nset start\nstep\nset n 100\ncontinue\n",
+    );
+
+    assert!(ok);
+    assert_eq!(
+        output,
+        "\
+-> count 5 2 x y
+(debug 1) args = x y
+start = 5
+step = 2
+(debug 1) -> set n 5
+(debug 2) args = x y
+start = 5
+step = 2
+(debug 2) #1 count 5 2 x y
+#2 set n 5
+(debug 2) 5
+(debug 2) -> incr n 2
+(debug 2) 100
+(debug 2) total 102
+"
+    );
+}
+
+#[test]
+fn test_help_and_abort() {
+    let (ok, output) = debug("molt-debug-abort", "help\nq\n");
+
+    assert!(!ok);
+    assert!(output.starts_with("-> count 5 2 x y\n(debug 1) continue, c"));
+    assert!(!output.contains("total"));
+}
@@ -5,23 +5,31 @@
 //! `molt_shell::bench` module.  The benchmarks are executed in the context of the
 //! the application's `molt::Interp` (and so can benchmark application-specific commands).
 //!
-//! The harness executes each benchmark many times and retains the average run-time
-//! in microseconds. The `molt-app` tool provides access to the test harness for a
-//! standard Molt interpreter.
+//! The harness executes each benchmark many times, optionally after a number of
+//! warm-up iterations, and retains the run-time of each measured iteration in nanoseconds.
+//! It reports the mean, median, minimum, maximum, and standard deviation of the timings.
+//! The mean timings can be saved to a file, and compared with a previously saved run.
+//! The `molt-app` tool provides access to the benchmark harness for a standard Molt
+//! interpreter.
 //!
 //! See the Molt Book (or the Molt benchmark suite) for how to write
 //! benchmarks and examples of benchmark scripts.
 
 use molt::check_args;
+use molt::molt_err;
 use molt::molt_ok;
 use molt::ContextID;
 use molt::Interp;
 use molt::MoltInt;
 use molt::MoltResult;
 use molt::Value;
+use std::collections::HashMap;
 use std::env;
 use std::fs;
+use std::io::IsTerminal;
+use std::path::Path;
 use std::path::PathBuf;
+use std::time::Instant;
 
 /// Executes the Molt benchmark harness, given the command-line arguments,
 /// in the context of the given interpreter.
@@ -65,6 +73,9 @@ pub fn benchmark(interp: &mut Interp, args: &[String]) {
 
     // NEXT, parse any options.
     let mut output_csv = false;
+    let mut warmup = 0;
+    let mut save_path: Option<PathBuf> = None;
+    let mut baseline: Option<HashMap<String, u128>> = None;
 
     let mut iter = args[1..].iter();
     loop {
@@ -79,6 +90,43 @@ pub fn benchmark(interp: &mut Interp, args: &[String]) {
             "-csv" => {
                 output_csv = true;
             }
+            "--warmup" => {
+                let count = iter.next().and_then(|arg| arg.parse::<usize>().ok());
+                match count {
+                    Some(count) => warmup = count,
+                    None => {
+                        eprintln!("Missing or invalid value for option: \"{}\"", opt);
+                        write_usage();
+                        return;
+                    }
+                }
+            }
+            "--save" | "--compare" => {
+                let file = match iter.next() {
+                    Some(file) => file,
+                    None => {
+                        eprintln!("Missing value for option: \"{}\"", opt);
+                        write_usage();
+                        return;
+                    }
+                };
+
+                if opt == "--save" {
+                    // The script is run in its own folder, so resolve the path now.
+                    save_path = match env::current_dir() {
+                        Ok(dir) => Some(dir.join(file)),
+                        Err(_) => Some(PathBuf::from(file)),
+                    };
+                } else {
+                    match read_saved(file) {
+                        Ok(saved) => baseline = Some(saved),
+                        Err(msg) => {
+                            eprintln!("{}", msg);
+                            return;
+                        }
+                    }
+                }
+            }
             _ => {
                 eprintln!("Unknown option: \"{}\"", opt);
                 write_usage();
@@ -92,12 +140,13 @@ pub fn benchmark(interp: &mut Interp, args: &[String]) {
     let path = PathBuf::from(&args[0]);
 
     // NEXT, initialize the benchmark context.
-    let context_id = interp.save_context(Context::new());
+    let context_id = interp.save_context(Context::new(warmup));
 
     // NEXT, install the test commands into the interpreter.
     interp.add_command("ident", cmd_ident);
     interp.add_context_command("measure", measure_cmd, context_id);
     interp.add_command("ok", cmd_ok);
+    interp.add_context_command("timings", timings_cmd, context_id);
 
     // NEXT, load the benchmark Tcl library
     if let Err(exception) = interp.eval(include_str!("bench.tcl")) {
@@ -107,22 +156,19 @@ pub fn benchmark(interp: &mut Interp, args: &[String]) {
         );
     }
 
-    // NEXT, execute the script.
-    match fs::read_to_string(&args[0]) {
-        Ok(script) => {
-            if let Some(parent) = path.parent() {
-                let _ = env::set_current_dir(parent);
-            }
+    // NEXT, execute the script in its own directory, so that it can source other
+    // scripts using relative paths.
+    let mut script = path.as_path();
 
-            match interp.eval(&script) {
-                Ok(_) => (),
-                Err(exception) => {
-                    eprintln!("{}", exception.value());
-                    std::process::exit(1);
-                }
-            }
+    if let (Some(parent), Some(name)) = (path.parent(), path.file_name()) {
+        if !parent.as_os_str().is_empty() && env::set_current_dir(parent).is_ok() {
+            script = Path::new(name);
         }
-        Err(e) => println!("{}", e),
+    }
+
+    if let Err(exception) = interp.eval_file(script) {
+        eprintln!("{}", exception.value());
+        std::process::exit(1);
     }
 
     // NEXT, output the test results:
@@ -130,23 +176,87 @@ pub fn benchmark(interp: &mut Interp, args: &[String]) {
 
     if output_csv {
         write_csv(ctx);
+    } else if let Some(baseline) = &baseline {
+        write_comparison(ctx, baseline);
     } else {
         write_formatted_text(ctx);
     }
+
+    if let Some(path) = save_path {
+        if let Err(e) = fs::write(&path, saved_text(ctx)) {
+            eprintln!("Error saving \"{}\": {}", path.display(), e);
+        }
+    }
+}
+
+/// Reads a file of saved benchmark results, one `name: nanos` line per benchmark, as
+/// written by the `--save` option.  Blank lines are ignored.
+fn read_saved(file: &str) -> Result<HashMap<String, u128>, String> {
+    let text = match fs::read_to_string(file) {
+        Ok(text) => text,
+        Err(e) => return Err(format!("Error reading \"{}\": {}", file, e)),
+    };
+
+    let mut saved = HashMap::new();
+
+    for (i, line) in text.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let nanos = line
+            .rsplit_once(':')
+            .and_then(|(name, nanos)| Some((name.trim(), nanos.trim().parse::<u128>().ok()?)));
+
+        match nanos {
+            Some((name, nanos)) => {
+                saved.insert(name.to_string(), nanos);
+            }
+            None => {
+                return Err(format!(
+                    "Error reading \"{}\", line {}: expected \"name: nanos\", got \"{}\"",
+                    file,
+                    i + 1,
+                    line
+                ))
+            }
+        }
+    }
+
+    Ok(saved)
+}
+
+/// Formats the benchmark results for saving as one `name: nanos` line per benchmark, giving
+/// the mean time per iteration.
+fn saved_text(ctx: &Context) -> String {
+    let mut text = String::new();
+
+    for record in &ctx.measurements {
+        text.push_str(&format!("{}: {}\n", record.name, record.stats.mean.round()));
+    }
+
+    text
 }
 
 fn write_csv(ctx: &Context) {
-    println!("\"benchmark\",\"description\",\"nanos\",\"norm\"");
+    println!(
+        "\"benchmark\",\"description\",\"nanos\",\"norm\",\"median\",\"min\",\"max\",\"stddev\""
+    );
 
     let baseline = ctx.baseline();
 
     for record in &ctx.measurements {
+        let stats = &record.stats;
         println!(
-            "\"{}\",\"{}\",{},{}",
+            "\"{}\",\"{}\",{},{},{},{},{},{}",
             strip_quotes(&record.name),
             strip_quotes(&record.description),
-            record.nanos,
-            record.nanos as f64 / (baseline as f64),
+            stats.mean.round(),
+            stats.mean / baseline,
+            stats.median,
+            stats.min,
+            stats.max,
+            stats.stddev,
         );
     }
 }
@@ -162,21 +272,88 @@ fn strip_quotes(string: &str) -> String {
 fn write_formatted_text(ctx: &Context) {
     write_version();
     println!();
-    println!("{:>8} {:>8} -- Benchmark", "Nanos", "Norm");
+    println!(
+        "{:>8} {:>8} {:>8} {:>8} {:>8} {:>8} -- Benchmark",
+        "Nanos", "Norm", "Median", "Min", "Max", "StdDev"
+    );
 
     let baseline = ctx.baseline();
 
     for record in &ctx.measurements {
+        let stats = &record.stats;
         println!(
-            "{:>8} {:>8.2} -- {} {}",
-            record.nanos,
-            record.nanos as f64 / (baseline as f64),
+            "{:>8.0} {:>8.2} {:>8.0} {:>8} {:>8} {:>8.1} -- {} {}",
+            stats.mean,
+            stats.mean / baseline,
+            stats.median,
+            stats.min,
+            stats.max,
+            stats.stddev,
             record.name,
             record.description
         );
     }
 }
 
+/// The percentage change beyond which a comparison is highlighted.
+const HIGHLIGHT_THRESHOLD: f64 = 5.0;
+
+fn write_comparison(ctx: &Context, baseline: &HashMap<String, u128>) {
+    write_version();
+    println!();
+    println!("{:>8} {:>8} {:>8} -- Benchmark", "Old", "New", "Change");
+
+    let color = std::io::stdout().is_terminal() && env::var_os("NO_COLOR").is_none();
+
+    for record in &ctx.measurements {
+        let new = record.stats.mean.round();
+
+        match baseline.get(&record.name) {
+            Some(&old) => {
+                let percent = if old > 0 {
+                    Some((new - old as f64) * 100.0 / old as f64)
+                } else {
+                    None
+                };
+                let change = match percent {
+                    Some(percent) => format!("{:+.1}%", percent),
+                    None => "n/a".to_string(),
+                };
+                let line = format!(
+                    "{:>8} {:>8} {:>8} -- {} {}",
+                    old, new, change, record.name, record.description
+                );
+
+                let percent = percent.unwrap_or(0.0);
+                if color && percent > HIGHLIGHT_THRESHOLD {
+                    // Regressions in red
+                    println!("\x1b[31m{}\x1b[0m", line);
+                } else if color && percent < -HIGHLIGHT_THRESHOLD {
+                    // Improvements in green
+                    println!("\x1b[32m{}\x1b[0m", line);
+                } else {
+                    println!("{}", line);
+                }
+            }
+            None => println!(
+                "{:>8} {:>8} {:>8} -- {} {}",
+                "-", new, "(new)", record.name, record.description
+            ),
+        }
+    }
+
+    // NEXT, flag the baseline benchmarks that weren't run.
+    let mut missing: Vec<(&String, &u128)> = baseline
+        .iter()
+        .filter(|(name, _)| !ctx.measurements.iter().any(|record| &record.name == *name))
+        .collect();
+    missing.sort();
+
+    for (name, old) in missing {
+        println!("{:>8} {:>8} {:>8} -- {}", old, "-", "(gone)", name);
+    }
+}
+
 fn write_version() {
     println!("Molt {} -- Benchmark", env!("CARGO_PKG_VERSION"));
 }
@@ -184,27 +361,36 @@ fn write_version() {
 fn write_usage() {
     write_version();
     println!();
-    println!("Usage: molt bench filename.tcl [-csv]");
+    println!(
+        "Usage: molt bench filename.tcl [-csv] [--warmup count] [--save file] [--compare file]"
+    );
 }
 
 struct Context {
-    // The baseline, in microseconds
-    baseline: Option<MoltInt>,
+    // The number of warm-up iterations to execute before measuring a benchmark
+    warmup: usize,
+
+    // The baseline, i.e., the mean of the first measurement, in nanoseconds
+    baseline: Option<f64>,
 
     // The list of measurements.
     measurements: Vec<Measurement>,
 }
 
 impl Context {
-    fn new() -> Self {
+    fn new(warmup: usize) -> Self {
         Self {
+            warmup,
             baseline: None,
             measurements: Vec::new(),
         }
     }
 
-    fn baseline(&self) -> MoltInt {
-        self.baseline.unwrap_or(1)
+    fn baseline(&self) -> f64 {
+        match self.baseline {
+            Some(baseline) if baseline > 0.0 => baseline,
+            _ => 1.0,
+        }
     }
 }
 
@@ -215,32 +401,101 @@ struct Measurement {
     // The measurement's human-readable description
     description: String,
 
-    // The average number of nanoseconds per measured iteration
-    nanos: MoltInt,
+    // The statistics of the measured iterations
+    stats: Stats,
+}
+
+/// Summary statistics for a benchmark's per-iteration timings, in nanoseconds.
+struct Stats {
+    mean: f64,
+    median: f64,
+    min: u128,
+    max: u128,
+    stddev: f64,
 }
 
-/// # measure *name* *description* *micros*
+impl Stats {
+    /// Computes the statistics for a non-empty list of timings.  The mean and standard
+    /// deviation are computed in a single pass using Welford's algorithm.
+    fn new(timings: &[u128]) -> Self {
+        let mut count = 0.0;
+        let mut mean = 0.0;
+        let mut m2 = 0.0;
+        let mut min = u128::MAX;
+        let mut max = 0;
+
+        for &nanos in timings {
+            let x = nanos as f64;
+            count += 1.0;
+            let delta = x - mean;
+            mean += delta / count;
+            m2 += delta * (x - mean);
+            min = min.min(nanos);
+            max = max.max(nanos);
+        }
+
+        let stddev = if count > 1.0 {
+            (m2 / (count - 1.0)).sqrt()
+        } else {
+            0.0
+        };
+
+        let mut sorted = timings.to_vec();
+        sorted.sort_unstable();
+        let mid = sorted.len() / 2;
+        let median = if sorted.len() % 2 == 0 {
+            (sorted[mid - 1] as f64 + sorted[mid] as f64) / 2.0
+        } else {
+            sorted[mid] as f64
+        };
+
+        Self {
+            mean,
+            median,
+            min,
+            max,
+            stddev,
+        }
+    }
+}
+
+/// # measure *name* *description* *nanos*
 ///
-/// Records a benchmark measurement.
+/// Records a benchmark measurement, given a list of one or more per-iteration timings
+/// in nanoseconds.
 fn measure_cmd(interp: &mut Interp, context_id: ContextID, argv: &[Value]) -> MoltResult {
     molt::check_args(1, argv, 4, 4, "name description nanos")?;
 
     // FIRST, get the arguments
     let name = argv[1].to_string();
     let description = argv[2].to_string();
-    let nanos = argv[3].as_int()?;
+
+    let mut timings: Vec<u128> = Vec::new();
+    for nanos in argv[3].as_list()?.iter() {
+        let nanos = nanos.as_int()?;
+        if nanos < 0 {
+            return molt_err!("expected non-negative timing but got \"{}\"", nanos);
+        }
+        timings.push(nanos as u128);
+    }
+
+    if timings.is_empty() {
+        return molt_err!("no timings for benchmark \"{}\"", name);
+    }
 
     // NEXT, get the test context
     let ctx = interp.context::<Context>(context_id);
 
+    let stats = Stats::new(&timings);
+
     if ctx.baseline.is_none() {
-        ctx.baseline = Some(nanos);
+        ctx.baseline = Some(stats.mean);
     }
 
     let record = Measurement {
         name,
         description,
-        nanos,
+        stats,
     };
 
     ctx.measurements.push(record);
@@ -248,6 +503,32 @@ fn measure_cmd(interp: &mut Interp, context_id: ContextID, argv: &[Value]) -> Mo
     molt_ok!()
 }
 
+/// # timings *body* *count*
+///
+/// Executes the body *count* times, after the harness's warm-up iterations, and returns
+/// a list of the run-time of each measured iteration in nanoseconds.
+fn timings_cmd(interp: &mut Interp, context_id: ContextID, argv: &[Value]) -> MoltResult {
+    check_args(1, argv, 3, 3, "body count")?;
+
+    let body = &argv[1];
+    let count = argv[2].as_int()?;
+    let warmup = interp.context::<Context>(context_id).warmup;
+
+    for _ in 0..warmup {
+        interp.eval_value(body)?;
+    }
+
+    let mut timings: Vec<Value> = Vec::new();
+
+    for _ in 0..count {
+        let start = Instant::now();
+        interp.eval_value(body)?;
+        timings.push(Value::from(start.elapsed().as_nanos() as MoltInt));
+    }
+
+    molt_ok!(timings)
+}
+
 /// # ident value
 ///
 /// Returns its argument.
@@ -0,0 +1,260 @@
+use molt::list_to_string;
+use molt::DebugAction;
+use molt::DebugHook;
+use molt::Interp;
+use molt::MoltList;
+use molt::Value;
+use std::io::{self, BufRead, Write};
+use std::path::Path;
+
+/// The debugger's help text.
+const HELP: &str = "\
+continue, c  -- Continue to the next breakpoint
+step, s      -- Step to the next command, into procedure calls
+next, n      -- Step to the next command, over procedure calls
+where, w     -- Show the commands being executed
+locals, l    -- Show the local variables and their values
+abort, q     -- Abort the script
+help, ?      -- Show this help
+Anything else is evaluated as a Molt script in the paused interpreter.";
+
+/// Executes a script under the debugger, from a set of command line arguments.
+///
+/// The arguments are zero or more `-break` *name* options, each setting a breakpoint on
+/// the named command, followed by the name of a Molt script file and any arguments to
+/// pass to it, as for [`script`](fn.script.html).  If no breakpoints are given, the
+/// debugger pauses before the script's first command.
+///
+/// When evaluation pauses the debugger displays the command about to be executed, and
+/// then prompts for debugger commands on standard input; see
+/// [`debugger`](fn.debugger.html).
+///
+/// # Example
+///
+/// ```no_run
+/// use molt::Interp;
+/// use std::env;
+///
+/// // FIRST, get the command line arguments, e.g., "-break myproc myscript.tcl".
+/// let args: Vec<String> = env::args().collect();
+///
+/// // NEXT, create and initialize the interpreter.
+/// let mut interp = Interp::new();
+///
+/// // NEXT, debug the script.
+/// molt_shell::debug(&mut interp, &args[1..]);
+/// ```
+pub fn debug(interp: &mut Interp, args: &[String]) {
+    // FIRST, get the breakpoints.
+    let mut i = 0;
+
+    while i < args.len() && args[i] == "-break" {
+        if i + 1 == args.len() {
+            eprintln!("Usage: debug ?-break command ...? filename ?arg ...?");
+            std::process::exit(1);
+        }
+        interp.set_breakpoint(&args[i + 1]);
+        i += 2;
+    }
+
+    if i == args.len() {
+        eprintln!("Usage: debug ?-break command ...? filename ?arg ...?");
+        std::process::exit(1);
+    }
+
+    if i == 0 {
+        interp.break_next();
+    }
+
+    // NEXT, execute the script.
+    let arg0 = &args[i];
+    let argv: MoltList = args[i + 1..].iter().map(Value::from).collect();
+    interp
+        .set_scalar("arg0", Value::from(arg0))
+        .expect("arg0 predefined as array!");
+    interp
+        .set_scalar("argv", Value::from(argv))
+        .expect("argv predefined as array!");
+
+    let stdin = Box::new(io::BufReader::new(io::stdin()));
+    interp.set_debug_hook(Some(debugger(stdin, Box::new(io::stdout()))));
+
+    let result = interp.eval_file(Path::new(arg0));
+    interp.set_debug_hook(None);
+
+    if let Err(exception) = result {
+        eprintln!("{}", exception.value());
+        std::process::exit(1);
+    }
+}
+
+/// Returns a debugger hook for use with
+/// [`Interp::set_debug_hook`](../molt/interp/struct.Interp.html#method.set_debug_hook)
+/// that reads debugger commands from `input` and writes to `output`.
+///
+/// When evaluation pauses, the hook writes the command about to be executed and then
+/// prompts for debugger commands until one of them resumes evaluation:
+///
+/// * `continue` or `c` continues to the next breakpoint.
+/// * `step` or `s` steps to the next command, into procedure calls.
+/// * `next` or `n` steps to the next command, over procedure calls.
+/// * `where` or `w` shows the commands being executed, outermost first.
+/// * `locals` or `l` shows the variables in the paused command's scope; or, if the paused
+///   command calls a procedure, the procedure's arguments as it will see them on entry.
+/// * `abort` or `q` aborts the evaluation.
+/// * `help` or `?` lists the debugger commands.
+///
+/// Any other input is evaluated as a Molt script in the paused interpreter, and its
+/// result is written to `output`.  At the end of the input, evaluation continues.
+///
+/// # Example
+///
+/// ```
+/// use molt::Interp;
+/// use std::io::{self, Cursor};
+///
+/// let mut interp = Interp::new();
+/// interp.eval("proc count {} { set n 1; incr n; return $n }").unwrap();
+///
+/// // Step from the call into the body, and change n before it's incremented.
+/// let input = Cursor::new("step\nstep\nset n 10\ncontinue\n");
+/// interp.set_debug_hook(Some(molt_shell::debugger(Box::new(input), Box::new(io::sink()))));
+/// interp.set_breakpoint("count");
+///
+/// assert_eq!(interp.eval("count").unwrap().as_int().unwrap(), 11);
+/// ```
+pub fn debugger(mut input: Box<dyn BufRead>, mut output: Box<dyn Write>) -> DebugHook {
+    Box::new(move |interp, words, level| {
+        match pause(interp, words, level, &mut *input, &mut *output) {
+            Ok(action) => action,
+            Err(err) => {
+                eprintln!("I/O Error: {}", err);
+                DebugAction::Continue
+            }
+        }
+    })
+}
+
+/// Displays the paused command, and reads and executes debugger commands until one of
+/// them resumes evaluation.
+fn pause(
+    interp: &mut Interp,
+    words: &[Value],
+    level: usize,
+    input: &mut dyn BufRead,
+    output: &mut dyn Write,
+) -> io::Result<DebugAction> {
+    writeln!(output, "-> {}", list_to_string(words))?;
+
+    loop {
+        write!(output, "(debug {}) ", level)?;
+        output.flush()?;
+
+        let mut line = String::new();
+        if input.read_line(&mut line)? == 0 {
+            writeln!(output)?;
+            return Ok(DebugAction::Continue);
+        }
+
+        match line.trim() {
+            "" => (),
+            "continue" | "c" => return Ok(DebugAction::Continue),
+            "step" | "s" => return Ok(DebugAction::Step),
+            "next" | "n" => return Ok(DebugAction::StepOver),
+            "abort" | "q" => return Ok(DebugAction::Abort),
+            "where" | "w" => show_frames(interp, output)?,
+            "locals" | "l" => show_locals(interp, words, output)?,
+            "help" | "?" => writeln!(output, "{}", HELP)?,
+            script => match interp.eval(script) {
+                Ok(value) => {
+                    if !value.as_str().is_empty() {
+                        writeln!(output, "{}", value)?;
+                    }
+                }
+                Err(exception) => writeln!(output, "{}", exception.value())?,
+            },
+        }
+    }
+}
+
+/// Writes the commands being executed, outermost first, with their frame numbers.
+fn show_frames(interp: &Interp, output: &mut dyn Write) -> io::Result<()> {
+    for number in 1..=interp.frame_count() {
+        let frame = match interp.frame_info(number as i64) {
+            Ok(frame) => frame,
+            Err(_) => continue,
+        };
+        let cmd = frame
+            .as_dict()
+            .ok()
+            .and_then(|dict| dict.get(&Value::from("cmd")).cloned())
+            .unwrap_or_else(Value::empty);
+
+        writeln!(output, "#{} {}", number, cmd)?;
+    }
+
+    Ok(())
+}
+
+/// Writes the variables in the current scope and their values, sorted by name.  If the
+/// paused command calls a procedure, the procedure's own scope doesn't exist yet, and so
+/// its arguments are written instead.
+fn show_locals(interp: &Interp, words: &[Value], output: &mut dyn Write) -> io::Result<()> {
+    if let Some(procname) = words.first() {
+        if let Ok(params) = interp.proc_args(procname.as_str()) {
+            return show_arguments(interp, procname.as_str(), &params, &words[1..], output);
+        }
+    }
+
+    let mut names: Vec<String> = interp
+        .vars_in_local_scope()
+        .iter()
+        .map(|name| name.to_string())
+        .collect();
+    names.sort();
+
+    for name in names {
+        if interp.array_exists(&name) {
+            writeln!(output, "{} = (array of {})", name, interp.array_size(&name))?;
+        } else if let Ok(value) = interp.scalar(&name) {
+            writeln!(output, "{} = {}", name, value)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Writes the values that a procedure's parameters will have on entry, given the arguments
+/// it's called with, sorted by name.  Parameters that will be unset, i.e., that have no
+/// argument or default value, are skipped.
+fn show_arguments(
+    interp: &Interp,
+    procname: &str,
+    params: &Value,
+    args: &[Value],
+    output: &mut dyn Write,
+) -> io::Result<()> {
+    let params = params.as_list().unwrap_or_default();
+    let mut locals = Vec::new();
+
+    for (i, param) in params.iter().enumerate() {
+        let value = if param.as_str() == "args" && i + 1 == params.len() {
+            Some(Value::from(args.get(i..).unwrap_or(&[]).to_vec()))
+        } else {
+            args.get(i)
+                .cloned()
+                .or_else(|| interp.proc_default(procname, param.as_str()).ok().flatten())
+        };
+
+        if let Some(value) = value {
+            locals.push((param.as_str(), value));
+        }
+    }
+    locals.sort_by(|a, b| a.0.cmp(b.0));
+
+    for (name, value) in locals {
+        writeln!(output, "{} = {}", name, value)?;
+    }
+
+    Ok(())
+}
@@ -8,13 +8,16 @@
 //!
 //! * To invoke the REPL, use [`molt_shell::repl`](./fn.repl.html).
 //! * To execute a script, use [`molt_shell::script`](./fn.script.html).
+//! * To execute a script under the debugger, use [`molt_shell::debug`](./fn.debug.html).
 //! * To execute the test harness on a Molt test script, use
 //!   [`molt_shell::test_harness`](./test_harness/index.html).
 //! * To execute the benchmark harness on a Molt test script, use
 //!   [`molt_shell::bench`](./bench/index.html).
 
 pub mod bench;
+mod debug;
 mod shell;
 
 pub use bench::*;
+pub use debug::*;
 pub use shell::*;
@@ -1,19 +1,49 @@
+use molt::parser::CompleteStatus;
 use molt::Interp;
 use molt::MoltList;
 use molt::Value;
+#[cfg(feature = "readline")]
+use rustyline::completion::Completer;
+#[cfg(feature = "readline")]
 use rustyline::error::ReadlineError;
-use rustyline::Editor;
-use std::fs;
+#[cfg(feature = "readline")]
+use rustyline::highlight::Highlighter;
+#[cfg(feature = "readline")]
+use rustyline::hint::Hinter;
+#[cfg(feature = "readline")]
+use rustyline::{Context, Editor, Helper};
+#[cfg(feature = "readline")]
+use std::collections::HashMap;
+use std::io::{self, BufRead, Write};
+use std::path::Path;
+#[cfg(feature = "readline")]
+use std::path::PathBuf;
 
-/// Invokes an interactive REPL for the given interpreter, using `rustyline` line editing.
+/// The name of the REPL's history file, in the user's home directory.
+#[cfg(feature = "readline")]
+const HISTORY_FILE: &str = ".molt_history";
+
+/// Invokes an interactive REPL for the given interpreter.
 ///
 /// The REPL will display a default prompt to the user.  Press `^C` to terminate
 /// the REPL, returning control to the caller.  Entering `exit` will also normally cause the
 /// application to terminate (but the `exit` command can be removed or redefined by the
 /// application).
 ///
+/// If a line of input isn't a complete script, e.g., it ends within a braced body, the
+/// REPL displays a continuation prompt and accumulates further lines until the script is
+/// complete, and then evaluates it.  Press `^C` while continuing to discard the
+/// accumulated input.
+///
 /// To change the prompt, set the `tcl_prompt1` TCL variable to a script that returns
-/// the desired prompt.
+/// the desired prompt; to change the continuation prompt, set the `tcl_prompt2` variable
+/// similarly.
+///
+/// With the `readline` feature, which is enabled by default, the REPL uses `rustyline`
+/// for line editing.  The command history is saved in `~/.molt_history` between
+/// sessions, and pressing `Tab` completes command names and the subcommand names of
+/// ensembles defined via `Interp::add_ensemble`.  Without it, the REPL reads plain lines
+/// from standard input.
 ///
 /// See [`molt::interp`](../molt/interp/index.html) for details on how to configure and
 /// add commands to a Molt interpreter.
@@ -32,53 +62,331 @@ use std::fs;
 /// molt_shell::repl(&mut interp);
 /// ```
 pub fn repl(interp: &mut Interp) {
-    let mut rl = Editor::<()>::new();
+    #[cfg(feature = "readline")]
+    {
+        let mut reader = LineReader::new();
+        let result = run_repl(interp, &mut reader, &mut io::stdout(), "% ");
+        reader.save_history();
+
+        if let Err(err) = result {
+            eprintln!("I/O Error: {}", err);
+        }
+    }
+
+    #[cfg(not(feature = "readline"))]
+    repl_on(interp, &mut io::stdin().lock(), &mut io::stdout(), "% ");
+}
+
+/// Invokes an interactive REPL for the given interpreter, reading lines of input from
+/// `input` and writing prompts, results, and error messages to `output`.  This allows a
+/// REPL to be embedded in a server, a GUI, or a test.  The REPL returns at the end of the
+/// input.
+///
+/// The REPL displays the given `prompt` unless `tcl_prompt1` is set, and handles
+/// incomplete scripts and `tcl_prompt2` just as [`repl`](fn.repl.html) does.  There's no
+/// line editing or history.  Note that output from the `puts` command still goes to the
+/// process's standard output.
+///
+/// # Example
+///
+/// ```
+/// use molt::Interp;
+/// use std::io::Cursor;
+///
+/// let mut interp = Interp::new();
+/// let mut input = Cursor::new("set a 1\nif {$a} {\n    set a 2\n}\nnonesuch\n");
+/// let mut output = Vec::new();
+///
+/// molt_shell::repl_on(&mut interp, &mut input, &mut output, "$ ");
+///
+/// assert_eq!(
+///     String::from_utf8(output).unwrap(),
+///     "$ 1\n$ >> >> 2\n$ invalid command name \"nonesuch\"\n$ "
+/// );
+/// ```
+pub fn repl_on(interp: &mut Interp, input: &mut dyn BufRead, output: &mut dyn Write, prompt: &str) {
+    if let Err(err) = run_repl(interp, &mut StreamReader(input), output, prompt) {
+        eprintln!("I/O Error: {}", err);
+    }
+}
+
+/// Runs the REPL, reading from the reader and writing results to the output.
+fn run_repl(
+    interp: &mut Interp,
+    reader: &mut dyn ReadLine,
+    output: &mut dyn Write,
+    prompt1: &str,
+) -> io::Result<()> {
+    // The input accumulated so far, if the script is incomplete.
+    let mut script = String::new();
 
     loop {
-        let readline = if let Ok(pscript) = interp.scalar("tcl_prompt1") {
-            match interp.eval(pscript.as_str()) {
-                Ok(prompt) => rl.readline(prompt.as_str()),
-                Err(exception) => {
-                    println!("{}", exception.value());
-                    rl.readline("% ")
-                }
-            }
+        let prompt = if script.is_empty() {
+            prompt(interp, output, "tcl_prompt1", prompt1)?
         } else {
-            rl.readline("% ")
+            prompt(interp, output, "tcl_prompt2", ">> ")?
         };
 
-        match readline {
-            Ok(line) => {
-                let line = line.trim();
-                if !line.is_empty() {
-                    match interp.eval(line) {
-                        Ok(value) => {
-                            rl.add_history_entry(line);
+        match reader.read_line(interp, &prompt, output)? {
+            Input::Line(line) => {
+                if !script.is_empty() {
+                    script.push('\n');
+                }
+                // When input isn't a terminal, the line includes its line ending.
+                script.push_str(line.trim_end_matches(['\n', '\r']));
+
+                // Wait for more input if the script is incomplete.
+                if let CompleteStatus::Incomplete { .. } = interp.check_complete(&script) {
+                    continue;
+                }
 
+                let input = script.trim();
+
+                if !input.is_empty() {
+                    reader.add_history(input);
+
+                    match interp.eval(input) {
+                        Ok(value) => {
                             // Don't output empty values.
                             if !value.as_str().is_empty() {
-                                println!("{}", value);
+                                writeln!(output, "{}", value)?;
                             }
                         }
                         Err(exception) => {
-                            println!("{}", exception.value());
+                            writeln!(output, "{}", exception.value())?;
                         }
                     }
                 }
+
+                script.clear();
             }
-            Err(ReadlineError::Interrupted) => {
-                println!("^C");
-                break;
+            Input::Interrupted => {
+                writeln!(output, "^C")?;
+
+                if script.is_empty() {
+                    return Ok(());
+                }
+
+                // Discard the incomplete script.
+                script.clear();
             }
-            Err(ReadlineError::Eof) => break,
-            Err(err) => {
-                println!("I/O Error: {:?}", err);
-                break;
+            Input::Eof => return Ok(()),
+        }
+    }
+}
+
+/// Gets the prompt: the result of evaluating the script in the named variable, if it's
+/// defined, or the default.
+fn prompt(
+    interp: &mut Interp,
+    output: &mut dyn Write,
+    var_name: &str,
+    default: &str,
+) -> io::Result<String> {
+    if let Ok(pscript) = interp.scalar(var_name) {
+        match interp.eval(pscript.as_str()) {
+            Ok(prompt) => return Ok(prompt.to_string()),
+            Err(exception) => writeln!(output, "{}", exception.value())?,
+        }
+    }
+
+    Ok(default.into())
+}
+
+/// The result of reading a line of input.
+enum Input {
+    /// A line of input.
+    Line(String),
+
+    /// The user pressed `^C`.
+    #[cfg_attr(not(feature = "readline"), allow(dead_code))]
+    Interrupted,
+
+    /// The end of the input.
+    Eof,
+}
+
+/// A source of REPL input.
+trait ReadLine {
+    /// Reads a line of input, first displaying the prompt.  Names are completed from the
+    /// interpreter's commands, if the source supports completion.
+    fn read_line(
+        &mut self,
+        interp: &Interp,
+        prompt: &str,
+        output: &mut dyn Write,
+    ) -> io::Result<Input>;
+
+    /// Adds a complete script to the input history, if the source has one.
+    fn add_history(&mut self, _script: &str) {}
+}
+
+/// Reads the REPL's input from a stream, a line at a time.
+struct StreamReader<'a>(&'a mut dyn BufRead);
+
+impl ReadLine for StreamReader<'_> {
+    fn read_line(&mut self, _: &Interp, prompt: &str, output: &mut dyn Write) -> io::Result<Input> {
+        write!(output, "{}", prompt)?;
+        output.flush()?;
+
+        let mut line = String::new();
+
+        if self.0.read_line(&mut line)? == 0 {
+            Ok(Input::Eof)
+        } else {
+            Ok(Input::Line(line))
+        }
+    }
+}
+
+/// Reads the REPL's input using `rustyline`, with history and completion.
+#[cfg(feature = "readline")]
+struct LineReader {
+    editor: Editor<Completions>,
+
+    /// The history file, if the user's home directory is known.
+    history_file: Option<PathBuf>,
+}
+
+#[cfg(feature = "readline")]
+impl LineReader {
+    fn new() -> Self {
+        let mut editor = Editor::new();
+        let history_file = dirs::home_dir().map(|dir| dir.join(HISTORY_FILE));
+
+        if let Some(path) = &history_file {
+            // There's no history to load on first use.
+            let _ = editor.load_history(path);
+        }
+
+        Self {
+            editor,
+            history_file,
+        }
+    }
+
+    fn save_history(&mut self) {
+        if let Some(path) = &self.history_file {
+            if let Err(err) = self.editor.save_history(path) {
+                eprintln!("Could not save history to {}: {}", path.display(), err);
             }
         }
     }
 }
 
+#[cfg(feature = "readline")]
+impl ReadLine for LineReader {
+    fn read_line(&mut self, interp: &Interp, prompt: &str, _: &mut dyn Write) -> io::Result<Input> {
+        self.editor.set_helper(Some(Completions::new(interp)));
+
+        match self.editor.readline(prompt) {
+            Ok(line) => Ok(Input::Line(line)),
+            Err(ReadlineError::Interrupted) => Ok(Input::Interrupted),
+            Err(ReadlineError::Eof) => Ok(Input::Eof),
+            Err(ReadlineError::Io(err)) => Err(err),
+            Err(err) => Err(io::Error::new(io::ErrorKind::Other, format!("{:?}", err))),
+        }
+    }
+
+    fn add_history(&mut self, script: &str) {
+        self.editor.add_history_entry(script);
+    }
+}
+
+/// The REPL's completion helper: a snapshot of the interpreter's command names, taken
+/// before each line is read.
+#[cfg(feature = "readline")]
+struct Completions {
+    /// The interpreter's command names.
+    commands: Vec<String>,
+
+    /// The subcommand names of the interpreter's ensembles, by ensemble name.
+    ensembles: HashMap<String, Vec<String>>,
+}
+
+#[cfg(feature = "readline")]
+impl Completions {
+    fn new(interp: &Interp) -> Self {
+        let mut commands = Vec::new();
+        let mut ensembles = HashMap::new();
+
+        for name in interp.command_names().iter() {
+            let name = name.to_string();
+
+            if let Some(ensemble) = interp.ensemble(&name) {
+                let subcommands = ensemble.command_names();
+                let subcommands = subcommands.iter().map(|s| s.to_string()).collect();
+                ensembles.insert(name.clone(), subcommands);
+            }
+
+            commands.push(name);
+        }
+
+        Self {
+            commands,
+            ensembles,
+        }
+    }
+
+    /// Gets the start of the word being typed at `pos`, and the names that complete it:
+    /// command names if it's the first word of a command, or subcommand names if it
+    /// follows the name of an ensemble.
+    fn candidates(&self, line: &str, pos: usize) -> (usize, Vec<String>) {
+        let text = &line[..pos];
+        let is_separator = |c: char| matches!(c, ';' | '[' | '{' | '\n');
+        let cmd_start = after_last(text, is_separator);
+        let word_start = after_last(text, |c| c.is_whitespace() || is_separator(c));
+
+        let words: Vec<&str> = line[cmd_start..word_start].split_whitespace().collect();
+        let names = match words.as_slice() {
+            [] => &self.commands,
+            [cmd] => match self.ensembles.get(*cmd) {
+                Some(subcommands) => subcommands,
+                None => return (pos, Vec::new()),
+            },
+            _ => return (pos, Vec::new()),
+        };
+
+        let prefix = &line[word_start..pos];
+        let matches = names.iter().filter(|name| name.starts_with(prefix));
+
+        (word_start, matches.cloned().collect())
+    }
+}
+
+/// Returns the index just past the last character in the text that matches the
+/// predicate, or 0 if there's none.
+#[cfg(feature = "readline")]
+fn after_last(text: &str, pred: impl Fn(char) -> bool) -> usize {
+    match text.char_indices().rev().find(|&(_, c)| pred(c)) {
+        Some((i, c)) => i + c.len_utf8(),
+        None => 0,
+    }
+}
+
+#[cfg(feature = "readline")]
+impl Completer for Completions {
+    type Candidate = String;
+
+    fn complete(
+        &self,
+        line: &str,
+        pos: usize,
+        _ctx: &Context<'_>,
+    ) -> rustyline::Result<(usize, Vec<String>)> {
+        Ok(self.candidates(line, pos))
+    }
+}
+
+#[cfg(feature = "readline")]
+impl Hinter for Completions {}
+
+#[cfg(feature = "readline")]
+impl Highlighter for Completions {}
+
+#[cfg(feature = "readline")]
+impl Helper for Completions {}
+
 /// Executes a script from a set of command line arguments.
 ///
 /// `args[0]` is presumed to be the name of a Molt script file, with any subsequent
@@ -120,27 +428,7 @@ pub fn repl(interp: &mut Interp) {
 /// ```
 pub fn script(interp: &mut Interp, args: &[String]) {
     let arg0 = &args[0];
-    let argv = &args[1..];
-    match fs::read_to_string(&args[0]) {
-        Ok(script) => execute_script(interp, script, arg0, argv),
-        Err(e) => println!("{}", e),
-    }
-}
-
-/// Executes a script read from a file, with any command-line arguments, in
-/// the context of the given interpreter.  The `script` is the text of the
-/// script, `arg0` is the name of the script file, and `argv` contains the script
-/// arguments.
-///
-/// # Molt Variables
-///
-/// The calling information will be passed to the interpreter in the form of Molt
-/// variables:
-///
-/// * The Molt variable `arg0` will be set to the `arg0` value.
-/// * The Molt variable `argv` will be set to the `argv` array as a Molt list.
-fn execute_script(interp: &mut Interp, script: String, arg0: &str, argv: &[String]) {
-    let argv: MoltList = argv.iter().map(Value::from).collect();
+    let argv: MoltList = args[1..].iter().map(Value::from).collect();
     interp
         .set_scalar("arg0", Value::from(arg0))
         .expect("arg0 predefined as array!");
@@ -148,7 +436,7 @@ fn execute_script(interp: &mut Interp, script: String, arg0: &str, argv: &[Strin
         .set_scalar("argv", Value::from(argv))
         .expect("argv predefined as array!");
 
-    match interp.eval(&script) {
+    match interp.eval_file(Path::new(arg0)) {
         Ok(_) => (),
         Err(exception) => {
             eprintln!("{}", exception.value());
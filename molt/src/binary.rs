@@ -0,0 +1,471 @@
+//! # The binary Command
+//!
+//! This module defines the `binary` ensemble, whose `format` and `scan` subcommands pack
+//! Molt values into strings of bytes and unpack them again, as directed by a _template_:
+//! a sequence of _fields_, each a type code, optionally followed by a count, e.g.,
+//! `a8 S2 I`.  Whitespace between fields is ignored.
+//!
+//! Byte strings are ordinary values whose data rep is a `Vec<u8>`; see the
+//! [`value`](../value/index.html#binary-data) module.
+
+use crate::check_args;
+use crate::interp::Interp;
+use crate::molt_err;
+use crate::molt_ok;
+use crate::types::*;
+
+/// A field's count.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Count {
+    /// No count was given.
+    Default,
+
+    /// An explicit count.
+    Num(usize),
+
+    /// The `*` count: all of the argument, or all of the remaining data.
+    All,
+}
+
+impl Count {
+    /// Returns the count, given the count to use if there's none and the count that `*`
+    /// stands for.
+    fn resolve(self, default: usize, all: usize) -> usize {
+        match self {
+            Count::Default => default,
+            Count::Num(n) => n,
+            Count::All => all,
+        }
+    }
+}
+
+/// A single template field.
+#[derive(Clone, Copy, Debug)]
+struct Field {
+    /// The type code.
+    code: char,
+
+    /// Whether the `u` flag was given, i.e., integers are unsigned.
+    unsigned: bool,
+
+    /// The count.
+    count: Count,
+}
+
+/// The kinds of numeric field, by type code.
+enum Number {
+    /// An integer with the given number of bytes, big-endian if true.
+    Int(usize, bool),
+
+    /// A float with the given number of bytes, big-endian if true.
+    Float(usize, bool),
+}
+
+impl Number {
+    /// Returns the kind of number for the type code, if it's a numeric code.  The `f` and
+    /// `d` codes use the machine's native byte order.
+    fn from_code(code: char) -> Option<Number> {
+        let native_big = cfg!(target_endian = "big");
+
+        match code {
+            'c' => Some(Number::Int(1, false)),
+            's' => Some(Number::Int(2, false)),
+            'S' => Some(Number::Int(2, true)),
+            'i' => Some(Number::Int(4, false)),
+            'I' => Some(Number::Int(4, true)),
+            'w' => Some(Number::Int(8, false)),
+            'W' => Some(Number::Int(8, true)),
+            'f' => Some(Number::Float(4, native_big)),
+            'r' => Some(Number::Float(4, false)),
+            'R' => Some(Number::Float(4, true)),
+            'd' => Some(Number::Float(8, native_big)),
+            'q' => Some(Number::Float(8, false)),
+            'Q' => Some(Number::Float(8, true)),
+            _ => None,
+        }
+    }
+
+    /// The number of bytes in the number.
+    fn size(&self) -> usize {
+        match self {
+            Number::Int(size, _) => *size,
+            Number::Float(size, _) => *size,
+        }
+    }
+
+    /// Appends the number, given as a value, to the bytes.
+    fn format(&self, value: &Value, bytes: &mut Vec<u8>) -> Result<(), Exception> {
+        let mut field = match self {
+            Number::Int(size, _) => value.as_int()?.to_le_bytes()[..*size].to_vec(),
+            Number::Float(4, _) => (value.as_float()? as f32).to_le_bytes().to_vec(),
+            Number::Float(_, _) => value.as_float()?.to_le_bytes().to_vec(),
+        };
+
+        if self.is_big_endian() {
+            field.reverse();
+        }
+        bytes.extend(field);
+        Ok(())
+    }
+
+    /// Reads the number from the given bytes, which are exactly the number's size.
+    fn scan(&self, field: &[u8], unsigned: bool) -> Value {
+        let mut buf = [0u8; 8];
+        buf[..field.len()].copy_from_slice(field);
+        if self.is_big_endian() {
+            buf[..field.len()].reverse();
+        }
+
+        match self {
+            Number::Int(size, _) => {
+                let bits = u64::from_le_bytes(buf);
+                let shift = 64 - 8 * *size as u32;
+                let int = if unsigned {
+                    bits as MoltInt
+                } else {
+                    // Sign-extend the field.
+                    ((bits << shift) as MoltInt) >> shift
+                };
+                Value::from(int)
+            }
+            Number::Float(4, _) => {
+                let float = f32::from_le_bytes([buf[0], buf[1], buf[2], buf[3]]);
+                Value::from(float as MoltFloat)
+            }
+            Number::Float(_, _) => Value::from(f64::from_le_bytes(buf)),
+        }
+    }
+
+    fn is_big_endian(&self) -> bool {
+        match self {
+            Number::Int(_, big) => *big,
+            Number::Float(_, big) => *big,
+        }
+    }
+}
+
+/// Parses a template into its fields.
+fn parse_template(template: &str) -> Result<Vec<Field>, Exception> {
+    let mut fields = Vec::new();
+    let mut chars = template.chars().peekable();
+
+    while let Some(code) = chars.next() {
+        if code.is_whitespace() {
+            continue;
+        }
+
+        if !"aAbBhHcsSiIwWfrRdqQx".contains(code) {
+            return molt_err!("bad field specifier \"{}\"", code);
+        }
+
+        let unsigned = chars.peek() == Some(&'u');
+        if unsigned {
+            chars.next();
+        }
+
+        let count = if chars.peek() == Some(&'*') {
+            chars.next();
+            Count::All
+        } else {
+            let mut digits = String::new();
+            while let Some(&ch) = chars.peek() {
+                if !ch.is_ascii_digit() {
+                    break;
+                }
+                digits.push(ch);
+                chars.next();
+            }
+
+            if digits.is_empty() {
+                Count::Default
+            } else {
+                match digits.parse() {
+                    Ok(n) => Count::Num(n),
+                    Err(_) => return molt_err!("bad field count \"{}\"", digits),
+                }
+            }
+        };
+
+        fields.push(Field {
+            code,
+            unsigned,
+            count,
+        });
+    }
+
+    Ok(fields)
+}
+
+/// # binary *subcommand* ?*arg*...?
+pub(crate) fn cmd_binary(interp: &mut Interp, context_id: ContextID, argv: &[Value]) -> MoltResult {
+    interp.call_subcommand(context_id, argv, 1, &BINARY_SUBCOMMANDS)
+}
+
+const BINARY_SUBCOMMANDS: [Subcommand; 2] = [
+    Subcommand("format", cmd_binary_format),
+    Subcommand("scan", cmd_binary_scan),
+];
+
+/// # binary format *template* ?*arg* ...?
+///
+/// Packs the arguments into a string of bytes as directed by the template.
+fn cmd_binary_format(_interp: &mut Interp, _: ContextID, argv: &[Value]) -> MoltResult {
+    check_args(2, argv, 3, 0, "formatString ?arg ...?")?;
+
+    let mut bytes: Vec<u8> = Vec::new();
+    let mut args = argv[3..].iter();
+
+    for field in parse_template(argv[2].as_str())? {
+        // FIRST, null bytes take no argument.
+        if field.code == 'x' {
+            let count = field.count.resolve(1, 0);
+            bytes.resize(bytes.len() + count, 0);
+            continue;
+        }
+
+        // NEXT, get the field's argument.
+        let arg = match args.next() {
+            Some(arg) => arg,
+            None => return molt_err!("not enough arguments for all format specifiers"),
+        };
+
+        match field.code {
+            'a' | 'A' => {
+                let data = arg.as_bytes();
+                let count = field.count.resolve(1, data.len());
+                let pad = if field.code == 'a' { 0 } else { b' ' };
+
+                bytes.extend(data.iter().take(count));
+                bytes.resize(bytes.len() + count.saturating_sub(data.len()), pad);
+            }
+            'b' | 'B' => {
+                let digits = arg.as_str();
+                let count = field.count.resolve(1, digits.len());
+                format_digits(&mut bytes, digits, count, field.code, 1)?;
+            }
+            'h' | 'H' => {
+                let digits = arg.as_str();
+                let count = field.count.resolve(1, digits.len());
+                format_digits(&mut bytes, digits, count, field.code, 4)?;
+            }
+            code => {
+                let number = Number::from_code(code).expect("numeric code");
+
+                if field.count == Count::Default {
+                    number.format(arg, &mut bytes)?;
+                } else {
+                    let list = arg.as_list()?;
+                    let count = field.count.resolve(1, list.len());
+
+                    if list.len() < count {
+                        return molt_err!("number of elements in list does not match count");
+                    }
+
+                    for value in &list[..count] {
+                        number.format(value, &mut bytes)?;
+                    }
+                }
+            }
+        }
+    }
+
+    molt_ok!(Value::from_bytes(bytes))
+}
+
+/// Appends a string of binary or hex digits to the bytes, each digit representing the
+/// given number of bits.  The `b` and `h` codes fill each byte starting with its low-order
+/// bits; `B` and `H`, with its high-order bits.  Missing digits are taken to be zeros.
+fn format_digits(
+    bytes: &mut Vec<u8>,
+    digits: &str,
+    count: usize,
+    code: char,
+    bits: usize,
+) -> Result<(), Exception> {
+    let per_byte = 8 / bits;
+    let start = bytes.len();
+    bytes.resize(start + count.div_ceil(per_byte), 0);
+
+    for (i, ch) in digits.chars().take(count).enumerate() {
+        let digit = match ch.to_digit(1 << bits) {
+            Some(digit) => digit as u8,
+            None if bits == 1 => {
+                return molt_err!("expected binary string but got \"{}\" instead", digits)
+            }
+            None => return molt_err!("expected hexadecimal string but got \"{}\" instead", digits),
+        };
+
+        let slot = i % per_byte;
+        let shift = if code.is_ascii_lowercase() {
+            slot * bits
+        } else {
+            8 - (slot + 1) * bits
+        };
+        bytes[start + i / per_byte] |= digit << shift;
+    }
+
+    Ok(())
+}
+
+/// # binary scan *value* *template* ?*varName* ...?
+///
+/// Unpacks the value's bytes as directed by the template, assigning each field's data to
+/// the next variable.  Stops at the first field for which there is too little data, and
+/// returns the number of variables assigned.
+fn cmd_binary_scan(interp: &mut Interp, _: ContextID, argv: &[Value]) -> MoltResult {
+    check_args(2, argv, 4, 0, "value formatString ?varName ...?")?;
+
+    let bytes = argv[2].as_bytes();
+    let mut var_names = argv[4..].iter();
+    let mut pos = 0;
+    let mut assigned: MoltInt = 0;
+
+    for field in parse_template(argv[3].as_str())? {
+        let remaining = bytes.len() - pos;
+
+        // FIRST, skipped bytes take no variable.
+        if field.code == 'x' {
+            pos += field.count.resolve(1, remaining).min(remaining);
+            continue;
+        }
+
+        // NEXT, get the field's variable.
+        let var_name = match var_names.next() {
+            Some(var_name) => var_name,
+            None => return molt_err!("not enough arguments for all format specifiers"),
+        };
+
+        // NEXT, get the field's data, stopping if there's too little data.
+        let (value, size) = match field.code {
+            'a' | 'A' => {
+                let count = field.count.resolve(1, remaining);
+                if count > remaining {
+                    break;
+                }
+
+                let mut data = &bytes[pos..pos + count];
+                if field.code == 'A' {
+                    while let Some((&(b' ' | 0), rest)) = data.split_last() {
+                        data = rest;
+                    }
+                }
+                (Value::from_bytes(data.to_vec()), count)
+            }
+            'b' | 'B' => {
+                let count = field.count.resolve(1, remaining * 8);
+                let size = count.div_ceil(8);
+                if size > remaining {
+                    break;
+                }
+                let digits = scan_digits(&bytes[pos..pos + size], count, field.code, 1);
+                (Value::from(digits), size)
+            }
+            'h' | 'H' => {
+                let count = field.count.resolve(1, remaining * 2);
+                let size = count.div_ceil(2);
+                if size > remaining {
+                    break;
+                }
+                let digits = scan_digits(&bytes[pos..pos + size], count, field.code, 4);
+                (Value::from(digits), size)
+            }
+            code => {
+                let number = Number::from_code(code).expect("numeric code");
+                let width = number.size();
+
+                if field.count == Count::Default {
+                    if width > remaining {
+                        break;
+                    }
+                    (number.scan(&bytes[pos..pos + width], field.unsigned), width)
+                } else {
+                    let count = field.count.resolve(1, remaining / width);
+                    let size = count * width;
+                    if size > remaining {
+                        break;
+                    }
+
+                    let list: MoltList = bytes[pos..pos + size]
+                        .chunks(width)
+                        .map(|chunk| number.scan(chunk, field.unsigned))
+                        .collect();
+                    (Value::from(list), size)
+                }
+            }
+        };
+
+        interp.set_var(var_name, value)?;
+        pos += size;
+        assigned += 1;
+    }
+
+    molt_ok!(assigned)
+}
+
+/// Converts the bytes to a string of binary or hex digits, each digit representing
+/// the given number of bits, as for `format_digits`.
+fn scan_digits(bytes: &[u8], count: usize, code: char, bits: usize) -> String {
+    let per_byte = 8 / bits;
+    let mask = (1u8 << bits) - 1;
+
+    (0..count)
+        .map(|i| {
+            let slot = i % per_byte;
+            let shift = if code.is_ascii_lowercase() {
+                slot * bits
+            } else {
+                8 - (slot + 1) * bits
+            };
+            let digit = (bytes[i / per_byte] >> shift) & mask;
+            std::char::from_digit(digit as u32, 16).expect("digit")
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_template() {
+        let fields = parse_template("a8 S2cu*I").unwrap();
+        let fields: Vec<_> = fields
+            .iter()
+            .map(|f| (f.code, f.unsigned, f.count))
+            .collect();
+
+        assert_eq!(
+            fields,
+            vec![
+                ('a', false, Count::Num(8)),
+                ('S', false, Count::Num(2)),
+                ('c', true, Count::All),
+                ('I', false, Count::Default),
+            ]
+        );
+
+        assert_eq!(
+            parse_template("a z").unwrap_err().value().as_str(),
+            "bad field specifier \"z\""
+        );
+    }
+
+    #[test]
+    fn test_numbers() {
+        let mut bytes = Vec::new();
+        Number::Int(2, true)
+            .format(&Value::from(0x1234), &mut bytes)
+            .unwrap();
+        Number::Int(2, false)
+            .format(&Value::from(0x1234), &mut bytes)
+            .unwrap();
+        assert_eq!(bytes, vec![0x12, 0x34, 0x34, 0x12]);
+
+        assert_eq!(Number::Int(1, false).scan(&[0xff], false).as_int(), Ok(-1));
+        assert_eq!(Number::Int(1, false).scan(&[0xff], true).as_int(), Ok(255));
+        assert_eq!(
+            Number::Int(4, true).scan(&[0x80, 0, 0, 0], false).as_int(),
+            Ok(-2147483648)
+        );
+    }
+}
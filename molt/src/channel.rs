@@ -0,0 +1,568 @@
+//! Channels
+//!
+//! A channel is a named stream of bytes that a Molt script can read from or write to:
+//! standard input, output, and error, and files opened by the `open` command.  Scripts refer
+//! to channels by their _channel IDs_, key strings like `stdin` or `file1`, which the I/O
+//! commands look up in the interpreter's `ChannelTable`.  The table is saved in the
+//! interpreter's context cache; all of the I/O commands share its `ContextID`.
+//!
+//! This module is only available with the `os` feature.
+
+use crate::molt_err;
+use crate::types::Exception;
+use std::collections::HashMap;
+use std::fs::File;
+use std::fs::OpenOptions;
+use std::io;
+use std::io::BufRead;
+use std::io::BufReader;
+use std::io::Read;
+use std::io::Seek;
+use std::io::SeekFrom;
+use std::io::Write;
+
+/// The size of a file channel's read and write buffers.
+const BUFFER_SIZE: usize = 8 * 1024;
+
+/// The table of open channels, by channel ID.
+pub(crate) struct ChannelTable {
+    channels: HashMap<String, Channel>,
+
+    /// The number of the last file opened, used to generate channel IDs.
+    last_file: usize,
+}
+
+impl ChannelTable {
+    /// Creates a channel table containing the standard channels.
+    pub fn new() -> Self {
+        let mut channels = HashMap::new();
+        channels.insert(
+            "stdin".into(),
+            Channel::new(
+                "stdin",
+                Stream::Reader(Box::new(BufReader::new(io::stdin()))),
+            ),
+        );
+        channels.insert(
+            "stdout".into(),
+            Channel::new("stdout", Stream::Writer(Box::new(io::stdout()))),
+        );
+        channels.insert(
+            "stderr".into(),
+            Channel::new("stderr", Stream::Writer(Box::new(io::stderr()))),
+        );
+
+        Self {
+            channels,
+            last_file: 0,
+        }
+    }
+
+    /// Retrieves the channel with the given ID, or an error if there is no such channel.
+    pub fn get(&mut self, id: &str) -> Result<&mut Channel, Exception> {
+        match self.channels.get_mut(id) {
+            Some(channel) => Ok(channel),
+            None => molt_err!("can not find channel named \"{}\"", id),
+        }
+    }
+
+    /// Replaces the stream written by the channel with the given ID, if it's a write-only
+    /// stream like `stdout`, flushing and returning the previous stream.
+    pub fn replace_writer(&mut self, id: &str, stream: Box<dyn Write>) -> Option<Box<dyn Write>> {
+        let channel = self.channels.get_mut(id)?;
+        match &mut channel.stream {
+            Stream::Writer(writer) => {
+                let _ = writer.flush();
+                Some(std::mem::replace(writer, stream))
+            }
+            _ => None,
+        }
+    }
+
+    /// Opens the named file with the given access mode, one of `r`, `r+`, `w`, `w+`, `a`,
+    /// or `a+`, as for C's `fopen`.  Returns the new channel's ID.
+    pub fn open(&mut self, path: &str, access: &str) -> Result<String, Exception> {
+        let mut options = OpenOptions::new();
+        let (read, write) = match access {
+            "r" => (true, false),
+            "r+" => (true, true),
+            "w" | "w+" => {
+                options.create(true).truncate(true);
+                (access == "w+", true)
+            }
+            "a" | "a+" => {
+                options.create(true).append(true);
+                (access == "a+", true)
+            }
+            _ => return molt_err!("illegal access mode \"{}\"", access),
+        };
+        options.read(read).write(write);
+
+        let file = match options.open(path) {
+            Ok(file) => file,
+            Err(e) => return molt_err!("couldn't open \"{}\": {}", path, error_message(&e)),
+        };
+
+        self.last_file += 1;
+        let id = format!("file{}", self.last_file);
+
+        let stream = Stream::File(FileStream::new(file, read, write));
+        self.channels.insert(id.clone(), Channel::new(&id, stream));
+        Ok(id)
+    }
+
+    /// Closes the channel with the given ID, flushing any pending output.
+    pub fn close(&mut self, id: &str) -> Result<(), Exception> {
+        match self.channels.remove(id) {
+            Some(mut channel) => channel.flush(),
+            None => molt_err!("can not find channel named \"{}\"", id),
+        }
+    }
+}
+
+/// Returns the reason for an I/O error in Standard TCL's wording, e.g., "no such file or
+/// directory", rather than Rust's "No such file or directory (os error 2)".
+fn error_message(err: &io::Error) -> String {
+    match err.kind() {
+        io::ErrorKind::NotFound => "no such file or directory".into(),
+        io::ErrorKind::PermissionDenied => "permission denied".into(),
+        io::ErrorKind::AlreadyExists => "file already exists".into(),
+        _ => {
+            // Other OS errors are given by the system's message, less the error number.
+            let message = err.to_string();
+            let message = match message.rfind(" (os error ") {
+                Some(index) => &message[..index],
+                None => &message,
+            };
+            let mut chars = message.chars();
+            match chars.next() {
+                Some(first) => first.to_lowercase().chain(chars).collect(),
+                None => String::new(),
+            }
+        }
+    }
+}
+
+/// A file opened for reading and/or writing.  Reads and writes share the file's offset,
+/// and so the stream buffers them itself: pending output is written to the file before
+/// reading or seeking, and input read ahead but not consumed is given back, by seeking
+/// back over it, before writing.
+struct FileStream {
+    file: File,
+    readable: bool,
+    writable: bool,
+
+    /// Input read from the file, and the position of the next byte to consume.
+    input: Vec<u8>,
+    pos: usize,
+
+    /// Output not yet written to the file.
+    output: Vec<u8>,
+}
+
+impl FileStream {
+    fn new(file: File, readable: bool, writable: bool) -> Self {
+        Self {
+            file,
+            readable,
+            writable,
+            input: Vec::new(),
+            pos: 0,
+            output: Vec::new(),
+        }
+    }
+
+    /// Writes any pending output to the file.
+    fn write_output(&mut self) -> io::Result<()> {
+        if !self.output.is_empty() {
+            self.file.write_all(&self.output)?;
+            self.output.clear();
+        }
+        Ok(())
+    }
+
+    /// Discards any buffered input, seeking back over the part of it not yet consumed so
+    /// that the file's offset is where the reader left off.
+    fn discard_input(&mut self) -> io::Result<()> {
+        let unread = self.input.len() - self.pos;
+        self.input.clear();
+        self.pos = 0;
+
+        if unread > 0 {
+            self.file.seek(SeekFrom::Current(-(unread as i64)))?;
+        }
+        Ok(())
+    }
+}
+
+impl Read for FileStream {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let available = self.fill_buf()?;
+        let count = available.len().min(buf.len());
+        buf[..count].copy_from_slice(&available[..count]);
+        self.consume(count);
+        Ok(count)
+    }
+}
+
+impl BufRead for FileStream {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        self.write_output()?;
+
+        if self.pos == self.input.len() {
+            self.input.resize(BUFFER_SIZE, 0);
+            self.pos = 0;
+            match self.file.read(&mut self.input) {
+                Ok(count) => self.input.truncate(count),
+                Err(e) => {
+                    self.input.clear();
+                    return Err(e);
+                }
+            }
+        }
+
+        Ok(&self.input[self.pos..])
+    }
+
+    fn consume(&mut self, amount: usize) {
+        self.pos = (self.pos + amount).min(self.input.len());
+    }
+}
+
+impl Write for FileStream {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.discard_input()?;
+
+        if self.output.len() + buf.len() > BUFFER_SIZE {
+            self.write_output()?;
+        }
+
+        if buf.len() >= BUFFER_SIZE {
+            self.file.write(buf)
+        } else {
+            self.output.extend_from_slice(buf);
+            Ok(buf.len())
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.write_output()?;
+        self.file.flush()
+    }
+}
+
+impl Seek for FileStream {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        self.write_output()?;
+
+        // The file's offset is past any input not yet consumed.
+        let unread = (self.input.len() - self.pos) as i64;
+        self.input.clear();
+        self.pos = 0;
+
+        match pos {
+            SeekFrom::Current(offset) => self.file.seek(SeekFrom::Current(offset - unread)),
+            _ => self.file.seek(pos),
+        }
+    }
+}
+
+impl Drop for FileStream {
+    fn drop(&mut self) {
+        let _ = self.write_output();
+    }
+}
+
+/// A channel's underlying stream.
+enum Stream {
+    /// A stream that can only be read, e.g., standard input.
+    Reader(Box<dyn BufRead>),
+
+    /// A stream that can only be written, e.g., standard output.
+    Writer(Box<dyn Write>),
+
+    /// A file.
+    File(FileStream),
+}
+
+/// A single channel.
+pub(crate) struct Channel {
+    /// The channel's ID, for use in error messages.
+    id: String,
+
+    /// The stream the channel reads from and/or writes to.
+    stream: Stream,
+
+    /// Whether the last read from the channel reached the end of the input.
+    eof: bool,
+}
+
+impl Channel {
+    /// Creates a channel that reads from and/or writes to the given stream.
+    fn new(id: &str, stream: Stream) -> Self {
+        Self {
+            id: id.into(),
+            stream,
+            eof: false,
+        }
+    }
+
+    /// Reads the next line from the channel, stripping the line ending.  Returns `None`
+    /// if the channel is at the end of its input.
+    pub fn gets(&mut self) -> Result<Option<String>, Exception> {
+        let mut line = String::new();
+
+        match self.reader()?.read_line(&mut line) {
+            Ok(0) => {
+                self.eof = true;
+                Ok(None)
+            }
+            Ok(_) => {
+                if line.ends_with('\n') {
+                    line.pop();
+                    if line.ends_with('\r') {
+                        line.pop();
+                    }
+                } else {
+                    // The last line had no line ending.
+                    self.eof = true;
+                }
+                Ok(Some(line))
+            }
+            Err(e) => molt_err!("error reading \"{}\": {}", self.id, e),
+        }
+    }
+
+    /// Reads the remainder of the channel's input, or at most `max` bytes of it.
+    /// Invalid UTF-8 is replaced with the Unicode replacement character.
+    pub fn read(&mut self, max: Option<usize>) -> Result<String, Exception> {
+        let mut bytes = Vec::new();
+
+        let reader = self.reader()?;
+        let result = match max {
+            Some(max) => reader.take(max as u64).read_to_end(&mut bytes),
+            None => reader.read_to_end(&mut bytes),
+        };
+
+        match result {
+            Ok(count) => {
+                self.eof = match max {
+                    Some(max) => count < max,
+                    None => true,
+                };
+                Ok(String::from_utf8_lossy(&bytes).into_owned())
+            }
+            Err(e) => molt_err!("error reading \"{}\": {}", self.id, e),
+        }
+    }
+
+    /// Writes the text to the channel.  Output to the standard channels is flushed
+    /// immediately; output to files is buffered until the channel is closed.
+    pub fn puts(&mut self, text: &str) -> Result<(), Exception> {
+        let result = self.writer()?.write_all(text.as_bytes());
+
+        if let Err(e) = result {
+            return molt_err!("error writing \"{}\": {}", self.id, e);
+        }
+
+        match self.stream {
+            Stream::Writer(_) => self.flush(),
+            _ => Ok(()),
+        }
+    }
+
+    /// Returns true if the last read from the channel reached the end of its input.
+    pub fn eof(&self) -> bool {
+        self.eof
+    }
+
+    /// Flushes any pending output.
+    fn flush(&mut self) -> Result<(), Exception> {
+        let result = match &mut self.stream {
+            Stream::Reader(_) => Ok(()),
+            Stream::Writer(writer) => writer.flush(),
+            Stream::File(file) => file.flush(),
+        };
+
+        match result {
+            Ok(()) => Ok(()),
+            Err(e) => molt_err!("error writing \"{}\": {}", self.id, e),
+        }
+    }
+
+    /// Gets the channel's stream for reading, or an error if it wasn't opened for reading.
+    fn reader(&mut self) -> Result<&mut dyn BufRead, Exception> {
+        match &mut self.stream {
+            Stream::Reader(reader) => Ok(reader.as_mut()),
+            Stream::File(file) if file.readable => Ok(file),
+            _ => molt_err!("channel \"{}\" wasn't opened for reading", self.id),
+        }
+    }
+
+    /// Gets the channel's stream for writing, or an error if it wasn't opened for writing.
+    fn writer(&mut self) -> Result<&mut dyn Write, Exception> {
+        match &mut self.stream {
+            Stream::Writer(writer) => Ok(writer.as_mut()),
+            Stream::File(file) if file.writable => Ok(file),
+            _ => molt_err!("channel \"{}\" wasn't opened for writing", self.id),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    fn reader(text: &'static str) -> Channel {
+        Channel::new("test", Stream::Reader(Box::new(Cursor::new(text))))
+    }
+
+    #[test]
+    fn test_gets() {
+        let mut chan = reader("abc\ndef\r\nghi");
+
+        assert_eq!(chan.gets(), Ok(Some("abc".into())));
+        assert_eq!(chan.gets(), Ok(Some("def".into())));
+        assert!(!chan.eof());
+        assert_eq!(chan.gets(), Ok(Some("ghi".into())));
+        assert!(chan.eof());
+        assert_eq!(chan.gets(), Ok(None));
+    }
+
+    #[test]
+    fn test_read() {
+        let mut chan = reader("abc\ndef\n");
+
+        assert_eq!(chan.read(Some(2)), Ok("ab".into()));
+        assert!(!chan.eof());
+        assert_eq!(chan.read(None), Ok("c\ndef\n".into()));
+        assert!(chan.eof());
+        assert_eq!(chan.read(None), Ok("".into()));
+    }
+
+    #[test]
+    fn test_get() {
+        let mut table = ChannelTable::new();
+
+        assert!(table.get("stdin").is_ok());
+        assert!(table.get("stdout").is_ok());
+        assert!(table.get("stderr").is_ok());
+        assert!(table.get("nonesuch").is_err());
+    }
+
+    #[test]
+    fn test_wrong_direction() {
+        let mut table = ChannelTable::new();
+
+        assert_eq!(
+            table.get("stdin").unwrap().puts("abc"),
+            molt_err!("channel \"stdin\" wasn't opened for writing")
+        );
+        assert_eq!(
+            table.get("stdout").unwrap().gets(),
+            molt_err!("channel \"stdout\" wasn't opened for reading")
+        );
+    }
+
+    #[test]
+    fn test_error_message() {
+        let err = io::Error::from(io::ErrorKind::NotFound);
+        assert_eq!(error_message(&err), "no such file or directory");
+
+        let err = io::Error::from(io::ErrorKind::PermissionDenied);
+        assert_eq!(error_message(&err), "permission denied");
+
+        // Other errors keep the system's message, without the error number.
+        let err = io::Error::new(io::ErrorKind::Other, "Is a directory (os error 21)");
+        assert_eq!(error_message(&err), "is a directory");
+
+        let mut table = ChannelTable::new();
+        assert_eq!(
+            table.open("molt_nonesuch.txt", "r"),
+            molt_err!("couldn't open \"molt_nonesuch.txt\": no such file or directory")
+        );
+    }
+
+    #[test]
+    fn test_open_close() {
+        let path = std::env::temp_dir().join("molt_channel_test.txt");
+        let path = path.to_str().unwrap();
+        let mut table = ChannelTable::new();
+
+        // Write a file.
+        let id = table.open(path, "w").unwrap();
+        assert_eq!(id, "file1");
+        table.get(&id).unwrap().puts("line 1\nline 2\n").unwrap();
+        table.close(&id).unwrap();
+        assert!(table.get(&id).is_err());
+
+        // Append to it.
+        let id = table.open(path, "a").unwrap();
+        table.get(&id).unwrap().puts("line 3\n").unwrap();
+        table.close(&id).unwrap();
+
+        // Read it back.
+        let id = table.open(path, "r").unwrap();
+        assert_eq!(id, "file3");
+        let chan = table.get(&id).unwrap();
+        assert_eq!(chan.gets(), Ok(Some("line 1".into())));
+        assert_eq!(chan.read(None), Ok("line 2\nline 3\n".into()));
+        assert!(chan.eof());
+        table.close(&id).unwrap();
+
+        let _ = std::fs::remove_file(path);
+
+        assert!(table.open(path, "r").is_err());
+        assert_eq!(
+            table.open(path, "q"),
+            molt_err!("illegal access mode \"q\"")
+        );
+    }
+
+    #[test]
+    fn test_read_after_write() {
+        let path = std::env::temp_dir().join("molt_channel_test_rw.txt");
+        let path = path.to_str().unwrap();
+        let mut table = ChannelTable::new();
+
+        // Output is written before reading, so that the read starts after it.
+        let id = table.open(path, "w+").unwrap();
+        let chan = table.get(&id).unwrap();
+        chan.puts("line 1\n").unwrap();
+        assert_eq!(chan.read(None), Ok("".into()));
+        assert!(chan.eof());
+        chan.puts("line 2\n").unwrap();
+        table.close(&id).unwrap();
+        assert_eq!(std::fs::read_to_string(path).unwrap(), "line 1\nline 2\n");
+
+        // Input read ahead is given back before writing, so that the write starts where
+        // the reader left off.
+        let id = table.open(path, "r+").unwrap();
+        let chan = table.get(&id).unwrap();
+        assert_eq!(chan.gets(), Ok(Some("line 1".into())));
+        chan.puts("LINE 2\n").unwrap();
+        table.close(&id).unwrap();
+        assert_eq!(std::fs::read_to_string(path).unwrap(), "line 1\nLINE 2\n");
+
+        // Appended output can be read back after seeking.
+        let file = OpenOptions::new()
+            .read(true)
+            .append(true)
+            .open(path)
+            .unwrap();
+        let mut stream = FileStream::new(file, true, true);
+        stream.write_all(b"line 3\n").unwrap();
+        stream.seek(SeekFrom::Start(0)).unwrap();
+        let mut line = String::new();
+        stream.read_line(&mut line).unwrap();
+        assert_eq!(line, "line 1\n");
+        assert_eq!(stream.seek(SeekFrom::Current(0)).unwrap(), 7);
+        let mut rest = String::new();
+        stream.read_to_string(&mut rest).unwrap();
+        assert_eq!(rest, "LINE 2\nline 3\n");
+        drop(stream);
+
+        let _ = std::fs::remove_file(path);
+    }
+}
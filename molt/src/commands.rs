@@ -2,6 +2,8 @@
 //!
 //! This module defines the standard Molt commands.
 
+#[cfg(feature = "os")]
+use crate::channel::ChannelTable;
 use crate::dict::dict_new;
 use crate::dict::dict_path_insert;
 use crate::dict::dict_path_remove;
@@ -10,8 +12,26 @@ use crate::interp::Interp;
 use crate::types::*;
 use crate::util;
 use crate::*;
+#[cfg(feature = "os")]
 use std::fs;
-use std::time::Instant;
+#[cfg(feature = "os")]
+use std::path::Path;
+
+/// # alias *name* ?*targetCmd* ?*arg* ...??
+///
+/// Defines an alias: a command that calls *targetCmd* with the given *args*, followed by
+/// the alias's own arguments.  Given only the *name*, returns the alias's target command
+/// prefix.
+pub fn cmd_alias(interp: &mut Interp, _: ContextID, argv: &[Value]) -> MoltResult {
+    check_args(1, argv, 2, 0, "name ?targetCmd ?arg ...??")?;
+
+    if argv.len() == 2 {
+        return interp.alias_target(argv[1].as_str());
+    }
+
+    interp.add_alias(argv[1].as_str(), argv[2..].to_vec());
+    molt_ok!()
+}
 
 /// # append *varName* ?*value* ...?
 ///
@@ -41,33 +61,123 @@ pub fn cmd_array(interp: &mut Interp, context_id: ContextID, argv: &[Value]) ->
     interp.call_subcommand(context_id, argv, 1, &ARRAY_SUBCOMMANDS)
 }
 
-const ARRAY_SUBCOMMANDS: [Subcommand; 6] = [
+const ARRAY_SUBCOMMANDS: [Subcommand; 11] = [
+    Subcommand("anymore", cmd_array_anymore),
+    Subcommand("donesearch", cmd_array_donesearch),
     Subcommand("exists", cmd_array_exists),
     Subcommand("get", cmd_array_get),
     Subcommand("names", cmd_array_names),
+    Subcommand("nextelement", cmd_array_nextelement),
     Subcommand("set", cmd_array_set),
     Subcommand("size", cmd_array_size),
+    Subcommand("startsearch", cmd_array_startsearch),
+    Subcommand("statistics", cmd_array_statistics),
     Subcommand("unset", cmd_array_unset),
 ];
 
+/// # array anymore arrayName searchId
+///
+/// Returns 1 if the search has more elements to return, and 0 otherwise.
+pub fn cmd_array_anymore(interp: &mut Interp, _: ContextID, argv: &[Value]) -> MoltResult {
+    check_args(2, argv, 4, 4, "arrayName searchId")?;
+
+    let next = interp.array_search_next(argv[2].as_str(), argv[3].as_str(), false)?;
+    molt_ok!(next.is_some())
+}
+
+/// # array donesearch arrayName searchId
+///
+/// Ends the search, discarding its state.
+pub fn cmd_array_donesearch(interp: &mut Interp, _: ContextID, argv: &[Value]) -> MoltResult {
+    check_args(2, argv, 4, 4, "arrayName searchId")?;
+
+    interp.array_done_search(argv[2].as_str(), argv[3].as_str())?;
+    molt_ok!()
+}
+
+/// # array nextelement arrayName searchId
+///
+/// Returns the next index in the search, or the empty string if there are no more.
+pub fn cmd_array_nextelement(interp: &mut Interp, _: ContextID, argv: &[Value]) -> MoltResult {
+    check_args(2, argv, 4, 4, "arrayName searchId")?;
+
+    let next = interp.array_search_next(argv[2].as_str(), argv[3].as_str(), true)?;
+    molt_ok!(next.unwrap_or_else(Value::empty))
+}
+
+/// # array startsearch arrayName
+///
+/// Starts a search of the array's elements, returning a search ID for use with
+/// `array nextelement`, `array anymore`, and `array donesearch`.  Adding or removing an
+/// element ends the array's searches, and unsetting the array deletes them.
+pub fn cmd_array_startsearch(interp: &mut Interp, _: ContextID, argv: &[Value]) -> MoltResult {
+    check_args(2, argv, 3, 3, "arrayName")?;
+
+    let id = interp.array_start_search(argv[2].as_str())?;
+    molt_ok!(id)
+}
+
 /// # array exists arrayName
 pub fn cmd_array_exists(interp: &mut Interp, _: ContextID, argv: &[Value]) -> MoltResult {
     check_args(2, argv, 3, 3, "arrayName")?;
     molt_ok!(Value::from(interp.array_exists(argv[2].as_str())))
 }
 
-/// # array names arrayName
-/// TODO: Add glob matching as a feature, and support standard TCL options.
+/// # array names arrayName ?mode? ?pattern?
+///
+/// Returns the array's indices, optionally only those that match the pattern.  The mode
+/// is `-exact` or `-glob`, the default.
 pub fn cmd_array_names(interp: &mut Interp, _: ContextID, argv: &[Value]) -> MoltResult {
-    check_args(2, argv, 3, 3, "arrayName")?;
-    molt_ok!(Value::from(interp.array_names(argv[2].as_str())))
+    check_args(2, argv, 3, 5, "arrayName ?mode? ?pattern?")?;
+
+    let names = interp.array_names(argv[2].as_str());
+
+    let (exact, pattern) = match argv.len() {
+        3 => return molt_ok!(Value::from(names)),
+        4 => (false, argv[3].as_str()),
+        _ => match argv[3].as_str() {
+            "-exact" => (true, argv[4].as_str()),
+            "-glob" => (false, argv[4].as_str()),
+            mode => return molt_err!("bad option \"{}\": must be -exact or -glob", mode),
+        },
+    };
+
+    let names: MoltList = names
+        .into_iter()
+        .filter(|name| {
+            if exact {
+                name.as_str() == pattern
+            } else {
+                util::glob_match(pattern, name.as_str())
+            }
+        })
+        .collect();
+
+    molt_ok!(Value::from(names))
 }
 
-/// # array get arrayname
-/// TODO: Add glob matching as a feature, and support standard TCL options.
+/// # array get arrayName ?pattern?
+///
+/// Returns a flat list of the array's indices and values, optionally only those whose
+/// indices match the glob pattern.
 pub fn cmd_array_get(interp: &mut Interp, _: ContextID, argv: &[Value]) -> MoltResult {
-    check_args(2, argv, 3, 3, "arrayName")?;
-    molt_ok!(Value::from(interp.array_get(argv[2].as_str())))
+    check_args(2, argv, 3, 4, "arrayName ?pattern?")?;
+
+    let kvlist = interp.array_get(argv[2].as_str());
+
+    if argv.len() == 3 {
+        return molt_ok!(Value::from(kvlist));
+    }
+
+    let pattern = argv[3].as_str();
+    let kvlist: MoltList = kvlist
+        .chunks(2)
+        .filter(|pair| util::glob_match(pattern, pair[0].as_str()))
+        .flatten()
+        .cloned()
+        .collect();
+
+    molt_ok!(Value::from(kvlist))
 }
 
 /// # array set arrayName list
@@ -99,6 +209,50 @@ pub fn cmd_array_size(interp: &mut Interp, _: ContextID, argv: &[Value]) -> Molt
     molt_ok!(Value::from(interp.array_size(argv[2].as_str()) as MoltInt))
 }
 
+/// # array statistics arrayName
+///
+/// Returns a human-readable description of the array's contents: the number of elements,
+/// and the distribution of the lengths of its indices and values.
+pub fn cmd_array_statistics(interp: &mut Interp, _: ContextID, argv: &[Value]) -> MoltResult {
+    check_args(2, argv, 3, 3, "arrayName")?;
+
+    let name = argv[2].as_str();
+    if !interp.array_exists(name) {
+        return molt_err!("\"{}\" isn't an array", name);
+    }
+
+    let kvlist = interp.array_get(name);
+    let count = kvlist.len() / 2;
+
+    let mut stats = format!("{} entries in table", count);
+
+    if count > 0 {
+        let keys: Vec<usize> = kvlist.iter().step_by(2).map(char_count).collect();
+        let values: Vec<usize> = kvlist.iter().skip(1).step_by(2).map(char_count).collect();
+        stats.push_str(&length_distribution("index", &keys));
+        stats.push_str(&length_distribution("value", &values));
+    }
+
+    molt_ok!(stats)
+}
+
+/// Returns the length of the value's string rep in characters.
+fn char_count(value: &Value) -> usize {
+    value.as_str().chars().count()
+}
+
+/// Formats a line of `array statistics` output describing a non-empty list of lengths.
+fn length_distribution(label: &str, lengths: &[usize]) -> String {
+    let min = lengths.iter().min().copied().unwrap_or(0);
+    let max = lengths.iter().max().copied().unwrap_or(0);
+    let mean = lengths.iter().sum::<usize>() as f64 / lengths.len() as f64;
+
+    format!(
+        "\n{} length: min {}, max {}, average {:.1}",
+        label, min, max, mean
+    )
+}
+
 /// # array unset arrayName ?*index*?
 pub fn cmd_array_unset(interp: &mut Interp, _: ContextID, argv: &[Value]) -> MoltResult {
     check_args(2, argv, 3, 4, "arrayName ?index?")?;
@@ -143,7 +297,7 @@ pub fn cmd_break(_interp: &mut Interp, _: ContextID, argv: &[Value]) -> MoltResu
 ///
 /// Executes a script, returning the result code.  If the resultVarName is given, the result
 /// of executing the script is returned in it.  The result code is returned as an integer,
-/// 0=Ok, 1=Error, 2=Return, 3=Break, 4=Continue.
+/// 0=Ok, 1=Error, 2=Return, 3=Break, 4=Continue, or an application-defined code.
 pub fn cmd_catch(interp: &mut Interp, _: ContextID, argv: &[Value]) -> MoltResult {
     check_args(1, argv, 2, 4, "script ?resultVarName? ?optionsVarName?")?;
 
@@ -158,7 +312,7 @@ pub fn cmd_catch(interp: &mut Interp, _: ContextID, argv: &[Value]) -> MoltResul
             ResultCode::Return => (2, exception.value()),
             ResultCode::Break => (3, exception.value()),
             ResultCode::Continue => (4, exception.value()),
-            ResultCode::Other(_) => unimplemented!(), // TODO: Not in use yet
+            ResultCode::Other(code) => (code, exception.value()),
         },
     };
 
@@ -173,6 +327,21 @@ pub fn cmd_catch(interp: &mut Interp, _: ContextID, argv: &[Value]) -> MoltResul
     Ok(Value::from(code))
 }
 
+/// # close *channelId*
+///
+/// Closes the channel, flushing any pending output.
+///
+/// Requires the `os` feature.
+#[cfg(feature = "os")]
+pub fn cmd_close(interp: &mut Interp, context_id: ContextID, argv: &[Value]) -> MoltResult {
+    check_args(1, argv, 2, 2, "channelId")?;
+
+    interp
+        .context::<ChannelTable>(context_id)
+        .close(argv[1].as_str())?;
+    molt_ok!()
+}
+
 /// # continue
 ///
 /// Continues with the next iteration of the inmost loop.
@@ -182,14 +351,27 @@ pub fn cmd_continue(_interp: &mut Interp, _: ContextID, argv: &[Value]) -> MoltR
     Err(Exception::molt_continue())
 }
 
+/// # coroutine *name* *command* ?*arg* ...?
+///
+/// Creates a coroutine called *name* that calls the procedure, and runs it until it
+/// yields: returns the yielded value, or the procedure's result if it finishes without
+/// yielding.  Calling *name* resumes the coroutine.
+#[cfg(feature = "coroutine")]
+pub fn cmd_coroutine(interp: &mut Interp, _: ContextID, argv: &[Value]) -> MoltResult {
+    check_args(1, argv, 3, 0, "name command ?arg ...?")?;
+
+    interp.add_coroutine(argv[1].as_str(), &argv[2..])
+}
+
 /// # dict *subcommand* ?*arg*...?
 pub fn cmd_dict(interp: &mut Interp, context_id: ContextID, argv: &[Value]) -> MoltResult {
     interp.call_subcommand(context_id, argv, 1, &DICT_SUBCOMMANDS)
 }
 
-const DICT_SUBCOMMANDS: [Subcommand; 9] = [
+const DICT_SUBCOMMANDS: [Subcommand; 10] = [
     Subcommand("create", cmd_dict_new),
     Subcommand("exists", cmd_dict_exists),
+    Subcommand("for", cmd_dict_for),
     Subcommand("get", cmd_dict_get),
     Subcommand("keys", cmd_dict_keys),
     Subcommand("remove", cmd_dict_remove),
@@ -203,11 +385,7 @@ const DICT_SUBCOMMANDS: [Subcommand; 9] = [
 fn cmd_dict_new(_: &mut Interp, _: ContextID, argv: &[Value]) -> MoltResult {
     // FIRST, we need an even number of arguments.
     if argv.len() % 2 != 0 {
-        return molt_err!(
-            "wrong # args: should be \"{} {}\"",
-            Value::from(&argv[0..2]).to_string(),
-            "?key value?"
-        );
+        return wrong_num_args(2, argv, "?key value ...?");
     }
 
     // NEXT, return the value.
@@ -240,6 +418,41 @@ fn cmd_dict_exists(_: &mut Interp, _: ContextID, argv: &[Value]) -> MoltResult {
     molt_ok!(true)
 }
 
+/// # dict for {*keyVar* *valueVar*} *dictionary* *body*
+///
+/// Loops over the entries in the dictionary, in order, assigning each key and value to
+/// the variables and executing the body.  Handles `break` and `continue` as for
+/// `foreach`.
+fn cmd_dict_for(interp: &mut Interp, _: ContextID, argv: &[Value]) -> MoltResult {
+    check_args(2, argv, 5, 5, "{keyVarName valueVarName} dictionary script")?;
+
+    let var_list = argv[2].as_list()?;
+
+    if var_list.len() != 2 {
+        return molt_err!("must have exactly two variable names");
+    }
+
+    let dict = argv[3].as_dict()?;
+    let body = &argv[4];
+
+    for (key, value) in dict.iter() {
+        interp.set_var(&var_list[0], key.clone())?;
+        interp.set_var(&var_list[1], value.clone())?;
+
+        let result = interp.eval_value(body);
+
+        if let Err(exception) = result {
+            match exception.code() {
+                ResultCode::Break => break,
+                ResultCode::Continue => (),
+                _ => return Err(exception),
+            }
+        }
+    }
+
+    molt_ok!()
+}
+
 /// # dict get *dictionary* ?*key* ...?
 fn cmd_dict_get(_: &mut Interp, _: ContextID, argv: &[Value]) -> MoltResult {
     check_args(2, argv, 3, 0, "dictionary ?key ...?")?;
@@ -334,17 +547,174 @@ fn cmd_dict_values(_: &mut Interp, _: ContextID, argv: &[Value]) -> MoltResult {
     molt_ok!(values)
 }
 
-/// error *message*
+/// # encoding *subcommand* ?*arg*...?
 ///
-/// Returns an error with the given message.
+/// Converts strings to and from byte strings in a given character encoding.
+pub fn cmd_encoding(interp: &mut Interp, context_id: ContextID, argv: &[Value]) -> MoltResult {
+    interp.call_subcommand(context_id, argv, 1, &ENCODING_SUBCOMMANDS)
+}
+
+const ENCODING_SUBCOMMANDS: [Subcommand; 3] = [
+    Subcommand("convertfrom", cmd_encoding_convertfrom),
+    Subcommand("convertto", cmd_encoding_convertto),
+    Subcommand("names", cmd_encoding_names),
+];
+
+/// The supported encodings; the first is the default.
+const ENCODINGS: [&str; 3] = ["utf-8", "ascii", "iso8859-1"];
+
+/// Gets the encoding name from the optional argument, if present, or returns the default.
+fn encoding_name(argv: &[Value]) -> Result<&str, Exception> {
+    if argv.len() == 4 {
+        let name = argv[2].as_str();
+        if ENCODINGS.contains(&name) {
+            Ok(name)
+        } else {
+            molt_err!("unknown encoding \"{}\"", name)
+        }
+    } else {
+        Ok(ENCODINGS[0])
+    }
+}
+
+/// # encoding convertfrom ?*encoding*? *data*
 ///
-/// ## TCL Liens
+/// Converts the byte string *data* from the encoding to a string.  Invalid UTF-8 bytes
+/// are converted as for `iso8859-1`, and non-ASCII bytes to `?`.
+pub fn cmd_encoding_convertfrom(_interp: &mut Interp, _: ContextID, argv: &[Value]) -> MoltResult {
+    check_args(2, argv, 3, 4, "?encoding? data")?;
+
+    let encoding = encoding_name(argv)?;
+    let bytes = argv[argv.len() - 1].as_bytes();
+
+    let string: String = match encoding {
+        "ascii" => bytes
+            .iter()
+            .map(|&byte| {
+                if byte.is_ascii() {
+                    char::from(byte)
+                } else {
+                    '?'
+                }
+            })
+            .collect(),
+        "iso8859-1" => bytes.iter().map(|&byte| char::from(byte)).collect(),
+        _ => {
+            let mut string = String::new();
+            let mut rest = &bytes[..];
+
+            loop {
+                match std::str::from_utf8(rest) {
+                    Ok(valid) => {
+                        string.push_str(valid);
+                        break;
+                    }
+                    Err(e) => {
+                        let (valid, invalid) = rest.split_at(e.valid_up_to());
+                        string.push_str(std::str::from_utf8(valid).expect("valid UTF-8"));
+                        string.push(char::from(invalid[0]));
+                        rest = &invalid[1..];
+                    }
+                }
+            }
+            string
+        }
+    };
+
+    molt_ok!(string)
+}
+
+/// # encoding convertto ?*encoding*? *string*
 ///
-/// * In Standard TCL, `error` can optionally set the stack trace and an error code.
+/// Converts the string to a byte string in the encoding.  Characters that can't be
+/// represented in the encoding are converted to `?`.
+pub fn cmd_encoding_convertto(_interp: &mut Interp, _: ContextID, argv: &[Value]) -> MoltResult {
+    check_args(2, argv, 3, 4, "?encoding? string")?;
+
+    let encoding = encoding_name(argv)?;
+    let string = argv[argv.len() - 1].as_str();
+
+    let bytes: Vec<u8> = match encoding {
+        "ascii" => string
+            .chars()
+            .map(|ch| if ch.is_ascii() { ch as u8 } else { b'?' })
+            .collect(),
+        "iso8859-1" => string
+            .chars()
+            .map(|ch| if (ch as u32) < 0x100 { ch as u8 } else { b'?' })
+            .collect(),
+        _ => string.as_bytes().to_vec(),
+    };
+
+    molt_ok!(Value::from_bytes(bytes))
+}
+
+/// # encoding names
+///
+/// Returns a list of the supported encodings.
+pub fn cmd_encoding_names(_interp: &mut Interp, _: ContextID, argv: &[Value]) -> MoltResult {
+    check_args(2, argv, 2, 2, "")?;
+
+    let names: MoltList = ENCODINGS.iter().map(|&name| Value::from(name)).collect();
+    molt_ok!(names)
+}
+
+/// # eof *channelId*
+///
+/// Returns 1 if the last read from the channel reached the end of its input, and 0
+/// otherwise.
+///
+/// Requires the `os` feature.
+#[cfg(feature = "os")]
+pub fn cmd_eof(interp: &mut Interp, context_id: ContextID, argv: &[Value]) -> MoltResult {
+    check_args(1, argv, 2, 2, "channelId")?;
+
+    let eof = interp
+        .context::<ChannelTable>(context_id)
+        .get(argv[1].as_str())?
+        .eof();
+    molt_ok!(eof)
+}
+
+/// # error *message* ?*info*? ?*code*?
+///
+/// Returns an error with the given message.  If given and non-empty, the *info* is the
+/// initial stack trace, i.e., the `-errorinfo`; and the *code* is the `-errorcode`, which
+/// defaults to `NONE`.
 pub fn cmd_error(_interp: &mut Interp, _: ContextID, argv: &[Value]) -> MoltResult {
-    check_args(1, argv, 2, 2, "message")?;
+    check_args(1, argv, 2, 4, "message ?errorInfo? ?errorCode?")?;
 
-    molt_err!(argv[1].clone())
+    let error_info = argv.get(2).cloned().unwrap_or_else(Value::empty);
+    let error_code = argv.get(3).cloned().unwrap_or_else(|| Value::from("NONE"));
+
+    Err(Exception::molt_err3(
+        error_code,
+        error_info,
+        argv[1].clone(),
+    ))
+}
+
+/// # eval *arg* ?*arg* ...?
+///
+/// Concatenates the arguments, as for `concat`, and evaluates the result as a script.
+/// A single argument is evaluated as is; if it's a pure list, e.g., one built by `list`,
+/// it's evaluated as a single command without being parsed.
+pub fn cmd_eval(interp: &mut Interp, _: ContextID, argv: &[Value]) -> MoltResult {
+    check_args(1, argv, 2, 0, "arg ?arg ...?")?;
+
+    if argv.len() == 2 {
+        return interp.eval_value(&argv[1]);
+    }
+
+    // NEXT, concatenate the arguments, trimming leading and trailing whitespace and
+    // omitting any that are empty.
+    let args: Vec<&str> = argv[1..]
+        .iter()
+        .map(|arg| arg.as_str().trim())
+        .filter(|arg| !arg.is_empty())
+        .collect();
+
+    interp.eval(&args.join(" "))
 }
 
 /// # exit ?*returnCode*?
@@ -352,6 +722,7 @@ pub fn cmd_error(_interp: &mut Interp, _: ContextID, argv: &[Value]) -> MoltResu
 /// Terminates the application by calling `std::process::exit()`.
 /// If given, _returnCode_ must be an integer return code; if absent, it
 /// defaults to 0.
+#[cfg(feature = "os")]
 pub fn cmd_exit(_interp: &mut Interp, _: ContextID, argv: &[Value]) -> MoltResult {
     check_args(1, argv, 1, 2, "?returnCode?")?;
 
@@ -364,6 +735,29 @@ pub fn cmd_exit(_interp: &mut Interp, _: ContextID, argv: &[Value]) -> MoltResul
     std::process::exit(return_code as i32)
 }
 
+/// # exit ?*returnCode*?
+///
+/// Without the `os` feature, there's no process to terminate; instead, `exit` throws an
+/// error with the message "exit *returnCode*" and the error code `EXIT` *returnCode*,
+/// leaving it to the application to decide what exiting means.  The error can be caught
+/// like any other.
+#[cfg(not(feature = "os"))]
+pub fn cmd_exit(_interp: &mut Interp, _: ContextID, argv: &[Value]) -> MoltResult {
+    check_args(1, argv, 1, 2, "?returnCode?")?;
+
+    let return_code: MoltInt = if argv.len() == 1 {
+        0
+    } else {
+        argv[1].as_int()?
+    };
+
+    let error_code = Value::from(vec![Value::from("EXIT"), Value::from(return_code)]);
+    Err(Exception::molt_err2(
+        error_code,
+        Value::from(format!("exit {}", return_code)),
+    ))
+}
+
 /// # expr expr
 ///
 /// Evaluates an expression and returns its result.
@@ -421,32 +815,46 @@ pub fn cmd_for(interp: &mut Interp, _: ContextID, argv: &[Value]) -> MoltResult
     molt_ok!()
 }
 
-/// # foreach *varList* *list* *body*
+/// # foreach *varList* *list* ?*varList* *list* ...? *body*
 ///
-/// Loops over the items the list, assigning successive items to the variables in the
-/// *varList* and calling the *body* as a script once for each set of assignments.
-/// On the last iteration, the second and subsequents variables in the *varList* will
-/// be assigned the empty string if there are not enough list elements to fill them.
-///
-/// ## TCL Liens
-///
-/// * In Standard TCL, `foreach` can loop over several lists at the same time.
+/// Loops over the items in the lists, assigning successive items of each *list* to the
+/// variables in its *varList* and calling the *body* as a script once for each set of
+/// assignments.  The loop continues until all of the lists are exhausted; on the last
+/// iterations, variables will be assigned the empty string if there are not enough list
+/// elements to fill them.
 pub fn cmd_foreach(interp: &mut Interp, _: ContextID, argv: &[Value]) -> MoltResult {
-    check_args(1, argv, 4, 4, "varList list body")?;
+    check_args(1, argv, 4, 0, "varList list ?varList list ...? body")?;
+
+    if argv.len() % 2 == 1 {
+        return wrong_num_args(1, argv, "varList list ?varList list ...? body");
+    }
 
-    let var_list = &*argv[1].as_list()?;
-    let list = &*argv[2].as_list()?;
-    let body = &argv[3];
+    // FIRST, get the varList/list pairs, and determine the number of iterations.
+    let mut pairs = Vec::new();
+    let mut iterations = 0;
 
-    let mut i = 0;
+    for pair in argv[1..argv.len() - 1].chunks(2) {
+        let var_list = pair[0].as_list()?;
+        let list = pair[1].as_list()?;
 
-    while i < list.len() {
-        for var in var_list {
-            if i < list.len() {
-                interp.set_var(&var, list[i].clone())?;
-                i += 1;
-            } else {
-                interp.set_var(&var, Value::empty())?;
+        if var_list.is_empty() {
+            return molt_err!("foreach varlist is empty");
+        }
+
+        let count = list.len().div_ceil(var_list.len());
+        iterations = iterations.max(count);
+        pairs.push((var_list, list));
+    }
+
+    let body = &argv[argv.len() - 1];
+
+    // NEXT, loop.
+    for iteration in 0..iterations {
+        for (var_list, list) in &pairs {
+            for (j, var) in var_list.iter().enumerate() {
+                let i = iteration * var_list.len() + j;
+                let value = list.get(i).cloned().unwrap_or_else(Value::empty);
+                interp.set_var(var, value)?;
             }
         }
 
@@ -464,6 +872,539 @@ pub fn cmd_foreach(interp: &mut Interp, _: ContextID, argv: &[Value]) -> MoltRes
     molt_ok!()
 }
 
+/// # format *formatString* ?*arg* ...?
+///
+/// Formats the arguments according to the *formatString*, in the manner of C's `sprintf`.
+/// Supports the `%d`, `%i`, `%u`, `%o`, `%x`, `%X`, `%b`, `%c`, `%s`, `%f`, `%e`, `%E`,
+/// `%g`, `%G`, and `%%` conversions; the `-`, `+`, space, `0`, and `#` flags; and
+/// field widths and precisions, either literal or taken from the argument list via `*`.
+///
+/// ## TCL Liens
+///
+/// * Does not support XPG3 positional specifiers, e.g., `%2$s`.
+/// * The size modifiers `h`, `l`, and `ll` are accepted but ignored; all integers are
+///   formatted as 64-bit values.
+pub fn cmd_format(_interp: &mut Interp, _: ContextID, argv: &[Value]) -> MoltResult {
+    check_args(1, argv, 2, 0, "formatString ?arg arg ...?")?;
+
+    let mut chars = argv[1].as_str().chars().peekable();
+    let mut args = argv[2..].iter();
+    let mut result = String::new();
+
+    while let Some(ch) = chars.next() {
+        if ch != '%' {
+            result.push(ch);
+            continue;
+        }
+
+        if chars.peek() == Some(&'%') {
+            chars.next();
+            result.push('%');
+            continue;
+        }
+
+        // FIRST, get the flags.
+        let mut spec = FormatSpec::default();
+
+        while let Some(&flag) = chars.peek() {
+            match flag {
+                '-' => spec.left = true,
+                '+' => spec.plus = true,
+                ' ' => spec.space = true,
+                '0' => spec.zero = true,
+                '#' => spec.alt = true,
+                _ => break,
+            }
+            chars.next();
+        }
+
+        // NEXT, get the field width.  A negative width taken from the argument list
+        // means left-alignment, as in C.
+        if chars.peek() == Some(&'*') {
+            chars.next();
+            let width = next_format_arg(&mut args)?.as_int()?;
+            if width < 0 {
+                spec.left = true;
+            }
+            spec.width = check_format_field(width.unsigned_abs(), "field width")?;
+        } else {
+            spec.width = read_format_number(&mut chars, "field width")?;
+        }
+
+        // NEXT, get the precision.  A negative precision taken from the argument list
+        // is treated as though no precision were given.
+        if chars.peek() == Some(&'.') {
+            chars.next();
+
+            if chars.peek() == Some(&'*') {
+                chars.next();
+                let precision = next_format_arg(&mut args)?.as_int()?;
+                if precision >= 0 {
+                    spec.precision = Some(check_format_field(precision as u64, "precision")?);
+                }
+            } else {
+                spec.precision = Some(read_format_number(&mut chars, "precision")?);
+            }
+        }
+
+        // NEXT, skip any size modifiers.
+        while let Some('h') | Some('l') | Some('L') = chars.peek() {
+            chars.next();
+        }
+
+        // NEXT, do the conversion.
+        let conv = match chars.next() {
+            Some(conv) => conv,
+            None => return molt_err!("format string ended in middle of field specifier"),
+        };
+
+        match conv {
+            'd' | 'i' => {
+                let int = next_format_arg(&mut args)?.as_int()?;
+                let sign = if int < 0 { "-" } else { spec.sign() };
+                let digits = spec.int_digits(int.unsigned_abs(), 10);
+                spec.pad(&mut result, sign, &digits, spec.precision.is_none());
+            }
+            'u' | 'o' | 'x' | 'X' | 'b' => {
+                let int = next_format_arg(&mut args)?.as_int()? as u64;
+                let (radix, prefix) = match conv {
+                    'o' => (8, "0"),
+                    'x' => (16, "0x"),
+                    'X' => (16, "0X"),
+                    'b' => (2, "0b"),
+                    _ => (10, ""),
+                };
+                let mut digits = spec.int_digits(int, radix);
+                if conv == 'X' {
+                    digits = digits.to_uppercase();
+                }
+
+                // The alternate form of %o only needs a 0 if there isn't one already.
+                let prefix = if !spec.alt || int == 0 || (conv == 'o' && digits.starts_with('0')) {
+                    ""
+                } else {
+                    prefix
+                };
+                spec.pad(&mut result, prefix, &digits, spec.precision.is_none());
+            }
+            'c' => {
+                let int = next_format_arg(&mut args)?.as_int()?;
+                // FIRST, range-check the code, so that it isn't truncated to 32 bits.
+                let ch = if (0..=0x10FFFF).contains(&int) {
+                    std::char::from_u32(int as u32)
+                } else {
+                    None
+                };
+                let ch = match ch {
+                    Some(ch) => ch,
+                    None => return molt_err!("invalid character code \"{}\"", int),
+                };
+                spec.pad(&mut result, "", &ch.to_string(), false);
+            }
+            's' => {
+                let arg = next_format_arg(&mut args)?;
+                let string = arg.as_str();
+                match spec.precision {
+                    Some(precision) => {
+                        let truncated: String = string.chars().take(precision).collect();
+                        spec.pad(&mut result, "", &truncated, false);
+                    }
+                    None => spec.pad(&mut result, "", string, false),
+                }
+            }
+            'f' | 'e' | 'E' | 'g' | 'G' => {
+                let flt = next_format_arg(&mut args)?.as_float()?;
+                let sign = if flt.is_sign_negative() && !flt.is_nan() {
+                    "-"
+                } else {
+                    spec.sign()
+                };
+
+                if flt.is_nan() {
+                    spec.pad(&mut result, sign, "NaN", false);
+                } else if flt.is_infinite() {
+                    spec.pad(&mut result, sign, "Inf", false);
+                } else {
+                    let digits = spec.float_digits(flt.abs(), conv);
+                    spec.pad(&mut result, sign, &digits, true);
+                }
+            }
+            _ => return molt_err!("bad field specifier \"{}\"", conv),
+        }
+    }
+
+    molt_ok!(result)
+}
+
+/// A single `format` field specifier: flags, width, and precision.
+#[derive(Default)]
+struct FormatSpec {
+    left: bool,
+    plus: bool,
+    space: bool,
+    zero: bool,
+    alt: bool,
+    width: usize,
+    precision: Option<usize>,
+}
+
+impl FormatSpec {
+    /// The sign to use for a non-negative number.
+    fn sign(&self) -> &'static str {
+        if self.plus {
+            "+"
+        } else if self.space {
+            " "
+        } else {
+            ""
+        }
+    }
+
+    /// Converts an unsigned integer to digits in the given radix, padding with zeroes
+    /// to the precision, if any.
+    fn int_digits(&self, mut int: u64, radix: u64) -> String {
+        let mut digits = Vec::new();
+
+        while int > 0 {
+            let digit = (int % radix) as u32;
+            digits.push(std::char::from_digit(digit, radix as u32).unwrap());
+            int /= radix;
+        }
+
+        // A precision of 0 means that the value 0 produces no digits at all, as in C.
+        let min_digits = self.precision.unwrap_or(1);
+        while digits.len() < min_digits {
+            digits.push('0');
+        }
+
+        digits.iter().rev().collect()
+    }
+
+    /// Converts a finite, non-negative float to digits according to the conversion
+    /// character.
+    fn float_digits(&self, flt: MoltFloat, conv: char) -> String {
+        let precision = self.precision.unwrap_or(6);
+
+        match conv {
+            'f' => {
+                let digits = format!("{:.*}", precision, flt);
+                if self.alt && precision == 0 {
+                    digits + "."
+                } else {
+                    digits
+                }
+            }
+            'e' | 'E' => {
+                let digits = format_exponential(flt, precision, self.alt);
+                if conv == 'E' {
+                    digits.to_uppercase()
+                } else {
+                    digits
+                }
+            }
+            _ => {
+                // %g: use %e if the exponent is less than -4 or not less than the
+                // precision; otherwise use %f.  Trailing zeroes are removed unless
+                // the "#" flag is given.
+                let precision = if precision == 0 { 1 } else { precision };
+                let exp = format!("{:.*e}", precision - 1, flt);
+                let exp: i64 = exp[exp.find('e').unwrap() + 1..].parse().unwrap();
+
+                let mut digits = if exp < -4 || exp >= precision as i64 {
+                    format_exponential(flt, precision - 1, self.alt)
+                } else {
+                    format!("{:.*}", (precision as i64 - 1 - exp) as usize, flt)
+                };
+
+                if !self.alt {
+                    digits = strip_float_zeroes(&digits);
+                } else if !digits.contains('.') {
+                    digits.push('.');
+                }
+
+                if conv == 'G' {
+                    digits.to_uppercase()
+                } else {
+                    digits
+                }
+            }
+        }
+    }
+
+    /// Pads the prefix and digits out to the field width, and appends them to the result.
+    /// If `zero_ok`, the "0" flag pads with zeroes between the prefix and the digits.
+    fn pad(&self, result: &mut String, prefix: &str, digits: &str, zero_ok: bool) {
+        let len = prefix.chars().count() + digits.chars().count();
+        let fill = self.width.saturating_sub(len);
+
+        if self.left {
+            result.push_str(prefix);
+            result.push_str(digits);
+            result.push_str(&" ".repeat(fill));
+        } else if self.zero && zero_ok {
+            result.push_str(prefix);
+            result.push_str(&"0".repeat(fill));
+            result.push_str(digits);
+        } else {
+            result.push_str(&" ".repeat(fill));
+            result.push_str(prefix);
+            result.push_str(digits);
+        }
+    }
+}
+
+/// Gets the next argument for a `format` field specifier.
+fn next_format_arg<'a>(args: &mut std::slice::Iter<'a, Value>) -> Result<&'a Value, Exception> {
+    match args.next() {
+        Some(arg) => Ok(arg),
+        None => molt_err!("not enough arguments for all format specifiers"),
+    }
+}
+
+/// The largest field width or precision `format` accepts, so that a field specifier can't
+/// make it allocate an unreasonable amount of memory.
+const MAX_FORMAT_FIELD: usize = 1_000_000;
+
+/// Reads a decimal number from a `format` field specifier, returning 0 if there is none.
+/// The `what` names the number in the error message if it's too large.
+fn read_format_number(
+    chars: &mut std::iter::Peekable<std::str::Chars>,
+    what: &str,
+) -> Result<usize, Exception> {
+    let mut number: u64 = 0;
+
+    while let Some(digit) = chars.peek().and_then(|ch| ch.to_digit(10)) {
+        let next = number
+            .checked_mul(10)
+            .and_then(|n| n.checked_add(digit.into()));
+        number = match next {
+            Some(number) => number,
+            None => return molt_err!("{} too large", what),
+        };
+        chars.next();
+    }
+
+    check_format_field(number, what)
+}
+
+/// Checks that a `format` field width or precision is no larger than `MAX_FORMAT_FIELD`.
+fn check_format_field(number: u64, what: &str) -> Result<usize, Exception> {
+    if number > MAX_FORMAT_FIELD as u64 {
+        molt_err!("{} too large", what)
+    } else {
+        Ok(number as usize)
+    }
+}
+
+/// Formats a float in C's exponential notation, e.g., "1.500000e+02".
+fn format_exponential(flt: MoltFloat, precision: usize, alt: bool) -> String {
+    let digits = format!("{:.*e}", precision, flt);
+    let epos = digits.find('e').unwrap();
+    let exp: i64 = digits[epos + 1..].parse().unwrap();
+
+    let mut mantissa = digits[..epos].to_string();
+    if alt && precision == 0 {
+        mantissa.push('.');
+    }
+
+    let sign = if exp < 0 { '-' } else { '+' };
+    format!("{}e{}{:02}", mantissa, sign, exp.abs())
+}
+
+/// Strips trailing zeroes from the fractional part of a %g-formatted float, along
+/// with the decimal point if nothing is left after it.
+fn strip_float_zeroes(digits: &str) -> String {
+    let (mantissa, exponent) = match digits.find('e') {
+        Some(epos) => digits.split_at(epos),
+        None => (digits, ""),
+    };
+
+    let mantissa = if mantissa.contains('.') {
+        mantissa.trim_end_matches('0').trim_end_matches('.')
+    } else {
+        mantissa
+    };
+
+    format!("{}{}", mantissa, exponent)
+}
+
+/// # gets *channelId* ?*varName*?
+///
+/// Reads the next line from the channel, stripping the line ending.  If *varName* is
+/// given, the line is assigned to the variable and the command returns the number of
+/// characters read, or -1 at the end of the input; otherwise, the command returns
+/// the line.
+///
+/// Requires the `os` feature.
+#[cfg(feature = "os")]
+pub fn cmd_gets(interp: &mut Interp, context_id: ContextID, argv: &[Value]) -> MoltResult {
+    check_args(1, argv, 2, 3, "channelId ?varName?")?;
+
+    let line = interp
+        .context::<ChannelTable>(context_id)
+        .get(argv[1].as_str())?
+        .gets()?;
+
+    if argv.len() == 3 {
+        match line {
+            Some(line) => {
+                let count = line.chars().count() as MoltInt;
+                interp.set_var(&argv[2], line.into())?;
+                molt_ok!(count)
+            }
+            None => {
+                interp.set_var(&argv[2], Value::empty())?;
+                molt_ok!(-1)
+            }
+        }
+    } else {
+        molt_ok!(line.unwrap_or_default())
+    }
+}
+
+/// # glob ?-nocomplain? ?-directory *directory*? ?--? *pattern* ?*pattern* ...?
+///
+/// Returns a list of the filesystem paths that match any of the glob-style patterns,
+/// sorted by name within each pattern.  It's an error if no paths match, unless
+/// `-nocomplain` is given.
+///
+/// Requires the `os` feature.
+///
+/// ## TCL Liens
+///
+/// * Supports only the `-nocomplain` and `-directory` switches.
+/// * Brace alternatives, e.g., `{a,b}`, and `~` are not supported.
+#[cfg(feature = "os")]
+pub fn cmd_glob(_interp: &mut Interp, _: ContextID, argv: &[Value]) -> MoltResult {
+    check_args(1, argv, 2, 0, "?switches? name ?name ...?")?;
+
+    // FIRST, get the switches.
+    let mut nocomplain = false;
+    let mut directory = None;
+    let mut i = 1;
+
+    while i < argv.len() && argv[i].as_str().starts_with('-') {
+        let opt = argv[i].as_str();
+        i += 1;
+
+        match opt {
+            "-nocomplain" => nocomplain = true,
+            "-directory" => {
+                if i == argv.len() {
+                    return molt_err!("missing argument to \"-directory\"");
+                }
+                directory = Some(argv[i].as_str());
+                i += 1;
+            }
+            "--" => break,
+            _ => {
+                return molt_err!(
+                    "bad option \"{}\": must be -directory, -nocomplain, or --",
+                    opt
+                )
+            }
+        }
+    }
+
+    if i == argv.len() {
+        return wrong_num_args(1, argv, "?switches? name ?name ...?");
+    }
+
+    // NEXT, match each of the patterns.
+    let patterns = &argv[i..];
+    let mut result = Vec::new();
+
+    for pattern in patterns {
+        let pattern = pattern.as_str();
+        let mut paths = Vec::new();
+
+        let (base, pattern) = match (directory, pattern.strip_prefix('/')) {
+            (_, Some(rest)) => ("/".to_string(), rest),
+            (Some(dir), None) => (dir.to_string(), pattern),
+            (None, None) => (String::new(), pattern),
+        };
+        let components: Vec<&str> = pattern.split('/').filter(|c| !c.is_empty()).collect();
+
+        glob_paths(&base, &components, &mut paths);
+        paths.sort();
+        result.extend(paths.into_iter().map(Value::from));
+    }
+
+    if result.is_empty() && !nocomplain {
+        if patterns.len() == 1 {
+            return molt_err!("no files matched glob pattern \"{}\"", patterns[0]);
+        } else {
+            return molt_err!(
+                "no files matched glob patterns \"{}\"",
+                Value::from(patterns)
+            );
+        }
+    }
+
+    molt_ok!(result)
+}
+
+/// Adds to the list the paths below the base path that match the remaining path
+/// components, walking the directory tree as needed.  An empty base is the current
+/// directory.
+#[cfg(feature = "os")]
+fn glob_paths(base: &str, components: &[&str], paths: &mut Vec<String>) {
+    let (component, rest) = match components.split_first() {
+        Some(pair) => pair,
+        None => {
+            if !base.is_empty() {
+                paths.push(base.to_string());
+            }
+            return;
+        }
+    };
+
+    let matches: Vec<String> = if component.contains(|c| "*?[\\".contains(c)) {
+        // A pattern: match it against the entries in the base directory.  As in Standard
+        // TCL, hidden files only match patterns that begin with ".".
+        let dir = if base.is_empty() { "." } else { base };
+        let entries = match fs::read_dir(dir) {
+            Ok(entries) => entries,
+            Err(_) => return,
+        };
+
+        entries
+            .filter_map(|entry| entry.ok())
+            .filter_map(|entry| entry.file_name().into_string().ok())
+            .filter(|name| !name.starts_with('.') || component.starts_with('.'))
+            .filter(|name| util::glob_match(component, name))
+            .map(|name| join_path(base, &name))
+            .collect()
+    } else {
+        // A literal name: just check that it exists.
+        let path = join_path(base, component);
+        if fs::symlink_metadata(&path).is_err() {
+            return;
+        }
+        vec![path]
+    };
+
+    for path in matches {
+        if rest.is_empty() {
+            paths.push(path);
+        } else if fs::metadata(&path).map(|m| m.is_dir()).unwrap_or(false) {
+            glob_paths(&path, rest, paths);
+        }
+    }
+}
+
+/// Joins a name to a base path.  An empty base is the current directory.
+#[cfg(feature = "os")]
+fn join_path(base: &str, name: &str) -> String {
+    if base.is_empty() {
+        name.to_string()
+    } else if base.ends_with('/') {
+        format!("{}{}", base, name)
+    } else {
+        format!("{}/{}", base, name)
+    }
+}
+
 /// # global ?*varName* ...?
 ///
 /// Appends any number of values to a variable's value, which need not
@@ -586,7 +1527,9 @@ pub fn cmd_if(interp: &mut Interp, _: ContextID, argv: &[Value]) -> MoltResult {
 
 /// # incr *varName* ?*increment* ...?
 ///
-/// Increments an integer variable by a value.
+/// Increments an integer variable by a value.  If the variable doesn't exist, it is
+/// initialized to 0 before being incremented; it's an error if it exists but its value
+/// isn't an integer.
 pub fn cmd_incr(interp: &mut Interp, _: ContextID, argv: &[Value]) -> MoltResult {
     check_args(1, argv, 2, 3, "varName ?increment?")?;
 
@@ -596,11 +1539,12 @@ pub fn cmd_incr(interp: &mut Interp, _: ContextID, argv: &[Value]) -> MoltResult
         1
     };
 
-    let new_value = increment
-        + interp
-            .var(&argv[1])
-            .and_then(|val| Ok(val.as_int()?))
-            .unwrap_or_else(|_| 0);
+    let old_value = if interp.var_exists(&argv[1]) {
+        interp.var(&argv[1])?.as_int()?
+    } else {
+        0
+    };
+    let new_value = old_value + increment;
 
     interp.set_var_return(&argv[1], new_value.into())
 }
@@ -610,7 +1554,7 @@ pub fn cmd_info(interp: &mut Interp, context_id: ContextID, argv: &[Value]) -> M
     interp.call_subcommand(context_id, argv, 1, &INFO_SUBCOMMANDS)
 }
 
-const INFO_SUBCOMMANDS: [Subcommand; 11] = [
+const INFO_SUBCOMMANDS: [Subcommand; 17] = [
     Subcommand("args", cmd_info_args),
     Subcommand("body", cmd_info_body),
     Subcommand("cmdtype", cmd_info_cmdtype),
@@ -618,9 +1562,15 @@ const INFO_SUBCOMMANDS: [Subcommand; 11] = [
     Subcommand("complete", cmd_info_complete),
     Subcommand("default", cmd_info_default),
     Subcommand("exists", cmd_info_exists),
+    Subcommand("frame", cmd_info_frame),
     Subcommand("globals", cmd_info_globals),
     Subcommand("locals", cmd_info_locals),
+    Subcommand("nameofexecutable", cmd_info_nameofexecutable),
+    Subcommand("patchlevel", cmd_info_patchlevel),
+    Subcommand("proc", cmd_info_proc),
     Subcommand("procs", cmd_info_procs),
+    Subcommand("script", cmd_info_script),
+    Subcommand("tclversion", cmd_info_tclversion),
     Subcommand("vars", cmd_info_vars),
 ];
 
@@ -642,9 +1592,29 @@ pub fn cmd_info_cmdtype(interp: &mut Interp, _: ContextID, argv: &[Value]) -> Mo
     interp.command_type(&argv[2].as_str())
 }
 
-/// # info commands ?*pattern*?
-pub fn cmd_info_commands(interp: &mut Interp, _: ContextID, _argv: &[Value]) -> MoltResult {
-    molt_ok!(Value::from(interp.command_names()))
+/// # info commands ?-ensemble *command*?
+pub fn cmd_info_commands(interp: &mut Interp, _: ContextID, argv: &[Value]) -> MoltResult {
+    if argv.len() == 2 {
+        return molt_ok!(Value::from(interp.command_names()));
+    }
+
+    if argv.len() != 4 || argv[2].as_str() != "-ensemble" {
+        return wrong_num_args(2, argv, "?-ensemble command?");
+    }
+
+    // Only ensembles defined at run time know their subcommands.
+    let name = argv[3].as_str();
+    match interp.ensemble(name) {
+        Some(ensemble) => {
+            let names: MoltList = ensemble
+                .command_names()
+                .into_iter()
+                .map(Value::from)
+                .collect();
+            molt_ok!(Value::from(names))
+        }
+        None => molt_err!("\"{}\" isn't an ensemble", name),
+    }
 }
 
 /// # info default *procname* *arg* *varname*
@@ -677,6 +1647,17 @@ pub fn cmd_info_complete(interp: &mut Interp, _: ContextID, argv: &[Value]) -> M
     }
 }
 
+/// # info frame ?*number*?
+pub fn cmd_info_frame(interp: &mut Interp, _: ContextID, argv: &[Value]) -> MoltResult {
+    check_args(2, argv, 2, 3, "?number?")?;
+
+    if argv.len() == 2 {
+        molt_ok!(interp.frame_count() as MoltInt)
+    } else {
+        interp.frame_info(argv[2].as_int()?)
+    }
+}
+
 /// # info globals
 /// TODO: Add glob matching as a feature, and provide optional pattern argument.
 pub fn cmd_info_globals(interp: &mut Interp, _: ContextID, _argv: &[Value]) -> MoltResult {
@@ -689,17 +1670,238 @@ pub fn cmd_info_locals(interp: &mut Interp, _: ContextID, _argv: &[Value]) -> Mo
     molt_ok!(Value::from(interp.vars_in_local_scope()))
 }
 
+/// # info nameofexecutable
+///
+/// Returns the full path of the running application's executable, or the empty string
+/// if it can't be determined.
+#[cfg(feature = "os")]
+pub fn cmd_info_nameofexecutable(_interp: &mut Interp, _: ContextID, argv: &[Value]) -> MoltResult {
+    check_args(2, argv, 2, 2, "")?;
+
+    match std::env::current_exe() {
+        Ok(path) => molt_ok!(path.to_string_lossy().into_owned()),
+        Err(_) => molt_ok!(),
+    }
+}
+
+/// # info nameofexecutable
+///
+/// Without the `os` feature, there's no executable to report; returns the empty string.
+#[cfg(not(feature = "os"))]
+pub fn cmd_info_nameofexecutable(_interp: &mut Interp, _: ContextID, argv: &[Value]) -> MoltResult {
+    check_args(2, argv, 2, 2, "")?;
+    molt_ok!()
+}
+
+/// # info patchlevel
+///
+/// Returns Molt's full version number, e.g., `0.3.2`.
+pub fn cmd_info_patchlevel(_interp: &mut Interp, _: ContextID, argv: &[Value]) -> MoltResult {
+    check_args(2, argv, 2, 2, "")?;
+    molt_ok!(env!("CARGO_PKG_VERSION"))
+}
+
+/// # info proc *procname*
+///
+/// Returns a dictionary describing the procedure's definition: its `args` and `body`, as
+/// for `info args` and `info body`; `numArgs` and `numOptionalArgs`, the numbers of
+/// required and optional arguments; and `hasArgs`, whether it takes variadic `args`.
+pub fn cmd_info_proc(interp: &mut Interp, _: ContextID, argv: &[Value]) -> MoltResult {
+    check_args(2, argv, 3, 3, "procname")?;
+
+    let procname = argv[2].as_str();
+    let args = interp.proc_args(procname)?;
+    let body = interp.proc_body(procname)?;
+
+    let mut num_args: MoltInt = 0;
+    let mut num_optional_args: MoltInt = 0;
+    let mut has_args = false;
+
+    for arg in &*args.as_list()? {
+        if arg.as_str() == "args" {
+            has_args = true;
+        } else if interp.proc_default(procname, arg.as_str())?.is_some() {
+            num_optional_args += 1;
+        } else {
+            num_args += 1;
+        }
+    }
+
+    let mut dict = dict_new();
+    dict.insert("args".into(), args);
+    dict.insert("body".into(), body);
+    dict.insert("numArgs".into(), num_args.into());
+    dict.insert("numOptionalArgs".into(), num_optional_args.into());
+    dict.insert("hasArgs".into(), has_args.into());
+
+    molt_ok!(dict)
+}
+
 /// # info procs ?*pattern*?
 pub fn cmd_info_procs(interp: &mut Interp, _: ContextID, _argv: &[Value]) -> MoltResult {
     molt_ok!(Value::from(interp.proc_names()))
 }
 
+/// # info script
+///
+/// Returns the path of the script file currently being evaluated, e.g., by `source`, or
+/// the empty string if none is.
+pub fn cmd_info_script(interp: &mut Interp, _: ContextID, argv: &[Value]) -> MoltResult {
+    check_args(2, argv, 2, 2, "")?;
+    molt_ok!(interp.script_file().unwrap_or(""))
+}
+
+/// # info tclversion
+///
+/// Returns Molt's major and minor version number, e.g., `0.3`.
+pub fn cmd_info_tclversion(_interp: &mut Interp, _: ContextID, argv: &[Value]) -> MoltResult {
+    check_args(2, argv, 2, 2, "")?;
+    molt_ok!(concat!(
+        env!("CARGO_PKG_VERSION_MAJOR"),
+        ".",
+        env!("CARGO_PKG_VERSION_MINOR")
+    ))
+}
+
 /// # info vars
 /// TODO: Add glob matching as a feature, and provide optional pattern argument.
 pub fn cmd_info_vars(interp: &mut Interp, _: ContextID, _argv: &[Value]) -> MoltResult {
     molt_ok!(Value::from(interp.vars_in_scope()))
 }
 
+/// # interp *subcommand* ?*arg*...?
+///
+/// Creates, evaluates scripts in, and deletes child interpreters.
+///
+/// ## TCL Liens
+///
+/// * Supports only the `create`, `delete`, `eval`, `exists`, and `issafe` subcommands.
+/// * Child interpreters are named by simple names, not paths.
+pub fn cmd_interp(interp: &mut Interp, context_id: ContextID, argv: &[Value]) -> MoltResult {
+    interp.call_subcommand(context_id, argv, 1, &INTERP_SUBCOMMANDS)
+}
+
+const INTERP_SUBCOMMANDS: [Subcommand; 6] = [
+    Subcommand("alias", cmd_interp_alias),
+    Subcommand("create", cmd_interp_create),
+    Subcommand("delete", cmd_interp_delete),
+    Subcommand("eval", cmd_interp_eval),
+    Subcommand("exists", cmd_interp_exists),
+    Subcommand("issafe", cmd_interp_issafe),
+];
+
+/// # interp alias *name* *aliasName* ?*targetCmd* ?*arg* ...??
+///
+/// Defines an alias in the named child interpreter that calls a command prefix in
+/// this interpreter, returning the alias name; or, if *name* is empty, defines an ordinary
+/// alias in this interpreter.  If *targetCmd* is the empty string, deletes the alias.  If
+/// it's omitted, returns the alias's target.
+pub fn cmd_interp_alias(interp: &mut Interp, _: ContextID, argv: &[Value]) -> MoltResult {
+    check_args(2, argv, 4, 0, "path aliasName ?targetCmd ?arg ...??")?;
+
+    let path = argv[2].as_str();
+    let alias = argv[3].as_str();
+    let target = &argv[4..];
+    let src = if path.is_empty() {
+        interp
+    } else {
+        interp.child(path)?
+    };
+
+    match target {
+        [] => src.alias_target(alias),
+        [cmd] if cmd.as_str().is_empty() => {
+            src.alias_target(alias)?;
+            src.remove_command(alias);
+            molt_ok!()
+        }
+        _ if path.is_empty() => {
+            src.add_alias(alias, target.to_vec());
+            molt_ok!(alias)
+        }
+        _ => {
+            src.add_parent_alias(alias, target.to_vec());
+            molt_ok!(alias)
+        }
+    }
+}
+
+/// # interp create ?-safe? ?--? ?*name*?
+///
+/// Creates a child interpreter, returning its name.  If no name is given, one is
+/// generated.
+pub fn cmd_interp_create(interp: &mut Interp, _: ContextID, argv: &[Value]) -> MoltResult {
+    check_args(2, argv, 2, 5, "?-safe? ?--? ?path?")?;
+
+    let mut safe = false;
+    let mut i = 2;
+
+    while i < argv.len() && argv[i].as_str().starts_with('-') {
+        let opt = argv[i].as_str();
+        i += 1;
+
+        match opt {
+            "-safe" => safe = true,
+            "--" => break,
+            _ => return molt_err!("bad option \"{}\": must be -safe or --", opt),
+        }
+    }
+
+    let name = match &argv[i..] {
+        [] => interp.new_child_name(),
+        [name] => name.to_string(),
+        _ => return wrong_num_args(2, argv, "?-safe? ?--? ?path?"),
+    };
+
+    interp.create_child(&name, safe)?;
+    molt_ok!(name)
+}
+
+/// # interp delete ?*name* ...?
+///
+/// Deletes the named child interpreters.
+pub fn cmd_interp_delete(interp: &mut Interp, _: ContextID, argv: &[Value]) -> MoltResult {
+    check_args(2, argv, 2, 0, "?path ...?")?;
+
+    for name in &argv[2..] {
+        interp.delete_child(name.as_str())?;
+    }
+
+    molt_ok!()
+}
+
+/// # interp eval *name* *script*
+///
+/// Evaluates the script in the named child interpreter, returning its result.
+pub fn cmd_interp_eval(interp: &mut Interp, _: ContextID, argv: &[Value]) -> MoltResult {
+    check_args(2, argv, 4, 4, "path script")?;
+
+    interp.eval_in_child(argv[2].as_str(), &argv[3])
+}
+
+/// # interp exists *name*
+///
+/// Returns 1 if there's a child interpreter with the given name, and 0 otherwise.
+pub fn cmd_interp_exists(interp: &mut Interp, _: ContextID, argv: &[Value]) -> MoltResult {
+    check_args(2, argv, 3, 3, "path")?;
+
+    molt_ok!(interp.has_child(argv[2].as_str()))
+}
+
+/// # interp issafe ?*name*?
+///
+/// Returns 1 if the named child interpreter is safe, and 0 otherwise.  If no name is
+/// given, checks the current interpreter.
+pub fn cmd_interp_issafe(interp: &mut Interp, _: ContextID, argv: &[Value]) -> MoltResult {
+    check_args(2, argv, 2, 3, "?path?")?;
+
+    if argv.len() == 3 {
+        molt_ok!(interp.child(argv[2].as_str())?.is_safe())
+    } else {
+        molt_ok!(interp.is_safe())
+    }
+}
+
 /// # join *list* ?*joinString*?
 ///
 /// Joins the elements of a list with a string.  The join string defaults to " ".
@@ -709,15 +1911,12 @@ pub fn cmd_join(_interp: &mut Interp, _: ContextID, argv: &[Value]) -> MoltResul
     let list = &argv[1].as_list()?;
 
     let join_string = if argv.len() == 3 {
-        argv[2].to_string()
+        argv[2].as_str()
     } else {
-        " ".to_string()
+        " "
     };
 
-    // TODO: Need to implement a standard join() method for MoltLists.
-    let list: Vec<String> = list.iter().map(|v| v.to_string()).collect();
-
-    molt_ok!(list.join(&join_string))
+    molt_ok!(list::list_join(list, join_string))
 }
 
 /// # lappend *varName* ?*value* ...?
@@ -758,7 +1957,7 @@ pub fn lindex_into(list: &Value, indices: &[Value]) -> MoltResult {
 
     for index_val in indices {
         let list = value.as_list()?;
-        let index = index_val.as_int()?;
+        let index = list::parse_index(index_val, list.len())?;
 
         value = if index < 0 || index as usize >= list.len() {
             Value::empty()
@@ -787,6 +1986,105 @@ pub fn cmd_llength(_interp: &mut Interp, _: ContextID, argv: &[Value]) -> MoltRe
     molt_ok!(argv[1].as_list()?.len() as MoltInt)
 }
 
+/// # namespace *subcommand* ?*arg*...?
+///
+/// Creates and queries namespaces.
+///
+/// ## TCL Liens
+///
+/// * Supports only the `current`, `eval`, and `exists` subcommands.
+/// * Namespace variables are global variables whose names carry the namespace prefix; see
+///   the `variable` command.
+pub fn cmd_namespace(interp: &mut Interp, context_id: ContextID, argv: &[Value]) -> MoltResult {
+    interp.call_subcommand(context_id, argv, 1, &NAMESPACE_SUBCOMMANDS)
+}
+
+const NAMESPACE_SUBCOMMANDS: [Subcommand; 3] = [
+    Subcommand("current", cmd_namespace_current),
+    Subcommand("eval", cmd_namespace_eval),
+    Subcommand("exists", cmd_namespace_exists),
+];
+
+/// # namespace current
+///
+/// Returns the fully qualified name of the current namespace.
+pub fn cmd_namespace_current(interp: &mut Interp, _: ContextID, argv: &[Value]) -> MoltResult {
+    check_args(2, argv, 2, 2, "")?;
+    molt_ok!(interp.current_namespace())
+}
+
+/// # namespace eval *namespace* *script*
+///
+/// Evaluates the script in the namespace, creating the namespace if it doesn't exist.
+/// Procedures defined by the script are defined in the namespace.  The script is
+/// evaluated in its own variable scope, in which unqualified variable names refer to the
+/// namespace's variables, or to existing global variables.
+pub fn cmd_namespace_eval(interp: &mut Interp, _: ContextID, argv: &[Value]) -> MoltResult {
+    check_args(2, argv, 4, 4, "name script")?;
+
+    let ns = interp.add_namespace(argv[2].as_str()).into();
+
+    let caller_ns = interp.set_current_namespace(&ns);
+    interp.push_namespace_scope(&ns);
+    let result = interp.eval_value(&argv[3]);
+    interp.pop_scope();
+    interp.set_current_namespace(&caller_ns);
+
+    result
+}
+
+/// # namespace exists *namespace*
+///
+/// Returns 1 if the namespace exists, and 0 otherwise.
+pub fn cmd_namespace_exists(interp: &mut Interp, _: ContextID, argv: &[Value]) -> MoltResult {
+    check_args(2, argv, 3, 3, "name")?;
+    molt_ok!(interp.namespace_exists(argv[2].as_str()))
+}
+
+/// # open *fileName* ?*access*?
+///
+/// Opens the file, returning a channel ID for use with the other I/O commands.  The
+/// *access* mode is one of `r`, `r+`, `w`, `w+`, `a`, or `a+`, as for C's `fopen`; it
+/// defaults to `r`.
+///
+/// Requires the `os` feature.
+///
+/// ## TCL Liens
+///
+/// * Does not support the POSIX access flags, e.g., `{WRONLY CREAT}`, or the *permissions*
+///   argument.
+#[cfg(feature = "os")]
+pub fn cmd_open(interp: &mut Interp, context_id: ContextID, argv: &[Value]) -> MoltResult {
+    check_args(1, argv, 2, 3, "fileName ?access?")?;
+
+    let access = if argv.len() == 3 {
+        argv[2].as_str()
+    } else {
+        "r"
+    };
+
+    let id = interp
+        .context::<ChannelTable>(context_id)
+        .open(argv[1].as_str(), access)?;
+    molt_ok!(id)
+}
+
+/// # parray *arrayName* ?*pattern*?
+///
+/// Writes the array's elements to standard output using `puts`, one per line, sorted by
+/// index.  If the glob *pattern* is given, only the elements whose indices match it are
+/// written.
+pub fn cmd_parray(interp: &mut Interp, _: ContextID, argv: &[Value]) -> MoltResult {
+    check_args(1, argv, 2, 3, "arrayName ?pattern?")?;
+
+    let pattern = argv.get(2).map(|p| p.as_str());
+    for line in interp.array_dump(argv[1].as_str(), pattern)? {
+        interp.call(&[Value::from("puts"), Value::from(line)])?;
+    }
+
+    molt_ok!()
+}
+
 /// # pdump
 ///
 /// Dumps profile data.  Developer use only.
@@ -819,36 +2117,105 @@ pub fn cmd_proc(interp: &mut Interp, _: ContextID, argv: &[Value]) -> MoltResult
     let name = argv[1].as_str();
     let args = &*argv[2].as_list()?;
 
-    // NEXT, validate the argument specs
-    for arg in args {
-        let vec = arg.as_list()?;
+    // NEXT, add the command; this validates the argument specs.
+    interp.add_proc(name, args, &argv[3])?;
 
-        if vec.is_empty() {
-            return molt_err!("argument with no name");
-        } else if vec.len() > 2 {
-            return molt_err!("too many fields in argument specifier \"{}\"", arg);
-        }
-    }
+    molt_ok!()
+}
+
+/// # puts ?-nonewline? ?*channelId*? *string*
+///
+/// Outputs the string to the channel, which defaults to `stdout`, followed by a newline
+/// unless `-nonewline` is given.
+///
+/// Without the `os` feature, the only channels are `stdout` and `stderr`.
+pub fn cmd_puts(interp: &mut Interp, context_id: ContextID, argv: &[Value]) -> MoltResult {
+    check_args(1, argv, 2, 4, "?-nonewline? ?channelId? string")?;
+
+    let nonewline = argv.len() > 2 && argv[1].as_str() == "-nonewline";
+    let args = if nonewline { &argv[2..] } else { &argv[1..] };
 
-    // NEXT, add the command.
-    interp.add_proc(name, args, &argv[3]);
+    let (id, string) = match args {
+        [string] => ("stdout", string),
+        [id, string] => (id.as_str(), string),
+        _ => return wrong_num_args(1, argv, "?-nonewline? ?channelId? string"),
+    };
+
+    let mut text = string.to_string();
+    if !nonewline {
+        text.push('\n');
+    }
 
+    puts_to_channel(interp, context_id, id, &text)?;
     molt_ok!()
 }
 
-/// # puts *string*
+#[cfg(feature = "os")]
+fn puts_to_channel(
+    interp: &mut Interp,
+    context_id: ContextID,
+    id: &str,
+    text: &str,
+) -> Result<(), Exception> {
+    interp
+        .context::<ChannelTable>(context_id)
+        .get(id)?
+        .puts(text)
+}
+
+#[cfg(not(feature = "os"))]
+fn puts_to_channel(_: &mut Interp, _: ContextID, id: &str, text: &str) -> Result<(), Exception> {
+    match id {
+        "stdout" => print!("{}", text),
+        "stderr" => eprint!("{}", text),
+        _ => return molt_err!("can not find channel named \"{}\"", id),
+    }
+    Ok(())
+}
+
+/// # read ?-nonewline? *channelId*
+/// # read *channelId* *numBytes*
 ///
-/// Outputs the string to stdout.
+/// Reads the remainder of the channel's input, or at most *numBytes* bytes of it.  If
+/// `-nonewline` is given, a final newline is dropped.
+///
+/// Requires the `os` feature.
 ///
 /// ## TCL Liens
 ///
-/// * Does not support `-nonewline`
-/// * Does not support `channelId`
-pub fn cmd_puts(_interp: &mut Interp, _: ContextID, argv: &[Value]) -> MoltResult {
-    check_args(1, argv, 2, 2, "string")?;
+/// * Standard TCL reads *numChars* characters rather than *numBytes* bytes.
+#[cfg(feature = "os")]
+pub fn cmd_read(interp: &mut Interp, context_id: ContextID, argv: &[Value]) -> MoltResult {
+    if argv.len() < 2 || argv.len() > 3 {
+        return molt_err!(
+            "wrong # args: should be \"read channelId ?numBytes?\" or \"read ?-nonewline? channelId\""
+        );
+    }
+
+    let nonewline = argv.len() == 3 && argv[1].as_str() == "-nonewline";
+
+    let (id, max) = if nonewline {
+        (&argv[2], None)
+    } else if argv.len() == 3 {
+        let count = argv[2].as_int()?;
+        if count < 0 {
+            return molt_err!("expected non-negative integer but got \"{}\"", argv[2]);
+        }
+        (&argv[1], Some(count as usize))
+    } else {
+        (&argv[1], None)
+    };
 
-    println!("{}", argv[1]);
-    molt_ok!()
+    let mut text = interp
+        .context::<ChannelTable>(context_id)
+        .get(id.as_str())?
+        .read(max)?;
+
+    if nonewline && text.ends_with('\n') {
+        text.pop();
+    }
+
+    molt_ok!(text)
 }
 
 /// # rename *oldName* *newName*
@@ -866,6 +2233,11 @@ pub fn cmd_rename(interp: &mut Interp, _: ContextID, argv: &[Value]) -> MoltResu
         return molt_err!("can't rename \"{}\": command doesn't exist", old_name);
     }
 
+    // The new name is qualified, so that a global command doesn't hide the namespace's own.
+    if !new_name.is_empty() && interp.has_command(&interp.qualify_name(new_name)) {
+        return molt_err!("can't rename to \"{}\": command already exists", new_name);
+    }
+
     // NEXT, rename or remove the command.
     if new_name.is_empty() {
         interp.remove_command(old_name);
@@ -876,14 +2248,12 @@ pub fn cmd_rename(interp: &mut Interp, _: ContextID, argv: &[Value]) -> MoltResu
     molt_ok!()
 }
 
-/// # return ?-code code? ?-level level? ?value?
+/// # return ?-code code? ?-level level? ?-options dict? ?option value...? ?value?
 ///
 /// Returns from a proc with the given *value*, which defaults to the empty result.
 /// See the documentation for **return** in The Molt Book for the option semantics.
-///
-/// ## TCL Liens
-///
-/// * Doesn't support all of TCL's fancy return machinery. Someday it will.
+/// Options other than the standard ones are retained, to be included in the `catch`
+/// options; `-options` merges an options dictionary into the options.
 pub fn cmd_return(_interp: &mut Interp, _: ContextID, argv: &[Value]) -> MoltResult {
     check_args(1, argv, 1, 0, "?options...? ?value?")?;
 
@@ -892,6 +2262,8 @@ pub fn cmd_return(_interp: &mut Interp, _: ContextID, argv: &[Value]) -> MoltRes
     let mut level: MoltInt = 1;
     let mut error_code: Option<Value> = None;
     let mut error_info: Option<Value> = None;
+    let mut error_line: Option<MoltInt> = None;
+    let mut extra_options = MoltDict::new();
 
     // NEXT, with no arguments just return.
     if argv.len() == 1 {
@@ -916,54 +2288,63 @@ pub fn cmd_return(_interp: &mut Interp, _: ContextID, argv: &[Value]) -> MoltRes
         &argv[1..argv.len()]
     };
 
-    // NEXT, Get any options
-    let mut queue = opt_args.iter();
+    // NEXT, get the option/value pairs, expanding any -options dictionaries in place.
+    let mut options: Vec<(Value, Value)> = Vec::new();
 
-    while let Some(opt) = queue.next() {
-        // We built the queue to have an even number of arguments, and every option requires
-        // a value; so there can't be a missing option value.
-        let val = queue
-            .next()
-            .expect("missing option value: coding error in cmd_return");
+    for pair in opt_args.chunks(2) {
+        if pair[0].as_str() == "-options" {
+            for (opt, val) in pair[1].as_dict()?.iter() {
+                options.push((opt.clone(), val.clone()));
+            }
+        } else {
+            options.push((pair[0].clone(), pair[1].clone()));
+        }
+    }
 
+    // NEXT, Get any options
+    for (opt, val) in options {
         match opt.as_str() {
             "-code" => {
-                code = ResultCode::from_value(val)?;
+                code = ResultCode::from_value(&val)?;
             }
             "-errorcode" => {
-                error_code = Some(val.clone());
+                error_code = Some(val);
             }
             "-errorinfo" => {
-                error_info = Some(val.clone());
+                error_info = Some(val);
+            }
+            "-errorline" => {
+                error_line = Some(val.as_int()?);
             }
             "-level" => {
                 // TODO: return better error:
                 // bad -level value: expected non-negative integer but got "{}"
                 level = val.as_int()?;
             }
-            // TODO: In standard TCL there are no invalid options; all options are retained.
-            _ => return molt_err!("invalid return option: \"{}\"", opt),
+            _ => {
+                // As in standard TCL, all other options are retained.
+                extra_options.insert(opt, val);
+            }
         }
     }
 
     // NEXT, return the result: normally a Return exception, but could be "Ok".
-    if code == ResultCode::Error {
-        Err(Exception::molt_return_err(
-            return_value,
-            level as usize,
-            error_code,
-            error_info,
-        ))
+    let mut exception = if code == ResultCode::Error {
+        let mut exception =
+            Exception::molt_return_err(return_value, level as usize, error_code, error_info);
+        if let Some(line) = error_line {
+            exception.set_error_line(line as usize);
+        }
+        exception
     } else if level == 0 && code == ResultCode::Okay {
-        // Not an exception!j
-        Ok(return_value)
+        // Not an exception!
+        return Ok(return_value);
     } else {
-        Err(Exception::molt_return_ext(
-            return_value,
-            level as usize,
-            code,
-        ))
-    }
+        Exception::molt_return_ext(return_value, level as usize, code)
+    };
+
+    exception.set_extra_options(extra_options);
+    Err(exception)
 }
 
 /// # set *varName* ?*newValue*?
@@ -984,15 +2365,51 @@ pub fn cmd_set(interp: &mut Interp, _: ContextID, argv: &[Value]) -> MoltResult
 /// # source *filename*
 ///
 /// Sources the file, returning the result.
+#[cfg(feature = "os")]
 pub fn cmd_source(interp: &mut Interp, _: ContextID, argv: &[Value]) -> MoltResult {
     check_args(1, argv, 2, 2, "filename")?;
 
-    let filename = argv[1].as_str();
+    interp.eval_file(Path::new(argv[1].as_str()))
+}
+
+/// # split *string* ?*splitChars*?
+///
+/// Splits the string into a list at each of the *splitChars*, which default to the
+/// whitespace characters.  If *splitChars* is the empty string, each character becomes
+/// a separate element.  The elements share the input string rather than copying it.
+pub fn cmd_split(_interp: &mut Interp, _: ContextID, argv: &[Value]) -> MoltResult {
+    check_args(1, argv, 2, 3, "string ?splitChars?")?;
+
+    let string = argv[1].as_str();
+    let split_chars = if argv.len() == 3 {
+        argv[2].as_str()
+    } else {
+        " \t\n\r"
+    };
+
+    let mut list = MoltList::new();
 
-    match fs::read_to_string(filename) {
-        Ok(script) => interp.eval(&script),
-        Err(e) => molt_err!("couldn't read file \"{}\": {}", filename, e),
+    if string.is_empty() {
+        return molt_ok!(list);
     }
+
+    if split_chars.is_empty() {
+        for (i, ch) in string.char_indices() {
+            list.push(Value::from_substring(&argv[1], i..i + ch.len_utf8()));
+        }
+    } else {
+        let mut start = 0;
+
+        for (i, ch) in string.char_indices() {
+            if split_chars.contains(ch) {
+                list.push(Value::from_substring(&argv[1], start..i));
+                start = i + ch.len_utf8();
+            }
+        }
+        list.push(Value::from_substring(&argv[1], start..string.len()));
+    }
+
+    molt_ok!(list)
 }
 
 /// # string *subcommand* ?*arg*...?
@@ -1000,15 +2417,19 @@ pub fn cmd_string(interp: &mut Interp, context_id: ContextID, argv: &[Value]) ->
     interp.call_subcommand(context_id, argv, 1, &STRING_SUBCOMMANDS)
 }
 
-const STRING_SUBCOMMANDS: [Subcommand; 13] = [
+const STRING_SUBCOMMANDS: [Subcommand; 19] = [
+    Subcommand("bytelength", cmd_string_bytelength),
     Subcommand("cat", cmd_string_cat),
     Subcommand("compare", cmd_string_compare),
     Subcommand("equal", cmd_string_equal),
     Subcommand("first", cmd_string_first),
     // Subcommand("index", cmd_string_todo),
+    Subcommand("insert", cmd_string_insert),
+    Subcommand("is", cmd_string_is),
     Subcommand("last", cmd_string_last),
     Subcommand("length", cmd_string_length),
     Subcommand("map", cmd_string_map),
+    Subcommand("match", cmd_string_match),
     Subcommand("range", cmd_string_range),
     // Subcommand("replace", cmd_string_todo),
     // Subcommand("repeat", cmd_string_todo),
@@ -1018,6 +2439,8 @@ const STRING_SUBCOMMANDS: [Subcommand; 13] = [
     Subcommand("trim", cmd_string_trim),
     Subcommand("trimleft", cmd_string_trim),
     Subcommand("trimright", cmd_string_trim),
+    Subcommand("wordend", cmd_string_wordend),
+    Subcommand("wordstart", cmd_string_wordstart),
 ];
 
 /// Temporary: stub for string subcommands.
@@ -1026,6 +2449,14 @@ pub fn cmd_string_todo(_interp: &mut Interp, _: ContextID, _argv: &[Value]) -> M
     molt_err!("TODO")
 }
 
+/// string bytelength *string*
+pub fn cmd_string_bytelength(_interp: &mut Interp, _: ContextID, argv: &[Value]) -> MoltResult {
+    check_args(2, argv, 3, 3, "string")?;
+
+    let len: MoltInt = argv[2].as_str().len() as MoltInt;
+    molt_ok!(len)
+}
+
 /// string cat ?*arg* ...?
 pub fn cmd_string_cat(_interp: &mut Interp, _: ContextID, argv: &[Value]) -> MoltResult {
     let mut buff = String::new();
@@ -1057,24 +2488,20 @@ pub fn cmd_string_compare(_interp: &mut Interp, _: ContextID, argv: &[Value]) ->
                 if let Some(val) = queue.next() {
                     length = Some(val.as_int()?);
                 } else {
-                    return molt_err!("wrong # args: should be \"string compare ?-nocase? ?-length length? string1 string2\"");
+                    return wrong_num_args(2, argv, "?-nocase? ?-length length? string1 string2");
                 }
             }
             _ => return molt_err!("bad option \"{}\": must be -nocase or -length", opt),
         }
     }
 
-    if nocase {
-        let val1 = &argv[arglen - 2];
-        let val2 = &argv[arglen - 1];
-
-        // TODO: *Not* the best way to do this; consider using the unicase crate.
-        let val1 = Value::from(val1.as_str().to_lowercase());
-        let val2 = Value::from(val2.as_str().to_lowercase());
+    let str1 = argv[arglen - 2].as_str();
+    let str2 = argv[arglen - 1].as_str();
 
-        molt_ok!(util::compare_len(val1.as_str(), val2.as_str(), length)?)
+    if nocase {
+        molt_ok!(util::compare_nocase_len(str1, str2, length)?)
     } else {
-        molt_ok!(util::compare_len(argv[arglen - 2].as_str(), argv[arglen - 1].as_str(), length)?)
+        molt_ok!(util::compare_len(str1, str2, length)?)
     }
 }
 
@@ -1098,27 +2525,23 @@ pub fn cmd_string_equal(_interp: &mut Interp, _: ContextID, argv: &[Value]) -> M
                 if let Some(val) = queue.next() {
                     length = Some(val.as_int()?);
                 } else {
-                    return molt_err!("wrong # args: should be \"string equal ?-nocase? ?-length length? string1 string2\"");
+                    return wrong_num_args(2, argv, "?-nocase? ?-length length? string1 string2");
                 }
             }
             _ => return molt_err!("bad option \"{}\": must be -nocase or -length", opt),
         }
     }
 
-    if nocase {
-        let val1 = &argv[arglen - 2];
-        let val2 = &argv[arglen - 1];
-
-        // TODO: *Not* the best way to do this; consider using the unicase crate.
-        let val1 = Value::from(val1.as_str().to_lowercase());
-        let val2 = Value::from(val2.as_str().to_lowercase());
+    let str1 = argv[arglen - 2].as_str();
+    let str2 = argv[arglen - 1].as_str();
 
-        let flag = util::compare_len(val1.as_str(), val2.as_str(), length)? == 0;
-        molt_ok!(flag)
+    let flag = if nocase {
+        util::compare_nocase_len(str1, str2, length)? == 0
     } else {
-        let flag = util::compare_len(argv[arglen - 2].as_str(), argv[arglen - 1].as_str(), length)? == 0;
-        molt_ok!(flag)
-    }
+        util::compare_len(str1, str2, length)? == 0
+    };
+
+    molt_ok!(flag)
 }
 
 /// string first *needleString* *haystackString* ?*startIndex*?
@@ -1131,28 +2554,202 @@ pub fn cmd_string_first(_interp: &mut Interp, _: ContextID, argv: &[Value]) -> M
     let start_char: usize = if argv.len() == 5 {
         let arg = argv[4].as_int()?;
 
-        if arg < 0 { 0 } else { arg as usize }
+        if arg < 0 {
+            0
+        } else {
+            arg as usize
+        }
     } else {
         0
     };
 
-    let pos_byte: Option<usize> = haystack
-        .char_indices()
-        .nth(start_char)
-        .and_then(|(start_byte, _)| haystack[start_byte..].find(needle));
-
-    let pos_char: MoltInt = match pos_byte {
+    // Search from the start character, converting the match's byte offset back to a
+    // character index.
+    let pos_char: MoltInt = match util::char_to_byte_offset(haystack, start_char) {
+        Some(start_byte) => match haystack[start_byte..].find(needle) {
+            Some(offset) => util::byte_to_char_offset(haystack, start_byte + offset) as MoltInt,
+            None => -1,
+        },
         None => -1,
-        Some(b) => haystack[b..]
-            .char_indices()
-            .take_while(|(i, _)| *i < b)
-            .count() as MoltInt
-            + start_char as MoltInt
     };
 
     molt_ok!(pos_char)
 }
 
+/// string insert *string* *index* *insertString*
+///
+/// Inserts *insertString* into *string* before the character at *index*.  An `end`-relative
+/// index counts from just past the last character, so that `end` appends the
+/// *insertString*.
+pub fn cmd_string_insert(_interp: &mut Interp, _: ContextID, argv: &[Value]) -> MoltResult {
+    check_args(2, argv, 5, 5, "string index insertString")?;
+
+    let string = argv[2].as_str();
+    let insert = argv[4].as_str();
+    let len = string.chars().count();
+
+    // Parse the index as though the string had one more character, so that "end" is
+    // the position following the last character.
+    let index = list::parse_index(&argv[3], len + 1)?.clamp(0, len as MoltInt) as usize;
+    let pos = util::char_to_byte_offset(string, index).unwrap_or(string.len());
+
+    let mut result = String::with_capacity(string.len() + insert.len());
+    result.push_str(&string[..pos]);
+    result.push_str(insert);
+    result.push_str(&string[pos..]);
+
+    molt_ok!(result)
+}
+
+/// string is *class* ?-strict? ?-failindex *varName*? *string*
+///
+/// Returns 1 if the *string* is a valid member of the *class*, and 0 otherwise.  The empty
+/// string is a member of every class unless `-strict` is given.  If the string is not a
+/// member and `-failindex` is given, the index of the first character that isn't valid is
+/// assigned to the variable.
+pub fn cmd_string_is(interp: &mut Interp, _: ContextID, argv: &[Value]) -> MoltResult {
+    check_args(2, argv, 4, 7, "class ?-strict? ?-failindex var? str")?;
+
+    // FIRST, get the options.
+    let arglen = argv.len();
+    let mut strict = false;
+    let mut fail_var: Option<&Value> = None;
+
+    let mut queue = argv[3..arglen - 1].iter();
+
+    while let Some(opt) = queue.next() {
+        match opt.as_str() {
+            "-strict" => strict = true,
+            "-failindex" => {
+                if let Some(var) = queue.next() {
+                    fail_var = Some(var);
+                } else {
+                    return wrong_num_args(2, argv, "class ?-strict? ?-failindex var? str");
+                }
+            }
+            _ => return molt_err!("bad option \"{}\": must be -strict or -failindex", opt),
+        }
+    }
+
+    // NEXT, check the string.  `fail_index` is None if the string is a member of the class.
+    let string = argv[arglen - 1].as_str();
+    let class = argv[2].as_str();
+
+    let fail_index = if !STRING_CLASSES.contains(&class) {
+        return molt_err!(
+            "bad class \"{}\": must be {}, or {}",
+            class,
+            STRING_CLASSES[..STRING_CLASSES.len() - 1].join(", "),
+            STRING_CLASSES[STRING_CLASSES.len() - 1]
+        );
+    } else if string.is_empty() {
+        if strict {
+            Some(0)
+        } else {
+            None
+        }
+    } else {
+        match class {
+            "boolean" => whole_string_check(Value::get_bool(string).is_ok()),
+            "dict" => whole_string_check(Value::from(string).as_dict().is_ok()),
+            "double" => numeric_check(string, |s| {
+                Value::get_float(s).is_ok() || Value::get_int(s).is_ok()
+            }),
+            "false" => whole_string_check(Value::get_bool(string) == Ok(false)),
+            "integer" | "entier" | "wideinteger" => {
+                numeric_check(string, |s| Value::get_int(s).is_ok())
+            }
+            "list" => whole_string_check(Value::from(string).as_list().is_ok()),
+            "true" => whole_string_check(Value::get_bool(string) == Ok(true)),
+            _ => string.chars().position(|ch| !char_in_class(class, ch)),
+        }
+    };
+
+    // NEXT, return the result.
+    if let Some(index) = fail_index {
+        if let Some(var) = fail_var {
+            interp.set_var(var, Value::from(index as MoltInt))?;
+        }
+        molt_ok!(false)
+    } else {
+        molt_ok!(true)
+    }
+}
+
+/// The character classes supported by `string is`.
+const STRING_CLASSES: [&str; 22] = [
+    "alnum",
+    "alpha",
+    "ascii",
+    "boolean",
+    "control",
+    "dict",
+    "digit",
+    "double",
+    "entier",
+    "false",
+    "graph",
+    "integer",
+    "list",
+    "lower",
+    "print",
+    "punct",
+    "space",
+    "true",
+    "upper",
+    "wideinteger",
+    "wordchar",
+    "xdigit",
+];
+
+/// Returns true if the character is a member of the named character class.
+fn char_in_class(class: &str, ch: char) -> bool {
+    match class {
+        "alnum" => ch.is_alphanumeric(),
+        "alpha" => ch.is_alphabetic(),
+        "ascii" => ch.is_ascii(),
+        "control" => ch.is_control(),
+        "digit" => ch.is_numeric(),
+        "graph" => !ch.is_whitespace() && !ch.is_control(),
+        "lower" => ch.is_lowercase(),
+        "print" => !ch.is_control(),
+        "punct" => !ch.is_alphanumeric() && !ch.is_whitespace() && !ch.is_control(),
+        "space" => ch.is_whitespace(),
+        "upper" => ch.is_uppercase(),
+        "wordchar" => util::is_varname_char(ch),
+        "xdigit" => ch.is_ascii_hexdigit(),
+        _ => unreachable!(),
+    }
+}
+
+/// The result of a `string is` check that applies to the string as a whole: if the check
+/// fails, the failure is at index 0.
+fn whole_string_check(is_valid: bool) -> Option<usize> {
+    if is_valid {
+        None
+    } else {
+        Some(0)
+    }
+}
+
+/// The result of a `string is` check for a numeric class: if the string isn't valid, the
+/// failure is at the end of the longest valid prefix.
+fn numeric_check(string: &str, is_valid: impl Fn(&str) -> bool) -> Option<usize> {
+    if is_valid(string) {
+        return None;
+    }
+
+    let mut fail_index = 0;
+
+    for (i, (pos, ch)) in string.char_indices().enumerate() {
+        if is_valid(&string[..pos + ch.len_utf8()]) {
+            fail_index = i + 1;
+        }
+    }
+
+    Some(fail_index)
+}
+
 /// string last *needleString* *haystackString* ?*lastIndex*?
 pub fn cmd_string_last(_interp: &mut Interp, _: ContextID, argv: &[Value]) -> MoltResult {
     check_args(2, argv, 4, 5, "needleString haystackString ?lastIndex?")?;
@@ -1180,20 +2777,15 @@ pub fn cmd_string_last(_interp: &mut Interp, _: ContextID, argv: &[Value]) -> Mo
 
     let slice = match last {
         None => haystack,
-        Some(n) => match haystack.char_indices().nth(n + 1) {
+        Some(n) => match util::char_to_byte_offset(haystack, n + 1) {
             None => haystack,
-            Some((byte, _)) => &haystack[..byte],
+            Some(byte) => &haystack[..byte],
         },
     };
 
-    let pos_byte = slice.rfind(needle);
-
-    let pos_char: MoltInt = match pos_byte {
+    let pos_char: MoltInt = match slice.rfind(needle) {
         None => -1,
-        Some(b) => haystack
-            .char_indices()
-            .take_while(|(i, _)| *i < b)
-            .count() as MoltInt
+        Some(b) => util::byte_to_char_offset(haystack, b) as MoltInt,
     };
 
     molt_ok!(pos_char)
@@ -1224,63 +2816,70 @@ pub fn cmd_string_map(_interp: &mut Interp, _: ContextID, argv: &[Value]) -> Mol
     }
 
     let char_map = argv[argv.len() - 2].as_dict()?;
-    let string = argv[argv.len() - 1].as_str();
+    let string: Vec<char> = argv[argv.len() - 1].as_str().chars().collect();
 
-    let filtered_keys = char_map
+    // The keys as characters, omitting empty keys, which never match.
+    let keys: Vec<(Vec<char>, &Value)> = char_map
         .iter()
-        .map(|(k, v)| {
-            let new_k = if nocase {
-                Value::from(k.as_str().to_lowercase())
-            } else {
-                k.clone()
-            };
+        .map(|(k, v)| (k.as_str().chars().collect::<Vec<_>>(), v))
+        .filter(|(k, _)| !k.is_empty())
+        .collect();
 
-            let count = new_k.as_str().chars().count();
-
-            (new_k, count, v.clone())
-        })
-        .filter(|(_, count, _)| *count > 0)
-        .collect::<Vec<_>>();
-
-    let string_lower: Option<String> = if nocase {
-        Some(string.to_lowercase())
-    } else {
-        None
-    };
+    // With -nocase, characters match if their lowercase forms are the same.
+    let same = |a: &char, b: &char| a == b || (nocase && a.to_lowercase().eq(b.to_lowercase()));
 
     let mut result = String::new();
-    let mut skip = 0;
+    let mut i = 0;
 
-    for (i, c) in string.char_indices() {
-        if skip > 0 {
-            skip -= 1;
-            continue;
-        }
+    while i < string.len() {
+        let rest = &string[i..];
+        let key = keys
+            .iter()
+            .find(|(k, _)| k.len() <= rest.len() && k.iter().zip(rest).all(|(a, b)| same(a, b)));
 
-        let mut matched = false;
+        match key {
+            Some((k, to)) => {
+                result.push_str(to.as_str());
+                i += k.len();
+            }
+            None => {
+                result.push(string[i]);
+                i += 1;
+            }
+        }
+    }
 
-        for (from, from_char_count, to) in &filtered_keys {
-            let haystack: &str = match &string_lower {
-                Some(x) => &x[i..],
-                None => &string[i..],
-            };
+    molt_ok!(result)
+}
 
-            if haystack.starts_with(&from.as_str()) {
-                matched = true;
+/// string match ?-nocase? *pattern* *string*
+///
+/// Returns 1 if the string matches the glob-style pattern, and 0 otherwise.
+pub fn cmd_string_match(_interp: &mut Interp, _: ContextID, argv: &[Value]) -> MoltResult {
+    check_args(2, argv, 4, 5, "?-nocase? pattern string")?;
 
-                result.push_str(to.as_str());
-                skip = from_char_count - 1;
+    let mut nocase = false;
 
-                break;
-            }
-        }
+    if argv.len() == 5 {
+        let opt = argv[2].as_str();
 
-        if !matched {
-            result.push(c);
+        if opt == "-nocase" {
+            nocase = true;
+        } else {
+            return molt_err!("bad option \"{}\": must be -nocase", opt);
         }
     }
 
-    molt_ok!(result)
+    let pattern = argv[argv.len() - 2].as_str();
+    let string = argv[argv.len() - 1].as_str();
+
+    let matched = if nocase {
+        util::glob_match(&pattern.to_lowercase(), &string.to_lowercase())
+    } else {
+        util::glob_match(pattern, string)
+    };
+
+    molt_ok!(matched)
 }
 
 /// string range *string* *first* *last*
@@ -1302,13 +2901,23 @@ pub fn cmd_string_range(_interp: &mut Interp, _: ContextID, argv: &[Value]) -> M
         }
     };
 
-    let substr = string
-        .chars()
-        .skip(clamp(first) as usize)
-        .take((clamp(last) - clamp(first) + 1) as usize)
-        .collect::<String>();
+    let (first, last) = (clamp(first) as usize, clamp(last) as usize);
+
+    if first > last {
+        return molt_ok!("");
+    }
+
+    // NEXT, convert the character indices to a byte range, and share the input string.
+    let start = match string.char_indices().nth(first) {
+        Some((i, _)) => i,
+        None => return molt_ok!(""),
+    };
+    let end = match string[start..].char_indices().nth(last - first + 1) {
+        Some((i, _)) => start + i,
+        None => string.len(),
+    };
 
-    molt_ok!(substr)
+    molt_ok!(Value::from_substring(&argv[2], start..end))
 }
 
 /// string tolower *string*
@@ -1341,6 +2950,68 @@ pub fn cmd_string_trim(_interp: &mut Interp, _: ContextID, argv: &[Value]) -> Mo
     molt_ok!(trimmed)
 }
 
+/// string wordend *string* *charIndex*
+///
+/// Returns the index of the character just after the last one in the word containing the
+/// character at *charIndex*.  A word is a run of alphanumeric characters and underscores;
+/// any other character is a word by itself.
+pub fn cmd_string_wordend(_interp: &mut Interp, _: ContextID, argv: &[Value]) -> MoltResult {
+    check_args(2, argv, 4, 4, "string charIndex")?;
+
+    let chars: Vec<char> = argv[2].as_str().chars().collect();
+    let index = list::parse_index(&argv[3], chars.len())?.max(0) as usize;
+
+    if index >= chars.len() {
+        return molt_ok!(chars.len() as MoltInt);
+    }
+
+    let mut end = index;
+    while end < chars.len() && util::is_varname_char(chars[end]) {
+        end += 1;
+    }
+
+    if end == index {
+        end += 1;
+    }
+
+    molt_ok!(end as MoltInt)
+}
+
+/// string wordstart *string* *charIndex*
+///
+/// Returns the index of the first character in the word containing the character at
+/// *charIndex*.  A word is a run of alphanumeric characters and underscores; any other
+/// character is a word by itself.
+pub fn cmd_string_wordstart(_interp: &mut Interp, _: ContextID, argv: &[Value]) -> MoltResult {
+    check_args(2, argv, 4, 4, "string charIndex")?;
+
+    let chars: Vec<char> = argv[2].as_str().chars().collect();
+    let index = list::parse_index(&argv[3], chars.len())?.min(chars.len() as MoltInt - 1);
+
+    if index <= 0 {
+        return molt_ok!(0);
+    }
+
+    let mut start = index as usize;
+    if util::is_varname_char(chars[start]) {
+        while start > 0 && util::is_varname_char(chars[start - 1]) {
+            start -= 1;
+        }
+    }
+
+    molt_ok!(start as MoltInt)
+}
+
+/// tailcall *command* ?*arg* ...?
+///
+/// Replaces the current procedure with a call to the command: the command is executed once
+/// the procedure returns, and its result is the procedure's result.
+pub fn cmd_tailcall(interp: &mut Interp, _: ContextID, argv: &[Value]) -> MoltResult {
+    check_args(1, argv, 2, 0, "command ?arg ...?")?;
+
+    interp.tailcall(&argv[1..])
+}
+
 /// throw *type* *message*
 ///
 /// Throws an error with the error code and message.
@@ -1350,22 +3021,45 @@ pub fn cmd_throw(_interp: &mut Interp, _: ContextID, argv: &[Value]) -> MoltResu
     Err(Exception::molt_err2(argv[1].clone(), argv[2].clone()))
 }
 
-/// # time *command* ?*count*?
+/// # time ?-result *format*? *command* ?*count*?
 ///
 /// Executes the command the given number of times, and returns the average
-/// number of microseconds per iteration.  The *count* defaults to 1.
+/// number of nanoseconds per iteration.  The *count* defaults to 1.  The *format* is
+/// `string`, the default, for a human-readable result, or `dict` for a dictionary of
+/// timing data.  It's an error if the interpreter has no clock; see `Interp::set_clock`.
 pub fn cmd_time(interp: &mut Interp, _: ContextID, argv: &[Value]) -> MoltResult {
-    check_args(1, argv, 2, 3, "command ?count?")?;
+    check_args(1, argv, 2, 5, "?-result format? command ?count?")?;
 
-    let command = &argv[1];
+    // FIRST, get the result format.
+    let mut as_dict = false;
+    let mut i = 1;
 
-    let count = if argv.len() == 3 {
-        argv[2].as_int()?
+    if argv[i].as_str() == "-result" && argv.len() > 2 {
+        as_dict = match argv[i + 1].as_str() {
+            "string" => false,
+            "dict" => true,
+            format => return molt_err!("bad format \"{}\": must be dict or string", format),
+        };
+        i += 2;
+    }
+
+    if argv.len() == i || argv.len() - i > 2 {
+        return wrong_num_args(1, argv, "?-result format? command ?count?");
+    }
+
+    // NEXT, time the command.
+    let command = &argv[i];
+
+    let count = if argv.len() == i + 2 {
+        argv[i + 1].as_int()?
     } else {
         1
     };
 
-    let start = Instant::now();
+    let start = match interp.now() {
+        Some(start) => start,
+        None => return molt_err!("time isn't available: the interpreter has no clock"),
+    };
 
     for _i in 0..count {
         let result = interp.eval_value(command);
@@ -1374,7 +3068,7 @@ pub fn cmd_time(interp: &mut Interp, _: ContextID, argv: &[Value]) -> MoltResult
         }
     }
 
-    let span = start.elapsed();
+    let span = interp.now().unwrap_or(start).saturating_sub(start);
 
     let avg = if count > 0 {
         span.as_nanos() / (count as u128)
@@ -1382,32 +3076,178 @@ pub fn cmd_time(interp: &mut Interp, _: ContextID, argv: &[Value]) -> MoltResult
         0
     } as MoltInt;
 
-    molt_ok!("{} nanoseconds per iteration", avg)
+    if !as_dict {
+        return molt_ok!("{} nanoseconds per iteration", avg);
+    }
+
+    let mut dict = dict_new();
+    dict.insert("nanoseconds".into(), avg.into());
+    dict.insert("microseconds".into(), (avg as MoltFloat / 1e3).into());
+    dict.insert("milliseconds".into(), (avg as MoltFloat / 1e6).into());
+    dict.insert("iterations".into(), count.max(0).into());
+    dict.insert(
+        "total_nanoseconds".into(),
+        (span.as_nanos() as MoltInt).into(),
+    );
+
+    molt_ok!(dict)
+}
+
+/// # trace *subcommand* ?*arg*...?
+///
+/// Adds, removes, and queries traces.
+///
+/// ## TCL Liens
+///
+/// * Supports only execution traces, and only the `enter` and `leave` operations.
+pub fn cmd_trace(interp: &mut Interp, context_id: ContextID, argv: &[Value]) -> MoltResult {
+    interp.call_subcommand(context_id, argv, 1, &TRACE_SUBCOMMANDS)
+}
+
+const TRACE_SUBCOMMANDS: [Subcommand; 3] = [
+    Subcommand("add", cmd_trace_add),
+    Subcommand("info", cmd_trace_info),
+    Subcommand("remove", cmd_trace_remove),
+];
+
+/// # trace add execution *name* *ops* *command*
+///
+/// Calls the *command* prefix when the named command is executed: with the command string
+/// and `enter` before the command is executed, and with the command string, the result
+/// code, the result, and `leave` after it returns.
+pub fn cmd_trace_add(interp: &mut Interp, _: ContextID, argv: &[Value]) -> MoltResult {
+    check_args(2, argv, 6, 6, "type name opList command")?;
+    trace_type(&argv[2])?;
+    let (enter, leave) = trace_ops(&argv[4])?;
+    interp.add_exec_trace(argv[3].as_str(), enter, leave, argv[5].clone())?;
+    molt_ok!()
+}
+
+/// # trace info execution *name*
+///
+/// Returns a list of the named command's execution traces, each a list of its operations
+/// and its command prefix.
+pub fn cmd_trace_info(interp: &mut Interp, _: ContextID, argv: &[Value]) -> MoltResult {
+    check_args(2, argv, 4, 4, "type name")?;
+    trace_type(&argv[2])?;
+    interp.exec_trace_info(argv[3].as_str())
+}
+
+/// # trace remove execution *name* *ops* *command*
+///
+/// Removes the named command's execution trace with the given operations and command
+/// prefix, if there is one.
+pub fn cmd_trace_remove(interp: &mut Interp, _: ContextID, argv: &[Value]) -> MoltResult {
+    check_args(2, argv, 6, 6, "type name opList command")?;
+    trace_type(&argv[2])?;
+    let (enter, leave) = trace_ops(&argv[4])?;
+    interp.remove_exec_trace(argv[3].as_str(), enter, leave, &argv[5])?;
+    molt_ok!()
+}
+
+/// Verifies that the trace type is one that Molt supports.
+fn trace_type(value: &Value) -> Result<(), Exception> {
+    if value.as_str() == "execution" {
+        Ok(())
+    } else {
+        molt_err!("bad option \"{}\": must be execution", value)
+    }
+}
+
+/// Parses an execution trace's list of operations, returning whether it includes `enter`
+/// and `leave`.
+fn trace_ops(value: &Value) -> Result<(bool, bool), Exception> {
+    let mut enter = false;
+    let mut leave = false;
+
+    for op in value.as_list()?.iter() {
+        match op.as_str() {
+            "enter" => enter = true,
+            "leave" => leave = true,
+            _ => return molt_err!("bad operation \"{}\": must be enter or leave", op),
+        }
+    }
+
+    if !enter && !leave {
+        return molt_err!(
+            "bad operation list \"{}\": must be one or more of enter or leave",
+            value
+        );
+    }
+
+    Ok((enter, leave))
 }
 
-/// # unset ?-nocomplain? *varName*
+/// # unset ?-nocomplain? ?--? ?*name* ...?
 ///
-/// Removes the variable from the interpreter.  This is a no op if
-/// there is no such variable.  The -nocomplain option is accepted for
-/// compatible with standard TCL, but is never required.
+/// Removes the variables from the interpreter.  It's an error if a variable doesn't
+/// exist, unless `-nocomplain` is given.
 pub fn cmd_unset(interp: &mut Interp, _: ContextID, argv: &[Value]) -> MoltResult {
     check_args(1, argv, 1, 0, "?-nocomplain? ?--? ?name name name...?")?;
 
-    let mut options_ok = true;
+    let mut complain = true;
+    let mut i = 1;
+
+    // FIRST, get the options; as in standard TCL, -nocomplain must come first.
+    if i < argv.len() && argv[i].as_str() == "-nocomplain" {
+        complain = false;
+        i += 1;
+    }
 
-    for arg in argv {
-        let var = arg.as_str();
+    if i < argv.len() && argv[i].as_str() == "--" {
+        i += 1;
+    }
 
-        if options_ok {
-            if var == "--" {
-                options_ok = false;
-                continue;
-            } else if var == "-nocomplain" {
-                continue;
-            }
+    // NEXT, unset the variables.
+    for arg in &argv[i..] {
+        if !interp.unset_var(arg) && complain {
+            let var_name = arg.as_var_name();
+
+            let reason = match var_name.index() {
+                Some(_) if interp.array_exists(var_name.name()) => "no such element in array",
+                Some(_) if interp.var_exists(&Value::from(var_name.name())) => {
+                    "variable isn't array"
+                }
+                _ => "no such variable",
+            };
+
+            return molt_err!("can't unset \"{}\": {}", arg, reason);
+        }
+    }
+
+    molt_ok!()
+}
+
+/// # variable ?*name* *value* ...? *name* ?*value*?
+///
+/// Declares variables in the current namespace, optionally setting their values.  Within
+/// a procedure or `namespace eval` script, each variable is linked to a local variable
+/// with the same simple name.
+///
+/// ## TCL Liens
+///
+/// * Namespace variables are global variables whose names carry the namespace prefix,
+///   e.g., `mylib::count` for variable `count` in namespace `::mylib`.
+pub fn cmd_variable(interp: &mut Interp, _: ContextID, argv: &[Value]) -> MoltResult {
+    check_args(1, argv, 2, 0, "?name value ...? name ?value?")?;
+
+    for pair in argv[1..].chunks(2) {
+        let name = pair[0].as_str();
+        let qualified = interp.qualify_name(name);
+
+        // FIRST, link the variable into the local scope, unless we're at the global scope.
+        if interp.scope_level() > 0 {
+            let local = match name.rfind("::") {
+                Some(i) => &name[i + 2..],
+                None => name,
+            };
+            interp.upvar_as(0, qualified.trim_start_matches("::"), local);
         }
 
-        interp.unset_var(arg);
+        // NEXT, set its value, if one was given.
+        if let Some(value) = pair.get(1) {
+            interp.set_scalar(&qualified, value.clone())?;
+        }
     }
 
     molt_ok!()
@@ -1434,3 +3274,15 @@ pub fn cmd_while(interp: &mut Interp, _: ContextID, argv: &[Value]) -> MoltResul
 
     molt_ok!()
 }
+
+/// # yield ?*value*?
+///
+/// Suspends the current coroutine, returning the *value* to its caller.  When the
+/// coroutine is resumed, returns the value passed to the coroutine's command.  It may only
+/// be called at the top level of the coroutine's body.
+#[cfg(feature = "coroutine")]
+pub fn cmd_yield(interp: &mut Interp, _: ContextID, argv: &[Value]) -> MoltResult {
+    check_args(1, argv, 1, 2, "?value?")?;
+
+    interp.yield_value(argv.get(1).cloned().unwrap_or_else(Value::empty))
+}
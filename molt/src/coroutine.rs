@@ -0,0 +1,85 @@
+//! Coroutines
+//!
+//! A coroutine is a command whose evaluation can be suspended by calling `yield`, and resumed
+//! later by calling the command again.  Coroutines are experimental, and are enabled by the
+//! `coroutine` feature.
+//!
+//! Molt's evaluator is recursive, and so it can't suspend an evaluation in the middle of a
+//! nested call.  Instead, a coroutine executes the body of a procedure one top-level command
+//! at a time, and `yield` may only be called as one of those commands.  A suspended
+//! coroutine saves the body's local scope and the index of the next command to execute;
+//! resuming it pushes the scope back onto the stack and continues with that command.
+//!
+//! A tail call to a procedure at the end of the body replaces the coroutine's body with the
+//! procedure's, and so a coroutine can loop by calling itself with `tailcall`.
+
+use crate::check_args;
+use crate::interp::Interp;
+use crate::molt_err;
+use crate::scope::SavedScope;
+use crate::types::*;
+use crate::value::Value;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+/// The procedure body that a coroutine is executing, and where it is in it.
+pub(crate) struct CoroutineBody {
+    /// The procedure's body script.
+    pub(crate) body: Value,
+
+    /// The namespace in which the body is executed.
+    pub(crate) ns: Rc<str>,
+
+    /// The body's local scope, while the coroutine is suspended.
+    pub(crate) scope: SavedScope,
+
+    /// The index of the next command in the body to execute.
+    pub(crate) next: usize,
+}
+
+/// How a coroutine's evaluation ended when it was resumed.
+pub(crate) enum Resumed {
+    /// The coroutine yielded the value, and can be resumed again.
+    Yielded(Value),
+
+    /// The coroutine's body returned the result, ending the coroutine.
+    Finished(MoltResult),
+}
+
+/// A coroutine's command: its body's state while it's suspended.
+pub(crate) struct Coroutine {
+    // The coroutine's body while it's suspended, or None while it's running.
+    body: RefCell<Option<CoroutineBody>>,
+}
+
+impl Coroutine {
+    /// Creates a coroutine that will execute the given body when it's first resumed.
+    pub(crate) fn new(body: CoroutineBody) -> Self {
+        Self {
+            body: RefCell::new(Some(body)),
+        }
+    }
+
+    /// Resumes the coroutine, returning the value it yields or, if it finishes, the result
+    /// of its body.  A finished coroutine's command is deleted.
+    pub(crate) fn resume(&self, interp: &mut Interp, argv: &[Value]) -> MoltResult {
+        check_args(1, argv, 1, 2, "?value?")?;
+
+        let mut body = match self.body.borrow_mut().take() {
+            Some(body) => body,
+            None => return molt_err!("coroutine \"{}\" is already running", argv[0]),
+        };
+        let value = argv.get(1).cloned().unwrap_or_else(Value::empty);
+
+        match interp.run_coroutine(&mut body, value) {
+            Resumed::Yielded(value) => {
+                *self.body.borrow_mut() = Some(body);
+                Ok(value)
+            }
+            Resumed::Finished(result) => {
+                interp.remove_coroutine(self);
+                result
+            }
+        }
+    }
+}
@@ -0,0 +1,415 @@
+//! Script Coverage
+//!
+//! When coverage is enabled by
+//! [`Interp::set_coverage`](../interp/struct.Interp.html#method.set_coverage), the
+//! interpreter records which lines of the script files evaluated by
+//! [`Interp::eval_file`](../interp/struct.Interp.html#method.eval_file) actually execute.
+//! The results are available as a [`Coverage`](struct.Coverage.html) via
+//! [`Interp::coverage`](../interp/struct.Interp.html#method.coverage); the test harness
+//! uses them to implement its `--coverage` and `--lcov` options.
+//!
+//! When a file is evaluated, it is parsed to find the lines on which its commands begin,
+//! including the commands in its multi-line braced words, e.g., procedure and test bodies;
+//! these are the file's executable lines.  When one of those braced words is later
+//! evaluated as a script, the interpreter recognizes its text and counts the lines of the
+//! commands it executes.  Scripts built at run-time don't appear in any file, and so
+//! aren't counted, nor are the commands in expressions.
+//!
+//! Multi-line braced words are presumed to be code if they parse as scripts, and so a
+//! multi-line data value, e.g., a test's expected result, may show up as unexecuted lines.
+//! Single-line braced words are as likely to be data as code, and so aren't counted as
+//! executable lines; but they share their line with the command that contains them.
+
+use crate::parser::parse_tree;
+use crate::parser::WordKind;
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::io::Write;
+use std::path::PathBuf;
+
+/// The coverage data collected by an interpreter: the executable lines of each script
+/// file it has evaluated, and the number of times each has been executed.
+#[derive(Debug, Default)]
+pub struct Coverage {
+    // The files, in the order in which they were first evaluated.
+    files: Vec<FileCoverage>,
+
+    // The scripts found in the files.
+    scripts: Vec<ScriptInfo>,
+
+    // The IDs of the scripts with each text, i.e., their indices in `scripts`.
+    script_ids: HashMap<String, Vec<usize>>,
+
+    // The scripts being evaluated, innermost last.
+    stack: Vec<ScriptFrame>,
+}
+
+/// A script found in a file.
+#[derive(Debug)]
+struct ScriptInfo {
+    // The index of the file.
+    file: usize,
+
+    // The file line for each line of the script's text.  They can differ by more than one,
+    // as the parser replaces backslash-newlines in braced words with spaces.
+    lines: Vec<usize>,
+}
+
+impl ScriptInfo {
+    /// Returns the file line for a line of the script, counting from 1.
+    fn file_line(&self, line: usize) -> usize {
+        match self.lines.get(line - 1) {
+            Some(file_line) => *file_line,
+            None => self.lines[self.lines.len() - 1] + line - self.lines.len(),
+        }
+    }
+}
+
+/// A script being evaluated.
+#[derive(Debug)]
+struct ScriptFrame {
+    // The ID of the script, if it's known.
+    script: Option<usize>,
+
+    // The file line of the command most recently executed in the script.
+    line: usize,
+}
+
+impl Coverage {
+    /// Creates an empty set of coverage data.
+    pub(crate) fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the coverage of each script file, in the order in which they were first
+    /// evaluated.
+    pub fn files(&self) -> &[FileCoverage] {
+        &self.files
+    }
+
+    /// Returns the total number of executable lines in all files.
+    pub fn lines_found(&self) -> usize {
+        self.files.iter().map(|file| file.lines_found()).sum()
+    }
+
+    /// Returns the total number of executed lines in all files.
+    pub fn lines_hit(&self) -> usize {
+        self.files.iter().map(|file| file.lines_hit()).sum()
+    }
+
+    /// Writes the coverage data in the `lcov` tracefile format, as read by `genhtml`.
+    pub fn write_lcov(&self, writer: &mut dyn Write) -> io::Result<()> {
+        writeln!(writer, "TN:")?;
+
+        for file in &self.files {
+            writeln!(writer, "SF:{}", file.path.display())?;
+            for (line, count) in &file.lines {
+                writeln!(writer, "DA:{},{}", line, count)?;
+            }
+            writeln!(writer, "LF:{}", file.lines_found())?;
+            writeln!(writer, "LH:{}", file.lines_hit())?;
+            writeln!(writer, "end_of_record")?;
+        }
+
+        Ok(())
+    }
+
+    /// Adds a script file about to be evaluated, given its name and text, finding its
+    /// executable lines and the scripts it contains.  A file that has already been added
+    /// is ignored.
+    pub(crate) fn add_file(&mut self, name: &str, text: &str) {
+        if self.files.iter().any(|file| file.name == name) {
+            return;
+        }
+
+        let index = self.files.len();
+        self.files.push(FileCoverage {
+            name: name.into(),
+            path: fs::canonicalize(name).unwrap_or_else(|_| PathBuf::from(name)),
+            lines: BTreeMap::new(),
+        });
+
+        let lines = (1..=text.matches('\n').count() + 1).collect();
+        self.add_script(index, text, lines);
+    }
+
+    /// Adds a script found in the given file, given its text and the file line for each
+    /// line of the text, along with its executable lines and any scripts in its multi-line
+    /// braced words.
+    fn add_script(&mut self, index: usize, text: &str, lines: Vec<usize>) {
+        // FIRST, ignore text that isn't a valid script, as it's probably data.
+        let tree = match parse_tree(text) {
+            Ok(tree) => tree,
+            Err(_) => return,
+        };
+
+        for cmd in tree.commands() {
+            self.files[index]
+                .lines
+                .entry(lines[cmd.line() - 1])
+                .or_insert(0);
+
+            for word in cmd.words() {
+                let word_text = word.text();
+
+                if word.kind() == WordKind::Braced && word_text.contains('\n') {
+                    let word_line = text[..word.span().start].matches('\n').count();
+                    let (body, offsets) = braced_value(&word_text[1..word_text.len() - 1]);
+                    let body_lines = offsets.iter().map(|i| lines[word_line + i]).collect();
+                    self.add_script(index, &body, body_lines);
+                }
+            }
+        }
+
+        self.script_ids
+            .entry(text.into())
+            .or_default()
+            .push(self.scripts.len());
+        self.scripts.push(ScriptInfo { file: index, lines });
+    }
+
+    /// Notes that the script with the given text is about to be evaluated.  Each call
+    /// must be paired with a call to [`leave`](#method.leave).
+    ///
+    /// If the same text appears in more than one place, it's presumed to be the first
+    /// occurrence following the command being executed in the enclosing script, e.g., the
+    /// body of a test; or else the first occurrence in the same file, e.g., the body of a
+    /// procedure defined earlier; or else the first occurrence overall.
+    pub(crate) fn enter(&mut self, text: &str) {
+        let enclosing = self
+            .stack
+            .iter()
+            .rev()
+            .find_map(|frame| frame.script.map(|id| (self.scripts[id].file, frame.line)));
+
+        let scripts = &self.scripts;
+        let script = self.script_ids.get(text).and_then(|ids| match enclosing {
+            Some((file, line)) => {
+                let mut in_file = ids.iter().filter(|id| scripts[**id].file == file);
+                in_file
+                    .clone()
+                    .find(|id| scripts[**id].lines[0] >= line)
+                    .or_else(|| in_file.next())
+                    .or_else(|| ids.first())
+                    .copied()
+            }
+            None => ids.first().copied(),
+        });
+
+        self.stack.push(ScriptFrame { script, line: 0 });
+    }
+
+    /// Stops counting commands until [`leave`](#method.leave) is called.
+    pub(crate) fn suspend(&mut self) {
+        self.stack.push(ScriptFrame {
+            script: None,
+            line: 0,
+        });
+    }
+
+    /// Notes that evaluation has returned to the enclosing script.
+    pub(crate) fn leave(&mut self) {
+        self.stack.pop();
+    }
+
+    /// Counts the execution of a command on the given line of the current script, if the
+    /// script's location is known.
+    pub(crate) fn hit(&mut self, line: usize) {
+        if let Some(frame) = self.stack.last_mut() {
+            if let Some(id) = frame.script {
+                let script = &self.scripts[id];
+                frame.line = script.file_line(line);
+                *self.files[script.file].lines.entry(frame.line).or_insert(0) += 1;
+            }
+        }
+    }
+}
+
+/// The coverage data for a single script file.
+#[derive(Debug)]
+pub struct FileCoverage {
+    // The file name as given to `eval_file`.
+    name: String,
+
+    // The absolute path of the file, if known.
+    path: PathBuf,
+
+    // The number of times each executable line has been executed.
+    lines: BTreeMap<usize, u64>,
+}
+
+impl FileCoverage {
+    /// Returns the file's name, as given to
+    /// [`Interp::eval_file`](../interp/struct.Interp.html#method.eval_file), e.g., by the
+    /// `source` command.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the number of times each executable line of the file has been executed,
+    /// in line order.  Lines are counted from 1.
+    pub fn lines(&self) -> impl Iterator<Item = (usize, u64)> + '_ {
+        self.lines.iter().map(|(line, count)| (*line, *count))
+    }
+
+    /// Returns the number of executable lines in the file.
+    pub fn lines_found(&self) -> usize {
+        self.lines.len()
+    }
+
+    /// Returns the number of executable lines in the file that have been executed.
+    pub fn lines_hit(&self) -> usize {
+        self.lines.values().filter(|count| **count > 0).count()
+    }
+}
+
+/// Returns the value of a braced word given the text between the braces, as the parser
+/// computes it: each backslash-newline and the spaces and tabs following it are replaced
+/// by a single space.  Also returns, for each line of the value, the number of newlines
+/// preceding it in the original text.
+fn braced_value(text: &str) -> (String, Vec<usize>) {
+    let mut value = String::with_capacity(text.len());
+    let mut offsets = vec![0];
+    let mut newlines = 0;
+    let mut chars = text.chars().peekable();
+
+    while let Some(ch) = chars.next() {
+        match ch {
+            '\\' => match chars.next() {
+                Some('\n') => {
+                    newlines += 1;
+                    value.push(' ');
+                    while let Some(' ') | Some('\t') = chars.peek() {
+                        chars.next();
+                    }
+                }
+                Some(next) => {
+                    value.push('\\');
+                    value.push(next);
+                }
+                None => value.push('\\'),
+            },
+            '\n' => {
+                newlines += 1;
+                offsets.push(newlines);
+                value.push(ch);
+            }
+            _ => value.push(ch),
+        }
+    }
+
+    (value, offsets)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(coverage: &Coverage, file: usize) -> Vec<(usize, u64)> {
+        coverage.files()[file].lines().collect()
+    }
+
+    #[test]
+    fn test_add_file() {
+        let mut coverage = Coverage::new();
+        coverage.add_file(
+            "x.tcl",
+            "set a 1\nproc p {} {\n    set b 2\n\n    incr b\n}\np\n",
+        );
+
+        assert_eq!(
+            lines(&coverage, 0),
+            vec![(1, 0), (2, 0), (3, 0), (5, 0), (7, 0)]
+        );
+        let ids = &coverage.script_ids["\n    set b 2\n\n    incr b\n"];
+        assert_eq!(coverage.scripts[ids[0]].lines, vec![2, 3, 4, 5, 6]);
+
+        // Single-line braced words may be data, and aren't scripts.
+        coverage.add_file("y.tcl", "set a {set b 1}\n");
+        assert_eq!(lines(&coverage, 1), vec![(1, 0)]);
+        assert!(coverage.script_ids.get("set b 1").is_none());
+    }
+
+    #[test]
+    fn test_add_file_continuations() {
+        let mut coverage = Coverage::new();
+        coverage.add_file("x.tcl", "t {\n    list \\\n        a b\n    set c 1\n}\n");
+
+        assert_eq!(lines(&coverage, 0), vec![(1, 0), (2, 0), (4, 0)]);
+
+        coverage.enter("\n    list  a b\n    set c 1\n");
+        coverage.hit(3);
+        coverage.leave();
+        assert_eq!(lines(&coverage, 0), vec![(1, 0), (2, 0), (4, 1)]);
+    }
+
+    #[test]
+    fn test_hit() {
+        let mut coverage = Coverage::new();
+        coverage.add_file("x.tcl", "set a 1\nproc p {} {\n    set b 2\n}\n");
+
+        // Unknown scripts aren't counted.
+        coverage.enter("set c 3");
+        coverage.hit(1);
+        assert_eq!(coverage.lines_hit(), 0);
+
+        coverage.enter("\n    set b 2\n");
+        coverage.hit(2);
+        coverage.hit(2);
+        coverage.suspend();
+        coverage.hit(1);
+        coverage.leave();
+        coverage.leave();
+        coverage.leave();
+
+        assert_eq!(lines(&coverage, 0), vec![(1, 0), (2, 0), (3, 2)]);
+        assert_eq!(coverage.lines_found(), 3);
+        assert_eq!(coverage.lines_hit(), 1);
+    }
+
+    #[test]
+    fn test_hit_duplicate_scripts() {
+        let mut coverage = Coverage::new();
+        let text = "t {\n    set a 1\n}\nt {\n    set a 1\n}\n";
+        coverage.add_file("x.tcl", text);
+
+        // The body is presumed to be the one following the command being executed.
+        coverage.enter(text);
+        coverage.hit(4);
+        coverage.enter("\n    set a 1\n");
+        coverage.hit(2);
+        coverage.leave();
+        coverage.leave();
+
+        assert_eq!(lines(&coverage, 0), vec![(1, 0), (2, 0), (4, 1), (5, 1)]);
+    }
+
+    #[test]
+    fn test_braced_value() {
+        assert_eq!(braced_value("a b"), ("a b".into(), vec![0]));
+        assert_eq!(braced_value("a\nb\n"), ("a\nb\n".into(), vec![0, 1, 2]));
+        assert_eq!(
+            braced_value("a \\\n\t  b\nc \\x"),
+            ("a  b\nc \\x".into(), vec![0, 2])
+        );
+    }
+
+    #[test]
+    fn test_write_lcov() {
+        let mut coverage = Coverage::new();
+        coverage.add_file("x.tcl", "set a 1\nset b 2\n");
+        coverage.enter("set a 1\nset b 2\n");
+        coverage.hit(1);
+
+        let mut output = Vec::new();
+        coverage.write_lcov(&mut output).unwrap();
+        let output = String::from_utf8(output).unwrap();
+
+        assert_eq!(
+            output,
+            "TN:\nSF:x.tcl\nDA:1,1\nDA:2,0\nLF:2\nLH:1\nend_of_record\n"
+        );
+    }
+}
@@ -5,6 +5,7 @@
 //! * Consider delegating skip_while() to iter::skip_while(), and replacing the
 //!   "skip_sequence" methods with some useful predicate functions.
 
+use crate::parser::IncompleteKind;
 use crate::tokenizer::Tokenizer;
 
 /// A struct that holds the parsing context: the iterator over the input string, and
@@ -19,17 +20,27 @@ pub struct EvalPtr<'a> {
     // The term_char: the character that ends the script, None or Some<']'>
     term_char: Option<char>,
 
-    // Whether we're evaluating commands or just checking for completeness.
-    no_eval: bool,
+    // The kind and offset of the construct that was left unterminated, if parsing failed
+    // because the input ended too soon.
+    unclosed: Option<(IncompleteKind, usize)>,
+
+    // The current nesting depth of command substitutions and other recursive constructs.
+    depth: usize,
 }
 
+/// The maximum nesting depth of command substitutions, array indices, and `{*}` prefixes
+/// in parsed input.  The parser recurses at each level, so the limit keeps pathological
+/// input from overflowing the Rust stack.
+const MAX_DEPTH: usize = 256;
+
 impl<'a> EvalPtr<'a> {
     pub fn new(input: &'a str) -> Self {
         Self {
             tok: Tokenizer::new(input),
             bracket_term: false,
             term_char: None,
-            no_eval: false,
+            unclosed: None,
+            depth: 0,
         }
     }
 
@@ -44,7 +55,8 @@ impl<'a> EvalPtr<'a> {
             tok: ptr.clone(),
             bracket_term: false,
             term_char: None,
-            no_eval: false,
+            unclosed: None,
+            depth: 0,
         }
     }
 
@@ -56,6 +68,34 @@ impl<'a> EvalPtr<'a> {
     //-----------------------------------------------------------------------
     // Configuration
 
+    /// Records that the construct of the given kind, opened at the given mark, is
+    /// unterminated.
+    pub fn set_unclosed(&mut self, kind: IncompleteKind, mark: usize) {
+        self.unclosed = Some((kind, mark));
+    }
+
+    /// Returns the kind and mark of the unterminated construct, if any.
+    pub fn unclosed(&self) -> Option<(IncompleteKind, usize)> {
+        self.unclosed
+    }
+
+    /// Enters a nested construct, returning false if the input is already nested as
+    /// deeply as the parser allows.  A successful call must be balanced by a call to
+    /// `leave`.
+    pub fn enter(&mut self) -> bool {
+        if self.depth < MAX_DEPTH {
+            self.depth += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Leaves a nested construct entered by `enter`.
+    pub fn leave(&mut self) {
+        self.depth -= 1;
+    }
+
     /// If true, the script ends with a right-bracket, ']'; otherwise it ends
     /// at the end of the input.
     pub fn set_bracket_term(&mut self, flag: bool) {
@@ -69,17 +109,6 @@ impl<'a> EvalPtr<'a> {
         self.bracket_term
     }
 
-    // Sets/clears "no eval" mode.  In "no eval" mode we scan the input for
-    // validity, e.g., no unmatched braces, brackets, or quotes.
-    pub fn set_no_eval(&mut self, flag: bool) {
-        self.no_eval = flag;
-    }
-
-    // Returns whether or not we are scanning the input for completeness.
-    pub fn is_no_eval(&self) -> bool {
-        self.no_eval
-    }
-
     //-----------------------------------------------------------------------
     // Tokenizer methods
 
@@ -121,6 +150,11 @@ impl<'a> EvalPtr<'a> {
         self.tok.mark()
     }
 
+    /// Returns the line number of the next character, counting from 1.
+    pub fn line(&self) -> usize {
+        self.tok.line()
+    }
+
     /// Get the token between the mark and the index.  Returns "" if we're at the
     /// end or mark == index.
     pub fn token(&self, mark: usize) -> &str {
@@ -160,27 +194,41 @@ impl<'a> EvalPtr<'a> {
     }
 
     /// Is the current character is a valid whitespace character, excluding newlines?
+    /// A backslash-newline counts as whitespace, as it's replaced by a single space.
     pub fn next_is_line_white(&mut self) -> bool {
         match self.tok.peek() {
+            Some('\\') => self.next_is_backslash_newline(),
             Some(c) => c.is_whitespace() && c != '\n',
             None => false,
         }
     }
 
-    /// Is the current character a valid variable name character?
+    /// Is the current character a backslash followed by a newline, i.e., a line
+    /// continuation?
+    pub fn next_is_backslash_newline(&self) -> bool {
+        self.tok.as_str().starts_with("\\\n")
+    }
+
+    /// Is the current character a valid variable name character, or the start of a
+    /// namespace separator?
     pub fn next_is_varname_char(&mut self) -> bool {
         match self.tok.peek() {
-            Some(c) => c.is_alphanumeric() || c == '_',
+            Some(c) => c.is_alphanumeric() || c == '_' || self.next_is_namespace_sep(),
             None => false,
         }
     }
 
+    /// Is the input at a namespace separator, "::"?
+    pub fn next_is_namespace_sep(&self) -> bool {
+        self.tok.as_str().starts_with("::")
+    }
+
     /// Skips past any whitespace at the current point, *including* newlines.
     /// When this is complete we will be at the end of the script or on a non-white-space
     /// character.
     pub fn skip_block_white(&mut self) {
-        while !self.at_end() && self.next_is_block_white() {
-            self.tok.next();
+        while !self.at_end() && (self.next_is_block_white() || self.next_is_backslash_newline()) {
+            self.skip_white_char();
         }
     }
 
@@ -189,8 +237,16 @@ impl<'a> EvalPtr<'a> {
     /// current command, or on a non-white-space character.
     pub fn skip_line_white(&mut self) {
         while !self.at_end() && self.next_is_line_white() {
+            self.skip_white_char();
+        }
+    }
+
+    /// Skips a single whitespace character, or a backslash-newline.
+    fn skip_white_char(&mut self) {
+        if self.next_is('\\') {
             self.tok.next();
         }
+        self.tok.next();
     }
 
     /// Skips past a comment if there is one, including any terminating newline.
@@ -305,6 +361,10 @@ mod tests {
         let mut ctx = EvalPtr::new(" \n 123");
         ctx.skip_block_white();
         assert!(ctx.next_is('1'));
+
+        let mut ctx = EvalPtr::new(" \\\n\n 123");
+        ctx.skip_block_white();
+        assert!(ctx.next_is('1'));
     }
 
     #[test]
@@ -317,6 +377,12 @@ mod tests {
 
         let mut ctx = EvalPtr::new("\n123");
         assert!(!ctx.next_is_line_white());
+
+        let mut ctx = EvalPtr::new("\\\n123");
+        assert!(ctx.next_is_line_white());
+
+        let mut ctx = EvalPtr::new("\\n123");
+        assert!(!ctx.next_is_line_white());
     }
 
     #[test]
@@ -332,6 +398,10 @@ mod tests {
         let mut ctx = EvalPtr::new(" \n 123");
         ctx.skip_line_white();
         assert!(ctx.next_is('\n'));
+
+        let mut ctx = EvalPtr::new(" \\\n  123");
+        ctx.skip_line_white();
+        assert!(ctx.next_is('1'));
     }
 
     #[test]
@@ -6,6 +6,7 @@
 use crate::eval_ptr::EvalPtr;
 use crate::interp::Interp;
 use crate::list;
+use crate::parser::Script;
 use crate::parser::Word;
 use crate::tokenizer::Tokenizer;
 use crate::*;
@@ -32,7 +33,7 @@ pub(crate) enum Type {
 ///
 /// I could have used a union to save space, but we don't keep large numbers of these
 /// around.
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone)]
 pub(crate) struct Datum {
     vtype: Type,
     int: MoltInt,
@@ -104,6 +105,7 @@ enum ArgType {
 
 type MathFunc = fn(args: &[Datum; MAX_MATH_ARGS]) -> DatumResult;
 
+#[derive(Debug)]
 struct BuiltinFunc {
     name: &'static str,
     num_args: usize,
@@ -139,15 +141,81 @@ const FUNC_TABLE: [BuiltinFunc; 4] = [
 ];
 
 //------------------------------------------------------------------------------------------------
-// Parsing Context
+// Parsed Expressions
 
-/// Context for expr parsing
+/// An expression token, as scanned from the expression's string.
+#[derive(Debug)]
+enum Token {
+    /// An operator or punctuation mark; see the token constants.
+    Op(i32),
+
+    /// A numeric literal or boolean constant.
+    Literal(Datum),
+
+    /// A braced string, converted to a number if it looks like one.
+    Braced(DatumResult),
+
+    /// A variable reference.
+    Var(Word),
+
+    /// An interpolated script, and whether it had its close-bracket.
+    Script(Script, bool),
+
+    /// A double-quoted string.
+    Quoted(Word),
+
+    /// The name of a math function.
+    Func(&'static BuiltinFunc),
+
+    /// A lexical error, reported only if evaluation reaches it.
+    Error(Exception),
+}
+
+/// A parsed expression: the tokens scanned from the expression's string.  The
+/// interpreter caches the parsed expression in the expression's `Value`, so that an
+/// expression evaluated repeatedly, e.g., a loop condition, is only scanned once.
+/// Substitutions are parsed but not evaluated; they are evaluated each time the
+/// expression is.
+#[derive(Debug)]
+pub(crate) struct ParsedExpr {
+    tokens: Vec<Token>,
+}
+
+impl ParsedExpr {
+    /// Scans the expression into tokens.  Scanning stops at the end of the expression or
+    /// at the first lexical error.
+    pub(crate) fn parse(expr: &str) -> Self {
+        let mut p = Tokenizer::new(expr);
+        let mut tokens = Vec::new();
+
+        loop {
+            let token = match expr_scan(&mut p) {
+                Ok(token) => token,
+                Err(exception) => Token::Error(exception),
+            };
+            let done = matches!(token, Token::Op(END) | Token::Error(_));
+
+            tokens.push(token);
+
+            if done {
+                break;
+            }
+        }
+
+        Self { tokens }
+    }
+}
+
+/// Context for expr evaluation
 struct ExprInfo<'a> {
     // The full expr.
-    original_expr: String,
+    original_expr: &'a str,
+
+    // The parsed expression's tokens.
+    tokens: &'a [Token],
 
-    // The input iterator, e.g., the pointer to the next character.
-    expr: Tokenizer<'a>,
+    // The index of the next token.
+    next: usize,
 
     // Last token's type; see constants
     token: i32,
@@ -157,10 +225,11 @@ struct ExprInfo<'a> {
 }
 
 impl<'a> ExprInfo<'a> {
-    fn new(expr: &'a str) -> Self {
+    fn new(expr: &'a str, parsed: &'a ParsedExpr) -> Self {
         Self {
-            original_expr: expr.to_string(),
-            expr: Tokenizer::new(expr),
+            original_expr: expr,
+            tokens: &parsed.tokens,
+            next: 0,
             token: -1,
             no_eval: 0,
         }
@@ -248,7 +317,7 @@ const OP_STRINGS: [&str; 36] = [
 
 /// Evaluates an expression and returns its value.
 pub fn expr(interp: &mut Interp, expr: &Value) -> MoltResult {
-    let value = expr_top_level(interp, expr.as_str())?;
+    let value = expr_top_level(interp, expr)?;
 
     match value.vtype {
         Type::Int => molt_ok!(Value::from(value.int)),
@@ -257,14 +326,58 @@ pub fn expr(interp: &mut Interp, expr: &Value) -> MoltResult {
     }
 }
 
+/// Evaluates an expression and returns its value as a boolean, without converting the
+/// result to a `Value` first.  Strings must be valid boolean strings.
+pub(crate) fn expr_as_bool(interp: &mut Interp, expr: &Value) -> Result<bool, Exception> {
+    let value = expr_top_level(interp, expr)?;
+
+    match value.vtype {
+        Type::Int => Ok(value.int != 0),
+        Type::Float => Ok(value.flt != 0.0),
+        Type::String => Value::get_bool(&value.str),
+    }
+}
+
+/// Evaluates an expression and returns its value as an integer, without converting an
+/// integer result to a `Value` first.
+pub(crate) fn expr_as_int(interp: &mut Interp, expr: &Value) -> Result<MoltInt, Exception> {
+    let value = expr_top_level(interp, expr)?;
+
+    match value.vtype {
+        Type::Int => Ok(value.int),
+        // A float converts only if its string rep is an integer, e.g., "3" for 3.0.
+        Type::Float => Value::from(value.flt).as_int(),
+        Type::String => Value::get_int(&value.str),
+    }
+}
+
+/// Evaluates an expression and returns its value as a float, without converting a
+/// float result to a `Value` first.
+pub(crate) fn expr_as_float(interp: &mut Interp, expr: &Value) -> Result<MoltFloat, Exception> {
+    let value = expr_top_level(interp, expr)?;
+
+    match value.vtype {
+        Type::Int => Ok(value.int as MoltFloat),
+        Type::Float => Ok(value.flt),
+        Type::String => Value::get_float(&value.str),
+    }
+}
+
 //------------------------------------------------------------------------------------------------
 // Expression Internals
 
 /// Provides top-level functionality shared by molt_expr_string, molt_expr_int, etc.
-fn expr_top_level<'a>(interp: &mut Interp, string: &'a str) -> DatumResult {
-    let info = &mut ExprInfo::new(string);
-
+fn expr_top_level(interp: &mut Interp, expr: &Value) -> DatumResult {
+    let string = expr.as_str();
+    let parsed = expr.as_expr();
+    let info = &mut ExprInfo::new(string, &parsed);
+
+    // The line numbers of the commands in the expression's command substitutions are
+    // relative to the expression rather than to the enclosing script, and so the commands
+    // aren't counted for coverage.
+    interp.suspend_coverage();
     let result = expr_get_value(interp, info, -1);
+    interp.resume_coverage();
 
     match result {
         Ok(value) => {
@@ -290,10 +403,33 @@ fn expr_top_level<'a>(interp: &mut Interp, string: &'a str) -> DatumResult {
 /// The `prec` is a precedence value; treat any unparenthesized operator
 /// with precedence less than or equal to `prec` as the end of the
 /// expression.
+fn expr_get_value(interp: &mut Interp, info: &mut ExprInfo, prec: i32) -> DatumResult {
+    expr_nested(interp, info, |interp, info| {
+        expr_get_subexpr(interp, info, prec)
+    })
+}
+
+/// Calls the parsing function as a nested level of the expression.  Parsing recurses
+/// for each subexpression, so each level counts against the interpreter's recursion
+/// limit; otherwise a deeply nested expression could overflow the Rust stack.
+fn expr_nested<F>(interp: &mut Interp, info: &mut ExprInfo, parser: F) -> DatumResult
+where
+    F: FnOnce(&mut Interp, &mut ExprInfo) -> DatumResult,
+{
+    if !interp.enter_level() {
+        return molt_err!("too many nested calls to Interp::eval (infinite loop?)");
+    }
+
+    let result = parser(interp, info);
+    interp.leave_level();
+    result
+}
+
+/// Does the work of `expr_get_value`.
 #[allow(clippy::collapsible_if)]
 #[allow(clippy::cognitive_complexity)]
 #[allow(clippy::float_cmp)]
-fn expr_get_value<'a>(interp: &mut Interp, info: &'a mut ExprInfo, prec: i32) -> DatumResult {
+fn expr_get_subexpr(interp: &mut Interp, info: &mut ExprInfo, prec: i32) -> DatumResult {
     // There are two phases to this procedure.  First, pick off an initial value.
     // Then, parse (binary operator, value) pairs until done.
     let mut got_op = false;
@@ -326,58 +462,7 @@ fn expr_get_value<'a>(interp: &mut Interp, info: &'a mut ExprInfo, prec: i32) ->
             value = expr_get_value(interp, info, PREC_TABLE[info.token as usize])?;
 
             if info.no_eval == 0 {
-                match operator {
-                    UNARY_MINUS => match value.vtype {
-                        Type::Int => {
-                            value.int = -value.int;
-                        }
-                        Type::Float => {
-                            value.flt = -value.flt;
-                        }
-                        _ => {
-                            return illegal_type(value.vtype, operator);
-                        }
-                    },
-                    UNARY_PLUS => {
-                        if !value.is_numeric() {
-                            return illegal_type(value.vtype, operator);
-                        }
-                    }
-                    NOT => {
-                        match value.vtype {
-                            Type::Int => {
-                                // NOTE: Tcl uses !int here, but in Rust !int_value is a bitwise
-                                // operator, not a logical one.
-                                if value.int == 0 {
-                                    value.int = 1;
-                                } else {
-                                    value.int = 0;
-                                }
-                            }
-                            Type::Float => {
-                                if value.flt == 0.0 {
-                                    value = Datum::int(1);
-                                } else {
-                                    value = Datum::int(0);
-                                }
-                            }
-                            _ => {
-                                return illegal_type(value.vtype, operator);
-                            }
-                        }
-                    }
-                    BIT_NOT => {
-                        if let Type::Int = value.vtype {
-                            // Note: in Rust, unlike C, !int_value is a bitwise operator.
-                            value.int = !value.int;
-                        } else {
-                            return illegal_type(value.vtype, operator);
-                        }
-                    }
-                    _ => {
-                        return molt_err!("unknown unary op: \"{}\"", operator);
-                    }
-                }
+                value = expr_unary_op(operator, value)?;
             }
             got_op = true;
         } else if info.token != VALUE {
@@ -447,34 +532,16 @@ fn expr_get_value<'a>(interp: &mut Interp, info: &'a mut ExprInfo, prec: i32) ->
 
                 // Go on to the next operator.
                 continue;
-            } else if operator == QUESTY {
-                // Special note: ?: operators must associate right to left.  To make
-                // this happen, use a precedence one lower than QUESTY when calling
-                // expr_get_value recursively.
-                if value.int != 0 {
-                    value = expr_get_value(interp, info, PREC_TABLE[QUESTY as usize] - 1)?;
-
-                    if info.token != COLON {
-                        return syntax_error(info);
-                    }
-
-                    info.no_eval += 1;
-                    value2 = expr_get_value(interp, info, PREC_TABLE[QUESTY as usize] - 1)?;
-                    info.no_eval -= 1;
-                } else {
-                    info.no_eval += 1;
-                    value2 = expr_get_value(interp, info, PREC_TABLE[QUESTY as usize] - 1)?;
-                    info.no_eval -= 1;
-
-                    if info.token != COLON {
-                        return syntax_error(info);
-                    }
-
-                    value = expr_get_value(interp, info, PREC_TABLE[QUESTY as usize] - 1)?;
-                }
-            } else {
-                value2 = expr_get_value(interp, info, PREC_TABLE[operator as usize])?;
             }
+        }
+
+        if operator == QUESTY {
+            // This is a second level of recursion, so it counts as another level.
+            let condition = value.int != 0;
+            value = expr_nested(interp, info, |interp, info| {
+                expr_get_choice(interp, info, condition)
+            })?;
+            value2 = Datum::none();
         } else {
             value2 = expr_get_value(interp, info, PREC_TABLE[operator as usize])?;
         }
@@ -492,338 +559,436 @@ fn expr_get_value<'a>(interp: &mut Interp, info: &'a mut ExprInfo, prec: i32) ->
             continue;
         }
 
-        // At this point we've got two values and an operator.  Check to make sure that the
-        // particular data types are appropriate for the particular operator, and perform
-        // type conversion if necessary.
+        value = expr_binary_op(operator, value, value2)?;
+    }
+}
 
-        match operator {
-            // For the operators below, no strings are allowed and ints get converted to
-            // floats if necessary.
-            MULT | DIVIDE | PLUS | MINUS => {
-                if value.vtype == Type::String || value2.vtype == Type::String {
-                    return illegal_type(Type::String, operator);
-                }
+/// Parses the two branches of a ?: operator, evaluating only the one selected by the
+/// condition, and returns its value.  This is kept out of `expr_get_subexpr`, which
+/// recurses for each subexpression, to keep its stack frame small.
+fn expr_get_choice(interp: &mut Interp, info: &mut ExprInfo, condition: bool) -> DatumResult {
+    // Special note: ?: operators must associate right to left.  To make
+    // this happen, use a precedence one lower than QUESTY when calling
+    // expr_get_value recursively.
+    let value;
 
-                if value.vtype == Type::Float {
-                    if value2.vtype == Type::Int {
-                        value2.flt = value2.int as MoltFloat;
-                        value2.vtype = Type::Float;
+    if condition {
+        value = expr_get_value(interp, info, PREC_TABLE[QUESTY as usize] - 1)?;
+
+        if info.token != COLON {
+            return syntax_error(info);
+        }
+
+        info.no_eval += 1;
+        let _ = expr_get_value(interp, info, PREC_TABLE[QUESTY as usize] - 1)?;
+        info.no_eval -= 1;
+    } else {
+        info.no_eval += 1;
+        let _ = expr_get_value(interp, info, PREC_TABLE[QUESTY as usize] - 1)?;
+        info.no_eval -= 1;
+
+        if info.token != COLON {
+            return syntax_error(info);
+        }
+
+        value = expr_get_value(interp, info, PREC_TABLE[QUESTY as usize] - 1)?;
+    }
+
+    Ok(value)
+}
+
+/// Applies a unary operator to its operand.
+fn expr_unary_op(operator: i32, mut value: Datum) -> DatumResult {
+    match operator {
+        UNARY_MINUS => match value.vtype {
+            Type::Int => {
+                value.int = -value.int;
+            }
+            Type::Float => {
+                value.flt = -value.flt;
+            }
+            _ => {
+                return illegal_type(value.vtype, operator);
+            }
+        },
+        UNARY_PLUS => {
+            if !value.is_numeric() {
+                return illegal_type(value.vtype, operator);
+            }
+        }
+        NOT => {
+            match value.vtype {
+                Type::Int => {
+                    // NOTE: Tcl uses !int here, but in Rust !int_value is a bitwise
+                    // operator, not a logical one.
+                    if value.int == 0 {
+                        value.int = 1;
+                    } else {
+                        value.int = 0;
                     }
-                } else if value2.vtype == Type::Float {
-                    if value.vtype == Type::Int {
-                        value.flt = value.int as MoltFloat;
-                        value.vtype = Type::Float;
+                }
+                Type::Float => {
+                    if value.flt == 0.0 {
+                        value = Datum::int(1);
+                    } else {
+                        value = Datum::int(0);
                     }
                 }
-            }
-
-            // For the operators below, only integers are allowed.
-            MOD | LEFT_SHIFT | RIGHT_SHIFT | BIT_AND | BIT_XOR | BIT_OR => {
-                if value.vtype != Type::Int {
+                _ => {
                     return illegal_type(value.vtype, operator);
-                } else if value2.vtype != Type::Int {
-                    return illegal_type(value2.vtype, operator);
                 }
             }
+        }
+        BIT_NOT => {
+            if let Type::Int = value.vtype {
+                // Note: in Rust, unlike C, !int_value is a bitwise operator.
+                value.int = !value.int;
+            } else {
+                return illegal_type(value.vtype, operator);
+            }
+        }
+        _ => {
+            return molt_err!("unknown unary op: \"{}\"", operator);
+        }
+    }
 
-            // For the operators below, any type is allowed, but the operators must have
-            // the same type.
-            LESS | GREATER | LEQ | GEQ | EQUAL | NEQ => {
-                if value.vtype == Type::String {
-                    if value2.vtype != Type::String {
-                        value2 = expr_as_str(value2);
-                    }
-                } else if value2.vtype == Type::String {
-                    if value.vtype != Type::String {
-                        value = expr_as_str(value);
-                    }
-                } else if value.vtype == Type::Float {
-                    if value2.vtype == Type::Int {
-                        value2 = Datum::float(value2.int as MoltFloat);
-                    }
-                } else if value2.vtype == Type::Float {
-                    if value.vtype == Type::Int {
-                        value = Datum::float(value.int as MoltFloat);
-                    }
+    Ok(value)
+}
+
+/// Applies a binary operator to its operands.  This is kept out of `expr_get_subexpr`,
+/// which recurses for each subexpression, to keep its stack frame small.
+#[allow(clippy::collapsible_if)]
+#[allow(clippy::cognitive_complexity)]
+#[allow(clippy::float_cmp)]
+fn expr_binary_op(operator: i32, mut value: Datum, mut value2: Datum) -> DatumResult {
+    // At this point we've got two values and an operator.  Check to make sure that the
+    // particular data types are appropriate for the particular operator, and perform
+    // type conversion if necessary.
+
+    match operator {
+        // For the operators below, no strings are allowed and ints get converted to
+        // floats if necessary.
+        MULT | DIVIDE | PLUS | MINUS => {
+            if value.vtype == Type::String || value2.vtype == Type::String {
+                return illegal_type(Type::String, operator);
+            }
+
+            if value.vtype == Type::Float {
+                if value2.vtype == Type::Int {
+                    value2.flt = value2.int as MoltFloat;
+                    value2.vtype = Type::Float;
+                }
+            } else if value2.vtype == Type::Float {
+                if value.vtype == Type::Int {
+                    value.flt = value.int as MoltFloat;
+                    value.vtype = Type::Float;
                 }
             }
+        }
 
-            // For the operators below, everything's treated as a string.
-            // For IN and NI, the second value is a list, but we'll parse it as a list
-            // as part of evaluation.
-            STRING_EQ | STRING_NE | IN | NI => {
+        // For the operators below, only integers are allowed.
+        MOD | LEFT_SHIFT | RIGHT_SHIFT | BIT_AND | BIT_XOR | BIT_OR => {
+            if value.vtype != Type::Int {
+                return illegal_type(value.vtype, operator);
+            } else if value2.vtype != Type::Int {
+                return illegal_type(value2.vtype, operator);
+            }
+        }
+
+        // For the operators below, any type is allowed, but the operators must have
+        // the same type.
+        LESS | GREATER | LEQ | GEQ | EQUAL | NEQ => {
+            if value.vtype == Type::String {
+                if value2.vtype != Type::String {
+                    value2 = expr_as_str(value2);
+                }
+            } else if value2.vtype == Type::String {
                 if value.vtype != Type::String {
                     value = expr_as_str(value);
                 }
-                if value2.vtype != Type::String {
-                    value2 = expr_as_str(value2);
+            } else if value.vtype == Type::Float {
+                if value2.vtype == Type::Int {
+                    value2 = Datum::float(value2.int as MoltFloat);
+                }
+            } else if value2.vtype == Type::Float {
+                if value.vtype == Type::Int {
+                    value = Datum::float(value.int as MoltFloat);
                 }
             }
+        }
 
-            // For the operators below, no strings are allowed, but no int->float conversions
-            // are performed.
-            AND | OR => {
-                if value.vtype == Type::String {
-                    return illegal_type(value.vtype, operator);
-                }
-                if value2.vtype == Type::String {
-                    return illegal_type(value2.vtype, operator);
-                }
+        // For the operators below, everything's treated as a string.
+        // For IN and NI, the second value is a list, but we'll parse it as a list
+        // as part of evaluation.
+        STRING_EQ | STRING_NE | IN | NI => {
+            if value.vtype != Type::String {
+                value = expr_as_str(value);
             }
+            if value2.vtype != Type::String {
+                value2 = expr_as_str(value2);
+            }
+        }
 
-            // For the operators below, type and conversions are irrelevant: they're
-            // handled elsewhere.
-            QUESTY | COLON => {
-                // Nothing to do
+        // For the operators below, no strings are allowed, but no int->float conversions
+        // are performed.
+        AND | OR => {
+            if value.vtype == Type::String {
+                return illegal_type(value.vtype, operator);
+            }
+            if value2.vtype == Type::String {
+                return illegal_type(value2.vtype, operator);
             }
+        }
 
-            _ => return molt_err!("unknown operator in expression"),
+        // For the operators below, type and conversions are irrelevant: they're
+        // handled elsewhere.
+        QUESTY | COLON => {
+            // Nothing to do
         }
 
-        // Carry out the function of the specified operator.
-        match operator {
-            MULT => {
-                if value.vtype == Type::Int {
-                    // value.int *= value2.int
-                    if let Some(int) = value.int.checked_mul(value2.int) {
-                        value.int = int;
-                    } else {
-                        return molt_err!("integer overflow");
-                    }
-                } else {
-                    value.flt *= value2.flt;
-                }
-            }
-            DIVIDE => {
-                if value.vtype == Type::Int {
-                    if value2.int == 0 {
-                        return molt_err!("divide by zero");
-                    }
+        _ => return molt_err!("unknown operator in expression"),
+    }
 
-                    if let Some(int) = value.int.checked_div(value2.int) {
-                        value.int = int;
-                    } else {
-                        return molt_err!("integer overflow");
-                    }
+    // Carry out the function of the specified operator.
+    match operator {
+        MULT => {
+            if value.vtype == Type::Int {
+                // value.int *= value2.int
+                if let Some(int) = value.int.checked_mul(value2.int) {
+                    value.int = int;
                 } else {
-                    if value2.flt == 0.0 {
-                        // TODO: return Inf or -Inf?  Waiting for response from KBK
-                        return molt_err!("divide by zero");
-                    }
-                    value.flt /= value2.flt;
+                    return molt_err!("integer overflow");
                 }
+            } else {
+                value.flt *= value2.flt;
             }
-            MOD => {
-                assert!(value.vtype == Type::Int);
-
+        }
+        DIVIDE => {
+            if value.vtype == Type::Int {
                 if value2.int == 0 {
                     return molt_err!("divide by zero");
                 }
 
-                if let Some(int) = value.int.checked_rem(value2.int) {
+                if let Some(int) = value.int.checked_div(value2.int) {
                     value.int = int;
                 } else {
                     return molt_err!("integer overflow");
                 }
-            }
-            PLUS => {
-                if value.vtype == Type::Int {
-                    // value.int += value2.int;
-                    if let Some(int) = value.int.checked_add(value2.int) {
-                        value.int = int;
-                    } else {
-                        return molt_err!("integer overflow");
-                    }
-                } else {
-                    value.flt += value2.flt;
+            } else {
+                if value2.flt == 0.0 {
+                    // TODO: return Inf or -Inf?  Waiting for response from KBK
+                    return molt_err!("divide by zero");
                 }
+                value.flt /= value2.flt;
             }
-            MINUS => {
-                if value.vtype == Type::Int {
-                    // value.int -= value2.int;
-                    if let Some(int) = value.int.checked_sub(value2.int) {
-                        value.int = int;
-                    } else {
-                        return molt_err!("integer overflow");
-                    }
-                } else {
-                    value.flt -= value2.flt;
-                }
+        }
+        MOD => {
+            assert!(value.vtype == Type::Int);
+
+            if value2.int == 0 {
+                return molt_err!("divide by zero");
             }
-            LEFT_SHIFT => {
-                // TODO: Use checked_shl
-                value.int <<= value2.int;
+
+            if let Some(int) = value.int.checked_rem(value2.int) {
+                value.int = int;
+            } else {
+                return molt_err!("integer overflow");
             }
-            RIGHT_SHIFT => {
-                // The following code is a bit tricky:  it ensures that
-                // right shifts propagate the sign bit even on machines
-                // where ">>" won't do it by default.
-                // WHD: Not sure if this is an issue in Rust.
-
-                // TODO: Use checked_shr
-                if value.int < 0 {
-                    value.int = !((!value.int) >> value2.int)
+        }
+        PLUS => {
+            if value.vtype == Type::Int {
+                // value.int += value2.int;
+                if let Some(int) = value.int.checked_add(value2.int) {
+                    value.int = int;
                 } else {
-                    value.int >>= value2.int;
+                    return molt_err!("integer overflow");
                 }
+            } else {
+                value.flt += value2.flt;
             }
-            LESS => {
-                let flag = match value.vtype {
-                    Type::Int => value.int < value2.int,
-                    Type::Float => value.flt < value2.flt,
-                    Type::String => value.str < value2.str,
-                };
-
-                value = if flag { Datum::int(1) } else { Datum::int(0) };
-            }
-            GREATER => {
-                let flag = match value.vtype {
-                    Type::Int => value.int > value2.int,
-                    Type::Float => value.flt > value2.flt,
-                    Type::String => value.str > value2.str,
-                };
-
-                value = if flag { Datum::int(1) } else { Datum::int(0) };
-            }
-            LEQ => {
-                let flag = match value.vtype {
-                    Type::Int => value.int <= value2.int,
-                    Type::Float => value.flt <= value2.flt,
-                    Type::String => value.str <= value2.str,
-                };
-
-                value = if flag { Datum::int(1) } else { Datum::int(0) };
-            }
-            GEQ => {
-                let flag = match value.vtype {
-                    Type::Int => value.int >= value2.int,
-                    Type::Float => value.flt >= value2.flt,
-                    Type::String => value.str >= value2.str,
-                };
-
-                value = if flag { Datum::int(1) } else { Datum::int(0) };
-            }
-            EQUAL => {
-                // NOTE: comparing floats using == is dangerous; but Tcl leaves that to the
-                // TCL programmer.
-                let flag = match value.vtype {
-                    Type::Int => value.int == value2.int,
-                    Type::Float => value.flt == value2.flt,
-                    Type::String => value.str == value2.str,
-                };
-
-                value = if flag { Datum::int(1) } else { Datum::int(0) };
-            }
-            NEQ => {
-                // NOTE: comparing floats using == is dangerous; but Tcl leaves that to the
-                // TCL programmer.
-                let flag = match value.vtype {
-                    Type::Int => value.int != value2.int,
-                    Type::Float => value.flt != value2.flt,
-                    Type::String => value.str != value2.str,
-                };
-
-                value = if flag { Datum::int(1) } else { Datum::int(0) };
-            }
-            STRING_EQ => {
-                value = if value.str == value2.str {
-                    Datum::int(1)
-                } else {
-                    Datum::int(0)
-                };
-            }
-            STRING_NE => {
-                value = if value.str != value2.str {
-                    Datum::int(1)
-                } else {
-                    Datum::int(0)
-                };
-            }
-            IN => {
-                let list = list::get_list(&value2.str)?;
-                // TODO: Need a better MoltList contains() method.
-                value = if list.contains(&Value::from(&value.str)) {
-                    Datum::int(1)
-                } else {
-                    Datum::int(0)
-                };
-            }
-            NI => {
-                let list = list::get_list(&value2.str)?;
-                // TODO: Need a better MoltList contains() method.
-                value = if list.contains(&Value::from(&value.str)) {
-                    Datum::int(0)
+        }
+        MINUS => {
+            if value.vtype == Type::Int {
+                // value.int -= value2.int;
+                if let Some(int) = value.int.checked_sub(value2.int) {
+                    value.int = int;
                 } else {
-                    Datum::int(1)
-                };
-            }
-            BIT_AND => {
-                value.int &= value2.int;
-            }
-            BIT_XOR => {
-                value.int ^= value2.int;
+                    return molt_err!("integer overflow");
+                }
+            } else {
+                value.flt -= value2.flt;
             }
-            BIT_OR => {
-                value.int |= value2.int;
+        }
+        LEFT_SHIFT => {
+            // TODO: Use checked_shl
+            value.int <<= value2.int;
+        }
+        RIGHT_SHIFT => {
+            // The following code is a bit tricky:  it ensures that
+            // right shifts propagate the sign bit even on machines
+            // where ">>" won't do it by default.
+            // WHD: Not sure if this is an issue in Rust.
+
+            // TODO: Use checked_shr
+            if value.int < 0 {
+                value.int = !((!value.int) >> value2.int)
+            } else {
+                value.int >>= value2.int;
             }
+        }
+        LESS => {
+            let flag = match value.vtype {
+                Type::Int => value.int < value2.int,
+                Type::Float => value.flt < value2.flt,
+                Type::String => value.str < value2.str,
+            };
+
+            value = if flag { Datum::int(1) } else { Datum::int(0) };
+        }
+        GREATER => {
+            let flag = match value.vtype {
+                Type::Int => value.int > value2.int,
+                Type::Float => value.flt > value2.flt,
+                Type::String => value.str > value2.str,
+            };
+
+            value = if flag { Datum::int(1) } else { Datum::int(0) };
+        }
+        LEQ => {
+            let flag = match value.vtype {
+                Type::Int => value.int <= value2.int,
+                Type::Float => value.flt <= value2.flt,
+                Type::String => value.str <= value2.str,
+            };
+
+            value = if flag { Datum::int(1) } else { Datum::int(0) };
+        }
+        GEQ => {
+            let flag = match value.vtype {
+                Type::Int => value.int >= value2.int,
+                Type::Float => value.flt >= value2.flt,
+                Type::String => value.str >= value2.str,
+            };
+
+            value = if flag { Datum::int(1) } else { Datum::int(0) };
+        }
+        EQUAL => {
+            // NOTE: comparing floats using == is dangerous; but Tcl leaves that to the
+            // TCL programmer.
+            let flag = match value.vtype {
+                Type::Int => value.int == value2.int,
+                Type::Float => value.flt == value2.flt,
+                Type::String => value.str == value2.str,
+            };
+
+            value = if flag { Datum::int(1) } else { Datum::int(0) };
+        }
+        NEQ => {
+            // NOTE: comparing floats using == is dangerous; but Tcl leaves that to the
+            // TCL programmer.
+            let flag = match value.vtype {
+                Type::Int => value.int != value2.int,
+                Type::Float => value.flt != value2.flt,
+                Type::String => value.str != value2.str,
+            };
+
+            value = if flag { Datum::int(1) } else { Datum::int(0) };
+        }
+        STRING_EQ => {
+            value = if value.str == value2.str {
+                Datum::int(1)
+            } else {
+                Datum::int(0)
+            };
+        }
+        STRING_NE => {
+            value = if value.str != value2.str {
+                Datum::int(1)
+            } else {
+                Datum::int(0)
+            };
+        }
+        IN => {
+            let list = list::get_list(&value2.str)?;
+            value = if list::list_contains(&list, &Value::from(&value.str)) {
+                Datum::int(1)
+            } else {
+                Datum::int(0)
+            };
+        }
+        NI => {
+            let list = list::get_list(&value2.str)?;
+            value = if list::list_contains(&list, &Value::from(&value.str)) {
+                Datum::int(0)
+            } else {
+                Datum::int(1)
+            };
+        }
+        BIT_AND => {
+            value.int &= value2.int;
+        }
+        BIT_XOR => {
+            value.int ^= value2.int;
+        }
+        BIT_OR => {
+            value.int |= value2.int;
+        }
 
-            // For AND and OR, we know that the first value has already been converted to
-            // an integer.  Thus we need only consider the possibility of int vs. double
-            // for the second value.
-            AND => {
-                if value2.vtype == Type::Float {
-                    value2.vtype = Type::Int;
-                    value2.int = if value2.flt != 0.0 { 1 } else { 0 };
-                }
-                value.int = if value.int != 0 && value2.int != 0 {
-                    1
-                } else {
-                    0
-                };
+        // For AND and OR, we know that the first value has already been converted to
+        // an integer.  Thus we need only consider the possibility of int vs. double
+        // for the second value.
+        AND => {
+            if value2.vtype == Type::Float {
+                value2.vtype = Type::Int;
+                value2.int = if value2.flt != 0.0 { 1 } else { 0 };
             }
-            OR => {
-                if value2.vtype == Type::Float {
-                    value2.vtype = Type::Int;
-                    value2.int = if value2.flt != 0.0 { 1 } else { 0 };
-                }
-                value.int = if value.int != 0 || value2.int != 0 {
-                    1
-                } else {
-                    0
-                };
+            value.int = if value.int != 0 && value2.int != 0 {
+                1
+            } else {
+                0
+            };
+        }
+        OR => {
+            if value2.vtype == Type::Float {
+                value2.vtype = Type::Int;
+                value2.int = if value2.flt != 0.0 { 1 } else { 0 };
             }
+            value.int = if value.int != 0 || value2.int != 0 {
+                1
+            } else {
+                0
+            };
+        }
 
-            COLON => {
-                return molt_err!("can't have : operator without ? first");
-            }
+        COLON => {
+            return molt_err!("can't have : operator without ? first");
+        }
 
-            _ => {
-                // Nothing to do.
-            }
+        _ => {
+            // Nothing to do.
         }
     }
+
+    Ok(value)
 }
 
-/// Lexical analyzer for the expression parser.  Parses a single value, operator, or other
-/// syntactic element from an expression string.
+/// Lexical analyzer for the expression parser.  Scans a single value, operator, or other
+/// syntactic element from an expression string, and updates the pointer to point to the
+/// next token.  Substitutions are parsed but not evaluated.
 ///
 /// ## Results
 ///
-/// Returns an error result if an error occurs while doing lexical analysis or
-/// executing an embedded command.  On success, info.token is set to the last token type,
-/// and info is updated to point to the next token.  If the token is VALUE, the returned
-/// Datum contains it.
-
-fn expr_lex(interp: &mut Interp, info: &mut ExprInfo) -> DatumResult {
+/// Returns an error result if an error occurs while doing lexical analysis; the caller
+/// saves it as an Error token.
+fn expr_scan(ptr: &mut Tokenizer) -> Result<Token, Exception> {
     // FIRST, skip white space.
-    let mut p = info.expr.clone();
+    let mut p = ptr.clone();
 
     p.skip_while(|c| c.is_whitespace());
 
     if p.at_end() {
-        info.token = END;
-        info.expr = p;
-        return Ok(Datum::none());
+        *ptr = p;
+        return Ok(Token::Op(END));
     }
 
     // First try to parse the token as an integer or floating-point number.
@@ -836,207 +1001,130 @@ fn expr_lex(interp: &mut Interp, info: &mut ExprInfo) -> DatumResult {
             // There's definitely an integer to parse; parse it.
             let token = util::read_int(&mut p).unwrap();
             let int = Value::get_int(&token)?;
-            info.token = VALUE;
-            info.expr = p;
-            return Ok(Datum::int(int));
+            *ptr = p;
+            return Ok(Token::Literal(Datum::int(int)));
         } else if let Some(token) = util::read_float(&mut p) {
-            info.token = VALUE;
-            info.expr = p;
-            return Ok(Datum::float(Value::get_float(&token)?));
+            let flt = Value::get_float(&token)?;
+            *ptr = p;
+            return Ok(Token::Literal(Datum::float(flt)));
         }
     }
 
     // It isn't a number, so the next character will determine what it is.
-    info.expr = p.clone();
-    info.expr.skip();
+    *ptr = p.clone();
+    ptr.skip();
 
     match p.peek() {
         Some('$') => {
             let mut ctx = EvalPtr::from_tokenizer(&p);
-            ctx.set_no_eval(info.no_eval > 0);
-            let var_val = parse_and_eval_variable(interp, &mut ctx)?;
-            info.token = VALUE;
-            info.expr = ctx.to_tokenizer();
-            if info.no_eval > 0 {
-                Ok(Datum::none())
-            } else {
-                expr_parse_value(&var_val)
-            }
+            let word = parse_variable(&mut ctx)?;
+            *ptr = ctx.to_tokenizer();
+            Ok(Token::Var(word))
         }
         Some('[') => {
             let mut ctx = EvalPtr::from_tokenizer(&p);
-            ctx.set_no_eval(info.no_eval > 0);
-            let script_val = parse_and_eval_script(interp, &mut ctx)?;
-            info.token = VALUE;
-            info.expr = ctx.to_tokenizer();
-            if info.no_eval > 0 {
-                Ok(Datum::none())
-            } else {
-                expr_parse_value(&script_val)
-            }
+            let (script, closed) = parse_script(&mut ctx)?;
+            *ptr = ctx.to_tokenizer();
+            Ok(Token::Script(script, closed))
         }
         Some('"') => {
             let mut ctx = EvalPtr::from_tokenizer(&p);
-            ctx.set_no_eval(info.no_eval > 0);
-            let val = parse_and_eval_quoted_word(interp, &mut ctx)?;
-            info.token = VALUE;
-            info.expr = ctx.to_tokenizer();
-            if info.no_eval > 0 {
-                Ok(Datum::none())
-            } else {
-                // Note: we got a Value, but since it was parsed from a quoted string,
-                // it won't already be numeric.
-                expr_parse_string(val.as_str())
-            }
+            let word = parser::parse_quoted_word(&mut ctx)?;
+            *ptr = ctx.to_tokenizer();
+            Ok(Token::Quoted(word))
         }
         Some('{') => {
             let mut ctx = EvalPtr::from_tokenizer(&p);
-            ctx.set_no_eval(info.no_eval > 0);
-            let val = parse_and_eval_braced_word(&mut ctx)?;
-            info.token = VALUE;
-            info.expr = ctx.to_tokenizer();
-            if info.no_eval > 0 {
-                Ok(Datum::none())
-            } else {
-                // Note: we got a Value, but since it was parsed from a braced string,
-                // it won't already be numeric.
-                expr_parse_string(val.as_str())
-            }
-        }
-        Some('(') => {
-            info.token = OPEN_PAREN;
-            Ok(Datum::none())
-        }
-        Some(')') => {
-            info.token = CLOSE_PAREN;
-            Ok(Datum::none())
-        }
-        Some(',') => {
-            info.token = COMMA;
-            Ok(Datum::none())
-        }
-        Some('*') => {
-            info.token = MULT;
-            Ok(Datum::none())
-        }
-        Some('/') => {
-            info.token = DIVIDE;
-            Ok(Datum::none())
-        }
-        Some('%') => {
-            info.token = MOD;
-            Ok(Datum::none())
-        }
-        Some('+') => {
-            info.token = PLUS;
-            Ok(Datum::none())
-        }
-        Some('-') => {
-            info.token = MINUS;
-            Ok(Datum::none())
-        }
-        Some('?') => {
-            info.token = QUESTY;
-            Ok(Datum::none())
-        }
-        Some(':') => {
-            info.token = COLON;
-            Ok(Datum::none())
+            let val = parse_braced_word(&mut ctx)?;
+            *ptr = ctx.to_tokenizer();
+            // Note: we got a Value, but since it was parsed from a braced string,
+            // it won't already be numeric.
+            Ok(Token::Braced(expr_parse_string(val.as_str())))
         }
+        Some('(') => Ok(Token::Op(OPEN_PAREN)),
+        Some(')') => Ok(Token::Op(CLOSE_PAREN)),
+        Some(',') => Ok(Token::Op(COMMA)),
+        Some('*') => Ok(Token::Op(MULT)),
+        Some('/') => Ok(Token::Op(DIVIDE)),
+        Some('%') => Ok(Token::Op(MOD)),
+        Some('+') => Ok(Token::Op(PLUS)),
+        Some('-') => Ok(Token::Op(MINUS)),
+        Some('?') => Ok(Token::Op(QUESTY)),
+        Some(':') => Ok(Token::Op(COLON)),
         Some('<') => {
             p.skip();
             match p.peek() {
                 Some('<') => {
-                    info.token = LEFT_SHIFT;
                     p.skip();
-                    info.expr = p;
-                    Ok(Datum::none())
+                    *ptr = p;
+                    Ok(Token::Op(LEFT_SHIFT))
                 }
                 Some('=') => {
-                    info.token = LEQ;
                     p.skip();
-                    info.expr = p;
-                    Ok(Datum::none())
-                }
-                _ => {
-                    info.token = LESS;
-                    Ok(Datum::none())
+                    *ptr = p;
+                    Ok(Token::Op(LEQ))
                 }
+                _ => Ok(Token::Op(LESS)),
             }
         }
         Some('>') => {
             p.skip();
             match p.peek() {
                 Some('>') => {
-                    info.token = RIGHT_SHIFT;
                     p.skip();
-                    info.expr = p;
-                    Ok(Datum::none())
+                    *ptr = p;
+                    Ok(Token::Op(RIGHT_SHIFT))
                 }
                 Some('=') => {
-                    info.token = GEQ;
                     p.skip();
-                    info.expr = p;
-                    Ok(Datum::none())
-                }
-                _ => {
-                    info.token = GREATER;
-                    Ok(Datum::none())
+                    *ptr = p;
+                    Ok(Token::Op(GEQ))
                 }
+                _ => Ok(Token::Op(GREATER)),
             }
         }
         Some('=') => {
             p.skip();
             if let Some('=') = p.peek() {
-                info.token = EQUAL;
                 p.skip();
-                info.expr = p;
+                *ptr = p;
+                Ok(Token::Op(EQUAL))
             } else {
-                info.token = UNKNOWN;
+                Ok(Token::Op(UNKNOWN))
             }
-            Ok(Datum::none())
         }
         Some('!') => {
             p.skip();
             if let Some('=') = p.peek() {
-                info.token = NEQ;
                 p.skip();
-                info.expr = p;
+                *ptr = p;
+                Ok(Token::Op(NEQ))
             } else {
-                info.token = NOT;
+                Ok(Token::Op(NOT))
             }
-            Ok(Datum::none())
         }
         Some('&') => {
             p.skip();
             if let Some('&') = p.peek() {
-                info.token = AND;
                 p.skip();
-                info.expr = p;
+                *ptr = p;
+                Ok(Token::Op(AND))
             } else {
-                info.token = BIT_AND;
+                Ok(Token::Op(BIT_AND))
             }
-            Ok(Datum::none())
-        }
-        Some('^') => {
-            info.token = BIT_XOR;
-            Ok(Datum::none())
         }
+        Some('^') => Ok(Token::Op(BIT_XOR)),
         Some('|') => {
             p.skip();
             if let Some('|') = p.peek() {
-                info.token = OR;
                 p.skip();
-                info.expr = p;
+                *ptr = p;
+                Ok(Token::Op(OR))
             } else {
-                info.token = BIT_OR;
+                Ok(Token::Op(BIT_OR))
             }
-            Ok(Datum::none())
-        }
-        Some('~') => {
-            info.token = BIT_NOT;
-            Ok(Datum::none())
         }
+        Some('~') => Ok(Token::Op(BIT_NOT)),
         Some(_) => {
             if p.has(|c| c.is_alphabetic()) {
                 let mut str = String::new();
@@ -1048,58 +1136,131 @@ fn expr_lex(interp: &mut Interp, info: &mut ExprInfo) -> DatumResult {
                 // probably overkill.
                 match str.as_ref() {
                     "true" | "yes" | "on" => {
-                        info.expr = p;
-                        info.token = VALUE;
-                        Ok(Datum::int(1))
+                        *ptr = p;
+                        Ok(Token::Literal(Datum::int(1)))
                     }
                     "false" | "no" | "off" => {
-                        info.expr = p;
-                        info.token = VALUE;
-                        Ok(Datum::int(0))
+                        *ptr = p;
+                        Ok(Token::Literal(Datum::int(0)))
                     }
                     "eq" => {
-                        info.expr = p;
-                        info.token = STRING_EQ;
-                        Ok(Datum::none())
+                        *ptr = p;
+                        Ok(Token::Op(STRING_EQ))
                     }
                     "ne" => {
-                        info.expr = p;
-                        info.token = STRING_NE;
-                        Ok(Datum::none())
+                        *ptr = p;
+                        Ok(Token::Op(STRING_NE))
                     }
                     "in" => {
-                        info.expr = p;
-                        info.token = IN;
-                        Ok(Datum::none())
+                        *ptr = p;
+                        Ok(Token::Op(IN))
                     }
                     "ni" => {
-                        info.expr = p;
-                        info.token = NI;
-                        Ok(Datum::none())
+                        *ptr = p;
+                        Ok(Token::Op(NI))
                     }
                     _ => {
-                        info.expr = p;
-                        expr_math_func(interp, info, &str)
+                        *ptr = p;
+                        Ok(Token::Func(expr_find_func(&str)?))
                     }
                 }
             } else {
                 p.skip();
-                info.expr = p;
-                info.token = UNKNOWN;
-                Ok(Datum::none())
+                *ptr = p;
+                Ok(Token::Op(UNKNOWN))
             }
         }
         None => {
             p.skip();
-            info.expr = p;
-            info.token = UNKNOWN;
+            *ptr = p;
+            Ok(Token::Op(UNKNOWN))
+        }
+    }
+}
+
+/// Gets the next token from the parsed expression, evaluating it if it is a value.
+///
+/// ## Results
+///
+/// Returns an error result if the token is a lexical error or an error occurs while
+/// executing an embedded command.  On success, info.token is set to the token type,
+/// and info is updated to point to the next token.  If the token is VALUE, the returned
+/// Datum contains it.
+fn expr_lex(interp: &mut Interp, info: &mut ExprInfo) -> DatumResult {
+    let tokens = info.tokens;
+
+    // FIRST, get the token.  Past the end of the expression there's only END.
+    let token = match tokens.get(info.next) {
+        Some(token) => token,
+        None => {
+            info.token = END;
+            return Ok(Datum::none());
+        }
+    };
+    info.next += 1;
+
+    // NEXT, evaluate it.
+    match token {
+        Token::Op(op) => {
+            info.token = *op;
             Ok(Datum::none())
         }
+        Token::Literal(datum) => {
+            info.token = VALUE;
+            Ok(datum.clone())
+        }
+        Token::Braced(result) => {
+            info.token = VALUE;
+            if info.no_eval > 0 {
+                Ok(Datum::none())
+            } else {
+                result.clone()
+            }
+        }
+        Token::Var(word) => {
+            if info.no_eval > 0 {
+                info.token = VALUE;
+                return Ok(Datum::none());
+            }
+            let var_val = interp.eval_word(word)?;
+            info.token = VALUE;
+            expr_parse_value(&var_val)
+        }
+        Token::Script(script, closed) => {
+            if info.no_eval > 0 {
+                info.token = VALUE;
+                return Ok(Datum::none());
+            }
+            let script_val = interp.eval_subst(script)?;
+
+            // The missing bracket is reported only after the script is evaluated.
+            if !closed {
+                return molt_err!("missing close-bracket");
+            }
+            info.token = VALUE;
+            expr_parse_value(&script_val)
+        }
+        Token::Quoted(word) => {
+            if info.no_eval > 0 {
+                info.token = VALUE;
+                return Ok(Datum::none());
+            }
+            let val = interp.eval_word(word)?;
+            info.token = VALUE;
+            // Note: we got a Value, but since it was parsed from a quoted string,
+            // it won't already be numeric.
+            expr_parse_string(val.as_str())
+        }
+        // The function's arguments are parsed recursively, another level of nesting.
+        Token::Func(bfunc) => expr_nested(interp, info, |interp, info| {
+            expr_math_func(interp, info, bfunc)
+        }),
+        Token::Error(exception) => Err(exception.clone()),
     }
 }
 
 // Parses a variable reference.  A bare "$" is an error.
-fn parse_and_eval_variable(interp: &mut Interp, ctx: &mut EvalPtr) -> MoltResult {
+fn parse_variable(ctx: &mut EvalPtr) -> Result<Word, Exception> {
     // FIRST, skip the '$'
     ctx.skip_char('$');
 
@@ -1109,19 +1270,13 @@ fn parse_and_eval_variable(interp: &mut Interp, ctx: &mut EvalPtr) -> MoltResult
     }
 
     // NEXT, get the variable reference.
-    let word = parser::parse_varname(ctx)?;
-
-    if ctx.is_no_eval() {
-        Ok(Value::empty())
-    } else {
-        interp.eval_word(&word)
-    }
+    parser::parse_varname(ctx)
 }
 
-/// Parses and evaluates an interpolated script in Molt input, i.e., a string beginning with
-/// a "[", returning a MoltResult.  If the no_eval flag is set, returns an empty value.
+/// Parses an interpolated script in Molt input, i.e., a string beginning with
+/// a "[", returning the script and whether it had its closing bracket.
 /// This is used to handled interpolated scripts in expressions.
-fn parse_and_eval_script(interp: &mut Interp, ctx: &mut EvalPtr) -> MoltResult {
+fn parse_script(ctx: &mut EvalPtr) -> Result<(Script, bool), Exception> {
     // FIRST, skip the '['
     ctx.skip_char('[');
 
@@ -1130,41 +1285,20 @@ fn parse_and_eval_script(interp: &mut Interp, ctx: &mut EvalPtr) -> MoltResult {
     ctx.set_bracket_term(true);
 
     let script = parser::parse_script(ctx)?;
-    let result = if ctx.is_no_eval() {
-        Ok(Value::empty())
-    } else {
-        interp.eval_script(&script)
-    };
 
     ctx.set_bracket_term(old_flag);
 
-    // NEXT, make sure there's a closing bracket
-    if result.is_ok() {
-        if ctx.next_is(']') {
-            ctx.next();
-        } else {
-            return molt_err!("missing close-bracket");
-        }
-    }
-
-    result
-}
-
-/// Parses and evaluates a quoted word in Molt input, i.e., a string beginning with
-/// a double quote, returning a MoltResult.  If the no_eval flag is set, returns an empty
-/// value.  This is used to handle double-quoted strings in expressions.
-fn parse_and_eval_quoted_word(interp: &mut Interp, ctx: &mut EvalPtr) -> MoltResult {
-    let word = parser::parse_quoted_word(ctx)?;
-
-    if ctx.is_no_eval() {
-        Ok(Value::empty())
+    // NEXT, see if there's a closing bracket
+    if ctx.next_is(']') {
+        ctx.next();
+        Ok((script, true))
     } else {
-        interp.eval_word(&word)
+        Ok((script, false))
     }
 }
 
 /// Parses a braced word, returning a Value.
-fn parse_and_eval_braced_word(ctx: &mut EvalPtr) -> MoltResult {
+fn parse_braced_word(ctx: &mut EvalPtr) -> MoltResult {
     if let Word::Value(val) = parser::parse_braced_word(ctx)? {
         Ok(val)
     } else {
@@ -1174,13 +1308,7 @@ fn parse_and_eval_braced_word(ctx: &mut EvalPtr) -> MoltResult {
 
 /// Parses math functions, returning the evaluated value.
 #[allow(clippy::needless_range_loop)]
-fn expr_math_func(interp: &mut Interp, info: &mut ExprInfo, func_name: &str) -> DatumResult {
-    // FIRST, is this actually a function?
-    // TODO: this does a linear search of the FUNC_TABLE.  Ultimately, it should probably
-    // be a hash lookup.  And if we want to allow users to add functions, it should be
-    // kept in the Interp.
-    let bfunc = expr_find_func(func_name)?;
-
+fn expr_math_func(interp: &mut Interp, info: &mut ExprInfo, bfunc: &BuiltinFunc) -> DatumResult {
     // NEXT, get the open paren.
     let _ = expr_lex(interp, info)?;
 
@@ -0,0 +1,131 @@
+//! Hash Tables
+//!
+//! This module defines the hash table type used for the interpreter's command table and
+//! context cache, which are consulted on every command dispatch.  By default it's the
+//! standard `HashMap`, with its DoS-resistant SipHash hasher; with the `fast-hash` feature
+//! it uses the much faster, deterministic Fx hash algorithm from `rustc`, at the cost of
+//! that resistance.
+
+use std::collections::HashMap;
+
+/// The hash table used for the interpreter's command table and context cache.
+pub(crate) type TableMap<K, V> = HashMap<K, V, TableHasher>;
+
+/// The hasher builder for a `TableMap`.
+#[cfg(feature = "fast-hash")]
+pub(crate) type TableHasher = std::hash::BuildHasherDefault<FxHasher>;
+
+/// The hasher builder for a `TableMap`.
+#[cfg(not(feature = "fast-hash"))]
+pub(crate) type TableHasher = std::collections::hash_map::RandomState;
+
+/// The multiplier used by the Fx hash algorithm.
+#[cfg(feature = "fast-hash")]
+const SEED: u64 = 0x51_7c_c1_b7_27_22_0a_95;
+
+/// A hasher implementing the Fx hash algorithm used by `rustc`: each word of input is
+/// mixed into the hash with a rotate, an xor, and a multiply.  It's fast for short keys
+/// such as command names, but it isn't resistant to deliberately colliding keys.
+#[cfg(feature = "fast-hash")]
+#[derive(Default, Clone, Copy)]
+pub(crate) struct FxHasher {
+    hash: u64,
+}
+
+#[cfg(feature = "fast-hash")]
+impl FxHasher {
+    #[inline]
+    fn add_to_hash(&mut self, word: u64) {
+        self.hash = (self.hash.rotate_left(5) ^ word).wrapping_mul(SEED);
+    }
+}
+
+#[cfg(feature = "fast-hash")]
+impl std::hash::Hasher for FxHasher {
+    #[inline]
+    fn write(&mut self, bytes: &[u8]) {
+        let mut chunks = bytes.chunks_exact(8);
+        for chunk in &mut chunks {
+            let mut word = [0u8; 8];
+            word.copy_from_slice(chunk);
+            self.add_to_hash(u64::from_le_bytes(word));
+        }
+
+        let mut rest = chunks.remainder();
+        if rest.len() >= 4 {
+            let mut word = [0u8; 4];
+            word.copy_from_slice(&rest[..4]);
+            self.add_to_hash(u32::from_le_bytes(word) as u64);
+            rest = &rest[4..];
+        }
+        if rest.len() >= 2 {
+            let mut word = [0u8; 2];
+            word.copy_from_slice(&rest[..2]);
+            self.add_to_hash(u16::from_le_bytes(word) as u64);
+            rest = &rest[2..];
+        }
+        if let Some(byte) = rest.first() {
+            self.add_to_hash(*byte as u64);
+        }
+    }
+
+    #[inline]
+    fn write_u8(&mut self, i: u8) {
+        self.add_to_hash(i as u64);
+    }
+
+    #[inline]
+    fn write_u32(&mut self, i: u32) {
+        self.add_to_hash(i as u64);
+    }
+
+    #[inline]
+    fn write_u64(&mut self, i: u64) {
+        self.add_to_hash(i);
+    }
+
+    #[inline]
+    fn write_usize(&mut self, i: usize) {
+        self.add_to_hash(i as u64);
+    }
+
+    #[inline]
+    fn finish(&self) -> u64 {
+        self.hash
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn table_map() {
+        let mut map: TableMap<String, i32> = TableMap::default();
+        map.insert("set".into(), 1);
+        map.insert("string".into(), 2);
+
+        assert_eq!(map.get("set"), Some(&1));
+        assert_eq!(map.get("string"), Some(&2));
+        assert_eq!(map.get("str"), None);
+    }
+
+    #[cfg(feature = "fast-hash")]
+    #[test]
+    fn fx_hasher() {
+        use std::hash::{Hash, Hasher};
+
+        let hash = |s: &str| {
+            let mut hasher = FxHasher::default();
+            s.hash(&mut hasher);
+            hasher.finish()
+        };
+
+        // The hash is deterministic, and depends on every byte of the key.
+        assert_eq!(hash("namespace"), hash("namespace"));
+        assert_ne!(hash("namespace"), hash("namespacf"));
+        assert_ne!(hash("a"), hash("b"));
+        assert_ne!(hash(""), hash("a"));
+        assert_ne!(hash("abcdefgh1"), hash("abcdefgh2"));
+    }
+}
@@ -388,6 +388,12 @@
 //! argument, rather than `1`.  That indicates that the first two arguments represent the
 //! command being called, e.g., `array exists`.
 //!
+//! Ensembles can also be defined at run time using the [`Ensemble`](struct.Ensemble.html)
+//! type, whose subcommands may be binary commands, each with its own context; procedures;
+//! or nested ensembles.  Existing ensembles, including binary ensembles like `info`, can
+//! be extended using
+//! [`Interp::extend_ensemble`](struct.Interp.html#method.extend_ensemble).
+//!
 //! # Object Commands
 //!
 //! An _object command_ is an _ensemble command_ that represents an object; the classic TCL
@@ -433,6 +439,14 @@
 //! implementing a Read-Eval-Print-Loop, as it allows the REPL to easily determine whether it
 //! should evaluate the input immediately or ask for an additional line of input.
 //!
+//! # Saving and Restoring State
+//!
+//! The [`Interp::snapshot`](struct.Interp.html#method.snapshot) method saves a copy of the
+//! interpreter's procedures, global variables, and cloneable context structs, and
+//! [`Interp::restore`](struct.Interp.html#method.restore) restores them.  This allows an
+//! application to undo the effects of a misbehaving script, e.g., in a test harness, a
+//! REPL, or a game scripting engine.
+//!
 //! [The Molt Book]: https://wduquette.github.io/molt/
 //! [`MoltResult`]: ../types/type.MoltResult.html
 //! [`Exception`]: ../types/enum.Exception.html
@@ -440,29 +454,49 @@
 //! [`Value`]: ../value/index.html
 //! [`Interp`]: struct.Interp.html
 
+use crate::binary;
+#[cfg(feature = "os")]
+use crate::channel::ChannelTable;
 use crate::check_args;
 use crate::commands;
+#[cfg(feature = "coroutine")]
+use crate::coroutine::{Coroutine, CoroutineBody, Resumed};
+use crate::coverage::Coverage;
 use crate::dict::dict_new;
 use crate::expr;
+use crate::hash::TableMap;
 use crate::list::list_to_string;
 use crate::molt_err;
 use crate::molt_ok;
 use crate::parser;
+use crate::parser::CompleteStatus;
 use crate::parser::Script;
 use crate::parser::Word;
+use crate::scope::GlobalSnapshot;
 use crate::scope::ScopeStack;
 use crate::types::*;
+use crate::util;
+use crate::value::Evaluable;
 use crate::value::Value;
 use std::any::Any;
+use std::cell::RefCell;
+use std::cell::RefMut;
+use std::collections::BTreeMap;
 use std::collections::HashMap;
+use std::collections::HashSet;
+use std::fs;
+#[cfg(feature = "os")]
+use std::io::Write;
+use std::path::Path;
 use std::rc::Rc;
-use std::time::Instant;
+use std::time::Duration;
 
 // Constants
 const OPT_CODE: &str = "-code";
 const OPT_LEVEL: &str = "-level";
 const OPT_ERRORCODE: &str = "-errorcode";
 const OPT_ERRORINFO: &str = "-errorinfo";
+const OPT_ERRORLINE: &str = "-errorline";
 const ZERO: &str = "0";
 
 /// The Molt Interpreter.
@@ -493,8 +527,38 @@ const ZERO: &str = "0";
 /// ```
 #[derive(Default)]
 pub struct Interp {
-    // Command Table
-    commands: HashMap<String, Rc<Command>>,
+    // Command Table.  Commands in the global namespace are stored by name; commands in
+    // other namespaces by their qualified names, less the leading "::".
+    commands: TableMap<String, Rc<Command>>,
+
+    // The qualified names of the namespaces other than the global namespace.
+    namespaces: HashSet<String>,
+
+    // The qualified name of the current namespace.  It's shared with the procedures
+    // defined in it, so that calling one needn't copy it.
+    namespace: Rc<str>,
+
+    // A buffer in which command table keys are built as commands are looked up in the
+    // current namespace, so that lookups needn't allocate.
+    key_buffer: RefCell<String>,
+
+    // Whether this is a safe interpreter, i.e., one created by `Interp::new_safe`.
+    safe: bool,
+
+    // The ID of the child interpreter table in the context cache, once it is created.
+    children: Option<ContextID>,
+
+    // The parent interpreter, lent to this child while the parent evaluates a script in it.
+    parent: Option<Box<Interp>>,
+
+    // The native handler for unknown commands, if any.
+    unknown_handler: Option<CommandFunc>,
+
+    // Whether an unknown command is currently being handled.
+    in_unknown: bool,
+
+    // The path of the script file currently being evaluated by `eval_file`, if any.
+    script_file: Option<String>,
 
     // Variable Table
     scopes: ScopeStack,
@@ -503,7 +567,7 @@ pub struct Interp {
     last_context_id: u64,
 
     // Context Map
-    context_map: HashMap<ContextID, ContextBox>,
+    context_map: TableMap<ContextID, ContextBox>,
 
     // Defines the recursion limit for Interp::eval().
     recursion_limit: usize,
@@ -511,6 +575,74 @@ pub struct Interp {
     // Current number of eval levels.
     num_levels: usize,
 
+    // The number of evaluation steps taken: commands executed plus scripts evaluated.
+    steps: usize,
+
+    // The step at which the command and time limits are next checked.
+    next_check: usize,
+
+    // The step beyond which further steps fail, if there's a command limit.
+    command_limit: Option<usize>,
+
+    // The clock used for timing and time limits, if the platform has one.
+    clock: Option<ClockFunc>,
+
+    // The clock time after which further steps fail, if there's a time limit.
+    deadline: Option<Duration>,
+
+    // The stack of commands currently being executed, innermost last.
+    frames: Vec<Frame>,
+
+    // Word lists left by commands that have finished executing, cleared, to be reused by
+    // the next commands so that executing a command needn't allocate one.
+    spare_words: Vec<MoltRc<MoltList>>,
+
+    // The hook called with each command's words before it's executed, if any.
+    exec_trace: Option<ExecTraceFunc>,
+
+    // The execution traces added by `trace add execution`, by command table key.
+    exec_traces: HashMap<String, Vec<ExecTrace>>,
+
+    // Whether an execution trace's script is currently being evaluated.
+    in_exec_trace: bool,
+
+    // The debugger hook, called when evaluation pauses, if any.
+    debug_hook: Option<DebugHook>,
+
+    // The command table keys of the commands at which evaluation pauses.
+    breakpoints: HashSet<String>,
+
+    // When stepping, the frame level at or above which the next command pauses.
+    debug_step: Option<usize>,
+
+    // Whether the debugger hook is currently being called.
+    in_debug_hook: bool,
+
+    // Whether the debugger has aborted the current evaluation.
+    debug_aborted: bool,
+
+    // The script coverage data, if coverage is enabled.
+    coverage: Option<Coverage>,
+
+    // The scope level of the innermost procedure being executed, or 0 if none.
+    proc_scope: usize,
+
+    // The commands passed to `tailcall`, innermost last, awaiting their procedures' return.
+    tailcalls: Vec<Tailcall>,
+
+    // While a coroutine's body is running, the number of frames and nesting levels at
+    // which its top-level commands execute; `yield` may be called only there.
+    #[cfg(feature = "coroutine")]
+    yield_at: Option<(usize, usize)>,
+
+    // The value passed to `yield`, until the coroutine's body is suspended.
+    #[cfg(feature = "coroutine")]
+    yielded: Option<Value>,
+
+    // The context ID of the channel table shared by the I/O commands, once they're added.
+    #[cfg(feature = "os")]
+    channels: Option<ContextID>,
+
     // Profile Map
     profile_map: HashMap<String, ProfileRecord>,
 }
@@ -522,6 +654,19 @@ enum Command {
 
     /// A Molt procedure
     Proc(Procedure),
+
+    /// An alias: a command prefix to which the alias's arguments are appended.
+    Alias(MoltList),
+
+    /// An alias to a command prefix in the parent interpreter.
+    ParentAlias(MoltList),
+
+    /// An ensemble defined at run time.
+    Ensemble(Ensemble),
+
+    /// A coroutine, which is resumed when the command is called.
+    #[cfg(feature = "coroutine")]
+    Coroutine(Coroutine),
 }
 
 impl Command {
@@ -530,6 +675,19 @@ impl Command {
         match self {
             Command::Native(func, context_id) => func(interp, *context_id, argv),
             Command::Proc(proc) => proc.execute(interp, argv),
+            Command::Alias(target) => {
+                let mut words = target.clone();
+                words.extend_from_slice(&argv[1..]);
+                interp.call_alias(&words)
+            }
+            Command::ParentAlias(target) => {
+                let mut words = target.clone();
+                words.extend_from_slice(&argv[1..]);
+                interp.call_parent(&argv[0], &words)
+            }
+            Command::Ensemble(ensemble) => ensemble.execute(interp, argv, 1),
+            #[cfg(feature = "coroutine")]
+            Command::Coroutine(coroutine) => coroutine.resume(interp, argv),
         }
     }
 
@@ -538,15 +696,24 @@ impl Command {
         match self {
             Command::Native(_, _) => Value::from("native"),
             Command::Proc(_) => Value::from("proc"),
+            Command::Alias(_) | Command::ParentAlias(_) => Value::from("alias"),
+            Command::Ensemble(_) => Value::from("ensemble"),
+            #[cfg(feature = "coroutine")]
+            Command::Coroutine(_) => Value::from("coroutine"),
         }
     }
 
-    /// Gets the command's context, or NULL_CONTEXT if none.
-    fn context_id(&self) -> ContextID {
+    /// Gets the IDs of the command's contexts, if any.  An ensemble may have a context
+    /// for each of its subcommands.
+    fn context_ids(&self) -> Vec<ContextID> {
+        let mut ids = Vec::new();
         match self {
-            Command::Native(_, context_id) => *context_id,
-            _ => NULL_CONTEXT,
+            Command::Native(_, context_id) => ids.push(*context_id),
+            Command::Ensemble(ensemble) => ensemble.context_ids(&mut ids),
+            _ => (),
         }
+        ids.retain(|id| *id != NULL_CONTEXT);
+        ids
     }
 
     /// Returns true if the command is a proc, and false otherwise.
@@ -559,6 +726,126 @@ impl Command {
     }
 }
 
+/// The number of evaluation steps between checks of the interpreter's time limit.
+const TIME_CHECK_STEPS: usize = 1000;
+
+/// The nesting levels that an evaluation in a child interpreter counts against the parent's
+/// recursion limit, beyond those of the evaluation itself.  Each level of a chain of children
+/// uses several times the Rust stack of a nested `eval`, so that at one level apiece the
+/// default limit would overflow the stack.
+const CHILD_EVAL_LEVELS: usize = 4;
+
+/// The commands removed from the standard command set by `Interp::make_safe`.
+const UNSAFE_COMMANDS: [&str; 7] = ["close", "exit", "gets", "glob", "open", "read", "source"];
+
+/// The child interpreters of an interpreter, by name, as saved in its context cache.
+#[derive(Default)]
+struct ChildTable {
+    children: HashMap<String, Interp>,
+
+    /// The number of the last automatically named child, used to generate names.
+    last_child: usize,
+}
+
+/// A saved copy of an interpreter's script-level state, as returned by
+/// [`Interp::snapshot`](struct.Interp.html#method.snapshot) and restored by
+/// [`Interp::restore`](struct.Interp.html#method.restore): its procedures and namespaces, its
+/// global variables, and any context structs saved with
+/// [`save_cloneable_context`](struct.Interp.html#method.save_cloneable_context).
+///
+/// A snapshot is independent of the interpreter; cloning it allows the same state to be
+/// restored any number of times.
+pub struct InterpSnapshot {
+    // The procedures, by command table key.
+    procs: Vec<(String, Rc<Command>)>,
+
+    // The namespaces.
+    namespaces: HashSet<String>,
+
+    // The global variables.
+    globals: GlobalSnapshot,
+
+    // Copies of the cloneable contexts, by ID.
+    contexts: Vec<(ContextID, Box<dyn Any>, ContextCloneFn)>,
+}
+
+impl Clone for InterpSnapshot {
+    fn clone(&self) -> Self {
+        Self {
+            procs: self.procs.clone(),
+            namespaces: self.namespaces.clone(),
+            globals: self.globals.clone(),
+            contexts: self
+                .contexts
+                .iter()
+                .map(|(id, data, clone_fn)| (*id, clone_fn(&**data), *clone_fn))
+                .collect(),
+        }
+    }
+}
+
+/// An execution trace added by `trace add execution`: a script called before the traced
+/// command is executed, after it returns, or both.
+#[derive(Clone)]
+struct ExecTrace {
+    /// Whether the script is called before the command is executed.
+    enter: bool,
+
+    /// Whether the script is called after the command returns.
+    leave: bool,
+
+    /// The command prefix to call.
+    script: Value,
+}
+
+/// The qualified name of the global namespace.
+const GLOBAL_NAMESPACE: &str = "::";
+
+/// Returns the command table key for a fully qualified command name: the name without
+/// its leading "::".
+fn command_key(qualified: &str) -> &str {
+    qualified.trim_start_matches("::")
+}
+
+/// Returns the qualified name of the namespace containing the fully qualified name, e.g.,
+/// `::a` for `::a::b` and `::` for `::a`.
+fn namespace_qualifiers(qualified: &str) -> &str {
+    match qualified.rfind("::") {
+        Some(0) | None => GLOBAL_NAMESPACE,
+        Some(i) => &qualified[..i],
+    }
+}
+
+/// Returns the earlier of two optional limits, where `None` means no limit.
+fn earliest<T: Ord>(a: Option<T>, b: Option<T>) -> Option<T> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(a.min(b)),
+        (Some(a), None) => Some(a),
+        (None, b) => b,
+    }
+}
+
+/// Returns the default clock: the system clock, measured from the first time it's read.
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+fn default_clock() -> Option<ClockFunc> {
+    fn system_clock() -> Duration {
+        use std::sync::OnceLock;
+        use std::time::Instant;
+
+        static EPOCH: OnceLock<Instant> = OnceLock::new();
+        EPOCH.get_or_init(Instant::now).elapsed()
+    }
+
+    Some(system_clock)
+}
+
+/// Returns the default clock.  `std::time::Instant` panics on `wasm32-unknown-unknown`, so
+/// by default there's none; the application can provide one with `Interp::set_clock`.
+#[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+fn default_clock() -> Option<ClockFunc> {
+    None
+}
+
 /// Sentinal value for command functions with no related context.
 ///
 /// **NOTE**: it would make no sense to use `Option<ContextID>` instead of a sentinal
@@ -579,6 +866,21 @@ const NULL_CONTEXT: ContextID = ContextID(0);
 struct ContextBox {
     data: Box<dyn Any>,
     ref_count: usize,
+
+    // A function that clones the data, if it was saved by `save_cloneable_context`.
+    clone_fn: Option<ContextCloneFn>,
+}
+
+/// A function that clones a context struct of a known type.
+type ContextCloneFn = fn(&dyn Any) -> Box<dyn Any>;
+
+/// Clones a context struct of type T.  Panics if the data isn't a T.
+fn clone_context<T: Clone + 'static>(data: &dyn Any) -> Box<dyn Any> {
+    Box::new(
+        data.downcast_ref::<T>()
+            .expect("context type mismatch")
+            .clone(),
+    )
 }
 
 impl ContextBox {
@@ -587,6 +889,17 @@ impl ContextBox {
         Self {
             data: Box::new(data),
             ref_count: 0,
+            clone_fn: None,
+        }
+    }
+
+    /// Creates a new context box for data that can be cloned when the interpreter's
+    /// state is saved by `Interp::snapshot`.
+    fn cloneable<T: Clone + 'static>(data: T) -> Self {
+        Self {
+            data: Box::new(data),
+            ref_count: 0,
+            clone_fn: Some(clone_context::<T>),
         }
     }
 
@@ -609,6 +922,34 @@ impl ContextBox {
     }
 }
 
+/// A command currently being executed, for use by `info frame`.
+struct Frame {
+    // The command's words, after substitution.
+    words: MoltRc<MoltList>,
+
+    // The scope level at which the command is executing.
+    level: usize,
+
+    // The command's line number within its script.
+    line: usize,
+
+    // Whether the command is a call to a procedure.
+    is_proc: bool,
+}
+
+/// A command passed to `tailcall`, to be executed in place of the procedure that called it
+/// once the procedure's body returns.
+struct Tailcall {
+    // The scope level of the procedure that called `tailcall`.
+    level: usize,
+
+    // The command, as resolved in the procedure's namespace, if it exists.
+    cmd: Option<Rc<Command>>,
+
+    // The command's words.
+    words: MoltRc<MoltList>,
+}
+
 struct ProfileRecord {
     count: u128,
     nanos: u128,
@@ -641,11 +982,44 @@ impl Interp {
     pub fn empty() -> Self {
         let mut interp = Self {
             recursion_limit: 1000,
-            commands: HashMap::new(),
+            commands: TableMap::default(),
+            namespaces: HashSet::new(),
+            namespace: GLOBAL_NAMESPACE.into(),
+            key_buffer: RefCell::new(String::new()),
+            safe: false,
+            children: None,
+            parent: None,
+            unknown_handler: None,
+            in_unknown: false,
+            script_file: None,
             last_context_id: 0,
-            context_map: HashMap::new(),
+            context_map: TableMap::default(),
             scopes: ScopeStack::new(),
             num_levels: 0,
+            steps: 0,
+            next_check: usize::MAX,
+            command_limit: None,
+            clock: default_clock(),
+            deadline: None,
+            frames: Vec::new(),
+            spare_words: Vec::new(),
+            exec_trace: None,
+            exec_traces: HashMap::new(),
+            in_exec_trace: false,
+            debug_hook: None,
+            breakpoints: HashSet::new(),
+            debug_step: None,
+            in_debug_hook: false,
+            debug_aborted: false,
+            coverage: None,
+            proc_scope: 0,
+            tailcalls: Vec::new(),
+            #[cfg(feature = "coroutine")]
+            yield_at: None,
+            #[cfg(feature = "coroutine")]
+            yielded: None,
+            #[cfg(feature = "os")]
+            channels: None,
             profile_map: HashMap::new(),
         };
 
@@ -658,8 +1032,9 @@ impl Interp {
     /// to retrieve the full list, and the [`add_command`](#method.add_command) family of
     /// methods to extend the interpreter with new commands.
     ///
-    /// TODO: Define command sets (sets of commands that go together, so that clients can
-    /// add or remove them in groups).
+    /// The standard commands are the union of the core, control, string, list, and (with
+    /// the `os` feature) OS command sets, plus a few developer tools.  To build a custom
+    /// command set, start with [`empty`](#method.empty) and add just the sets you want.
     ///
     /// ```
     /// # use molt::types::*;
@@ -676,69 +1051,252 @@ impl Interp {
     pub fn new() -> Self {
         let mut interp = Interp::empty();
 
-        // TODO: These commands affect the interpreter only, not the external environment.
-        // It might be desirable to subdivide them further, into those that can cause
-        // denial-of-service kinds of problems, e.g., for, while, proc, rename, and those
-        // that can't.
-        interp.add_command("append", commands::cmd_append);
-        interp.add_command("array", commands::cmd_array);
-        interp.add_command("assert_eq", commands::cmd_assert_eq);
-        interp.add_command("break", commands::cmd_break);
-        interp.add_command("catch", commands::cmd_catch);
-        interp.add_command("continue", commands::cmd_continue);
-        interp.add_command("dict", commands::cmd_dict);
-        interp.add_command("error", commands::cmd_error);
-        interp.add_command("expr", commands::cmd_expr);
-        interp.add_command("for", commands::cmd_for);
-        interp.add_command("foreach", commands::cmd_foreach);
-        interp.add_command("global", commands::cmd_global);
-        interp.add_command("if", commands::cmd_if);
-        interp.add_command("incr", commands::cmd_incr);
-        interp.add_command("info", commands::cmd_info);
-        interp.add_command("join", commands::cmd_join);
-        interp.add_command("lappend", commands::cmd_lappend);
-        interp.add_command("lindex", commands::cmd_lindex);
-        interp.add_command("list", commands::cmd_list);
-        interp.add_command("llength", commands::cmd_llength);
-        interp.add_command("proc", commands::cmd_proc);
-        interp.add_command("puts", commands::cmd_puts);
-        interp.add_command("rename", commands::cmd_rename);
-        interp.add_command("return", commands::cmd_return);
-        interp.add_command("set", commands::cmd_set);
-        interp.add_command("string", commands::cmd_string);
-        interp.add_command("throw", commands::cmd_throw);
-        interp.add_command("time", commands::cmd_time);
-        interp.add_command("unset", commands::cmd_unset);
-        interp.add_command("while", commands::cmd_while);
-
-        // TODO: Requires file access.  Ultimately, might go in an extension crate if
-        // the necessary operations aren't available in core::.
-        interp.add_command("source", commands::cmd_source);
-
-        // TODO: Useful for entire programs written in Molt; but not necessarily wanted in
-        // extension scripts.
-        interp.add_command("exit", commands::cmd_exit);
+        interp.add_core_commands();
+        interp.add_control_commands();
+        interp.add_string_commands();
+        interp.add_list_commands();
+        #[cfg(feature = "os")]
+        interp.add_os_commands();
 
         // TODO: Developer Tools
         interp.add_command("parse", parser::cmd_parse);
         interp.add_command("pdump", commands::cmd_pdump);
         interp.add_command("pclear", commands::cmd_pclear);
 
+        interp
+    }
+
+    /// Adds the core command set: the commands for working with variables, procedures,
+    /// namespaces, errors, expressions, and child interpreters, i.e., `alias`, `array`,
+    /// `assert_eq`, `catch`, `error`, `eval`, `expr`, `global`, `incr`, `info`, `interp`,
+    /// `namespace`, `parray`, `proc`, `rename`, `return`, `set`, `throw`, `time`, `trace`,
+    /// `unset`, and `variable`.  Without the `os` feature, it also includes a `puts` that writes to
+    /// standard output and an `exit` that throws an `EXIT` error for the application to
+    /// handle.
+    ///
+    /// These commands affect only the interpreter, not the world outside it.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use molt::types::*;
+    /// # use molt::Interp;
+    /// # use molt::molt_ok;
+    /// # fn dummy() -> MoltResult {
+    /// // A configuration file interpreter: no loops, no file access.
+    /// let mut interp = Interp::empty();
+    /// interp.add_core_commands();
+    /// interp.add_string_commands();
+    /// interp.add_list_commands();
+    ///
+    /// interp.eval("set name [string toupper molt]")?;
+    /// assert_eq!(interp.eval("set name")?.as_str(), "MOLT");
+    /// assert!(!interp.has_command("while"));
+    /// assert!(!interp.has_command("source"));
+    /// # molt_ok!()
+    /// # }
+    /// ```
+    pub fn add_core_commands(&mut self) {
+        // TODO: It might be desirable to subdivide these further, into those that can cause
+        // denial-of-service kinds of problems, e.g., proc and rename, and those that can't.
+        self.add_command("alias", commands::cmd_alias);
+        self.add_command("array", commands::cmd_array);
+        self.add_command("assert_eq", commands::cmd_assert_eq);
+        self.add_command("catch", commands::cmd_catch);
+        self.add_command("error", commands::cmd_error);
+        self.add_command("eval", commands::cmd_eval);
+        #[cfg(not(feature = "os"))]
+        self.add_command("exit", commands::cmd_exit);
+        self.add_command("expr", commands::cmd_expr);
+        self.add_command("global", commands::cmd_global);
+        self.add_command("incr", commands::cmd_incr);
+        self.add_command("info", commands::cmd_info);
+        self.add_command("interp", commands::cmd_interp);
+        self.add_command("namespace", commands::cmd_namespace);
+        self.add_command("parray", commands::cmd_parray);
+        self.add_command("proc", commands::cmd_proc);
+        #[cfg(not(feature = "os"))]
+        self.add_command("puts", commands::cmd_puts);
+        self.add_command("rename", commands::cmd_rename);
+        self.add_command("return", commands::cmd_return);
+        self.add_command("set", commands::cmd_set);
+        self.add_command("tailcall", commands::cmd_tailcall);
+        self.add_command("throw", commands::cmd_throw);
+        self.add_command("time", commands::cmd_time);
+        self.add_command("trace", commands::cmd_trace);
+        self.add_command("unset", commands::cmd_unset);
+        self.add_command("variable", commands::cmd_variable);
+    }
+
+    /// Adds the control structure command set: `break`, `continue`, `for`, `foreach`,
+    /// `if`, and `while`, plus `coroutine` and `yield` with the `coroutine` feature.  Omit
+    /// these to keep scripts from looping indefinitely.
+    pub fn add_control_commands(&mut self) {
+        self.add_command("break", commands::cmd_break);
+        self.add_command("continue", commands::cmd_continue);
+        #[cfg(feature = "coroutine")]
+        self.add_command("coroutine", commands::cmd_coroutine);
+        self.add_command("for", commands::cmd_for);
+        self.add_command("foreach", commands::cmd_foreach);
+        self.add_command("if", commands::cmd_if);
+        self.add_command("while", commands::cmd_while);
+        #[cfg(feature = "coroutine")]
+        self.add_command("yield", commands::cmd_yield);
+    }
+
+    /// Adds the string command set: `append`, `binary`, `encoding`, `format`, and `string`.
+    pub fn add_string_commands(&mut self) {
+        self.add_command("append", commands::cmd_append);
+        self.add_command("binary", binary::cmd_binary);
+        self.add_command("encoding", commands::cmd_encoding);
+        self.add_command("format", commands::cmd_format);
+        self.add_command("string", commands::cmd_string);
+    }
+
+    /// Adds the list and dictionary command set: `dict`, `join`, `lappend`, `lindex`,
+    /// `list`, `llength`, and `split`.
+    pub fn add_list_commands(&mut self) {
+        self.add_command("dict", commands::cmd_dict);
+        self.add_command("join", commands::cmd_join);
+        self.add_command("lappend", commands::cmd_lappend);
+        self.add_command("lindex", commands::cmd_lindex);
+        self.add_command("list", commands::cmd_list);
+        self.add_command("llength", commands::cmd_llength);
+        self.add_command("split", commands::cmd_split);
+    }
+
+    /// Adds the OS command set, the commands that interact with the operating system:
+    /// the I/O commands `close`, `eof`, `gets`, `open`, `puts`, and `read`, which share a
+    /// table of open channels, plus `exit`, `glob`, and `source`.  Also populates the
+    /// `env()` array with a copy of the process environment; to have changes to `env()`
+    /// written back to the environment, call [`link_env`](#method.link_env).
+    ///
+    /// This method is only available with the `os` feature.
+    #[cfg(feature = "os")]
+    pub fn add_os_commands(&mut self) {
+        let channels = self.save_context(ChannelTable::new());
+        self.channels = Some(channels);
+        self.add_context_command("close", commands::cmd_close, channels);
+        self.add_context_command("eof", commands::cmd_eof, channels);
+        self.add_command("exit", commands::cmd_exit);
+        self.add_context_command("gets", commands::cmd_gets, channels);
+        self.add_command("glob", commands::cmd_glob);
+        self.add_context_command("open", commands::cmd_open, channels);
+        self.add_context_command("puts", commands::cmd_puts, channels);
+        self.add_context_command("read", commands::cmd_read, channels);
+        self.add_command("source", commands::cmd_source);
+
         // Populate the environment variable.
-        // TODO: Really should be a "linked" variable, where sets to it are tracked and
-        // written back to the environment.
-        interp.populate_env();
+        self.populate_env();
+    }
+
+    /// Replaces the stream written by the `stdout` channel, returning the previous stream,
+    /// or None if the I/O commands haven't been added or `stdout` has been closed.  The
+    /// test harness uses this to capture a test's output.
+    #[cfg(feature = "os")]
+    pub(crate) fn replace_stdout(&mut self, stream: Box<dyn Write>) -> Option<Box<dyn Write>> {
+        let id = self.channels?;
+        let table = self
+            .context_map
+            .get_mut(&id)?
+            .data
+            .downcast_mut::<ChannelTable>()?;
+        table.replace_writer("stdout", stream)
+    }
 
+    /// Creates a new safe Molt interpreter: one that is pre-populated with the standard Molt
+    /// commands, less those that can access the filesystem or otherwise affect the
+    /// process.  See [`make_safe`](#method.make_safe) for details.
+    ///
+    /// Use this to evaluate untrusted scripts.
+    ///
+    /// ```
+    /// # use molt::Interp;
+    /// let mut interp = Interp::new_safe();
+    /// assert!(interp.is_safe());
+    /// assert!(!interp.has_command("source"));
+    /// assert!(interp.eval("exit").is_err());
+    /// ```
+    pub fn new_safe() -> Self {
+        let mut interp = Interp::new();
+        interp.make_safe();
         interp
     }
 
-    /// Populates the TCL `env()` array with the process's environment variables.
+    /// Makes the interpreter safe, removing the standard commands that can access the
+    /// filesystem or the process's standard input, or otherwise affect the process:
+    /// `close`, `exit`, `gets`, `glob`, `open`, `read`, and `source`.  The `puts`
+    /// command remains, but can only write to `stdout` and `stderr`, as no other channels
+    /// can be opened.  The `env()` array is unset.  Child interpreters created by a safe
+    /// interpreter are also safe; existing children are unaffected.
+    ///
+    /// Commands the application added itself are retained; it's up to the application
+    /// to decide whether they are safe.
+    ///
+    /// ```
+    /// # use molt::Interp;
+    /// let mut interp = Interp::new();
+    /// interp.make_safe();
+    /// assert!(interp.is_safe());
+    /// assert!(!interp.has_command("open"));
+    /// assert!(interp.has_command("puts"));
+    /// ```
+    pub fn make_safe(&mut self) {
+        for name in UNSAFE_COMMANDS {
+            if self.has_command(name) {
+                self.remove_command(name);
+            }
+        }
+
+        // Unsetting env() also breaks its link to the process environment.
+        self.scopes.unset("env");
+        self.safe = true;
+    }
+
+    /// Returns true if this is a safe interpreter, i.e., one created by
+    /// [`new_safe`](#method.new_safe) or made safe by [`make_safe`](#method.make_safe),
+    /// and false otherwise.
+    pub fn is_safe(&self) -> bool {
+        self.safe
+    }
+
+    /// Links the `env()` array to the process environment: the array is repopulated from
+    /// the environment, sets and unsets of `env` elements are written back to the
+    /// environment, and reads of `env` elements see changes made from Rust.  Does nothing
+    /// if the interpreter is safe.
+    ///
+    /// Interpreters don't link `env()` unless the application asks, as any script could
+    /// then change the environment of the whole process.  Child interpreters created by
+    /// `interp create` or [`create_child`](#method.create_child) are never linked; they
+    /// get a copy of the environment.
     ///
-    /// # TCL Liens
+    /// **Note:** the process environment is shared by all threads, and changing it while
+    /// another thread is reading it is unsafe on many platforms.  Applications that link
+    /// `env()` should be single-threaded.
     ///
-    /// Changes to the variable are not mirrored back into the process's environment.
+    /// This method is only available with the `os` feature.
+    ///
+    /// ```
+    /// # use molt::Interp;
+    /// let mut interp = Interp::new();
+    /// interp.link_env();
+    /// interp.eval("set env(MOLT_LINK_ENV_EXAMPLE) 1").unwrap();
+    /// assert_eq!(std::env::var("MOLT_LINK_ENV_EXAMPLE").unwrap(), "1");
+    /// # std::env::remove_var("MOLT_LINK_ENV_EXAMPLE");
+    /// ```
+    #[cfg(feature = "os")]
+    pub fn link_env(&mut self) {
+        if !self.safe {
+            self.populate_env();
+            self.scopes.link_env();
+        }
+    }
+
+    /// Populates the TCL `env()` array with the process's environment variables.
+    /// Variables whose names or values aren't valid Unicode are skipped.
+    #[cfg(feature = "os")]
     fn populate_env(&mut self) {
-        for (key, value) in std::env::vars() {
+        for (key, value) in crate::scope::env_vars() {
             // Drop the result, as there's no good reason for this to ever throw an error.
             let _ = self.set_element("env", &key, value.into());
         }
@@ -805,11 +1363,59 @@ impl Interp {
     ///
     /// [`Value`]: ../value/index.html
     pub fn eval_value(&mut self, value: &Value) -> MoltResult {
-        // TODO: Could probably do better, here.  If the value is already a list, for
-        // example, can maybe evaluate it as a command without using as_script().
-        // Tricky, though.  Don't want to have to parse it as a list.  Need a quick way
-        // to determine if something is already a list.  (Might need two methods!)
+        // FIRST, check the number of nesting levels and the command and time limits.
+        self.step()?;
+        self.num_levels += 1;
+
+        if self.num_levels > self.recursion_limit {
+            self.num_levels -= 1;
+            return molt_err!("too many nested calls to Interp::eval (infinite loop?)");
+        }
+
+        // NEXT, evaluate the value and translate the result to Ok or Error.  If the value
+        // is a pure list, i.e., has no string rep, its elements are the words of a single
+        // command; there's no need to parse it.
+        let result = match value.as_evaluable() {
+            Ok(Evaluable::Script(script)) if self.coverage.is_some() => {
+                self.eval_script_covered(value, &script)
+            }
+            Ok(Evaluable::Script(script)) => self.eval_script(&script),
+            Ok(Evaluable::Command(words)) => self.eval_words(words),
+            Err(exception) => Err(exception),
+        };
+
+        // NEXT, decrement the number of nesting levels.
+        self.num_levels -= 1;
+
+        self.complete_eval(result)
+    }
 
+    /// Calls a command given its words, the command name followed by its arguments,
+    /// returning the result as for [`eval`](#method.eval).  The command is looked up and
+    /// executed directly, just as `eval` executes each command in a script once its words
+    /// have been substituted; the words are not parsed or substituted themselves.  This is
+    /// the safe way to call a command with pre-built arguments that might contain special
+    /// characters, e.g., a callback built from a command prefix and some data.  An empty
+    /// list of words is a no-op that yields the empty string.
+    ///
+    /// If the command isn't found, the `unknown` command is called, if defined, as for
+    /// `eval`; otherwise, the result is an `invalid command name` error.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use molt::types::*;
+    /// # use molt::Interp;
+    /// # fn dummy() -> MoltResult {
+    /// let mut interp = Interp::new();
+    ///
+    /// let argv = vec![Value::from("set"), Value::from("a"), Value::from("{$b [c]")];
+    /// let result = interp.call(&argv)?;
+    /// assert_eq!(result.as_str(), "{$b [c]");
+    /// # Ok(result)
+    /// # }
+    /// ```
+    pub fn call(&mut self, argv: &[Value]) -> MoltResult {
         // FIRST, check the number of nesting levels
         self.num_levels += 1;
 
@@ -818,14 +1424,30 @@ impl Interp {
             return molt_err!("too many nested calls to Interp::eval (infinite loop?)");
         }
 
-        // NEXT, evaluate the script and translate the result to Ok or Error
-        let mut result = self.eval_script(&*value.as_script()?);
+        // NEXT, execute the command.
+        let result = if argv.is_empty() {
+            Ok(Value::empty())
+        } else {
+            self.eval_command(MoltRc::new(argv.to_vec()), 1)
+        };
 
-        // NEXT, decrement the number of nesting levels.
         self.num_levels -= 1;
 
-        // NEXT, translate and return the result.
+        self.complete_eval(result)
+    }
+
+    /// Completes a call to `eval_value` or `call` once the nesting level has been
+    /// decremented: at the top level, translates the result to Ok or Error, and saves
+    /// the error data of any error.
+    fn complete_eval(&mut self, mut result: MoltResult) -> MoltResult {
         if self.num_levels == 0 {
+            // Debugging resumes afresh with the next evaluation.
+            self.debug_step = None;
+            if self.debug_aborted {
+                self.debug_aborted = false;
+                self.schedule_check();
+            }
+
             if let Err(mut exception) = result {
                 // FIRST, handle the return -code, -level protocol
                 if exception.code() == ResultCode::Return {
@@ -838,8 +1460,7 @@ impl Interp {
                     ResultCode::Return => Err(exception), // -level > 0
                     ResultCode::Break => molt_err!("invoked \"break\" outside of a loop"),
                     ResultCode::Continue => molt_err!("invoked \"continue\" outside of a loop"),
-                    // TODO: Better error message
-                    ResultCode::Other(_) => molt_err!("unexpected result code."),
+                    ResultCode::Other(code) => molt_err!("command returned bad code: {}", code),
                 };
             }
         }
@@ -853,112 +1474,404 @@ impl Interp {
         result
     }
 
-    /// Saves the error exception data
-    fn set_global_error_data(&mut self, error_data: Option<&ErrorData>) -> Result<(), Exception> {
-        if let Some(data) = error_data {
-            // TODO: Might want a public method for this.  Or, if I implement namespaces, that's
-            // sufficient.
-            self.scopes.set_global("errorInfo", data.error_info())?;
-            self.scopes.set_global("errorCode", data.error_code())?;
-        }
-
-        Ok(())
+    /// Evaluates a list of words as a single command, returning the result as for
+    /// [`eval`](#method.eval).  The words are used as is: they are not subject
+    /// to further substitution, which makes this the safe way to call a command whose
+    /// arguments might contain special characters, e.g., a callback built from a
+    /// command prefix and some data.  An empty list is a no-op that yields the empty
+    /// string.
+    ///
+    /// This is equivalent to [`call`](#method.call).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use molt::types::*;
+    /// # use molt::Interp;
+    /// # fn dummy() -> MoltResult {
+    /// let mut interp = Interp::new();
+    ///
+    /// let words = vec![Value::from("set"), Value::from("a"), Value::from("$b [c]")];
+    /// let result = interp.eval_list(&words)?;
+    /// assert_eq!(result.as_str(), "$b [c]");
+    /// # Ok(result)
+    /// # }
+    /// ```
+    pub fn eval_list(&mut self, words: &[Value]) -> MoltResult {
+        self.call(words)
     }
 
-    /// Evaluates a parsed Script, producing a normal MoltResult.
-    /// Also used by expr.rs.
-    pub(crate) fn eval_script(&mut self, script: &Script) -> MoltResult {
-        let mut result_value = Value::empty();
-
-        for word_vec in script.commands() {
-            let words = self.eval_word_vec(word_vec.words())?;
+    /// Reads a script from a file and evaluates it, as for [`eval`](#method.eval), returning
+    /// the `Value` of the last command in the file, or any error thrown by it.  This is how
+    /// the `source` command and the Molt shell run script files.
+    ///
+    /// While the file is being evaluated its path is available as the
+    /// [`script_file`](#method.script_file).  If the file can't be read, the error message
+    /// is `couldn't read file "path": reason`; if the script throws an error, a
+    /// `(file "path" line N)` frame is added to its stack trace.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use molt::types::*;
+    /// # use molt::Interp;
+    /// use std::path::Path;
+    ///
+    /// let mut interp = Interp::new();
+    ///
+    /// let result = interp.eval_file(Path::new("nonesuch.tcl"));
+    /// assert!(result.unwrap_err().value().as_str()
+    ///     .starts_with("couldn't read file \"nonesuch.tcl\": "));
+    /// ```
+    pub fn eval_file(&mut self, path: &Path) -> MoltResult {
+        let name = path.display().to_string();
 
-            if words.is_empty() {
-                break;
-            }
+        let script = match fs::read_to_string(path) {
+            Ok(script) => script,
+            Err(e) => return molt_err!("couldn't read file \"{}\": {}", name, e),
+        };
 
-            let name = words[0].as_str();
-
-            if let Some(cmd) = self.commands.get(name) {
-                // let start = Instant::now();
-                let cmd = Rc::clone(cmd);
-                let result = cmd.execute(self, words.as_slice());
-                // self.profile_save(&format!("cmd.execute({})", name), start);
-
-                if let Ok(v) = result {
-                    result_value = v;
-                } else if let Err(mut exception) = result {
-                    // TODO: I think this needs to be done up above.
-                    // // Handle the return -code, -level protocol
-                    // if exception.code() == ResultCode::Return {
-                    //     exception.decrement_level();
-                    // }
-
-                    match exception.code() {
-                        // ResultCode::Okay => result_value = exception.value(),
-                        ResultCode::Error => {
-                            // FIRST, new error, an error from within a proc, or an error from
-                            // within some other body (ignored).
-                            if exception.is_new_error() {
-                                exception.add_error_info("    while executing");
-                            } else if cmd.is_proc() {
-                                exception.add_error_info("    invoked from within");
-                                exception.add_error_info(&format!(
-                                    "    (procedure \"{}\" line TODO)",
-                                    name
-                                ));
-                            } else {
-                                return Err(exception);
-                            }
-
-                            // TODO: Add command.  In standard TCL, this is the text of the command
-                            // before interpolation; at present, we don't have that info in a
-                            // convenient form.  For now, just convert the final words to a string.
-                            exception.add_error_info(&format!("\"{}\"", &list_to_string(&words)));
-                            return Err(exception);
-                        }
-                        _ => return Err(exception),
-                    }
-                } else {
-                    unreachable!();
-                }
-            } else {
-                return molt_err!("invalid command name \"{}\"", name);
-            }
+        if let Some(coverage) = self.coverage.as_mut() {
+            coverage.add_file(&name, &script);
         }
 
-        Ok(result_value)
-    }
+        let saved = self.script_file.replace(name.clone());
+        let result = self.eval(&script);
+        self.script_file = saved;
 
-    /// Evaluates a WordVec, producing a list of Values.  The expansion operator is handled
-    /// as a special case.
-    fn eval_word_vec(&mut self, words: &[Word]) -> Result<MoltList, Exception> {
-        let mut list: MoltList = Vec::new();
+        match result {
+            Err(mut exception) if exception.is_error() => {
+                let line = exception.error_data().map_or(0, |data| data.error_line());
+                exception.add_error_info(&format!("    (file \"{}\" line {})", name, line));
 
-        for word in words {
-            if let Word::Expand(word_to_expand) = word {
-                let value = self.eval_word(word_to_expand)?;
-                for val in &*value.as_list()? {
-                    list.push(val.clone());
+                // At the top level, `eval` has already saved the error data.
+                if self.num_levels == 0 {
+                    self.set_global_error_data(exception.error_data())?;
                 }
-            } else {
-                list.push(self.eval_word(word)?);
+                Err(exception)
             }
+            result => result,
         }
-
-        Ok(list)
     }
 
-    /// Evaluates a single word, producing a value.  This is also used by expr.rs.
-    pub(crate) fn eval_word(&mut self, word: &Word) -> MoltResult {
-        match word {
+    /// Returns the path of the script file currently being evaluated by
+    /// [`eval_file`](#method.eval_file), e.g., by the `source` command, or `None` if
+    /// no file is being evaluated.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use molt::Interp;
+    /// let interp = Interp::new();
+    /// assert_eq!(interp.script_file(), None);
+    /// ```
+    pub fn script_file(&self) -> Option<&str> {
+        self.script_file.as_deref()
+    }
+
+    /// Returns the number of commands currently being executed, i.e., the frame number
+    /// of the innermost command.  Commands are numbered from 1, the outermost command.
+    /// This is the value returned by `info frame`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use molt::Interp;
+    /// let mut interp = Interp::new();
+    /// assert_eq!(interp.frame_count(), 0);
+    /// assert_eq!(interp.eval("info frame").unwrap().as_str(), "1");
+    /// ```
+    pub fn frame_count(&self) -> usize {
+        self.frames.len()
+    }
+
+    /// Returns a dictionary describing the command with the given frame number, as for
+    /// `info frame` *number*.  If the number is greater than zero it is an absolute frame
+    /// number, counting from 1, the outermost command; otherwise it is relative to
+    /// the innermost command, which is frame 0.  Returns an error if there is no such frame.
+    ///
+    /// The dictionary contains the following keys:
+    ///
+    /// * `type`: `proc` if the command is in the body of a procedure, and `eval` otherwise.
+    /// * `cmd`: The command, after substitution, as a list.
+    /// * `line`: The command's line number within its script.
+    /// * `level`: The scope level at which the command is executing, 0 for the global scope.
+    /// * `proc`: The name of the procedure, if the `type` is `proc`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use molt::Interp;
+    /// # use molt::types::*;
+    /// # fn dummy() -> MoltResult {
+    /// let mut interp = Interp::new();
+    /// let info = interp.eval("proc myproc {x} { info frame 0 }; myproc 1")?;
+    /// assert_eq!(info.as_str(), "type proc cmd {info frame 0} line 1 level 1 proc myproc");
+    /// # Ok(info)
+    /// # }
+    /// ```
+    pub fn frame_info(&self, number: MoltInt) -> MoltResult {
+        let count = self.frames.len() as MoltInt;
+        let index = if number > 0 {
+            number - 1
+        } else {
+            count - 1 + number
+        };
+
+        if index < 0 || index >= count {
+            return molt_err!("bad level \"{}\"", number);
+        }
+
+        let index = index as usize;
+        let frame = &self.frames[index];
+        let mut info = dict_new();
+
+        // FIRST, find the call to the procedure whose body contains the command, if any.
+        let caller = self.frames[..index]
+            .iter()
+            .rev()
+            .find(|caller| caller.level < frame.level)
+            .filter(|caller| caller.is_proc && caller.level + 1 == frame.level);
+
+        let frame_type = if caller.is_some() { "proc" } else { "eval" };
+        info.insert("type".into(), frame_type.into());
+        info.insert("cmd".into(), Value::from(&frame.words[..]));
+        info.insert("line".into(), Value::from(frame.line as MoltInt));
+        info.insert("level".into(), Value::from(frame.level as MoltInt));
+
+        if let Some(caller) = caller {
+            info.insert("proc".into(), caller.words[0].clone());
+        }
+
+        molt_ok!(info)
+    }
+
+    /// Saves the error exception data
+    fn set_global_error_data(&mut self, error_data: Option<&ErrorData>) -> Result<(), Exception> {
+        if let Some(data) = error_data {
+            // TODO: Might want a public method for this.  Or, if I implement namespaces, that's
+            // sufficient.
+            self.scopes.set_global("errorInfo", data.error_info())?;
+            self.scopes.set_global("errorCode", data.error_code())?;
+        }
+
+        Ok(())
+    }
+
+    /// Evaluates a parsed Script, producing a normal MoltResult.
+    /// Also used by expr.rs.
+    pub(crate) fn eval_script(&mut self, script: &Script) -> MoltResult {
+        let mut result_value = Value::empty();
+
+        for word_vec in script.commands() {
+            // The word list is recycled on every path; eval_command recycles it itself.
+            let mut words = self.spare_words.pop().unwrap_or_default();
+            let list = MoltRc::get_mut(&mut words).expect("spare word list is shared");
+            let substituted = self.eval_word_vec_into(word_vec.words(), list);
+
+            if substituted.is_err() || words.is_empty() {
+                self.recycle_words(words);
+                substituted?;
+                break;
+            }
+
+            if let Some(coverage) = self.coverage.as_mut() {
+                coverage.hit(word_vec.line());
+            }
+
+            result_value = self.eval_command(words, word_vec.line())?;
+        }
+
+        Ok(result_value)
+    }
+
+    /// Evaluates a parsed Script, given the value from which it was parsed, counting its
+    /// commands for coverage.  This is kept out of `eval_value` so as not to enlarge its
+    /// stack frame, which limits the depth of nested evaluation.
+    #[inline(never)]
+    fn eval_script_covered(&mut self, value: &Value, script: &Script) -> MoltResult {
+        if let Some(coverage) = self.coverage.as_mut() {
+            coverage.enter(value.as_str());
+        }
+        let result = self.eval_script(script);
+        self.resume_coverage();
+        result
+    }
+
+    /// Evaluates a list of words as a single command; an empty list is a no-op.
+    fn eval_words(&mut self, words: MoltRc<MoltList>) -> MoltResult {
+        if words.is_empty() {
+            return Ok(Value::empty());
+        }
+
+        self.eval_command(words, 1)
+    }
+
+    /// Executes a single command, given its words, which must not be empty; `line` is the
+    /// command's line number within its script.  Also adds the command to the error info
+    /// of any error it throws.
+    fn eval_command(&mut self, words: MoltRc<MoltList>, line: usize) -> MoltResult {
+        self.step()?;
+
+        let name = words[0].as_str();
+        let cmd = self.command(name).map(Rc::clone);
+        let is_proc = match &cmd {
+            Some(cmd) => cmd.is_proc(),
+            None => false,
+        };
+
+        self.frames.push(Frame {
+            words: MoltRc::clone(&words),
+            level: self.scopes.current(),
+            line,
+            is_proc,
+        });
+
+        if let Some(trace) = self.exec_trace.as_mut() {
+            trace(words.as_slice(), self.frames.len());
+        }
+
+        let result = if self.debug_hook.is_some() && self.debug_pause(&words) == DebugAction::Abort
+        {
+            molt_err!("evaluation aborted by debugger")
+        } else if let Some(cmd) = cmd {
+            // let start = self.now().unwrap_or_default();
+            if self.exec_traces.is_empty() || self.in_exec_trace {
+                cmd.execute(self, words.as_slice())
+            } else {
+                self.execute_traced(&cmd, words.as_slice())
+            }
+            // self.profile_save(&format!("cmd.execute({})", name), start);
+        } else if let Some(result) = self.call_unknown(&words) {
+            result
+        } else {
+            self.frames.pop();
+            let result = molt_err!("invalid command name \"{}\"", name);
+            self.recycle_words(words);
+            return result;
+        };
+
+        self.frames.pop();
+
+        let result = match result {
+            // TODO: I think this needs to be done up above.
+            // // Handle the return -code, -level protocol
+            // if exception.code() == ResultCode::Return {
+            //     exception.decrement_level();
+            // }
+            Err(exception) if exception.code() == ResultCode::Error => {
+                Self::add_command_error_info(exception, &words, line, is_proc)
+            }
+            result => result,
+        };
+
+        self.recycle_words(words);
+        result
+    }
+
+    /// Saves a finished command's word list for reuse, if nothing else refers to it.  The
+    /// number of spare lists is limited, as is the size of a list that's kept.
+    fn recycle_words(&mut self, mut words: MoltRc<MoltList>) {
+        const MAX_SPARES: usize = 64;
+        const MAX_CAPACITY: usize = 64;
+
+        if self.spare_words.len() < MAX_SPARES {
+            if let Some(list) = MoltRc::get_mut(&mut words) {
+                if list.capacity() <= MAX_CAPACITY {
+                    list.clear();
+                    self.spare_words.push(words);
+                }
+            }
+        }
+    }
+
+    /// Adds a command to the error info of an error it threw, given the command's words
+    /// and line number, and whether it's a procedure.  This is kept out of `eval_command`
+    /// so as not to enlarge its stack frame, which limits the depth of nested evaluation.
+    #[cold]
+    #[inline(never)]
+    fn add_command_error_info(
+        mut exception: Exception,
+        words: &[Value],
+        line: usize,
+        is_proc: bool,
+    ) -> MoltResult {
+        // FIRST, get the line within the procedure body at which the
+        // error occurred, if this is a procedure call.
+        let body_line = exception.error_data().map_or(0, |data| data.error_line());
+
+        // NEXT, the error occurred in this command, as far as this script
+        // is concerned.
+        exception.set_error_line(line);
+
+        // NEXT, new error, an error from within a proc, or an error from
+        // within some other body (ignored).
+        if exception.is_new_error() {
+            exception.add_error_info("    while executing");
+        } else if is_proc {
+            exception.add_error_info(&format!(
+                "    (procedure \"{}\" line {})",
+                words[0], body_line
+            ));
+            exception.add_error_info("    invoked from within");
+        } else {
+            return Err(exception);
+        }
+
+        // TODO: Add command.  In standard TCL, this is the text of the command
+        // before interpolation; at present, we don't have that info in a
+        // convenient form.  For now, just convert the final words to a string.
+        exception.add_error_info(&format!("\"{}\"", &list_to_string(words)));
+        Err(exception)
+    }
+
+    /// Evaluates a WordVec, producing a list of Values.  The expansion operator is handled
+    /// as a special case.
+    fn eval_word_vec(&mut self, words: &[Word]) -> Result<MoltList, Exception> {
+        let mut list: MoltList = Vec::new();
+        self.eval_word_vec_into(words, &mut list)?;
+        Ok(list)
+    }
+
+    /// Evaluates a WordVec, as for `eval_word_vec`, appending the Values to the list.
+    fn eval_word_vec_into(&mut self, words: &[Word], list: &mut MoltList) -> Result<(), Exception> {
+        for word in words {
+            if let Word::Expand(word_to_expand) = word {
+                let value = self.eval_word(word_to_expand)?;
+                for val in &*value.as_list()? {
+                    list.push(val.clone());
+                }
+            } else {
+                list.push(self.eval_word(word)?);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Evaluates a command substitution.  Command substitutions recurse on the Rust stack
+    /// just as nested evals do, so they count against the recursion limit.  This is
+    /// also used by expr.rs.
+    pub(crate) fn eval_subst(&mut self, script: &Script) -> MoltResult {
+        if !self.enter_level() {
+            return molt_err!("too many nested calls to Interp::eval (infinite loop?)");
+        }
+
+        let result = self.eval_script(script);
+        self.leave_level();
+        result
+    }
+
+    /// Evaluates a single word, producing a value.  This is also used by expr.rs.
+    pub(crate) fn eval_word(&mut self, word: &Word) -> MoltResult {
+        match word {
             Word::Value(val) => Ok(val.clone()),
             Word::VarRef(name) => self.scalar(name),
             Word::ArrayRef(name, index_word) => {
                 let index = self.eval_word(index_word)?;
                 self.element(name, index.as_str())
             }
-            Word::Script(script) => self.eval_script(script),
+            Word::Script(script) => self.eval_subst(script),
             Word::Tokens(tokens) => {
                 let tlist = self.eval_word_vec(tokens)?;
                 let string: String = tlist.iter().map(|i| i.as_str()).collect();
@@ -974,13 +1887,22 @@ impl Interp {
     pub(crate) fn return_options(&self, result: &MoltResult) -> Value {
         let mut opts = dict_new();
 
+        // FIRST, include any non-standard options given to `return`, as Standard TCL does.
+        if let Err(exception) = result {
+            if let Some(extra) = exception.extra_options() {
+                for (opt, val) in extra {
+                    opts.insert(opt.clone(), val.clone());
+                }
+            }
+        }
+
         match result {
             Ok(_) => {
                 opts.insert(OPT_CODE.into(), ZERO.into());
                 opts.insert(OPT_LEVEL.into(), ZERO.into());
             }
             Err(exception) => {
-                // FIRST, set the -code
+                // NEXT, set the -code
                 match exception.code() {
                     ResultCode::Okay => unreachable!(), // TODO: Not in use yet
                     ResultCode::Error => {
@@ -988,7 +1910,11 @@ impl Interp {
                         opts.insert(OPT_CODE.into(), "1".into());
                         opts.insert(OPT_ERRORCODE.into(), data.error_code());
                         opts.insert(OPT_ERRORINFO.into(), data.error_info());
-                        // TODO: Standard TCL also sets -errorstack, -errorline.
+                        opts.insert(
+                            OPT_ERRORLINE.into(),
+                            Value::from(data.error_line() as MoltInt),
+                        );
+                        // TODO: Standard TCL also sets -errorstack.
                     }
                     ResultCode::Return => {
                         opts.insert(OPT_CODE.into(), exception.next_code().as_int().into());
@@ -1033,7 +1959,62 @@ impl Interp {
     /// ```
 
     pub fn complete(&mut self, script: &str) -> bool {
-        parser::parse(script).is_ok()
+        self.check_complete(script) == CompleteStatus::Complete
+    }
+
+    /// Determines whether or not the script is syntactically complete, as for
+    /// [`complete`](#method.complete).  If the script is incomplete, the result gives the
+    /// kind of the innermost unterminated brace, bracket, or quote, and the byte offset in
+    /// the script at which it was opened.
+    ///
+    /// Editors and REPLs use this to explain what input is still expected.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use molt::interp::Interp;
+    /// use molt::parser::CompleteStatus;
+    /// use molt::parser::IncompleteKind;
+    ///
+    /// let mut interp = Interp::new();
+    /// assert_eq!(interp.check_complete("set a [expr {1+1}]"), CompleteStatus::Complete);
+    /// assert_eq!(
+    ///     interp.check_complete("set a [expr 1+1"),
+    ///     CompleteStatus::Incomplete { kind: IncompleteKind::Bracket, open_at: 6 }
+    /// );
+    /// ```
+    pub fn check_complete(&mut self, script: &str) -> CompleteStatus {
+        parser::check_complete(script)
+    }
+
+    /// Determines whether or not the script is syntactically complete, returning `None` if
+    /// it is and a brief description of the problem if it isn't, e.g.,
+    /// "unmatched open brace".  The script is complete, as for
+    /// [`complete`](#method.complete), exactly when this method returns `None`.
+    ///
+    /// REPLs use this to explain why a script can't be evaluated.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use molt::types::*;
+    /// # use molt::interp::Interp;
+    /// let mut interp = Interp::new();
+    /// assert_eq!(interp.complete_reason("set a [expr {1+1}]"), None);
+    /// assert_eq!(interp.complete_reason("set a [expr 1+1"), Some("unmatched open bracket"));
+    /// ```
+    pub fn complete_reason(&mut self, script: &str) -> Option<&'static str> {
+        let exception = parser::parse(script).err()?;
+
+        Some(match exception.value().as_str() {
+            "missing close-brace" => "unmatched open brace",
+            "missing close-bracket" => "unmatched open bracket",
+            "missing \"" => "missing close quote",
+            "missing close-brace for variable name" => "unmatched open brace in variable name",
+            "extra characters after close-brace" => "extra characters after close-brace",
+            "extra characters after close-quote" => "extra characters after close-quote",
+            _ => "syntax error",
+        })
     }
 
     /// Evaluates a [Molt expression](https://wduquette.github.io/molt/ref/expr.html) and
@@ -1083,7 +2064,13 @@ impl Interp {
     /// # }
     /// ```
     pub fn expr_bool(&mut self, expr: &Value) -> Result<bool, Exception> {
-        self.expr(expr)?.as_bool()
+        let result = expr::expr_as_bool(self, expr);
+
+        if let Err(exception) = &result {
+            self.set_global_error_data(exception.error_data())?;
+        }
+
+        result
     }
 
     /// Evaluates a [Molt expression](https://wduquette.github.io/molt/ref/expr.html)
@@ -1106,7 +2093,13 @@ impl Interp {
     /// # }
     /// ```
     pub fn expr_int(&mut self, expr: &Value) -> Result<MoltInt, Exception> {
-        self.expr(expr)?.as_int()
+        let result = expr::expr_as_int(self, expr);
+
+        if let Err(exception) = &result {
+            self.set_global_error_data(exception.error_data())?;
+        }
+
+        result
     }
 
     /// Evaluates a [Molt expression](https://wduquette.github.io/molt/ref/expr.html)
@@ -1129,7 +2122,13 @@ impl Interp {
     /// # }
     /// ```
     pub fn expr_float(&mut self, expr: &Value) -> Result<MoltFloat, Exception> {
-        self.expr(expr)?.as_float()
+        let result = expr::expr_as_float(self, expr);
+
+        if let Err(exception) = &result {
+            self.set_global_error_data(exception.error_data())?;
+        }
+
+        result
     }
 
     //--------------------------------------------------------------------------------------------
@@ -1175,7 +2174,30 @@ impl Interp {
         }
     }
 
-    /// Returns 1 if the named variable is defined and exists, and 0 otherwise.
+    /// Returns true if the named variable exists, and false otherwise.  The `var_name` may
+    /// name a scalar variable, an array variable, or an array element.  An array exists
+    /// as soon as it's created, e.g., by `array set a {}`, even if it has no elements,
+    /// and continues to exist after its last element is unset; thus, for a whole array this
+    /// is equivalent to [`array_exists`](#method.array_exists).  An array element exists only
+    /// if it's been set; an "element" of a scalar never exists.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use molt::Interp;
+    /// # use molt::types::*;
+    /// # use molt::molt_ok;
+    /// # fn dummy() -> MoltResult {
+    /// # let mut interp = Interp::new();
+    /// interp.eval("array set a {}; set b(1) x")?;
+    ///
+    /// assert!(interp.var_exists(&Value::from("a")));
+    /// assert!(!interp.var_exists(&Value::from("a(1)")));
+    /// assert!(interp.var_exists(&Value::from("b(1)")));
+    /// assert!(!interp.var_exists(&Value::from("c")));
+    /// # molt_ok!()
+    /// # }
+    /// ```
     pub fn var_exists(&self, var_name: &Value) -> bool {
         let var_name = &*var_name.as_var_name();
         match var_name.index() {
@@ -1257,6 +2279,58 @@ impl Interp {
         }
     }
 
+    /// Retrieves the value of the named variable in the current scope as a string.  This is
+    /// a convenience wrapper around [`var`](#method.var) for host code that deals only in
+    /// strings; the `var_name` may name a scalar variable or an array element.
+    ///
+    /// Returns an error if the variable is not found, or if it is an array variable.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use molt::types::*;
+    /// use molt::Interp;
+    /// use molt::molt_ok;
+    /// # fn dummy() -> MoltResult {
+    /// let mut interp = Interp::new();
+    ///
+    /// interp.eval("set a 1; set b(1) Howdy")?;
+    /// assert_eq!(*interp.var_str("a")?, "1");
+    /// assert_eq!(*interp.var_str("b(1)")?, "Howdy");
+    /// # molt_ok!()
+    /// # }
+    /// ```
+    pub fn var_str(&self, var_name: &str) -> Result<MoltRc<String>, Exception> {
+        let value = self.var(&Value::from(var_name))?;
+        Ok(MoltRc::new(value.as_str().to_string()))
+    }
+
+    /// Sets the value of the named variable in the current scope to a string.  This is a
+    /// convenience wrapper around [`set_var`](#method.set_var) for host code that deals
+    /// only in strings; the `var_name` may name a scalar variable or an array element.
+    ///
+    /// Returns an error if the variable is scalar and the name names an array element,
+    /// and vice-versa.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use molt::types::*;
+    /// use molt::Interp;
+    /// use molt::molt_ok;
+    /// # fn dummy() -> MoltResult {
+    /// let mut interp = Interp::new();
+    ///
+    /// interp.set_var_str("a", "1")?;
+    /// interp.set_var_str("b(1)", "Howdy")?;
+    /// assert_eq!(interp.eval("list $a $b(1)")?.as_str(), "1 Howdy");
+    /// # molt_ok!()
+    /// # }
+    /// ```
+    pub fn set_var_str(&mut self, var_name: &str, value: &str) -> Result<(), Exception> {
+        self.set_var(&Value::from(var_name), Value::from(value))
+    }
+
     /// Retrieves the value of the named scalar variable in the current scope.
     ///
     /// Returns an error if the variable is not found, or if the variable is an array variable.
@@ -1411,7 +2485,8 @@ impl Interp {
     /// arrays this is the name of the array proper, e.g., `myArray`, not the name of an
     /// element, e.g., `myArray(1)`.
     ///
-    /// It is _not_ an error to unset a variable that doesn't exist.
+    /// It is _not_ an error to unset a variable that doesn't exist.  Returns true if a
+    /// variable was unset, and false if there was no such variable.
     ///
     /// # Example
     ///
@@ -1425,18 +2500,20 @@ impl Interp {
     /// interp.set_scalar("a", Value::from("1"))?;
     /// interp.set_element("b", "1", Value::from("2"))?;
     ///
-    /// interp.unset("a"); // Unset scalar
-    /// interp.unset("b"); // Unset entire array
+    /// assert!(interp.unset("a")); // Unset scalar
+    /// assert!(interp.unset("b")); // Unset entire array
+    /// assert!(!interp.unset("a")); // No such variable
     /// # molt_ok!()
     /// # }
     /// ```
-    pub fn unset(&mut self, name: &str) {
-        self.scopes.unset(name);
+    pub fn unset(&mut self, name: &str) -> bool {
+        self.scopes.unset(name)
     }
 
     /// Unsets the value of the named variable or array element in the current scope.
     ///
-    /// It is _not_ an error to unset a variable that doesn't exist.
+    /// It is _not_ an error to unset a variable that doesn't exist.  Returns true if a
+    /// variable or element was unset, and false if there was no such variable or element.
     ///
     /// # Example
     ///
@@ -1457,19 +2534,20 @@ impl Interp {
     /// # molt_ok!()
     /// # }
     /// ```
-    pub fn unset_var(&mut self, name: &Value) {
+    pub fn unset_var(&mut self, name: &Value) -> bool {
         let var_name = name.as_var_name();
 
         if let Some(index) = var_name.index() {
-            self.unset_element(var_name.name(), index);
+            self.unset_element(var_name.name(), index)
         } else {
-            self.unset(var_name.name());
+            self.unset(var_name.name())
         }
     }
 
     /// Unsets a single element in an array given the array name and index.
     ///
-    /// It is _not_ an error to unset an array element that doesn't exist.
+    /// It is _not_ an error to unset an array element that doesn't exist.  Returns true if
+    /// an element was unset, and false if there was no such element.
     ///
     /// # Example
     ///
@@ -1486,8 +2564,8 @@ impl Interp {
     /// # molt_ok!()
     /// # }
     /// ```
-    pub fn unset_element(&mut self, array_name: &str, index: &str) {
-        self.scopes.unset_element(array_name, index);
+    pub fn unset_element(&mut self, array_name: &str, index: &str) -> bool {
+        self.scopes.unset_element(array_name, index)
     }
 
     /// Gets a list of the names of the variables that are visible in the current scope.
@@ -1557,6 +2635,13 @@ impl Interp {
         self.scopes.upvar(level, name);
     }
 
+    /// Links the variable `local` in the current scope to the variable `name` in the given
+    /// scope.  This is used to implement the `variable` command, which links local
+    /// variables to namespace variables.
+    pub(crate) fn upvar_as(&mut self, level: usize, name: &str, local: &str) {
+        self.scopes.upvar_as(level, name, local);
+    }
+
     /// Pushes a variable scope (i.e., a stack level) onto the scope stack.
     ///
     /// Procs use this to define their local scope.  Client code should seldom need to call
@@ -1570,6 +2655,14 @@ impl Interp {
         self.scopes.push();
     }
 
+    /// Pushes a variable scope for the body of a `namespace eval` of the namespace with the
+    /// given fully qualified name: a name that isn't a local variable refers to the
+    /// namespace's variable of that name, e.g., `y` to `::a::y`, unless there's no such
+    /// variable but there's a global variable with the name.
+    pub(crate) fn push_namespace_scope(&mut self, ns: &str) {
+        self.scopes.push_namespace(command_key(ns));
+    }
+
     /// Pops a variable scope (i.e., a stack level) off of the scope stack.  Calls to
     /// `Interp::push_scope` and `Interp::pop_scope` must exist in pairs.
     pub fn pop_scope(&mut self) {
@@ -1691,35 +2784,117 @@ impl Interp {
         self.scopes.array_indices(array_name)
     }
 
-    /// Gets the number of elements in the named array.  Returns 0 if the variable doesn't exist
-    /// (or isn't an array variable).
+    /// Starts a search of the named array's elements, returning the search ID.  This is used
+    /// to implement the `array startsearch` command.
+    pub(crate) fn array_start_search(&mut self, array_name: &str) -> Result<String, Exception> {
+        self.scopes.array_start_search(array_name)
+    }
+
+    /// Returns the next index in the given search of the named array, or `None` if there are
+    /// no more, consuming it if `advance` is true.  This is used to implement the
+    /// `array nextelement` and `array anymore` commands.
+    pub(crate) fn array_search_next(
+        &mut self,
+        array_name: &str,
+        search_id: &str,
+        advance: bool,
+    ) -> Result<Option<Value>, Exception> {
+        self.scopes
+            .array_search_next(array_name, search_id, advance)
+    }
+
+    /// Ends the given search of the named array.  This is used to implement the
+    /// `array donesearch` command.
+    pub(crate) fn array_done_search(
+        &mut self,
+        array_name: &str,
+        search_id: &str,
+    ) -> Result<(), Exception> {
+        self.scopes.array_done_search(array_name, search_id)
+    }
+
+    /// Formats the elements of the named array for display, one line per element, sorted
+    /// by index, e.g., `a(x) = 1`; the element names are padded so that the values line up.
+    /// If a glob pattern is given, only the elements whose indices match it are included.
+    /// This is used to implement the `parray` command.  It's an error if the variable
+    /// isn't an array variable.
     ///
     /// # Example
     ///
     /// ```
     /// use molt::Interp;
     /// use molt::types::*;
-    ///
-    /// # use molt::molt_ok;
     /// # fn dummy() -> MoltResult {
     /// let mut interp = Interp::new();
+    /// interp.eval("array set a {x 1 yy 2}")?;
     ///
-    /// assert_eq!(interp.array_size("a"), 0);
-    ///
-    /// interp.set_element("a", "1", Value::from("xyz"))?;
-    /// assert_eq!(interp.array_size("a"), 1);
-    /// # molt_ok!()
+    /// assert_eq!(interp.array_dump("a", None)?, vec!["a(x)  = 1", "a(yy) = 2"]);
+    /// assert_eq!(interp.array_dump("a", Some("y*"))?, vec!["a(yy) = 2"]);
+    /// # Ok(Value::empty())
     /// # }
     /// ```
-    pub fn array_size(&self, array_name: &str) -> usize {
-        self.scopes.array_size(array_name)
-    }
+    pub fn array_dump(
+        &self,
+        array_name: &str,
+        pattern: Option<&str>,
+    ) -> Result<Vec<String>, Exception> {
+        if !self.array_exists(array_name) {
+            return molt_err!("\"{}\" isn't an array", array_name);
+        }
 
-    //--------------------------------------------------------------------------------------------
-    // Command Definition and Handling
+        let kvlist = self.array_get(array_name);
+        let mut pairs: Vec<&[Value]> = kvlist
+            .chunks(2)
+            .filter(|pair| pattern.is_none_or(|p| util::glob_match(p, pair[0].as_str())))
+            .collect();
+        pairs.sort_by(|a, b| a[0].as_str().cmp(b[0].as_str()));
 
-    /// Adds a binary command with no related context to the interpreter.  This is the normal
-    /// way to add most commands.
+        let names: Vec<String> = pairs
+            .iter()
+            .map(|pair| format!("{}({})", array_name, pair[0]))
+            .collect();
+        let width = names
+            .iter()
+            .map(|name| name.chars().count())
+            .max()
+            .unwrap_or(0);
+
+        Ok(names
+            .iter()
+            .zip(pairs.iter())
+            .map(|(name, pair)| format!("{:<width$} = {}", name, pair[1], width = width))
+            .collect())
+    }
+
+    /// Gets the number of elements in the named array.  Returns 0 if the variable doesn't exist
+    /// (or isn't an array variable).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use molt::Interp;
+    /// use molt::types::*;
+    ///
+    /// # use molt::molt_ok;
+    /// # fn dummy() -> MoltResult {
+    /// let mut interp = Interp::new();
+    ///
+    /// assert_eq!(interp.array_size("a"), 0);
+    ///
+    /// interp.set_element("a", "1", Value::from("xyz"))?;
+    /// assert_eq!(interp.array_size("a"), 1);
+    /// # molt_ok!()
+    /// # }
+    /// ```
+    pub fn array_size(&self, array_name: &str) -> usize {
+        self.scopes.array_size(array_name)
+    }
+
+    //--------------------------------------------------------------------------------------------
+    // Command Definition and Handling
+
+    /// Adds a binary command with no related context to the interpreter.  This is the normal
+    /// way to add most commands.
     ///
     /// If the command needs access to some form of application or context data,
     /// use [`add_context_command`](#method.add_context_command) instead.  See the
@@ -1732,6 +2907,10 @@ impl Interp {
     ///
     /// This is the normal way to add commands requiring application context.  See the
     /// [module level documentation](index.html) for an overview and examples.
+    ///
+    /// # Panics
+    ///
+    /// This call panics if the context ID is unknown.
     pub fn add_context_command(&mut self, name: &str, func: CommandFunc, context_id: ContextID) {
         if context_id != NULL_CONTEXT {
             self.context_map
@@ -1740,8 +2919,11 @@ impl Interp {
                 .increment();
         }
 
-        self.commands
-            .insert(name.into(), Rc::new(Command::Native(func, context_id)));
+        // Release the contexts of the command being replaced, if any.
+        let cmd = Rc::new(Command::Native(func, context_id));
+        if let Some(old_cmd) = self.commands.insert(name.into(), cmd) {
+            self.forget_contexts(&old_cmd);
+        }
     }
 
     /// Adds a procedure to the interpreter.
@@ -1749,25 +2931,358 @@ impl Interp {
     /// This is how to add a Molt `proc` to the interpreter.  The arguments are the same
     /// as for the `proc` command and the `commands::cmd_proc` function.
     ///
-    /// TODO: If this method is ever made public, the parameter list validation done
-    /// in cmd_proc should be moved here.
-    pub(crate) fn add_proc(&mut self, name: &str, parms: &[Value], body: &Value) {
-        let proc = Procedure {
-            parms: parms.to_owned(),
-            body: body.clone(),
-        };
+    /// The name is relative to the current namespace, unless it is fully qualified; and
+    /// the procedure's body is executed in the namespace in which it is defined.  It's an
+    /// error if that namespace doesn't exist.
+    ///
+    /// It's an error if the parameter list is invalid.
+    pub(crate) fn add_proc(
+        &mut self,
+        name: &str,
+        parms: &[Value],
+        body: &Value,
+    ) -> Result<(), Exception> {
+        let qualified = self.qualify_name(name);
+        let ns = namespace_qualifiers(&qualified);
+
+        if !self.namespace_exists(ns) {
+            return molt_err!("can't create procedure \"{}\": unknown namespace", name);
+        }
+
+        let proc = Procedure::new(name, parms, body, ns)?;
+
+        self.commands
+            .insert(command_key(&qualified).into(), Rc::new(Command::Proc(proc)));
+        Ok(())
+    }
+
+    /// Adds an alias to the interpreter.  When the alias is called, the words of the
+    /// `target` command prefix are prepended to its arguments, and the resulting command is
+    /// looked up and executed as usual; thus, if the target command is later redefined,
+    /// the alias calls the new definition.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use molt::Interp;
+    /// use molt::types::*;
+    /// use molt::molt_ok;
+    /// # fn dummy() -> MoltResult {
+    /// let mut interp = Interp::new();
+    ///
+    /// interp.add_alias("greet", vec![Value::from("list"), Value::from("Hello,")]);
+    ///
+    /// let greeting = interp.eval("greet World")?;
+    /// assert_eq!(greeting.as_str(), "Hello, World");
+    /// # molt_ok!()
+    /// # }
+    /// ```
+    pub fn add_alias(&mut self, name: &str, target: MoltList) {
+        self.commands
+            .insert(name.into(), Rc::new(Command::Alias(target)));
+    }
 
+    /// Adds an alias to a command in this interpreter's parent.  When the alias is called,
+    /// the words of the `target` command prefix are prepended to its arguments, and the
+    /// resulting command is executed in the parent, as for [`call`](#method.call).  This is
+    /// how `interp alias` gives a child, e.g., a safe interpreter, controlled access to the
+    /// parent's commands.
+    ///
+    /// The parent is only available while it is evaluating a script in the child via
+    /// `interp eval` or [`eval_in_child`](#method.eval_in_child); at other times, calling
+    /// the alias is an error.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use molt::Interp;
+    /// use molt::types::*;
+    /// use molt::molt_ok;
+    /// # fn dummy() -> MoltResult {
+    /// let mut interp = Interp::new();
+    /// interp.eval("proc greet {name} { return \"Hello, $name\" }")?;
+    ///
+    /// let child = interp.create_child("plugin", true)?;
+    /// child.add_parent_alias("hello", vec![Value::from("greet")]);
+    ///
+    /// let greeting = interp.eval("interp eval plugin {hello World}")?;
+    /// assert_eq!(greeting.as_str(), "Hello, World");
+    /// # molt_ok!()
+    /// # }
+    /// ```
+    pub fn add_parent_alias(&mut self, name: &str, target: MoltList) {
         self.commands
-            .insert(name.into(), Rc::new(Command::Proc(proc)));
+            .insert(name.into(), Rc::new(Command::ParentAlias(target)));
+    }
+
+    /// Returns the target command prefix of the named alias, or an error if the name
+    /// doesn't name an alias.  The alias may be an alias to a command in this interpreter
+    /// or in its parent.
+    pub fn alias_target(&self, name: &str) -> MoltResult {
+        if let Some(cmd) = self.command(name) {
+            if let Command::Alias(target) | Command::ParentAlias(target) = &**cmd {
+                return molt_ok!(Value::from(target.as_slice()));
+            }
+        }
+
+        molt_err!("\"{}\" isn't an alias", name)
+    }
+
+    /// Adds an ensemble command defined at run time to the interpreter, replacing any
+    /// existing command with the same name.  See [`Ensemble`](struct.Ensemble.html) for
+    /// an example.
+    pub fn add_ensemble(&mut self, name: &str, ensemble: Ensemble) {
+        let cmd = Command::Ensemble(ensemble);
+        for context_id in cmd.context_ids() {
+            self.context_map
+                .get_mut(&context_id)
+                .expect("unknown context ID")
+                .increment();
+        }
+
+        // Install the new ensemble before releasing the old command's contexts, so that
+        // contexts shared by the two aren't forgotten.
+        if let Some(old) = self.commands.insert(name.into(), Rc::new(cmd)) {
+            self.forget_contexts(&old);
+        }
+    }
+
+    /// Adds an ensemble command whose subcommands are the binary commands in a table of
+    /// [`Subcommand`](../types/struct.Subcommand.html) structs, like those used with
+    /// [`call_subcommand`](#method.call_subcommand), replacing any existing command with
+    /// the same name.  This is equivalent to creating an
+    /// [`Ensemble`](struct.Ensemble.html) with [`Ensemble::from_table`] and adding it with
+    /// [`add_ensemble`](#method.add_ensemble).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use molt::Interp;
+    /// use molt::types::*;
+    /// use molt::molt_ok;
+    /// # fn dummy() -> MoltResult {
+    /// const GREET: [Subcommand; 2] = [
+    ///     Subcommand("goodbye", cmd_goodbye),
+    ///     Subcommand("hello", cmd_hello),
+    /// ];
+    ///
+    /// let mut interp = Interp::new();
+    /// interp.add_ensemble_from_table("greet", &GREET);
+    ///
+    /// assert_eq!(interp.eval("greet hello")?.as_str(), "Hello");
+    /// # molt_ok!()
+    /// # }
+    /// # fn cmd_hello(_: &mut Interp, _: ContextID, _: &[Value]) -> MoltResult {
+    /// #     molt_ok!("Hello")
+    /// # }
+    /// # fn cmd_goodbye(_: &mut Interp, _: ContextID, _: &[Value]) -> MoltResult {
+    /// #     molt_ok!("Goodbye")
+    /// # }
+    /// ```
+    ///
+    /// [`Ensemble::from_table`]: struct.Ensemble.html#method.from_table
+    pub fn add_ensemble_from_table(&mut self, name: &str, table: &[Subcommand]) {
+        self.add_ensemble(name, Ensemble::from_table(table));
+    }
+
+    /// Returns a copy of the named ensemble command's [`Ensemble`](struct.Ensemble.html),
+    /// or `None` if the command doesn't exist or isn't an ensemble defined at run time.
+    ///
+    /// The copy can be modified and put back using [`add_ensemble`](#method.add_ensemble);
+    /// to add subcommands to a binary ensemble like `info`, use
+    /// [`extend_ensemble`](#method.extend_ensemble).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use molt::Interp;
+    /// use molt::interp::Ensemble;
+    /// use molt::types::*;
+    /// use molt::molt_ok;
+    /// # fn dummy() -> MoltResult {
+    /// let mut interp = Interp::new();
+    ///
+    /// let mut greet = Ensemble::new();
+    /// greet.add_proc("hello", &[], &Value::from("return Hello"))?;
+    /// greet.add_proc("goodbye", &[], &Value::from("return Goodbye"))?;
+    /// interp.add_ensemble("greet", greet);
+    ///
+    /// let mut greet = interp.ensemble("greet").unwrap();
+    /// greet.remove_command("goodbye");
+    /// interp.add_ensemble("greet", greet);
+    ///
+    /// assert_eq!(interp.ensemble("greet").unwrap().command_names(), vec!["hello"]);
+    /// assert!(interp.eval("greet goodbye").is_err());
+    /// # molt_ok!()
+    /// # }
+    /// ```
+    pub fn ensemble(&self, name: &str) -> Option<Ensemble> {
+        if let Command::Ensemble(ensemble) = &**self.command(name)? {
+            Some(ensemble.clone())
+        } else {
+            None
+        }
+    }
+
+    /// Extends an existing ensemble command: retrieves a copy of the named command's
+    /// ensemble, passes it to `update` to add or replace subcommands, and reinstalls the
+    /// result in place of the original command.
+    ///
+    /// The command may also be a binary ensemble command, like the standard `info` or
+    /// `string` commands.  In that case the new ensemble handles the added subcommands
+    /// itself and passes any other subcommand to the original command function.
+    ///
+    /// It's an error if the command doesn't exist, or is a procedure or alias.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use molt::Interp;
+    /// use molt::types::*;
+    /// use molt::molt_ok;
+    /// # fn dummy() -> MoltResult {
+    /// let mut interp = Interp::new();
+    ///
+    /// interp.extend_ensemble("string", |ensemble| {
+    ///     ensemble.add_proc(
+    ///         "shout",
+    ///         &[Value::from("text")],
+    ///         &Value::from("string toupper $text"),
+    ///     )
+    /// })?;
+    ///
+    /// assert_eq!(interp.eval("string shout hello")?.as_str(), "HELLO");
+    /// assert_eq!(interp.eval("string length hello")?.as_int()?, 5);
+    /// # molt_ok!()
+    /// # }
+    /// ```
+    pub fn extend_ensemble<F>(&mut self, name: &str, update: F) -> Result<(), Exception>
+    where
+        F: FnOnce(&mut Ensemble) -> Result<(), Exception>,
+    {
+        let key = match self.find_command_key(name) {
+            Some(key) => key,
+            None => return molt_err!("invalid command name \"{}\"", name),
+        };
+
+        let old = Rc::clone(&self.commands[&key]);
+        let mut ensemble = match &*old {
+            Command::Ensemble(ensemble) => ensemble.clone(),
+            Command::Native(func, context_id) => Ensemble::wrap(*func, *context_id),
+            _ => return molt_err!("\"{}\" isn't an ensemble", name),
+        };
+
+        update(&mut ensemble)?;
+
+        self.add_ensemble(&key, ensemble);
+        Ok(())
+    }
+
+    /// Executes the command given by the words of an alias's expanded command line.  Each
+    /// call adds a level to the interpreter's recursion count, so that an alias that
+    /// calls itself hits the recursion limit.
+    fn call_alias(&mut self, words: &[Value]) -> MoltResult {
+        if words.is_empty() {
+            return molt_ok!();
+        }
+
+        self.num_levels += 1;
+
+        let result = if self.num_levels > self.recursion_limit {
+            molt_err!("too many nested calls to Interp::eval (infinite loop?)")
+        } else if let Some(cmd) = self.command(words[0].as_str()) {
+            let cmd = Rc::clone(cmd);
+            cmd.execute(self, words)
+        } else {
+            molt_err!("invalid command name \"{}\"", words[0])
+        };
+
+        self.num_levels -= 1;
+        result
+    }
+
+    /// Executes the command given by the words of a parent alias's expanded command line
+    /// in the parent interpreter.
+    fn call_parent(&mut self, alias: &Value, words: &[Value]) -> MoltResult {
+        match self.parent.take() {
+            Some(mut parent) => {
+                let result = parent.call(words);
+                self.parent = Some(parent);
+                result
+            }
+            None => molt_err!("can't call alias \"{}\": no parent interpreter", alias),
+        }
+    }
+
+    /// Sets the native handler for unknown commands.  When a script calls a command that
+    /// isn't defined, and there's no `unknown` command, the handler is called with the
+    /// words of the original command, i.e., `argv[0]` is the name of the unknown command.
+    /// Its result becomes the result of the original command.
+    ///
+    /// A command named `unknown`, usually a procedure, takes precedence over the handler.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use molt::Interp;
+    /// use molt::types::*;
+    /// use molt::molt_ok;
+    ///
+    /// fn cmd_unknown(_: &mut Interp, _: ContextID, argv: &[Value]) -> MoltResult {
+    ///     molt_ok!("no such command: {}", argv[0])
+    /// }
+    ///
+    /// # fn dummy() -> MoltResult {
+    /// let mut interp = Interp::new();
+    /// interp.set_unknown_handler(cmd_unknown);
+    ///
+    /// assert_eq!(interp.eval("nonesuch 1 2")?.as_str(), "no such command: nonesuch");
+    /// # molt_ok!()
+    /// # }
+    /// ```
+    pub fn set_unknown_handler(&mut self, handler: CommandFunc) {
+        self.unknown_handler = Some(handler);
+    }
+
+    /// Handles a call to an undefined command by calling the `unknown` command with the
+    /// original words as its arguments, or, if there is no `unknown` command, the native
+    /// unknown handler.  Returns `None` if there's neither, or if an unknown command is
+    /// already being handled; the latter prevents infinite recursion when the handler
+    /// itself calls an undefined command.
+    fn call_unknown(&mut self, words: &[Value]) -> Option<MoltResult> {
+        if self.in_unknown {
+            return None;
+        }
+
+        let unknown = self.command("unknown").map(Rc::clone);
+
+        if unknown.is_none() && self.unknown_handler.is_none() {
+            return None;
+        }
+
+        self.in_unknown = true;
+
+        let result = if let Some(cmd) = unknown {
+            let mut argv = vec![Value::from("unknown")];
+            argv.extend_from_slice(words);
+            cmd.execute(self, &argv)
+        } else {
+            let handler = self.unknown_handler.expect("unknown handler");
+            handler(self, NULL_CONTEXT, words)
+        };
+
+        self.in_unknown = false;
+        Some(result)
     }
 
     /// Determines whether or not the interpreter contains a command with the given
-    /// name.
+    /// name, as seen from the current namespace.
     pub fn has_command(&self, name: &str) -> bool {
-        self.commands.contains_key(name)
+        self.command(name).is_some()
     }
 
-    /// Renames the command.
+    /// Renames the command.  Any existing command with the new name is replaced.  Does
+    /// nothing if there's no command with the old name.
     ///
     /// **Note:** This does not update procedures that reference the command under the old
     /// name.  This is intentional: it is a common TCL programming technique to wrap an
@@ -1792,14 +3307,21 @@ impl Interp {
     /// # }
     /// ```
     pub fn rename_command(&mut self, old_name: &str, new_name: &str) {
-        if let Some(cmd) = self.commands.get(old_name) {
-            let cmd = Rc::clone(cmd);
-            self.commands.remove(old_name);
-            self.commands.insert(new_name.into(), cmd);
+        if let Some(key) = self.find_command_key(old_name) {
+            let new_key = command_key(&self.qualify_name(new_name)).to_string();
+            let cmd = self.commands.remove(&key).expect("undefined command");
+            self.exec_traces.remove(&new_key);
+            if let Some(traces) = self.exec_traces.remove(&key) {
+                self.exec_traces.insert(new_key.clone(), traces);
+            }
+            if let Some(old_cmd) = self.commands.insert(new_key, cmd) {
+                self.forget_contexts(&old_cmd);
+            }
         }
     }
 
-    /// Removes the command with the given name.
+    /// Removes the command with the given name, returning true if the command existed and
+    /// false otherwise.
     ///
     /// This would typically be done when destroying an object command.
     ///
@@ -1812,35 +3334,43 @@ impl Interp {
     ///
     /// let mut interp = Interp::new();
     ///
-    /// interp.remove_command("set");  // You'll be sorry....
+    /// assert!(interp.remove_command("set"));  // You'll be sorry....
     ///
     /// assert!(!interp.has_command("set"));
+    /// assert!(!interp.remove_command("set"));
     /// ```
-    pub fn remove_command(&mut self, name: &str) {
-        // FIRST, get the command's context ID, if any.
-        let context_id = self
-            .commands
-            .get(name)
-            .expect("undefined command")
-            .context_id();
+    pub fn remove_command(&mut self, name: &str) -> bool {
+        let key = match self.find_command_key(name) {
+            Some(key) => key,
+            None => return false,
+        };
 
-        // NEXT, If it has a context ID, decrement its reference count; and if the reference
-        // is zero, remove the context.
-        if context_id != NULL_CONTEXT
-            && self
+        // FIRST, release the command's contexts, if any.
+        let cmd = Rc::clone(&self.commands[&key]);
+        self.forget_contexts(&cmd);
+
+        // FINALLY, remove the command itself, and its execution traces.
+        self.exec_traces.remove(&key);
+        self.commands.remove(&key);
+        true
+    }
+
+    /// Decrements the reference counts of the command's contexts, removing any context
+    /// whose count drops to zero.
+    fn forget_contexts(&mut self, cmd: &Command) {
+        for context_id in cmd.context_ids() {
+            if self
                 .context_map
                 .get_mut(&context_id)
                 .expect("unknown context ID")
                 .decrement()
-        {
-            self.context_map.remove(&context_id);
+            {
+                self.context_map.remove(&context_id);
+            }
         }
-
-        // FINALLY, remove the command itself.
-        self.commands.remove(name);
     }
 
-    /// Gets a vector of the names of the existing commands.
+    /// Gets a vector of the names of the existing commands, sorted by name.
     ///
     /// # Example
     ///
@@ -1856,27 +3386,23 @@ impl Interp {
     /// }
     /// ```
     pub fn command_names(&self) -> MoltList {
-        let vec: MoltList = self
-            .commands
-            .keys()
-            .cloned()
-            .map(|x| Value::from(&x))
-            .collect();
+        let mut names: Vec<&String> = self.commands.keys().collect();
+        names.sort();
 
-        vec
+        names.into_iter().map(Value::from).collect()
     }
 
     /// Returns the body of the named procedure, or an error if the name doesn't
     /// name a procedure.
     pub fn command_type(&self, command: &str) -> MoltResult {
-        if let Some(cmd) = self.commands.get(command) {
+        if let Some(cmd) = self.command(command) {
             molt_ok!(cmd.cmdtype())
         } else {
             molt_err!("\"{}\" isn't a command", command)
         }
     }
 
-    /// Gets a vector of the names of the existing procedures.
+    /// Gets a vector of the names of the existing procedures, sorted by name.
     ///
     /// # Example
     ///
@@ -1892,20 +3418,21 @@ impl Interp {
     /// }
     /// ```
     pub fn proc_names(&self) -> MoltList {
-        let vec: MoltList = self
+        let mut names: Vec<&String> = self
             .commands
             .iter()
             .filter(|(_, cmd)| cmd.is_proc())
-            .map(|(name, _)| Value::from(name))
+            .map(|(name, _)| name)
             .collect();
+        names.sort();
 
-        vec
+        names.into_iter().map(Value::from).collect()
     }
 
     /// Returns the body of the named procedure, or an error if the name doesn't
     /// name a procedure.
     pub fn proc_body(&self, procname: &str) -> MoltResult {
-        if let Some(cmd) = self.commands.get(procname) {
+        if let Some(cmd) = self.command(procname) {
             if let Command::Proc(proc) = &**cmd {
                 return molt_ok!(proc.body.clone());
             }
@@ -1917,7 +3444,7 @@ impl Interp {
     /// Returns a list of the names of the arguments of the named procedure, or an
     /// error if the name doesn't name a procedure.
     pub fn proc_args(&self, procname: &str) -> MoltResult {
-        if let Some(cmd) = self.commands.get(procname) {
+        if let Some(cmd) = self.command(procname) {
             if let Command::Proc(proc) = &**cmd {
                 // Note: the item is guaranteed to be parsible as a list of 1 or 2 elements.
                 let vec: MoltList = proc
@@ -1936,7 +3463,7 @@ impl Interp {
     /// Returns an error if the procedure has no such argument, or the `procname` doesn't name
     /// a procedure.
     pub fn proc_default(&self, procname: &str, arg: &str) -> Result<Option<Value>, Exception> {
-        if let Some(cmd) = self.commands.get(procname) {
+        if let Some(cmd) = self.command(procname) {
             if let Command::Proc(proc) = &**cmd {
                 for argvec in &proc.parms {
                     let argvec = argvec.as_list()?; // Should never fail
@@ -1959,37 +3486,573 @@ impl Interp {
         molt_err!("\"{}\" isn't a procedure", procname)
     }
 
-    /// Calls a subcommand of the current command, looking up its name in an array of
-    /// `Subcommand` tuples.
+    /// Arranges for the command given by its words to be executed in place of the current
+    /// procedure, once the procedure's body returns: the command's result becomes the
+    /// procedure's result.  The command is resolved in the current namespace, and is
+    /// executed after the procedure's scope is popped, so that a chain of tail calls
+    /// doesn't grow the stack.  Returns a `Return` exception, to end the procedure's body.
     ///
-    /// The subcommand, if found, is called with the same `context_id` and `argv` as its
-    /// parent ensemble.  `subc` is the index of the subcommand's name in the `argv` array;
-    /// in most cases it will be `1`, but it is possible to define subcommands with
-    /// subcommands of their own.  The `subcommands` argument is a borrow of an array of
-    /// `Subcommand` records, each defining a subcommand's name and `CommandFunc`.
-    ///
-    /// If the subcommand name is found in the array, the matching `CommandFunc` is called.
-    /// otherwise, the error message gives the ensemble syntax.  If an invalid subcommand
-    /// name was provided, the error message includes the valid options.
-    ///
-    /// See the implementation of the `array` command in `commands.rs` and the
-    /// [module level documentation](index.html) for examples.
-    pub fn call_subcommand(
-        &mut self,
-        context_id: ContextID,
-        argv: &[Value],
-        subc: usize,
-        subcommands: &[Subcommand],
-    ) -> MoltResult {
-        check_args(subc, argv, subc + 1, 0, "subcommand ?arg ...?")?;
-        let rec = Subcommand::find(subcommands, argv[subc].as_str())?;
-        (rec.1)(self, context_id, argv)
+    /// This is the implementation of the `tailcall` command.
+    pub(crate) fn tailcall(&mut self, words: &[Value]) -> MoltResult {
+        let level = self.scopes.current();
+
+        if level == 0 || level != self.proc_scope {
+            return molt_err!("tailcall can only be called from a proc or lambda");
+        }
+
+        // A procedure has at most one tail call; a later one replaces an earlier one that
+        // was caught.
+        if self.tailcalls.last().is_some_and(|t| t.level == level) {
+            self.tailcalls.pop();
+        }
+
+        self.tailcalls.push(Tailcall {
+            level,
+            cmd: self.command(words[0].as_str()).map(Rc::clone),
+            words: MoltRc::new(words.to_vec()),
+        });
+
+        Err(Exception::molt_return(Value::empty()))
     }
 
-    //--------------------------------------------------------------------------------------------
-    // Interpreter Configuration
+    /// Creates a coroutine with the given name, which executes the procedure call given by
+    /// its words, and runs it until it yields or finishes: returns the value it yields, or
+    /// the procedure's result.  The procedure's body is executed at the global level, in the
+    /// procedure's namespace.  A command that isn't a procedure can't yield, and so is
+    /// simply executed, without creating the coroutine.  It's an error if a command with
+    /// the given name already exists.
+    ///
+    /// This is the implementation of the `coroutine` command.
+    #[cfg(feature = "coroutine")]
+    pub(crate) fn add_coroutine(&mut self, name: &str, words: &[Value]) -> MoltResult {
+        let qualified = self.qualify_name(name);
+
+        if self.has_command(&qualified) {
+            return molt_err!(
+                "can't create coroutine \"{}\": command already exists",
+                name
+            );
+        } else if !self.namespace_exists(namespace_qualifiers(&qualified)) {
+            return molt_err!("can't create coroutine \"{}\": unknown namespace", name);
+        }
 
-    /// Gets the interpreter's recursion limit: how deep the stack of script evaluations may be.
+        let body = match self.command(words[0].as_str()).map(Rc::clone).as_deref() {
+            Some(Command::Proc(proc)) => self.coroutine_body(proc, words)?,
+            _ => return self.eval_list(words),
+        };
+
+        let coroutine = Rc::new(Command::Coroutine(Coroutine::new(body)));
+        self.commands
+            .insert(command_key(&qualified).into(), Rc::clone(&coroutine));
+
+        coroutine.execute(self, &[Value::from(qualified)])
+    }
+
+    /// Binds the arguments of a call to the procedure in a new local scope, and returns
+    /// the procedure's body, ready to be executed by a coroutine from its first command.
+    #[cfg(feature = "coroutine")]
+    fn coroutine_body(
+        &mut self,
+        proc: &Procedure,
+        words: &[Value],
+    ) -> Result<CoroutineBody, Exception> {
+        self.push_scope();
+        let bound = proc.bind_args(self, words);
+        let scope = self.scopes.pop_saved();
+        bound?;
+
+        Ok(CoroutineBody {
+            body: proc.body.clone(),
+            ns: Rc::clone(&proc.ns),
+            scope,
+            next: 0,
+        })
+    }
+
+    /// Resumes a coroutine's body, which continues until it yields or returns; the `yield`
+    /// that suspended it, if any, returns the value.  If the body returns with a tail call
+    /// to a procedure, the procedure's body replaces the coroutine's, and is executed from
+    /// its first command.
+    #[cfg(feature = "coroutine")]
+    pub(crate) fn run_coroutine(&mut self, body: &mut CoroutineBody, mut value: Value) -> Resumed {
+        loop {
+            // FIRST, push the body's scope, and execute its commands in its namespace.
+            let caller_ns = self.set_current_namespace(&body.ns);
+            self.scopes.push_saved(std::mem::take(&mut body.scope));
+            let caller_proc_scope = std::mem::replace(&mut self.proc_scope, self.scopes.current());
+            let top_level = (self.frames.len() + 1, self.num_levels);
+            let caller_yield_at = self.yield_at.replace(top_level);
+
+            if let Some(coverage) = self.coverage.as_mut() {
+                coverage.enter(body.body.as_str());
+            }
+            let result = self.eval_coroutine_body(body, value);
+            self.resume_coverage();
+
+            self.yield_at = caller_yield_at;
+            self.proc_scope = caller_proc_scope;
+            body.scope = self.scopes.pop_saved();
+            self.set_current_namespace(&caller_ns);
+
+            if let Some(value) = self.yielded.take() {
+                return Resumed::Yielded(value);
+            }
+
+            // NEXT, the body has returned.  Continue with the procedure named by its tail
+            // call, if any; execute any other command in its place as a procedure would.
+            let result = Procedure::complete(result);
+            let level = self.scopes.current() + 1;
+            let tailcall = match self.tailcalls.last() {
+                Some(t)
+                    if t.level == level
+                        && result.is_ok()
+                        && t.cmd.as_ref().is_some_and(|cmd| cmd.is_proc()) =>
+                {
+                    self.tailcalls.pop().expect("tail call")
+                }
+                Some(_) => return Resumed::Finished(self.execute_tailcalls(result)),
+                None => return Resumed::Finished(result),
+            };
+
+            if let Some(Command::Proc(proc)) = tailcall.cmd.as_deref() {
+                match self
+                    .step()
+                    .and_then(|_| self.coroutine_body(proc, &tailcall.words))
+                {
+                    Ok(next) => *body = next,
+                    Err(exception) => return Resumed::Finished(Err(exception)),
+                }
+            }
+
+            value = Value::empty();
+        }
+    }
+
+    /// Executes a coroutine's body from its next command, one top-level command at a time,
+    /// until a command yields or the body returns; `value` is the result if there are no
+    /// more commands.
+    #[cfg(feature = "coroutine")]
+    fn eval_coroutine_body(&mut self, body: &mut CoroutineBody, value: Value) -> MoltResult {
+        let script = body.body.as_script()?;
+        let mut result_value = value;
+
+        for word_vec in &script.commands()[body.next..] {
+            body.next += 1;
+
+            // The word list is recycled on every path; eval_command recycles it itself.
+            let mut words = self.spare_words.pop().unwrap_or_default();
+            let list = MoltRc::get_mut(&mut words).expect("spare word list is shared");
+            let substituted = self.eval_word_vec_into(word_vec.words(), list);
+
+            if substituted.is_err() || words.is_empty() {
+                self.recycle_words(words);
+                substituted?;
+                break;
+            }
+
+            if let Some(coverage) = self.coverage.as_mut() {
+                coverage.hit(word_vec.line());
+            }
+
+            result_value = self.eval_command(words, word_vec.line())?;
+
+            if self.yielded.is_some() {
+                break;
+            }
+        }
+
+        Ok(result_value)
+    }
+
+    /// Suspends the current coroutine, returning the value to its caller once the current
+    /// command completes.  It's an error if no coroutine is running, or if this isn't one of
+    /// the top-level commands of the coroutine's body.
+    ///
+    /// This is the implementation of the `yield` command.
+    #[cfg(feature = "coroutine")]
+    pub(crate) fn yield_value(&mut self, value: Value) -> MoltResult {
+        match self.yield_at {
+            Some(at) if at == (self.frames.len(), self.num_levels) => {
+                self.yielded = Some(value);
+                molt_ok!()
+            }
+            Some(_) => molt_err!("yield can only be called at the top level of a coroutine's body"),
+            None => molt_err!("yield can only be called in a coroutine"),
+        }
+    }
+
+    /// Removes the coroutine's command, whatever its current name, once the coroutine has
+    /// finished.
+    #[cfg(feature = "coroutine")]
+    pub(crate) fn remove_coroutine(&mut self, coroutine: &Coroutine) {
+        let key = self.commands.iter().find_map(|(key, cmd)| match &**cmd {
+            Command::Coroutine(c) if std::ptr::eq(c, coroutine) => Some(key.clone()),
+            _ => None,
+        });
+
+        if let Some(key) = key {
+            self.exec_traces.remove(&key);
+            self.commands.remove(&key);
+        }
+    }
+
+    /// Executes the tail calls of the procedure that has just returned the given result,
+    /// if any, returning the final result.  A tail call to another procedure is executed
+    /// directly, rather than by recursion, and its own tail call, if any, by the next
+    /// iteration.  The tail calls are discarded if the procedure threw an error.
+    #[inline(never)]
+    fn execute_tailcalls(&mut self, mut result: MoltResult) -> MoltResult {
+        let level = self.scopes.current() + 1;
+
+        loop {
+            // FIRST, get the procedure's pending tail call, if any.
+            let tailcall = match self.tailcalls.last() {
+                Some(t) if t.level == level => self.tailcalls.pop().unwrap(),
+                _ => return result,
+            };
+
+            // NEXT, an error discards the tail call; otherwise it replaces the procedure's
+            // frame.
+            result?;
+            self.step()?;
+
+            if let Some(frame) = self.frames.last_mut() {
+                frame.words = MoltRc::clone(&tailcall.words);
+                frame.is_proc = tailcall.cmd.as_ref().is_some_and(|cmd| cmd.is_proc());
+            }
+
+            let words = tailcall.words.as_slice();
+
+            result = match tailcall.cmd.as_deref() {
+                Some(Command::Proc(proc)) => proc.call(self, words),
+                Some(cmd) => cmd.execute(self, words),
+                None => molt_err!("invalid command name \"{}\"", words[0]),
+            };
+        }
+    }
+
+    /// Calls a subcommand of the current command, looking up its name in an array of
+    /// `Subcommand` tuples.
+    ///
+    /// The subcommand, if found, is called with the same `context_id` and `argv` as its
+    /// parent ensemble.  `subc` is the index of the subcommand's name in the `argv` array;
+    /// in most cases it will be `1`, but it is possible to define subcommands with
+    /// subcommands of their own.  The `subcommands` argument is a borrow of an array of
+    /// `Subcommand` records, each defining a subcommand's name and `CommandFunc`.
+    ///
+    /// If the subcommand name is found in the array, the matching `CommandFunc` is called.
+    /// otherwise, the error message gives the ensemble syntax.  If an invalid subcommand
+    /// name was provided, the error message includes the valid options.
+    ///
+    /// See the implementation of the `array` command in `commands.rs` and the
+    /// [module level documentation](index.html) for examples.
+    pub fn call_subcommand(
+        &mut self,
+        context_id: ContextID,
+        argv: &[Value],
+        subc: usize,
+        subcommands: &[Subcommand],
+    ) -> MoltResult {
+        check_args(subc, argv, subc + 1, 0, "subcommand ?arg ...?")?;
+        let rec = Subcommand::find(subcommands, argv[subc].as_str())?;
+        (rec.1)(self, context_id, argv)
+    }
+
+    /// Looks up the command with the given name, as seen from the current namespace.  A
+    /// name beginning with "::" is fully qualified; otherwise the name is looked up in the
+    /// current namespace, and then in the global namespace.
+    fn command(&self, name: &str) -> Option<&Rc<Command>> {
+        if let Some(qualified) = name.strip_prefix("::") {
+            return self.commands.get(qualified);
+        }
+
+        if &*self.namespace != GLOBAL_NAMESPACE {
+            let cmd = self.commands.get(self.namespace_key(name).as_str());
+            if cmd.is_some() {
+                return cmd;
+            }
+        }
+
+        self.commands.get(name)
+    }
+
+    /// Returns the command table key of the name in the current namespace, which isn't the
+    /// global namespace.  The key is built in a buffer that's reused from one lookup to the
+    /// next.
+    fn namespace_key(&self, name: &str) -> RefMut<'_, String> {
+        let mut key = self.key_buffer.borrow_mut();
+        key.clear();
+        key.push_str(command_key(&self.namespace));
+        key.push_str("::");
+        key.push_str(name);
+        key
+    }
+
+    /// Returns the command table key of the command with the given name, as seen from
+    /// the current namespace, or None if there is no such command.
+    fn find_command_key(&self, name: &str) -> Option<String> {
+        if let Some(qualified) = name.strip_prefix("::") {
+            if self.commands.contains_key(qualified) {
+                return Some(qualified.into());
+            }
+            return None;
+        }
+
+        if &*self.namespace != GLOBAL_NAMESPACE {
+            let key = self.namespace_key(name);
+            if self.commands.contains_key(key.as_str()) {
+                return Some(key.clone());
+            }
+        }
+
+        if self.commands.contains_key(name) {
+            Some(name.into())
+        } else {
+            None
+        }
+    }
+
+    //--------------------------------------------------------------------------------------------
+    // Namespaces
+
+    /// Returns the fully qualified name of the current namespace, e.g., `::` for the global
+    /// namespace or `::mylib` for a namespace called `mylib`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use molt::Interp;
+    /// use molt::types::*;
+    /// use molt::molt_ok;
+    /// # fn dummy() -> MoltResult {
+    /// let mut interp = Interp::new();
+    ///
+    /// assert_eq!(interp.current_namespace(), "::");
+    /// let ns = interp.eval("namespace eval mylib { namespace current }")?;
+    /// assert_eq!(ns.as_str(), "::mylib");
+    /// # molt_ok!()
+    /// # }
+    /// ```
+    pub fn current_namespace(&self) -> &str {
+        &self.namespace
+    }
+
+    /// Returns the fully qualified form of the name, which is relative to the current
+    /// namespace unless it begins with "::".
+    pub fn qualify_name(&self, name: &str) -> String {
+        if name.starts_with("::") {
+            name.into()
+        } else if &*self.namespace == GLOBAL_NAMESPACE {
+            format!("::{}", name)
+        } else {
+            format!("{}::{}", self.namespace, name)
+        }
+    }
+
+    /// Determines whether the named namespace exists.  The name is relative to the current
+    /// namespace unless it begins with "::".
+    pub fn namespace_exists(&self, name: &str) -> bool {
+        let qualified = self.qualify_name(name);
+        qualified == GLOBAL_NAMESPACE || self.namespaces.contains(&qualified)
+    }
+
+    /// Creates the named namespace, along with any parent namespaces that don't already
+    /// exist, and returns its fully qualified name.  The name is relative to the current
+    /// namespace unless it begins with "::".
+    pub fn add_namespace(&mut self, name: &str) -> String {
+        let qualified = self.qualify_name(name);
+        let mut ns = qualified.as_str();
+
+        while ns != GLOBAL_NAMESPACE && self.namespaces.insert(ns.into()) {
+            ns = namespace_qualifiers(ns);
+        }
+
+        qualified
+    }
+
+    /// Makes the namespace with the given fully qualified name the current namespace,
+    /// returning the name of the previous current namespace so that it can be restored.
+    /// This is used by the `namespace eval` command and by procedures.
+    pub(crate) fn set_current_namespace(&mut self, ns: &Rc<str>) -> Rc<str> {
+        std::mem::replace(&mut self.namespace, Rc::clone(ns))
+    }
+
+    //--------------------------------------------------------------------------------------------
+    // Child Interpreters
+
+    /// Creates a child interpreter with the given name, returning a mutable reference to it
+    /// so that it can be configured.  The child is created by `Interp::new`, or by
+    /// `Interp::new_safe` if `safe` is true or this interpreter is itself safe, and inherits
+    /// this interpreter's recursion limit.  The child is owned by this interpreter, and is
+    /// dropped along with it.  It's an error if there's already a child with the given name.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use molt::Interp;
+    /// use molt::types::*;
+    /// use molt::molt_ok;
+    /// # fn dummy() -> MoltResult {
+    /// let mut interp = Interp::new();
+    ///
+    /// let child = interp.create_child("plugin", true)?;
+    /// child.set_scalar("greeting", Value::from("Howdy"))?;
+    ///
+    /// let greeting = interp.eval("interp eval plugin {set greeting}")?;
+    /// assert_eq!(greeting.as_str(), "Howdy");
+    ///
+    /// interp.delete_child("plugin")?;
+    /// assert!(!interp.has_child("plugin"));
+    /// # molt_ok!()
+    /// # }
+    /// ```
+    pub fn create_child(&mut self, name: &str, safe: bool) -> Result<&mut Interp, Exception> {
+        if self.has_child(name) {
+            return molt_err!(
+                "interpreter named \"{}\" already exists, cannot create",
+                name
+            );
+        }
+
+        let mut child = if safe || self.safe {
+            Interp::new_safe()
+        } else {
+            Interp::new()
+        };
+        child.recursion_limit = self.recursion_limit;
+
+        let children = &mut self.child_table().children;
+        children.insert(name.into(), child);
+        Ok(children.get_mut(name).expect("child was just created"))
+    }
+
+    /// Returns a name for a new child interpreter, one that isn't used by any existing child.
+    pub(crate) fn new_child_name(&mut self) -> String {
+        let table = self.child_table();
+
+        loop {
+            let name = format!("interp{}", table.last_child);
+            table.last_child += 1;
+
+            if !table.children.contains_key(&name) {
+                return name;
+            }
+        }
+    }
+
+    /// Returns true if there's a child interpreter with the given name.
+    pub fn has_child(&self, name: &str) -> bool {
+        match self.children {
+            Some(id) => self.context_map[&id]
+                .data
+                .downcast_ref::<ChildTable>()
+                .expect("child table")
+                .children
+                .contains_key(name),
+            None => false,
+        }
+    }
+
+    /// Returns a mutable reference to the named child interpreter, or an error if there's
+    /// no child with that name.
+    pub fn child(&mut self, name: &str) -> Result<&mut Interp, Exception> {
+        match self.child_table().children.get_mut(name) {
+            Some(child) => Ok(child),
+            None => molt_err!("could not find interpreter \"{}\"", name),
+        }
+    }
+
+    /// Evaluates the script in the named child interpreter, as for `interp eval`, returning
+    /// the result.  The script's nesting levels and evaluation steps count against this
+    /// interpreter's recursion, command, and time limits as well as the child's, so that a
+    /// script can't evade the limits by evaluating code in a child interpreter.  Entering
+    /// the child uses far more of the Rust stack than a nested `eval`, and so it counts as
+    /// several levels.
+    ///
+    /// While the script is evaluated, this interpreter is lent to the child so that the
+    /// child's parent aliases can call it; see
+    /// [`add_parent_alias`](#method.add_parent_alias).  The child itself is unavailable
+    /// to the parent in the meantime.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use molt::Interp;
+    /// use molt::types::*;
+    /// use molt::molt_ok;
+    /// # fn dummy() -> MoltResult {
+    /// let mut interp = Interp::new();
+    /// interp.create_child("plugin", true)?;
+    ///
+    /// let value = interp.eval_in_child("plugin", &Value::from("expr {1 + 1}"))?;
+    /// assert_eq!(value.as_str(), "2");
+    /// # molt_ok!()
+    /// # }
+    /// ```
+    pub fn eval_in_child(&mut self, name: &str, script: &Value) -> MoltResult {
+        let levels = self
+            .recursion_limit
+            .saturating_sub(self.num_levels + CHILD_EVAL_LEVELS);
+        let steps = self
+            .command_limit
+            .map(|limit| limit.saturating_sub(self.steps));
+        let deadline = self.deadline;
+
+        let mut child = match self.child_table().children.remove(name) {
+            Some(child) => child,
+            None => return molt_err!("could not find interpreter \"{}\"", name),
+        };
+        let saved = (child.recursion_limit, child.command_limit, child.deadline);
+        let start = child.steps;
+
+        child.recursion_limit = saved.0.min(levels);
+        child.command_limit = earliest(saved.1, steps.map(|steps| start + steps));
+        child.deadline = earliest(saved.2, deadline);
+        child.schedule_check();
+
+        // Lend this interpreter to the child; an empty interpreter stands in for it.
+        child.parent = Some(Box::new(std::mem::replace(self, Interp::empty())));
+        let result = child.eval_value(script);
+        *self = *child.parent.take().expect("parent is returned");
+
+        let taken = child.steps - start;
+        child.recursion_limit = saved.0;
+        child.command_limit = saved.1;
+        child.deadline = saved.2;
+        child.schedule_check();
+
+        self.steps += taken;
+        self.child_table().children.insert(name.into(), child);
+        result
+    }
+
+    /// Deletes the named child interpreter, dropping it and its commands, variables, and
+    /// context data.  It's an error if there's no child with that name.
+    pub fn delete_child(&mut self, name: &str) -> Result<(), Exception> {
+        match self.child_table().children.remove(name) {
+            Some(_) => Ok(()),
+            None => molt_err!("could not find interpreter \"{}\"", name),
+        }
+    }
+
+    /// Gets the child interpreter table, creating it if need be.
+    fn child_table(&mut self) -> &mut ChildTable {
+        let id = match self.children {
+            Some(id) => id,
+            None => {
+                let id = self.save_context(ChildTable::default());
+                self.children = Some(id);
+                id
+            }
+        };
+
+        self.context::<ChildTable>(id)
+    }
+
+    //--------------------------------------------------------------------------------------------
+    // Interpreter Configuration
+
+    /// Gets the interpreter's recursion limit: how deep the stack of script evaluations may be.
     ///
     /// A script stack level is added by each nested script evaluation (i.e., by each call)
     /// to [`eval`](#method.eval) or [`eval_value`](#method.eval_value).
@@ -2009,7 +4072,10 @@ impl Interp {
     /// be.  The default is 1000.
     ///
     /// A script stack level is added by each nested script evaluation (i.e., by each call)
-    /// to [`eval`](#method.eval) or [`eval_value`](#method.eval_value).
+    /// to [`eval`](#method.eval) or [`eval_value`](#method.eval_value).  Command
+    /// substitutions and nested subexpressions in `expr` also add levels, as they too
+    /// recurse on the Rust stack; the limit keeps deeply nested input from overflowing it.
+    /// Separately, the parser rejects input with more than 256 levels of nested brackets.
     ///
     /// # Example
     /// ```
@@ -2023,437 +4089,2592 @@ impl Interp {
         self.recursion_limit = limit;
     }
 
-    //--------------------------------------------------------------------------------------------
-    // Context Cache
-
-    /// Saves the client's context data in the interpreter's context cache,
-    /// returning a generated context ID.  Client commands can retrieve the data
-    /// given the ID.
+    /// Limits the number of further commands the interpreter may execute, or removes the
+    /// limit if `limit` is `None`.  There's no limit by default.
     ///
-    /// See the [module level documentation](index.html) for an overview and examples.
+    /// Each command executed counts against the limit, as does each script evaluated, e.g.,
+    /// each iteration of a loop body, so that even an empty loop is limited.  Once the
+    /// limit is exceeded every further command fails with the error "command count limit
+    /// exceeded", so that the script can't recover by catching the error, until the
+    /// application sets a new limit.  Commands evaluated by child interpreters via
+    /// `interp eval` count against this interpreter's limit as well as the child's.
     ///
-    /// # Example
+    /// Use this, along with [`set_time_limit`](#method.set_time_limit), to keep untrusted
+    /// scripts from running forever.
     ///
+    /// # Example
     /// ```
-    /// use molt::types::*;
-    /// use molt::interp::Interp;
-    ///
+    /// # use molt::interp::Interp;
     /// let mut interp = Interp::new();
-    /// let data: Vec<String> = Vec::new();
-    /// let id = interp.save_context(data);
+    /// interp.set_command_limit(Some(1000));
+    /// assert!(interp.eval("while {1} {}").is_err());
+    ///
+    /// interp.set_command_limit(None);
+    /// assert!(interp.eval("set a 1").is_ok());
     /// ```
-    pub fn save_context<T: 'static>(&mut self, data: T) -> ContextID {
-        let id = self.context_id();
-        self.context_map.insert(id, ContextBox::new(data));
-        id
+    pub fn set_command_limit(&mut self, limit: Option<usize>) {
+        self.command_limit = limit.map(|limit| self.steps.saturating_add(limit));
+        self.schedule_check();
     }
 
-    /// Retrieves mutable client context data given the context ID.
+    /// Limits the time for which the interpreter may continue to execute commands, or
+    /// removes the limit if `limit` is `None`.  There's no limit by default.
     ///
-    /// See the [module level documentation](index.html) for an overview and examples.
+    /// Once the time is up every further command fails with the error "time limit
+    /// exceeded", until the application sets a new limit.  As with
+    /// [`set_command_limit`](#method.set_command_limit), the limit applies to the
+    /// evaluation of loop bodies as well as to commands, and to child interpreters via
+    /// `interp eval`.  The time is checked every so many commands, so a long-running
+    /// command may exceed the limit, but a script can't.
     ///
     /// # Example
-    ///
     /// ```
-    /// use molt::types::*;
-    /// use molt::interp::Interp;
+    /// # use molt::interp::Interp;
+    /// use std::time::Duration;
     ///
     /// let mut interp = Interp::new();
-    /// let data: Vec<String> = Vec::new();
-    /// let id = interp.save_context(data);
-    ///
-    /// // Later...
-    /// let data: &mut Vec<String> = interp.context(id);
-    /// data.push("New Value".into());
-    ///
-    /// // Or
-    /// let data = interp.context::<Vec<String>>(id);
-    /// data.push("New Value".into());
+    /// interp.set_time_limit(Some(Duration::from_millis(10)));
+    /// assert!(interp.eval("while {1} {}").is_err());
     /// ```
     ///
-    /// # Panics
-    ///
-    /// This call panics if the context ID is unknown, or if the retrieved data
-    /// has an unexpected type.
-    pub fn context<T: 'static>(&mut self, id: ContextID) -> &mut T {
-        self.context_map
-            .get_mut(&id)
-            .expect("unknown context ID")
-            .data
-            .downcast_mut::<T>()
-            .expect("context type mismatch")
+    /// The limit is measured by the interpreter's clock; if the interpreter has no clock,
+    /// setting a limit has no effect.  See [`set_clock`](#method.set_clock).
+    pub fn set_time_limit(&mut self, limit: Option<Duration>) {
+        self.deadline = match (limit, self.now()) {
+            (Some(limit), Some(now)) => Some(now + limit),
+            _ => None,
+        };
+        self.schedule_check();
     }
 
-    /// Generates a unique context ID for command context data.
+    /// Sets the clock the interpreter uses for the `time` command and for time limits, or
+    /// removes it, in which case `time` throws an error.  By default the interpreter uses
+    /// the system clock; but on `wasm32-unknown-unknown`, where there's no system clock,
+    /// the interpreter has no clock until the application sets one, e.g., one based
+    /// on the browser's `performance.now()`.
     ///
-    /// Normally the client will use [`save_context`](#method.save_context) to
-    /// save the context data and generate the client ID in one operation, rather than
-    /// call this explicitly.
-    ////
-    /// # Example
+    /// Child interpreters evaluated subject to the parent's time limit are assumed to use
+    /// the same clock as the parent.
     ///
+    /// # Example
     /// ```
-    /// use molt::types::*;
-    /// use molt::interp::Interp;
-    ///
+    /// # use molt::interp::Interp;
     /// let mut interp = Interp::new();
-    /// let id1 = interp.context_id();
-    /// let id2 = interp.context_id();
-    /// assert_ne!(id1, id2);
+    /// interp.set_clock(None);
+    /// assert!(interp.eval("time {set a 1}").is_err());
     /// ```
-    pub fn context_id(&mut self) -> ContextID {
-        // TODO: Practically speaking we won't overflow u64; but practically speaking
-        // we should check any.
-        self.last_context_id += 1;
-        ContextID(self.last_context_id)
+    pub fn set_clock(&mut self, clock: Option<ClockFunc>) {
+        self.clock = clock;
     }
 
-    /// Saves a client context value in the interpreter for the given
-    /// context ID.  Client commands can retrieve the data given the context ID.
-    ///
-    /// Normally the client will use [`save_context`](#method.save_context) to
-    /// save the context data and generate the client ID in one operation, rather than
-    /// call this explicitly.
-    ///
-    /// TODO: This method allows the user to generate a context ID and
-    /// put data into the context cache as two separate steps; and to update the
-    /// the data in the context cache for a given ID.  I'm not at all sure that
-    /// either of those things is a good idea.  Waiting to see.
+    /// Reads the interpreter's clock, returning `None` if it has no clock.
+    pub fn now(&self) -> Option<Duration> {
+        self.clock.map(|clock| clock())
+    }
+
+    /// Adds a nesting level for a construct that recurses on the Rust stack, e.g., a
+    /// command substitution, returning false if this would exceed the recursion limit.
+    /// A successful call must be balanced by a call to `leave_level`.
+    pub(crate) fn enter_level(&mut self) -> bool {
+        if self.num_levels < self.recursion_limit {
+            self.num_levels += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Removes a nesting level added by `enter_level`.
+    pub(crate) fn leave_level(&mut self) {
+        self.num_levels -= 1;
+    }
+
+    /// Counts an evaluation step, checking the command and time limits as needed.
+    #[inline]
+    fn step(&mut self) -> Result<(), Exception> {
+        self.steps += 1;
+
+        if self.steps >= self.next_check {
+            self.check_limits()
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Returns an error if the command or time limit has been exceeded, and otherwise
+    /// schedules the next check.
+    #[inline(never)]
+    fn check_limits(&mut self) -> Result<(), Exception> {
+        if self.debug_aborted {
+            return molt_err!("evaluation aborted by debugger");
+        }
+
+        if let Some(limit) = self.command_limit {
+            if self.steps > limit {
+                return molt_err!("command count limit exceeded");
+            }
+        }
+
+        if let (Some(deadline), Some(now)) = (self.deadline, self.now()) {
+            if now >= deadline {
+                return molt_err!("time limit exceeded");
+            }
+        }
+
+        self.schedule_check();
+        Ok(())
+    }
+
+    /// Determines the step at which the limits will next be checked: the step that exceeds
+    /// the command limit, or the next periodic check of the time limit.
+    fn schedule_check(&mut self) {
+        self.next_check = usize::MAX;
+
+        if let Some(limit) = self.command_limit {
+            self.next_check = limit.saturating_add(1);
+        }
+
+        if self.deadline.is_some() {
+            let time_check = self.steps.saturating_add(TIME_CHECK_STEPS);
+            self.next_check = self.next_check.min(time_check);
+        }
+    }
+
+    //--------------------------------------------------------------------------------------------
+    // Execution Traces
+
+    /// Sets or clears the execution trace hook, which is called with the words of each
+    /// command, after substitution, just before the command is executed, and with the
+    /// command's frame level, as for `info frame`.  The hook is called for every command,
+    /// including those in procedure bodies and command substitutions, in the order in
+    /// which they are executed.
     ///
     /// # Example
     ///
     /// ```
+    /// use molt::Interp;
     /// use molt::types::*;
-    /// use molt::interp::Interp;
-    ///
+    /// use std::cell::RefCell;
+    /// use std::rc::Rc;
+    /// # fn dummy() -> MoltResult {
     /// let mut interp = Interp::new();
-    /// let id = interp.context_id();
-    /// let data: Vec<String> = Vec::new();
-    /// interp.set_context(id, data);
+    /// let log = Rc::new(RefCell::new(Vec::new()));
+    ///
+    /// let hook_log = Rc::clone(&log);
+    /// interp.set_exec_trace(Some(Box::new(move |words: &[Value], level: usize| {
+    ///     hook_log.borrow_mut().push(format!("{} {}", level, words[0]));
+    /// })));
+    ///
+    /// interp.eval("proc two {} { expr {1 + 1} }; set a [two]")?;
+    /// interp.set_exec_trace(None);
+    ///
+    /// assert_eq!(*log.borrow(), vec!["1 proc", "1 two", "2 expr", "1 set"]);
+    /// # Ok(Value::empty())
+    /// # }
     /// ```
-    pub fn set_context<T: 'static>(&mut self, id: ContextID, data: T) {
-        self.context_map.insert(id, ContextBox::new(data));
+    pub fn set_exec_trace(&mut self, trace: Option<ExecTraceFunc>) {
+        self.exec_trace = trace;
     }
 
-    //--------------------------------------------------------------------------------------------
-    // Profiling
+    /// Enables or disables writing an execution trace to the standard error: each command,
+    /// after substitution, is written as it is executed, indented by its frame level.  This
+    /// uses the execution trace hook, replacing any existing hook; see
+    /// [`set_exec_trace`](#method.set_exec_trace).
+    pub fn set_trace_to_stderr(&mut self, flag: bool) {
+        if flag {
+            self.exec_trace = Some(Box::new(|words, level| {
+                eprintln!("{}{}", "  ".repeat(level - 1), list_to_string(words));
+            }));
+        } else {
+            self.exec_trace = None;
+        }
+    }
 
-    /// Unstable; use at own risk.
-    pub fn profile_save(&mut self, name: &str, start: std::time::Instant) {
-        let dur = Instant::now().duration_since(start).as_nanos();
-        let rec = self
-            .profile_map
-            .entry(name.into())
-            .or_insert_with(ProfileRecord::new);
+    /// Adds an execution trace to the named command, as for `trace add execution`.  The
+    /// `script` is a command prefix; if `enter` is true it's called before the command
+    /// is executed, and if `leave` is true it's called after the command returns.
+    pub(crate) fn add_exec_trace(
+        &mut self,
+        name: &str,
+        enter: bool,
+        leave: bool,
+        script: Value,
+    ) -> Result<(), Exception> {
+        let key = match self.find_command_key(name) {
+            Some(key) => key,
+            None => return molt_err!("unknown command \"{}\"", name),
+        };
 
-        rec.count += 1;
-        rec.nanos += dur;
+        self.exec_traces.entry(key).or_default().push(ExecTrace {
+            enter,
+            leave,
+            script,
+        });
+        Ok(())
     }
 
-    /// Unstable; use at own risk.
-    pub fn profile_clear(&mut self) {
-        self.profile_map.clear();
-    }
+    /// Removes the named command's execution trace with the given operations and script,
+    /// if there is one, as for `trace remove execution`.
+    pub(crate) fn remove_exec_trace(
+        &mut self,
+        name: &str,
+        enter: bool,
+        leave: bool,
+        script: &Value,
+    ) -> Result<(), Exception> {
+        let key = match self.find_command_key(name) {
+            Some(key) => key,
+            None => return molt_err!("unknown command \"{}\"", name),
+        };
 
-    /// Unstable; use at own risk.
-    pub fn profile_dump(&self) {
-        if self.profile_map.is_empty() {
-            println!("no profile data");
-        } else {
-            for (name, rec) in &self.profile_map {
-                let avg = rec.nanos / rec.count;
-                println!("{} nanos {}, count={}", avg, name, rec.count);
+        if let Some(traces) = self.exec_traces.get_mut(&key) {
+            if let Some(i) = traces.iter().position(|trace| {
+                trace.enter == enter
+                    && trace.leave == leave
+                    && trace.script.as_str() == script.as_str()
+            }) {
+                traces.remove(i);
+            }
+
+            if traces.is_empty() {
+                self.exec_traces.remove(&key);
             }
         }
+        Ok(())
     }
-}
 
-/// How a procedure is defined: as an argument list and a body script.
-/// The argument list is a list of Values, and the body is a Value; each will
-/// retain its parsed form.
-///
-/// NOTE: We do not save the procedure's name; the name exists only in the
-/// commands table, and can be changed there freely.  The procedure truly doesn't
-/// know what its name is except when it is being executed.
-struct Procedure {
-    /// The procedure's parameter list.  Each item in the list is a name or a
-    /// name/default value pair.  (This is verified by the `proc` command.)
-    parms: MoltList,
+    /// Returns a list of the named command's execution traces, as for `trace info
+    /// execution`: for each trace, a list of its operations and its script.
+    pub(crate) fn exec_trace_info(&self, name: &str) -> MoltResult {
+        let key = match self.find_command_key(name) {
+            Some(key) => key,
+            None => return molt_err!("unknown command \"{}\"", name),
+        };
 
-    /// The procedure's body string, as a Value.  As such, it retains both its
-    /// string value, as needed for introspection, and its parsed Script.
-    body: Value,
-}
+        let mut result = MoltList::new();
 
-impl Procedure {
-    fn execute(&self, interp: &mut Interp, argv: &[Value]) -> MoltResult {
-        // FIRST, push the proc's local scope onto the stack.
-        interp.push_scope();
+        for trace in self.exec_traces.get(&key).into_iter().flatten() {
+            let mut ops = MoltList::new();
+            if trace.enter {
+                ops.push(Value::from("enter"));
+            }
+            if trace.leave {
+                ops.push(Value::from("leave"));
+            }
+            result.push(Value::from(vec![Value::from(ops), trace.script.clone()]));
+        }
 
-        // NEXT, process the proc's argument list.
-        let mut argi = 1; // Skip the proc's name
+        molt_ok!(result)
+    }
 
-        for (speci, spec) in self.parms.iter().enumerate() {
-            // FIRST, get the parameter as a vector.  It should be a list of
-            // one or two elements.
-            let vec = &*spec.as_list()?; // Should never fail
-            assert!(vec.len() == 1 || vec.len() == 2);
+    /// Executes a command that might have execution traces, calling the traces' scripts
+    /// before and after the command.  An error in a trace script is the command's result.
+    fn execute_traced(&mut self, cmd: &Command, words: &[Value]) -> MoltResult {
+        let traces = match self.find_command_key(words[0].as_str()) {
+            Some(key) => match self.exec_traces.get(&key) {
+                Some(traces) => traces.clone(),
+                None => return cmd.execute(self, words),
+            },
+            None => return cmd.execute(self, words),
+        };
+        let command = Value::from(list_to_string(words));
 
-            // NEXT, if this is the args parameter, give the remaining args,
-            // if any.  Note that "args" has special meaning only if it's the
-            // final arg spec in the list.
-            if vec[0].as_str() == "args" && speci == self.parms.len() - 1 {
-                interp.set_scalar("args", Value::from(&argv[argi..]))?;
+        for trace in traces.iter().filter(|trace| trace.enter) {
+            self.call_exec_trace(&trace.script, &[command.clone(), Value::from("enter")])?;
+        }
 
-                // We've processed all of the args
-                argi = argv.len();
-                break;
-            }
+        let result = cmd.execute(self, words);
 
-            // NEXT, do we have a matching argument?
-            if argi < argv.len() {
-                // Pair them up
-                interp.set_scalar(vec[0].as_str(), argv[argi].clone())?;
-                argi += 1;
-                continue;
-            }
+        let (code, value) = match &result {
+            Ok(value) => (0, value.clone()),
+            Err(exception) => (exception.code().as_int(), exception.value()),
+        };
 
-            // NEXT, do we have a default value?
-            if vec.len() == 2 {
-                interp.set_scalar(vec[0].as_str(), vec[1].clone())?;
-            } else {
-                // We don't; we're missing a required argument.
-                return self.wrong_num_args(&argv[0]);
-            }
+        for trace in traces.iter().filter(|trace| trace.leave) {
+            self.call_exec_trace(
+                &trace.script,
+                &[
+                    command.clone(),
+                    Value::from(code),
+                    value.clone(),
+                    Value::from("leave"),
+                ],
+            )?;
         }
 
-        // NEXT, do we have any arguments left over?
+        result
+    }
 
-        if argi != argv.len() {
-            return self.wrong_num_args(&argv[0]);
-        }
+    /// Calls an execution trace's script with the given arguments appended.  Further
+    /// traces are disabled while it runs.
+    fn call_exec_trace(&mut self, script: &Value, args: &[Value]) -> Result<(), Exception> {
+        let mut words = script.as_list()?.to_vec();
+        words.extend_from_slice(args);
 
-        // NEXT, evaluate the proc's body, getting the result.
-        let result = interp.eval_value(&self.body);
+        self.in_exec_trace = true;
+        let result = self.eval_value(&Value::from(words));
+        self.in_exec_trace = false;
 
-        // NEXT, pop the scope off of the stack; we're done with it.
-        interp.pop_scope();
+        result.map(|_| ())
+    }
 
-        if let Err(mut exception) = result {
-            // FIRST, handle the return -code, -level protocol
-            if exception.code() == ResultCode::Return {
-                exception.decrement_level();
-            }
+    //--------------------------------------------------------------------------------------------
+    // Debugging
 
-            return match exception.code() {
-                ResultCode::Okay => Ok(exception.value()),
-                ResultCode::Error => Err(exception),
-                ResultCode::Return => Err(exception), // -level > 0
-                ResultCode::Break => molt_err!("invoked \"break\" outside of a loop"),
-                ResultCode::Continue => molt_err!("invoked \"continue\" outside of a loop"),
-                // TODO: Better error message
-                ResultCode::Other(_) => molt_err!("unexpected result code."),
-            };
-        }
+    /// Sets or clears the debugger hook.  When evaluation pauses, at a breakpoint or while
+    /// stepping, the interpreter calls the hook with itself, the words of the command about
+    /// to be executed, and the command's frame level, as for `info frame`.  The hook can
+    /// inspect the paused interpreter, e.g., its local variables, or evaluate scripts in
+    /// it; the debugger is disabled while the hook runs.  The returned
+    /// [`DebugAction`](../types/enum.DebugAction.html) says how evaluation proceeds.
+    ///
+    /// Without a hook, breakpoints have no effect.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use molt::Interp;
+    /// use molt::types::*;
+    /// # fn dummy() -> MoltResult {
+    /// let mut interp = Interp::new();
+    ///
+    /// // Pause at the call to "double", step into it, and change its argument before
+    /// // the "incr" command is executed.
+    /// interp.set_debug_hook(Some(Box::new(|interp: &mut Interp, words: &[Value], _| {
+    ///     if words[0].as_str() == "incr" {
+    ///         interp.set_scalar("x", Value::from(10)).unwrap();
+    ///         DebugAction::Continue
+    ///     } else {
+    ///         DebugAction::Step
+    ///     }
+    /// })));
+    /// interp.set_breakpoint("double");
+    ///
+    /// interp.eval("proc double {x} { incr x $x }")?;
+    /// assert_eq!(interp.eval("double 1")?.as_int()?, 11);
+    /// # Ok(Value::empty())
+    /// # }
+    /// ```
+    pub fn set_debug_hook(&mut self, hook: Option<DebugHook>) {
+        self.debug_hook = hook;
+    }
 
-        // NEXT, return the computed result.
-        // Note: no need for special handling for return, break, continue;
-        // interp.eval() returns only Ok or a real error.
-        result
+    /// Sets a breakpoint on the named command: evaluation will pause before each call to
+    /// the command, if there's a debugger hook.  The name is relative to the current
+    /// namespace unless it begins with "::"; the command needn't exist yet.
+    pub fn set_breakpoint(&mut self, name: &str) {
+        let key = command_key(&self.qualify_name(name)).to_string();
+        self.breakpoints.insert(key);
     }
 
-    // Outputs the wrong # args message for the proc.  The name is passed in
-    // because it can be changed via the `rename` command.
-    fn wrong_num_args(&self, name: &Value) -> MoltResult {
-        let mut msg = String::new();
-        msg.push_str("wrong # args: should be \"");
-        msg.push_str(name.as_str());
+    /// Clears the breakpoint on the named command, if any.
+    pub fn clear_breakpoint(&mut self, name: &str) {
+        let key = command_key(&self.qualify_name(name)).to_string();
+        self.breakpoints.remove(&key);
+    }
 
-        for (i, arg) in self.parms.iter().enumerate() {
-            msg.push(' ');
+    /// Pauses evaluation before the next command, e.g., the first command of the next
+    /// script evaluated, if there's a debugger hook.
+    pub fn break_next(&mut self) {
+        self.debug_step = Some(usize::MAX);
+    }
 
-            // "args" has special meaning only in the last place.
-            if arg.as_str() == "args" && i == self.parms.len() - 1 {
-                msg.push_str("?arg ...?");
-                break;
-            }
+    /// Calls the debugger hook if evaluation should pause before the command, and returns
+    /// the action to take.
+    fn debug_pause(&mut self, words: &[Value]) -> DebugAction {
+        let level = self.frames.len();
+        let stepping = match self.debug_step {
+            Some(step_level) => level <= step_level,
+            None => false,
+        };
 
-            let vec = arg.as_list().expect("error in proc arglist validation!");
+        if self.in_debug_hook || !(stepping || self.is_breakpoint(words[0].as_str())) {
+            return DebugAction::Continue;
+        }
 
-            if vec.len() == 1 {
-                msg.push_str(vec[0].as_str());
-            } else {
-                msg.push('?');
-                msg.push_str(vec[0].as_str());
-                msg.push('?');
+        let mut hook = self.debug_hook.take().expect("no debugger hook");
+        self.in_debug_hook = true;
+        let action = hook(self, words, level);
+        self.in_debug_hook = false;
+        self.debug_hook = Some(hook);
+
+        self.debug_step = match action {
+            DebugAction::Continue => None,
+            DebugAction::Step => Some(usize::MAX),
+            DebugAction::StepOver => Some(level),
+            DebugAction::Abort => {
+                // Every further step fails until control returns to the application.
+                self.debug_aborted = true;
+                self.next_check = 0;
+                None
             }
+        };
+
+        action
+    }
+
+    /// Returns true if there's a breakpoint on the named command.
+    fn is_breakpoint(&self, name: &str) -> bool {
+        !self.breakpoints.is_empty()
+            && self
+                .find_command_key(name)
+                .is_some_and(|key| self.breakpoints.contains(&key))
+    }
+
+    //--------------------------------------------------------------------------------------------
+    // Coverage
+
+    /// Enables or disables script coverage.  While coverage is enabled, the interpreter
+    /// records the executable lines of each script file evaluated by
+    /// [`eval_file`](#method.eval_file), and the number of times each is executed; see
+    /// [`molt::coverage`](../coverage/index.html) for details.  Disabling coverage
+    /// discards the data collected so far.  Coverage is disabled by default, and costs
+    /// nothing when disabled.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use molt::types::*;
+    /// # use molt::Interp;
+    /// # fn dummy() -> MoltResult {
+    /// use std::fs;
+    ///
+    /// let path = std::env::temp_dir().join("molt_coverage_example.tcl");
+    /// fs::write(&path, "proc p {x} {\n    if {$x} {\n        set a 1\n    }\n}\np 0\n").unwrap();
+    ///
+    /// let mut interp = Interp::new();
+    /// interp.set_coverage(true);
+    /// interp.eval_file(&path)?;
+    ///
+    /// // Every line but the body of the "if" was executed.
+    /// let file = &interp.coverage().unwrap().files()[0];
+    /// let lines: Vec<_> = file.lines().collect();
+    /// assert_eq!(lines, vec![(1, 1), (2, 1), (3, 0), (6, 1)]);
+    /// # fs::remove_file(&path).unwrap();
+    /// # Ok(Value::empty())
+    /// # }
+    /// ```
+    pub fn set_coverage(&mut self, enabled: bool) {
+        if !enabled {
+            self.coverage = None;
+        } else if self.coverage.is_none() {
+            self.coverage = Some(Coverage::new());
         }
-        msg.push_str("\"");
+    }
 
-        molt_err!(&msg)
+    /// Returns the script coverage data collected so far, or `None` if coverage isn't
+    /// enabled.  See [`set_coverage`](#method.set_coverage).
+    pub fn coverage(&self) -> Option<&Coverage> {
+        self.coverage.as_ref()
+    }
+
+    /// Stops counting commands for coverage until
+    /// [`resume_coverage`](#method.resume_coverage) is called.
+    pub(crate) fn suspend_coverage(&mut self) {
+        if let Some(coverage) = self.coverage.as_mut() {
+            coverage.suspend();
+        }
+    }
+
+    /// Resumes counting commands for coverage in the enclosing script.
+    pub(crate) fn resume_coverage(&mut self) {
+        if let Some(coverage) = self.coverage.as_mut() {
+            coverage.leave();
+        }
+    }
+
+    //--------------------------------------------------------------------------------------------
+    // Context Cache
+
+    /// Saves the client's context data in the interpreter's context cache,
+    /// returning a generated context ID.  Client commands can retrieve the data
+    /// given the ID.
+    ///
+    /// See the [module level documentation](index.html) for an overview and examples.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use molt::types::*;
+    /// use molt::interp::Interp;
+    ///
+    /// let mut interp = Interp::new();
+    /// let data: Vec<String> = Vec::new();
+    /// let id = interp.save_context(data);
+    /// ```
+    pub fn save_context<T: 'static>(&mut self, data: T) -> ContextID {
+        let id = self.context_id();
+        self.context_map.insert(id, ContextBox::new(data));
+        id
+    }
+
+    /// Retrieves mutable client context data given the context ID.
+    ///
+    /// See the [module level documentation](index.html) for an overview and examples.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use molt::types::*;
+    /// use molt::interp::Interp;
+    ///
+    /// let mut interp = Interp::new();
+    /// let data: Vec<String> = Vec::new();
+    /// let id = interp.save_context(data);
+    ///
+    /// // Later...
+    /// let data: &mut Vec<String> = interp.context(id);
+    /// data.push("New Value".into());
+    ///
+    /// // Or
+    /// let data = interp.context::<Vec<String>>(id);
+    /// data.push("New Value".into());
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// This call panics if the context ID is unknown, or if the retrieved data
+    /// has an unexpected type.
+    pub fn context<T: 'static>(&mut self, id: ContextID) -> &mut T {
+        self.context_map
+            .get_mut(&id)
+            .expect("unknown context ID")
+            .data
+            .downcast_mut::<T>()
+            .expect("context type mismatch")
+    }
+
+    /// Generates a unique context ID for command context data.
+    ///
+    /// Normally the client will use [`save_context`](#method.save_context) to
+    /// save the context data and generate the client ID in one operation, rather than
+    /// call this explicitly.
+    ////
+    /// # Example
+    ///
+    /// ```
+    /// use molt::types::*;
+    /// use molt::interp::Interp;
+    ///
+    /// let mut interp = Interp::new();
+    /// let id1 = interp.context_id();
+    /// let id2 = interp.context_id();
+    /// assert_ne!(id1, id2);
+    /// ```
+    pub fn context_id(&mut self) -> ContextID {
+        // TODO: Practically speaking we won't overflow u64; but practically speaking
+        // we should check any.
+        self.last_context_id += 1;
+        ContextID(self.last_context_id)
+    }
+
+    /// Saves a client context value in the interpreter for the given
+    /// context ID.  Client commands can retrieve the data given the context ID.
+    ///
+    /// Normally the client will use [`save_context`](#method.save_context) to
+    /// save the context data and generate the client ID in one operation, rather than
+    /// call this explicitly.
+    ///
+    /// TODO: This method allows the user to generate a context ID and
+    /// put data into the context cache as two separate steps; and to update the
+    /// the data in the context cache for a given ID.  I'm not at all sure that
+    /// either of those things is a good idea.  Waiting to see.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use molt::types::*;
+    /// use molt::interp::Interp;
+    ///
+    /// let mut interp = Interp::new();
+    /// let id = interp.context_id();
+    /// let data: Vec<String> = Vec::new();
+    /// interp.set_context(id, data);
+    /// ```
+    pub fn set_context<T: 'static>(&mut self, id: ContextID, data: T) {
+        self.context_map.insert(id, ContextBox::new(data));
+    }
+
+    /// Saves the client's context data in the interpreter's context cache, as for
+    /// [`save_context`](#method.save_context), returning a generated context ID.
+    /// Because the data can be cloned, it's included in the interpreter's
+    /// [`snapshot`](#method.snapshot), and so is rolled back by
+    /// [`restore`](#method.restore).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use molt::types::*;
+    /// use molt::interp::Interp;
+    ///
+    /// let mut interp = Interp::new();
+    /// let id = interp.save_cloneable_context(vec![String::from("a")]);
+    ///
+    /// let snapshot = interp.snapshot();
+    /// interp.context::<Vec<String>>(id).push("b".into());
+    /// interp.restore(snapshot);
+    ///
+    /// assert_eq!(interp.context::<Vec<String>>(id).len(), 1);
+    /// ```
+    pub fn save_cloneable_context<T: Clone + 'static>(&mut self, data: T) -> ContextID {
+        let id = self.context_id();
+        self.context_map.insert(id, ContextBox::cloneable(data));
+        id
+    }
+
+    //--------------------------------------------------------------------------------------------
+    // Snapshots
+
+    /// Saves a copy of the interpreter's script-level state, so that it can be restored
+    /// later by [`restore`](#method.restore), e.g., after evaluating an untrusted script
+    /// or to provide "undo".  The snapshot includes:
+    ///
+    /// * The interpreter's procedures, and its namespaces.
+    /// * The variables in the global scope, including namespace variables.
+    /// * Copies of any context structs saved with
+    ///   [`save_cloneable_context`](#method.save_cloneable_context).
+    ///
+    /// Native commands, aliases, ensembles, and other context structs aren't included;
+    /// they're left as they are when the snapshot is restored.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use molt::types::*;
+    /// use molt::interp::Interp;
+    /// # fn dummy() -> MoltResult {
+    ///
+    /// let mut interp = Interp::new();
+    /// interp.eval("set a 1; proc greet {} { return hello }")?;
+    ///
+    /// let snapshot = interp.snapshot();
+    ///
+    /// // The script misbehaves.
+    /// interp.eval("set a 2; set b 3; proc greet {} { return goodbye }")?;
+    ///
+    /// interp.restore(snapshot);
+    /// assert_eq!(interp.eval("set a")?.as_str(), "1");
+    /// assert!(!interp.var_exists(&Value::from("b")));
+    /// assert_eq!(interp.eval("greet")?.as_str(), "hello");
+    /// # Ok(Value::empty())
+    /// # }
+    /// ```
+    pub fn snapshot(&self) -> InterpSnapshot {
+        let procs = self
+            .commands
+            .iter()
+            .filter(|(_, cmd)| cmd.is_proc())
+            .map(|(key, cmd)| (key.clone(), Rc::clone(cmd)))
+            .collect();
+
+        let contexts = self
+            .context_map
+            .iter()
+            .filter_map(|(id, ctx)| {
+                ctx.clone_fn
+                    .map(|clone_fn| (*id, clone_fn(&*ctx.data), clone_fn))
+            })
+            .collect();
+
+        InterpSnapshot {
+            procs,
+            namespaces: self.namespaces.clone(),
+            globals: self.scopes.snapshot_globals(),
+            contexts,
+        }
+    }
+
+    /// Restores the interpreter's script-level state from a snapshot saved by
+    /// [`snapshot`](#method.snapshot).  Procedures defined since the snapshot are removed,
+    /// and those that were redefined, renamed, or removed are restored; the global
+    /// variables are replaced by those in the snapshot; and each cloneable context struct
+    /// whose commands still exist is replaced by its saved copy.  See
+    /// [`snapshot`](#method.snapshot) for an example.
+    ///
+    /// Native commands and other context structs are preserved.  If a procedure in the
+    /// snapshot has the same name as a command defined since, the procedure replaces it.
+    ///
+    /// The snapshot should be restored at the top level, i.e., not from within a command
+    /// called by a script.  Note that the `env` array reflects the process environment,
+    /// which isn't saved.
+    pub fn restore(&mut self, snapshot: InterpSnapshot) {
+        // FIRST, restore the procedures.
+        self.commands.retain(|_, cmd| !cmd.is_proc());
+
+        for (key, proc) in snapshot.procs {
+            if let Some(cmd) = self.commands.remove(&key) {
+                self.forget_contexts(&cmd);
+            }
+            self.commands.insert(key, proc);
+        }
+
+        self.namespaces = snapshot.namespaces;
+
+        // NEXT, restore the global variables.
+        self.scopes.restore_globals(snapshot.globals);
+
+        // NEXT, restore the contexts that still exist.
+        for (id, data, _) in snapshot.contexts {
+            if let Some(ctx) = self.context_map.get_mut(&id) {
+                ctx.data = data;
+            }
+        }
+    }
+
+    //--------------------------------------------------------------------------------------------
+    // Resetting
+
+    /// Returns the interpreter to a pristine state, so that it can be reused, e.g., for a new
+    /// user session, without losing the native commands and their contexts:
+    ///
+    /// * Removes the global variables, as for [`reset_vars`](#method.reset_vars).
+    /// * Removes the procedures, as for
+    ///   [`remove_proc_commands`](#method.remove_proc_commands).
+    /// * Clears the profile data.
+    /// * Pops any procedure scopes, leaving only the global scope, and makes the global
+    ///   namespace current.
+    ///
+    /// Native commands, aliases, ensembles, and context structs are preserved.  The
+    /// interpreter should be reset at the top level, i.e., not from within a command called
+    /// by a script.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use molt::types::*;
+    /// use molt::interp::Interp;
+    /// # fn dummy() -> MoltResult {
+    ///
+    /// let mut interp = Interp::new();
+    /// interp.eval("set a 1; proc greet {} { return hello }")?;
+    ///
+    /// interp.reset();
+    /// assert!(!interp.var_exists(&Value::from("a")));
+    /// assert!(!interp.has_command("greet"));
+    /// assert!(interp.has_command("set"));
+    /// # Ok(Value::empty())
+    /// # }
+    /// ```
+    pub fn reset(&mut self) {
+        self.scopes.pop_to_global();
+        self.namespace = GLOBAL_NAMESPACE.into();
+        self.reset_vars();
+        self.remove_proc_commands();
+        self.profile_clear();
+    }
+
+    /// Removes all variables from the global scope, including namespace variables.  The
+    /// `errorInfo` variable is recreated, empty; and if there was an `env` array, it is
+    /// repopulated from the environment, and relinked to it if it was linked.
+    pub fn reset_vars(&mut self) {
+        #[cfg(feature = "os")]
+        let (had_env, env_linked) = (self.scopes.array_exists("env"), self.scopes.is_env_linked());
+
+        self.scopes.clear_globals();
+        self.set_scalar("errorInfo", Value::empty()).unwrap();
+
+        #[cfg(feature = "os")]
+        {
+            if had_env {
+                self.populate_env();
+            }
+            if env_linked {
+                self.scopes.link_env();
+            }
+        }
+    }
+
+    /// Removes all procedures, leaving native commands, aliases, and ensembles in place.
+    /// The procedures' execution traces are removed along with them.
+    pub fn remove_proc_commands(&mut self) {
+        let keys: Vec<String> = self
+            .commands
+            .iter()
+            .filter(|(_, cmd)| cmd.is_proc())
+            .map(|(key, _)| key.clone())
+            .collect();
+
+        for key in keys {
+            let cmd = self.commands.remove(&key).expect("undefined command");
+            self.forget_contexts(&cmd);
+            self.exec_traces.remove(&key);
+        }
+    }
+
+    //--------------------------------------------------------------------------------------------
+    // Profiling
+
+    /// Unstable; use at own risk.
+    pub fn profile_save(&mut self, name: &str, start: Duration) {
+        let dur = match self.now() {
+            Some(now) => now.saturating_sub(start).as_nanos(),
+            None => 0,
+        };
+        let rec = self
+            .profile_map
+            .entry(name.into())
+            .or_insert_with(ProfileRecord::new);
+
+        rec.count += 1;
+        rec.nanos += dur;
+    }
+
+    /// Unstable; use at own risk.
+    pub fn profile_clear(&mut self) {
+        self.profile_map.clear();
+    }
+
+    /// Unstable; use at own risk.
+    pub fn profile_dump(&self) {
+        if self.profile_map.is_empty() {
+            println!("no profile data");
+        } else {
+            for (name, rec) in &self.profile_map {
+                let avg = rec.nanos / rec.count;
+                println!("{} nanos {}, count={}", avg, name, rec.count);
+            }
+        }
+    }
+}
+
+/// An ensemble command defined at run time, as an alternative to the usual array of
+/// [`Subcommand`] structs.  Each subcommand may be a binary command with its own context,
+/// a procedure, or a nested ensemble; thus, an object command's subcommands can be
+/// written in Molt, and an ensemble can have subcommands of its own.  The ensemble is
+/// added to the interpreter using
+/// [`Interp::add_ensemble`](struct.Interp.html#method.add_ensemble).
+///
+/// A binary subcommand is called with the full `argv`, as for
+/// [`Interp::call_subcommand`](struct.Interp.html#method.call_subcommand); a subcommand of
+/// a nested ensemble is at index 2, and so on.  A procedure subcommand sees only its own
+/// arguments, and its body is executed in the global namespace.
+///
+/// # Example
+///
+/// ```
+/// use molt::Interp;
+/// use molt::interp::Ensemble;
+/// use molt::types::*;
+/// use molt::molt_ok;
+/// # fn dummy() -> MoltResult {
+/// let mut interp = Interp::new();
+///
+/// let mut stats = Ensemble::new();
+/// stats.add_proc("dump", &[], &Value::from("return $::count"))?;
+///
+/// let mut counter = Ensemble::new();
+/// counter.add_proc("incr", &[], &Value::from("incr ::count"))?;
+/// counter.add_ensemble("stats", stats);
+/// interp.add_ensemble("counter", counter);
+///
+/// interp.eval("set count 0; counter incr; counter incr")?;
+/// assert_eq!(interp.eval("counter stats dump")?.as_int()?, 2);
+/// # molt_ok!()
+/// # }
+/// ```
+///
+/// [`Subcommand`]: ../types/struct.Subcommand.html
+#[derive(Clone, Default)]
+pub struct Ensemble {
+    /// The subcommands, by name.
+    subcommands: BTreeMap<String, EnsembleSubcommand>,
+
+    /// The binary command to which unknown subcommands are passed, if any.  This is used
+    /// when extending a binary ensemble command.
+    fallback: Option<(CommandFunc, ContextID)>,
+}
+
+/// A subcommand of an `Ensemble`.
+#[derive(Clone)]
+enum EnsembleSubcommand {
+    /// A binary subcommand implemented as a Rust CommandFunc.
+    Native(CommandFunc, ContextID),
+
+    /// A subcommand implemented as a Molt procedure.
+    Proc(Procedure),
+
+    /// A nested ensemble.
+    Ensemble(Ensemble),
+}
+
+impl Ensemble {
+    /// Creates an ensemble with no subcommands.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates an ensemble whose subcommands are the binary commands in a table of
+    /// [`Subcommand`](../types/struct.Subcommand.html) structs, with no related context.
+    /// More subcommands can be added in the usual way.
+    pub fn from_table(table: &[Subcommand]) -> Self {
+        let mut ensemble = Self::new();
+
+        for Subcommand(name, func) in table {
+            ensemble.add_command(name, *func);
+        }
+
+        ensemble
+    }
+
+    /// Creates an ensemble that passes unknown subcommands to a binary command.
+    fn wrap(func: CommandFunc, context_id: ContextID) -> Self {
+        Self {
+            subcommands: BTreeMap::new(),
+            fallback: Some((func, context_id)),
+        }
+    }
+
+    /// Adds a binary subcommand with no related context, replacing any existing
+    /// subcommand with the same name.
+    pub fn add_command(&mut self, name: &str, func: CommandFunc) {
+        self.add_context_command(name, func, NULL_CONTEXT);
+    }
+
+    /// Adds a binary subcommand with related context data, replacing any existing
+    /// subcommand with the same name.  Each subcommand may have its own context; the
+    /// context is retained for as long as the ensemble's command exists.
+    pub fn add_context_command(&mut self, name: &str, func: CommandFunc, context_id: ContextID) {
+        self.subcommands
+            .insert(name.into(), EnsembleSubcommand::Native(func, context_id));
+    }
+
+    /// Adds a subcommand implemented as a procedure, replacing any existing subcommand
+    /// with the same name.  The arguments are the same as for the `proc` command; it's an
+    /// error if the parameter list is invalid.
+    pub fn add_proc(&mut self, name: &str, parms: &[Value], body: &Value) -> Result<(), Exception> {
+        let proc = Procedure::new(name, parms, body, GLOBAL_NAMESPACE)?;
+        self.subcommands
+            .insert(name.into(), EnsembleSubcommand::Proc(proc));
+        Ok(())
+    }
+
+    /// Adds a nested ensemble as a subcommand, replacing any existing subcommand with
+    /// the same name.
+    pub fn add_ensemble(&mut self, name: &str, ensemble: Ensemble) {
+        self.subcommands
+            .insert(name.into(), EnsembleSubcommand::Ensemble(ensemble));
+    }
+
+    /// Removes the named subcommand, if it exists.
+    pub fn remove_command(&mut self, name: &str) {
+        self.subcommands.remove(name);
+    }
+
+    /// Returns true if the ensemble has a subcommand with the given name.
+    pub fn has_command(&self, name: &str) -> bool {
+        self.subcommands.contains_key(name)
+    }
+
+    /// Returns the names of the ensemble's subcommands, sorted by name.  Subcommands handled
+    /// by the original command of an extended binary ensemble aren't included.
+    pub fn command_names(&self) -> Vec<&str> {
+        self.subcommands.keys().map(|name| name.as_str()).collect()
+    }
+
+    /// Executes the subcommand named by `argv[subc]`.
+    fn execute(&self, interp: &mut Interp, argv: &[Value], subc: usize) -> MoltResult {
+        check_args(subc, argv, subc + 1, 0, "subcommand ?arg ...?")?;
+
+        match self.subcommands.get(argv[subc].as_str()) {
+            Some(EnsembleSubcommand::Native(func, context_id)) => func(interp, *context_id, argv),
+            Some(EnsembleSubcommand::Proc(proc)) => {
+                // The procedure's "name" is the full command path, for use in its
+                // wrong # args message.
+                let mut words = vec![Value::from(
+                    argv[..=subc]
+                        .iter()
+                        .map(|w| w.as_str())
+                        .collect::<Vec<_>>()
+                        .join(" "),
+                )];
+                words.extend_from_slice(&argv[subc + 1..]);
+                proc.execute(interp, &words)
+            }
+            Some(EnsembleSubcommand::Ensemble(ensemble)) => {
+                ensemble.execute(interp, argv, subc + 1)
+            }
+            None => match self.fallback {
+                Some((func, context_id)) => func(interp, context_id, argv),
+                None => {
+                    // Name the full subcommand path, e.g., "stats nonesuch".
+                    let path: Vec<&str> = argv[1..=subc].iter().map(|w| w.as_str()).collect();
+                    let names: Vec<&str> = self.subcommands.keys().map(|k| k.as_str()).collect();
+                    Err(unknown_subcommand(&path.join(" "), &names))
+                }
+            },
+        }
+    }
+
+    /// Adds the IDs of the ensemble's contexts, including those of nested ensembles, to
+    /// the vector.
+    fn context_ids(&self, ids: &mut Vec<ContextID>) {
+        if let Some((_, context_id)) = self.fallback {
+            ids.push(context_id);
+        }
+
+        for subcmd in self.subcommands.values() {
+            match subcmd {
+                EnsembleSubcommand::Native(_, context_id) => ids.push(*context_id),
+                EnsembleSubcommand::Ensemble(ensemble) => ensemble.context_ids(ids),
+                EnsembleSubcommand::Proc(_) => (),
+            }
+        }
+    }
+}
+
+/// How a procedure is defined: as an argument list and a body script.
+/// The argument list is a list of Values, and the body is a Value; each will
+/// retain its parsed form.
+///
+/// NOTE: We do not save the procedure's name; the name exists only in the
+/// commands table, and can be changed there freely.  The procedure truly doesn't
+/// know what its name is except when it is being executed.
+#[derive(Clone)]
+struct Procedure {
+    /// The procedure's parameter list.  Each item in the list is a name or a
+    /// name/default value pair.  (This is verified by `Procedure::new`.)
+    parms: MoltList,
+
+    /// The procedure's body string, as a Value.  As such, it retains both its
+    /// string value, as needed for introspection, and its parsed Script.
+    body: Value,
+
+    /// The qualified name of the namespace in which the procedure was defined, and in
+    /// which its body is executed.
+    ns: Rc<str>,
+}
+
+impl Procedure {
+    /// Creates a procedure, validating its parameter list: each parameter is a name and an
+    /// optional default value, the names are unique, the parameters with defaults follow
+    /// those without, and `args`, if present, is the last parameter and has no default.
+    /// The procedure's `name` is used only in error messages.
+    fn new(name: &str, parms: &[Value], body: &Value, ns: &str) -> Result<Self, Exception> {
+        let mut names: Vec<Value> = Vec::with_capacity(parms.len());
+        let mut optional: Option<Value> = None;
+
+        for (i, parm) in parms.iter().enumerate() {
+            let vec = parm.as_list()?;
+
+            if vec.is_empty() {
+                return molt_err!("argument with no name");
+            } else if vec.len() > 2 {
+                return molt_err!("too many fields in argument specifier \"{}\"", parm);
+            }
+
+            if names.iter().any(|n| n.as_str() == vec[0].as_str()) {
+                return molt_err!(
+                    "procedure \"{}\": formal parameter \"{}\" is not unique",
+                    name,
+                    vec[0]
+                );
+            }
+            names.push(vec[0].clone());
+
+            if vec[0].as_str() == "args" {
+                if i < parms.len() - 1 {
+                    return molt_err!(
+                        "procedure \"{}\": formal parameter \"args\" is not last",
+                        name
+                    );
+                } else if vec.len() == 2 {
+                    return molt_err!(
+                        "procedure \"{}\": formal parameter \"args\" can't have a default value",
+                        name
+                    );
+                }
+            } else if vec.len() == 2 {
+                optional = Some(vec[0].clone());
+            } else if let Some(name) = &optional {
+                return molt_err!(
+                    "required argument \"{}\" follows optional argument \"{}\"",
+                    vec[0],
+                    name
+                );
+            }
+        }
+
+        Ok(Self {
+            parms: parms.to_owned(),
+            body: body.clone(),
+            ns: ns.into(),
+        })
+    }
+
+    fn execute(&self, interp: &mut Interp, argv: &[Value]) -> MoltResult {
+        let result = self.call(interp, argv);
+
+        // NEXT, if the body called `tailcall`, execute the command in place of this one.
+        if interp.tailcalls.is_empty() {
+            result
+        } else {
+            interp.execute_tailcalls(result)
+        }
+    }
+
+    /// Calls the procedure with the given arguments: binds them to its parameters in a new
+    /// local scope, and evaluates its body.
+    fn call(&self, interp: &mut Interp, argv: &[Value]) -> MoltResult {
+        // FIRST, push the proc's local scope onto the stack, and bind the arguments.
+        interp.push_scope();
+        let caller_proc_scope = std::mem::replace(&mut interp.proc_scope, interp.scopes.current());
+
+        // NEXT, evaluate the proc's body in its namespace, getting the result.
+        let result = match self.bind_args(interp, argv) {
+            Ok(()) => {
+                let caller_ns = interp.set_current_namespace(&self.ns);
+                let result = interp.eval_value(&self.body);
+                interp.set_current_namespace(&caller_ns);
+                result
+            }
+            Err(exception) => Err(exception),
+        };
+
+        // NEXT, pop the scope off of the stack; we're done with it.
+        interp.proc_scope = caller_proc_scope;
+        interp.pop_scope();
+
+        Self::complete(result)
+    }
+
+    /// Converts the result of a procedure's body to the procedure's result, following the
+    /// `return -code`/`-level` protocol.
+    fn complete(result: MoltResult) -> MoltResult {
+        if let Err(mut exception) = result {
+            // FIRST, handle the return -code, -level protocol
+            if exception.code() == ResultCode::Return {
+                exception.decrement_level();
+            }
+
+            return match exception.code() {
+                ResultCode::Okay => Ok(exception.value()),
+                ResultCode::Error => Err(exception),
+                ResultCode::Return => Err(exception), // -level > 0
+                ResultCode::Break => molt_err!("invoked \"break\" outside of a loop"),
+                ResultCode::Continue => molt_err!("invoked \"continue\" outside of a loop"),
+                // As in Standard TCL, application-defined codes propagate to the caller.
+                ResultCode::Other(_) => Err(exception),
+            };
+        }
+
+        // NEXT, return the computed result.
+        // Note: no need for special handling for return, break, continue;
+        // interp.eval() returns only Ok or a real error.
+        result
+    }
+
+    /// Binds the arguments to the procedure's parameters in the current scope.
+    fn bind_args(&self, interp: &mut Interp, argv: &[Value]) -> Result<(), Exception> {
+        let mut argi = 1; // Skip the proc's name
+
+        for (speci, spec) in self.parms.iter().enumerate() {
+            // FIRST, get the parameter as a vector.  It should be a list of
+            // one or two elements.
+            let vec = &*spec.as_list()?; // Should never fail
+            assert!(vec.len() == 1 || vec.len() == 2);
+
+            // NEXT, if this is the args parameter, give the remaining args,
+            // if any.  `Procedure::new` ensures that "args" is the final arg spec
+            // in the list.
+            if vec[0].as_str() == "args" && speci == self.parms.len() - 1 {
+                interp.set_scalar("args", Value::from(&argv[argi..]))?;
+
+                // We've processed all of the args
+                argi = argv.len();
+                break;
+            }
+
+            // NEXT, do we have a matching argument?
+            if argi < argv.len() {
+                // Pair them up
+                interp.set_scalar(vec[0].as_str(), argv[argi].clone())?;
+                argi += 1;
+                continue;
+            }
+
+            // NEXT, do we have a default value?
+            if vec.len() == 2 {
+                interp.set_scalar(vec[0].as_str(), vec[1].clone())?;
+            } else {
+                // We don't; we're missing a required argument.
+                return self.wrong_num_args(&argv[0]);
+            }
+        }
+
+        // NEXT, do we have any arguments left over?
+        if argi != argv.len() {
+            return self.wrong_num_args(&argv[0]);
+        }
+
+        Ok(())
+    }
+
+    // Outputs the wrong # args message for the proc.  The name is passed in
+    // because it can be changed via the `rename` command.
+    fn wrong_num_args(&self, name: &Value) -> Result<(), Exception> {
+        let mut msg = String::new();
+        msg.push_str("wrong # args: should be \"");
+        msg.push_str(name.as_str());
+
+        for (i, arg) in self.parms.iter().enumerate() {
+            msg.push(' ');
+
+            // "args" has special meaning only in the last place.
+            if arg.as_str() == "args" && i == self.parms.len() - 1 {
+                msg.push_str("?arg ...?");
+                break;
+            }
+
+            let vec = arg.as_list().expect("error in proc arglist validation!");
+
+            if vec.len() == 1 {
+                msg.push_str(vec[0].as_str());
+            } else {
+                msg.push('?');
+                msg.push_str(vec[0].as_str());
+                msg.push('?');
+            }
+        }
+        msg.push_str("\"");
+
+        molt_err!(&msg)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::IncompleteKind;
+    use std::cell::Cell;
+
+    #[test]
+    fn test_empty() {
+        let interp = Interp::empty();
+        // Interpreter is empty
+        assert!(interp.command_names().is_empty());
+    }
+
+    #[test]
+    fn test_new() {
+        let interp = Interp::new();
+
+        // Interpreter is not empty
+        assert!(!interp.command_names().is_empty());
+
+        // Note: in theory, we should test here that the normal set of commands is present.
+        // In fact, that should be tested by the `molt test` suite.
+    }
+
+    #[test]
+    fn test_command_names_sorted() {
+        let mut interp = Interp::new();
+        interp.eval("proc zed {} {}; proc alpha {} {}").unwrap();
+
+        let names = interp.command_names();
+        assert_eq!(names, interp.command_names());
+        assert!(names.windows(2).all(|w| w[0].as_str() < w[1].as_str()));
+
+        let procs = interp.proc_names();
+        assert_eq!(procs, vec![Value::from("alpha"), Value::from("zed")]);
+    }
+
+    #[test]
+    #[cfg(feature = "os")]
+    fn test_eval_file() {
+        let dir = std::env::temp_dir();
+        let inner = dir.join("molt_eval_file_inner.tcl");
+        let outer = dir.join("molt_eval_file_outer.tcl");
+        fs::write(
+            &inner,
+            "set where [list inner $where]\nerror \"inner failed\"\n",
+        )
+        .unwrap();
+        fs::write(
+            &outer,
+            format!("set where outer\nsource {{{}}}\n", inner.display()),
+        )
+        .unwrap();
+
+        let mut interp = Interp::new();
+        let result = interp.eval_file(&outer);
+        assert_eq!(result.unwrap_err().value().as_str(), "inner failed");
+        assert_eq!(interp.scalar("where").unwrap().as_str(), "inner outer");
+        assert_eq!(interp.script_file(), None);
+
+        let info = interp.scalar("errorInfo").unwrap();
+        assert!(info
+            .as_str()
+            .contains(&format!("    (file \"{}\" line 2)", inner.display())));
+        assert!(info
+            .as_str()
+            .ends_with(&format!("    (file \"{}\" line 2)", outer.display())));
+
+        let _ = fs::remove_file(&inner);
+        let _ = fs::remove_file(&outer);
+
+        let missing = dir.join("molt_eval_file_nonesuch.tcl");
+        let msg = interp.eval_file(&missing).unwrap_err().value();
+        assert!(msg
+            .as_str()
+            .starts_with(&format!("couldn't read file \"{}\": ", missing.display())));
+    }
+
+    #[test]
+    fn test_complete_reason() {
+        let mut interp = Interp::new();
+
+        assert_eq!(interp.complete_reason("set a {b c}"), None);
+        assert_eq!(
+            interp.complete_reason("set a {b c"),
+            Some("unmatched open brace")
+        );
+        assert_eq!(
+            interp.complete_reason("set a [list b"),
+            Some("unmatched open bracket")
+        );
+        assert_eq!(
+            interp.complete_reason("set a \"b c"),
+            Some("missing close quote")
+        );
+        assert_eq!(
+            interp.complete_reason("set a ${b"),
+            Some("unmatched open brace in variable name")
+        );
+        assert_eq!(
+            interp.complete_reason("set a {b}c"),
+            Some("extra characters after close-brace")
+        );
+        assert!(!interp.complete("set a {b}c"));
+    }
+
+    #[test]
+    fn test_eval() {
+        let mut interp = Interp::new();
+
+        assert_eq!(interp.eval("set a 1"), Ok(Value::from("1")));
+        assert!(ex_match(
+            &interp.eval("error 2"),
+            Exception::molt_err(Value::from("2"))
+        ));
+        assert_eq!(interp.eval("return 3"), Ok(Value::from("3")));
+        assert!(ex_match(
+            &interp.eval("break"),
+            Exception::molt_err(Value::from("invoked \"break\" outside of a loop"))
+        ));
+        assert!(ex_match(
+            &interp.eval("continue"),
+            Exception::molt_err(Value::from("invoked \"continue\" outside of a loop"))
+        ));
+        assert!(ex_match(
+            &interp.eval("return -code 5 -level 0 x"),
+            Exception::molt_err(Value::from("command returned bad code: 5"))
+        ));
+    }
+
+    // Shows that the result is matches the given exception.  Ignores the exception's
+    // ErrorData, if any.
+    fn ex_match(r: &MoltResult, expected: Exception) -> bool {
+        // FIRST, if the results are of different types, there's no match.
+        if let Err(e) = r {
+            e.code() == expected.code() && e.value() == expected.value()
+        } else {
+            false
+        }
+    }
+
+    #[test]
+    fn test_eval_value() {
+        let mut interp = Interp::new();
+
+        assert_eq!(
+            interp.eval_value(&Value::from("set a 1")),
+            Ok(Value::from("1"))
+        );
+        assert!(ex_match(
+            &interp.eval_value(&Value::from("error 2")),
+            Exception::molt_err(Value::from("2"))
+        ));
+        assert_eq!(
+            interp.eval_value(&Value::from("return 3")),
+            Ok(Value::from("3"))
+        );
+        assert!(ex_match(
+            &interp.eval_value(&Value::from("break")),
+            Exception::molt_err(Value::from("invoked \"break\" outside of a loop"))
+        ));
+        assert!(ex_match(
+            &interp.eval_value(&Value::from("continue")),
+            Exception::molt_err(Value::from("invoked \"continue\" outside of a loop"))
+        ));
+    }
+
+    #[test]
+    fn test_eval_list() {
+        let mut interp = Interp::new();
+
+        // The words aren't substituted again.
+        let words = [
+            Value::from("set"),
+            Value::from("a"),
+            Value::from("$b [c] {d"),
+        ];
+        assert_eq!(interp.eval_list(&words), Ok(Value::from("$b [c] {d")));
+        assert_eq!(interp.eval("set a"), Ok(Value::from("$b [c] {d")));
+
+        // An empty list is a no-op.
+        assert_eq!(interp.eval_list(&[]), Ok(Value::empty()));
+
+        // Results are translated as for eval.
+        assert!(ex_match(
+            &interp.eval_list(&[Value::from("break")]),
+            Exception::molt_err(Value::from("invoked \"break\" outside of a loop"))
+        ));
+        assert!(ex_match(
+            &interp.eval_list(&[Value::from("nonesuch")]),
+            Exception::molt_err(Value::from("invalid command name \"nonesuch\""))
+        ));
+    }
+
+    #[test]
+    fn test_call() {
+        let mut interp = Interp::new();
+
+        // Procs and native commands are called with the words as is.
+        interp.eval("proc second {a b} { return $b }").unwrap();
+        let argv = [
+            Value::from("second"),
+            Value::from("[x]"),
+            Value::from("{ $y"),
+        ];
+        assert_eq!(interp.call(&argv), Ok(Value::from("{ $y")));
+        assert_eq!(
+            interp.call(&[Value::from("llength"), Value::from("a b c")]),
+            Ok(Value::from(3))
+        );
+
+        // Unknown commands go to the unknown handler.
+        interp
+            .eval("proc unknown {args} { return \"unknown: $args\" }")
+            .unwrap();
+        assert_eq!(
+            interp.call(&[Value::from("nonesuch"), Value::from(1)]),
+            Ok(Value::from("unknown: nonesuch 1"))
+        );
+
+        // Errors are reported as for eval.
+        interp.eval("rename unknown {}").unwrap();
+        assert!(interp
+            .call(&[Value::from("error"), Value::from("oops")])
+            .is_err());
+        assert_eq!(
+            interp.eval("set errorInfo"),
+            Ok(Value::from("oops\n    while executing\n\"error oops\""))
+        );
+    }
+
+    #[test]
+    fn test_eval_value_pure_list() {
+        let mut interp = Interp::new();
+
+        // A pure list is evaluated as a single command, without substitution.
+        let cmd = Value::from(vec![
+            Value::from("set"),
+            Value::from("a"),
+            Value::from("$x"),
+        ]);
+        assert_eq!(interp.eval_value(&cmd), Ok(Value::from("$x")));
+
+        // Once it has a string rep, it's evaluated as a script; canonical list quoting
+        // means the result is the same.
+        assert_eq!(cmd.as_str(), "set a {$x}");
+        assert_eq!(interp.eval_value(&cmd), Ok(Value::from("$x")));
+
+        // A parse error doesn't leave the interpreter nested.
+        assert!(interp.eval_value(&Value::from("set a {1")).is_err());
+        assert_eq!(interp.num_levels, 0);
+    }
+
+    #[test]
+    fn test_check_complete() {
+        let mut interp = Interp::new();
+
+        assert_eq!(
+            interp.check_complete("a {b} [c] \"d\""),
+            CompleteStatus::Complete
+        );
+        assert_eq!(interp.check_complete("a {b}x"), CompleteStatus::Invalid);
+        assert_eq!(
+            interp.check_complete("a {b"),
+            CompleteStatus::Incomplete {
+                kind: IncompleteKind::Brace,
+                open_at: 2
+            }
+        );
+        assert_eq!(
+            interp.check_complete("a $b [c"),
+            CompleteStatus::Incomplete {
+                kind: IncompleteKind::Bracket,
+                open_at: 5
+            }
+        );
+        assert_eq!(
+            interp.check_complete("a \"b"),
+            CompleteStatus::Incomplete {
+                kind: IncompleteKind::Quote,
+                open_at: 2
+            }
+        );
+        assert_eq!(
+            interp.check_complete("a ${b"),
+            CompleteStatus::Incomplete {
+                kind: IncompleteKind::Brace,
+                open_at: 3
+            }
+        );
+
+        // The innermost unterminated construct is reported.
+        assert_eq!(
+            interp.check_complete("a [b \"c {d"),
+            CompleteStatus::Incomplete {
+                kind: IncompleteKind::Quote,
+                open_at: 5
+            }
+        );
+        assert_eq!(
+            interp.check_complete("a [b [c {d} [e"),
+            CompleteStatus::Incomplete {
+                kind: IncompleteKind::Bracket,
+                open_at: 12
+            }
+        );
+        assert_eq!(
+            interp.check_complete("a {b\n{c}\n{d"),
+            CompleteStatus::Incomplete {
+                kind: IncompleteKind::Brace,
+                open_at: 2
+            }
+        );
+        assert_eq!(
+            interp.check_complete("a [b {c [d"),
+            CompleteStatus::Incomplete {
+                kind: IncompleteKind::Brace,
+                open_at: 5
+            }
+        );
+    }
+
+    #[test]
+    fn test_complete() {
+        let mut interp = Interp::new();
+
+        assert!(interp.complete("abc"));
+        assert!(interp.complete("a {bc} [def] \"ghi\" xyz"));
+
+        assert!(!interp.complete("a {bc"));
+        assert!(!interp.complete("a [bc"));
+        assert!(!interp.complete("a \"bc"));
+    }
+
+    #[test]
+    fn test_expr() {
+        let mut interp = Interp::new();
+        assert_eq!(interp.expr(&Value::from("1 + 2")), Ok(Value::from(3)));
+        assert_eq!(
+            interp.expr(&Value::from("a + b")),
+            Err(Exception::molt_err(Value::from(
+                "unknown math function \"a\""
+            )))
+        );
+    }
+
+    #[test]
+    fn test_expr_bool() {
+        let mut interp = Interp::new();
+        assert_eq!(interp.expr_bool(&Value::from("1")), Ok(true));
+        assert_eq!(interp.expr_bool(&Value::from("0")), Ok(false));
+        assert_eq!(interp.expr_bool(&Value::from("\"yes\"")), Ok(true));
+        assert_eq!(interp.expr_bool(&Value::from("0.5")), Ok(true));
+        assert_eq!(
+            interp.expr_bool(&Value::from("a")),
+            Err(Exception::molt_err(Value::from(
+                "unknown math function \"a\""
+            )))
+        );
+    }
+
+    #[test]
+    fn test_expr_int() {
+        let mut interp = Interp::new();
+        assert_eq!(interp.expr_int(&Value::from("1 + 2")), Ok(3));
+        assert_eq!(interp.expr_int(&Value::from("1.5 * 2")), Ok(3));
+        assert_eq!(interp.expr_int(&Value::from("\"0x10\"")), Ok(16));
+        assert!(interp.expr_int(&Value::from("1.5")).is_err());
+        assert_eq!(
+            interp.expr_int(&Value::from("a")),
+            Err(Exception::molt_err(Value::from(
+                "unknown math function \"a\""
+            )))
+        );
+    }
+
+    #[test]
+    fn test_expr_float() {
+        let mut interp = Interp::new();
+        let val = interp
+            .expr_float(&Value::from("1.1 + 2.2"))
+            .expect("floating point value");
+
+        assert!((val - 3.3).abs() < 0.001);
+        assert_eq!(interp.expr_float(&Value::from("1 + 2")), Ok(3.0));
+        assert_eq!(interp.expr_float(&Value::from("\"2.5\"")), Ok(2.5));
+
+        assert_eq!(
+            interp.expr_float(&Value::from("a")),
+            Err(Exception::molt_err(Value::from(
+                "unknown math function \"a\""
+            )))
+        );
+    }
+
+    #[test]
+    fn test_recursion_limit() {
+        let mut interp = Interp::new();
+
+        assert_eq!(interp.recursion_limit(), 1000);
+        interp.set_recursion_limit(100);
+        assert_eq!(interp.recursion_limit(), 100);
+
+        assert!(dbg!(interp.eval("proc myproc {} { myproc }")).is_ok());
+        assert!(ex_match(
+            &interp.eval("myproc"),
+            Exception::molt_err(Value::from(
+                "too many nested calls to Interp::eval (infinite loop?)"
+            ))
+        ));
+    }
+
+    #[test]
+    fn test_spare_words() {
+        let mut interp = Interp::new();
+
+        // Nested commands fill the pool of spare word lists; an error in a command
+        // substitution returns the list to the pool.
+        interp.eval("list [list [list [list]]]").unwrap();
+        let spares = interp.spare_words.len();
+        assert!(interp.eval("set a [error oops]").is_err());
+        assert!(interp.eval("set a [set b [nonesuch]]").is_err());
+        assert_eq!(interp.spare_words.len(), spares);
+    }
+
+    #[test]
+    fn test_nesting_limit() {
+        let mut interp = Interp::new();
+        interp.set_recursion_limit(50);
+        let nest =
+            |n: usize, open: &str, close: &str| format!("{}1{}", open.repeat(n), close.repeat(n));
+
+        // Command substitutions count against the recursion limit.
+        let script = format!("set x {}", nest(20, "[list ", "]"));
+        assert_eq!(interp.eval(&script).unwrap().as_str(), "1");
+        assert_eq!(
+            interp
+                .eval(&format!("set x {}", nest(100, "[list ", "]")))
+                .unwrap_err()
+                .value()
+                .as_str(),
+            "too many nested calls to Interp::eval (infinite loop?)"
+        );
+
+        // As do subexpressions.
+        assert_eq!(
+            interp.expr(&Value::from(nest(20, "(", ")"))),
+            Ok(Value::from(1))
+        );
+        assert_eq!(
+            interp
+                .expr(&Value::from(nest(10000, "(", ")")))
+                .unwrap_err()
+                .value()
+                .as_str(),
+            "too many nested calls to Interp::eval (infinite loop?)"
+        );
+        assert!(interp
+            .expr(&Value::from(nest(10000, "1 ? ", " : 0")))
+            .is_err());
+        assert!(interp.expr(&Value::from(nest(10000, "abs(", ")"))).is_err());
+
+        // The limit is restored afterwards.
+        assert_eq!(interp.num_levels, 0);
+        assert!(interp.eval(&script).is_ok());
+    }
+
+    #[test]
+    fn test_unknown_handler() {
+        fn cmd_unknown(interp: &mut Interp, _: ContextID, argv: &[Value]) -> MoltResult {
+            if argv[0].as_str() == "recurse" {
+                // Calling another missing command from the handler is an error.
+                interp.eval("alsomissing")
+            } else {
+                molt_ok!(format!("native: {}", Value::from(argv)))
+            }
+        }
+
+        let mut interp = Interp::new();
+        interp.set_unknown_handler(cmd_unknown);
+
+        assert_eq!(
+            interp.eval("nonesuch 1 2"),
+            Ok(Value::from("native: nonesuch 1 2"))
+        );
+        assert!(ex_match(
+            &interp.eval("recurse"),
+            Exception::molt_err(Value::from("invalid command name \"alsomissing\""))
+        ));
+
+        // An unknown command takes precedence over the native handler.
+        assert!(interp
+            .eval("proc unknown {args} { return \"proc: $args\" }")
+            .is_ok());
+        assert_eq!(
+            interp.eval("nonesuch 1 2"),
+            Ok(Value::from("proc: nonesuch 1 2"))
+        );
+
+        interp.remove_command("unknown");
+        assert_eq!(interp.eval("nonesuch"), Ok(Value::from("native: nonesuch")));
+    }
+
+    #[test]
+    fn test_var_str() {
+        let mut interp = Interp::new();
+
+        assert!(interp.set_var_str("a", "1").is_ok());
+        assert!(interp.set_var_str("b(x)", "2").is_ok());
+        assert_eq!(*interp.var_str("a").unwrap(), "1");
+        assert_eq!(*interp.var_str("b(x)").unwrap(), "2");
+
+        // Errors
+        assert_eq!(
+            interp.set_var_str("a(1)", "3"),
+            molt_err!("can't set \"a(1)\": variable isn't array")
+        );
+        assert_eq!(
+            interp.set_var_str("b", "3"),
+            molt_err!("can't set \"b\": variable is array")
+        );
+        assert_eq!(
+            interp.var_str("nonesuch"),
+            molt_err!("can't read \"nonesuch\": no such variable")
+        );
+    }
+
+    // The process environment is shared by all threads, and tests run in parallel; so the
+    // tests that change it are steps of this one test, and run one at a time.
+    #[test]
+    #[cfg(feature = "os")]
+    fn test_env() {
+        env_linked();
+        env_unlinked();
+        #[cfg(unix)]
+        env_non_unicode();
+        env_reset_vars();
+    }
+
+    #[cfg(feature = "os")]
+    fn env_linked() {
+        let mut interp = Interp::new();
+        interp.link_env();
+
+        // Sets and unsets are written back to the process environment.
+        assert!(interp.eval("set env(MOLT_TEST_VAR) abc").is_ok());
+        assert_eq!(std::env::var("MOLT_TEST_VAR"), Ok("abc".into()));
+
+        assert!(interp
+            .eval("proc setit {} { global env; set env(MOLT_TEST_VAR) def }")
+            .is_ok());
+        assert!(interp.eval("setit").is_ok());
+        assert_eq!(std::env::var("MOLT_TEST_VAR"), Ok("def".into()));
+
+        assert!(interp.eval("unset env(MOLT_TEST_VAR)").is_ok());
+        assert!(std::env::var("MOLT_TEST_VAR").is_err());
+
+        // Changes made from Rust are visible on the next read.
+        std::env::set_var("MOLT_TEST_VAR2", "xyz");
+        assert_eq!(
+            interp.eval("set env(MOLT_TEST_VAR2)"),
+            Ok(Value::from("xyz"))
+        );
+        std::env::remove_var("MOLT_TEST_VAR2");
+        assert!(interp.eval("set env(MOLT_TEST_VAR2)").is_err());
+
+        // Names the environment can't represent are rejected.
+        assert!(ex_match(
+            &interp.eval("set env(A=B) 1"),
+            Exception::molt_err(Value::from(
+                "can't set \"env(A=B)\": invalid environment variable"
+            ))
+        ));
+    }
+
+    #[cfg(feature = "os")]
+    fn env_unlinked() {
+        // By default, env() is a copy of the environment.
+        let mut interp = Interp::new();
+        std::env::set_var("MOLT_TEST_UNLINKED", "abc");
+        assert!(interp.eval("set env(MOLT_TEST_UNLINKED) def").is_ok());
+        assert_eq!(std::env::var("MOLT_TEST_UNLINKED"), Ok("abc".into()));
+
+        // A child of a linked interpreter gets a copy, too.
+        interp.link_env();
+        let child = interp.create_child("child", false).unwrap();
+        assert!(child.eval("set env(MOLT_TEST_UNLINKED) ghi").is_ok());
+        assert!(child.eval("unset env(MOLT_TEST_UNLINKED)").is_ok());
+        assert_eq!(std::env::var("MOLT_TEST_UNLINKED"), Ok("abc".into()));
+        std::env::remove_var("MOLT_TEST_UNLINKED");
+
+        // A safe interpreter can't be linked.
+        let mut interp = Interp::new_safe();
+        interp.link_env();
+        assert_eq!(interp.eval("info exists ::env"), Ok(Value::from(false)));
+    }
+
+    #[cfg(all(feature = "os", unix))]
+    fn env_non_unicode() {
+        use std::ffi::OsStr;
+        use std::os::unix::ffi::OsStrExt;
+
+        // Variables that aren't valid Unicode are skipped rather than panicking.
+        std::env::set_var("MOLT_TEST_NON_UNICODE", OsStr::from_bytes(b"\xff"));
+        let mut interp = Interp::new();
+        assert_eq!(
+            interp.eval("info exists env(MOLT_TEST_NON_UNICODE)"),
+            Ok(Value::from(false))
+        );
+
+        interp.link_env();
+        let names = interp.eval("array names env").unwrap();
+        assert!(!names
+            .as_list()
+            .unwrap()
+            .contains(&Value::from("MOLT_TEST_NON_UNICODE")));
+        assert!(interp.eval("array size env").is_ok());
+        assert!(interp.eval("array get env").is_ok());
+        std::env::remove_var("MOLT_TEST_NON_UNICODE");
+    }
+
+    #[test]
+    fn test_exec_trace() {
+        let mut interp = Interp::new();
+        let log = Rc::new(RefCell::new(Vec::new()));
+
+        let hook_log = Rc::clone(&log);
+        interp.set_exec_trace(Some(Box::new(move |words, level| {
+            hook_log
+                .borrow_mut()
+                .push(format!("{} {}", level, list_to_string(words)));
+        })));
+
+        assert!(interp
+            .eval("proc double {x} { expr {2 * $x} }; set a 3")
+            .is_ok());
+        log.borrow_mut().clear();
+
+        // The hook sees the substituted words, in execution order.  Command substitutions
+        // are evaluated before their command is executed, so they're at the same level.
+        assert_eq!(
+            interp.eval("set b [double [double $a]]"),
+            Ok(Value::from(12))
+        );
+        assert_eq!(
+            *log.borrow(),
+            vec![
+                "1 double 3",
+                "2 expr {2 * $x}",
+                "1 double 6",
+                "2 expr {2 * $x}",
+                "1 set b 12"
+            ]
+        );
+
+        // Once the hook is cleared, it's no longer called.
+        interp.set_exec_trace(None);
+        log.borrow_mut().clear();
+        assert!(interp.eval("double 1").is_ok());
+        assert!(log.borrow().is_empty());
+    }
+
+    #[test]
+    fn test_exec_trace_redefines_commands() {
+        let mut interp = Interp::new();
+
+        // The hook can't see the interpreter, so the command table can change freely
+        // while it's set.
+        interp.set_exec_trace(Some(Box::new(|_, _| {})));
+        assert_eq!(
+            interp.eval("proc p {} { proc p {} { return 2 }; return 1 }; list [p] [p]"),
+            Ok(Value::from("1 2"))
+        );
+
+        interp.set_trace_to_stderr(true);
+        assert!(interp.exec_trace.is_some());
+        interp.set_trace_to_stderr(false);
+        assert!(interp.exec_trace.is_none());
+    }
+
+    // Installs a debugger hook that logs each pause as "level command" and then returns
+    // the next of the given actions, or Continue once they're used up.
+    fn add_debug_log(interp: &mut Interp, actions: &[DebugAction]) -> Rc<RefCell<Vec<String>>> {
+        let log = Rc::new(RefCell::new(Vec::new()));
+        let hook_log = Rc::clone(&log);
+        let mut actions = actions.to_vec().into_iter();
+
+        interp.set_debug_hook(Some(Box::new(move |_, words, level| {
+            hook_log
+                .borrow_mut()
+                .push(format!("{} {}", level, list_to_string(words)));
+            actions.next().unwrap_or(DebugAction::Continue)
+        })));
+        log
+    }
+
+    #[test]
+    fn test_debug_breakpoint() {
+        let mut interp = Interp::new();
+        assert!(interp
+            .eval("proc double {x} { expr {2 * $x} }; proc quad {x} { double [double $x] }")
+            .is_ok());
+        let log = add_debug_log(&mut interp, &[]);
+
+        // No breakpoints, no pauses.
+        assert_eq!(interp.eval("quad 1"), Ok(Value::from(4)));
+        assert!(log.borrow().is_empty());
+
+        interp.set_breakpoint("double");
+        assert_eq!(interp.eval("quad 1"), Ok(Value::from(4)));
+        assert_eq!(*log.borrow(), vec!["2 double 1", "2 double 2"]);
+
+        log.borrow_mut().clear();
+        interp.clear_breakpoint("double");
+        assert_eq!(interp.eval("quad 1"), Ok(Value::from(4)));
+        assert!(log.borrow().is_empty());
+    }
+
+    #[test]
+    fn test_debug_step() {
+        let mut interp = Interp::new();
+        assert!(interp
+            .eval("proc double {x} { expr {2 * $x} }; proc quad {x} { double [double $x] }")
+            .is_ok());
+        interp.set_breakpoint("quad");
+
+        // Step into the procedures.
+        let log = add_debug_log(&mut interp, &[DebugAction::Step; 4]);
+        assert_eq!(interp.eval("quad 1; set a done"), Ok(Value::from("done")));
+        assert_eq!(
+            *log.borrow(),
+            vec![
+                "1 quad 1",
+                "2 double 1",
+                "3 expr {2 * $x}",
+                "2 double 2",
+                "3 expr {2 * $x}"
+            ]
+        );
+
+        // Step over them.
+        let log = add_debug_log(&mut interp, &[DebugAction::StepOver; 2]);
+        assert_eq!(interp.eval("quad 1; set a done"), Ok(Value::from("done")));
+        assert_eq!(*log.borrow(), vec!["1 quad 1", "1 set a done"]);
+
+        // Stepping ends with the evaluation.
+        let log = add_debug_log(&mut interp, &[DebugAction::Step]);
+        assert!(interp.eval("set a 1").is_ok());
+        assert!(log.borrow().is_empty());
+
+        // Unless it's requested.
+        interp.break_next();
+        assert!(interp.eval("set a 1").is_ok());
+        assert_eq!(*log.borrow(), vec!["1 set a 1"]);
+    }
+
+    #[test]
+    fn test_debug_abort() {
+        let mut interp = Interp::new();
+        interp.set_breakpoint("incr");
+        let log = add_debug_log(&mut interp, &[DebugAction::Abort]);
+
+        // The abort can't be caught.
+        assert_eq!(
+            interp
+                .eval("set a 0; catch {incr a}; set a 5")
+                .unwrap_err()
+                .value()
+                .as_str(),
+            "evaluation aborted by debugger"
+        );
+        assert_eq!(*log.borrow(), vec!["2 incr a"]);
+        assert_eq!(interp.scalar("a"), Ok(Value::from(0)));
+
+        // The next evaluation proceeds normally.
+        assert_eq!(interp.eval("incr a"), Ok(Value::from(1)));
+    }
+
+    #[test]
+    fn test_debug_hook_uses_interp() {
+        let mut interp = Interp::new();
+        assert!(interp.eval("proc double {x} { expr {2 * $x} }").is_ok());
+        interp.set_breakpoint("double");
+
+        // The hook can evaluate scripts, even ones that hit the breakpoint.
+        interp.set_debug_hook(Some(Box::new(|interp: &mut Interp, _: &[Value], _| {
+            let value = interp.eval("double 10").unwrap();
+            interp.set_scalar("::seen", value).unwrap();
+            DebugAction::Continue
+        })));
+        assert_eq!(interp.eval("double 1"), Ok(Value::from(2)));
+        assert_eq!(interp.scalar("seen"), Ok(Value::from(20)));
+    }
+
+    //-----------------------------------------------------------------------
+    // Ensemble tests
+
+    // Defines the `counter` ensemble: `counter incr ?by?`, `counter get`, and
+    // `counter stats dump`, where `get` and `dump` share the counter's context.
+    fn add_counter(interp: &mut Interp) -> ContextID {
+        let id = interp.save_context(0 as MoltInt);
+
+        let mut stats = Ensemble::new();
+        stats.add_context_command("dump", cmd_counter_dump, id);
+
+        let mut counter = Ensemble::new();
+        counter
+            .add_proc(
+                "incr",
+                &[Value::from("by 1")],
+                &Value::from("counter set [expr {[counter get] + $by}]"),
+            )
+            .unwrap();
+        counter.add_context_command("get", cmd_counter_get, id);
+        counter.add_context_command("set", cmd_counter_set, id);
+        counter.add_ensemble("stats", stats);
+        interp.add_ensemble("counter", counter);
+        id
+    }
+
+    fn cmd_counter_get(interp: &mut Interp, context_id: ContextID, argv: &[Value]) -> MoltResult {
+        check_args(2, argv, 2, 2, "")?;
+        molt_ok!(*interp.context::<MoltInt>(context_id))
+    }
+
+    fn cmd_counter_set(interp: &mut Interp, context_id: ContextID, argv: &[Value]) -> MoltResult {
+        check_args(2, argv, 3, 3, "value")?;
+        *interp.context::<MoltInt>(context_id) = argv[2].as_int()?;
+        molt_ok!(argv[2].clone())
+    }
+
+    fn cmd_counter_dump(interp: &mut Interp, context_id: ContextID, argv: &[Value]) -> MoltResult {
+        check_args(3, argv, 3, 3, "")?;
+        molt_ok!(format!("count={}", interp.context::<MoltInt>(context_id)))
+    }
+
+    #[test]
+    fn test_ensemble() {
+        let mut interp = Interp::new();
+        add_counter(&mut interp);
+
+        assert_eq!(interp.command_type("counter"), Ok(Value::from("ensemble")));
+        assert_eq!(interp.eval("counter incr"), Ok(Value::from(1)));
+        assert_eq!(interp.eval("counter incr 5"), Ok(Value::from(6)));
+        assert_eq!(interp.eval("counter get"), Ok(Value::from(6)));
+        assert_eq!(
+            interp.eval("counter stats dump"),
+            Ok(Value::from("count=6"))
+        );
+    }
+
+    #[test]
+    fn test_ensemble_errors() {
+        let mut interp = Interp::new();
+        add_counter(&mut interp);
+
+        assert_eq!(
+            interp.eval("counter").unwrap_err().value(),
+            Value::from("wrong # args: should be \"counter subcommand ?arg ...?\"")
+        );
+        assert_eq!(
+            interp.eval("counter nonesuch").unwrap_err().value(),
+            Value::from(
+                "unknown or ambiguous subcommand \"nonesuch\": must be get, incr, set, or stats"
+            )
+        );
+        assert_eq!(
+            interp.eval("counter stats").unwrap_err().value(),
+            Value::from("wrong # args: should be \"counter stats subcommand ?arg ...?\"")
+        );
+        assert_eq!(
+            interp.eval("counter stats nonesuch").unwrap_err().value(),
+            Value::from("unknown or ambiguous subcommand \"stats nonesuch\": must be dump")
+        );
+        assert_eq!(
+            interp.eval("counter incr 1 2").unwrap_err().value(),
+            Value::from("wrong # args: should be \"counter incr ?by?\"")
+        );
+        assert_eq!(
+            interp.eval("counter stats dump extra").unwrap_err().value(),
+            Value::from("wrong # args: should be \"counter stats dump\"")
+        );
+
+        let mut ensemble = Ensemble::new();
+        assert_eq!(
+            ensemble.add_proc("bad", &[Value::from("")], &Value::empty()),
+            molt_err!("argument with no name")
+        );
+    }
+
+    #[test]
+    fn test_ensemble_contexts() {
+        let mut interp = Interp::new();
+        let id = add_counter(&mut interp);
+
+        // The context is shared by several subcommands; it's released with the command.
+        assert_eq!(*interp.context::<MoltInt>(id), 0);
+        interp.remove_command("counter");
+        assert!(!interp.context_map.contains_key(&id));
+    }
+
+    #[test]
+    fn test_ensemble_remove_command() {
+        let mut interp = Interp::new();
+        let id = add_counter(&mut interp);
+
+        let mut counter = interp.ensemble("counter").unwrap();
+        assert_eq!(counter.command_names(), vec!["get", "incr", "set", "stats"]);
+        assert!(counter.has_command("get"));
+        assert!(!counter.has_command("nonesuch"));
+
+        counter.remove_command("incr");
+        counter.remove_command("nonesuch");
+        assert!(!counter.has_command("incr"));
+        assert_eq!(counter.command_names(), vec!["get", "set", "stats"]);
+
+        // Put it back; the context is retained, and released with the command.
+        interp.add_ensemble("counter", counter);
+        assert_eq!(interp.eval("counter set 5"), Ok(Value::from(5)));
+        assert_eq!(
+            interp.eval("counter incr").unwrap_err().value().as_str(),
+            "unknown or ambiguous subcommand \"incr\": must be get, set, or stats"
+        );
+        interp.remove_command("counter");
+        assert!(!interp.context_map.contains_key(&id));
+
+        // Only ensembles defined at run time can be retrieved.
+        assert!(interp.ensemble("nonesuch").is_none());
+        assert!(interp.ensemble("info").is_none());
+    }
+
+    #[test]
+    fn test_info_commands_ensemble() {
+        let mut interp = Interp::new();
+        add_counter(&mut interp);
+
+        assert_eq!(
+            interp.eval("info commands -ensemble counter"),
+            Ok(Value::from("get incr set stats"))
+        );
+
+        // Only the added subcommands of an extended binary ensemble are known.
+        interp
+            .extend_ensemble("info", |info| {
+                info.add_proc("answer", &[], &Value::from("return 42"))
+            })
+            .unwrap();
+        assert_eq!(
+            interp.eval("info commands -ensemble info"),
+            Ok(Value::from("answer"))
+        );
+
+        assert_eq!(
+            interp
+                .eval("info commands -ensemble string")
+                .unwrap_err()
+                .value()
+                .as_str(),
+            "\"string\" isn't an ensemble"
+        );
+    }
+
+    #[test]
+    fn test_add_ensemble_from_table() {
+        const TABLE: [Subcommand; 2] = [
+            Subcommand("get", cmd_table_get),
+            Subcommand("name", cmd_table_name),
+        ];
+
+        let mut interp = Interp::new();
+        interp.add_ensemble_from_table("table", &TABLE);
+
+        assert_eq!(interp.eval("table get"), Ok(Value::from("got")));
+        assert_eq!(interp.eval("table name"), Ok(Value::from("table name")));
+        assert_eq!(
+            interp.eval("table nonesuch").unwrap_err().value().as_str(),
+            "unknown or ambiguous subcommand \"nonesuch\": must be get or name"
+        );
+        assert_eq!(interp.command_type("table"), Ok(Value::from("ensemble")));
+    }
+
+    fn cmd_table_get(_: &mut Interp, _: ContextID, _: &[Value]) -> MoltResult {
+        molt_ok!("got")
+    }
+
+    fn cmd_table_name(_: &mut Interp, _: ContextID, argv: &[Value]) -> MoltResult {
+        molt_ok!(format!("{} {}", argv[0], argv[1]))
+    }
+
+    #[test]
+    fn test_extend_ensemble() {
+        let mut interp = Interp::new();
+        let id = add_counter(&mut interp);
+
+        interp
+            .extend_ensemble("counter", |counter| {
+                counter.add_proc("reset", &[], &Value::from("counter set 0"))
+            })
+            .unwrap();
+
+        // The existing subcommands and contexts are retained.
+        assert!(interp.context_map.contains_key(&id));
+        assert_eq!(interp.eval("counter incr 3"), Ok(Value::from(3)));
+        assert_eq!(interp.eval("counter reset"), Ok(Value::from(0)));
+
+        // Binary ensembles can be extended too.
+        interp
+            .extend_ensemble("info", |info| {
+                info.add_proc("answer", &[], &Value::from("return 42"))
+            })
+            .unwrap();
+        assert_eq!(interp.eval("info answer"), Ok(Value::from(42)));
+        assert_eq!(interp.eval("info exists nonesuch"), Ok(Value::from(false)));
+
+        // Errors
+        assert!(interp.eval("proc myproc {} {}").is_ok());
+        assert_eq!(
+            interp.extend_ensemble("myproc", |_| Ok(())),
+            molt_err!("\"myproc\" isn't an ensemble")
+        );
+        assert_eq!(
+            interp.extend_ensemble("nonesuch", |_| Ok(())),
+            molt_err!("invalid command name \"nonesuch\"")
+        );
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    //-----------------------------------------------------------------------
+    // Child Interpreter tests
 
     #[test]
-    fn test_empty() {
-        let interp = Interp::empty();
-        // Interpreter is empty
-        assert!(interp.command_names().is_empty());
+    fn test_new_safe() {
+        let interp = Interp::new_safe();
+
+        assert!(interp.is_safe());
+        assert!(!interp.has_command("exit"));
+        assert!(!interp.has_command("source"));
+        assert!(interp.has_command("set"));
+        assert!(!Interp::new().is_safe());
     }
 
     #[test]
-    fn test_new() {
-        let interp = Interp::new();
+    fn test_command_sets() {
+        let mut interp = Interp::empty();
+        interp.add_core_commands();
+        assert!(interp.has_command("set"));
+        assert!(interp.has_command("proc"));
+        assert!(!interp.has_command("if"));
+        assert!(!interp.has_command("string"));
+        assert!(!interp.has_command("list"));
+        assert!(!interp.has_command("source"));
 
-        // Interpreter is not empty
-        assert!(!interp.command_names().is_empty());
+        interp.add_control_commands();
+        assert!(interp.has_command("if"));
+        assert!(interp.has_command("while"));
 
-        // Note: in theory, we should test here that the normal set of commands is present.
-        // In fact, that should be tested by the `molt test` suite.
+        interp.add_string_commands();
+        assert!(interp.has_command("string"));
+
+        interp.add_list_commands();
+        assert!(interp.has_command("list"));
+
+        #[cfg(feature = "os")]
+        {
+            interp.add_os_commands();
+            assert!(interp.has_command("source"));
+            assert!(interp.has_command("open"));
+            assert_eq!(interp.eval("info exists ::env"), Ok(Value::from(true)));
+        }
+
+        // The sets together make up the standard commands, less the developer tools.
+        let mut names: Vec<String> = interp
+            .command_names()
+            .iter()
+            .map(|name| name.to_string())
+            .collect();
+        names.extend(
+            ["parse", "pclear", "pdump"]
+                .iter()
+                .map(|name| name.to_string()),
+        );
+        names.sort();
+
+        let mut standard: Vec<String> = Interp::new()
+            .command_names()
+            .iter()
+            .map(|name| name.to_string())
+            .collect();
+        standard.sort();
+        assert_eq!(names, standard);
     }
 
     #[test]
-    fn test_eval() {
+    fn test_make_safe() {
         let mut interp = Interp::new();
+        interp.add_command("mine", dummy_cmd);
+        interp.make_safe();
 
-        assert_eq!(interp.eval("set a 1"), Ok(Value::from("1")));
-        assert!(ex_match(
-            &interp.eval("error 2"),
-            Exception::molt_err(Value::from("2"))
-        ));
-        assert_eq!(interp.eval("return 3"), Ok(Value::from("3")));
-        assert!(ex_match(
-            &interp.eval("break"),
-            Exception::molt_err(Value::from("invoked \"break\" outside of a loop"))
-        ));
-        assert!(ex_match(
-            &interp.eval("continue"),
-            Exception::molt_err(Value::from("invoked \"continue\" outside of a loop"))
-        ));
+        assert!(interp.is_safe());
+        for name in UNSAFE_COMMANDS {
+            assert!(!interp.has_command(name), "command: {}", name);
+        }
+        assert!(interp.has_command("mine"));
+        assert_eq!(interp.eval("info exists ::env"), Ok(Value::from(false)));
     }
 
-    // Shows that the result is matches the given exception.  Ignores the exception's
-    // ErrorData, if any.
-    fn ex_match(r: &MoltResult, expected: Exception) -> bool {
-        // FIRST, if the results are of different types, there's no match.
-        if let Err(e) = r {
-            e.code() == expected.code() && e.value() == expected.value()
-        } else {
-            false
-        }
+    #[test]
+    fn test_child_basic_use() {
+        let mut interp = Interp::new();
+
+        assert!(interp.create_child("child", false).is_ok());
+        assert!(interp.has_child("child"));
+        assert!(interp.create_child("child", false).is_err());
+
+        interp.child("child").unwrap().eval("set a 1").unwrap();
+        assert_eq!(
+            interp.child("child").unwrap().eval("set a"),
+            Ok(Value::from(1))
+        );
+        assert!(interp.eval("set a").is_err());
+
+        assert!(interp.delete_child("child").is_ok());
+        assert!(!interp.has_child("child"));
+        assert!(interp.child("child").is_err());
+        assert!(interp.delete_child("child").is_err());
     }
 
     #[test]
-    fn test_eval_value() {
+    fn test_child_of_safe_is_safe() {
+        let mut interp = Interp::new_safe();
+
+        assert!(interp.create_child("child", false).unwrap().is_safe());
+    }
+
+    #[test]
+    fn test_command_limit() {
         let mut interp = Interp::new();
 
+        // Commands and loop bodies count as steps.
+        interp.set_command_limit(Some(4));
+        assert!(interp.eval("set a 1; set b 2").is_ok());
         assert_eq!(
-            interp.eval_value(&Value::from("set a 1")),
-            Ok(Value::from("1"))
+            interp
+                .eval("foreach x {1 2 3} {}")
+                .unwrap_err()
+                .value()
+                .as_str(),
+            "command count limit exceeded"
         );
-        assert!(ex_match(
-            &interp.eval_value(&Value::from("error 2")),
-            Exception::molt_err(Value::from("2"))
-        ));
+
+        // Catching the error doesn't help.
+        interp.set_command_limit(Some(100));
         assert_eq!(
-            interp.eval_value(&Value::from("return 3")),
-            Ok(Value::from("3"))
+            interp
+                .eval("catch {while {1} {}}; set a 1")
+                .unwrap_err()
+                .value()
+                .as_str(),
+            "command count limit exceeded"
         );
-        assert!(ex_match(
-            &interp.eval_value(&Value::from("break")),
-            Exception::molt_err(Value::from("invoked \"break\" outside of a loop"))
-        ));
-        assert!(ex_match(
-            &interp.eval_value(&Value::from("continue")),
-            Exception::molt_err(Value::from("invoked \"continue\" outside of a loop"))
-        ));
+        assert!(interp.eval("set a 1").is_err());
+
+        interp.set_command_limit(None);
+        assert_eq!(interp.eval("set a 1"), molt_ok!("1"));
     }
 
     #[test]
-    fn test_complete() {
+    fn test_time_limit() {
         let mut interp = Interp::new();
 
-        assert!(interp.complete("abc"));
-        assert!(interp.complete("a {bc} [def] \"ghi\" xyz"));
+        interp.set_time_limit(Some(Duration::from_millis(10)));
+        assert_eq!(
+            interp.eval("while {1} {}").unwrap_err().value().as_str(),
+            "time limit exceeded"
+        );
+        assert!(interp.eval("set a 1").is_err());
 
-        assert!(!interp.complete("a {bc"));
-        assert!(!interp.complete("a [bc"));
-        assert!(!interp.complete("a \"bc"));
+        interp.set_time_limit(None);
+        assert_eq!(interp.eval("set a 1"), molt_ok!("1"));
     }
 
     #[test]
-    fn test_expr() {
+    fn test_set_clock() {
+        fn frozen_clock() -> Duration {
+            Duration::from_secs(1)
+        }
+
         let mut interp = Interp::new();
-        assert_eq!(interp.expr(&Value::from("1 + 2")), Ok(Value::from(3)));
+        assert!(interp.now().is_some());
+
+        // A clock that never advances: no time passes, and so no time limit expires.
+        interp.set_clock(Some(frozen_clock));
+        assert_eq!(interp.now(), Some(Duration::from_secs(1)));
         assert_eq!(
-            interp.expr(&Value::from("a + b")),
-            Err(Exception::molt_err(Value::from(
-                "unknown math function \"a\""
-            )))
+            interp.eval("time {set a 1} 10"),
+            molt_ok!("0 nanoseconds per iteration")
+        );
+        interp.set_time_limit(Some(Duration::from_millis(10)));
+        assert_eq!(interp.eval("set a 1"), molt_ok!("1"));
+        interp.set_time_limit(None);
+
+        // No clock: time is unavailable, but the interpreter otherwise works.
+        interp.set_clock(None);
+        assert_eq!(interp.now(), None);
+        assert_eq!(
+            interp.eval("time {set a 1}").unwrap_err().value().as_str(),
+            "time isn't available: the interpreter has no clock"
         );
+        assert_eq!(interp.eval("set a 1"), molt_ok!("1"));
     }
 
     #[test]
-    fn test_expr_bool() {
+    #[cfg(not(feature = "os"))]
+    fn test_exit_without_os() {
         let mut interp = Interp::new();
-        assert_eq!(interp.expr_bool(&Value::from("1")), Ok(true));
-        assert_eq!(interp.expr_bool(&Value::from("0")), Ok(false));
-        assert_eq!(
-            interp.expr_bool(&Value::from("a")),
-            Err(Exception::molt_err(Value::from(
-                "unknown math function \"a\""
-            )))
-        );
+
+        let exception = interp.eval("exit 3").unwrap_err();
+        assert_eq!(exception.value().as_str(), "exit 3");
+        assert_eq!(exception.error_code().as_str(), "EXIT 3");
+        assert_eq!(interp.eval("catch {exit} msg; set msg"), molt_ok!("exit 0"));
     }
 
     #[test]
-    fn test_expr_int() {
+    fn test_child_limits() {
         let mut interp = Interp::new();
-        assert_eq!(interp.expr_int(&Value::from("1 + 2")), Ok(3));
+        interp.create_child("child", false).unwrap();
+
+        // The child's steps count against the parent's limit.
+        interp.set_command_limit(Some(1000));
         assert_eq!(
-            interp.expr_int(&Value::from("a")),
-            Err(Exception::molt_err(Value::from(
-                "unknown math function \"a\""
-            )))
+            interp
+                .eval("catch {interp eval child {while {1} {}}} result; set result")
+                .unwrap_err()
+                .value()
+                .as_str(),
+            "command count limit exceeded"
+        );
+
+        // The child's own limits are restored afterwards.
+        interp.set_command_limit(None);
+        assert_eq!(interp.child("child").unwrap().command_limit, None);
+        assert!(interp.eval("interp eval child {set a 1}").is_ok());
+
+        interp.set_time_limit(Some(Duration::from_millis(10)));
+        assert_eq!(
+            interp
+                .eval("interp eval child {while {1} {}}")
+                .unwrap_err()
+                .value()
+                .as_str(),
+            "time limit exceeded"
         );
     }
 
     #[test]
-    fn test_expr_float() {
+    fn test_parent_alias() {
         let mut interp = Interp::new();
-        let val = interp
-            .expr_float(&Value::from("1.1 + 2.2"))
-            .expect("floating point value");
+        interp.set_scalar("a", Value::from("parent")).unwrap();
 
-        assert!((val - 3.3).abs() < 0.001);
+        let child = interp.create_child("child", true).unwrap();
+        child.set_scalar("a", Value::from("child")).unwrap();
+        child.add_parent_alias("get", vec![Value::from("set"), Value::from("a")]);
+        assert_eq!(child.alias_target("get"), molt_ok!("set a"));
 
+        // The parent is only available while it's evaluating a script in the child.
         assert_eq!(
-            interp.expr_float(&Value::from("a")),
-            Err(Exception::molt_err(Value::from(
-                "unknown math function \"a\""
-            )))
+            child.eval("get").unwrap_err().value().as_str(),
+            "can't call alias \"get\": no parent interpreter"
         );
+
+        assert_eq!(
+            interp.eval_in_child("child", &Value::from("list [get] $a")),
+            molt_ok!("parent child")
+        );
+        assert!(interp.has_child("child"));
     }
 
     #[test]
-    fn test_recursion_limit() {
-        let mut interp = Interp::new();
+    fn test_child_recursion_limit() {
+        let mut interp = Interp::new_safe();
+        interp.set_recursion_limit(20);
+        assert_eq!(
+            interp
+                .create_child("child", false)
+                .unwrap()
+                .recursion_limit(),
+            20
+        );
 
-        assert_eq!(interp.recursion_limit(), 1000);
-        interp.set_recursion_limit(100);
-        assert_eq!(interp.recursion_limit(), 100);
+        // Nesting evaluations in a chain of children counts against the parent's limit.
+        let result = interp.eval(
+            "
+            set script {
+                interp create c
+                interp eval c [list set script $script]
+                interp eval c $script
+            }
+            eval $script
+            ",
+        );
+        assert_eq!(
+            result.unwrap_err().value().as_str(),
+            "too many nested calls to Interp::eval (infinite loop?)"
+        );
+        assert_eq!(interp.child("c").unwrap().recursion_limit(), 20);
+
+        // Each child counts as several levels.
+        let mut depth = 0;
+        let mut child = &mut interp;
+        while let Ok(next) = child.child("c") {
+            depth += 1;
+            child = next;
+        }
+        assert_eq!(depth, 20 / (CHILD_EVAL_LEVELS + 1));
+    }
 
-        assert!(dbg!(interp.eval("proc myproc {} { myproc }")).is_ok());
-        assert!(ex_match(
-            &interp.eval("myproc"),
-            Exception::molt_err(Value::from(
-                "too many nested calls to Interp::eval (infinite loop?)"
-            ))
-        ));
+    #[test]
+    fn test_child_delete_drops_context() {
+        struct Tracker(Rc<Cell<bool>>);
+
+        impl Drop for Tracker {
+            fn drop(&mut self) {
+                self.0.set(true);
+            }
+        }
+
+        let dropped = Rc::new(Cell::new(false));
+        let mut interp = Interp::new();
+        let child = interp.create_child("child", false).unwrap();
+        let id = child.save_context(Tracker(dropped.clone()));
+        child.add_context_command("tracked", dummy_cmd, id);
+
+        assert!(!dropped.get());
+        interp.delete_child("child").unwrap();
+        assert!(dropped.get());
     }
 
     //-----------------------------------------------------------------------
@@ -2555,6 +6776,287 @@ mod tests {
         let _ctx = interp.context::<String>(id);
     }
 
+    #[test]
+    fn context_forgotten_replaced_command() {
+        let mut interp = Interp::new();
+        let id1 = interp.save_context(String::from("ABC"));
+        let id2 = interp.save_context(String::from("DEF"));
+
+        // Replacing a command releases its context.
+        interp.add_context_command("dummy", dummy_cmd, id1);
+        interp.add_context_command("dummy", dummy_cmd, id2);
+        assert!(!interp.context_map.contains_key(&id1));
+
+        // So does renaming a command onto it.
+        let id3 = interp.save_context(String::from("GHI"));
+        interp.add_context_command("dummy2", dummy_cmd, id3);
+        interp.rename_command("dummy2", "dummy");
+        assert!(!interp.context_map.contains_key(&id2));
+        assert_eq!(interp.context::<String>(id3), "GHI");
+    }
+
+    #[test]
+    fn remove_command_twice() {
+        let mut interp = Interp::new();
+        let id = interp.save_context(String::from("ABC"));
+        interp.add_context_command("dummy", dummy_cmd, id);
+
+        assert!(interp.remove_command("dummy"));
+        assert!(!interp.remove_command("dummy"));
+        assert!(!interp.remove_command("nonesuch"));
+        assert!(!interp.context_map.contains_key(&id));
+    }
+
+    //-----------------------------------------------------------------------
+    // Snapshot tests
+
+    #[test]
+    fn snapshot_procs() {
+        let mut interp = Interp::new();
+        interp.eval("proc kept {} { return kept }").unwrap();
+        interp.eval("proc changed {} { return old }").unwrap();
+        interp.eval("proc removed {} { return removed }").unwrap();
+        interp
+            .eval("namespace eval ns { proc p {} { return ns } }")
+            .unwrap();
+
+        let snapshot = interp.snapshot();
+
+        interp.eval("proc changed {} { return new }").unwrap();
+        interp.eval("rename removed {}").unwrap();
+        interp.eval("proc added {} {}").unwrap();
+        interp.eval("namespace eval ns2 { proc q {} {} }").unwrap();
+
+        interp.restore(snapshot);
+
+        assert_eq!(interp.eval("kept"), Ok(Value::from("kept")));
+        assert_eq!(interp.eval("changed"), Ok(Value::from("old")));
+        assert_eq!(interp.eval("removed"), Ok(Value::from("removed")));
+        assert_eq!(interp.eval("ns::p"), Ok(Value::from("ns")));
+        assert!(!interp.has_command("added"));
+        assert!(!interp.has_command("ns2::q"));
+        assert_eq!(interp.eval("namespace exists ns2"), Ok(Value::from(false)));
+    }
+
+    #[test]
+    fn snapshot_native_commands() {
+        let mut interp = Interp::new();
+        let snapshot = interp.snapshot();
+
+        // Native commands added since the snapshot are preserved.
+        interp.add_command("dummy", dummy_cmd);
+        interp.restore(snapshot);
+        assert!(interp.has_command("dummy"));
+        assert!(interp.has_command("set"));
+
+        // A restored proc replaces a native command with the same name.
+        interp.eval("proc dummy2 {} { return proc }").unwrap();
+        let snapshot = interp.snapshot();
+        let id = interp.save_context(String::from("ABC"));
+        interp.remove_command("dummy2");
+        interp.add_context_command("dummy2", dummy_cmd, id);
+
+        interp.restore(snapshot);
+        assert_eq!(interp.eval("dummy2"), Ok(Value::from("proc")));
+        assert!(!interp.context_map.contains_key(&id));
+    }
+
+    #[test]
+    fn snapshot_globals() {
+        let mut interp = Interp::new();
+        interp
+            .eval("set a 1; set b(1) x; namespace eval ns { variable v 1 }")
+            .unwrap();
+
+        let snapshot = interp.snapshot();
+
+        interp
+            .eval("set a 2; set b(2) y; unset ns::v; set c 3; array set d {}")
+            .unwrap();
+        interp.restore(snapshot);
+
+        assert_eq!(interp.eval("set a"), Ok(Value::from("1")));
+        assert_eq!(interp.eval("array names b"), Ok(Value::from("1")));
+        assert_eq!(interp.eval("set ns::v"), Ok(Value::from("1")));
+        assert!(!interp.var_exists(&Value::from("c")));
+        assert!(!interp.var_exists(&Value::from("d")));
+    }
+
+    #[test]
+    fn snapshot_contexts() {
+        let mut interp = Interp::new();
+        let cloneable = interp.save_cloneable_context(String::from("ABC"));
+        let plain = interp.save_context(String::from("ABC"));
+
+        let snapshot = interp.snapshot();
+
+        interp.context::<String>(cloneable).push_str("DEF");
+        interp.context::<String>(plain).push_str("DEF");
+
+        // Cloneable contexts are restored; other contexts are preserved.  The snapshot
+        // can be restored repeatedly by cloning it.
+        interp.restore(snapshot.clone());
+        assert_eq!(interp.context::<String>(cloneable), "ABC");
+        assert_eq!(interp.context::<String>(plain), "ABCDEF");
+
+        interp.context::<String>(cloneable).push_str("GHI");
+        interp.restore(snapshot);
+        assert_eq!(interp.context::<String>(cloneable), "ABC");
+    }
+
+    //-----------------------------------------------------------------------
+    // Reset tests
+
+    #[test]
+    fn reset() {
+        let mut interp = Interp::new();
+        let natives = interp.command_names();
+        let globals = interp.vars_in_global_scope();
+
+        let id = interp.save_context(String::from("ABC"));
+        interp.add_context_command("native", context_cmd, id);
+        interp.eval("proc p {} { return p }").unwrap();
+        interp
+            .eval("namespace eval ns { proc q {} {}; variable v 1 }")
+            .unwrap();
+        interp.eval("set a 1; set b(1) x").unwrap();
+        interp.eval("trace add execution p enter list").unwrap();
+        interp.profile_save("dummy", Duration::from_nanos(0));
+
+        interp.reset();
+
+        // Only the native commands remain, and their contexts are intact.
+        let mut expected = natives;
+        expected.push(Value::from("native"));
+        expected.sort_by(|a, b| a.as_str().cmp(b.as_str()));
+        assert_eq!(interp.command_names(), expected);
+        assert_eq!(interp.eval("native"), Ok(Value::from("ABC")));
+        assert!(interp.exec_traces.is_empty());
+        assert!(interp.profile_map.is_empty());
+
+        // Only the standard globals remain.
+        let mut names = interp.vars_in_global_scope();
+        names.sort_by(|a, b| a.as_str().cmp(b.as_str()));
+        let mut expected = globals;
+        expected.sort_by(|a, b| a.as_str().cmp(b.as_str()));
+        assert_eq!(names, expected);
+        assert_eq!(interp.scalar("errorInfo"), Ok(Value::empty()));
+
+        // The interpreter can be used as before.
+        interp.eval("proc p {} { return again }").unwrap();
+        assert_eq!(interp.eval("p"), Ok(Value::from("again")));
+    }
+
+    // A step of test_env.
+    #[cfg(feature = "os")]
+    fn env_reset_vars() {
+        let mut interp = Interp::new();
+        interp.link_env();
+        std::env::set_var("MOLT_RESET_VARS_ENV", "1");
+        interp.eval("set a 1").unwrap();
+
+        interp.reset_vars();
+
+        assert!(!interp.var_exists(&Value::from("a")));
+        assert_eq!(
+            interp.eval("set env(MOLT_RESET_VARS_ENV)"),
+            Ok(Value::from("1"))
+        );
+        interp.eval("set env(MOLT_RESET_VARS_ENV) 2").unwrap();
+        assert_eq!(std::env::var("MOLT_RESET_VARS_ENV").unwrap(), "2");
+        std::env::remove_var("MOLT_RESET_VARS_ENV");
+
+        // An unlinked env array is repopulated, and stays unlinked.
+        let mut interp = Interp::new();
+        std::env::set_var("MOLT_RESET_VARS_ENV_COPY", "1");
+        interp.reset_vars();
+        assert_eq!(
+            interp.eval("set env(MOLT_RESET_VARS_ENV_COPY)"),
+            Ok(Value::from("1"))
+        );
+        interp.eval("set env(MOLT_RESET_VARS_ENV_COPY) 2").unwrap();
+        assert_eq!(std::env::var("MOLT_RESET_VARS_ENV_COPY").unwrap(), "1");
+        std::env::remove_var("MOLT_RESET_VARS_ENV_COPY");
+
+        // A safe interpreter has no env array to restore.
+        let mut interp = Interp::new_safe();
+        interp.reset_vars();
+        assert!(!interp.var_exists(&Value::from("env")));
+    }
+
+    #[test]
+    fn remove_proc_commands() {
+        let mut interp = Interp::new();
+        let id = interp.save_context(String::from("ABC"));
+        interp.add_context_command("native1", context_cmd, id);
+        interp.add_context_command("native2", context_cmd, id);
+        interp.eval("proc p {} {}; alias al set").unwrap();
+
+        interp.remove_proc_commands();
+
+        assert!(!interp.has_command("p"));
+        assert!(interp.has_command("al"));
+        assert_eq!(interp.eval("native1"), Ok(Value::from("ABC")));
+
+        // The context's reference count is unchanged, so it's released with its last
+        // command.
+        interp.remove_command("native1");
+        assert!(interp.context_map.contains_key(&id));
+        interp.remove_command("native2");
+        assert!(!interp.context_map.contains_key(&id));
+    }
+
+    #[cfg(feature = "coroutine")]
+    #[test]
+    fn coroutine_yield_at_top_level() {
+        let mut interp = Interp::new();
+        interp
+            .eval("proc gen {} { yield 1; set x [yield 2] }")
+            .unwrap();
+        assert_eq!(interp.eval("coroutine g gen"), Ok(Value::from("1")));
+
+        // A yield within a top-level command is an error, which ends the coroutine.  Nested
+        // commands first fill the pool of spare word lists, which the error leaves as it was.
+        interp.eval("list [list [list [list]]]").unwrap();
+        let spares = interp.spare_words.len();
+        assert_eq!(
+            interp.eval("g").unwrap_err().value().as_str(),
+            "yield can only be called at the top level of a coroutine's body"
+        );
+        assert!(!interp.has_command("g"));
+        assert_eq!(interp.spare_words.len(), spares);
+
+        // The interpreter's state is restored.
+        assert_eq!(interp.scopes.current(), 0);
+        assert!(interp.yield_at.is_none());
+        assert!(interp.yielded.is_none());
+        assert_eq!(
+            interp.eval("yield").unwrap_err().value().as_str(),
+            "yield can only be called in a coroutine"
+        );
+    }
+
+    #[cfg(feature = "coroutine")]
+    #[test]
+    fn coroutine_tailcall_loop() {
+        let mut interp = Interp::new();
+        interp
+            .eval("proc count {i} { yield $i; tailcall count [expr {$i + 1}] }")
+            .unwrap();
+        assert_eq!(interp.eval("coroutine c count 1"), Ok(Value::from("1")));
+
+        // The tail call replaces the coroutine's body, without growing the stack.
+        for i in 2..1000 {
+            assert_eq!(interp.eval("c"), Ok(Value::from(i)));
+        }
+        assert_eq!(interp.scopes.current(), 0);
+        assert!(interp.tailcalls.is_empty());
+    }
+
+    fn context_cmd(interp: &mut Interp, context_id: ContextID, _: &[Value]) -> MoltResult {
+        molt_ok!(interp.context::<String>(context_id).clone())
+    }
+
     fn dummy_cmd(_: &mut Interp, _: ContextID, _: &[Value]) -> MoltResult {
         molt_err!("Not really meant to be called")
     }
@@ -6,6 +6,15 @@
 //! relevant data types, including [`MoltResult`] and [`ResultCode`], are defined in
 //! the [`types`] module.
 //!
+//! The [`list`] module parses and formats Molt lists, and provides helpers for working with
+//! them.
+//!
+//! The [`parser`] module parses Molt scripts into an abstract syntax tree without
+//! evaluating them, for use by tools such as linters and editor integrations.
+//!
+//! With the `serde` feature, the [`value_serde`] module implements `Serialize` and
+//! `Deserialize` for [`Value`], by its string rep or, optionally, by its structure.
+//!
 //! The [`test_harness`] module defines the test runner for Molt's TCL-level testing.  It
 //! can be used directly in Cargo integration tests or via a Molt shell, whether standard or
 //! custom.
@@ -17,31 +26,46 @@
 //! [`ResultCode`]: types/enum.ResultCode.html
 //! [`Value`]: value/index.html
 //! [`interp`]: interp/index.html
+//! [`list`]: list/index.html
+//! [`parser`]: parser/index.html
 //! [`types`]: types/index.html
 //! [`test_harness`]: test_harness/index.html
+//! [`value_serde`]: value_serde/index.html
 
 #![doc(html_root_url = "https://docs.rs/molt/0.3.0")]
 #![doc(html_logo_url = "https://github.com/wduquette/molt/raw/master/MoltLogo.png")]
 
 pub use crate::interp::Interp;
+pub use crate::list::get_list;
+pub use crate::list::list_to_string;
+pub use crate::parser::parse;
 pub use crate::test_harness::test_harness;
 pub use crate::types::*;
 
+mod binary;
+#[cfg(feature = "os")]
+mod channel;
 mod commands;
+#[cfg(feature = "coroutine")]
+mod coroutine;
+pub mod coverage;
 pub mod dict;
 mod eval_ptr;
 mod expr;
+mod hash;
 pub mod interp;
-mod list;
+pub mod list;
 mod tokenizer;
 #[macro_use]
 mod macros;
-mod parser;
+pub mod parser;
 mod scope;
 pub mod test_harness;
 pub mod types;
 mod util;
 pub mod value;
+#[cfg(feature = "serde")]
+pub mod value_serde;
 
 /// This function is used in command functions to check whether the command's argument
 /// list is of a proper size for the given command.  If it is, `check_args` returns
@@ -59,7 +83,7 @@ pub mod value;
 /// _max_ is zero, the command takes an arbitrary number of arguments (but at least _min_).
 ///
 /// _argsig_ is the argument signature, to be appended to the command name for inclusion
-/// in the error message.
+/// in the error message.  It may be empty, for a command that takes no arguments.
 ///
 /// ## Example
 ///
@@ -97,17 +121,36 @@ pub fn check_args(
     assert!(!argv.is_empty());
 
     if argv.len() < min || (max > 0 && argv.len() > max) {
-        let cmd_tokens = Value::from(&argv[0..namec]);
-        molt_err!(
-            "wrong # args: should be \"{} {}\"",
-            cmd_tokens.to_string(),
-            argsig
-        )
+        wrong_num_args(namec, argv, argsig)
     } else {
         molt_ok!()
     }
 }
 
+/// Returns the Molt error message `wrong # args: should be "syntax..."`, just as
+/// [`check_args`](fn.check_args.html) does, for use by command functions that must check
+/// their arguments in some other way, e.g., after parsing their options.
+///
+/// As for `check_args`, the _namec_ is the number of tokens in _argv_ that constitute the
+/// command name, e.g., 2 for a subcommand, and _argsig_ is the argument signature.  Taking
+/// the command name from _argv_ means that the message names the command as it was
+/// called, even if it has been renamed.
+///
+/// ## Example
+///
+/// ```ignore
+/// return wrong_num_args(2, argv, "?-nocase? ?-length length? string1 string2");
+/// ```
+pub fn wrong_num_args(namec: usize, argv: &[Value], argsig: &str) -> MoltResult {
+    let cmd_tokens = Value::from(&argv[0..namec]);
+
+    if argsig.is_empty() {
+        molt_err!("wrong # args: should be \"{}\"", cmd_tokens)
+    } else {
+        molt_err!("wrong # args: should be \"{} {}\"", cmd_tokens, argsig)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -151,6 +194,32 @@ mod tests {
             ),
             "wrong # args: should be \"mycmd arg1\"",
         );
+        assert_err(
+            &check_args(1, &mklist(vec!["mycmd", "val1"].as_slice()), 1, 1, ""),
+            "wrong # args: should be \"mycmd\"",
+        );
+        assert_err(
+            &check_args(2, &mklist(vec!["mycmd", "sub"].as_slice()), 3, 3, "arg1"),
+            "wrong # args: should be \"mycmd sub arg1\"",
+        );
+    }
+
+    #[test]
+    fn test_wrong_num_args() {
+        let argv = mklist(vec!["mycmd", "sub", "val1"].as_slice());
+
+        assert_err(
+            &wrong_num_args(1, &argv, "sub arg1"),
+            "wrong # args: should be \"mycmd sub arg1\"",
+        );
+        assert_err(
+            &wrong_num_args(2, &argv, "arg1"),
+            "wrong # args: should be \"mycmd sub arg1\"",
+        );
+        assert_err(
+            &wrong_num_args(2, &argv, ""),
+            "wrong # args: should be \"mycmd sub\"",
+        );
     }
 
     // TODO: stopgap until we have finalized the MoltList API.
@@ -1,16 +1,49 @@
 //! TCL List Parsing and Formatting
+//!
+//! A Molt list is represented in Rust as a [`MoltList`], a vector of [`Value`]s, and
+//! as a string in TCL list syntax.  A `Value` converts between the two on demand, via
+//! [`Value::as_list`] and its string rep, using the parser and formatter defined here.
+//! This module also provides helpers for the common operations on lists that Molt's
+//! own commands use, and that client commands will often need as well.
+//!
+//! # Example
+//!
+//! ```
+//! use molt::list::*;
+//! use molt::types::*;
+//! # fn dummy() -> Result<(), Exception> {
+//!
+//! let list = get_list("a {b c} d")?;
+//! assert_eq!(list.len(), 3);
+//! assert!(list_contains(&list, &Value::from("b c")));
+//! assert_eq!(list_join(&list, "+"), "a+b c+d");
+//! assert_eq!(list_to_string(&list_range(&list, 1, 2)), "{b c} d");
+//! assert_eq!(parse_index(&Value::from("end-1"), list.len())?, 1);
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! [`MoltList`]: ../types/type.MoltList.html
+//! [`Value`]: ../value/index.html
+//! [`Value::as_list`]: ../value/struct.Value.html#method.as_list
 
 use crate::molt_err;
 use crate::tokenizer::Tokenizer;
 use crate::types::*;
 use crate::value::Value;
+use std::borrow::Cow;
 
 //--------------------------------------------------------------------------
 // List Parsing
 
 /// Parses a list-formatted string into a vector, throwing
 /// a Molt error if the list cannot be parsed as a list.
-pub(crate) fn get_list(str: &str) -> Result<MoltList, Exception> {
+///
+/// Client code will usually call [`Value::as_list`] instead, which caches the parsed
+/// list in the `Value`.
+///
+/// [`Value::as_list`]: ../value/struct.Value.html#method.as_list
+pub fn get_list(str: &str) -> Result<MoltList, Exception> {
     let mut ctx = Tokenizer::new(str);
 
     parse_list(&mut ctx)
@@ -166,70 +199,97 @@ fn parse_bare_item(ctx: &mut Tokenizer) -> MoltResult {
 // List Formatting
 
 /// Converts a list, represented as a vector of `Value`s, into a string, doing
-/// all necessary quoting and escaping.
+/// all necessary quoting and escaping.  The result can be parsed by [`get_list`]
+/// to yield the same elements.
+///
+/// As in TCL, each element is output as is if that's unambiguous, in braces if braces
+/// are needed and will work, and with backslash escapes otherwise.  In addition, a first
+/// element beginning with `#` is always quoted, so that the list can be evaluated as a
+/// command without being mistaken for a comment.
+///
+/// [`get_list`]: fn.get_list.html
 pub fn list_to_string(list: &[Value]) -> String {
-    let mut vec: Vec<String> = Vec::new();
+    let mut result = String::new();
 
-    let mut hash = !list.is_empty() && list[0].as_str().starts_with('#');
+    for (i, item) in list.iter().enumerate() {
+        let item = item.as_str();
+        let hash = i == 0 && item.starts_with('#');
 
-    for item in list {
-        let item = item.to_string();
-        match get_mode(&item) {
-            Mode::AsIs => {
-                if hash {
-                    vec.push(brace_item(&item));
-                    hash = false;
-                } else {
-                    vec.push(item)
-                }
-            }
-            Mode::Brace => {
-                vec.push(brace_item(&item));
-            }
-            Mode::Escape => {
-                vec.push(escape_item(hash, &item));
-                hash = false;
-            }
+        if i > 0 {
+            result.push(' ');
+        }
+
+        match get_mode(item) {
+            Mode::AsIs if !hash => result.push_str(item),
+            Mode::AsIs | Mode::Brace => brace_item(&mut result, item),
+            Mode::Escape => escape_item(&mut result, item),
         }
     }
 
-    vec.join(" ")
+    result
 }
 
-fn brace_item(item: &str) -> String {
-    let mut word = String::new();
+/// Converts a list into a string, as for [`list_to_string`], but without allocating when
+/// the result is the empty string, for the empty list, or is the string rep of the list's
+/// only element, when that element needs no quoting.
+///
+/// [`list_to_string`]: fn.list_to_string.html
+///
+/// # Example
+///
+/// ```
+/// use molt::list::list_to_string_cow;
+/// use molt::types::Value;
+/// use std::borrow::Cow;
+///
+/// let list = vec![Value::from("abc")];
+/// assert!(matches!(list_to_string_cow(&list), Cow::Borrowed("abc")));
+///
+/// let list = vec![Value::from("a b")];
+/// assert_eq!(list_to_string_cow(&list), "{a b}");
+/// ```
+pub fn list_to_string_cow(list: &[Value]) -> Cow<'_, str> {
+    match list {
+        [] => Cow::Borrowed(""),
+        [item] => {
+            let item = item.as_str();
+            if get_mode(item) == Mode::AsIs && !item.starts_with('#') {
+                Cow::Borrowed(item)
+            } else {
+                Cow::Owned(list_to_string(list))
+            }
+        }
+        _ => Cow::Owned(list_to_string(list)),
+    }
+}
+
+fn brace_item(word: &mut String, item: &str) {
     word.push('{');
     word.push_str(item);
     word.push('}');
-    word
 }
 
-fn escape_item(hash: bool, item: &str) -> String {
-    let mut word = String::new();
-
-    // If hash, the first character is a "#" that must be escaped.
-    // Just push the backslash on the front.
-    if hash {
-        word.push('\\');
-    }
-
+fn escape_item(word: &mut String, item: &str) {
     for ch in item.chars() {
-        if ch.is_whitespace() {
-            word.push('\\');
-            word.push(ch);
-            continue;
-        }
-
         match ch {
-            '{' | ';' | '$' | '[' | ']' | '\\' => {
+            // List whitespace is escaped by name, so that it can't be mistaken for a
+            // line continuation.
+            '\n' => word.push_str("\\n"),
+            '\r' => word.push_str("\\r"),
+            '\t' => word.push_str("\\t"),
+            '\x0B' => word.push_str("\\v"),
+            '\x0C' => word.push_str("\\f"),
+            '{' | '}' | '"' | '#' | ';' | '$' | '[' | ']' | '\\' => {
+                word.push('\\');
+                word.push(ch);
+            }
+            _ if ch.is_whitespace() => {
                 word.push('\\');
                 word.push(ch);
             }
             _ => word.push(ch),
         }
     }
-
-    word
 }
 
 #[derive(Eq, PartialEq, Debug)]
@@ -245,11 +305,18 @@ fn get_mode(word: &str) -> Mode {
         return Mode::Brace;
     }
 
-    // NEXT, inspect the content.
-    let mut mode = Mode::AsIs;
-    let mut brace_count = 0;
+    // NEXT, a leading brace or quote would be mistaken for list quoting.
+    let mut mode = if word.starts_with(['{', '"']) {
+        Mode::Brace
+    } else {
+        Mode::AsIs
+    };
 
-    let mut iter = word.chars().peekable();
+    // NEXT, inspect the content.  Braces are counted as `parse_braced_item` counts them,
+    // skipping the character following each backslash; if they don't balance, or the
+    // word ends with a backslash that would escape the close-brace, braces won't work.
+    let mut brace_count = 0;
+    let mut iter = word.chars();
 
     while let Some(ch) = iter.next() {
         if ch.is_whitespace() {
@@ -261,14 +328,16 @@ fn get_mode(word: &str) -> Mode {
                 mode = Mode::Brace;
             }
             '{' => brace_count += 1,
-            '}' => brace_count -= 1,
-            '\\' => {
-                if iter.peek() == Some(&'\n') {
+            '}' => {
+                brace_count -= 1;
+                if brace_count < 0 {
                     return Mode::Escape;
-                } else {
-                    mode = Mode::Brace;
                 }
             }
+            '\\' => match iter.next() {
+                Some('\n') | None => return Mode::Escape,
+                Some(_) => mode = Mode::Brace,
+            },
             _ => (),
         }
     }
@@ -280,6 +349,115 @@ fn get_mode(word: &str) -> Mode {
     }
 }
 
+//--------------------------------------------------------------------------
+// List Utilities
+
+/// Returns true if the list contains an element whose string rep is the same as
+/// the value's, and false otherwise.  This is the test used by the `in` and `ni`
+/// operators in `expr`.
+pub fn list_contains(list: &[Value], value: &Value) -> bool {
+    let value = value.as_str();
+    list.iter().any(|item| item.as_str() == value)
+}
+
+/// Joins the string reps of the list's elements, separated by the separator, as
+/// for the `join` command.  Note that the elements aren't quoted; use
+/// [`list_to_string`] to produce a string that can be parsed as a list.
+///
+/// [`list_to_string`]: fn.list_to_string.html
+pub fn list_join(list: &[Value], sep: &str) -> String {
+    let mut result = String::new();
+
+    for (i, item) in list.iter().enumerate() {
+        if i > 0 {
+            result.push_str(sep);
+        }
+        result.push_str(item.as_str());
+    }
+
+    result
+}
+
+/// Returns the elements of the list from index `first` through `last`, inclusive, as
+/// for the TCL `lrange` command.  Indices less than zero are treated as zero, and
+/// indices past the end of the list as the index of the last element; if `first` is
+/// greater than `last`, the result is the empty list.
+pub fn list_range(list: &[Value], first: MoltInt, last: MoltInt) -> MoltList {
+    if list.is_empty() || last < 0 {
+        return Vec::new();
+    }
+
+    let first = first.max(0) as usize;
+    let last = (last as usize).min(list.len() - 1);
+
+    if first > last {
+        return Vec::new();
+    }
+
+    list[first..=last].to_vec()
+}
+
+/// Parses a list index, given the length of the list.  The index may be an integer,
+/// `end` (the index of the last element), or either of these plus or minus an integer
+/// offset, e.g., `end-1` or `3+2`.  The result may be outside the bounds of the list; it's
+/// up to the caller to decide whether that's an error.
+///
+/// Returns an error if the index can't be parsed.
+pub fn parse_index(index: &Value, len: usize) -> Result<MoltInt, Exception> {
+    // FIRST, the common case: an integer.
+    if let Ok(index) = index.as_int() {
+        return Ok(index);
+    }
+
+    let string = index.as_str().trim();
+    let (base, rest) = if let Some(rest) = string.strip_prefix("end") {
+        (len as MoltInt - 1, rest)
+    } else {
+        // NEXT, an integer plus or minus an offset.  Skip a leading sign when looking
+        // for the operator.
+        let start = if string.starts_with(['-', '+']) { 1 } else { 0 };
+
+        match string[start..].find(['+', '-']) {
+            Some(pos) => match Value::from(&string[..start + pos]).as_int() {
+                Ok(base) => (base, &string[start + pos..]),
+                Err(_) => return bad_index(string),
+            },
+            None => return bad_index(string),
+        }
+    };
+
+    if rest.is_empty() {
+        return Ok(base);
+    }
+
+    // NEXT, the offset must be a signed integer.
+    let offset = match rest.chars().next() {
+        Some('+') | Some('-') => match Value::from(&rest[1..]).as_int() {
+            Ok(offset) => offset,
+            Err(_) => return bad_index(string),
+        },
+        _ => return bad_index(string),
+    };
+
+    let result = if rest.starts_with('+') {
+        base.checked_add(offset)
+    } else {
+        base.checked_sub(offset)
+    };
+
+    match result {
+        Some(index) => Ok(index),
+        None => bad_index(string),
+    }
+}
+
+fn bad_index(index: &str) -> Result<MoltInt, Exception> {
+    molt_err!(
+        "bad index \"{}\": must be integer?[+-]integer? or end?[+-]integer?",
+        index
+    )
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -373,6 +551,171 @@ mod tests {
         }
     }
 
+    // A list whose elements need quoting of various kinds.
+    fn quoted_list() -> MoltList {
+        let list: MoltList = vec![
+            Value::from("a"),
+            Value::from("b c"),
+            Value::from("d{}"),
+            Value::from("e\\f"),
+            Value::from("{"),
+        ];
+
+        // The quoting round-trips.
+        assert_eq!(get_list(&list_to_string(&list)).unwrap(), list);
+        list
+    }
+
+    #[test]
+    fn test_list_contains() {
+        let list = quoted_list();
+
+        assert!(list_contains(&list, &Value::from("a")));
+        assert!(list_contains(&list, &Value::from("b c")));
+        assert!(list_contains(&list, &Value::from("d{}")));
+        assert!(list_contains(&list, &Value::from("e\\f")));
+        assert!(list_contains(&list, &Value::from("{")));
+        assert!(!list_contains(&list, &Value::from("b")));
+        assert!(!list_contains(&list, &Value::from("{b c}")));
+        assert!(!list_contains(&[], &Value::from("a")));
+
+        // Comparison is by string rep.
+        let list = vec![Value::from(1), Value::from(2.5)];
+        assert!(list_contains(&list, &Value::from("1")));
+        assert!(list_contains(&list, &Value::from("2.5")));
+        assert!(!list_contains(&list, &Value::from("01")));
+    }
+
+    #[test]
+    fn test_list_join() {
+        let list = quoted_list();
+
+        // Elements are joined as is, without quoting.
+        assert_eq!(list_join(&list, " "), "a b c d{} e\\f {");
+        assert_eq!(list_join(&list, ", "), "a, b c, d{}, e\\f, {");
+        assert_eq!(list_join(&list, ""), "ab cd{}e\\f{");
+        assert_eq!(list_join(&[Value::from("a")], "+"), "a");
+        assert_eq!(list_join(&[], "+"), "");
+    }
+
+    #[test]
+    fn test_list_range() {
+        let list = quoted_list();
+
+        assert_eq!(list_range(&list, 0, 4), list);
+        assert_eq!(list_to_string(&list_range(&list, 1, 2)), "{b c} d{}");
+        assert_eq!(list_to_string(&list_range(&list, 3, 4)), "{e\\f} \\{");
+        assert_eq!(list_to_string(&list_range(&list, -5, 0)), "a");
+        assert_eq!(list_range(&list, 2, 10), list[2..].to_vec());
+        assert!(list_range(&list, 2, 1).is_empty());
+        assert!(list_range(&list, 5, 10).is_empty());
+        assert!(list_range(&list, -2, -1).is_empty());
+        assert!(list_range(&[], 0, 1).is_empty());
+    }
+
+    #[test]
+    fn test_parse_index() {
+        assert_eq!(parse_index(&Value::from(2), 5), Ok(2));
+        assert_eq!(parse_index(&Value::from("-1"), 5), Ok(-1));
+        assert_eq!(parse_index(&Value::from("end"), 5), Ok(4));
+        assert_eq!(parse_index(&Value::from("end-1"), 5), Ok(3));
+        assert_eq!(parse_index(&Value::from("end+1"), 5), Ok(5));
+        assert_eq!(parse_index(&Value::from("end--1"), 5), Ok(5));
+        assert_eq!(parse_index(&Value::from("end+-1"), 5), Ok(3));
+        assert_eq!(parse_index(&Value::from("end"), 0), Ok(-1));
+        assert_eq!(parse_index(&Value::from("1+2"), 5), Ok(3));
+        assert_eq!(parse_index(&Value::from("1-2"), 5), Ok(-1));
+        assert_eq!(parse_index(&Value::from("-1+2"), 5), Ok(1));
+        assert_eq!(parse_index(&Value::from(" end-1 "), 5), Ok(3));
+
+        for bad in &[
+            "", "x", "en", "end-", "end-x", "end-1-1", "1+", "1+x", "1.5",
+        ] {
+            assert_eq!(
+                parse_index(&Value::from(*bad), 5),
+                molt_err!(
+                    "bad index \"{}\": must be integer?[+-]integer? or end?[+-]integer?",
+                    bad.trim()
+                ),
+                "index: {:?}",
+                bad
+            );
+        }
+    }
+
+    #[test]
+    fn test_list_round_trip() {
+        // Strings that are hard to quote, alone and in combination.
+        let nasty = [
+            "", "{", "}", "\\", "\"", " ", "\n", "\t", "#", "$", "[", "]", ";", "a", "{}", "}{",
+            "\\{", "\\}", "{\\", "a\\", "\\\n", "\\ ", "\"a b\"", "x\\}{",
+        ];
+
+        let mut corpus: Vec<String> = Vec::new();
+        for a in &nasty {
+            for b in &nasty {
+                corpus.push(format!("{}{}", a, b));
+            }
+        }
+
+        for a in &corpus {
+            // Each string alone, first in the list, and following another element.
+            for elements in &[vec![a.as_str()], vec![a, "z"], vec!["z", a], vec![a, a]] {
+                let list: MoltList = elements.iter().map(|e| Value::from(*e)).collect();
+                let string = Value::from(list.clone()).as_str().to_string();
+
+                assert_eq!(
+                    Value::from(string.as_str()).as_list().unwrap().to_vec(),
+                    list,
+                    "list: {:?}",
+                    string
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_list_to_string_cow() {
+        assert!(matches!(list_to_string_cow(&[]), Cow::Borrowed("")));
+        assert!(matches!(
+            list_to_string_cow(&[Value::from("abc")]),
+            Cow::Borrowed("abc")
+        ));
+
+        // Elements that need quoting, and longer lists, are formatted as usual.
+        for list in &[
+            vec![Value::from("")],
+            vec![Value::from("a b")],
+            vec![Value::from("#a")],
+            vec![Value::from("{a")],
+            vec![Value::from("a"), Value::from("b")],
+        ] {
+            let cow = list_to_string_cow(list);
+            assert!(matches!(cow, Cow::Owned(_)));
+            assert_eq!(cow, list_to_string(list));
+        }
+    }
+
+    #[test]
+    fn test_list_to_string_quoting() {
+        let quote = |item: &str| list_to_string(&[Value::from(item)]);
+
+        assert_eq!(quote("{a}"), "{{a}}");
+        assert_eq!(quote("\"a"), "{\"a}");
+        assert_eq!(quote("a\"b"), "a\"b");
+        assert_eq!(quote("a\\"), "a\\\\");
+        assert_eq!(quote("}{"), "\\}\\{");
+        assert_eq!(quote("a\\}{"), "a\\\\\\}\\{");
+        assert_eq!(quote("{\n"), "\\{\\n");
+        assert_eq!(quote("#"), "{#}");
+        assert_eq!(quote("#{"), "\\#\\{");
+        assert_eq!(list_to_string(&[Value::from("a"), Value::from("#")]), "a #");
+        assert_eq!(
+            list_to_string(&[Value::from("# a"), Value::from("#")]),
+            "{# a} #"
+        );
+    }
+
     // Most list parsing is tested in the Molt test suite.
 
     #[test]
@@ -1,6 +1,9 @@
 //! Convenience Macros
 //!
 //! This module contains macros for use by command authors.
+//!
+//! The macros refer to Molt's types by their absolute paths, so they can be used without
+//! importing anything but the macros themselves, e.g., `use molt::molt_ok;`.
 
 /// Returns an `Ok` `MoltResult`.
 ///
@@ -13,7 +16,9 @@
 /// # Examples
 ///
 /// ```
-/// use molt::*;
+/// use molt::molt_ok;
+/// use molt::MoltResult;
+/// use molt::Value;
 ///
 /// // Return the empty result
 /// fn func1() -> MoltResult {
@@ -42,13 +47,13 @@
 #[macro_export]
 macro_rules! molt_ok {
     () => (
-        Ok(Value::empty())
+        Ok($crate::Value::empty())
     );
     ($arg:expr) => (
-        Ok(Value::from($arg))
+        Ok($crate::Value::from($arg))
     );
     ($($arg:tt)*) => (
-        Ok(Value::from(format!($($arg)*)))
+        Ok($crate::Value::from(format!($($arg)*)))
     )
 }
 
@@ -65,7 +70,8 @@ macro_rules! molt_ok {
 /// # Examples
 ///
 /// ```
-/// use molt::*;
+/// use molt::molt_err;
+/// use molt::MoltResult;
 ///
 /// // Return a simple error message
 /// fn err1() -> MoltResult {
@@ -96,10 +102,10 @@ macro_rules! molt_ok {
 #[macro_export]
 macro_rules! molt_err {
     ($arg:expr) => (
-        Err(Exception::molt_err(Value::from($arg)))
+        Err($crate::Exception::molt_err($crate::Value::from($arg)))
     );
     ($($arg:tt)*) => (
-        Err(Exception::molt_err(Value::from(format!($($arg)*))))
+        Err($crate::Exception::molt_err($crate::Value::from(format!($($arg)*))))
     )
 }
 
@@ -118,7 +124,8 @@ macro_rules! molt_err {
 /// # Examples
 ///
 /// ```
-/// use molt::*;
+/// use molt::molt_throw;
+/// use molt::MoltResult;
 ///
 /// // Throw a simple error
 /// fn throw1() -> MoltResult {
@@ -151,13 +158,138 @@ macro_rules! molt_err {
 #[macro_export]
 macro_rules! molt_throw {
     ($code:expr, $msg:expr) => (
-        Err(Exception::molt_err2(Value::from($code), Value::from($msg)))
+        Err($crate::Exception::molt_err2($crate::Value::from($code), $crate::Value::from($msg)))
     );
     ($code:expr, $($arg:tt)*) => (
-        Err(Exception::molt_err2(Value::from($code), Value::from(format!($($arg)*))))
+        Err($crate::Exception::molt_err2(
+            $crate::Value::from($code),
+            $crate::Value::from(format!($($arg)*)),
+        ))
     )
 }
 
+/// Returns an `Error` `MoltResult` with a specific error code and initial error info, i.e.,
+/// the values of the `-errorcode` and `-errorinfo` return options.  The error message is
+/// formatted as with `format!()`.
+///
+/// The macro requires three or more arguments: the error code, the error info, and the
+/// error message.  If called with more than three arguments, the third is a `format!()`
+/// format string and the remainder are the values to format.  If the error info is empty,
+/// the stack trace begins with the error message, as usual.
+///
+/// This is used to rethrow an error, preserving its stack trace.  This macro wraps
+/// the [`Exception::molt_err3`](types/struct.Exception.html#method.molt_err3)
+/// method.
+///
+/// # Examples
+///
+/// ```
+/// use molt::molt_opt_err;
+/// use molt::MoltResult;
+///
+/// // Rethrow an error with its stack trace
+/// fn rethrow() -> MoltResult {
+///     // ...
+///     molt_opt_err!("MYCODE", "the stack trace", "error message")
+/// }
+///
+/// let exception = rethrow().err().unwrap();
+/// assert!(exception.is_error());
+/// assert_eq!(exception.value(), "error message".into());
+/// assert_eq!(exception.error_code(), "MYCODE".into());
+/// assert_eq!(exception.error_info(), "the stack trace".into());
+///
+/// // Return a formatted error
+/// fn rethrow2() -> MoltResult {
+///    // ...
+///    molt_opt_err!("MYCODE", "", "invalid value: {}", 17)
+/// }
+///
+/// let exception = rethrow2().err().unwrap();
+/// assert_eq!(exception.value(), "invalid value: 17".into());
+/// assert_eq!(exception.error_info(), "invalid value: 17".into());
+/// ```
+#[macro_export]
+macro_rules! molt_opt_err {
+    ($code:expr, $info:expr, $msg:expr) => (
+        Err($crate::Exception::molt_err3(
+            $crate::Value::from($code),
+            $crate::Value::from($info),
+            $crate::Value::from($msg),
+        ))
+    );
+    ($code:expr, $info:expr, $($arg:tt)*) => (
+        Err($crate::Exception::molt_err3(
+            $crate::Value::from($code),
+            $crate::Value::from($info),
+            $crate::Value::from(format!($($arg)*)),
+        ))
+    )
+}
+
+/// Checks a command's argument count, returning early with the standard
+/// "wrong # args" error if it is wrong.  This wraps the
+/// [`check_args`](fn.check_args.html) function, applying the `?` operator.
+///
+/// The arguments are as for `check_args`: the number of words in the command's name,
+/// the command's `argv`, the minimum and maximum number of arguments (where 0 means
+/// there's no maximum), and the argument signature.  The number of words in the command's
+/// name can be omitted; it defaults to 1.
+///
+/// The macro can be used in any function whose result is a `Result<_, Exception>`.
+///
+/// # Examples
+///
+/// ```
+/// use molt::molt_check_args;
+/// use molt::molt_ok;
+/// use molt::ContextID;
+/// use molt::Interp;
+/// use molt::MoltResult;
+/// use molt::Subcommand;
+/// use molt::Value;
+///
+/// // The command "square number"
+/// fn cmd_square(_interp: &mut Interp, _: ContextID, argv: &[Value]) -> MoltResult {
+///     molt_check_args!(argv, 2, 2, "number");
+///     let x = argv[1].as_int()?;
+///     molt_ok!(x * x)
+/// }
+///
+/// // The ensemble subcommand "mycmd double number"
+/// fn cmd_mycmd_double(_interp: &mut Interp, _: ContextID, argv: &[Value]) -> MoltResult {
+///     molt_check_args!(2, argv, 3, 3, "number");
+///     let x = argv[2].as_int()?;
+///     molt_ok!(2 * x)
+/// }
+///
+/// let mut interp = Interp::new();
+/// interp.add_command("square", cmd_square);
+///
+/// assert_eq!(interp.eval("square 3"), Ok(Value::from(9)));
+/// assert_eq!(
+///     interp.eval("square").err().unwrap().value(),
+///     "wrong # args: should be \"square number\"".into()
+/// );
+///
+/// interp.add_ensemble_from_table("mycmd", &[Subcommand("double", cmd_mycmd_double)]);
+///
+/// assert_eq!(interp.eval("mycmd double 4"), Ok(Value::from(8)));
+/// assert_eq!(
+///     interp.eval("mycmd double").err().unwrap().value(),
+///     "wrong # args: should be \"mycmd double number\"".into()
+/// );
+/// ```
+#[macro_export]
+macro_rules! molt_check_args {
+    ($namec:expr, $argv:expr, $min:expr, $max:expr, $argsig:expr) => {
+        $crate::check_args($namec, $argv, $min, $max, $argsig)?
+    };
+    ($argv:expr, $min:expr, $max:expr, $argsig:expr) => {
+        $crate::check_args(1, $argv, $min, $max, $argsig)?
+    };
+}
+
 #[cfg(test)]
 mod tests {
     use crate::*;
@@ -193,6 +325,46 @@ mod tests {
         check_throw(molt_throw!("MYERR", "error {}", 5), "MYERR", "error 5");
     }
 
+    #[test]
+    fn test_molt_opt_err() {
+        let result: MoltResult = molt_opt_err!("MYERR", "info", "error message");
+        let exception = result.err().unwrap();
+        assert!(check_throw(
+            Err(exception.clone()),
+            "MYERR",
+            "error message"
+        ));
+        assert_eq!(exception.error_info(), "info".into());
+
+        let result: MoltResult = molt_opt_err!("MYERR", "", "error {}", 5);
+        assert!(check_throw(result, "MYERR", "error 5"));
+    }
+
+    #[test]
+    fn test_molt_check_args() {
+        fn check(argv: &[Value]) -> MoltResult {
+            molt_check_args!(argv, 2, 3, "a ?b?");
+            molt_ok!()
+        }
+
+        fn check2(argv: &[Value]) -> MoltResult {
+            molt_check_args!(2, argv, 3, 0, "a ?b ...?");
+            molt_ok!()
+        }
+
+        let argv = [Value::from("cmd"), Value::from("x")];
+        assert!(check(&argv).is_ok());
+        assert!(check2(&argv).is_err());
+        assert!(check_err(
+            check(&argv[0..1]),
+            "wrong # args: should be \"cmd a ?b?\""
+        ));
+        assert!(check_err(
+            check2(&argv),
+            "wrong # args: should be \"cmd x a ?b ...?\""
+        ));
+    }
+
     fn check_err(result: MoltResult, msg: &str) -> bool {
         match result {
             Err(exception) => exception.is_error() && exception.value() == msg.into(),
@@ -36,9 +36,51 @@
 //! script evaluation by the `Interp`.  In this way, the script's internal form persists and
 //! need not be recomputed for each evaluation.
 //!
+//! ## Parsing Scripts for Tools
+//!
+//! The [`parse`](fn.parse.html) function parses a script into its internal form without
+//! evaluating it, so that tools such as linters, formatters, and editor integrations can
+//! analyze Molt code.  The following parts of the API are stable:
+//!
+//! * The `parse` function, which is also available as `molt::parse`.
+//! * The `Script`, `WordVec`, and `Word` types, and the `Script::commands`,
+//!   `WordVec::words`, and `WordVec::line` accessors.
+//! * The variants of `Word`.  New variants may be added in future releases, so `Word` is
+//!   marked `#[non_exhaustive]`; tools should handle unknown variants gracefully.
+//!
+//! The `Debug` representation of these types, as output by the undocumented `parse`
+//! command, is not stable.
+//!
+//! The internal form records what each word evaluates to, but not how it was written or where
+//! it appears in the source text.  For that, the [`parse_tree`](fn.parse_tree.html) function
+//! returns a `ParsedScript`, a read-only view of the script's top-level commands in which each
+//! word has a `WordKind`, e.g., braced, quoted, or variable reference, and a byte span in the
+//! source.  The `parse_tree` function and its `ParsedScript`, `ParsedCommand`, `ParsedWord`,
+//! and `WordKind` types are also stable; new variants may be added to `WordKind`.
+//!
+//! The [`check_complete`](fn.check_complete.html) function determines whether a script is
+//! complete, and if not, which brace, bracket, or quote was left open and where, as
+//! editors and REPLs need to know when the user's input is not yet finished.
+//!
+//! ```
+//! use molt::parser::Word;
+//! # use molt::types::*;
+//! # fn dummy() -> Result<(), Exception> {
+//! let script = molt::parse("set a [expr {1 + 1}]; puts $a")?;
+//! let commands = script.commands();
+//! assert_eq!(commands.len(), 2);
+//!
+//! let words = commands[1].words();
+//! assert_eq!(words[0], Word::Value(Value::from("puts")));
+//! assert_eq!(words[1], Word::VarRef("a".into()));
+//! # Ok(())
+//! # }
+//! ```
+//!
 //! ## Other Parsing Functions
 //!
-//! The module provides a number lower-level parsing functions to the rest of the library.
+//! The module provides a number lower-level parsing functions to the rest of the library;
+//! these are not part of the public API.
 //! For example, the `expr` parser sometimes need to parse quoted string and variable names.
 //!
 //! ## Variable Name Literals
@@ -57,10 +99,12 @@ use crate::types::MoltResult;
 use crate::types::VarName;
 use crate::util::is_varname_char;
 use crate::value::Value;
+use std::fmt;
+use std::ops::Range;
 
 /// A compiled script, which can be executed in the context of an interpreter.
 #[derive(Debug, PartialEq)]
-pub(crate) struct Script {
+pub struct Script {
     // A script is a list of one or more commands to execute.
     commands: Vec<WordVec>,
 }
@@ -81,25 +125,38 @@ impl Script {
 
 /// A single command, consisting of a vector of `Word`'s for evaluation.
 #[derive(Debug, PartialEq)]
-pub(crate) struct WordVec {
+pub struct WordVec {
     words: Vec<Word>,
+
+    // The line on which the command begins, counting from 1, within the parsed input.
+    line: usize,
 }
 
 impl WordVec {
     /// Create a new `WordVec`, to which `Word`'s can be added during parsing.
     fn new() -> Self {
-        Self { words: Vec::new() }
+        Self {
+            words: Vec::new(),
+            line: 1,
+        }
     }
 
     /// Return the list of words for evaluation.
     pub fn words(&self) -> &[Word] {
         &self.words
     }
+
+    /// Returns the line on which the command begins, counting from 1.  Lines are counted
+    /// from the beginning of the parsed input, e.g., a procedure body.
+    pub fn line(&self) -> usize {
+        self.line
+    }
 }
 
 /// A single `Word` in a command.  A `Word` can be evaluated to produce a `Value`.
 #[derive(Debug, PartialEq)]
-pub(crate) enum Word {
+#[non_exhaustive]
+pub enum Word {
     /// A `Value`, e.g., the braced word `{a b c}` parses to the value "a b c".
     Value(Value),
 
@@ -127,26 +184,264 @@ pub(crate) enum Word {
 }
 
 /// Parses a script, given as a string slice.  Returns a parsed `Script` (or an error).
-pub(crate) fn parse(input: &str) -> Result<Script, Exception> {
+/// The script is not evaluated, and no commands need be defined.
+pub fn parse(input: &str) -> Result<Script, Exception> {
     // FIRST, create an EvalPtr as a parsing aid; then parse the script.
     let mut ctx = EvalPtr::new(input);
     parse_script(&mut ctx)
 }
 
+/// The result of [`check_complete`](fn.check_complete.html).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompleteStatus {
+    /// The script is complete and can be evaluated.
+    Complete,
+
+    /// The input ended before a construct was terminated.  `kind` is the kind of the
+    /// innermost unterminated construct, and `open_at` is the byte offset of its opening
+    /// brace, bracket, or quote in the script.
+    Incomplete {
+        kind: IncompleteKind,
+        open_at: usize,
+    },
+
+    /// The script is invalid for some other reason, e.g., there are extra characters
+    /// after a close-brace.  More input won't make it complete.
+    Invalid,
+}
+
+/// The kinds of unterminated construct reported by
+/// [`CompleteStatus::Incomplete`](enum.CompleteStatus.html#variant.Incomplete).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IncompleteKind {
+    /// A braced word, e.g., `{a b`, or braced variable name, e.g., `${a`.
+    Brace,
+
+    /// A command substitution, e.g., `[foo`.
+    Bracket,
+
+    /// A quoted word, e.g., `"a b`.
+    Quote,
+}
+
+impl fmt::Display for IncompleteKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            IncompleteKind::Brace => write!(f, "brace"),
+            IncompleteKind::Bracket => write!(f, "bracket"),
+            IncompleteKind::Quote => write!(f, "quote"),
+        }
+    }
+}
+
+/// Determines whether a script, given as a string slice, is complete, i.e., whether every
+/// brace, bracket, and quote in it has been closed.  If not, reports the kind and byte
+/// offset of the innermost construct that is still open.  The script is not evaluated.
+///
+/// # Example
+///
+/// ```
+/// use molt::parser::*;
+///
+/// assert_eq!(check_complete("set a {b c}"), CompleteStatus::Complete);
+/// assert_eq!(
+///     check_complete("set a [expr {1 +"),
+///     CompleteStatus::Incomplete { kind: IncompleteKind::Brace, open_at: 12 }
+/// );
+/// ```
+pub fn check_complete(input: &str) -> CompleteStatus {
+    let mut ctx = EvalPtr::new(input);
+
+    match parse_script(&mut ctx) {
+        Ok(_) => CompleteStatus::Complete,
+        Err(_) => match ctx.unclosed() {
+            Some((kind, open_at)) => CompleteStatus::Incomplete { kind, open_at },
+            None => CompleteStatus::Invalid,
+        },
+    }
+}
+
+/// A script parsed for use by tools, as returned by [`parse_tree`](fn.parse_tree.html).
+/// It's a read-only view of the script's top-level commands and their words, with the
+/// location of each in the source text.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParsedScript<'a> {
+    commands: Vec<ParsedCommand<'a>>,
+}
+
+impl<'a> ParsedScript<'a> {
+    /// Returns the script's commands, in order.  Comments and empty commands are omitted.
+    pub fn commands(&self) -> &[ParsedCommand<'a>] {
+        &self.commands
+    }
+}
+
+/// A single command in a [`ParsedScript`](struct.ParsedScript.html).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParsedCommand<'a> {
+    words: Vec<ParsedWord<'a>>,
+    line: usize,
+}
+
+impl<'a> ParsedCommand<'a> {
+    /// Returns the command's words: the command name and its arguments.
+    pub fn words(&self) -> &[ParsedWord<'a>] {
+        &self.words
+    }
+
+    /// Returns the line on which the command begins, counting from 1.
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    /// Returns the byte span of the command in the source text, from the beginning of its
+    /// first word to the end of its last word.
+    pub fn span(&self) -> Range<usize> {
+        let first = self.words.first().expect("empty command");
+        let last = self.words.last().expect("empty command");
+        first.span.start..last.span.end
+    }
+}
+
+/// A single word in a [`ParsedCommand`](struct.ParsedCommand.html).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParsedWord<'a> {
+    kind: WordKind,
+    span: Range<usize>,
+    text: &'a str,
+}
+
+impl<'a> ParsedWord<'a> {
+    /// Returns the kind of word.
+    pub fn kind(&self) -> WordKind {
+        self.kind
+    }
+
+    /// Returns the byte span of the word in the source text, including any braces,
+    /// quotes, or brackets.
+    pub fn span(&self) -> Range<usize> {
+        self.span.clone()
+    }
+
+    /// Returns the text of the word as it appears in the source text.
+    pub fn text(&self) -> &'a str {
+        self.text
+    }
+}
+
+/// The kinds of [`ParsedWord`](struct.ParsedWord.html).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum WordKind {
+    /// A bare word, e.g., `abc` or `a$b`, which may contain substitutions.
+    Bare,
+
+    /// A quoted word, e.g., `"a $b"`, which may contain substitutions.
+    Quoted,
+
+    /// A braced word, e.g., `{a $b}`, which contains no substitutions.
+    Braced,
+
+    /// A bare word consisting of a single variable reference, e.g., `$a` or `$a(1)`.
+    Variable,
+
+    /// A bare word consisting of a single command substitution, e.g., `[foo]`.
+    Command,
+
+    /// A word preceded by the expansion operator, e.g., `{*}$args`.
+    Expansion,
+}
+
+impl WordKind {
+    /// Determines the kind of a parsed word given its source text.
+    fn of(word: &Word, text: &str) -> Self {
+        match word {
+            Word::Expand(_) => WordKind::Expansion,
+            _ if text.starts_with('{') => WordKind::Braced,
+            _ if text.starts_with('"') => WordKind::Quoted,
+            Word::VarRef(_) | Word::ArrayRef(_, _) => WordKind::Variable,
+            Word::Script(_) => WordKind::Command,
+            _ => WordKind::Bare,
+        }
+    }
+}
+
+/// Parses a script for use by tools such as linters and editor integrations, given as a
+/// string slice.  Returns a [`ParsedScript`](struct.ParsedScript.html) giving the
+/// kind and location of each word of each top-level command, or an error if the
+/// script is invalid.  The script is not evaluated, and no commands need be defined.
+///
+/// # Example
+///
+/// ```
+/// use molt::parser::parse_tree;
+/// use molt::parser::WordKind;
+/// # use molt::types::*;
+/// # fn dummy() -> Result<(), Exception> {
+/// let input = "set a [expr {1 + 1}]\nputs \"a=$a\"";
+/// let script = parse_tree(input)?;
+///
+/// let puts = &script.commands()[1];
+/// assert_eq!(puts.line(), 2);
+///
+/// let arg = &puts.words()[1];
+/// assert_eq!(arg.kind(), WordKind::Quoted);
+/// assert_eq!(arg.text(), "\"a=$a\"");
+/// assert_eq!(&input[arg.span()], "\"a=$a\"");
+/// # Ok(())
+/// # }
+/// ```
+pub fn parse_tree(input: &str) -> Result<ParsedScript<'_>, Exception> {
+    let mut ctx = EvalPtr::new(input);
+    let mut commands = Vec::new();
+
+    while !ctx.at_end_of_script() {
+        let mut spans = Vec::new();
+        let cmd = parse_command(&mut ctx, Some(&mut spans))?;
+
+        if !cmd.words.is_empty() {
+            let words = cmd
+                .words
+                .iter()
+                .zip(spans)
+                .map(|(word, span)| {
+                    let text = &input[span.clone()];
+                    ParsedWord {
+                        kind: WordKind::of(word, text),
+                        span,
+                        text,
+                    }
+                })
+                .collect();
+
+            commands.push(ParsedCommand {
+                words,
+                line: cmd.line,
+            });
+        }
+    }
+
+    Ok(ParsedScript { commands })
+}
+
 /// Parses a script represented by an `EvalPtr`.  This form is also used by `expr`.
 pub(crate) fn parse_script(ctx: &mut EvalPtr) -> Result<Script, Exception> {
     let mut script = Script::new();
 
     // Parse commands from the input until we've reach the end.
     while !ctx.at_end_of_script() {
-        script.commands.push(parse_command(ctx)?);
+        script.commands.push(parse_command(ctx, None)?);
     }
 
     Ok(script)
 }
 
-/// Parses a single command from the input, returning it as a `WordVec`.
-fn parse_command(ctx: &mut EvalPtr) -> Result<WordVec, Exception> {
+/// Parses a single command from the input, returning it as a `WordVec`.  If `spans` is
+/// given, the byte span of each word in the input is pushed onto it.
+fn parse_command(
+    ctx: &mut EvalPtr,
+    mut spans: Option<&mut Vec<Range<usize>>>,
+) -> Result<WordVec, Exception> {
     let mut cmd: WordVec = WordVec::new();
 
     // FIRST, deal with whitespace and comments between "here" and the next command.
@@ -161,12 +456,20 @@ fn parse_command(ctx: &mut EvalPtr) -> Result<WordVec, Exception> {
         }
     }
 
+    // NEXT, note the line on which the command begins.
+    cmd.line = ctx.line();
+
     // NEXT, Read words until we get to the end of the line or hit an error
     // NOTE: parse_word() can always assume that it's at the beginning of a word.
     while !ctx.at_end_of_command() {
         // FIRST, get the next word; there has to be one, or there's an input error.
+        let start = ctx.mark();
         cmd.words.push(parse_next_word(ctx)?);
 
+        if let Some(spans) = &mut spans {
+            spans.push(start..ctx.mark());
+        }
+
         // NEXT, skip any whitespace.
         ctx.skip_line_white();
     }
@@ -195,7 +498,8 @@ fn parse_next_word(ctx: &mut EvalPtr) -> Result<Word, Exception> {
             if ctx.at_end() || ctx.next_is_block_white() {
                 return Ok(Word::Value(Value::from("*")));
             } else {
-                return Ok(Word::Expand(Box::new(parse_next_word(ctx)?)));
+                let word = nested(ctx, parse_next_word)?;
+                return Ok(Word::Expand(Box::new(word)));
             }
         }
 
@@ -213,6 +517,7 @@ fn parse_next_word(ctx: &mut EvalPtr) -> Result<Word, Exception> {
 pub(crate) fn parse_braced_word(ctx: &mut EvalPtr) -> Result<Word, Exception> {
     // FIRST, skip the opening brace, and count it; non-escaped braces need to
     // balance.
+    let open_at = ctx.mark();
     ctx.skip_char('{');
     let mut count = 1;
 
@@ -253,6 +558,7 @@ pub(crate) fn parse_braced_word(ctx: &mut EvalPtr) -> Result<Word, Exception> {
             if let Some(ch) = ctx.next() {
                 if ch == '\n' {
                     text.push(' ');
+                    ctx.skip_while(|ch| *ch == ' ' || *ch == '\t');
                 } else {
                     text.push('\\');
                     text.push(ch);
@@ -264,6 +570,7 @@ pub(crate) fn parse_braced_word(ctx: &mut EvalPtr) -> Result<Word, Exception> {
         }
     }
 
+    ctx.set_unclosed(IncompleteKind::Brace, open_at);
     molt_err!("missing close-brace")
 }
 
@@ -272,6 +579,7 @@ pub(crate) fn parse_braced_word(ctx: &mut EvalPtr) -> Result<Word, Exception> {
 /// if the close quote is missing.
 pub(crate) fn parse_quoted_word(ctx: &mut EvalPtr) -> Result<Word, Exception> {
     // FIRST, consume the the opening quote.
+    let open_at = ctx.mark();
     ctx.next();
 
     // NEXT, add tokens to the word until we reach the close quote
@@ -313,6 +621,7 @@ pub(crate) fn parse_quoted_word(ctx: &mut EvalPtr) -> Result<Word, Exception> {
         }
     }
 
+    ctx.set_unclosed(IncompleteKind::Quote, open_at);
     molt_err!("missing \"")
 }
 
@@ -356,16 +665,33 @@ fn parse_bare_word(ctx: &mut EvalPtr, index_flag: bool) -> Result<Word, Exceptio
     Ok(tokens.take())
 }
 
+/// Parses a nested construct using the given parser, returning an error if the input is
+/// nested too deeply.  The parser recurses at each level of nesting, and would otherwise
+/// overflow the Rust stack on pathological input.
+fn nested<T>(
+    ctx: &mut EvalPtr,
+    parser: fn(&mut EvalPtr) -> Result<T, Exception>,
+) -> Result<T, Exception> {
+    if !ctx.enter() {
+        return molt_err!("too many nested substitutions");
+    }
+
+    let result = parser(ctx);
+    ctx.leave();
+    result
+}
+
 /// Parses an embedded script in a bare or quoted word, returning the result as a
 /// Script.  It's an error if the close-bracket is missing.
 fn parse_brackets(ctx: &mut EvalPtr) -> Result<Script, Exception> {
     // FIRST, skip the '['
+    let open_at = ctx.mark();
     ctx.skip_char('[');
 
     // NEXT, parse the script up to the matching ']'
     let old_flag = ctx.is_bracket_term();
     ctx.set_bracket_term(true);
-    let result = parse_script(ctx);
+    let result = nested(ctx, parse_script);
     ctx.set_bracket_term(old_flag);
 
     // NEXT, make sure there's a closing bracket
@@ -373,6 +699,7 @@ fn parse_brackets(ctx: &mut EvalPtr) -> Result<Script, Exception> {
         if ctx.next_is(']') {
             ctx.next();
         } else {
+            ctx.set_unclosed(IncompleteKind::Bracket, open_at);
             return molt_err!("missing close-bracket");
         }
     }
@@ -404,11 +731,13 @@ fn parse_dollar(ctx: &mut EvalPtr, tokens: &mut Tokens) -> Result<(), Exception>
 pub(crate) fn parse_varname(ctx: &mut EvalPtr) -> Result<Word, Exception> {
     // FIRST, is this a braced variable name?
     if ctx.next_is('{') {
+        let open_at = ctx.mark();
         ctx.skip_char('{');
         let start = ctx.mark();
         ctx.skip_while(|ch| *ch != '}');
 
         if ctx.at_end() {
+            ctx.set_unclosed(IncompleteKind::Brace, open_at);
             return molt_err!("missing close-brace for variable name");
         }
 
@@ -422,8 +751,16 @@ pub(crate) fn parse_varname(ctx: &mut EvalPtr) -> Result<Word, Exception> {
             None => Ok(Word::VarRef(var_name.name().into())),
         }
     } else {
+        // Variable names can include namespace separators, e.g., "$::mylib::count".
         let start = ctx.mark();
-        ctx.skip_while(|ch| is_varname_char(*ch));
+        loop {
+            ctx.skip_while(|ch| is_varname_char(*ch));
+            if !ctx.next_is_namespace_sep() {
+                break;
+            }
+            ctx.skip_char(':');
+            ctx.skip_char(':');
+        }
         let name = ctx.token(start).to_string();
 
         if !ctx.next_is('(') {
@@ -432,7 +769,7 @@ pub(crate) fn parse_varname(ctx: &mut EvalPtr) -> Result<Word, Exception> {
         } else {
             // Array; parse out the word that evaluates to the index.
             ctx.skip();
-            let index = parse_bare_word(ctx, true)?;
+            let index = nested(ctx, |ctx| parse_bare_word(ctx, true))?;
             ctx.skip_char(')');
             Ok(Word::ArrayRef(name, Box::new(index)))
         }
@@ -445,37 +782,14 @@ pub(crate) fn parse_varname(ctx: &mut EvalPtr) -> Result<Word, Exception> {
 /// If it contains an opening parenthesis and ends with a closing parenthesis, then
 /// it's an array reference; otherwise it's just a scalar name.
 pub(crate) fn parse_varname_literal(literal: &str) -> VarName {
-    let mut ctx = EvalPtr::new(literal);
-
-    // FIRST, find the first open parenthesis.  If there is none, just return the literal
-    // as a scalar.
-    let start = ctx.mark();
-    ctx.skip_while(|ch| *ch != '(');
-
-    if ctx.at_end() {
-        return VarName::scalar(literal.into());
-    }
-
-    // NEXT, pluck out the name.
-    let name = ctx.token(start).to_string();
-    ctx.skip_char('(');
-
-    if ctx.tok().as_str().is_empty() {
-        return VarName::scalar(literal.into());
-    }
-
-    // NEXT, skip to the final character.
-    let start = ctx.mark();
-    let chars_left = ctx.tok().as_str().len() - 1;
-
-    for _ in 0..chars_left {
-        ctx.skip();
-    }
-
-    if ctx.next_is(')') {
-        VarName::array(name, ctx.token(start).to_string())
-    } else {
-        VarName::scalar(literal.into())
+    // FIRST, find the first open parenthesis.  If there is none, or it's the last
+    // character, or the literal doesn't end with a close parenthesis, it's a scalar.
+    match literal.find('(') {
+        Some(open) if literal.len() > open + 1 && literal.ends_with(')') => VarName::array(
+            literal[..open].into(),
+            literal[open + 1..literal.len() - 1].into(),
+        ),
+        _ => VarName::scalar(literal.into()),
     }
 }
 
@@ -556,7 +870,7 @@ impl Tokens {
 ///
 /// A command for parsing an arbitrary script and outputting the parsed form.
 /// This is an undocumented debugging aid.  The output can be greatly improved.
-pub fn cmd_parse(_interp: &mut Interp, _: ContextID, argv: &[Value]) -> MoltResult {
+pub(crate) fn cmd_parse(_interp: &mut Interp, _: ContextID, argv: &[Value]) -> MoltResult {
     check_args(1, argv, 2, 2, "script")?;
 
     let script = &argv[1];
@@ -568,6 +882,55 @@ pub fn cmd_parse(_interp: &mut Interp, _: ContextID, argv: &[Value]) -> MoltResu
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_parse_tree() {
+        let input = "cmd bare \"quoted $x\" {braced $x} $var $arr(1) [sub 1] {*}$list a$b\n\n# comment\n  next;";
+        let script = parse_tree(input).unwrap();
+        let cmds = script.commands();
+        assert_eq!(cmds.len(), 2);
+
+        let words: Vec<(WordKind, &str)> = cmds[0]
+            .words()
+            .iter()
+            .map(|w| (w.kind(), w.text()))
+            .collect();
+        assert_eq!(
+            words,
+            vec![
+                (WordKind::Bare, "cmd"),
+                (WordKind::Bare, "bare"),
+                (WordKind::Quoted, "\"quoted $x\""),
+                (WordKind::Braced, "{braced $x}"),
+                (WordKind::Variable, "$var"),
+                (WordKind::Variable, "$arr(1)"),
+                (WordKind::Command, "[sub 1]"),
+                (WordKind::Expansion, "{*}$list"),
+                (WordKind::Bare, "a$b"),
+            ]
+        );
+
+        // Spans index the source text.
+        for word in cmds[0].words() {
+            assert_eq!(&input[word.span()], word.text());
+        }
+        assert_eq!(cmds[0].words()[2].span(), 9..20);
+        assert_eq!(cmds[0].span(), 0..input.find('\n').unwrap());
+        assert_eq!(cmds[0].line(), 1);
+
+        assert_eq!(cmds[1].words()[0].text(), "next");
+        let next = input.find("next").unwrap();
+        assert_eq!(cmds[1].words()[0].span(), next..next + 4);
+        assert_eq!(cmds[1].line(), 4);
+
+        // Spans are byte offsets.
+        let script = parse_tree("puts \u{e9}t\u{e9} x").unwrap();
+        assert_eq!(script.commands()[0].words()[1].span(), 5..10);
+        assert_eq!(script.commands()[0].words()[2].span(), 11..12);
+
+        // Errors
+        assert_eq!(parse_tree("set a {b"), molt_err!("missing close-brace"));
+    }
+
     #[test]
     fn test_tokens() {
         // No tokens pushed; get empty string.
@@ -647,6 +1010,29 @@ mod tests {
         assert_eq!(cmds[1].words, vec![Word::Value(Value::from("b"))]);
 
         assert_eq!(parse("a {"), molt_err!("missing close-brace"));
+
+        // Line numbers
+        let cmds = parse("a\n\n  # comment\n  b {\n}; c\n\"\n\" [\nd]\ne")
+            .unwrap()
+            .commands;
+        let lines: Vec<usize> = cmds.iter().map(|cmd| cmd.line()).collect();
+        assert_eq!(lines, vec![1, 4, 5, 6, 9]);
+
+        // Backslash-newline separates words, and doesn't end the command.
+        let cmds = parse("a\\\n  b \\\n c").unwrap().commands;
+        assert_eq!(cmds.len(), 1);
+        assert_eq!(
+            cmds[0].words,
+            vec![
+                Word::Value(Value::from("a")),
+                Word::Value(Value::from("b")),
+                Word::Value(Value::from("c")),
+            ]
+        );
+
+        let cmds = parse("\\\n a\nb").unwrap().commands;
+        assert_eq!(cmds.len(), 2);
+        assert_eq!(cmds[0].words, vec![Word::Value(Value::from("a"))]);
     }
 
     #[test]
@@ -747,6 +1133,17 @@ mod tests {
             Ok((Word::Value(Value::from("ab c")), " ".into()))
         );
 
+        assert_eq!(
+            pbrace("{ab\\\n  \tc} "),
+            Ok((Word::Value(Value::from("ab c")), " ".into()))
+        );
+
+        // Other escapes are retained as is.
+        assert_eq!(
+            pbrace("{a\\nb} "),
+            Ok((Word::Value(Value::from("a\\nb")), " ".into()))
+        );
+
         // Strings with missing close-brace
         assert_eq!(pbrace("{abc"), molt_err!("missing close-brace"));
 
@@ -789,6 +1186,33 @@ mod tests {
             Ok((Word::Value(Value::from("-w")), " ".into()))
         );
 
+        // Each escape form
+        assert_eq!(
+            pqw("\"\\a\\b\\f\\n\\r\\t\\v\""),
+            Ok((
+                Word::Value(Value::from("\x07\x08\x0c\n\r\t\x0b")),
+                "".into()
+            ))
+        );
+        assert_eq!(
+            pqw("\"\\x41\\u00e9\\U0001F600\""),
+            Ok((Word::Value(Value::from("A\u{e9}\u{1F600}")), "".into()))
+        );
+        assert_eq!(
+            pqw("\"\\101\\0\\7\""),
+            Ok((Word::Value(Value::from("A\x00\x07")), "".into()))
+        );
+        assert_eq!(
+            pqw("\"\\\"\\$\\[\\\\\""),
+            Ok((Word::Value(Value::from("\"$[\\")), "".into()))
+        );
+
+        // Line continuation
+        assert_eq!(
+            pqw("\"line1\\\n    line2\""),
+            Ok((Word::Value(Value::from("line1 line2")), "".into()))
+        );
+
         // Variable reference
         assert_eq!(
             pqw("\"a$x.b\" "),
@@ -879,6 +1303,33 @@ mod tests {
             Ok((Word::Value(Value::from("-w")), " ".into()))
         );
 
+        // Each escape form
+        assert_eq!(
+            pbare("\\a\\b\\f\\n\\r\\t\\v ", false),
+            Ok((
+                Word::Value(Value::from("\x07\x08\x0c\n\r\t\x0b")),
+                " ".into()
+            ))
+        );
+        assert_eq!(
+            pbare("\\x41\\u00e9\\U0001F600 ", false),
+            Ok((Word::Value(Value::from("A\u{e9}\u{1F600}")), " ".into()))
+        );
+        assert_eq!(
+            pbare("\\101\\7 ", false),
+            Ok((Word::Value(Value::from("A\x07")), " ".into()))
+        );
+        assert_eq!(
+            pbare("a\\ b ", false),
+            Ok((Word::Value(Value::from("a b")), " ".into()))
+        );
+
+        // Line continuation ends the word
+        assert_eq!(
+            pbare("a\\\n b", false),
+            Ok((Word::Value(Value::from("a")), "\\\n b".into()))
+        );
+
         // Variable reference
         assert_eq!(
             pbare("a$x.b ", false),
@@ -954,6 +1405,23 @@ mod tests {
         assert_eq!(pbrack("[incomplete"), molt_err!("missing close-bracket"));
     }
 
+    #[test]
+    fn test_parse_nesting_limit() {
+        let nest = |n: usize| format!("{}1{}", "[a ".repeat(n), "]".repeat(n));
+
+        assert!(parse(&nest(100)).is_ok());
+        assert_eq!(
+            parse(&nest(10000)).unwrap_err().value().as_str(),
+            "too many nested substitutions"
+        );
+
+        let nest = |n: usize| format!("set x {}1{}", "$a(".repeat(n), ")".repeat(n));
+        assert!(parse(&nest(100)).is_ok());
+        assert!(parse(&nest(10000)).is_err());
+
+        assert!(parse(&format!("list {}a", "{*}".repeat(10000))).is_err());
+    }
+
     fn pbrack(input: &str) -> Result<Script, Exception> {
         let mut ctx = EvalPtr::new(input);
         parse_brackets(&mut ctx)
@@ -971,6 +1439,14 @@ mod tests {
             Ok((Word::VarRef("a1_".into()), ".bc".into()))
         );
 
+        // Qualified var names
+        assert_eq!(pvar("$::a"), Ok((Word::VarRef("::a".into()), "".into())));
+        assert_eq!(
+            pvar("$a::b::c."),
+            Ok((Word::VarRef("a::b::c".into()), ".".into()))
+        );
+        assert_eq!(pvar("$a:b"), Ok((Word::VarRef("a".into()), ":b".into())));
+
         // Array names
         assert_eq!(
             pvar("$a(1)"),
@@ -1027,6 +1503,11 @@ mod tests {
         assert_eq!(parse_varname_literal("a()"), array("a", ""));
         assert_eq!(parse_varname_literal("%(()"), array("%", "("));
         assert_eq!(parse_varname_literal("%())"), array("%", ")"));
+
+        // Multibyte characters
+        assert_eq!(parse_varname_literal("a(é)"), array("a", "é"));
+        assert_eq!(parse_varname_literal("ü(日本)"), array("ü", "日本"));
+        assert_eq!(parse_varname_literal("a(é"), scalar("a(é"));
     }
 
     fn scalar(name: &str) -> VarName {
@@ -9,30 +9,51 @@
 //! the current are said to be higher in the stack, following Standard TCL practice (e.g.,
 //! `upvar`, `uplevel`).
 //!
+//! A qualified variable name, i.e., one containing `::`, always refers to a variable in the
+//! global scope: `::count` is the global `count`, and both `mylib::count` and
+//! `::mylib::count` are the global `mylib::count`.  Namespace variables are global variables
+//! whose names carry the namespace prefix.
+//!
 //! Molt clients do not interact with this mechanism directly, but via the
 //! `Interp` (or the Molt language itself).
+//!
+//! ## The `env` Array
+//!
+//! Once `ScopeStack::link_env` has been called, e.g., by `Interp::link_env`, the global
+//! array variable `env` is linked to
+//! the process environment: setting or unsetting an element of `env` sets or removes the
+//! environment variable, and reading `env` reads the process environment, so that changes
+//! made to the environment from Rust are visible to scripts.  Unsetting the entire `env`
+//! array breaks the link.
+//!
+//! **Note:** the process environment is shared by all threads.  Setting environment variables
+//! while other threads are reading them (e.g., via `std::env::var`, or any C library call
+//! that reads the environment) is unsafe on many platforms, and so the `env` array should
+//! only be modified by single-threaded applications.
 
 use crate::types::Exception;
 use crate::types::MoltList;
 use crate::value::Value;
+use indexmap::IndexMap;
+use std::borrow::Cow;
 use std::collections::HashMap;
 use std::fmt::Debug;
 
 /// A variable in a `Scope`.  If the variable is defined in the given `Scope`, it is a
 /// `Scalar` or an `Array`; if it is an alias to a variable in a higher scope (e.g., a global)
-/// then the `Upvar` gives the referenced scope.  The `New` variant is used transiently as
-/// part of setting a variable for the first time.
-#[derive(Eq, PartialEq)]
+/// then the `Upvar` gives the referenced scope and the variable's name in that scope.  The
+/// `New` variant is used transiently as part of setting a variable for the first time.
+#[derive(Clone, Eq, PartialEq)]
 enum Var {
     /// A scalar variable, with its value.
     Scalar(Value),
 
-    /// An array variable, with its hash table from names to values.
-    Array(HashMap<String, Value>),
+    /// An array variable, with its elements and searches.
+    Array(Array),
 
-    /// An alias to a variable at a higher stack level, with the referenced stack level.
-    /// Note that aliases can chain.
-    Upvar(usize),
+    /// An alias to a variable at a higher stack level, with the referenced stack level
+    /// and variable name.  Note that aliases can chain.
+    Upvar(usize, String),
 
     /// A variable that has just been created so that it can be set.
     New,
@@ -41,10 +62,49 @@ enum Var {
 impl Var {
     /// This is an upvar'd variable?
     fn is_upvar(&self) -> bool {
-        if let Var::Upvar(_) = self {
-            true
-        } else {
-            false
+        matches!(self, Var::Upvar(_, _))
+    }
+}
+
+/// An array variable's elements, by index, and the searches of them in progress.  A search
+/// is a position in the elements' order, so adding or removing an element changes the
+/// positions of the others and invalidates every search of the array; the searches
+/// go away with the array.
+#[derive(Clone, Default, Eq, PartialEq)]
+struct Array {
+    elements: IndexMap<String, Value>,
+
+    /// The searches by ID, with the position of each search's next element; `None` if the
+    /// array has changed since the search started.
+    searches: Vec<(String, Option<usize>)>,
+}
+
+impl Array {
+    /// Sets the indexed element, creating it if it doesn't exist.  Only copies the index
+    /// if the element is new.
+    fn insert(&mut self, index: &str, val: Value) {
+        match self.elements.get_mut(index) {
+            Some(elem) => *elem = val,
+            None => {
+                self.elements.insert(index.into(), val);
+                self.changed();
+            }
+        }
+    }
+
+    /// Removes the indexed element, returning true if it existed.
+    fn remove(&mut self, index: &str) -> bool {
+        let removed = self.elements.swap_remove(index).is_some();
+        if removed {
+            self.changed();
+        }
+        removed
+    }
+
+    /// Invalidates the array's searches.
+    fn changed(&mut self) {
+        for (_, next) in &mut self.searches {
+            *next = None;
         }
     }
 }
@@ -54,28 +114,113 @@ impl Debug for Var {
         match self {
             Var::Scalar(value) => write!(f, "Var::Scalar({})", value.as_str()),
             Var::Array(_) => write!(f, "Var::Array(TODO)"),
-            Var::Upvar(level) => write!(f, "Var::Upvar({})", level),
+            Var::Upvar(level, name) => write!(f, "Var::Upvar({}, {})", level, name),
             Var::New => write!(f, "Var::New"),
         }
     }
 }
 
-/// A scope: a level in the `ScopeStack`.  It contains a hash table of `Var`'s by name.
+/// A scope: a level in the `ScopeStack`.  It contains a table of `Var`'s by name.
 /// Scopes may be pushed onto the stack and popped off later.  Most typically, a scope is
 /// pushed on the stack by a `proc` before executing its body, and then popped afterwards.
 #[derive(Default, Debug)]
 struct Scope {
     /// Vars in this scope by name.
-    map: HashMap<String, Var>,
+    map: VarMap,
+
+    /// The namespace whose variables are visible in this scope, if it's the scope of a
+    /// `namespace eval`, as a global variable name prefix, e.g., `a::b`, or the empty
+    /// string for the global namespace.
+    namespace: Option<String>,
 }
 
 impl Scope {
     /// Create a new empty scope.
     pub fn new() -> Self {
         Scope {
-            map: HashMap::new(),
+            map: VarMap::default(),
+            namespace: None,
+        }
+    }
+}
+
+/// The largest number of variables a `VarMap` keeps in its vector.
+const SMALL_SCOPE: usize = 8;
+
+/// A table of `Var`'s by name.  A procedure's scope usually contains only a few
+/// variables, and searching a short vector is faster than hashing the name; so the
+/// variables are kept in a vector until there are more than `SMALL_SCOPE` of them,
+/// and then moved to a hash table.  At most one of the two is non-empty.
+#[derive(Clone, Default, Debug)]
+struct VarMap {
+    small: Vec<(String, Var)>,
+    large: HashMap<String, Var>,
+}
+
+impl VarMap {
+    fn get(&self, name: &str) -> Option<&Var> {
+        if self.large.is_empty() {
+            self.small
+                .iter()
+                .find(|(n, _)| n == name)
+                .map(|(_, var)| var)
+        } else {
+            self.large.get(name)
+        }
+    }
+
+    fn get_mut(&mut self, name: &str) -> Option<&mut Var> {
+        if self.large.is_empty() {
+            self.small
+                .iter_mut()
+                .find(|(n, _)| n == name)
+                .map(|(_, var)| var)
+        } else {
+            self.large.get_mut(name)
+        }
+    }
+
+    /// Inserts the variable, replacing any existing variable with the same name, and
+    /// returns a mutable borrow of it.
+    fn insert(&mut self, name: &str, var: Var) -> &mut Var {
+        if self.large.is_empty() {
+            match self.small.iter().position(|(n, _)| n == name) {
+                Some(i) => {
+                    self.small[i].1 = var;
+                    return &mut self.small[i].1;
+                }
+                None if self.small.len() < SMALL_SCOPE => {
+                    self.small.push((name.into(), var));
+                    return &mut self.small.last_mut().expect("just pushed").1;
+                }
+                None => self.large.extend(self.small.drain(..)),
+            }
+        }
+
+        let slot = self.large.entry(name.into()).or_insert(Var::New);
+        *slot = var;
+        slot
+    }
+
+    fn remove(&mut self, name: &str) -> Option<Var> {
+        if self.large.is_empty() {
+            let i = self.small.iter().position(|(n, _)| n == name)?;
+            Some(self.small.swap_remove(i).1)
+        } else {
+            self.large.remove(name)
         }
     }
+
+    fn iter(&self) -> impl Iterator<Item = (&String, &Var)> {
+        self.small
+            .iter()
+            .map(|(name, var)| (name, var))
+            .chain(self.large.iter())
+    }
+
+    fn keys(&self) -> impl Iterator<Item = &String> {
+        self.iter().map(|(name, _)| name)
+    }
 }
 
 /// The scope stack: a stack of variable scopes corresponding to the Molt `proc`
@@ -83,8 +228,29 @@ impl Scope {
 #[derive(Default, Debug)]
 pub(crate) struct ScopeStack {
     stack: Vec<Scope>,
+
+    /// Whether the global `env` array is linked to the process environment.
+    env_linked: bool,
+
+    /// The number of the last array search started, used to generate search IDs.
+    last_search: usize,
+}
+
+/// The name of the global array variable that can be linked to the process environment.
+const ENV_ARRAY: &str = "env";
+
+/// A copy of the global scope's variables, as saved by `ScopeStack::snapshot_globals`.
+#[derive(Clone)]
+pub(crate) struct GlobalSnapshot {
+    map: VarMap,
+    env_linked: bool,
 }
 
+/// A scope popped from the stack by `ScopeStack::pop_saved`, to be pushed again later.
+#[cfg(feature = "coroutine")]
+#[derive(Default)]
+pub(crate) struct SavedScope(Scope);
+
 impl ScopeStack {
     //-------------------------------------------------------------------------------------
     // Public API
@@ -92,7 +258,11 @@ impl ScopeStack {
     /// Creates a scope stack containing only scope `0`, the global scope.  This is usually
     /// done once, as part of creating an `Interp`.
     pub fn new() -> Self {
-        let mut ss = Self { stack: Vec::new() };
+        let mut ss = Self {
+            stack: Vec::new(),
+            env_linked: false,
+            last_search: 0,
+        };
 
         ss.stack.push(Scope::new());
 
@@ -111,12 +281,23 @@ impl ScopeStack {
 
     /// Requires the value of an array element given its variable name and index.
     pub fn get_elem(&self, name: &str, index: &str) -> Result<Value, Exception> {
+        if self.is_env(self.current(), name) {
+            return match std::env::var(index) {
+                Ok(value) => Ok(Value::from(value)),
+                Err(_) => molt_err!(
+                    "can't read \"{}({})\": no such element in array",
+                    name,
+                    index
+                ),
+            };
+        }
+
         match self.var(self.current(), name) {
             Some(Var::Scalar(_)) => {
                 molt_err!("can't read \"{}({})\": variable isn't array", name, index)
             }
-            Some(Var::Array(map)) => {
-                if let Some(val) = map.get(index) {
+            Some(Var::Array(array)) => {
+                if let Some(val) = array.elements.get(index) {
                     Ok(val.clone())
                 } else {
                     molt_err!(
@@ -136,7 +317,7 @@ impl ScopeStack {
     /// variable.
     pub fn set_global(&mut self, name: &str, val: Value) -> Result<(), Exception> {
         match self.var_mut(0, name) {
-            Some(Var::Upvar(_)) => unreachable!(),
+            Some(Var::Upvar(_, _)) => unreachable!(),
             Some(Var::Array(_)) => molt_err!("can't set \"{}\": variable is array", name),
             Some(var) => {
                 // It was either Var::Scalar or Var::New; either way, replace it with a new
@@ -153,7 +334,7 @@ impl ScopeStack {
     /// variable.
     pub fn set(&mut self, name: &str, val: Value) -> Result<(), Exception> {
         match self.var_mut(self.current(), name) {
-            Some(Var::Upvar(_)) => unreachable!(),
+            Some(Var::Upvar(_, _)) => unreachable!(),
             Some(Var::Array(_)) => molt_err!("can't set \"{}\": variable is array", name),
             Some(var) => {
                 // It was either Var::Scalar or Var::New; either way, replace it with a new
@@ -171,23 +352,27 @@ impl ScopeStack {
     pub fn set_elem(&mut self, name: &str, index: &str, val: Value) -> Result<(), Exception> {
         let top = self.current();
 
+        if self.is_env(top, name) {
+            set_env_var(name, index, &val)?;
+        }
+
         match self.var_mut(top, name) {
-            Some(Var::Upvar(_)) => unreachable!(),
+            Some(Var::Upvar(_, _)) => unreachable!(),
             Some(Var::Scalar(_)) => {
                 molt_err!("can't set \"{}({})\": variable isn't array", name, index)
             }
-            Some(Var::Array(map)) => {
-                // It was already an array; just update the indexed element (which will
-                // create it if it didn't exist).
-                map.insert(index.into(), val);
+            Some(Var::Array(array)) => {
+                // It was already an array; just update the indexed element, creating it
+                // if it didn't exist.
+                array.insert(index, val);
                 Ok(())
             }
             Some(var) => {
                 assert_eq!(*var, Var::New);
                 // Create new variable on the top of the stack.
-                let mut map = HashMap::new();
-                map.insert(index.into(), val);
-                *var = Var::Array(map);
+                let mut array = Array::default();
+                array.insert(index, val);
+                *var = Var::Array(array);
                 Ok(())
             }
             None => unreachable!(),
@@ -200,7 +385,7 @@ impl ScopeStack {
         self.var(self.current(), name).is_some()
     }
 
-    /// Returns true if there's a variable with the given name, of whatever type, and
+    /// Returns true if the named array variable has an element with the given index, and
     /// false otherwise.
     pub fn elem_exists(&self, name: &str, index: &str) -> bool {
         self.get_elem(name, index).is_ok()
@@ -211,29 +396,48 @@ impl ScopeStack {
     /// scope as well.
     ///
     /// Note: it's irrelevant whether the variable is a scalar or array; it's going away.
-    pub fn unset(&mut self, name: &str) {
-        self.unset_at(self.current(), name, false);
+    ///
+    /// Returns true if a variable was removed, and false if there was no such variable.
+    pub fn unset(&mut self, name: &str) -> bool {
+        self.unset_at(self.current(), name, false)
     }
 
     /// Unset a variable at a given level in the stack.  If the variable at that level
     /// is linked to a higher level, follows the chain down, unsetting as it goes.
-    fn unset_at(&mut self, level: usize, name: &str, array_only: bool) {
+    /// Returns true if a scalar or array variable was removed at the end of the chain.
+    fn unset_at(&mut self, level: usize, name: &str, array_only: bool) -> bool {
         // FIRST, if the variable at this level links to a lower level, follow the chain.
-        if let Some(Var::Upvar(at)) = self.stack[level].map.get(name) {
-            // NOTE: Using the variable true_level prevents a "doubly-borrowed" error.
-            // Once Polonius is in use, this should no longer be necessary.
+        let (level, name) = self.resolve(level, name);
+        let name = &*name;
+        let mut removed = false;
+
+        if let Some(Var::Upvar(at, true_name)) = self.stack[level].map.get(name) {
+            // NOTE: Using the variables true_level and true_name prevents a "doubly-borrowed"
+            // error. Once Polonius is in use, this should no longer be necessary.
             let true_level = *at;
-            self.unset_at(true_level, name, array_only);
+            let true_name = true_name.clone();
+            removed = self.unset_at(true_level, &true_name, array_only);
+        }
+
+        // NEXT, unsetting the entire env array breaks its link to the process environment.
+        if level == 0 && name == ENV_ARRAY {
+            self.env_linked = false;
         }
 
         // NEXT, remove the variable at this level.
         if array_only {
             if let Some(Var::Array(_)) = self.stack[level].map.get(name) {
                 self.stack[level].map.remove(name);
+                removed = true;
             }
         } else {
-            self.stack[level].map.remove(name);
+            match self.stack[level].map.remove(name) {
+                Some(Var::Scalar(_)) | Some(Var::Array(_)) => removed = true,
+                _ => (),
+            }
         }
+
+        removed
     }
 
     /// Links a variable in the current scope to variable at the given level, counting
@@ -243,9 +447,18 @@ impl ScopeStack {
     /// does not exist; the variable will be created on the first `set`, if any.  This is
     /// consistent with standard TCL behavior.
     pub fn upvar(&mut self, level: usize, name: &str) {
+        self.upvar_as(level, name, name);
+    }
+
+    /// Links the variable called `local` in the current scope to the variable called `name`
+    /// at the given level, counting from `0`, the global scope.  This is used to link a
+    /// procedure's local variables to namespace variables.
+    pub fn upvar_as(&mut self, level: usize, name: &str, local: &str) {
         assert!(level < self.current(), "Can't upvar to current stack level");
         let top = self.current();
-        self.stack[top].map.insert(name.into(), Var::Upvar(level));
+        self.stack[top]
+            .map
+            .insert(local, Var::Upvar(level, name.into()));
     }
 
     /// Returns the index of the current stack level, counting from 0, the global scope.
@@ -261,6 +474,16 @@ impl ScopeStack {
         self.stack.push(Scope::new());
     }
 
+    /// Pushes a new scope for the body of a `namespace eval`, given the namespace's name
+    /// without its leading "::".  An unqualified name that isn't a local variable refers to
+    /// the namespace's variable of that name, e.g., `y` to `a::y`, unless there's no such
+    /// variable but there's a global variable with the name, as in Standard TCL.
+    pub fn push_namespace(&mut self, namespace: &str) {
+        let mut scope = Scope::new();
+        scope.namespace = Some(namespace.into());
+        self.stack.push(scope);
+    }
+
     /// Pops the current scope from the stack. Panics if we're at the global scope; this implies an
     /// coding error at the Rust level.
     pub fn pop(&mut self) {
@@ -315,16 +538,29 @@ impl ScopeStack {
     /// Gets a list of the array indices for the named array.  Returns the empty list
     /// if `name` doesn't name an array variable.
     pub fn array_indices(&self, name: &str) -> MoltList {
+        if self.is_env(self.current(), name) {
+            return env_vars().map(|(key, _)| Value::from(key)).collect();
+        }
+
         match self.var(self.current(), name) {
-            Some(Var::Array(map)) => map.keys().cloned().map(|x| Value::from(&x)).collect(),
+            Some(Var::Array(array)) => array
+                .elements
+                .keys()
+                .cloned()
+                .map(|x| Value::from(&x))
+                .collect(),
             _ => Vec::new(),
         }
     }
 
     /// Gets the size of the named array.  Returns 0 if `name` doesn't name an array variable.
     pub fn array_size(&self, name: &str) -> usize {
+        if self.is_env(self.current(), name) {
+            return env_vars().count();
+        }
+
         match self.var(self.current(), name) {
-            Some(Var::Array(map)) => map.len(),
+            Some(Var::Array(array)) => array.elements.len(),
             _ => 0,
         }
     }
@@ -332,11 +568,21 @@ impl ScopeStack {
     /// Gets the content of an array as a flat list of names and values.  If the named
     /// variable is not an array, returns the empty list.
     pub fn array_get(&self, name: &str) -> MoltList {
+        if self.is_env(self.current(), name) {
+            let mut list = Vec::new();
+
+            for (key, value) in env_vars() {
+                list.push(Value::from(key));
+                list.push(Value::from(value));
+            }
+            return list;
+        }
+
         match self.var(self.current(), name) {
-            Some(Var::Array(map)) => {
+            Some(Var::Array(array)) => {
                 let mut list = Vec::new();
 
-                for (key, value) in map {
+                for (key, value) in &array.elements {
                     list.push(Value::from(key));
                     list.push(value.clone());
                 }
@@ -348,11 +594,20 @@ impl ScopeStack {
 
     /// Unsets the value of the indexed array element in the current scope, if it exists.
     /// Does nothing if the array element doesn't exist, or the variable isn't an array
-    /// variable.
-    pub fn unset_element(&mut self, name: &str, index: &str) {
-        if let Some(Var::Array(map)) = self.var_mut(self.current(), name) {
-            map.remove(index);
+    /// variable.  Returns true if an element was removed, and false otherwise.
+    pub fn unset_element(&mut self, name: &str, index: &str) -> bool {
+        if self.is_env(self.current(), name) && is_valid_env_name(index) {
+            std::env::remove_var(index);
         }
+
+        // Check first, so as not to create a `Var::New` for a missing variable.
+        if let Some(Var::Array(_)) = self.var(self.current(), name) {
+            if let Some(Var::Array(array)) = self.var_mut(self.current(), name) {
+                return array.remove(index);
+            }
+        }
+
+        false
     }
 
     /// Merges a flat list of keys and values into the array variable, creating the variable
@@ -361,20 +616,26 @@ impl ScopeStack {
         // List must be even.
         assert!(kvlist.len() % 2 == 0);
 
+        if self.is_env(self.current(), name) {
+            for kv in kvlist.chunks(2) {
+                set_env_var(name, kv[0].as_str(), &kv[1])?;
+            }
+        }
+
         match self.var_mut(self.current(), name) {
-            Some(Var::Upvar(_)) => unreachable!(),
+            Some(Var::Upvar(_, _)) => unreachable!(),
             Some(Var::Scalar(_)) => molt_err!("can't array set \"{}\": variable isn't array", name),
-            Some(Var::Array(map)) => {
+            Some(Var::Array(array)) => {
                 // It was already an array; just add the new elements.
-                insert_kvlist(map, &kvlist);
+                insert_kvlist(array, &kvlist);
                 Ok(())
             }
             Some(var) => {
                 assert_eq!(*var, Var::New);
                 // Create new variable on the top of the stack.
-                let mut map = HashMap::new();
-                insert_kvlist(&mut map, &kvlist);
-                *var = Var::Array(map);
+                let mut array = Array::default();
+                insert_kvlist(&mut array, &kvlist);
+                *var = Var::Array(array);
                 Ok(())
             }
             None => unreachable!(),
@@ -390,22 +651,183 @@ impl ScopeStack {
         self.unset_at(self.current(), name, true);
     }
 
+    /// Starts a search of the named array's elements, returning the search's ID.  It's an
+    /// error if the variable isn't an array variable.
+    pub fn array_start_search(&mut self, name: &str) -> Result<String, Exception> {
+        if !self.array_exists(name) {
+            return molt_err!("\"{}\" isn't an array", name);
+        }
+
+        self.last_search += 1;
+        let id = format!("s-{}-{}", self.last_search, name);
+
+        if let Some(Var::Array(array)) = self.var_mut(self.current(), name) {
+            array.searches.push((id.clone(), Some(0)));
+        }
+        Ok(id)
+    }
+
+    /// Returns the next index in the named search of the named array, or `None` if the
+    /// search has returned them all, consuming it if `advance` is true.  It's an error if
+    /// there's no such search, or if the array has changed since the search started.
+    pub fn array_search_next(
+        &mut self,
+        name: &str,
+        id: &str,
+        advance: bool,
+    ) -> Result<Option<Value>, Exception> {
+        let (array, i) = self.array_search(name, id)?;
+
+        let next = match array.searches[i].1 {
+            Some(next) => next,
+            None => return molt_err!("array \"{}\" changed during search \"{}\"", name, id),
+        };
+
+        match array.elements.get_index(next) {
+            Some((index, _)) => {
+                let index = Value::from(index.as_str());
+                if advance {
+                    array.searches[i].1 = Some(next + 1);
+                }
+                Ok(Some(index))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Ends the named search of the named array.  It's an error if there's no such search.
+    pub fn array_done_search(&mut self, name: &str, id: &str) -> Result<(), Exception> {
+        let (array, i) = self.array_search(name, id)?;
+        array.searches.remove(i);
+        Ok(())
+    }
+
+    // Gets the named array and the position of the named search in its list of searches,
+    // or an error if there's no such search of the array.
+    fn array_search(&mut self, name: &str, id: &str) -> Result<(&mut Array, usize), Exception> {
+        let found = match self.var(self.current(), name) {
+            Some(Var::Array(array)) => array.searches.iter().position(|(s, _)| s == id),
+            _ => return molt_err!("\"{}\" isn't an array", name),
+        };
+
+        if let Some(i) = found {
+            if let Some(Var::Array(array)) = self.var_mut(self.current(), name) {
+                return Ok((array, i));
+            }
+        }
+
+        // A search ID names the array it was started on; report a search of another array
+        // as such.
+        match id.strip_prefix("s-").and_then(|rest| rest.split_once('-')) {
+            Some((_, array)) if array != name => molt_err!(
+                "search identifier \"{}\" isn't for variable \"{}\"",
+                id,
+                name
+            ),
+            _ => molt_err!("couldn't find search \"{}\"", id),
+        }
+    }
+
+    /// Links the global `env` array to the process environment.  See the module
+    /// documentation for details.
+    #[cfg(feature = "os")]
+    pub fn link_env(&mut self) {
+        self.env_linked = true;
+    }
+
+    /// Returns true if the global `env` array is linked to the process environment.
+    #[cfg(feature = "os")]
+    pub fn is_env_linked(&self) -> bool {
+        self.env_linked
+    }
+
+    /// Removes all variables from the global scope, and unlinks the `env` array from the
+    /// process environment.  Variables in other scopes are unaffected.
+    pub fn clear_globals(&mut self) {
+        self.stack[0].map = VarMap::default();
+        self.env_linked = false;
+    }
+
+    /// Pops all scopes but the global scope.
+    pub fn pop_to_global(&mut self) {
+        self.stack.truncate(1);
+    }
+
+    /// Pops the current scope from the stack, returning it so that it can be pushed again
+    /// by `push_saved`.  A suspended coroutine keeps its body's scope this way.  Panics if
+    /// we're at the global scope.
+    #[cfg(feature = "coroutine")]
+    pub fn pop_saved(&mut self) -> SavedScope {
+        assert!(self.stack.len() > 1, "Popped global scope!");
+        SavedScope(self.stack.pop().expect("scope stack is empty"))
+    }
+
+    /// Pushes a scope saved by `pop_saved` onto the stack.
+    #[cfg(feature = "coroutine")]
+    pub fn push_saved(&mut self, scope: SavedScope) {
+        self.stack.push(scope.0);
+    }
+
+    /// Saves a copy of the variables in the global scope, for later restoration.
+    pub fn snapshot_globals(&self) -> GlobalSnapshot {
+        GlobalSnapshot {
+            map: self.stack[0].map.clone(),
+            env_linked: self.env_linked,
+        }
+    }
+
+    /// Replaces the variables in the global scope with those in the snapshot.  Variables
+    /// in other scopes are unaffected.
+    pub fn restore_globals(&mut self, snapshot: GlobalSnapshot) {
+        self.stack[0].map = snapshot.map;
+        self.env_linked = snapshot.env_linked;
+    }
+
     //--------------------------------------------------------------
     // Utilities
 
+    /// Returns true if the named variable, as seen from the given level, is the global
+    /// `env` array and the array is linked to the process environment.
+    fn is_env(&self, level: usize, name: &str) -> bool {
+        if !self.env_linked {
+            return false;
+        }
+
+        // Follow the alias chain, if any, to find the level and name at which the variable
+        // lives.
+        let (mut level, mut name) = self.resolve(level, name);
+        while let Some(Var::Upvar(at, true_name)) = self.stack[level].map.get(&name) {
+            let (at, true_name) = self.resolve(*at, true_name);
+            level = at;
+            name = true_name;
+        }
+
+        level == 0 && name == ENV_ARRAY
+    }
+
     /// Retrieves an immutable borrow of the variable of the given name, searching the
     /// the scope stack for the variable starting at the current level and following the
     /// alias chain as needed.
     ///
     /// This call is the basis for all public APIs that retrieve information about a variable.
+    /// A qualified name always refers to a variable in the global scope.
     ///
-    /// TODO: Try using a loop rather than recursion, and see if that's any faster.
+    /// The search never walks the stack: a name is looked up only in the given scope, and
+    /// an alias names its target's level directly.  The aliases created by `global` and
+    /// `variable` refer to the global scope, which can't contain aliases, so reading a
+    /// global from a procedure costs just one more lookup than reading a local.
     fn var(&self, level: usize, name: &str) -> Option<&Var> {
-        let var = self.stack[level].map.get(name);
-        if let Some(Var::Upvar(at)) = var {
-            self.var(*at, name)
-        } else {
-            var
+        let (mut level, mut name) = self.resolve(level, name);
+
+        loop {
+            match self.stack[level].map.get(&name) {
+                Some(Var::Upvar(at, true_name)) => {
+                    let (at, true_name) = self.resolve(*at, true_name);
+                    level = at;
+                    name = true_name;
+                }
+                var => return var,
+            }
         }
     }
 
@@ -415,29 +837,96 @@ impl ScopeStack {
     /// relevant place.  The caller should *always* replace the `Var::New` with either
     /// `Var::Scalar` or `Var::Array`.
     ///
-    /// TODO: Try using a loop rather than recursion, and see if that's any faster.
+    /// The variable's name is only copied if the variable has to be created.
     fn var_mut(&mut self, level: usize, name: &str) -> Option<&mut Var> {
-        let var = self.stack[level].map.entry(name.into()).or_insert(Var::New);
+        let (mut level, mut name) = self.resolve(level, name);
+
+        loop {
+            // NOTE: 11/28/2019.  Without this transmutation, the borrow checker will not allow
+            // the map to be borrowed again when the variable isn't found, or to follow the
+            // alias chain, even though it can be seen that all we are using from the first
+            // borrow is the alias level and name. Under Polonius, a new borrow checker
+            // currently under development, this pattern is allowed, and the unsafe code can
+            // be deleted.  (An alias always refers to a lower level, and so the alias's
+            // name remains valid while that level's map is modified.)
+            let var: Option<&mut Var> =
+                unsafe { ::core::mem::transmute(self.stack[level].map.get_mut(&name)) };
+
+            match var {
+                Some(Var::Upvar(at, true_name)) => {
+                    let (at, true_name) = self.resolve(*at, true_name);
+                    level = at;
+                    name = true_name;
+                }
+                Some(var) => return Some(var),
+                None => {
+                    return Some(self.stack[level].map.insert(&name, Var::New));
+                }
+            }
+        }
+    }
 
-        // NOTE: 11/28/2019.  Without this transmutation, the borrow checker will not allow the
-        // recursive call to var_mut, even though it can be seen that all we are using
-        // from the first borrow is the alias level. Under Polonius, a new borrow checker
-        // currently under development, this pattern is allowed, and the unsafe code can
-        // be deleted.
-        let var: Option<&mut Var> = unsafe { ::core::mem::transmute(var) };
+    // Resolves a variable name as seen from the given level.  A qualified name, i.e., one
+    // containing "::", refers to the global variable with that name less any leading "::".
+    // In the scope of a `namespace eval`, an unqualified name that isn't a local variable
+    // refers to the namespace's variable, or failing that to an existing global; see
+    // `push_namespace`.
+    fn resolve<'a>(&self, level: usize, name: &'a str) -> (usize, Cow<'a, str>) {
+        if name.contains("::") {
+            return (0, Cow::Borrowed(name.trim_start_matches("::")));
+        }
 
-        if let Some(Var::Upvar(at)) = var {
-            self.var_mut(*at, name)
-        } else {
-            var
+        match &self.stack[level].namespace {
+            Some(ns) if self.stack[level].map.get(name).is_none() => {
+                let key = if ns.is_empty() {
+                    name.to_string()
+                } else {
+                    format!("{}::{}", ns, name)
+                };
+
+                if self.stack[0].map.get(&key).is_none() && self.stack[0].map.get(name).is_some() {
+                    (0, Cow::Borrowed(name))
+                } else {
+                    (0, Cow::Owned(key))
+                }
+            }
+            _ => (level, Cow::Borrowed(name)),
         }
     }
 }
 
-// Insert the flat key-value list into the map.
-fn insert_kvlist(map: &mut HashMap<String, Value>, list: &[Value]) {
+/// Returns the process's environment variables, skipping any whose name or value isn't
+/// valid Unicode, as the `env` array can't represent them.
+pub(crate) fn env_vars() -> impl Iterator<Item = (String, String)> {
+    std::env::vars_os()
+        .filter_map(|(key, value)| Some((key.into_string().ok()?, value.into_string().ok()?)))
+}
+
+// Is the string a name that can be set in the process environment?  Setting a
+// variable whose name is empty or contains "=" or NUL would panic.
+fn is_valid_env_name(name: &str) -> bool {
+    !name.is_empty() && !name.contains('=') && !name.contains('\0')
+}
+
+// Sets a variable in the process environment, or returns an error if the name or value
+// is one that the environment can't represent.
+fn set_env_var(name: &str, index: &str, val: &Value) -> Result<(), Exception> {
+    if !is_valid_env_name(index) || val.as_str().contains('\0') {
+        return molt_err!(
+            "can't set \"{}({})\": invalid environment variable",
+            name,
+            index
+        );
+    }
+
+    std::env::set_var(index, val.as_str());
+    Ok(())
+}
+
+// Insert the flat key-value list into the array.
+fn insert_kvlist(array: &mut Array, list: &[Value]) {
     for kv in list.chunks(2) {
-        map.insert(kv[0].as_str().into(), kv[1].clone());
+        array.insert(kv[0].as_str(), kv[1].clone());
     }
 }
 
@@ -452,6 +941,47 @@ mod tests {
         assert_eq!(ss.current(), 0);
     }
 
+    #[test]
+    fn test_var_map() {
+        let mut map = VarMap::default();
+
+        // Small maps use the vector; replacing a variable doesn't add one.
+        for i in 0..SMALL_SCOPE {
+            map.insert(
+                &format!("v{}", i),
+                Var::Scalar(Value::from(i as crate::types::MoltInt)),
+            );
+        }
+        map.insert("v0", Var::Scalar(Value::from("zero")));
+        assert_eq!(map.small.len(), SMALL_SCOPE);
+        assert!(map.large.is_empty());
+        assert_eq!(map.get("v0"), Some(&Var::Scalar(Value::from("zero"))));
+
+        // Adding one more moves the variables to the hash table.
+        map.insert("extra", Var::New);
+        assert!(map.small.is_empty());
+        assert_eq!(map.large.len(), SMALL_SCOPE + 1);
+        assert_eq!(map.get("v0"), Some(&Var::Scalar(Value::from("zero"))));
+        assert_eq!(map.get("v7"), Some(&Var::Scalar(Value::from(7))));
+        assert_eq!(map.keys().count(), SMALL_SCOPE + 1);
+
+        // Removal works either way.
+        assert_eq!(map.remove("extra"), Some(Var::New));
+        assert_eq!(map.remove("extra"), None);
+
+        let mut map = VarMap::default();
+        map.insert("a", Var::New);
+        map.insert("b", Var::New);
+        assert_eq!(map.remove("a"), Some(Var::New));
+        assert_eq!(map.get("a"), None);
+        assert_eq!(map.get("b"), Some(&Var::New));
+        if let Some(var) = map.get_mut("b") {
+            *var = Var::Scalar(Value::from(1));
+        }
+        assert_eq!(map.iter().count(), 1);
+        assert_eq!(map.get("b"), Some(&Var::Scalar(Value::from(1))));
+    }
+
     #[test]
     fn test_set_get_basic() {
         let mut ss = ScopeStack::new();
@@ -517,8 +1047,9 @@ mod tests {
 
         let _ = ss.set("a", Value::from("1"));
         assert!(ss.get("a").is_ok());
-        ss.unset("a");
+        assert!(ss.unset("a"));
         assert!(ss.get("a").is_err());
+        assert!(!ss.unset("a"));
     }
 
     #[test]
@@ -611,6 +1142,43 @@ mod tests {
         assert_eq!(ss.get("b").unwrap().as_str(), "2");
     }
 
+    #[test]
+    fn test_set_get_upvar_as() {
+        let mut ss = ScopeStack::new();
+
+        let _ = ss.set("ns::a", Value::from("1"));
+
+        ss.push();
+        ss.upvar_as(0, "ns::a", "a");
+        assert_eq!(ss.get("a").unwrap().as_str(), "1");
+
+        let _ = ss.set("a", Value::from("2"));
+        assert_eq!(ss.get("ns::a").unwrap().as_str(), "2");
+
+        ss.unset("a");
+        ss.pop();
+        assert!(ss.get("ns::a").is_err());
+    }
+
+    #[test]
+    fn test_set_get_qualified() {
+        let mut ss = ScopeStack::new();
+
+        let _ = ss.set("a", Value::from("1"));
+
+        ss.push();
+        let _ = ss.set("a", Value::from("2"));
+        assert_eq!(ss.get("::a").unwrap().as_str(), "1");
+
+        let _ = ss.set("::b", Value::from("3"));
+        assert!(ss.get("b").is_err());
+        ss.unset("::a");
+
+        ss.pop();
+        assert!(ss.get("a").is_err());
+        assert_eq!(ss.get("b").unwrap().as_str(), "3");
+    }
+
     #[test]
     fn test_unset_levels() {
         let mut ss = ScopeStack::new();
@@ -651,6 +1219,31 @@ mod tests {
         assert!(ss.get("a").is_err());
     }
 
+    #[cfg(feature = "coroutine")]
+    #[test]
+    fn test_saved_scope() {
+        let mut ss = ScopeStack::new();
+        let _ = ss.set("a", Value::from("1"));
+        ss.push();
+        let _ = ss.set("b", Value::from("2"));
+        ss.upvar(0, "a");
+
+        // Popping the scope saves its variables, including its links.
+        let saved = ss.pop_saved();
+        assert_eq!(ss.current(), 0);
+        assert!(ss.get("b").is_err());
+
+        // The scope can be pushed again at a different level.
+        ss.push();
+        ss.push_saved(saved);
+        assert_eq!(ss.current(), 2);
+        assert_eq!(ss.get("b").unwrap().as_str(), "2");
+        let _ = ss.set("a", Value::from("3"));
+        ss.pop();
+        ss.pop();
+        assert_eq!(ss.get("a").unwrap().as_str(), "3");
+    }
+
     #[test]
     fn test_vars_in_scope() {
         let mut ss = ScopeStack::new();
@@ -826,18 +1419,20 @@ mod tests {
         let _ = ss.set_elem("b", "2", "two".into());
 
         // Array unset of an unknown variable has no effect.
-        ss.unset_element("x", "1"); // No error
+        assert!(!ss.unset_element("x", "1")); // No error
+        assert!(!ss.exists("x"));
 
         // Array unset of a scalar has no effect.
-        ss.unset_element("a", "1");
+        assert!(!ss.unset_element("a", "1"));
         let out = ss.get("a");
         assert!(out.is_ok());
         assert_eq!(out.unwrap().as_str(), "zero");
 
         // Array unset of an element unsets just that element.
-        ss.unset_element("b", "1");
+        assert!(ss.unset_element("b", "1"));
         assert!(ss.get_elem("b", "1").is_err());
         assert!(ss.get_elem("b", "2").is_ok());
+        assert!(!ss.unset_element("b", "1"));
     }
 
     #[test]
@@ -883,4 +1478,26 @@ mod tests {
         assert!(ss.exists("b"));
         assert!(ss.elem_exists("b", "1"));
     }
+
+    #[test]
+    fn test_exists_array() {
+        let mut ss = ScopeStack::new();
+        ss.set("a", "1".into()).expect("success");
+        ss.array_set("b", &[]).expect("success");
+
+        // An empty array exists, but has no elements.
+        assert!(ss.exists("b"));
+        assert!(ss.array_exists("b"));
+        assert!(!ss.elem_exists("b", "1"));
+
+        // A scalar has no elements.
+        assert!(!ss.elem_exists("a", "1"));
+
+        // An array continues to exist when its last element is unset.
+        ss.set_elem("c", "1", "2".into()).expect("success");
+        ss.unset_element("c", "1");
+        assert!(ss.exists("c"));
+        assert!(ss.array_exists("c"));
+        assert!(!ss.elem_exists("c", "1"));
+    }
 }
@@ -20,26 +20,92 @@
 //!
 //! If a test fails or returns an error, the test harness outputs the details.
 //!
+//! If the test script defines a proc called `testSetup`, the test harness calls it before
+//! each test; and if it defines a proc called `testTeardown`, the test harness calls it after
+//! each test, whether the test passed or not.  A test fails if `testSetup` throws an error.
+//! The `--setup` *proc* and `--teardown` *proc* options name different hooks.
+//!
+//! A test can be given a list of _constraints_, e.g., `unix` or `knownBug`; it is skipped
+//! unless all of them are satisfied.  The `testConstraint` command defines constraints;
+//! the `unix`, `windows`, and `macos` constraints are defined by default according to the
+//! platform.
+//!
+//! Given one or more `--match` *pattern* options, the test harness executes only the tests
+//! whose names match one of the glob patterns; and given one or more `--skip` *pattern*
+//! options, it doesn't execute the tests whose names match any of them.  The summary
+//! gives the number of tests filtered out in this way.
+//!
+//! Given the `--junit` *file* option, the test harness also writes the results to the
+//! named file in the JUnit XML format expected by continuous integration systems:
+//!
+//! ```bash
+//! $ molt test test/all.tcl --junit results.xml
+//! ```
+//!
+//! Given the `--tap` option, the test harness instead writes the results to standard output
+//! in the [Test Anything Protocol](https://testanything.org) format, as understood by `prove`
+//! and many continuous integration systems.
+//!
+//! Given the `--coverage` option, the test harness records which lines of the test scripts
+//! and the scripts they source are executed, and outputs the number of lines executed in
+//! each file.  Given the `--lcov` *file* option, it also writes the coverage data to the
+//! named file in the `lcov` tracefile format, for use with `genhtml` and similar tools:
+//!
+//! ```bash
+//! $ molt test test/all.tcl --lcov coverage.info
+//! $ genhtml coverage.info -o coverage
+//! ```
+//!
 //! See the Molt Book (or the Molt test suite) for examples of test scripts.
 
 use crate::check_args;
+use crate::coverage::Coverage;
 use crate::molt_ok;
 use crate::types::ContextID;
+use crate::util::glob_match;
 use crate::Interp;
 use crate::MoltResult;
 use crate::ResultCode;
 use crate::Value;
+#[cfg(feature = "os")]
+use std::cell::RefCell;
+use std::collections::HashSet;
 use std::env;
-use std::fs;
+use std::fs::File;
+use std::io;
+use std::io::Write;
+use std::path::Path;
 use std::path::PathBuf;
+#[cfg(feature = "os")]
+use std::rc::Rc;
+use std::time::Duration;
+use std::time::Instant;
 
 /// Executes the Molt test harness, given the command-line arguments,
 /// in the context of the given interpreter.
 ///
 ///
 /// The first element of the `args` array must be the name of the test script
-/// to execute.  The remaining elements are meant to be test harness options,
-/// but are currently ignored.
+/// to execute.  The remaining elements are test harness options:
+///
+/// * `--junit` *file*: Also writes the test results to the *file* in JUnit XML format;
+///   see [`test_harness_junit`](fn.test_harness_junit.html).
+/// * `--tap`: Writes the test results to standard output in TAP format, in place of the
+///   usual output; see [`test_harness_tap`](fn.test_harness_tap.html).
+/// * `--setup` *proc*: Names the proc to call before each test, if it's defined; defaults to
+///   `testSetup`.  The test fails if the proc throws an error.
+/// * `--teardown` *proc*: Names the proc to call after each test, if it's defined; defaults
+///   to `testTeardown`.
+/// * `--match` *pattern*: Executes only the tests whose names match the glob pattern.  May
+///   be repeated, in which case a test is executed if its name matches any of the patterns.
+/// * `--skip` *pattern*: Doesn't execute the tests whose names match the glob pattern.  May
+///   be repeated.
+/// * `--coverage`: Records which script lines are executed, and outputs the number of
+///   executable lines executed in each script file.
+/// * `--lcov` *file*: Implies `--coverage`, and also writes the coverage data to the *file*
+///   in `lcov` tracefile format.
+///
+/// Other options are currently ignored.
 ///
 /// See [`molt::interp`](../molt/interp/index.html) for details on how to configure and
 /// add commands to a Molt interpreter.
@@ -67,8 +133,189 @@ use std::path::PathBuf;
 /// ```
 
 pub fn test_harness(interp: &mut Interp, args: &[String]) -> Result<(), ()> {
+    // FIRST, get the options.
+    let options = HarnessOptions::parse(args)?;
+
+    // NEXT, create the JUnit output file, if requested.  It's created before the tests
+    // are run, as the tests are run in the test script's directory.
+    let mut junit_file = match &options.junit {
+        Some(path) => match File::create(path) {
+            Ok(file) => Some(file),
+            Err(e) => {
+                eprintln!("couldn't create \"{}\": {}", path, e);
+                return Err(());
+            }
+        },
+        None => None,
+    };
+
+    let mut lcov_file = match &options.lcov {
+        Some(path) => match File::create(path) {
+            Ok(file) => Some(file),
+            Err(e) => {
+                eprintln!("couldn't create \"{}\": {}", path, e);
+                return Err(());
+            }
+        },
+        None => None,
+    };
+
+    // NEXT, run the tests, and write the requested output.
+    let ctx = run_tests(interp, args, &options, options.tap)?;
+
+    if let (Some(file), Some(coverage)) = (&mut lcov_file, interp.coverage()) {
+        if let Err(e) = coverage.write_lcov(file) {
+            eprintln!("error writing lcov output: {}", e);
+            return Err(());
+        }
+    }
+
+    if options.tap {
+        if let Err(e) = write_tap(&mut io::stdout(), &ctx) {
+            eprintln!("error writing TAP output: {}", e);
+            return Err(());
+        }
+    }
+
+    if let Some(file) = &mut junit_file {
+        if let Err(e) = write_junit(file, &args[0], &ctx) {
+            eprintln!("error writing JUnit output: {}", e);
+            return Err(());
+        }
+    }
+
+    ctx.status()
+}
+
+/// Executes the Molt test harness, given the command-line arguments, in the context of
+/// the given interpreter, just as [`test_harness`](fn.test_harness.html) does; and then
+/// writes the test results to the `writer` in JUnit XML format.
+///
+/// The output is a single `<testsuite>` element named for the test script, containing a
+/// `<testcase>` element for each test.  A test's `classname` is the name of the script
+/// file that defines it, less the `.tcl` extension, and its `time` is its run time in
+/// seconds.  A test that fails has a `<failure>` sub-element; a test that returns an
+/// unexpected result code or is malformed has an `<error>` sub-element.
+///
+/// Returns `Err(())` if the test script couldn't be executed or any test failed.
+#[allow(clippy::result_unit_err)]
+pub fn test_harness_junit(
+    interp: &mut Interp,
+    args: &[String],
+    writer: &mut dyn Write,
+) -> Result<(), ()> {
+    let options = HarnessOptions::parse(args)?;
+    let ctx = run_tests(interp, args, &options, false)?;
+
+    if let Err(e) = write_junit(writer, &args[0], &ctx) {
+        eprintln!("error writing JUnit output: {}", e);
+        return Err(());
+    }
+
+    ctx.status()
+}
+
+/// Executes the Molt test harness, given the command-line arguments, in the context of
+/// the given interpreter, as [`test_harness`](fn.test_harness.html) does; but instead of
+/// the usual output, writes the test results to the `writer` in TAP (Test Anything
+/// Protocol) format once all tests have run.
+///
+/// The output begins with the plan line, `1..`*N*, followed by an `ok` or `not ok` line
+/// for each test, giving its number, name, and description.  Each `not ok` line is
+/// followed by diagnostic comments beginning with `# FAILED` or `# ERROR` that explain
+/// what went wrong.
+///
+/// Returns `Err(())` if the test script couldn't be executed or any test failed.
+#[allow(clippy::result_unit_err)]
+pub fn test_harness_tap(
+    interp: &mut Interp,
+    args: &[String],
+    writer: &mut dyn Write,
+) -> Result<(), ()> {
+    let options = HarnessOptions::parse(args)?;
+    let ctx = run_tests(interp, args, &options, true)?;
+
+    if let Err(e) = write_tap(writer, &ctx) {
+        eprintln!("error writing TAP output: {}", e);
+        return Err(());
+    }
+
+    ctx.status()
+}
+
+/// The test harness options, as parsed from the command-line arguments.
+struct HarnessOptions {
+    junit: Option<String>,
+    tap: bool,
+    setup_hook: String,
+    teardown_hook: String,
+    match_patterns: Vec<String>,
+    skip_patterns: Vec<String>,
+    coverage: bool,
+    lcov: Option<String>,
+}
+
+impl HarnessOptions {
+    /// Parses the options following the test script name in the `args`.  Unknown options
+    /// are ignored.
+    fn parse(args: &[String]) -> Result<Self, ()> {
+        let mut options = Self {
+            junit: None,
+            tap: false,
+            setup_hook: DEFAULT_SETUP_HOOK.into(),
+            teardown_hook: DEFAULT_TEARDOWN_HOOK.into(),
+            match_patterns: Vec::new(),
+            skip_patterns: Vec::new(),
+            coverage: false,
+            lcov: None,
+        };
+        let mut opts = args.iter().skip(1);
+
+        while let Some(opt) = opts.next() {
+            match opt.as_str() {
+                "--junit" => options.junit = Some(option_value(opt, opts.next())?),
+                "--setup" => options.setup_hook = option_value(opt, opts.next())?,
+                "--teardown" => options.teardown_hook = option_value(opt, opts.next())?,
+                "--match" => options.match_patterns.push(option_value(opt, opts.next())?),
+                "--skip" => options.skip_patterns.push(option_value(opt, opts.next())?),
+                "--tap" => options.tap = true,
+                "--coverage" => options.coverage = true,
+                "--lcov" => {
+                    options.lcov = Some(option_value(opt, opts.next())?);
+                    options.coverage = true;
+                }
+                _ => (),
+            }
+        }
+
+        Ok(options)
+    }
+}
+
+/// Returns the value of a harness option, or outputs an error if it is missing.
+fn option_value(opt: &str, value: Option<&String>) -> Result<String, ()> {
+    match value {
+        Some(value) => Ok(value.clone()),
+        None => {
+            eprintln!("missing value for {}", opt);
+            Err(())
+        }
+    }
+}
+
+/// Executes the test script named by `args[0]`, and outputs the test results unless
+/// `quiet` is set.  Returns the test results, or `Err(())` if the script couldn't be
+/// executed.
+fn run_tests(
+    interp: &mut Interp,
+    args: &[String],
+    options: &HarnessOptions,
+    quiet: bool,
+) -> Result<TestContext, ()> {
     // FIRST, announce who we are.
-    println!("Molt {} -- Test Harness", env!("CARGO_PKG_VERSION"));
+    if !quiet {
+        println!("Molt {} -- Test Harness", env!("CARGO_PKG_VERSION"));
+    }
 
     // NEXT, get the script file name
     if args.is_empty() {
@@ -79,64 +326,346 @@ pub fn test_harness(interp: &mut Interp, args: &[String]) -> Result<(), ()> {
     let path = PathBuf::from(&args[0]);
 
     // NEXT, initialize the test result.
-    let context_id = interp.save_context(TestContext::new());
+    let mut ctx = TestContext::new(quiet);
+    ctx.setup_hook = options.setup_hook.clone();
+    ctx.teardown_hook = options.teardown_hook.clone();
+    ctx.match_patterns = options.match_patterns.clone();
+    ctx.skip_patterns = options.skip_patterns.clone();
+    let context_id = interp.save_context(ctx);
 
     // NEXT, install the test commands into the interpreter.
     interp.add_context_command("test", test_cmd, context_id);
+    interp.add_context_command("testConstraint", test_constraint_cmd, context_id);
 
-    // NEXT, execute the script.
-    match fs::read_to_string(&args[0]) {
-        Ok(script) => {
-            if let Some(parent) = path.parent() {
-                let _ = env::set_current_dir(parent);
-            }
+    if options.coverage {
+        interp.set_coverage(true);
+    }
 
-            if let Err(exception) = interp.eval(&script) {
-                if exception.code() == ResultCode::Error {
-                    eprintln!("{}", exception.value());
-                    return Err(());
-                } else {
-                    eprintln!("Unexpected eval return: {:?}", exception);
-                    return Err(());
-                }
-            }
+    // NEXT, execute the script in its own directory, so that it can source other test
+    // scripts using relative paths.
+    let start = Instant::now();
+    let mut script = path.as_path();
+
+    if let (Some(parent), Some(name)) = (path.parent(), path.file_name()) {
+        if !parent.as_os_str().is_empty() && env::set_current_dir(parent).is_ok() {
+            script = Path::new(name);
         }
-        Err(e) => {
-            println!("{}", e);
-            return Err(());
+    }
+
+    if let Err(exception) = interp.eval_file(script) {
+        if exception.code() == ResultCode::Error {
+            eprintln!("{}", exception.value());
+        } else {
+            eprintln!("Unexpected eval return: {:?}", exception);
         }
+        return Err(());
     }
 
     // NEXT, output the test results:
     let ctx = interp.context::<TestContext>(context_id);
-    println!(
-        "\n{} tests, {} passed, {} failed, {} errors",
-        ctx.num_tests, ctx.num_passed, ctx.num_failed, ctx.num_errors
+    ctx.time = start.elapsed();
+    if !quiet {
+        print!(
+            "\n{} tests, {} passed, {} failed, {} errors",
+            ctx.num_tests, ctx.num_passed, ctx.num_failed, ctx.num_errors
+        );
+        if ctx.num_skipped > 0 {
+            print!(", {} skipped", ctx.num_skipped);
+        }
+        if ctx.num_filtered > 0 {
+            print!(", {} filtered out", ctx.num_filtered);
+        }
+        println!();
+    }
+
+    let ctx = std::mem::replace(ctx, TestContext::new(quiet));
+
+    if let (false, Some(coverage)) = (quiet, interp.coverage()) {
+        write_coverage(&mut io::stdout(), coverage);
+    }
+
+    Ok(ctx)
+}
+
+/// Writes the coverage summary: the number of executable lines executed in each file,
+/// and in total.
+fn write_coverage(writer: &mut dyn Write, coverage: &Coverage) {
+    let width = coverage
+        .files()
+        .iter()
+        .map(|file| file.name().len())
+        .chain(Some("Total".len()))
+        .max()
+        .unwrap_or(0);
+    let percent = |hit: usize, found: usize| {
+        if found == 0 {
+            100.0
+        } else {
+            100.0 * hit as f64 / found as f64
+        }
+    };
+
+    let _ = writeln!(writer, "\nCoverage:");
+    for file in coverage.files() {
+        let (hit, found) = (file.lines_hit(), file.lines_found());
+        let _ = writeln!(
+            writer,
+            "  {:<width$}  {:>5}/{:<5} lines  {:5.1}%",
+            file.name(),
+            hit,
+            found,
+            percent(hit, found),
+            width = width
+        );
+    }
+
+    let (hit, found) = (coverage.lines_hit(), coverage.lines_found());
+    let _ = writeln!(
+        writer,
+        "  {:<width$}  {:>5}/{:<5} lines  {:5.1}%",
+        "Total",
+        hit,
+        found,
+        percent(hit, found),
+        width = width
     );
+}
 
-    if ctx.num_failed + ctx.num_errors == 0 {
-        Ok(())
-    } else {
-        Err(())
+/// Writes the test results to the writer in TAP format.
+fn write_tap(writer: &mut dyn Write, ctx: &TestContext) -> io::Result<()> {
+    writeln!(writer, "1..{}", ctx.results.len())?;
+
+    for (i, result) in ctx.results.iter().enumerate() {
+        // A "#" would begin a TAP directive or comment.
+        let name = format!("{} {}", result.name, result.description).replace('#', "\\#");
+
+        let label = match result.outcome {
+            Outcome::Passed => {
+                writeln!(writer, "ok {} - {}", i + 1, name)?;
+                continue;
+            }
+            Outcome::Skipped => {
+                writeln!(writer, "ok {} - {} # SKIP {}", i + 1, name, result.message)?;
+                continue;
+            }
+            Outcome::Failed => "FAILED",
+            Outcome::Error => "ERROR",
+        };
+
+        writeln!(writer, "not ok {} - {}", i + 1, name)?;
+        for (j, line) in result.message.lines().enumerate() {
+            if j == 0 {
+                writeln!(writer, "# {} {}", label, line)?;
+            } else {
+                writeln!(writer, "# {}", line)?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Writes the test results to the writer in JUnit XML format.
+fn write_junit(writer: &mut dyn Write, suite: &str, ctx: &TestContext) -> io::Result<()> {
+    writeln!(writer, "<?xml version=\"1.0\" encoding=\"UTF-8\"?>")?;
+    writeln!(
+        writer,
+        "<testsuite name=\"{}\" tests=\"{}\" failures=\"{}\" errors=\"{}\" skipped=\"{}\" \
+         time=\"{:.3}\">",
+        xml_escape(suite),
+        ctx.results.len(),
+        ctx.num_failed,
+        ctx.num_errors,
+        ctx.num_skipped,
+        ctx.time.as_secs_f64()
+    )?;
+
+    for result in &ctx.results {
+        write!(
+            writer,
+            "  <testcase classname=\"{}\" name=\"{}\" time=\"{:.3}\"",
+            xml_escape(&result.classname),
+            xml_escape(&format!("{} {}", result.name, result.description)),
+            result.time.as_secs_f64()
+        )?;
+
+        let tag = match result.outcome {
+            Outcome::Passed => {
+                writeln!(writer, "/>")?;
+                continue;
+            }
+            Outcome::Skipped => {
+                writeln!(writer, ">")?;
+                writeln!(
+                    writer,
+                    "    <skipped message=\"{}\"/>",
+                    xml_escape(&result.message)
+                )?;
+                writeln!(writer, "  </testcase>")?;
+                continue;
+            }
+            Outcome::Failed => "failure",
+            Outcome::Error => "error",
+        };
+
+        let message = result.message.lines().next().unwrap_or("");
+        writeln!(writer, ">")?;
+        writeln!(
+            writer,
+            "    <{} message=\"{}\">{}</{}>",
+            tag,
+            xml_escape(message),
+            xml_escape(&result.message),
+            tag
+        )?;
+        writeln!(writer, "  </testcase>")?;
+    }
+
+    writeln!(writer, "</testsuite>")
+}
+
+/// Escapes the XML special characters in the text, so that it can be used in attribute
+/// values and element content.
+fn xml_escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+
+    for ch in text.chars() {
+        match ch {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            '\n' => escaped.push_str("&#10;"),
+            _ => escaped.push(ch),
+        }
     }
+
+    escaped
 }
 
+/// The default name of the proc to call before each test.
+const DEFAULT_SETUP_HOOK: &str = "testSetup";
+
+/// The default name of the proc to call after each test.
+const DEFAULT_TEARDOWN_HOOK: &str = "testTeardown";
+
 struct TestContext {
     num_tests: usize,
     num_passed: usize,
     num_failed: usize,
     num_errors: usize,
+    num_skipped: usize,
+
+    // The number of tests not executed because of `--match` or `--skip`.
+    num_filtered: usize,
+
+    // The satisfied test constraints.
+    constraint_set: HashSet<String>,
+
+    // The results of the individual tests, in order, including malformed tests.
+    results: Vec<TestResult>,
+
+    // The time taken to run the test script.
+    time: Duration,
+
+    // Whether to suppress the usual output, as for TAP.
+    quiet: bool,
+
+    // The names of the procs to call before and after each test, if they are defined.
+    setup_hook: String,
+    teardown_hook: String,
+
+    // The glob patterns given by `--match` and `--skip`.
+    match_patterns: Vec<String>,
+    skip_patterns: Vec<String>,
 }
 
 impl TestContext {
-    fn new() -> Self {
+    fn new(quiet: bool) -> Self {
         Self {
             num_tests: 0,
             num_passed: 0,
             num_failed: 0,
             num_errors: 0,
+            num_skipped: 0,
+            num_filtered: 0,
+            constraint_set: default_constraints(),
+            results: Vec::new(),
+            time: Duration::default(),
+            quiet,
+            setup_hook: DEFAULT_SETUP_HOOK.into(),
+            teardown_hook: DEFAULT_TEARDOWN_HOOK.into(),
+            match_patterns: Vec::new(),
+            skip_patterns: Vec::new(),
+        }
+    }
+
+    /// Returns true if the named test should be executed, given the `--match` and `--skip`
+    /// patterns.
+    fn is_selected(&self, name: &str) -> bool {
+        (self.match_patterns.is_empty()
+            || self.match_patterns.iter().any(|pat| glob_match(pat, name)))
+            && !self.skip_patterns.iter().any(|pat| glob_match(pat, name))
+    }
+
+    /// Returns `Ok(())` if all tests passed, and `Err(())` otherwise.
+    fn status(&self) -> Result<(), ()> {
+        if self.num_failed + self.num_errors == 0 {
+            Ok(())
+        } else {
+            Err(())
         }
     }
+
+    /// Saves the result of a test.
+    fn save(&mut self, info: &TestInfo, outcome: Outcome, message: String, time: Duration) {
+        self.results.push(TestResult {
+            name: info.name.clone(),
+            description: info.description.clone(),
+            classname: info.classname.clone(),
+            outcome,
+            message,
+            time,
+        });
+    }
+}
+
+/// Returns the set of constraints satisfied by default, i.e., those for the platform.
+fn default_constraints() -> HashSet<String> {
+    let mut constraints = HashSet::new();
+
+    if cfg!(unix) {
+        constraints.insert("unix".to_string());
+    }
+    if cfg!(windows) {
+        constraints.insert("windows".to_string());
+    }
+    if cfg!(target_os = "macos") {
+        constraints.insert("macos".to_string());
+    }
+
+    constraints
+}
+
+/// The outcome of a single test.
+#[derive(Eq, PartialEq, Debug, Clone, Copy)]
+enum Outcome {
+    Passed,
+    Failed,
+    Error,
+    Skipped,
+}
+
+/// The result of a single test, for output in JUnit format.
+#[derive(Debug)]
+struct TestResult {
+    name: String,
+    description: String,
+    classname: String,
+    outcome: Outcome,
+    message: String,
+    time: Duration,
 }
 
 #[derive(Eq, PartialEq, Debug)]
@@ -158,54 +687,117 @@ impl std::fmt::Display for Code {
 struct TestInfo {
     name: String,
     description: String,
+    classname: String,
     setup: String,
     body: String,
     cleanup: String,
+    constraints: Vec<String>,
     code: Code,
     expect: String,
+    output: Option<String>,
+    quiet: bool,
 }
 
 impl TestInfo {
-    fn new(name: &str, description: &str) -> Self {
+    fn new(interp: &mut Interp, context_id: ContextID, name: &str, description: &str) -> Self {
+        // The class name is the name of the script file that defines the test.
+        let classname = interp
+            .script_file()
+            .and_then(|file| Path::new(file).file_stem())
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_else(|| "molt".into());
+        let quiet = interp.context::<TestContext>(context_id).quiet;
+
         Self {
             name: name.into(),
             description: description.into(),
+            classname,
             setup: String::new(),
             body: String::new(),
             cleanup: String::new(),
+            constraints: Vec::new(),
             code: Code::Ok,
             expect: String::new(),
+            output: None,
+            quiet,
         }
     }
 
-    fn print_failure(&self, got_code: &str, received: &str) {
-        println!("\n*** FAILED {} {}", self.name, self.description);
-        println!("Expected {} <{}>", self.code.to_string(), self.expect);
-        println!("Received {} <{}>", got_code, received);
+    fn print_failure(&self, got_code: &str, received: &str) -> String {
+        let message = format!(
+            "Expected {} <{}>\nReceived {} <{}>",
+            self.code, self.expect, got_code, received
+        );
+
+        if !self.quiet {
+            println!("\n*** FAILED {} {}", self.name, self.description);
+            println!("{}", message);
+        }
+        message
     }
 
-    fn print_error(&self, result: &MoltResult) {
-        println!("\n*** ERROR {} {}", self.name, self.description);
-        println!("Expected {} <{}>", self.code.to_string(), self.expect);
+    fn print_output_failure(&self, received: Option<&str>) -> String {
+        let expect = self.output.as_deref().unwrap_or_default();
+        let message = match received {
+            Some(received) => format!(
+                "Expected -output <{}>\nReceived -output <{}>",
+                expect, received
+            ),
+            None => format!(
+                "Expected -output <{}>\nReceived no output: stdout can't be captured",
+                expect
+            ),
+        };
 
-        match result {
-            Ok(val) => println!("Received -ok <{}>", val),
+        if !self.quiet {
+            println!("\n*** FAILED {} {}", self.name, self.description);
+            println!("{}", message);
+        }
+        message
+    }
+
+    fn print_error(&self, result: &MoltResult) -> String {
+        let received = match result {
+            Ok(val) => format!("Received -ok <{}>", val),
             Err(exception) => match exception.code() {
-                ResultCode::Error => println!("Received -error <{}>", exception.value()),
-                ResultCode::Return => println!("Received -return <{}>", exception.value()),
-                ResultCode::Break => println!("Received -break <>"),
-                ResultCode::Continue => println!("Received -continue <>"),
-                _ => unimplemented!(),
+                ResultCode::Error => format!("Received -error <{}>", exception.value()),
+                ResultCode::Return => format!("Received -return <{}>", exception.value()),
+                ResultCode::Break => "Received -break <>".to_string(),
+                ResultCode::Continue => "Received -continue <>".to_string(),
+                ResultCode::Other(code) => {
+                    format!("Received -code {} <{}>", code, exception.value())
+                }
+                ResultCode::Okay => unreachable!(),
             },
+        };
+        let message = format!("Expected {} <{}>\n{}", self.code, self.expect, received);
+
+        if !self.quiet {
+            println!("\n*** ERROR {} {}", self.name, self.description);
+            println!("{}", message);
         }
+        message
     }
 
-    fn print_helper_error(&self, part: &str, msg: &str) {
-        println!(
-            "\n*** ERROR (in {}) {} {}",
-            part, self.name, self.description
-        );
-        println!("    {}", msg);
+    fn print_hook_failure(&self, hook: &str, msg: &str) -> String {
+        let message = format!("Error in {}: {}", hook, msg);
+
+        if !self.quiet {
+            println!("\n*** FAILED {} {}", self.name, self.description);
+            println!("{}", message);
+        }
+        message
+    }
+
+    fn print_helper_error(&self, part: &str, msg: &str) -> String {
+        if !self.quiet {
+            println!(
+                "\n*** ERROR (in {}) {} {}",
+                part, self.name, self.description
+            );
+            println!("    {}", msg);
+        }
+        format!("Error in {}: {}", part, msg)
     }
 }
 
@@ -221,6 +813,13 @@ fn test_cmd(interp: &mut Interp, context_id: ContextID, argv: &[Value]) -> MoltR
     // FIRST, check the minimum command line.
     check_args(1, argv, 4, 0, "name description args...")?;
 
+    // NEXT, skip the test if it's filtered out by `--match` or `--skip`.
+    let ctx = interp.context::<TestContext>(context_id);
+    if !ctx.is_selected(argv[1].as_str()) {
+        ctx.num_filtered += 1;
+        return molt_ok!();
+    }
+
     // NEXT, see which kind of command it is.
     let arg = argv[3].as_str();
     if arg.starts_with('-') {
@@ -235,7 +834,7 @@ fn simple_test(interp: &mut Interp, context_id: ContextID, argv: &[Value]) -> Mo
     check_args(1, argv, 6, 6, "name description script -ok|-error result")?;
 
     // FIRST, get the test info
-    let mut info = TestInfo::new(argv[1].as_str(), argv[2].as_str());
+    let mut info = TestInfo::new(interp, context_id, argv[1].as_str(), argv[2].as_str());
     info.body = argv[3].to_string();
     info.expect = argv[5].to_string();
 
@@ -246,8 +845,8 @@ fn simple_test(interp: &mut Interp, context_id: ContextID, argv: &[Value]) -> Mo
     } else if code == "-error" {
         Code::Error
     } else {
-        incr_errors(interp, context_id);
-        info.print_helper_error("test command", &format!("invalid option: \"{}\"", code));
+        let msg = info.print_helper_error("test command", &format!("invalid option: \"{}\"", code));
+        incr_errors(interp, context_id, &info, msg);
 
         return molt_ok!();
     };
@@ -268,7 +867,7 @@ fn fancy_test(interp: &mut Interp, context_id: ContextID, argv: &[Value]) -> Mol
     )?;
 
     // FIRST, get the test tinfo
-    let mut info = TestInfo::new(argv[1].as_str(), argv[2].as_str());
+    let mut info = TestInfo::new(interp, context_id, argv[1].as_str(), argv[2].as_str());
     let mut iter = argv[3..].iter();
     loop {
         let opt = iter.next();
@@ -279,8 +878,9 @@ fn fancy_test(interp: &mut Interp, context_id: ContextID, argv: &[Value]) -> Mol
 
         let val = iter.next();
         if val.is_none() {
-            incr_errors(interp, context_id);
-            info.print_helper_error("test command", &format!("missing value for {}", opt));
+            let msg =
+                info.print_helper_error("test command", &format!("missing value for {}", opt));
+            incr_errors(interp, context_id, &info, msg);
             return molt_ok!();
         }
         let val = val.unwrap().as_str();
@@ -289,6 +889,14 @@ fn fancy_test(interp: &mut Interp, context_id: ContextID, argv: &[Value]) -> Mol
             "-setup" => info.setup = val.to_string(),
             "-body" => info.body = val.to_string(),
             "-cleanup" => info.cleanup = val.to_string(),
+            "-constraints" => match Value::from(val).as_list() {
+                Ok(list) => info.constraints = list.iter().map(|c| c.to_string()).collect(),
+                Err(exception) => {
+                    let msg = info.print_helper_error("-constraints", exception.value().as_str());
+                    incr_errors(interp, context_id, &info, msg);
+                    return molt_ok!();
+                }
+            },
             "-ok" => {
                 info.code = Code::Ok;
                 info.expect = val.to_string();
@@ -297,9 +905,11 @@ fn fancy_test(interp: &mut Interp, context_id: ContextID, argv: &[Value]) -> Mol
                 info.code = Code::Error;
                 info.expect = val.to_string();
             }
+            "-output" => info.output = Some(val.to_string()),
             _ => {
-                incr_errors(interp, context_id);
-                info.print_helper_error("test command", &format!("invalid option: \"{}\"", val));
+                let msg = info
+                    .print_helper_error("test command", &format!("invalid option: \"{}\"", val));
+                incr_errors(interp, context_id, &info, msg);
                 return molt_ok!();
             }
         }
@@ -310,9 +920,70 @@ fn fancy_test(interp: &mut Interp, context_id: ContextID, argv: &[Value]) -> Mol
     molt_ok!()
 }
 
+/// # testConstraint *name* ?*value*?
+///
+/// Sets the named test constraint to be satisfied or not, if the boolean *value* is given,
+/// and returns 1 if the constraint is satisfied and 0 otherwise.
+///
+/// Note: See the Molt Book for the full syntax.
+fn test_constraint_cmd(interp: &mut Interp, context_id: ContextID, argv: &[Value]) -> MoltResult {
+    check_args(1, argv, 2, 3, "name ?value?")?;
+
+    let name = argv[1].as_str();
+    let flag = match argv.get(2) {
+        Some(value) => Some(value.as_bool()?),
+        None => None,
+    };
+
+    let constraints = &mut interp.context::<TestContext>(context_id).constraint_set;
+    match flag {
+        Some(true) => {
+            constraints.insert(name.to_string());
+        }
+        Some(false) => {
+            constraints.remove(name);
+        }
+        None => (),
+    }
+
+    molt_ok!(constraints.contains(name))
+}
+
 // Run the actual test and save the result.
 fn run_test(interp: &mut Interp, context_id: ContextID, info: &TestInfo) {
-    // FIRST, push a variable scope; -setup, -body, and -cleanup will share it.
+    // FIRST, skip the test if any of its constraints isn't satisfied.
+    let ctx = interp.context::<TestContext>(context_id);
+    let unsatisfied = info
+        .constraints
+        .iter()
+        .find(|constraint| !ctx.constraint_set.contains(*constraint));
+
+    if let Some(constraint) = unsatisfied {
+        let msg = format!("unsatisfied constraint: {}", constraint);
+        ctx.num_tests += 1;
+        ctx.num_skipped += 1;
+        ctx.save(info, Outcome::Skipped, msg, Duration::default());
+        return;
+    }
+
+    let setup_hook = ctx.setup_hook.clone();
+    let teardown_hook = ctx.teardown_hook.clone();
+    let start = Instant::now();
+
+    // NEXT, call the setup hook; if it fails, the test fails.  The teardown hook is
+    // called regardless.
+    if let Err(exception) = call_hook(interp, &setup_hook) {
+        call_teardown_hook(interp, info, &teardown_hook);
+
+        let ctx = interp.context::<TestContext>(context_id);
+        ctx.num_tests += 1;
+        ctx.num_failed += 1;
+        let msg = info.print_hook_failure(&setup_hook, exception.value().as_str());
+        ctx.save(info, Outcome::Failed, msg, start.elapsed());
+        return;
+    }
+
+    // NEXT, push a variable scope; -setup, -body, and -cleanup will share it.
     interp.push_scope();
 
     // NEXT, execute the parts of the test.
@@ -327,9 +998,13 @@ fn run_test(interp: &mut Interp, context_id: ContextID, info: &TestInfo) {
     //     info.print_helper_error("-setup", &msg.to_string());
     // }
 
-    // Body
+    // Body, capturing its output if the test expects some.
     let body = Value::from(&info.body);
-    let result = interp.eval_value(&body);
+    let (result, output) = if info.output.is_some() {
+        eval_capturing_stdout(interp, &body)
+    } else {
+        (interp.eval_value(&body), None)
+    };
 
     // Cleanup
     if let Err(exception) = interp.eval(&info.cleanup) {
@@ -341,8 +1016,10 @@ fn run_test(interp: &mut Interp, context_id: ContextID, info: &TestInfo) {
     //     info.print_helper_error("-cleanup", &msg.to_string());
     // }
 
-    // NEXT, pop the scope.
+    // NEXT, pop the scope, and call the teardown hook.
     interp.pop_scope();
+    call_teardown_hook(interp, info, &teardown_hook);
+    let time = start.elapsed();
 
     // NEXT, get the context and save the results.
     let ctx = interp.context::<TestContext>(context_id);
@@ -352,10 +1029,11 @@ fn run_test(interp: &mut Interp, context_id: ContextID, info: &TestInfo) {
         Ok(out) => {
             if info.code == Code::Ok {
                 if *out == Value::from(&info.expect) {
-                    ctx.num_passed += 1;
+                    save_passed(ctx, info, output.as_deref(), time);
                 } else {
                     ctx.num_failed += 1;
-                    info.print_failure("-ok", &out.to_string());
+                    let msg = info.print_failure("-ok", &out.to_string());
+                    ctx.save(info, Outcome::Failed, msg, time);
                 }
                 return;
             }
@@ -363,20 +1041,278 @@ fn run_test(interp: &mut Interp, context_id: ContextID, info: &TestInfo) {
         Err(exception) => {
             if info.code == Code::Error {
                 if exception.value() == Value::from(&info.expect) {
-                    ctx.num_passed += 1;
+                    save_passed(ctx, info, output.as_deref(), time);
                 } else {
                     ctx.num_failed += 1;
-                    info.print_failure("-error", exception.value().as_str());
+                    let msg = info.print_failure("-error", exception.value().as_str());
+                    ctx.save(info, Outcome::Failed, msg, time);
                 }
                 return;
             }
         }
     }
     ctx.num_errors += 1;
-    info.print_error(&result);
+    let msg = info.print_error(&result);
+    ctx.save(info, Outcome::Error, msg, time);
 }
 
-// Increment the failure counter.
-fn incr_errors(interp: &mut Interp, context_id: ContextID) {
-    interp.context::<TestContext>(context_id).num_errors += 1;
+// Saves the result of a test whose body returned the expected result: it passes unless
+// it expects output, and the body's output doesn't match.
+fn save_passed(ctx: &mut TestContext, info: &TestInfo, output: Option<&str>, time: Duration) {
+    match &info.output {
+        Some(expect) if output != Some(expect.as_str()) => {
+            ctx.num_failed += 1;
+            let msg = info.print_output_failure(output);
+            ctx.save(info, Outcome::Failed, msg, time);
+        }
+        _ => {
+            ctx.num_passed += 1;
+            ctx.save(info, Outcome::Passed, String::new(), time);
+        }
+    }
+}
+
+// Evaluates the script, returning its result and what it wrote to the `stdout` channel,
+// or None if `stdout` can't be captured.
+#[cfg(feature = "os")]
+fn eval_capturing_stdout(interp: &mut Interp, script: &Value) -> (MoltResult, Option<String>) {
+    let buffer = OutputBuffer::default();
+    match interp.replace_stdout(Box::new(buffer.clone())) {
+        Some(stdout) => {
+            let result = interp.eval_value(script);
+            interp.replace_stdout(stdout);
+            let output = String::from_utf8_lossy(&buffer.0.borrow()).into_owned();
+            (result, Some(output))
+        }
+        None => (interp.eval_value(script), None),
+    }
+}
+
+// Without the `os` feature there's no `stdout` channel to capture.
+#[cfg(not(feature = "os"))]
+fn eval_capturing_stdout(interp: &mut Interp, script: &Value) -> (MoltResult, Option<String>) {
+    (interp.eval_value(script), None)
+}
+
+/// A stream that saves the bytes written to it, for capturing a test's output.
+#[cfg(feature = "os")]
+#[derive(Clone, Default)]
+struct OutputBuffer(Rc<RefCell<Vec<u8>>>);
+
+#[cfg(feature = "os")]
+impl Write for OutputBuffer {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.borrow_mut().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+// Calls the named hook proc, if it's defined.
+fn call_hook(interp: &mut Interp, hook: &str) -> MoltResult {
+    if hook.is_empty() || !interp.has_command(hook) {
+        return molt_ok!();
+    }
+
+    interp.eval_value(&Value::from(&[Value::from(hook)][..]))
+}
+
+// Calls the teardown hook, reporting any error.
+fn call_teardown_hook(interp: &mut Interp, info: &TestInfo, hook: &str) {
+    if let Err(exception) = call_hook(interp, hook) {
+        if exception.code() == ResultCode::Error {
+            info.print_helper_error(hook, exception.value().as_str());
+        }
+    }
+}
+
+// Increment the failure counter, saving the error for the malformed test.
+fn incr_errors(interp: &mut Interp, context_id: ContextID, info: &TestInfo, msg: String) {
+    let ctx = interp.context::<TestContext>(context_id);
+    ctx.num_errors += 1;
+    ctx.save(info, Outcome::Error, msg, Duration::default());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_xml_escape() {
+        assert_eq!(xml_escape("plain"), "plain");
+        assert_eq!(
+            xml_escape("<a href=\"x\">'b' & c</a>\n"),
+            "&lt;a href=&quot;x&quot;&gt;&apos;b&apos; &amp; c&lt;/a&gt;&#10;"
+        );
+    }
+
+    // Returns test results for four tests: one passed, one failed, one error, and one
+    // skipped.
+    fn sample_results() -> TestContext {
+        let mut interp = Interp::empty();
+        let context_id = interp.save_context(TestContext::new(true));
+        let mut ctx = TestContext::new(true);
+        ctx.num_tests = 4;
+        ctx.num_passed = 1;
+        ctx.num_failed = 1;
+        ctx.num_errors = 1;
+        ctx.num_skipped = 1;
+
+        let info = TestInfo::new(&mut interp, context_id, "good-1.1", "passes");
+        ctx.save(
+            &info,
+            Outcome::Passed,
+            String::new(),
+            Duration::from_millis(2),
+        );
+        let info = TestInfo::new(&mut interp, context_id, "bad-1.1", "fails <badly>");
+        ctx.save(
+            &info,
+            Outcome::Failed,
+            "Expected -ok <a>\nReceived -ok <b>".into(),
+            Duration::default(),
+        );
+        let info = TestInfo::new(&mut interp, context_id, "bad-1.2", "errors #1");
+        ctx.save(&info, Outcome::Error, "Oops".into(), Duration::default());
+        let info = TestInfo::new(&mut interp, context_id, "skip-1.1", "skipped");
+        ctx.save(
+            &info,
+            Outcome::Skipped,
+            "unsatisfied constraint: knownBug".into(),
+            Duration::default(),
+        );
+
+        ctx
+    }
+
+    #[test]
+    fn test_default_constraints() {
+        let constraints = default_constraints();
+
+        assert_eq!(constraints.contains("unix"), cfg!(unix));
+        assert_eq!(constraints.contains("windows"), cfg!(windows));
+        assert_eq!(constraints.contains("macos"), cfg!(target_os = "macos"));
+        assert!(!constraints.contains("knownBug"));
+    }
+
+    #[test]
+    fn test_is_selected() {
+        let mut ctx = TestContext::new(true);
+        assert!(ctx.is_selected("expr-1.1"));
+
+        ctx.match_patterns = vec!["expr-*".into(), "list-2.*".into()];
+        assert!(ctx.is_selected("expr-1.1"));
+        assert!(ctx.is_selected("list-2.3"));
+        assert!(!ctx.is_selected("list-1.1"));
+
+        ctx.skip_patterns = vec!["expr-1.*".into()];
+        assert!(!ctx.is_selected("expr-1.1"));
+        assert!(ctx.is_selected("expr-2.1"));
+
+        ctx.match_patterns.clear();
+        assert!(ctx.is_selected("list-1.1"));
+        assert!(!ctx.is_selected("expr-1.2"));
+    }
+
+    #[test]
+    fn test_setup_hook_failure() {
+        let mut interp = Interp::new();
+        let context_id = interp.save_context(TestContext::new(true));
+        interp.add_context_command("test", test_cmd, context_id);
+        interp
+            .eval(
+                r#"
+                set ::log {}
+                proc testSetup {} { error "no setup" }
+                proc testTeardown {} { lappend ::log teardown }
+                test hook-1.1 {setup fails} { lappend ::log body } -ok {body}
+                "#,
+            )
+            .unwrap();
+
+        assert_eq!(interp.eval("set ::log").unwrap().as_str(), "teardown");
+
+        let ctx = interp.context::<TestContext>(context_id);
+        assert_eq!(ctx.num_tests, 1);
+        assert_eq!(ctx.num_failed, 1);
+        assert_eq!(ctx.results[0].outcome, Outcome::Failed);
+        assert_eq!(ctx.results[0].message, "Error in testSetup: no setup");
+    }
+
+    #[test]
+    #[cfg(feature = "os")]
+    fn test_output() {
+        let mut interp = Interp::new();
+        let context_id = interp.save_context(TestContext::new(true));
+        interp.add_context_command("test", test_cmd, context_id);
+        interp
+            .eval(
+                r#"
+                test output-1.1 {matches} -body { puts "Howdy" } -ok {} -output "Howdy\n"
+                test output-1.2 {differs} -body { puts -nonewline "Hi" } -ok {} -output "Howdy"
+                "#,
+            )
+            .unwrap();
+
+        let ctx = interp.context::<TestContext>(context_id);
+        assert_eq!(ctx.num_passed, 1);
+        assert_eq!(ctx.num_failed, 1);
+        assert_eq!(
+            ctx.results[1].message,
+            "Expected -output <Howdy>\nReceived -output <Hi>"
+        );
+
+        // The original stdout is restored after each test.
+        assert!(interp.replace_stdout(Box::new(io::sink())).is_some());
+    }
+
+    #[test]
+    fn test_write_tap() {
+        let ctx = sample_results();
+        let mut out: Vec<u8> = Vec::new();
+        write_tap(&mut out, &ctx).unwrap();
+        let tap = String::from_utf8(out).unwrap();
+
+        assert_eq!(
+            tap,
+            r#"1..4
+ok 1 - good-1.1 passes
+not ok 2 - bad-1.1 fails <badly>
+# FAILED Expected -ok <a>
+# Received -ok <b>
+not ok 3 - bad-1.2 errors \#1
+# ERROR Oops
+ok 4 - skip-1.1 skipped # SKIP unsatisfied constraint: knownBug
+"#
+        );
+    }
+
+    #[test]
+    fn test_write_junit() {
+        let ctx = sample_results();
+        let mut out: Vec<u8> = Vec::new();
+        write_junit(&mut out, "all.tcl", &ctx).unwrap();
+        let xml = String::from_utf8(out).unwrap();
+
+        assert_eq!(
+            xml,
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<testsuite name="all.tcl" tests="4" failures="1" errors="1" skipped="1" time="0.000">
+  <testcase classname="molt" name="good-1.1 passes" time="0.002"/>
+  <testcase classname="molt" name="bad-1.1 fails &lt;badly&gt;" time="0.000">
+    <failure message="Expected -ok &lt;a&gt;">Expected -ok &lt;a&gt;&#10;Received -ok &lt;b&gt;</failure>
+  </testcase>
+  <testcase classname="molt" name="bad-1.2 errors #1" time="0.000">
+    <error message="Oops">Oops</error>
+  </testcase>
+  <testcase classname="molt" name="skip-1.1 skipped" time="0.000">
+    <skipped message="unsatisfied constraint: knownBug"/>
+  </testcase>
+</testsuite>
+"#
+        );
+    }
 }
@@ -20,6 +20,9 @@ pub struct Tokenizer<'a> {
 
     // The iterator used to extract characters from the input
     chars: Peekable<Chars<'a>>,
+
+    // The line number of the next character, counting from 1.
+    line: usize,
 }
 
 impl<'a> Tokenizer<'a> {
@@ -29,6 +32,7 @@ impl<'a> Tokenizer<'a> {
             input,
             index: 0,
             chars: input.chars().peekable(),
+            line: 1,
         }
     }
 
@@ -48,6 +52,11 @@ impl<'a> Tokenizer<'a> {
         self.index
     }
 
+    /// Returns the line number of the next character, counting from 1.
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
     // Returns the remainder of the input starting at the given mark.
     #[allow(dead_code)]
     pub fn tail(&self, mark: usize) -> &str {
@@ -61,6 +70,10 @@ impl<'a> Tokenizer<'a> {
 
         if let Some(c) = ch {
             self.index += c.len_utf8();
+
+            if c == '\n' {
+                self.line += 1;
+            }
         }
 
         ch
@@ -88,6 +101,7 @@ impl<'a> Tokenizer<'a> {
 
     /// Resets the index to the given mark.  For internal use only.
     fn reset_to(&mut self, mark: usize) {
+        self.line -= self.input[mark..self.index].matches('\n').count();
         self.index = mark;
         self.chars = self.input[self.index..].chars().peekable();
     }
@@ -178,6 +192,13 @@ impl<'a> Tokenizer<'a> {
                 't' => '\t',   // Tab
                 'v' => '\x0b', // Vertical Tab
 
+                // Line continuation: the newline and any following spaces and tabs
+                // are replaced by a single space.
+                '\n' => {
+                    self.skip_while(|ch| *ch == ' ' || *ch == '\t');
+                    ' '
+                }
+
                 // 1 to 3 octal digits
                 '0'..='7' => {
                     // Note: only works because these digits are single bytes.
@@ -188,8 +209,9 @@ impl<'a> Tokenizer<'a> {
 
                     let octal = &self.input[start..self.index];
 
-                    let val = u8::from_str_radix(octal, 8).unwrap();
-                    val as char
+                    // As in TCL, values greater than \377 keep only the low byte.
+                    let val = u32::from_str_radix(octal, 8).unwrap();
+                    (val & 0xff) as u8 as char
                 }
 
                 // \xhh, \uhhhh, \Uhhhhhhhh
@@ -393,6 +415,21 @@ mod tests {
         assert_eq!(ptr.as_str(), "");
     }
 
+    #[test]
+    fn test_line() {
+        let mut ptr = Tokenizer::new("a\nb\n\nc");
+        assert_eq!(ptr.line(), 1);
+        ptr.skip();
+        assert_eq!(ptr.line(), 1);
+        ptr.skip();
+        assert_eq!(ptr.line(), 2);
+        ptr.skip_while(|ch| *ch != 'c');
+        assert_eq!(ptr.line(), 4);
+        ptr.reset_to(2);
+        assert_eq!(ptr.line(), 2);
+        assert_eq!(ptr.peek(), Some('b'));
+    }
+
     #[test]
     fn test_backslash_subst_single() {
         // Single Character Escapes
@@ -414,6 +451,8 @@ mod tests {
         assert_eq!(bsubst("\\1772-"), ('\x7f', Some('2')));
         assert_eq!(bsubst("\\18-"), ('\x01', Some('8')));
         assert_eq!(bsubst("\\8-"), ('8', Some('-')));
+        assert_eq!(bsubst("\\377-"), ('\u{ff}', Some('-')));
+        assert_eq!(bsubst("\\777-"), ('\u{ff}', Some('-')));
     }
 
     #[test]
@@ -450,6 +489,14 @@ mod tests {
         assert_eq!(bsubst("\\U000000077-"), ('\x07', Some('7')));
     }
 
+    #[test]
+    fn test_backslash_subst_newline() {
+        // Line continuation
+        assert_eq!(bsubst("\\\n-"), (' ', Some('-')));
+        assert_eq!(bsubst("\\\n \t -"), (' ', Some('-')));
+        assert_eq!(bsubst("\\\n\n-"), (' ', Some('\n')));
+    }
+
     #[test]
     fn test_backslash_subst_other() {
         // Arbitrary Character
@@ -24,10 +24,14 @@
 //! [`interp`]: interp/index.html
 
 use crate::interp::Interp;
+pub use crate::value::MoltRc;
+pub use crate::value::MoltValue;
+pub use crate::value::Shareable;
 pub use crate::value::Value;
 use indexmap::IndexMap;
 use std::fmt;
 use std::str::FromStr;
+use std::time::Duration;
 
 // Molt Numeric Types
 
@@ -256,6 +260,10 @@ pub struct Exception {
 
     /// The error info, if any.
     error_data: Option<ErrorData>,
+
+    /// Any other options given to the `return` command, to be included in the `catch`
+    /// options.  Boxed, as they are rarely used and the `Exception` should be small.
+    extra_options: Option<Box<MoltDict>>,
 }
 
 impl Exception {
@@ -307,6 +315,33 @@ impl Exception {
             .error_info()
     }
 
+    /// Returns the exception's error code, if it is an error exception, and `None`
+    /// otherwise.  Unlike [`error_code`](#method.error_code), this never panics.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use molt::types::*;
+    /// # use molt::Interp;
+    /// let mut interp = Interp::new();
+    ///
+    /// let exception = interp.eval("throw MYERR \"Error Message\"").unwrap_err();
+    /// assert_eq!(exception.error_code_value(), Some(Value::from("MYERR")));
+    ///
+    /// let exception = Exception::molt_break();
+    /// assert_eq!(exception.error_code_value(), None);
+    /// ```
+    pub fn error_code_value(&self) -> Option<Value> {
+        self.error_data.as_ref().map(|data| data.error_code())
+    }
+
+    /// Returns the exception's error info, i.e., the human-readable stack trace, if it
+    /// is an error exception, and `None` otherwise.  Unlike
+    /// [`error_info`](#method.error_info), this never panics.
+    pub fn error_info_value(&self) -> Option<Value> {
+        self.error_data.as_ref().map(|data| data.error_info())
+    }
+
     /// Gets the exception's [`ErrorData`], if any; the error data is available only when
     /// the `code()` is `ResultCode::Error`.  The error data contains the error's error code
     /// and stack trace information.
@@ -474,6 +509,7 @@ impl Exception {
             level: 0,
             next_code: ResultCode::Error,
             error_data: Some(data),
+            extra_options: None,
         }
     }
 
@@ -505,6 +541,43 @@ impl Exception {
             level: 0,
             next_code: ResultCode::Error,
             error_data: Some(data),
+            extra_options: None,
+        }
+    }
+
+    /// Creates an `Error` exception with the given error code, initial error info, and
+    /// message.  The error info is the initial stack trace; the interpreter will add to
+    /// it as the error propagates.  If the error info is empty, the stack trace begins
+    /// with the message, as for [`molt_err2`].  This is primarily for use by the `error`
+    /// command, when rethrowing an error caught by `catch`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use molt::types::*;
+    ///
+    /// let ex = Exception::molt_err3("MYERR".into(), "stack trace".into(), "error message".into());
+    /// assert!(ex.is_error());
+    /// assert_eq!(ex.error_code(), "MYERR".into());
+    /// assert_eq!(ex.error_info(), "stack trace".into());
+    /// assert_eq!(ex.value(), "error message".into());
+    /// ```
+    ///
+    /// [`molt_err2`]: #method.molt_err2
+    pub fn molt_err3(error_code: Value, error_info: Value, msg: Value) -> Self {
+        let data = if error_info.as_str().is_empty() {
+            ErrorData::new(error_code, msg.as_str())
+        } else {
+            ErrorData::rethrow(error_code, error_info.as_str())
+        };
+
+        Self {
+            code: ResultCode::Error,
+            value: msg,
+            level: 0,
+            next_code: ResultCode::Error,
+            error_data: Some(data),
+            extra_options: None,
         }
     }
 
@@ -522,6 +595,7 @@ impl Exception {
             level: 1,
             next_code: ResultCode::Okay,
             error_data: None,
+            extra_options: None,
         }
     }
 
@@ -548,6 +622,7 @@ impl Exception {
             level,
             next_code,
             error_data: None,
+            extra_options: None,
         }
     }
 
@@ -580,6 +655,7 @@ impl Exception {
             level,
             next_code: ResultCode::Error,
             error_data: Some(data),
+            extra_options: None,
         }
     }
 
@@ -596,6 +672,7 @@ impl Exception {
             level: 0,
             next_code: ResultCode::Break,
             error_data: None,
+            extra_options: None,
         }
     }
 
@@ -612,6 +689,7 @@ impl Exception {
             level: 0,
             next_code: ResultCode::Continue,
             error_data: None,
+            extra_options: None,
         }
     }
 
@@ -630,6 +708,32 @@ impl Exception {
         }
     }
 
+    /// Returns the options given to the `return` command other than the standard
+    /// `-code`, `-level`, `-errorcode`, and `-errorinfo`, if any.  They are included in the
+    /// options dictionary retrieved by `catch`.
+    pub fn extra_options(&self) -> Option<&MoltDict> {
+        self.extra_options.as_deref()
+    }
+
+    /// Sets the non-standard `return` options to be included in the `catch` options.
+    /// See [`extra_options`](#method.extra_options).
+    pub(crate) fn set_extra_options(&mut self, options: MoltDict) {
+        self.extra_options = if options.is_empty() {
+            None
+        } else {
+            Some(Box::new(options))
+        };
+    }
+
+    /// Sets the error line, if this is an error.  The interpreter calls this as the error
+    /// propagates, so that it is the line in the outermost script evaluated so far.
+    /// See Interp::eval_script.
+    pub(crate) fn set_error_line(&mut self, line: usize) {
+        if let Some(data) = &mut self.error_data {
+            data.line = line;
+        }
+    }
+
     /// This is used by the interpreter when accumulating stack trace information.
     /// See Interp::eval_script.
     pub(crate) fn is_new_error(&self) -> bool {
@@ -641,6 +745,54 @@ impl Exception {
     }
 }
 
+/// Formats the exception as its result value, i.e., the error message for error
+/// exceptions.
+impl fmt::Display for Exception {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.value)
+    }
+}
+
+impl std::error::Error for Exception {}
+
+/// Converts an I/O error into a Molt error exception, so that commands that do I/O can
+/// use the `?` operator.  The error message is "couldn't complete I/O operation: "
+/// followed by the I/O error's own message.
+///
+/// # Example
+///
+/// ```
+/// use molt::check_args;
+/// use molt::molt_ok;
+/// use molt::types::*;
+/// use molt::Interp;
+/// use std::fs;
+///
+/// // A command that returns the content of a file.
+/// fn cmd_readfile(_interp: &mut Interp, _: ContextID, argv: &[Value]) -> MoltResult {
+///     check_args(1, argv, 2, 2, "path")?;
+///     let text = fs::read_to_string(argv[1].as_str())?;
+///     molt_ok!(text)
+/// }
+///
+/// let mut interp = Interp::new();
+/// interp.add_command("readfile", cmd_readfile);
+///
+/// let exception = interp.eval("readfile /nonesuch/file.txt").unwrap_err();
+/// assert!(exception.is_error());
+/// assert!(exception
+///     .to_string()
+///     .starts_with("couldn't complete I/O operation: "));
+/// ```
+impl From<std::io::Error> for Exception {
+    fn from(err: std::io::Error) -> Self {
+        Exception::molt_err(Value::from(format!(
+            "couldn't complete I/O operation: {}",
+            err
+        )))
+    }
+}
+
 /// This struct contains the error code and stack trace (i.e., the "error info" string)
 /// for `ResultCode::Error` exceptions.
 #[derive(Debug, Clone, Eq, PartialEq)]
@@ -651,6 +803,10 @@ pub struct ErrorData {
     /// The TCL stack trace.
     stack_trace: Vec<String>,
 
+    /// The line number, within the evaluated script, of the command in which the error
+    /// occurred; 0 if it hasn't yet been determined.
+    line: usize,
+
     /// Is this a new error?
     is_new: bool,
 }
@@ -663,6 +819,7 @@ impl ErrorData {
         Self {
             error_code,
             stack_trace: vec![error_msg.into()],
+            line: 0,
             is_new: true,
         }
     }
@@ -674,6 +831,7 @@ impl ErrorData {
         Self {
             error_code,
             stack_trace: vec![error_info.into()],
+            line: 0,
             is_new: false,
         }
     }
@@ -693,6 +851,13 @@ impl ErrorData {
         Value::from(self.stack_trace.join("\n"))
     }
 
+    /// Returns the line number, within the evaluated script, of the command in which the
+    /// error occurred, i.e., the `-errorline` return option.  Returns 0 if the error
+    /// hasn't yet propagated through a script.
+    pub fn error_line(&self) -> usize {
+        self.line
+    }
+
     /// Adds to the stack trace, which, having been extended, is no longer new.
     pub(crate) fn add_info(&mut self, info: &str) {
         self.stack_trace.push(info.into());
@@ -724,6 +889,44 @@ pub struct ContextID(pub(crate) u64);
 /// [`ContextID`]: struct.ContextID.html
 pub type CommandFunc = fn(&mut Interp, ContextID, &[Value]) -> MoltResult;
 
+/// A function that reads the interpreter's clock, returning the time elapsed since some
+/// fixed point in the past.  The clock must never run backwards.  The interpreter uses
+/// its clock for the `time` command and for time limits; see
+/// [`Interp::set_clock`](../interp/struct.Interp.html#method.set_clock).
+pub type ClockFunc = fn() -> Duration;
+
+/// A hook called with the words of each command just before the command is executed, and
+/// the command's frame level, as for `info frame`: 1 for a command in a top-level script,
+/// 2 for a command in the body of a procedure called by it, and so on.  See
+/// [`Interp::set_exec_trace`](../interp/struct.Interp.html#method.set_exec_trace).
+pub type ExecTraceFunc = Box<dyn FnMut(&[Value], usize)>;
+
+/// A debugger hook, called when evaluation pauses at a breakpoint or while stepping, with
+/// the interpreter, the words of the command about to be executed, and the command's
+/// frame level.  The hook can inspect and modify the paused interpreter, e.g., by running
+/// a nested REPL, and returns the [`DebugAction`] to take next.  See
+/// [`Interp::set_debug_hook`](../interp/struct.Interp.html#method.set_debug_hook).
+pub type DebugHook = Box<dyn FnMut(&mut Interp, &[Value], usize) -> DebugAction>;
+
+/// What the interpreter should do when a [`DebugHook`] returns.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum DebugAction {
+    /// Execute the command, and continue until the next breakpoint.
+    Continue,
+
+    /// Execute the command, pausing again before the next command, e.g., the first
+    /// command in a procedure's body.
+    Step,
+
+    /// Execute the command, pausing again before the next command at the same or an
+    /// outer frame level, i.e., stepping over procedure calls.
+    StepOver,
+
+    /// Abort the evaluation: the command and every further command fail with an error
+    /// until control returns to the application.
+    Abort,
+}
+
 /// A Molt command that has subcommands is called an _ensemble_ command.  In Rust code,
 /// the ensemble is defined as an array of `Subcommand` structs, each one mapping from
 /// a subcommand name to the implementing [`CommandFunc`].  For more information,
@@ -758,30 +961,24 @@ impl Subcommand {
             }
         }
 
-        let mut names = String::new();
-        names.push_str(ensemble[0].0);
-        let last = ensemble.len() - 1;
-
-        if ensemble.len() > 1 {
-            names.push_str(", ");
-        }
-
-        if ensemble.len() > 2 {
-            let vec: Vec<&str> = ensemble[1..last].iter().map(|x| x.0).collect();
-            names.push_str(&vec.join(", "));
-        }
+        let names: Vec<&str> = ensemble.iter().map(|x| x.0).collect();
+        Err(unknown_subcommand(sub_name, &names))
+    }
+}
 
-        if ensemble.len() > 1 {
-            names.push_str(", or ");
-            names.push_str(ensemble[last].0);
-        }
+/// Returns the standard error for an unknown subcommand, listing the valid subcommand
+/// names: e.g., `unknown or ambiguous subcommand "x": must be a, b, or c`.
+pub(crate) fn unknown_subcommand(sub_name: &str, names: &[&str]) -> Exception {
+    let mut msg = format!("unknown or ambiguous subcommand \"{}\"", sub_name);
 
-        molt_err!(
-            "unknown or ambiguous subcommand \"{}\": must be {}",
-            sub_name,
-            &names
-        )
+    match names {
+        [] => (),
+        [name] => msg.push_str(&format!(": must be {}", name)),
+        [first, second] => msg.push_str(&format!(": must be {} or {}", first, second)),
+        [rest @ .., last] => msg.push_str(&format!(": must be {}, or {}", rest.join(", "), last)),
     }
+
+    Exception::molt_err(Value::from(msg))
 }
 
 /// In TCL, variable references have two forms.  A string like "_some_var_(_some_index_)" is
@@ -908,6 +1105,20 @@ mod tests {
         assert!(!data.is_new());
     }
 
+    #[test]
+    fn test_exception_error_line() {
+        let mut err = Exception::molt_err("error message".into());
+        assert_eq!(err.error_data().unwrap().error_line(), 0);
+
+        err.set_error_line(3);
+        assert_eq!(err.error_data().unwrap().error_line(), 3);
+
+        // Non-errors have no error line.
+        let mut exception = Exception::molt_break();
+        exception.set_error_line(3);
+        assert!(exception.error_data().is_none());
+    }
+
     #[test]
     fn test_exception_molt_err() {
         let mut exception = Exception::molt_err("error message".into());
@@ -944,6 +1155,26 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_exception_molt_err3() {
+        let exception =
+            Exception::molt_err3("CODE".into(), "stack trace".into(), "error message".into());
+
+        assert_eq!(exception.code(), ResultCode::Error);
+        assert_eq!(exception.value(), "error message".into());
+        assert!(!exception.is_new_error());
+
+        if let Some(data) = exception.error_data() {
+            assert_eq!(data.error_code(), "CODE".into());
+            assert_eq!(data.error_info(), "stack trace".into());
+        }
+
+        // With no error info, the stack trace begins with the message.
+        let exception = Exception::molt_err3("CODE".into(), Value::empty(), "error message".into());
+        assert!(exception.is_new_error());
+        assert_eq!(exception.error_info(), "error message".into());
+    }
+
     #[test]
     fn test_exception_molt_return_err_level0() {
         let exception = Exception::molt_return_err(
@@ -1020,6 +1251,54 @@ mod tests {
         assert!(!exception.error_data().is_some());
     }
 
+    #[test]
+    fn test_exception_extra_options() {
+        let mut exception = Exception::molt_return("result".into());
+        assert!(exception.extra_options().is_none());
+
+        let mut options = MoltDict::new();
+        options.insert("-mine".into(), "yes".into());
+        exception.set_extra_options(options.clone());
+        assert_eq!(exception.extra_options(), Some(&options));
+
+        exception.set_extra_options(MoltDict::new());
+        assert!(exception.extra_options().is_none());
+    }
+
+    #[test]
+    fn test_exception_error_values() {
+        let err = Exception::molt_err2("CODE".into(), "error message".into());
+        assert_eq!(err.error_code_value(), Some("CODE".into()));
+        assert_eq!(err.error_info_value(), Some("error message".into()));
+
+        let exception = Exception::molt_break();
+        assert_eq!(exception.error_code_value(), None);
+        assert_eq!(exception.error_info_value(), None);
+    }
+
+    #[test]
+    fn test_exception_display() {
+        let err = Exception::molt_err("error message".into());
+        assert_eq!(err.to_string(), "error message");
+
+        // It can be used as a standard error.
+        let err: Box<dyn std::error::Error> = Box::new(err);
+        assert_eq!(err.to_string(), "error message");
+    }
+
+    #[test]
+    fn test_exception_from_io_error() {
+        let io_err = std::io::Error::new(std::io::ErrorKind::NotFound, "no such file");
+        let err = Exception::from(io_err);
+
+        assert!(err.is_error());
+        assert_eq!(
+            err.value(),
+            "couldn't complete I/O operation: no such file".into()
+        );
+        assert_eq!(err.error_code_value(), Some("NONE".into()));
+    }
+
     #[test]
     fn test_exception_molt_break() {
         let exception = Exception::molt_break();
@@ -149,25 +149,150 @@ pub fn read_float(ptr: &mut Tokenizer) -> Option<String> {
     }
 }
 
-/// Compare two strings, up to an optional length, returning -1, 0, or 1 as a
-/// molt result.
-pub(crate) fn compare_len(str1: &str, str2: &str, length: Option<MoltInt>) -> Result<MoltInt,Exception> {
-    let s1;
-    let s2;
-
-    if let Some(len) = length {
-        s1 = str1.substring(0, len as usize);
-        s2 = str2.substring(0, len as usize);
-    } else {
-        s1 = str1;
-        s2 = str2;
+/// Compare two strings, up to an optional length in characters, returning -1, 0, or 1 as a
+/// molt result.  The strings are compared by Unicode scalar value, which for UTF-8 is the
+/// same as comparing their bytes.  A negative length compares the whole strings.
+pub(crate) fn compare_len(
+    str1: &str,
+    str2: &str,
+    length: Option<MoltInt>,
+) -> Result<MoltInt, Exception> {
+    let s1 = prefix_chars(str1, length);
+    let s2 = prefix_chars(str2, length);
+
+    Ok(ordering_to_int(s1.cmp(s2)))
+}
+
+/// Compare two strings case-insensitively, up to an optional length in characters, returning
+/// -1, 0, or 1 as a molt result.  The strings are compared character by character, as
+/// lowercased by `char::to_lowercase`, without allocating lowercase copies.
+pub(crate) fn compare_nocase_len(
+    str1: &str,
+    str2: &str,
+    length: Option<MoltInt>,
+) -> Result<MoltInt, Exception> {
+    let lower1 = prefix_chars(str1, length).chars().flat_map(char::to_lowercase);
+    let lower2 = prefix_chars(str2, length).chars().flat_map(char::to_lowercase);
+
+    Ok(ordering_to_int(lower1.cmp(lower2)))
+}
+
+/// Returns the first `length` characters of the string, or the whole string if the length
+/// is `None` or negative.
+fn prefix_chars(s: &str, length: Option<MoltInt>) -> &str {
+    match length {
+        Some(len) if len >= 0 => s.substring(0, len as usize),
+        _ => s,
+    }
+}
+
+/// Converts an ordering to -1, 0, or 1.
+fn ordering_to_int(ordering: Ordering) -> MoltInt {
+    match ordering {
+        Ordering::Less => -1,
+        Ordering::Equal => 0,
+        Ordering::Greater => 1,
+    }
+}
+
+/// Converts a character index into the string to the byte offset of that character, or
+/// `None` if the string has no such character.
+pub(crate) fn char_to_byte_offset(s: &str, char_index: usize) -> Option<usize> {
+    s.char_indices().nth(char_index).map(|(byte, _)| byte)
+}
+
+/// Converts a byte offset into the string, which must be on a character boundary, to the
+/// index of the character at that offset.
+pub(crate) fn byte_to_char_offset(s: &str, byte_index: usize) -> usize {
+    s[..byte_index].chars().count()
+}
+
+/// Matches the string against a glob-style pattern, as for Standard TCL's `string match`.
+/// The pattern may contain the following special sequences:
+///
+/// * `*` matches any sequence of characters, including the empty sequence.
+/// * `?` matches any single character.
+/// * `[chars]` matches any single character in the set, which may include ranges, e.g.,
+///   `[a-z0-9_]`; ranges compare code points.  `[^chars]` matches any single character
+///   that's not in the set.  Within the brackets, `\]` is a literal `]`.
+/// * `\x` matches the character `x`, e.g., `\*` matches `*`.
+///
+/// Any other character matches itself.
+pub fn glob_match(pattern: &str, string: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let string: Vec<char> = string.chars().collect();
+
+    glob_match_chars(&pattern, &string)
+}
+
+fn glob_match_chars(pattern: &[char], string: &[char]) -> bool {
+    match pattern.first() {
+        None => string.is_empty(),
+        Some('*') => {
+            // A run of stars is the same as one star.
+            let mut rest = &pattern[1..];
+            while rest.first() == Some(&'*') {
+                rest = &rest[1..];
+            }
+
+            (0..=string.len()).any(|i| glob_match_chars(rest, &string[i..]))
+        }
+        Some('?') => !string.is_empty() && glob_match_chars(&pattern[1..], &string[1..]),
+        Some('[') => {
+            if string.is_empty() {
+                return false;
+            }
+
+            match glob_match_set(&pattern[1..], string[0]) {
+                Some((true, rest)) => glob_match_chars(rest, &string[1..]),
+                _ => false,
+            }
+        }
+        Some('\\') if pattern.len() > 1 => {
+            string.first() == Some(&pattern[1]) && glob_match_chars(&pattern[2..], &string[1..])
+        }
+        Some(ch) => string.first() == Some(ch) && glob_match_chars(&pattern[1..], &string[1..]),
     }
+}
+
+// Matches the character against the bracketed set at the head of the pattern; the opening
+// bracket has already been consumed.  Returns whether the character matched, and the remainder
+// of the pattern following the closing bracket, or None if there is no closing bracket.
+fn glob_match_set(pattern: &[char], ch: char) -> Option<(bool, &[char])> {
+    let mut matched = false;
+    let mut i = 0;
+
+    // A leading caret negates the set.
+    let negated = pattern.first() == Some(&'^');
+    if negated {
+        i += 1;
+    }
+
+    while i < pattern.len() {
+        if pattern[i] == ']' {
+            return Some((matched != negated, &pattern[i + 1..]));
+        }
 
-    match s1.cmp(s2) {
-        Ordering::Less => Ok(-1),
-        Ordering::Equal => Ok(0),
-        Ordering::Greater => Ok(1),
+        if pattern[i] == '\\' && i + 1 < pattern.len() {
+            i += 1;
+        }
+        let lo = pattern[i];
+
+        if i + 2 < pattern.len() && pattern[i + 1] == '-' && pattern[i + 2] != ']' {
+            let hi = pattern[i + 2];
+            if (lo <= ch && ch <= hi) || (hi <= ch && ch <= lo) {
+                matched = true;
+            }
+            i += 3;
+        } else {
+            if lo == ch {
+                matched = true;
+            }
+            i += 1;
+        }
     }
+
+    None
 }
 
 // From carlomilanesi, rust forums
@@ -229,6 +354,55 @@ impl StringUtils for str {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_util_char_offsets() {
+        let s = "aé日😀b";
+        assert_eq!(char_to_byte_offset(s, 0), Some(0));
+        assert_eq!(char_to_byte_offset(s, 2), Some(3));
+        assert_eq!(char_to_byte_offset(s, 4), Some(10));
+        assert_eq!(char_to_byte_offset(s, 5), None);
+        assert_eq!(char_to_byte_offset("", 0), None);
+
+        assert_eq!(byte_to_char_offset(s, 0), 0);
+        assert_eq!(byte_to_char_offset(s, 3), 2);
+        assert_eq!(byte_to_char_offset(s, 10), 4);
+        assert_eq!(byte_to_char_offset(s, s.len()), 5);
+    }
+
+    #[test]
+    fn test_util_compare_len() {
+        assert_eq!(compare_len("abc", "abc", None), Ok(0));
+        assert_eq!(compare_len("abc", "abd", None), Ok(-1));
+        assert_eq!(compare_len("abd", "abc", None), Ok(1));
+        assert_eq!(compare_len("ab", "abc", None), Ok(-1));
+        assert_eq!(compare_len("abX", "abY", Some(2)), Ok(0));
+        assert_eq!(compare_len("abX", "abY", Some(-1)), Ok(-1));
+        assert_eq!(compare_len("abX", "abY", Some(0)), Ok(0));
+
+        // The length counts characters, not bytes.
+        assert_eq!(compare_len("日本語", "日本人", Some(2)), Ok(0));
+        assert_eq!(compare_len("日本語", "日本人", Some(3)), Ok(1));
+        assert_eq!(compare_len("éa", "éb", Some(1)), Ok(0));
+
+        // Characters are ordered by scalar value.
+        assert_eq!(compare_len("z", "é", None), Ok(-1));
+        assert_eq!(compare_len("\u{ffff}", "😀", None), Ok(-1));
+    }
+
+    #[test]
+    fn test_util_compare_nocase_len() {
+        assert_eq!(compare_nocase_len("abc", "ABC", None), Ok(0));
+        assert_eq!(compare_nocase_len("abc", "ABD", None), Ok(-1));
+        assert_eq!(compare_nocase_len("ABD", "abc", None), Ok(1));
+        assert_eq!(compare_nocase_len("ab", "ABC", None), Ok(-1));
+        assert_eq!(compare_nocase_len("abX", "ABy", Some(2)), Ok(0));
+        assert_eq!(compare_nocase_len("ÉCOLE", "école", None), Ok(0));
+        assert_eq!(compare_nocase_len("Σx", "σy", Some(1)), Ok(0));
+
+        // Characters whose lowercase forms are longer than themselves.
+        assert_eq!(compare_nocase_len("İ", "i\u{307}", None), Ok(0));
+    }
+
     #[test]
     fn test_util_read_int() {
         let mut p = Tokenizer::new("abc");
@@ -260,6 +434,44 @@ mod tests {
         assert_eq!(Some('a'), p.peek());
     }
 
+    #[test]
+    fn test_util_glob_match() {
+        assert!(glob_match("", ""));
+        assert!(glob_match("abc", "abc"));
+        assert!(!glob_match("abc", "abcd"));
+
+        assert!(glob_match("*", ""));
+        assert!(glob_match("*", "abc"));
+        assert!(glob_match("a*c", "abbbc"));
+        assert!(glob_match("a**c", "ac"));
+        assert!(!glob_match("a*c", "abcd"));
+        assert!(glob_match("*.rs", "lib.rs"));
+
+        assert!(glob_match("a?c", "abc"));
+        assert!(!glob_match("a?c", "ac"));
+
+        assert!(glob_match("[abc]x", "bx"));
+        assert!(!glob_match("[abc]x", "dx"));
+        assert!(glob_match("[a-c]", "b"));
+        assert!(glob_match("[c-a]", "b"));
+        assert!(glob_match("[0-9a-f]*", "f00"));
+        assert!(!glob_match("[abc", "a"));
+
+        assert!(glob_match("a\\*", "a*"));
+        assert!(!glob_match("a\\*", "ab"));
+        assert!(glob_match("[\\]]", "]"));
+
+        assert!(glob_match("[^abc]x", "dx"));
+        assert!(!glob_match("[^abc]x", "bx"));
+        assert!(!glob_match("[^a-z]", "q"));
+        assert!(glob_match("[^a-z]", "Q"));
+        assert!(!glob_match("[^a]", ""));
+        assert!(glob_match("[aeiou]*", "apple"));
+        assert!(!glob_match("[aeiou]*", "banana"));
+        assert!(glob_match("[a\\]b]", "]"));
+        assert!(glob_match("[α-ω]", "λ"));
+    }
+
     #[test]
     #[allow(clippy::cognitive_complexity)]
     fn test_util_read_float() {
@@ -25,6 +25,14 @@
 //! may be used to pass values between `Interps` in the same thread (at the cost of
 //! potential shimmering), but between threads one should pass the value's string rep instead.
 //!
+//! With the `sync-values` feature, `Value` is `Send` and `Sync`, so values, including whole
+//! lists, can be built on one thread and passed to an interpreter on another.  Internally,
+//! the feature shares values with `Arc` instead of `Rc` and guards their data reps with
+//! locks; this makes every `Value` a little slower, and so the feature is off by default.
+//! Application code that names the shared pointer type should use the [`MoltRc`] alias,
+//! which is `Rc` or `Arc` as appropriate, and external types stored in a `Value` must be
+//! [`Shareable`]: with the feature, `Send` and `Sync`.
+//!
 //! # Comparisons
 //!
 //! If two `Value`'s are compared for equality in Rust, Rust compares their string reps;
@@ -74,6 +82,11 @@
 //! can usually be avoided with a little care.  Note that accessing the value's string rep
 //! doesn't cause shimmering; the string is always readily available.
 //!
+//! **Interning**: A few very common values, the empty string, `0`, and `1`, are interned:
+//! `Value::empty()`, `Value::from("")`, and the `Value` conversions from `bool` and
+//! `MoltInt` return shared copies of them rather than allocating new values.  This is
+//! invisible to the client, as a value's string rep never changes.
+//!
 //! `Value` handles strings, integers, floating-point values, lists, and a few other things as
 //! special cases, since they are part of the language and are so frequently used.
 //! In addition, a `Value` can also contain _external types_: Rust types that implement
@@ -102,6 +115,7 @@
 //! a simple enum.
 //!
 //! ```
+//! use molt::molt_err;
 //! use molt::types::*;
 //! use std::fmt;
 //! use std::str::FromStr;
@@ -139,37 +153,60 @@
 //!     }
 //! }
 //!
-//! impl Flavor {
-//!     /// A convenience: retrieves the enumerated value, converting it from
-//!     /// `Option<Flavor>` into `Result<Flavor,Exception>`.
-//!     pub fn from_molt(value: &Value) -> Result<Self, Exception> {
-//!         if let Some(x) = value.as_copy::<Flavor>() {
-//!             Ok(x)
-//!         } else {
-//!             Err(Exception::molt_err(Value::from("Not a flavor string")))
-//!         }
-//!     }
-//! }
+//! impl MoltValue for Flavor {}
+//!
+//! // A Flavor converts directly into a Value...
+//! let value = Value::from(Flavor::SALTY);
+//! assert_eq!(value.as_str(), "salty");
+//!
+//! // ...and a Value converts back into a Flavor, or into the standard error message.
+//! assert_eq!(*Value::from("sweet").as_external::<Flavor>()?, Flavor::SWEET);
+//! assert_eq!(
+//!     Value::from("bitter").as_external::<Flavor>(),
+//!     molt_err!("expected Flavor but got \"bitter\"")
+//! );
+//! # Ok::<(), Exception>(())
 //! ```
 //!
+//! Implementing the [`MoltValue`] marker trait is optional; without it, the type can
+//! still be saved with `Value::from_other` and retrieved with `Value::as_other` or
+//! `Value::as_copy`, which return `Option` and leave the error message to the caller.
+//!
 //! # Special Implementation Types
 //!
 //! Values can also be interpreted as two special types, `Script` and `VarName`.  The
-//! Interpreter uses the (non-public) `as_script` method to parse script bodies for
-//! evaluation; generally this means that a script will get parsed only once.
+//! Interpreter uses the `as_script` method to parse script bodies for
+//! evaluation; generally this means that a script will get parsed only once.  A value
+//! that is a _pure list_, i.e., a list that has never been converted to a string, isn't
+//! parsed at all: its elements are evaluated as the words of a single command.
 //!
 //! Similarly, `as_var_name` interprets a variable name reference as a `VarName`, which
 //! contains the variable name and, optionally, an array index.  This is usually hidden
 //! from the extension author by the `var` and `set_var` methods, but it is available if
 //! publically if needed.
 //!
+//! # Binary Data
+//!
+//! A value can also hold a string of bytes, as produced by the `binary format` command
+//! or by `Value::from_bytes`.  As in standard TCL, its string rep represents each byte as
+//! the character with the same code point, U+0000 to U+00FF, i.e., the bytes are
+//! interpreted as ISO-8859-1.  Conversely, `Value::as_bytes` interprets any string as
+//! bytes by taking the low eight bits of each character's code point.  The bytes are
+//! cached as the value's data rep, so binary data needn't round-trip through the string.
+//!
+//! The `expr` command and the control structures likewise cache an expression's
+//! scanned tokens in the expression's value, so that a loop condition is scanned only
+//! once however many times the loop repeats.
+//!
 //! [`Value`]: struct.Value.html
+//! [`MoltValue`]: trait.MoltValue.html
 
 use crate::dict::dict_to_string;
 use crate::dict::list_to_dict;
 use crate::expr::Datum;
+use crate::expr::ParsedExpr;
 use crate::list::get_list;
-use crate::list::list_to_string;
+use crate::list::list_to_string_cow;
 use crate::parser;
 use crate::parser::Script;
 use crate::types::Exception;
@@ -180,14 +217,26 @@ use crate::types::MoltList;
 use crate::types::VarName;
 use std::any::Any;
 use std::any::TypeId;
+#[cfg(not(feature = "sync-values"))]
+use std::cell::OnceCell;
+#[cfg(not(feature = "sync-values"))]
 use std::cell::RefCell;
-use std::cell::UnsafeCell;
 use std::fmt::Debug;
 use std::fmt::Display;
 use std::hash::Hash;
 use std::hash::Hasher;
-use std::rc::Rc;
+use std::ops::Range;
 use std::str::FromStr;
+#[cfg(feature = "sync-values")]
+use std::sync::OnceLock;
+#[cfg(feature = "sync-values")]
+use std::sync::PoisonError;
+#[cfg(feature = "sync-values")]
+use std::sync::RwLock;
+#[cfg(feature = "sync-values")]
+use std::sync::RwLockReadGuard;
+#[cfg(feature = "sync-values")]
+use std::sync::RwLockWriteGuard;
 
 //-----------------------------------------------------------------------------
 // Public Data Types
@@ -196,7 +245,7 @@ use std::str::FromStr;
 #[derive(Clone)]
 pub struct Value {
     /// The actual data, to be shared among multiple instances of `Value`.
-    inner: Rc<InnerValue>,
+    inner: MoltRc<InnerValue>,
 }
 
 impl Hash for Value {
@@ -207,11 +256,125 @@ impl Hash for Value {
     }
 }
 
-/// The inner value of a `Value`, to be wrapped in an `Rc<T>` so that `Values` can be shared.
+/// The reference-counted pointer used to share values and their data reps: `Rc`, or `Arc`
+/// with the `sync-values` feature.
+#[cfg(not(feature = "sync-values"))]
+pub type MoltRc<T> = std::rc::Rc<T>;
+
+/// The reference-counted pointer used to share values and their data reps: `Rc`, or `Arc`
+/// with the `sync-values` feature.
+#[cfg(feature = "sync-values")]
+pub type MoltRc<T> = std::sync::Arc<T>;
+
+/// A marker trait for the types that can be stored in a `Value`: every type by default,
+/// and with the `sync-values` feature, only types that are `Send` and `Sync`.
+#[cfg(not(feature = "sync-values"))]
+pub trait Shareable {}
+
+#[cfg(not(feature = "sync-values"))]
+impl<T: ?Sized> Shareable for T {}
+
+/// A marker trait for the types that can be stored in a `Value`: every type by default,
+/// and with the `sync-values` feature, only types that are `Send` and `Sync`.
+#[cfg(feature = "sync-values")]
+pub trait Shareable: Send + Sync {}
+
+#[cfg(feature = "sync-values")]
+impl<T: ?Sized + Send + Sync> Shareable for T {}
+
+/// The inner value of a `Value`, to be wrapped in a `MoltRc<T>` so that `Values` can be shared.
 #[derive(Debug)]
 struct InnerValue {
-    string_rep: UnsafeCell<Option<String>>,
-    data_rep: RefCell<DataRep>,
+    string_rep: StringCell,
+    data_rep: DataCell,
+}
+
+/// The cell holding a value's string rep, which is set at most once.
+#[cfg(not(feature = "sync-values"))]
+type StringCell = OnceCell<StringRep>;
+
+#[cfg(feature = "sync-values")]
+type StringCell = OnceLock<StringRep>;
+
+/// The cell holding a value's data rep, which changes as the value shimmers.
+#[cfg(not(feature = "sync-values"))]
+type DataCell = RefCell<DataRep>;
+
+/// With the `sync-values` feature, the data rep is guarded by a lock, which has the same
+/// `borrow` and `borrow_mut` interface as a `RefCell`.
+#[cfg(feature = "sync-values")]
+#[derive(Debug)]
+struct DataCell(RwLock<DataRep>);
+
+#[cfg(feature = "sync-values")]
+impl DataCell {
+    fn new(data: DataRep) -> Self {
+        Self(RwLock::new(data))
+    }
+
+    fn borrow(&self) -> RwLockReadGuard<'_, DataRep> {
+        self.0.read().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn borrow_mut(&self) -> RwLockWriteGuard<'_, DataRep> {
+        self.0.write().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// A `Value` in the form in which it's evaluated; see `Value::as_evaluable`.
+pub(crate) enum Evaluable {
+    /// The words of a single command, from a pure list.
+    Command(MoltRc<MoltList>),
+
+    /// A parsed script.
+    Script(MoltRc<Script>),
+}
+
+/// The structure of a `Value`'s data rep, for structured serialization; see
+/// `Value::structure`.
+#[cfg(feature = "serde")]
+pub(crate) enum Structure {
+    /// The value holds a list.
+    List(MoltRc<MoltList>),
+
+    /// The value holds a dictionary.
+    Dict(MoltRc<MoltDict>),
+
+    /// The value holds anything else, and is serialized as its string rep.
+    Scalar,
+}
+
+/// The string rep of a `Value`.  Once set, it never changes; and so a substring of one
+/// value's string rep can be shared by another value without copying it.
+///
+/// Note: an owned string rep costs one allocation for the string's bytes, in addition to
+/// the `Value`'s own `InnerValue`.  Storing an `Rc<str>` instead wouldn't reduce that,
+/// as an `Rc<str>` is also allocated separately, and would add a copy to `From<String>`.
+#[derive(Debug)]
+enum StringRep {
+    /// A string owned by this value.
+    Owned(String),
+
+    /// A string constant, e.g., the string rep of one of the interned values.
+    Static(&'static str),
+
+    /// A string shared with the application, or with other values.
+    Shared(MoltRc<String>),
+
+    /// The given byte range of another value's string rep.  The other value's string rep
+    /// is never itself a `Slice`.
+    Slice(Value, Range<usize>),
+}
+
+impl StringRep {
+    fn as_str(&self) -> &str {
+        match self {
+            StringRep::Owned(str) => str,
+            StringRep::Static(str) => str,
+            StringRep::Shared(str) => str,
+            StringRep::Slice(parent, range) => &parent.as_str()[range.clone()],
+        }
+    }
 }
 
 impl std::fmt::Debug for Value {
@@ -227,29 +390,58 @@ impl std::fmt::Debug for Value {
 impl Value {
     /// Creates a value whose `InnerValue` is defined by its string rep.
     fn inner_from_string(str: String) -> Self {
+        Value::inner_from_string_rep(StringRep::Owned(str))
+    }
+
+    /// Creates a value whose `InnerValue` is defined by the given `StringRep`.
+    fn inner_from_string_rep(rep: StringRep) -> Self {
         let inner = InnerValue {
-            string_rep: UnsafeCell::new(Some(str)),
-            data_rep: RefCell::new(DataRep::None),
+            string_rep: StringCell::from(rep),
+            data_rep: DataCell::new(DataRep::None),
         };
 
         Self {
-            inner: Rc::new(inner),
+            inner: MoltRc::new(inner),
+        }
+    }
+
+    /// Creates a value with the given constant string rep and the equivalent data rep,
+    /// for use as an interned value.
+    fn inner_from_static(str: &'static str, data: DataRep) -> Self {
+        let inner = InnerValue {
+            string_rep: StringCell::from(StringRep::Static(str)),
+            data_rep: DataCell::new(data),
+        };
+
+        Self {
+            inner: MoltRc::new(inner),
         }
     }
 
     /// Creates a value whose `InnerValue` is defined by its data rep.
     fn inner_from_data(data: DataRep) -> Self {
         let inner = InnerValue {
-            string_rep: UnsafeCell::new(None),
-            data_rep: RefCell::new(data),
+            string_rep: StringCell::new(),
+            data_rep: DataCell::new(data),
         };
 
         Self {
-            inner: Rc::new(inner),
+            inner: MoltRc::new(inner),
         }
     }
 }
 
+// The interned values: very common values that are shared rather than allocated anew
+// each time they are needed.  Each thread has its own, as a `Value` is `Sync` only with
+// the `sync-values` feature.  Sharing
+// is invisible to clients, as a shared value's string rep never changes; only its data rep
+// shimmers, as it would for any value.
+thread_local! {
+    static EMPTY: Value = Value::inner_from_static("", DataRep::None);
+    static ZERO: Value = Value::inner_from_static("0", DataRep::Int(0));
+    static ONE: Value = Value::inner_from_static("1", DataRep::Int(1));
+}
+
 impl Display for Value {
     /// The `Display` formatter for `Value`.  Outputs the value's string rep.
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
@@ -278,6 +470,9 @@ impl From<String> for Value {
     /// assert_eq!(value.as_str(), "My New String");
     /// ```
     fn from(str: String) -> Self {
+        if str.is_empty() {
+            return Value::empty();
+        }
         Value::inner_from_string(str)
     }
 }
@@ -293,10 +488,32 @@ impl From<&str> for Value {
     /// assert_eq!(value.as_str(), "My String Slice");
     /// ```
     fn from(str: &str) -> Self {
+        if str.is_empty() {
+            return Value::empty();
+        }
         Value::inner_from_string(str.to_string())
     }
 }
 
+impl From<MoltRc<String>> for Value {
+    /// Creates a new `Value` from the given shared string.  The `Value` shares the string
+    /// rather than copying it.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use molt::types::MoltRc;
+    /// use molt::types::Value;
+    ///
+    /// let string = MoltRc::new(String::from("My Shared String"));
+    /// let value = Value::from(MoltRc::clone(&string));
+    /// assert_eq!(value.as_str(), "My Shared String");
+    /// ```
+    fn from(str: MoltRc<String>) -> Self {
+        Value::inner_from_string_rep(StringRep::Shared(str))
+    }
+}
+
 impl From<&String> for Value {
     /// Creates a new `Value` from the given string reference.
     ///
@@ -327,7 +544,12 @@ impl From<bool> for Value {
     /// assert_eq!(value.as_str(), "0");
     /// ```
     fn from(flag: bool) -> Self {
-        Value::inner_from_data(DataRep::Bool(flag))
+        // The interned "0" and "1" values are valid booleans.
+        if flag {
+            ONE.with(Value::clone)
+        } else {
+            ZERO.with(Value::clone)
+        }
     }
 }
 
@@ -347,7 +569,7 @@ impl From<MoltDict> for Value {
     /// assert_eq!(value.as_str(), "abc 123");
     /// ```
     fn from(dict: MoltDict) -> Self {
-        Value::inner_from_data(DataRep::Dict(Rc::new(dict)))
+        Value::inner_from_data(DataRep::Dict(MoltRc::new(dict)))
     }
 }
 
@@ -363,7 +585,11 @@ impl From<MoltInt> for Value {
     /// assert_eq!(value.as_str(), "123");
     /// ```
     fn from(int: MoltInt) -> Self {
-        Value::inner_from_data(DataRep::Int(int))
+        match int {
+            0 => ZERO.with(Value::clone),
+            1 => ONE.with(Value::clone),
+            _ => Value::inner_from_data(DataRep::Int(int)),
+        }
     }
 }
 
@@ -405,7 +631,16 @@ impl From<MoltList> for Value {
     /// assert_eq!(value.as_str(), "1234 abc");
     /// ```
     fn from(list: MoltList) -> Self {
-        Value::inner_from_data(DataRep::List(Rc::new(list)))
+        Value::inner_from_data(DataRep::List(MoltRc::new(list)))
+    }
+}
+
+/// Any external type that implements [`MoltValue`](trait.MoltValue.html) can be
+/// converted directly into a `Value`, as by
+/// [`Value::from_other`](struct.Value.html#method.from_other).
+impl<T: MoltValue> From<T> for Value {
+    fn from(value: T) -> Self {
+        Value::from_other(value)
     }
 }
 
@@ -422,18 +657,15 @@ impl From<&[Value]> for Value {
     /// assert_eq!(value.as_str(), "1234 abc");
     /// ```
     fn from(list: &[Value]) -> Self {
-        Value::inner_from_data(DataRep::List(Rc::new(list.to_vec())))
+        Value::inner_from_data(DataRep::List(MoltRc::new(list.to_vec())))
     }
 }
 
 impl Value {
     /// Returns the empty `Value`, a value whose string representation is the empty
-    /// string.
-    ///
-    /// TODO: This should really be a constant, but there's way to build it as one
-    /// unless I use lazy_static.
+    /// string.  The empty value is interned, so this doesn't allocate.
     pub fn empty() -> Value {
-        Value::inner_from_string("".into())
+        EMPTY.with(Value::clone)
     }
 
     /// Returns the value's string representation as a reference-counted
@@ -450,24 +682,62 @@ impl Value {
     /// assert_eq!(value.as_str(), "123");
     /// ```
     pub fn as_str(&self) -> &str {
-        // FIRST, get the string rep, computing it from the data_rep if necessary.
-        // self.inner.string_rep.get_or_init(|| (self.inner.data_rep.borrow()).to_string())
-
-        // NOTE: This method is the only place where the string_rep is queried.
-        let slot = unsafe { &*self.inner.string_rep.get() };
+        // NOTE: This method is the only place where the string_rep is set, and the only
+        // place where it's queried, other than `from_substring`, which needs to know
+        // whether its parent is a slice, and `as_evaluable`, which needs to know whether
+        // it has been set.  Once set, it never changes.
+        self.inner
+            .string_rep
+            .get_or_init(|| StringRep::Owned((self.inner.data_rep.borrow()).to_string()))
+            .as_str()
+    }
 
-        if let Some(inner) = slot {
-            return inner;
-        }
+    /// Creates a new `Value` whose string rep is the given character.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use molt::types::Value;
+    /// let value = Value::from_char('x');
+    /// assert_eq!(value.as_str(), "x");
+    /// ```
+    pub fn from_char(ch: char) -> Value {
+        Value::inner_from_string(ch.to_string())
+    }
 
-        // NOTE: This is the only place where the string_rep is set.
-        // Because we returned it if it was Some, it is only ever set once.
-        // Thus, this is safe: as_str() is the only way to retrieve the string_rep,
-        // and it computes the string_rep lazily after which it is immutable.
-        let slot = unsafe { &mut *self.inner.string_rep.get() };
-        *slot = Some((self.inner.data_rep.borrow()).to_string());
+    /// Creates a new `Value` whose string rep is the given byte range of the `parent`'s
+    /// string rep.  The new value shares the parent's string rather than copying it; this
+    /// makes it cheap to split a string into many pieces, at the cost of keeping the entire
+    /// parent string alive as long as any of the pieces.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range is out of bounds, or doesn't begin and end on character
+    /// boundaries, as for `&str` indexing.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use molt::types::Value;
+    /// let parent = Value::from("abc,def");
+    /// let value = Value::from_substring(&parent, 4..7);
+    /// assert_eq!(value.as_str(), "def");
+    /// ```
+    pub fn from_substring(parent: &Value, range: Range<usize>) -> Value {
+        // FIRST, make sure the range is valid, panicking if not.
+        let _ = &parent.as_str()[range.clone()];
+
+        // NEXT, if the parent is itself a slice, share its parent instead, so that chains of
+        // slices don't build up.
+        let rep = match parent.inner.string_rep.get() {
+            Some(StringRep::Slice(root, outer)) => StringRep::Slice(
+                root.clone(),
+                (outer.start + range.start)..(outer.start + range.end),
+            ),
+            _ => StringRep::Slice(parent.clone(), range),
+        };
 
-        slot.as_ref().expect("string rep")
+        Value::inner_from_string_rep(rep)
     }
 
     /// Tries to return the `Value` as a `bool`, parsing the
@@ -580,20 +850,20 @@ impl Value {
         }
     }
 
-    /// Tries to return the `Value` as an `Rc<MoltDict>`, parsing the
+    /// Tries to return the `Value` as a `MoltRc<MoltDict>`, parsing the
     /// value's string representation if necessary.
     ///
     /// # Example
     ///
     /// ```
-    /// use std::rc::Rc;
+    /// use molt::types::MoltRc;
     /// use molt::types::Value;
     /// use molt::types::MoltDict;
     /// use molt::types::Exception;
     /// # fn dummy() -> Result<(),Exception> {
     ///
     /// let value = Value::from("abc 1234");
-    /// let dict: Rc<MoltDict> = value.as_dict()?;
+    /// let dict: MoltRc<MoltDict> = value.as_dict()?;
     ///
     /// assert_eq!(dict.len(), 1);
     /// assert_eq!(dict.get(&Value::from("abc")), Some(&Value::from("1234")));
@@ -601,7 +871,7 @@ impl Value {
     /// # Ok(())
     /// # }
     /// ```
-    pub fn as_dict(&self) -> Result<Rc<MoltDict>, Exception> {
+    pub fn as_dict(&self) -> Result<MoltRc<MoltDict>, Exception> {
         // FIRST, if we have the desired type, return it.
         if let DataRep::Dict(dict) = &*self.inner.data_rep.borrow() {
             return Ok(dict.clone());
@@ -615,7 +885,7 @@ impl Value {
             return molt_err!("missing value to go with key");
         }
 
-        let dict = Rc::new(list_to_dict(&list));
+        let dict = MoltRc::new(list_to_dict(&list));
 
         *self.inner.data_rep.borrow_mut() = DataRep::Dict(dict.clone());
 
@@ -808,20 +1078,20 @@ impl Value {
         }
     }
 
-    /// Tries to return the `Value` as an `Rc<MoltList>`, parsing the
+    /// Tries to return the `Value` as a `MoltRc<MoltList>`, parsing the
     /// value's string representation if necessary.
     ///
     /// # Example
     ///
     /// ```
-    /// use std::rc::Rc;
+    /// use molt::types::MoltRc;
     /// use molt::types::Value;
     /// use molt::types::MoltList;
     /// use molt::types::Exception;
     /// # fn dummy() -> Result<String,Exception> {
     ///
     /// let value = Value::from("1234 abc");
-    /// let list: Rc<MoltList> = value.as_list()?;
+    /// let list: MoltRc<MoltList> = value.as_list()?;
     /// assert_eq!(list.len(), 2);
     ///
     /// assert_eq!(list[0], Value::from("1234"));
@@ -830,7 +1100,7 @@ impl Value {
     /// # Ok("dummy".to_string())
     /// # }
     /// ```
-    pub fn as_list(&self) -> Result<Rc<MoltList>, Exception> {
+    pub fn as_list(&self) -> Result<MoltRc<MoltList>, Exception> {
         // FIRST, if we have the desired type, return it.
         if let DataRep::List(list) = &*self.inner.data_rep.borrow() {
             return Ok(list.clone());
@@ -838,7 +1108,7 @@ impl Value {
 
         // NEXT, try to parse the string_rep as a list.
         let str = self.as_str();
-        let list = Rc::new(get_list(str)?);
+        let list = MoltRc::new(get_list(str)?);
         *self.inner.data_rep.borrow_mut() = DataRep::List(list.clone());
 
         Ok(list)
@@ -872,13 +1142,68 @@ impl Value {
         Ok((&*self.as_list()?).to_owned())
     }
 
-    /// Tries to return the `Value` as an `Rc<Script>`, parsing the
-    /// value's string representation if necessary.
+    /// Creates a new `Value` whose data representation is the given string of bytes.  Its
+    /// string rep, if needed, represents each byte as the character with the same code
+    /// point.  See the [module level documentation](index.html#binary-data).
+    ///
+    /// # Example
     ///
-    /// For internal use only.  Note: this is the normal way to convert a script string
-    /// into a Script object.  Converting the Script back into a Tcl string is not
-    /// currently supported.
-    pub(crate) fn as_script(&self) -> Result<Rc<Script>, Exception> {
+    /// ```
+    /// use molt::types::Value;
+    ///
+    /// let value = Value::from_bytes(vec![0x41, 0x42, 0xe9]);
+    /// assert_eq!(value.as_str(), "AB\u{e9}");
+    /// ```
+    pub fn from_bytes(bytes: Vec<u8>) -> Value {
+        Value::inner_from_data(DataRep::Bytes(MoltRc::new(bytes)))
+    }
+
+    /// Returns the `Value` as a string of bytes, converting the string rep if necessary by
+    /// taking the low eight bits of each character.  See the
+    /// [module level documentation](index.html#binary-data).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use molt::types::Value;
+    ///
+    /// let value = Value::from("AB\u{e9}");
+    /// assert_eq!(*value.as_bytes(), vec![0x41, 0x42, 0xe9]);
+    /// ```
+    pub fn as_bytes(&self) -> MoltRc<Vec<u8>> {
+        // FIRST, if we have the desired type, return it.
+        if let DataRep::Bytes(bytes) = &*self.inner.data_rep.borrow() {
+            return bytes.clone();
+        }
+
+        // NEXT, convert the string rep.
+        let bytes = MoltRc::new(self.as_str().chars().map(|c| c as u32 as u8).collect());
+        *self.inner.data_rep.borrow_mut() = DataRep::Bytes(MoltRc::clone(&bytes));
+
+        bytes
+    }
+
+    /// Tries to return the `Value` as a `MoltRc<Script>`, parsing the
+    /// value's string representation if necessary.  The parsed script is cached as the
+    /// value's data rep, so that evaluating the value later doesn't parse it again; an
+    /// application can use this to check a callback script's syntax when it is registered,
+    /// rather than when it is first called.
+    ///
+    /// Note: this is the normal way to convert a script string into a Script object.
+    /// Converting the Script back into a Tcl string is not currently supported.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use molt::types::Value;
+    ///
+    /// let script = Value::from("set a 1; set b 2");
+    /// assert_eq!(script.as_script().unwrap().commands().len(), 2);
+    ///
+    /// let bad = Value::from("set a {1");
+    /// assert!(bad.as_script().is_err());
+    /// ```
+    pub fn as_script(&self) -> Result<MoltRc<Script>, Exception> {
         // FIRST, if we have the desired type, return it.
         if let DataRep::Script(script) = &*self.inner.data_rep.borrow() {
             return Ok(script.clone());
@@ -886,13 +1211,61 @@ impl Value {
 
         // NEXT, try to parse the string_rep as a script.
         let str = self.as_str();
-        let script = Rc::new(parser::parse(str)?);
+        let script = MoltRc::new(parser::parse(str)?);
         *self.inner.data_rep.borrow_mut() = DataRep::Script(script.clone());
 
         Ok(script)
     }
 
-    /// Returns the `Value` as an `Rc<VarName>`, parsing the
+    /// Returns the `Value` as a parsed expression, scanning the value's string
+    /// representation if necessary.  As with `as_script`, the parsed expression is cached
+    /// as the value's data rep, so that a loop condition, for example, is scanned only once
+    /// no matter how many times the loop repeats.  Scanning can't fail; lexical errors are
+    /// reported when the expression is evaluated.
+    pub(crate) fn as_expr(&self) -> MoltRc<ParsedExpr> {
+        // FIRST, if we have the desired type, return it.
+        if let DataRep::Expr(expr) = &*self.inner.data_rep.borrow() {
+            return expr.clone();
+        }
+
+        // NEXT, scan the string_rep as an expression.
+        let expr = MoltRc::new(ParsedExpr::parse(self.as_str()));
+        *self.inner.data_rep.borrow_mut() = DataRep::Expr(expr.clone());
+
+        expr
+    }
+
+    /// Returns the `Value` in the form in which it's evaluated: as the words of a single
+    /// command if it's a _pure list_, a list that has no string rep, and otherwise as a
+    /// parsed script, parsing the value's string rep if necessary.  The words of a pure
+    /// list can be evaluated directly, without converting the list to a string and parsing
+    /// it.  The string rep is checked only if the value's data rep is already a list.
+    pub(crate) fn as_evaluable(&self) -> Result<Evaluable, Exception> {
+        // FIRST, if it's already a script or a pure list, return it.  See as_str(); this
+        // only checks whether the string rep has been set.
+        match &*self.inner.data_rep.borrow() {
+            DataRep::Script(script) => return Ok(Evaluable::Script(script.clone())),
+            DataRep::List(list) if self.inner.string_rep.get().is_none() => {
+                return Ok(Evaluable::Command(list.clone()))
+            }
+            _ => {}
+        }
+
+        // NEXT, parse it as a script.
+        self.as_script().map(Evaluable::Script)
+    }
+
+    /// Returns the structure of the value's current data rep, without converting it.
+    #[cfg(feature = "serde")]
+    pub(crate) fn structure(&self) -> Structure {
+        match &*self.inner.data_rep.borrow() {
+            DataRep::List(list) => Structure::List(list.clone()),
+            DataRep::Dict(dict) => Structure::Dict(dict.clone()),
+            _ => Structure::Scalar,
+        }
+    }
+
+    /// Returns the `Value` as a `MoltRc<VarName>`, parsing the
     /// value's string representation if necessary.  This type is usually hidden by the
     /// `Interp`'s `var` and `set_var` methods, which use it implicitly; however it is
     /// available to extension authors if need be.
@@ -912,14 +1285,14 @@ impl Value {
     /// assert_eq!(var_name.name(), "my_array");
     /// assert_eq!(var_name.index(), Some("1"));
     /// ```
-    pub fn as_var_name(&self) -> Rc<VarName> {
+    pub fn as_var_name(&self) -> MoltRc<VarName> {
         // FIRST, if we have the desired type, return it.
         if let DataRep::VarName(var_name) = &*self.inner.data_rep.borrow() {
             return var_name.clone();
         }
 
         // NEXT, try to parse the string_rep as a variable name.
-        let var_name = Rc::new(parser::parse_varname_literal(self.as_str()));
+        let var_name = MoltRc::new(parser::parse_varname_literal(self.as_str()));
 
         *self.inner.data_rep.borrow_mut() = DataRep::VarName(var_name.clone());
         var_name
@@ -949,9 +1322,9 @@ impl Value {
     /// retrieve a `MyType` value from a `Value`.
     pub fn from_other<T: 'static>(value: T) -> Value
     where
-        T: Display + Debug,
+        T: Display + Debug + Shareable,
     {
-        Value::inner_from_data(DataRep::Other(Rc::new(value)))
+        Value::inner_from_data(DataRep::Other(MoltRc::new(value)))
     }
 
     /// Tries to interpret the `Value` as a value of external type `T`, parsing
@@ -963,10 +1336,10 @@ impl Value {
     ///
     /// # Return Value
     ///
-    /// The value is returned as an `Rc<T>`, as this allows the client to
+    /// The value is returned as a `MoltRc<T>`, as this allows the client to
     /// use the value freely and clone it efficiently if needed.
     ///
-    /// This method returns `Option<Rc<T>>` rather than `Result<Rc<T>,Exception>`
+    /// This method returns `Option<MoltRc<T>>` rather than `Result<MoltRc<T>,Exception>`
     /// because it is up to the caller to provide a meaningful error message.
     /// It is normal for externally defined types to wrap this function in a function
     /// that does so; see the [module level documentation](index.html) for an example.
@@ -980,7 +1353,7 @@ impl Value {
     /// // Just a normal Molt string
     /// let value = Value::from("#112233");
     ///
-    /// // Retrieve it as an Option<Rc<HexColor>>:
+    /// // Retrieve it as an Option<MoltRc<HexColor>>:
     /// let color = value.as_other::<HexColor>()
     ///
     /// if color.is_some() {
@@ -990,13 +1363,13 @@ impl Value {
     ///     let b = *color.blue();
     /// }
     /// ```
-    pub fn as_other<T: 'static>(&self) -> Option<Rc<T>>
+    pub fn as_other<T>(&self) -> Option<MoltRc<T>>
     where
-        T: Display + Debug + FromStr,
+        T: 'static + Display + Debug + FromStr + Shareable,
     {
         // FIRST, if we have the desired type, return it.
         if let DataRep::Other(other) = &*self.inner.data_rep.borrow() {
-            // other is an &Rc<MoltAny>
+            // other is an &MoltRc<MoltAny>
             if let Ok(out) = other.clone().downcast::<T>() {
                 return Some(out);
             }
@@ -1007,9 +1380,9 @@ impl Value {
         let str = self.as_str();
 
         if let Ok(tval) = str.parse::<T>() {
-            let tval = Rc::new(tval);
+            let tval = MoltRc::new(tval);
             let out = tval.clone();
-            *self.inner.data_rep.borrow_mut() = DataRep::Other(Rc::new(tval));
+            *self.inner.data_rep.borrow_mut() = DataRep::Other(MoltRc::new(tval));
             return Some(out);
         }
 
@@ -1051,11 +1424,11 @@ impl Value {
     /// ```
     pub fn as_copy<T: 'static>(&self) -> Option<T>
     where
-        T: Display + Debug + FromStr + Copy,
+        T: Display + Debug + FromStr + Copy + Shareable,
     {
         // FIRST, if we have the desired type, return it.
         if let DataRep::Other(other) = &*self.inner.data_rep.borrow() {
-            // other is an &Rc<MoltAny>
+            // other is an &MoltRc<MoltAny>
             if let Ok(out) = other.clone().downcast::<T>() {
                 return Some(*out);
             }
@@ -1066,9 +1439,9 @@ impl Value {
         let str = self.as_str();
 
         if let Ok(tval) = str.parse::<T>() {
-            let tval = Rc::new(tval);
+            let tval = MoltRc::new(tval);
             let out = tval.clone();
-            *self.inner.data_rep.borrow_mut() = DataRep::Other(Rc::new(tval));
+            *self.inner.data_rep.borrow_mut() = DataRep::Other(MoltRc::new(tval));
             return Some(*out);
         }
 
@@ -1076,6 +1449,20 @@ impl Value {
         None
     }
 
+    /// Tries to interpret the `Value` as a value of external type `T`, parsing
+    /// the string representation if necessary, as for
+    /// [`Value::as_other`](#method.as_other).  Returns the standard error message,
+    /// `expected <typename> but got "<string>"`, if the value can't be interpreted as a `T`;
+    /// the type name is given by [`MoltValue::type_name`](trait.MoltValue.html#method.type_name).
+    ///
+    /// See the [module level documentation](index.html) for an example.
+    pub fn as_external<T: MoltValue>(&self) -> Result<MoltRc<T>, Exception> {
+        match self.as_other::<T>() {
+            Some(out) => Ok(out),
+            None => molt_err!("expected {} but got \"{}\"", T::type_name(), self.as_str()),
+        }
+    }
+
     /// For use by `expr::expr` in parsing out `Values`.
     pub(crate) fn already_number(&self) -> Option<Datum> {
         let iref = self.inner.data_rep.borrow();
@@ -1088,12 +1475,44 @@ impl Value {
     }
 }
 
+//-----------------------------------------------------------------------------
+// The MoltValue Trait: a convenience for defining external types.
+
+/// A marker trait for external types, i.e., types that can be saved in a `Value` and
+/// parsed back out of its string rep.  Implementing it allows the type to be converted
+/// into a `Value` with `Value::from` (and hence returned with `molt_ok!`), and to be
+/// retrieved with [`Value::as_external`](struct.Value.html#method.as_external), which
+/// produces a standard error message on failure.
+///
+/// See the [module level documentation](index.html) for an example.
+pub trait MoltValue: Display + Debug + FromStr + Shareable + 'static {
+    /// The type's name, for use in error messages.  Defaults to the type's name without
+    /// its module path, e.g., `Flavor` rather than `my_crate::flavor::Flavor`.
+    fn type_name() -> String {
+        short_type_name(std::any::type_name::<Self>()).into()
+    }
+}
+
+/// Strips the module path from a full type name as returned by `std::any::type_name`,
+/// leaving any generic parameters alone.
+fn short_type_name(name: &str) -> &str {
+    let base = match name.find('<') {
+        Some(i) => &name[..i],
+        None => name,
+    };
+
+    match base.rfind("::") {
+        Some(i) => &name[i + 2..],
+        None => name,
+    }
+}
+
 //-----------------------------------------------------------------------------
 // The MoltAny Trait: a tool for handling external types.
 
 /// This trait allows us to except "other" types, and still compute their
 /// string rep on demand.
-trait MoltAny: Any + Display + Debug {
+trait MoltAny: Any + Display + Debug + Shareable {
     fn as_any(&self) -> &dyn Any;
     fn as_any_mut(&mut self) -> &mut dyn Any;
     fn into_any(self: Box<Self>) -> Box<dyn Any>;
@@ -1105,17 +1524,17 @@ impl dyn MoltAny {
         TypeId::of::<T>() == self.type_id()
     }
 
-    /// Downcast an `Rc<MoltAny>` to an `Rc<T>`
-    fn downcast<T: 'static>(self: Rc<Self>) -> Result<Rc<T>, Rc<Self>> {
+    /// Downcast a `MoltRc<MoltAny>` to a `MoltRc<T>`
+    fn downcast<T: 'static>(self: MoltRc<Self>) -> Result<MoltRc<T>, MoltRc<Self>> {
         if self.is::<T>() {
-            unsafe { Ok(Rc::from_raw(Rc::into_raw(self) as _)) }
+            unsafe { Ok(MoltRc::from_raw(MoltRc::into_raw(self) as _)) }
         } else {
             Err(self)
         }
     }
 }
 
-impl<T: Any + Display + Debug> MoltAny for T {
+impl<T: Any + Display + Debug + Shareable> MoltAny for T {
     fn as_any(&self) -> &dyn Any {
         self
     }
@@ -1136,8 +1555,11 @@ enum DataRep {
     /// A Boolean
     Bool(bool),
 
+    /// A string of bytes
+    Bytes(MoltRc<Vec<u8>>),
+
     /// A Molt Dictionary
-    Dict(Rc<MoltDict>),
+    Dict(MoltRc<MoltDict>),
 
     /// A Molt integer
     Int(MoltInt),
@@ -1146,16 +1568,19 @@ enum DataRep {
     Flt(MoltFloat),
 
     /// A Molt List
-    List(Rc<MoltList>),
+    List(MoltRc<MoltList>),
 
     /// A Script
-    Script(Rc<Script>),
+    Script(MoltRc<Script>),
+
+    /// A parsed expression
+    Expr(MoltRc<ParsedExpr>),
 
     /// A Variable Name
-    VarName(Rc<VarName>),
+    VarName(MoltRc<VarName>),
 
     /// An external data type
-    Other(Rc<dyn MoltAny>),
+    Other(MoltRc<dyn MoltAny>),
 
     /// The Value has no data rep at present.
     None,
@@ -1165,11 +1590,16 @@ impl Display for DataRep {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         match self {
             DataRep::Bool(flag) => write!(f, "{}", if *flag { 1 } else { 0 }),
+            DataRep::Bytes(bytes) => {
+                let str: String = bytes.iter().map(|&byte| char::from(byte)).collect();
+                write!(f, "{}", str)
+            }
             DataRep::Dict(dict) => write!(f, "{}", dict_to_string(&*dict)),
             DataRep::Int(int) => write!(f, "{}", int),
             DataRep::Flt(flt) => Value::fmt_float(f, *flt),
-            DataRep::List(list) => write!(f, "{}", list_to_string(&*list)),
+            DataRep::List(list) => write!(f, "{}", list_to_string_cow(list)),
             DataRep::Script(script) => write!(f, "{:?}", script),
+            DataRep::Expr(expr) => write!(f, "{:?}", expr),
             DataRep::VarName(var_name) => write!(f, "{:?}", var_name),
             DataRep::Other(other) => write!(f, "{}", other),
             DataRep::None => write!(f, ""),
@@ -1181,6 +1611,8 @@ impl Display for DataRep {
 mod tests {
     use super::*;
     use crate::dict::dict_new;
+    use crate::molt_ok;
+    use crate::types::MoltResult;
     use std::fmt;
     use std::str::FromStr;
 
@@ -1233,6 +1665,40 @@ mod tests {
         assert_ne!(val, val3);
     }
 
+    #[test]
+    fn interned_values() {
+        // The common values are shared.
+        assert!(MoltRc::ptr_eq(
+            &Value::empty().inner,
+            &Value::from("").inner
+        ));
+        assert!(MoltRc::ptr_eq(
+            &Value::empty().inner,
+            &Value::from(String::new()).inner
+        ));
+        assert!(MoltRc::ptr_eq(
+            &Value::from(0).inner,
+            &Value::from(false).inner
+        ));
+        assert!(MoltRc::ptr_eq(
+            &Value::from(1).inner,
+            &Value::from(true).inner
+        ));
+        assert!(!MoltRc::ptr_eq(
+            &Value::from(2).inner,
+            &Value::from(2).inner
+        ));
+
+        // Shimmering a shared value doesn't change its meaning.
+        let one = Value::from(1);
+        assert_eq!(one.as_list().unwrap().len(), 1);
+        assert_eq!(Value::from(true).as_bool(), Ok(true));
+        assert_eq!(Value::from(true).as_int(), Ok(1));
+        assert_eq!(Value::from(false).as_str(), "0");
+        assert!(Value::empty().as_list().unwrap().is_empty());
+        assert_eq!(Value::empty().as_str(), "");
+    }
+
     #[test]
     fn from_bool() {
         // Using From<bool>
@@ -1459,6 +1925,23 @@ mod tests {
         assert_eq!(list[1].to_string(), "def".to_string());
     }
 
+    #[test]
+    fn bytes() {
+        // From bytes to string and back.
+        let value = Value::from_bytes(vec![0, 0x41, 0x7f, 0x80, 0xff]);
+        assert_eq!(value.as_str(), "\u{0}A\u{7f}\u{80}\u{ff}");
+        assert_eq!(*value.as_bytes(), vec![0, 0x41, 0x7f, 0x80, 0xff]);
+
+        // From string to bytes: each character's low byte.
+        let value = Value::from("a\u{e9}\u{20ac}");
+        assert_eq!(*value.as_bytes(), vec![0x61, 0xe9, 0xac]);
+        assert_eq!(value.as_str(), "a\u{e9}\u{20ac}");
+
+        // The bytes are cached.
+        let value = Value::from("abc");
+        assert!(MoltRc::ptr_eq(&value.as_bytes(), &value.as_bytes()));
+    }
+
     #[test]
     fn as_script() {
         let val = Value::from("a");
@@ -1466,6 +1949,16 @@ mod tests {
 
         let val = Value::from("a {b");
         assert_eq!(val.as_script(), molt_err!("missing close-brace"));
+
+        // The parsed script is cached, and reparsed only after the value shimmers.
+        let val = Value::from("set a 1; set b 2");
+        let script = val.as_script().unwrap();
+        assert!(MoltRc::ptr_eq(&script, &val.as_script().unwrap()));
+
+        assert!(val.as_list().is_ok());
+        let reparsed = val.as_script().unwrap();
+        assert!(!MoltRc::ptr_eq(&script, &reparsed));
+        assert_eq!(*script, *reparsed);
     }
 
     #[test]
@@ -1477,6 +1970,25 @@ mod tests {
         let val = Value::from("a(b)");
         assert_eq!(val.as_var_name().name(), "a");
         assert_eq!(val.as_var_name().index(), Some("b"));
+
+        // Multi-byte characters in the name and index
+        let val = Value::from("é(ü)");
+        assert_eq!(val.as_var_name().name(), "é");
+        assert_eq!(val.as_var_name().index(), Some("ü"));
+    }
+
+    #[test]
+    fn as_var_name_shimmering() {
+        // The cached variable name doesn't change the string rep, and is recomputed
+        // if the value is used as something else in the meantime.
+        let val = Value::from("a(1)");
+        assert_eq!(val.as_var_name().name(), "a");
+        assert_eq!(val.as_str(), "a(1)");
+
+        assert_eq!(val.as_list().unwrap().len(), 1);
+        assert_eq!(val.as_var_name().name(), "a");
+        assert_eq!(val.as_var_name().index(), Some("1"));
+        assert_eq!(val.as_str(), "a(1)");
     }
 
     #[test]
@@ -1489,16 +2001,70 @@ mod tests {
         assert_eq!(listval.as_str(), "abc def");
     }
 
+    #[test]
+    fn from_rc_string() {
+        let string = MoltRc::new(String::from("abc def"));
+        let value = Value::from(MoltRc::clone(&string));
+        assert_eq!(value.as_str(), "abc def");
+        assert_eq!(MoltRc::strong_count(&string), 2);
+
+        // The value can be parsed without copying the string.
+        assert_eq!(value.as_list().unwrap().len(), 2);
+        assert_eq!(value.as_str(), "abc def");
+    }
+
+    #[test]
+    fn from_char() {
+        assert_eq!(Value::from_char('a').as_str(), "a");
+        assert_eq!(Value::from_char('é').as_str(), Value::from("é").as_str());
+    }
+
+    #[test]
+    fn from_substring() {
+        let parent = Value::from("one,two,thrée");
+        let pieces: Vec<Value> = [0..3, 4..7, 8..14, 3..3]
+            .iter()
+            .map(|range| Value::from_substring(&parent, range.clone()))
+            .collect();
+
+        assert_eq!(pieces[0], Value::from("one"));
+        assert_eq!(pieces[1].as_str(), "two");
+        assert_eq!(pieces[2].as_str(), "thrée");
+        assert_eq!(pieces[3].as_str(), "");
+
+        // Substrings have data reps, like any other value.
+        let number = Value::from_substring(&Value::from("x=123"), 2..5);
+        assert_eq!(number.as_int(), Ok(123));
+
+        // A substring of a substring shares the original parent.
+        let inner = Value::from_substring(&pieces[2], 2..6);
+        assert_eq!(inner.as_str(), "rée");
+        drop(pieces);
+        assert_eq!(inner.as_str(), "rée");
+
+        // Shared strings and computed string reps can be sliced too.
+        let shared = Value::from(MoltRc::new(String::from("shared")));
+        assert_eq!(Value::from_substring(&shared, 0..3).as_str(), "sha");
+        let list = Value::from(vec![Value::from("a"), Value::from("b c")]);
+        assert_eq!(Value::from_substring(&list, 2..7).as_str(), "{b c}");
+    }
+
+    #[test]
+    #[should_panic]
+    fn from_substring_not_char_boundary() {
+        Value::from_substring(&Value::from("é"), 0..1);
+    }
+
     #[test]
     fn from_to_flavor() {
-        // Give a Flavor, get an Rc<Flavor> back.
+        // Give a Flavor, get a MoltRc<Flavor> back.
         let myval = Value::from_other(Flavor::SALTY);
         let result = myval.as_other::<Flavor>();
         assert!(result.is_some());
         let out = result.unwrap();
         assert_eq!(*out, Flavor::SALTY);
 
-        // Give a String, get an Rc<Flavor> back.
+        // Give a String, get a MoltRc<Flavor> back.
         let myval = Value::from("sweet");
         let result = myval.as_other::<Flavor>();
         assert!(result.is_some());
@@ -1513,6 +2079,39 @@ mod tests {
         assert_eq!(out, Flavor::SALTY);
     }
 
+    #[test]
+    fn as_external_flavor() {
+        // A Flavor converts into a Value, and converts back again.
+        let myval = Value::from(Flavor::SALTY);
+        assert_eq!(myval.as_str(), "salty");
+        assert_eq!(*myval.as_external::<Flavor>().unwrap(), Flavor::SALTY);
+
+        // A String converts into a Flavor.
+        let myval = Value::from("sweet");
+        assert_eq!(*myval.as_external::<Flavor>().unwrap(), Flavor::SWEET);
+
+        // Anything else yields the standard error.
+        let myval = Value::from("bitter");
+        assert_eq!(
+            myval.as_external::<Flavor>(),
+            molt_err!("expected Flavor but got \"bitter\"")
+        );
+
+        // And so molt_ok! works with a Flavor.
+        let result: MoltResult = molt_ok!(Flavor::SWEET);
+        assert_eq!(result.unwrap().as_str(), "sweet");
+    }
+
+    #[test]
+    fn test_short_type_name() {
+        assert_eq!(short_type_name("Flavor"), "Flavor");
+        assert_eq!(short_type_name("my_crate::flavor::Flavor"), "Flavor");
+        assert_eq!(
+            short_type_name("my_crate::Wrapper<my_crate::Flavor>"),
+            "Wrapper<my_crate::Flavor>"
+        );
+    }
+
     #[test]
     fn already_number() {
         // Can retrieve a DataRep::Int as a Datum::Int.
@@ -1534,6 +2133,24 @@ mod tests {
 
     // Sample external type, used for testing.
 
+    #[test]
+    #[cfg(feature = "sync-values")]
+    fn value_is_send() {
+        fn assert_send<T: Send>() {}
+        fn assert_sync<T: Sync>() {}
+
+        assert_send::<Value>();
+        assert_sync::<Value>();
+        assert_send::<MoltList>();
+
+        // Build a list on a worker thread, and use it on this one.
+        let list = std::thread::spawn(|| Value::from(vec![Value::from(1), Value::from("two")]))
+            .join()
+            .unwrap();
+        assert_eq!(list.as_list().unwrap().len(), 2);
+        assert_eq!(list.as_str(), "1 two");
+    }
+
     #[derive(Debug, PartialEq, Copy, Clone)]
     pub enum Flavor {
         SALTY,
@@ -1565,4 +2182,6 @@ mod tests {
             }
         }
     }
+
+    impl MoltValue for Flavor {}
 }
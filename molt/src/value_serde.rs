@@ -0,0 +1,287 @@
+//! Serializing and deserializing Values with serde
+//!
+//! With the `serde` feature, [`Value`] implements serde's `Serialize` and `Deserialize`
+//! traits.  By default a `Value` is serialized as its string rep, which is exactly what a
+//! script sees, and is deserialized from a string; so any `Value` survives the round trip,
+//! though its data rep is recomputed as needed.  For convenience, numbers and booleans
+//! are also accepted when deserializing.
+//!
+//! ```
+//! use molt::types::*;
+//!
+//! let value = Value::from(vec![Value::from("a b"), Value::from(5)]);
+//! let json = serde_json::to_string(&value).unwrap();
+//! assert_eq!(json, "\"{a b} 5\"");
+//!
+//! let copy: Value = serde_json::from_str(&json).unwrap();
+//! assert_eq!(copy, value);
+//! ```
+//!
+//! The [`Structured`] wrapper serializes a `Value` by its structure instead: a value that
+//! holds a list is serialized as a sequence of its elements, e.g., a JSON array, and a
+//! value that holds a dictionary as a map, e.g., a JSON object, recursively; any other
+//! value is serialized as its string rep.  Deserializing a sequence produces a list, and a
+//! map a dictionary.  The [`structured`] module does the same for a field, via
+//! `#[serde(with = "molt::value_serde::structured")]`.
+//!
+//! Since every list is also a string, and many strings are also valid lists, the structure
+//! is taken from the value's current data rep; it isn't inferred from the string.  Use
+//! `Value::as_list` or `Value::as_dict` first to serialize a value as a list or a
+//! dictionary.
+//!
+//! ```
+//! use molt::types::*;
+//! use molt::value_serde::Structured;
+//!
+//! let value = Value::from(vec![Value::from("a b"), Value::from(5)]);
+//! let json = serde_json::to_string(&Structured(value.clone())).unwrap();
+//! assert_eq!(json, "[\"a b\",\"5\"]");
+//!
+//! let Structured(copy) = serde_json::from_str(&json).unwrap();
+//! assert_eq!(copy, value);
+//! ```
+//!
+//! [`Value`]: ../value/index.html
+//! [`Structured`]: struct.Structured.html
+//! [`structured`]: structured/index.html
+
+use crate::dict::dict_new;
+use crate::types::*;
+use crate::value::Structure;
+use serde::de::{self, Deserialize, Deserializer, MapAccess, SeqAccess, Visitor};
+use serde::ser::{Serialize, SerializeMap, SerializeSeq, Serializer};
+use std::fmt;
+
+impl Serialize for Value {
+    /// Serializes the value as its string rep.
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for Value {
+    /// Deserializes a value from a string, a number, or a boolean.
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(ValueVisitor { structured: false })
+    }
+}
+
+/// A `Value` that is serialized by its structure: as a sequence if it holds a list, as a
+/// map if it holds a dictionary, and otherwise as its string rep.  See the
+/// [module documentation](index.html).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Structured(pub Value);
+
+impl Serialize for Structured {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        structured::serialize(&self.0, serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Structured {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        structured::deserialize(deserializer).map(Structured)
+    }
+}
+
+/// Functions that serialize a `Value` field by its structure, for use with
+/// `#[serde(with = "molt::value_serde::structured")]`.
+///
+/// ```
+/// use molt::types::*;
+/// use serde::{Deserialize, Serialize};
+///
+/// #[derive(Serialize, Deserialize)]
+/// struct Config {
+///     #[serde(with = "molt::value_serde::structured")]
+///     servers: Value,
+/// }
+/// ```
+pub mod structured {
+    use super::*;
+
+    /// Serializes the value by its structure.
+    pub fn serialize<S: Serializer>(value: &Value, serializer: S) -> Result<S::Ok, S::Error> {
+        match value.structure() {
+            Structure::List(list) => {
+                let mut seq = serializer.serialize_seq(Some(list.len()))?;
+                for item in list.iter() {
+                    seq.serialize_element(&Structured(item.clone()))?;
+                }
+                seq.end()
+            }
+            Structure::Dict(dict) => {
+                let mut map = serializer.serialize_map(Some(dict.len()))?;
+                for (key, item) in dict.iter() {
+                    map.serialize_entry(key.as_str(), &Structured(item.clone()))?;
+                }
+                map.end()
+            }
+            Structure::Scalar => serializer.serialize_str(value.as_str()),
+        }
+    }
+
+    /// Deserializes a value from a string, number, boolean, sequence, or map; a sequence
+    /// becomes a list, and a map a dictionary.
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Value, D::Error> {
+        deserializer.deserialize_any(ValueVisitor { structured: true })
+    }
+}
+
+/// Builds a `Value` from serialized data, accepting sequences and maps only if the value
+/// is structured.
+struct ValueVisitor {
+    structured: bool,
+}
+
+impl<'de> Visitor<'de> for ValueVisitor {
+    type Value = Value;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.structured {
+            write!(f, "a string, number, boolean, sequence, or map")
+        } else {
+            write!(f, "a string, number, or boolean")
+        }
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Value, E> {
+        Ok(Value::from(v))
+    }
+
+    fn visit_string<E: de::Error>(self, v: String) -> Result<Value, E> {
+        Ok(Value::from(v))
+    }
+
+    fn visit_bool<E: de::Error>(self, v: bool) -> Result<Value, E> {
+        Ok(Value::from(v))
+    }
+
+    fn visit_i64<E: de::Error>(self, v: i64) -> Result<Value, E> {
+        Ok(Value::from(v as MoltInt))
+    }
+
+    fn visit_u64<E: de::Error>(self, v: u64) -> Result<Value, E> {
+        if v > MoltInt::MAX as u64 {
+            Ok(Value::from(v.to_string()))
+        } else {
+            Ok(Value::from(v as MoltInt))
+        }
+    }
+
+    fn visit_f64<E: de::Error>(self, v: f64) -> Result<Value, E> {
+        Ok(Value::from(v as MoltFloat))
+    }
+
+    fn visit_unit<E: de::Error>(self) -> Result<Value, E> {
+        Ok(Value::empty())
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Value, A::Error> {
+        if !self.structured {
+            return Err(de::Error::invalid_type(de::Unexpected::Seq, &self));
+        }
+
+        let mut list = MoltList::with_capacity(seq.size_hint().unwrap_or(0));
+        while let Some(Structured(item)) = seq.next_element()? {
+            list.push(item);
+        }
+        Ok(Value::from(list))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Value, A::Error> {
+        if !self.structured {
+            return Err(de::Error::invalid_type(de::Unexpected::Map, &self));
+        }
+
+        let mut dict = dict_new();
+        while let Some((key, Structured(item))) = map.next_entry::<Value, Structured>()? {
+            dict.insert(key, item);
+        }
+        Ok(Value::from(dict))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_string_rep() {
+        let value = Value::from(vec![Value::from("a b"), Value::from(5)]);
+        let json = serde_json::to_string(&value).unwrap();
+        assert_eq!(json, r#""{a b} 5""#);
+
+        let copy: Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(copy.as_str(), "{a b} 5");
+        assert_eq!(copy.as_list().unwrap().len(), 2);
+
+        // Numbers and booleans are accepted.
+        let copy: Vec<Value> = serde_json::from_str("[1, -2, 2.5, true, null]").unwrap();
+        let strings: Vec<&str> = copy.iter().map(|v| v.as_str()).collect();
+        assert_eq!(strings, vec!["1", "-2", "2.5", "1", ""]);
+        assert_eq!(copy[1].as_int(), Ok(-2));
+
+        // Structure isn't.
+        assert!(serde_json::from_str::<Value>(r#"["a"]"#).is_err());
+        assert!(serde_json::from_str::<Value>(r#"{"a": "b"}"#).is_err());
+    }
+
+    #[test]
+    fn test_structured_list() {
+        let inner = Value::from(vec![Value::from("x"), Value::from("y z")]);
+        let value = Value::from(vec![Value::from("a"), inner, Value::from(1)]);
+        let json = serde_json::to_string(&Structured(value.clone())).unwrap();
+        assert_eq!(json, r#"["a",["x","y z"],"1"]"#);
+
+        let Structured(copy) = serde_json::from_str(&json).unwrap();
+        assert_eq!(copy, value);
+        assert_eq!(copy.as_str(), "a {x {y z}} 1");
+    }
+
+    #[test]
+    fn test_structured_dict() {
+        let mut dict = dict_new();
+        dict.insert(Value::from("name"), Value::from("molt"));
+        dict.insert(
+            Value::from("tags"),
+            Value::from(vec![Value::from("tcl"), Value::from("rust")]),
+        );
+        let value = Value::from(dict);
+        let json = serde_json::to_string(&Structured(value.clone())).unwrap();
+        assert_eq!(json, r#"{"name":"molt","tags":["tcl","rust"]}"#);
+
+        let Structured(copy) = serde_json::from_str(&json).unwrap();
+        assert_eq!(copy, value);
+        assert_eq!(copy.as_dict().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_structured_string() {
+        // A value that holds no list is serialized as its string, even if it's a valid list.
+        let value = Value::from("a b c");
+        let json = serde_json::to_string(&Structured(value.clone())).unwrap();
+        assert_eq!(json, r#""a b c""#);
+
+        // Once it holds a list, it's serialized as one.
+        value.as_list().unwrap();
+        let json = serde_json::to_string(&Structured(value)).unwrap();
+        assert_eq!(json, r#"["a","b","c"]"#);
+    }
+
+    #[test]
+    fn test_structured_field() {
+        #[derive(serde::Serialize, serde::Deserialize)]
+        struct Config {
+            name: Value,
+            #[serde(with = "structured")]
+            servers: Value,
+        }
+
+        let json = r#"{"name":"test","servers":["a","b"]}"#;
+        let config: Config = serde_json::from_str(json).unwrap();
+        assert_eq!(config.name.as_str(), "test");
+        assert_eq!(config.servers.as_str(), "a b");
+        assert_eq!(serde_json::to_string(&config).unwrap(), json);
+    }
+}
@@ -0,0 +1,79 @@
+# Test Script: alias
+
+test alias-1.1 {alias errors} {
+    alias
+} -error {wrong # args: should be "alias name ?targetCmd ?arg ...??"}
+
+test alias-1.2 {alias errors} {
+    alias nonesuch
+} -error {"nonesuch" isn't an alias}
+
+test alias-2.1 {alias with no prepended args} -setup {
+    alias mylist list
+} -body {
+    mylist a b c
+} -cleanup {
+    rename mylist ""
+} -ok {a b c}
+
+test alias-2.2 {alias prepends args} -setup {
+    alias mylist list x {y z}
+} -body {
+    mylist a b
+} -cleanup {
+    rename mylist ""
+} -ok {x {y z} a b}
+
+test alias-2.3 {alias returns target prefix} -setup {
+    alias mylist list x {y z}
+} -body {
+    alias mylist
+} -cleanup {
+    rename mylist ""
+} -ok {list x {y z}}
+
+test alias-2.4 {alias cmdtype} -setup {
+    alias mylist list
+} -body {
+    info cmdtype mylist
+} -cleanup {
+    rename mylist ""
+} -ok {alias}
+
+test alias-2.5 {alias honors redefinition of target} -setup {
+    proc target {args} { return "old $args" }
+    alias myalias target 1
+} -body {
+    set a [myalias 2]
+    proc target {args} { return "new $args" }
+    list $a [myalias 2]
+} -cleanup {
+    rename myalias ""
+    rename target ""
+} -ok {{old 1 2} {new 1 2}}
+
+test alias-2.6 {alias to undefined target} -setup {
+    alias myalias nonesuch 1
+} -body {
+    myalias 2
+} -cleanup {
+    rename myalias ""
+} -error {invalid command name "nonesuch"}
+
+test alias-2.7 {alias to alias} -setup {
+    alias first list 1
+    alias second first 2
+} -body {
+    second 3
+} -cleanup {
+    rename first ""
+    rename second ""
+} -ok {1 2 3}
+
+test alias-3.1 {self-referential alias hits recursion limit} -setup {
+    alias loop loop x
+} -body {
+    loop
+} -cleanup {
+    rename loop ""
+} -error {too many nested calls to Interp::eval (infinite loop?)}
@@ -2,18 +2,26 @@
 #
 # If I add the "glob" command, I'll use that to pick up the tests.
 
+source alias.tcl
 source append.tcl
 source array.tcl
 source assert_eq.tcl
+source binary.tcl
 source break.tcl
 source catch.tcl
 source continue.tcl
+source coroutine.tcl
 source dict.tcl
+source encoding.tcl
 source error.tcl
+source eval.tcl
 source exit.tcl
 source expr.tcl
 source for.tcl
 source foreach.tcl
+source format.tcl
+source gets.tcl
+source glob.tcl
 source if.tcl
 source info.tcl
 source incr.tcl
@@ -23,13 +31,24 @@ source lappend.tcl
 source lindex.tcl
 source list.tcl
 source llength.tcl
+source namespace.tcl
+source open.tcl
+source parray.tcl
 source parser.tcl
 source proc.tcl
+source puts.tcl
+source read.tcl
 source rename.tcl
 source return.tcl
 source set.tcl
+source source.tcl
+source split.tcl
 source string.tcl
+source tailcall.tcl
 source test.tcl
 source throw.tcl
+source time.tcl
+source trace.tcl
+source unknown.tcl
 source unset.tcl
 source while.tcl
@@ -40,7 +40,7 @@ proc match_lists {expected got} {
 
 test array-1.1 {array names, no var} {
     array names
-} -error {wrong # args: should be "array names arrayName"}
+} -error {wrong # args: should be "array names arrayName ?mode? ?pattern?"}
 
 test array-1.2 {array names, unknown var} {
     array names unknown_variable
@@ -57,6 +57,35 @@ test array-1.4 {array names, array var} {
     match_lists {1 2} [array names a]
 } -ok {1}
 
+test array-1.5 {array names, glob pattern} {
+    array set a {a1 x a2 y b1 z}
+    match_lists {a1 a2} [array names a a*]
+} -ok {1}
+
+test array-1.6 {array names, -glob} {
+    array set a {a1 x a2 y b1 z}
+    match_lists {a1 b1} [array names a -glob {?1}]
+} -ok {1}
+
+test array-1.7 {array names, -exact} {
+    array set a {a* x a1 y}
+    array names a -exact a*
+} -ok {a*}
+
+test array-1.8 {array names, no match} {
+    array set a {a1 x}
+    list [array names a b*] [array names a -exact a] [array names unknown_variable *]
+} -ok {{} {} {}}
+
+test array-1.9 {array names, bad mode} {
+    array set a {a1 x}
+    array names a -regexp {a.}
+} -error {bad option "-regexp": must be -exact or -glob}
+
+test array-1.10 {array names, too many args} {
+    array names a -glob a* extra
+} -error {wrong # args: should be "array names arrayName ?mode? ?pattern?"}
+
 test array-2.1 {array size, no var} {
     array size
 } -error {wrong # args: should be "array size arrayName"}
@@ -97,7 +126,7 @@ test array-3.4 {array exists, array var} {
 
 test array-4.1 {array get, no var} {
     array get
-} -error {wrong # args: should be "array get arrayName"}
+} -error {wrong # args: should be "array get arrayName ?pattern?"}
 
 test array-4.2 {array get, unknown var} {
     array get unknown_variable
@@ -114,6 +143,16 @@ test array-4.4 {array get, array var} {
     match_dicts {1 one 2 two} [array get a]
 } -ok {1}
 
+test array-4.5 {array get, pattern} {
+    array set a {a1 x a2 y b1 z}
+    match_dicts {a1 x a2 y} [array get a a*]
+} -ok {1}
+
+test array-4.6 {array get, no match} {
+    array set a {a1 x}
+    list [array get a b*] [array get unknown_variable *]
+} -ok {{} {}}
+
 test array-5.1 {array unset, no var} {
     array unset
 } -error {wrong # args: should be "array unset arrayName ?index?"}
@@ -175,6 +214,141 @@ test array-6.5 {array set on index} {
     list $result1 $result2
 } -ok {{can't set "a(1)": variable isn't array} {can't set "a": variable is array}}
 
+test array-7.1 {array statistics, no args} {
+    array statistics
+} -error {wrong # args: should be "array statistics arrayName"}
+
+test array-7.2 {array statistics, not an array} {
+    set scalar 1
+    list [catch {array statistics scalar} result] $result \
+        [catch {array statistics unknown_variable} result] $result
+} -ok {1 {"scalar" isn't an array} 1 {"unknown_variable" isn't an array}}
+
+test array-7.3 {array statistics, empty array} {
+    array set a {}
+    array statistics a
+} -ok {0 entries in table}
+
+test array-7.4 {array statistics} {
+    array set a {a x bb yyyy ccc {}}
+    array statistics a
+} -ok {3 entries in table
+index length: min 1, max 3, average 2.0
+value length: min 0, max 4, average 1.7}
+
+test array-8.1 {array startsearch errors} {
+    array startsearch
+} -error {wrong # args: should be "array startsearch arrayName"}
+
+test array-8.2 {array startsearch, not an array} {
+    set scalar 1
+    array startsearch scalar
+} -error {"scalar" isn't an array}
+
+test array-8.3 {array search, all elements} {
+    array set a {x 1 y 2 z 3}
+    set id [array startsearch a]
+    set got {}
+    while {[array anymore a $id]} {
+        lappend got [array nextelement a $id]
+    }
+    set result [list [match_lists {x y z} $got] [array anymore a $id] \
+        [array nextelement a $id]]
+    array donesearch a $id
+    set result
+} -ok {1 0 {}}
+
+test array-8.4 {array search, empty array} {
+    array set a {}
+    set id [array startsearch a]
+    set result [list [array anymore a $id] [array nextelement a $id]]
+    array donesearch a $id
+    set result
+} -ok {0 {}}
+
+test array-8.5 {array search, element unset during the search} {
+    array set a {x 1 y 2}
+    set id [array startsearch a]
+    array nextelement a $id
+    unset a(x)
+    set result [list [catch {array anymore a $id} result] \
+        [expr {$result eq "array \"a\" changed during search \"$id\""}] \
+        [catch {array nextelement a $id}]]
+    array donesearch a $id
+    set result
+} -ok {1 1 1}
+
+test array-8.6 {array search, independent searches} {
+    array set a {x 1}
+    set id1 [array startsearch a]
+    set id2 [array startsearch a]
+    set result [list [expr {$id1 ne $id2}] [array nextelement a $id1] \
+        [array nextelement a $id2]]
+    array donesearch a $id1
+    array donesearch a $id2
+    set result
+} -ok {1 x x}
+
+test array-8.7 {array search, unknown search} {
+    array set a {x 1}
+    list [catch {array nextelement a nonesuch} result] $result \
+        [catch {array anymore a nonesuch} result] $result \
+        [catch {array donesearch a nonesuch} result] $result
+} -ok {1 {couldn't find search "nonesuch"} 1 {couldn't find search "nonesuch"} 1 {couldn't find search "nonesuch"}}
+
+test array-8.8 {array search, wrong array} {
+    array set ::search1 {x 1}
+    array set ::search2 {x 1}
+    set id [array startsearch ::search1]
+    set code [catch {array nextelement ::search2 $id} result]
+    array donesearch ::search1 $id
+    unset ::search1 ::search2
+    list $code [expr {$result eq "search identifier \"$id\" isn't for variable \"::search2\""}]
+} -ok {1 1}
+
+test array-8.9 {array search, done search} {
+    array set a {x 1}
+    set id [array startsearch a]
+    array donesearch a $id
+    catch {array nextelement a $id} result
+    expr {$result eq "couldn't find search \"$id\""}
+} -ok {1}
+
+test array-8.10 {array search errors} {
+    list [catch {array anymore a} result] $result \
+        [catch {array nextelement a} result] $result \
+        [catch {array donesearch a} result] $result
+} -ok {1 {wrong # args: should be "array anymore arrayName searchId"} 1 {wrong # args: should be "array nextelement arrayName searchId"} 1 {wrong # args: should be "array donesearch arrayName searchId"}}
+
+test array-8.11 {array search, element added during the search} {
+    array set a {x 1}
+    set id [array startsearch a]
+    set a(y) 2
+    set code [catch {array nextelement a $id} result]
+    array donesearch a $id
+    list $code [expr {$result eq "array \"a\" changed during search \"$id\""}]
+} -ok {1 1}
+
+test array-8.12 {array search, element set during the search} {
+    array set a {x 1 y 2}
+    set id [array startsearch a]
+    set got [list [array nextelement a $id]]
+    array set a {x 3 y 4}
+    lappend got [array nextelement a $id]
+    array donesearch a $id
+    match_lists {x y} $got
+} -ok {1}
+
+test array-8.13 {array search, array unset during the search} {
+    array set a {x 1}
+    set id [array startsearch a]
+    unset a
+    set got [list [catch {array nextelement a $id} result] $result]
+    array set a {x 1}
+    lappend got [catch {array nextelement a $id} result] \
+        [expr {$result eq "couldn't find search \"$id\""}]
+} -ok {1 {"a" isn't an array} 1 1}
+
 #----------------------------------------------------------------------------
 # Cleanup
 
@@ -0,0 +1,185 @@
+# Test Suite: binary command
+
+# Returns the bytes of a binary string as hex digits.
+proc hex {bytes} {
+    binary scan $bytes H* digits
+    return $digits
+}
+
+test binary-1.1 {binary: syntax} {
+    binary
+} -error {wrong # args: should be "binary subcommand ?arg ...?"}
+
+test binary-1.2 {binary format: syntax} {
+    binary format
+} -error {wrong # args: should be "binary format formatString ?arg ...?"}
+
+test binary-1.3 {binary scan: syntax} {
+    binary scan abc
+} -error {wrong # args: should be "binary scan value formatString ?varName ...?"}
+
+test binary-1.4 {binary format: bad field} {
+    binary format z 1
+} -error {bad field specifier "z"}
+
+test binary-1.5 {binary format: not enough arguments} {
+    binary format cc 1
+} -error {not enough arguments for all format specifiers}
+
+test binary-1.6 {binary scan: not enough variables} {
+    binary scan abc a1a1 x
+} -error {not enough arguments for all format specifiers}
+
+test binary-1.7 {binary format: list too short} {
+    binary format c3 {1 2}
+} -error {number of elements in list does not match count}
+
+test binary-1.8 {binary format: bad integer} {
+    binary format c abc
+} -error {expected integer but got "abc"}
+
+test binary-1.9 {binary format: bad hex digits} {
+    binary format H2 zz
+} -error {expected hexadecimal string but got "zz" instead}
+
+test binary-1.10 {binary format: bad binary digits} {
+    binary format B3 102
+} -error {expected binary string but got "102" instead}
+
+# Strings
+test binary-2.1 {binary format: a, padding and truncation} {
+    list [hex [binary format a5 abc]] [binary format a2 abc] [binary format a abc]
+} -ok {6162630000 ab a}
+
+test binary-2.2 {binary format: A pads with spaces} {
+    hex [binary format A5 abc]
+} -ok {6162632020}
+
+test binary-2.3 {binary format: a*} {
+    binary format a*a* abc def
+} -ok {abcdef}
+
+test binary-2.4 {binary scan: a and A} {
+    set data [binary format a5A5 ab cd]
+    set n [binary scan $data a5A5 x y]
+    list $n [hex $x] $y
+} -ok {2 6162000000 cd}
+
+test binary-2.5 {binary scan: a*} {
+    binary scan abcdef a2a* x y
+    list $x $y
+} -ok {ab cdef}
+
+# Hex and binary digits
+test binary-3.1 {binary format: H and h} {
+    list [hex [binary format H4 12ab]] [hex [binary format h4 12ab]]
+} -ok {12ab 21ba}
+
+test binary-3.2 {binary format: odd hex digits are padded} {
+    hex [binary format H3 abc]
+} -ok {abc0}
+
+test binary-3.3 {binary format: B and b} {
+    list [hex [binary format B8 10000001]] [hex [binary format b8 10000000]] \
+        [hex [binary format B* 1111]]
+} -ok {81 01 f0}
+
+test binary-3.4 {binary scan: B, b, H, h} {
+    set data [binary format H4 81f0]
+    binary scan $data B8b8 x y
+    binary scan $data h* z
+    list $x $y $z
+} -ok {10000001 00001111 180f}
+
+# Integers
+test binary-4.1 {binary format: endianness} {
+    list [hex [binary format s 0x1234]] [hex [binary format S 0x1234]] \
+        [hex [binary format i 0x12345678]] [hex [binary format I 0x12345678]]
+} -ok {3412 1234 78563412 12345678}
+
+test binary-4.2 {binary format: 64-bit integers} {
+    list [hex [binary format w 0x0102030405060708]] [hex [binary format W 0x0102030405060708]]
+} -ok {0807060504030201 0102030405060708}
+
+test binary-4.3 {binary format: integers are truncated} {
+    list [hex [binary format c 0x1ff]] [hex [binary format c -1]]
+} -ok {ff ff}
+
+test binary-4.4 {binary format: integer lists} {
+    list [hex [binary format c3 {1 2 3 4}]] [hex [binary format S* {1 2}]]
+} -ok {010203 00010002}
+
+test binary-4.5 {binary scan: signed and unsigned} {
+    set data [binary format c2 {-1 127}]
+    binary scan $data c2 signed
+    binary scan $data cu2 unsigned
+    binary scan [binary format S -2] S short
+    binary scan [binary format S -2] Su ushort
+    list $signed $unsigned $short $ushort
+} -ok {{-1 127} {255 127} -2 65534}
+
+test binary-4.6 {binary scan: round trip} {
+    set data [binary format sSiIwW -2 300 -70000 70000 -5000000000 5000000000]
+    set n [binary scan $data sSiIwW a b c d e f]
+    list $n $a $b $c $d $e $f
+} -ok {6 -2 300 -70000 70000 -5000000000 5000000000}
+
+test binary-4.7 {binary scan: *} {
+    binary scan [binary format c5 {1 2 3 4 5}] cc* first rest
+    list $first $rest
+} -ok {1 {2 3 4 5}}
+
+test binary-4.8 {binary scan: endianness} {
+    set data [binary format H8 12345678]
+    binary scan $data i little
+    binary scan $data I big
+    list [format %x $little] [format %x $big]
+} -ok {78563412 12345678}
+
+# Floats
+test binary-5.1 {binary format: doubles round trip} {
+    binary scan [binary format d2 {1.5 -0.25}] d2 values
+    set values
+} -ok {1.5 -0.25}
+
+test binary-5.2 {binary format: floats round trip} {
+    binary scan [binary format f 0.5] f value
+    set value
+} -ok {0.5}
+
+test binary-5.3 {binary format: float endianness} {
+    list [hex [binary format R 1.0]] [hex [binary format r 1.0]] \
+        [hex [binary format Q 1.0]] [hex [binary format q 1.0]]
+} -ok {3f800000 0000803f 3ff0000000000000 000000000000f03f}
+
+# Nulls and skipping
+test binary-6.1 {binary format: x} {
+    hex [binary format cx3c 1 2]
+} -ok {0100000002}
+
+test binary-6.2 {binary scan: x} {
+    binary scan [binary format cx3c 1 2] x4c value
+    set value
+} -ok {2}
+
+# Running out of data
+test binary-7.1 {binary scan: stops when data runs out} {
+    catch {unset ::b}
+    set n [binary scan [binary format S 1] SS a ::b]
+    list $n $a [info exists ::b]
+} -ok {1 1 0}
+
+test binary-7.2 {binary scan: empty data} {
+    binary scan "" c value
+} -ok {0}
+
+# Byte strings are strings
+test binary-8.1 {binary: strings as bytes} {
+    hex "ABé"
+} -ok {4142e9}
+
+test binary-8.2 {binary: bytes as strings} {
+    string length [binary format H* 41e9ff]
+} -ok {3}
+
+rename hex ""
@@ -24,6 +24,10 @@ test catch-2.5 {catch continue} {
     catch {continue}
 } -ok {4}
 
+test catch-2.6 {catch application-defined code} {
+    catch {return -code 5 -level 0 abc}
+} -ok {5}
+
 test catch-3.1 {catch ok value} {
     catch {set a "abc"} myvar
     set myvar
@@ -53,3 +57,69 @@ test catch-5.1 {catch options} {
     set a [catch { throw CODE "Message" } result opts]
     list $a $result [dict get $opts -code] [dict get $opts -level] [dict get $opts -errorcode]
 } -ok {1 Message 1 0 CODE}
+
+test catch-5.2 {catch options: -errorline} {
+    catch {
+        set a 1
+
+        error "Message"
+    } result opts
+    dict get $opts -errorline
+} -ok {4}
+
+test catch-5.3 {catch options: -errorline, error in proc} -setup {
+    proc fail {} {
+        set a 1
+        error "Message"
+    }
+} -body {
+    catch {
+        set a 1
+        fail
+    } result opts
+    dict get $opts -errorline
+} -cleanup {
+    rename fail ""
+} -ok {3}
+
+test catch-5.4 {catch options: -errorline, error in nested body} {
+    catch {
+        set a 1
+        if {$a == 1} {
+            error "Message"
+        }
+    } result opts
+    dict get $opts -errorline
+} -ok {3}
+
+test catch-5.5 {catch options: no -errorline for non-errors} {
+    catch {break} result opts
+    dict exists $opts -errorline
+} -ok {0}
+
+test catch-5.6 {catch options: -errorline is not retained by return} {
+    catch {return -errorline 5 -level 0 x} result opts
+    dict exists $opts -errorline
+} -ok {0}
+
+test catch-6.1 {catch application-defined code: value and options} {
+    set code [catch {return -code 5 -level 0 abc} result opts]
+    list $code $result $opts
+} -ok {5 abc {-code 5 -level 0}}
+
+test catch-6.2 {catch application-defined code: propagates from procs} -setup {
+    proc myproc {} {
+        return -code 7 "custom"
+        error "not reached"
+    }
+} -body {
+    set code [catch {myproc} result opts]
+    list $code $result $opts
+} -cleanup {
+    rename myproc ""
+} -ok {7 custom {-code 7 -level 0}}
+
+test catch-6.3 {catch application-defined code: return -level} {
+    set code [catch {return -code 6 -level 2 abc} result opts]
+    list $code $result $opts
+} -ok {2 abc {-code 6 -level 2}}
@@ -0,0 +1,307 @@
+# Test Script: coroutine and yield
+#
+# Coroutines are available only with the "coroutine" feature.
+
+testConstraint coroutine [expr {![catch {info cmdtype coroutine}]}]
+
+proc counter {limit} {
+    yield start
+    tailcall countFrom 1 $limit
+}
+
+proc countFrom {i limit} {
+    if {$i > $limit} {
+        return done
+    }
+    yield $i
+    tailcall countFrom [incr i] $limit
+}
+
+test coroutine-1.1 {coroutine signature} -constraints coroutine -body {
+    coroutine gen
+} -error {wrong # args: should be "coroutine name command ?arg ...?"}
+
+test coroutine-1.2 {yield signature} -constraints coroutine -body {
+    yield a b
+} -error {wrong # args: should be "yield ?value?"}
+
+test coroutine-1.3 {yield outside a coroutine} -constraints coroutine -body {
+    yield a
+} -error {yield can only be called in a coroutine}
+
+test coroutine-1.4 {coroutine name in use} -constraints coroutine -body {
+    coroutine set counter 1
+} -error {can't create coroutine "set": command already exists}
+
+test coroutine-1.5 {coroutine in unknown namespace} -constraints coroutine -body {
+    coroutine ::nonesuch::gen counter 1
+} -error {can't create coroutine "::nonesuch::gen": unknown namespace}
+
+test coroutine-1.6 {resume signature} -constraints coroutine -body {
+    coroutine gen counter 1
+    gen a b
+} -cleanup {
+    rename gen ""
+} -error {wrong # args: should be "gen ?value?"}
+
+test coroutine-2.1 {coroutine returns the first yielded value} -constraints coroutine -body {
+    coroutine gen counter 2
+} -cleanup {
+    rename gen ""
+} -ok {start}
+
+test coroutine-2.2 {resuming returns the next yielded value} -constraints coroutine -body {
+    coroutine gen counter 2
+    list [gen] [gen] [gen]
+} -ok {1 2 done}
+
+test coroutine-2.3 {a finished coroutine is deleted} -constraints coroutine -body {
+    coroutine gen counter 0
+    gen
+    gen
+} -error {invalid command name "gen"}
+
+test coroutine-2.4 {a coroutine that doesn't yield} -constraints coroutine -body {
+    list [coroutine gen list a b] [catch {info cmdtype gen}]
+} -ok {{a b} 1}
+
+test coroutine-2.5 {coroutine command type} -constraints coroutine -body {
+    coroutine gen counter 1
+    info cmdtype gen
+} -cleanup {
+    rename gen ""
+} -ok {coroutine}
+
+test coroutine-2.6 {yield returns the resume value} -constraints coroutine -setup {
+    proc echo {} {
+        yield ready
+    }
+} -body {
+    list [coroutine e echo] [e hello] [catch {info cmdtype e}]
+} -cleanup {
+    rename echo ""
+} -ok {ready hello 1}
+
+test coroutine-2.7 {yield with no value} -constraints coroutine -setup {
+    proc quiet {} {
+        yield
+        yield
+    }
+} -body {
+    list [coroutine q quiet] [q hello] [q]
+} -cleanup {
+    rename quiet ""
+} -ok {{} {} {}}
+
+test coroutine-2.8 {the command's error ends the coroutine} -constraints coroutine -setup {
+    proc failer {} {
+        yield ok
+        error "simulated error"
+    }
+} -body {
+    coroutine f failer
+    list [catch {f} msg] $msg [catch {info cmdtype f}]
+} -cleanup {
+    rename failer ""
+} -ok {1 {simulated error} 1}
+
+test coroutine-2.9 {an unknown command ends the coroutine} -constraints coroutine -body {
+    coroutine gen nonesuch a b
+} -error {invalid command name "nonesuch"}
+
+test coroutine-3.1 {the body runs between yields} -constraints coroutine -setup {
+    proc steps {} {
+        set ::csteps a
+        yield 1
+        append ::csteps b
+        yield 2
+        append ::csteps c
+    }
+} -body {
+    list [coroutine gen steps] $::csteps [gen] $::csteps [gen] $::csteps
+} -cleanup {
+    rename steps ""
+    unset ::csteps
+} -ok {1 a 2 ab abc abc}
+
+test coroutine-3.2 {the coroutine has its own locals} -constraints coroutine -setup {
+    proc locals {} {
+        set x coroutine
+        yield [info exists y]
+        return $x
+    }
+} -body {
+    set x caller
+    set y caller
+    coroutine gen locals
+    list [gen] $x
+} -cleanup {
+    rename locals ""
+} -ok {coroutine caller}
+
+test coroutine-3.3 {the coroutine runs at the global level} -constraints coroutine -setup {
+    set ::cglobal 1
+    proc globals {} {
+        global cglobal
+        incr cglobal
+        yield $cglobal
+        incr cglobal
+    }
+} -body {
+    list [coroutine gen globals] $::cglobal [gen] $::cglobal
+} -cleanup {
+    rename globals ""
+    unset ::cglobal
+} -ok {2 2 3 3}
+
+test coroutine-3.4 {coroutine runs in the current namespace} -constraints coroutine -body {
+    namespace eval ::cns {
+        proc gen {} {
+            yield [namespace current]
+            return [namespace current]
+        }
+        coroutine g gen
+    }
+    list [::cns::g] [catch {info cmdtype ::g}]
+} -cleanup {
+    rename ::cns::gen ""
+} -ok {::cns 1}
+
+test coroutine-3.5 {coroutine can't resume itself} -constraints coroutine -setup {
+    proc selfish {} {
+        yield ok
+        selfgen
+    }
+} -body {
+    coroutine selfgen selfish
+    selfgen
+} -cleanup {
+    rename selfish ""
+} -error {coroutine "selfgen" is already running}
+
+test coroutine-3.6 {coroutines can resume each other} -constraints coroutine -setup {
+    proc doubler {} {
+        yield [expr {2 * [numbers]}]
+        tailcall doubler
+    }
+} -body {
+    coroutine numbers counter 10
+    list [coroutine doubles doubler] [doubles] [doubles]
+} -cleanup {
+    rename numbers ""
+    rename doubles ""
+    rename doubler ""
+} -ok {2 4 6}
+
+test coroutine-3.7 {tailcall in a coroutine} -constraints coroutine -setup {
+    proc tail {} {
+        yield first
+        tailcall list tail done
+    }
+} -body {
+    list [coroutine gen tail] [gen]
+} -cleanup {
+    rename tail ""
+} -ok {first {tail done}}
+
+test coroutine-3.8 {a coroutine in a child interpreter} -constraints coroutine -body {
+    interp create child
+    interp eval child {
+        proc gen {} { yield 1; yield 2 }
+        coroutine g gen
+    }
+    interp eval child g
+} -cleanup {
+    interp delete child
+} -ok {2}
+
+test coroutine-3.9 {a coroutine loops by tail calls} -constraints coroutine -setup {
+    proc squares {{i 1}} {
+        yield [expr {$i * $i}]
+        tailcall squares [incr i]
+    }
+} -body {
+    coroutine nextSquare squares
+    list [nextSquare] [nextSquare] [nextSquare]
+} -cleanup {
+    rename nextSquare ""
+    rename squares ""
+} -ok {4 9 16}
+
+test coroutine-3.10 {a coroutine's arguments are checked} -constraints coroutine -body {
+    list [catch {coroutine gen counter} msg] $msg [catch {info cmdtype gen}]
+} -ok {1 {wrong # args: should be "counter limit"} 1}
+
+test coroutine-4.1 {deleting a suspended coroutine} -constraints coroutine -setup {
+    set ::cdeleted 0
+    proc deletable {} {
+        yield ok
+        set ::cdeleted 1
+    }
+} -body {
+    coroutine gen deletable
+    rename gen ""
+    set ::cdeleted
+} -cleanup {
+    rename deletable ""
+    unset ::cdeleted
+} -ok {0}
+
+test coroutine-4.2 {a coroutine deletes itself} -constraints coroutine -setup {
+    proc deleter {} {
+        rename gen ""
+        yield deleted
+        return "not reached"
+    }
+} -body {
+    list [coroutine gen deleter] [catch {info cmdtype gen}]
+} -cleanup {
+    rename deleter ""
+} -ok {deleted 1}
+
+test coroutine-4.3 {a renamed coroutine} -constraints coroutine -body {
+    coroutine gen counter 1
+    rename gen gen2
+    list [gen2] [gen2] [catch {info cmdtype gen2}]
+} -ok {1 done 1}
+
+test coroutine-5.1 {yield from a nested call} -constraints coroutine -setup {
+    proc inner {} {
+        yield inner
+    }
+    proc outer {} {
+        inner
+    }
+} -body {
+    list [catch {coroutine gen outer} msg] $msg [catch {info cmdtype gen}]
+} -cleanup {
+    rename inner ""
+    rename outer ""
+} -ok {1 {yield can only be called at the top level of a coroutine's body} 1}
+
+test coroutine-5.2 {yield from a loop body} -constraints coroutine -setup {
+    proc looper {} {
+        yield first
+        while 1 {
+            yield next
+        }
+    }
+} -body {
+    coroutine gen looper
+    gen
+} -cleanup {
+    rename looper ""
+} -error {yield can only be called at the top level of a coroutine's body}
+
+test coroutine-5.3 {yield from a command substitution} -constraints coroutine -setup {
+    proc substituter {} {
+        set x [yield]
+    }
+} -body {
+    coroutine gen substituter
+} -cleanup {
+    rename substituter ""
+} -error {yield can only be called at the top level of a coroutine's body}
+
+rename counter ""
+rename countFrom ""
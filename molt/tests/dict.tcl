@@ -3,7 +3,7 @@
 # dict create
 test dict-1.1 {dict create: odd arguments} {
     dict create a
-} -error {wrong # args: should be "dict create ?key value?"}
+} -error {wrong # args: should be "dict create ?key value ...?"}
 
 test dict-1.2 {dict create: no arguments} {
     dict create
@@ -206,3 +206,67 @@ test dict-9.6 {dict unset: inner dict not a dict} {
     set var {a 1 b 2}
     dict unset var b z
 } -error {missing value to go with key}
+
+# dict for
+test dict-10.1 {dict for: signature} {
+    dict for {k v} {a 1}
+} -error {wrong # args: should be "dict for {keyVarName valueVarName} dictionary script"}
+
+test dict-10.2 {dict for: bad variable list} {
+    dict for {k} {a 1} {}
+} -error {must have exactly two variable names}
+
+test dict-10.3 {dict for: non-dictionary} {
+    dict for {k v} {a 1 b} {}
+} -error {missing value to go with key}
+
+test dict-10.4 {dict for: empty dictionary} {
+    set result ""
+    dict for {k v} {} { lappend result $k }
+    set result
+} -ok {}
+
+test dict-10.5 {dict for: iterates in order} {
+    set result ""
+    dict for {k v} {a 1 b 2 c 3} { lappend result $k=$v }
+    set result
+} -ok {a=1 b=2 c=3}
+
+test dict-10.6 {dict for: returns empty string} {
+    dict for {k v} {a 1} { set k }
+} -ok {}
+
+test dict-10.7 {dict for: break} {
+    set result ""
+    set r [dict for {k v} {a 1 b 2 c 3} {
+        if {$k eq "b"} break
+        lappend result $k
+    }]
+    list $r $result
+} -ok {{} a}
+
+test dict-10.8 {dict for: continue} {
+    set result ""
+    dict for {k v} {a 1 b 2 c 3} {
+        if {$k eq "b"} continue
+        lappend result $k
+    }
+    set result
+} -ok {a c}
+
+test dict-10.9 {dict for: return from enclosing proc} -setup {
+    proc findkey {dict value} {
+        dict for {k v} $dict {
+            if {$v == $value} { return $k }
+        }
+        return none
+    }
+} -body {
+    list [findkey {a 1 b 2} 2] [findkey {a 1 b 2} 3]
+} -cleanup {
+    rename findkey ""
+} -ok {b none}
+
+test dict-10.10 {dict for: errors propagate} {
+    dict for {k v} {a 1} { error "oops" }
+} -error {oops}
@@ -0,0 +1,66 @@
+# Test Suite: encoding command
+
+# Returns the bytes of a binary string as hex digits.
+proc hex {bytes} {
+    binary scan $bytes H* digits
+    return $digits
+}
+
+test encoding-1.1 {encoding: syntax} {
+    encoding
+} -error {wrong # args: should be "encoding subcommand ?arg ...?"}
+
+test encoding-1.2 {encoding convertto: syntax} {
+    encoding convertto
+} -error {wrong # args: should be "encoding convertto ?encoding? string"}
+
+test encoding-1.3 {encoding convertfrom: syntax} {
+    encoding convertfrom a b c
+} -error {wrong # args: should be "encoding convertfrom ?encoding? data"}
+
+test encoding-1.4 {encoding: unknown encoding} {
+    encoding convertto koi8-r abc
+} -error {unknown encoding "koi8-r"}
+
+test encoding-2.1 {encoding names} {
+    encoding names
+} -ok {utf-8 ascii iso8859-1}
+
+test encoding-3.1 {encoding convertto: utf-8 is the default} {
+    list [hex [encoding convertto "aé😀"]] [hex [encoding convertto utf-8 "aé😀"]]
+} -ok {61c3a9f09f9880 61c3a9f09f9880}
+
+test encoding-3.2 {encoding convertto: iso8859-1} {
+    hex [encoding convertto iso8859-1 "aé€"]
+} -ok {61e93f}
+
+test encoding-3.3 {encoding convertto: ascii} {
+    hex [encoding convertto ascii "aé~"]
+} -ok {613f7e}
+
+test encoding-4.1 {encoding convertfrom: utf-8} {
+    encoding convertfrom [binary format H* 61c3a9f09f9880]
+} -ok {aé😀}
+
+test encoding-4.2 {encoding convertfrom: invalid utf-8} {
+    encoding convertfrom utf-8 [binary format H* 61e962]
+} -ok {aéb}
+
+test encoding-4.3 {encoding convertfrom: iso8859-1} {
+    encoding convertfrom iso8859-1 [binary format H* 61e9]
+} -ok {aé}
+
+test encoding-4.4 {encoding convertfrom: ascii} {
+    encoding convertfrom ascii [binary format H* 61e962]
+} -ok {a?b}
+
+test encoding-5.1 {encoding: round trip} {
+    set s "日本語 😀 école"
+    expr {[encoding convertfrom utf-8 [encoding convertto utf-8 $s]] eq $s}
+} -ok {1}
+
+test encoding-5.2 {encoding: utf-8 byte length} {
+    string length [encoding convertto utf-8 "日本語"]
+} -ok {9}
+
+rename hex ""
@@ -2,7 +2,11 @@
 
 test error-1.1 {error error} {
     error
-} -error {wrong # args: should be "error message"}
+} -error {wrong # args: should be "error message ?errorInfo? ?errorCode?"}
+
+test error-1.2 {error error} {
+    error a b c d
+} -error {wrong # args: should be "error message ?errorInfo? ?errorCode?"}
 
 test error-2.1 {error returns error} {
     error "simulated error"
@@ -29,3 +33,81 @@ test error-3.1 {error sets return options} {
     set a [catch { error "Message" } result opts]
     list $a $result [dict get $opts -code] [dict get $opts -level] [dict get $opts -errorcode]
 } -ok {1 Message 1 0 NONE}
+
+test error-3.2 {error sets -errorinfo} {
+    catch { error "Message" "Initial info" } result opts
+    list $result [dict get $opts -errorinfo] [dict get $opts -errorcode]
+} -ok {Message {Initial info} NONE}
+
+test error-3.3 {error sets -errorcode} {
+    catch { error "Message" "" {MY CODE} } result opts
+    list $result [dict get $opts -errorcode] [dict get $opts -errorinfo]
+} -ok {Message {MY CODE} {Message
+    while executing
+"error Message {} {MY CODE}"}}
+
+test error-3.4 {error rethrows caught error} -setup {
+    proc fail {} { error "original" "" ORIG }
+} -body {
+    catch { fail } result opts
+    catch { error $result [dict get $opts -errorinfo] [dict get $opts -errorcode] } \
+        result2 opts2
+    list $result2 [dict get $opts2 -errorcode] \
+        [string equal [dict get $opts -errorinfo] \
+            [string range [dict get $opts2 -errorinfo] 0 [expr {[string length [dict get $opts -errorinfo]] - 1}]]]
+} -cleanup {
+    rename fail ""
+} -ok {original ORIG 1}
+
+test error-3.5 {error sets errorInfo and errorCode globals} -setup {
+    proc fail {} { error "Message" "Initial info" {MY CODE} }
+} -body {
+    catch { fail }
+    list $::errorCode [string range $::errorInfo 0 11]
+} -cleanup {
+    rename fail ""
+} -ok {{MY CODE} {Initial info}}
+
+test error-4.1 {errorInfo reports command line numbers} -setup {
+    proc fail {} {
+        set a 1
+
+        error "Failed"
+    }
+} -body {
+    catch {
+        set b 2
+        fail
+    }
+    set ::errorInfo
+} -cleanup {
+    rename fail ""
+} -ok {Failed
+    while executing
+"error Failed"
+    (procedure "fail" line 4)
+    invoked from within
+"fail"}
+
+test error-4.2 {errorInfo has a frame for each procedure call} -setup {
+    proc outer {} {
+        set x 1
+        middle
+    }
+    proc middle {} {
+        inner
+    }
+    proc inner {} {
+        set y 2
+        if {1} {
+            error "Failed"
+        }
+    }
+} -body {
+    catch {outer}
+    split $::errorInfo \n
+} -cleanup {
+    rename outer ""
+    rename middle ""
+    rename inner ""
+} -ok {Failed {    while executing} {"error Failed"} {    (procedure "inner" line 3)} {    invoked from within} {"inner"} {    (procedure "middle" line 2)} {    invoked from within} {"middle"} {    (procedure "outer" line 3)} {    invoked from within} {"outer"}}
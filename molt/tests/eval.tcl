@@ -0,0 +1,53 @@
+# Test Script: eval command.
+
+test eval-1.1 {eval errors} {
+    eval
+} -error {wrong # args: should be "eval arg ?arg ...?"}
+
+test eval-2.1 {eval a script} {
+    eval {set a 1; set b 2}
+} -ok {2}
+
+test eval-2.2 {eval concatenates its arguments} {
+    eval set a { 5 }
+    set a
+} -ok {5}
+
+test eval-2.3 {eval in the caller's scope} -setup {
+    proc myproc {} {
+        set x local
+        eval {set x}
+    }
+} -body {
+    myproc
+} -cleanup {
+    rename myproc ""
+} -ok {local}
+
+test eval-2.4 {eval errors propagate} {
+    eval {error "oops"}
+} -error {oops}
+
+test eval-3.1 {eval a pure list: words are not substituted again} {
+    set y "wrong"
+    eval [list set x {$y [error boom]}]
+} -ok {$y [error boom]}
+
+test eval-3.2 {eval a pure list: whitespace and braces in words} {
+    eval [list set x "a \{b\n c"]
+    string length $x
+} -ok {7}
+
+test eval-3.3 {eval an empty list} {
+    eval [list]
+} -ok {}
+
+test eval-3.4 {eval a list that has a string rep} {
+    set cmd [list set x {$y}]
+    string length $cmd
+    eval $cmd
+} -ok {$y}
+
+test eval-3.5 {eval a pure list: unknown command} {
+    eval [list nonesuch a b]
+} -error {invalid command name "nonesuch"}
@@ -301,3 +301,83 @@ rename aflag ""
 rename bflag ""
 rename a ""
 rename b ""
+
+#-------------------------------------------------------------------------
+# Parsed expressions are cached, but substitutions are evaluated each time.
+
+test expr-7.1 {cached expression sees new variable values} -body {
+    set ::cond {$::n < 3}
+    set ::n 0
+    set result [list]
+    while {[expr $::cond]} {
+        lappend result $::n
+        incr ::n
+    }
+    set result
+} -cleanup {
+    unset ::cond ::n
+} -ok {0 1 2}
+
+test expr-7.2 {cached expression re-evaluates commands} -body {
+    set ::n 0
+    set e {[incr ::n] * 10}
+    list [expr $e] [expr $e] [expr $e]
+} -cleanup {
+    unset ::n
+} -ok {10 20 30}
+
+test expr-7.3 {syntax error is reported after earlier substitutions} -body {
+    set ::n 0
+    catch {expr {[incr ::n] + $}} msg
+    list $::n $msg
+} -cleanup {
+    unset ::n
+} -ok {1 {invalid character "$"}}
+
+test expr-7.4 {missing close-bracket is reported after the command runs} -body {
+    set ::n 0
+    catch {expr {1 + [incr ::n}} msg
+    list $::n $msg
+} -cleanup {
+    unset ::n
+} -ok {1 {missing close-bracket}}
+
+test expr-7.5 {unknown math function} -body {
+    expr {1 + nonesuch(2)}
+} -error {unknown math function "nonesuch"}
+
+#-------------------------------------------------------------------------
+# Deeply nested expressions are an error, not a stack overflow.
+
+test expr-8.1 {deeply parenthesized expression} -body {
+    set e 1
+    for {set i 0} {$i < 10000} {incr i} {
+        set e "($e)"
+    }
+    expr $e
+} -error {too many nested calls to Interp::eval (infinite loop?)}
+
+test expr-8.2 {deeply nested unary operators} -body {
+    set e 1
+    for {set i 0} {$i < 10000} {incr i} {
+        set e "-$e"
+    }
+    expr $e
+} -error {too many nested calls to Interp::eval (infinite loop?)}
+
+test expr-8.3 {deeply nested command substitutions} -body {
+    set e 1
+    for {set i 0} {$i < 2000} {incr i} {
+        set e "\[expr {1 + $e}\]"
+    }
+    catch {expr $e} result
+    set result
+} -ok {too many nested calls to Interp::eval (infinite loop?)}
+
+test expr-8.4 {moderately parenthesized expression} -body {
+    set e 1
+    for {set i 0} {$i < 50} {incr i} {
+        set e "($e)"
+    }
+    expr $e
+} -ok {1}
@@ -103,3 +103,17 @@ test for-3.5 {continue in for test} {
 test for-3.6 {continue in for next} {
     for {} {1} {continue} {}
 } -error {invoked "continue" outside of a loop}
+
+test for-4.1 {for with boolean string condition} {
+    set i 0
+    for {} {"no"} {incr i} {}
+    set i
+} -ok {0}
+
+test for-4.2 {for with float condition} {
+    set result {}
+    for {set x 1.0} {$x} {set x [expr {$x - 0.5}]} {
+        lappend result $x
+    }
+    set result
+} -ok {1.0 0.5}
@@ -2,7 +2,7 @@
 
 test foreach-1.1 {foreach argument error} {
     foreach
-} -error {wrong # args: should be "foreach varList list body"}
+} -error {wrong # args: should be "foreach varList list ?varList list ...? body"}
 
 test foreach-1.2 {error in body} {
     foreach x {1 2 3} {
@@ -54,3 +54,77 @@ test foreach-4.2 {continue in loop body} {
     }
     list $a $b
 } -ok {3 start}
+
+test foreach-5.1 {multiple lists: argument errors} {
+    foreach a {1 2} b {3 4}
+} -error {wrong # args: should be "foreach varList list ?varList list ...? body"}
+
+test foreach-5.2 {empty varlist} {
+    foreach {} {1 2} {}
+} -error {foreach varlist is empty}
+
+test foreach-5.3 {multiple lists} {
+    set result ""
+    foreach a {1 2 3} b {x y z} {
+        lappend result $a$b
+    }
+    set result
+} -ok {1x 2y 3z}
+
+test foreach-5.4 {multiple lists with strides} {
+    set result ""
+    foreach {a b} {1 2 3 4} c {x y} {
+        lappend result "$a$b$c"
+    }
+    set result
+} -ok {12x 34y}
+
+test foreach-5.5 {shorter lists are padded} {
+    set result ""
+    foreach a {1 2 3} {b c} {x y z} {
+        lappend result [list $a $b $c]
+    }
+    set result
+} -ok {{1 x y} {2 z {}} {3 {} {}}}
+
+test foreach-5.6 {three lists} {
+    set result ""
+    foreach a {1 2} b {3 4} c {5 6} {
+        lappend result [expr {$a + $b + $c}]
+    }
+    set result
+} -ok {9 12}
+
+test foreach-5.7 {break with multiple lists} {
+    set result ""
+    foreach a {1 2 3} b {x y z} {
+        if {$a == 2} break
+        lappend result $a$b
+    }
+    set result
+} -ok {1x}
+
+test foreach-6.1 {return from enclosing proc} -setup {
+    proc findit {list value} {
+        foreach item $list {
+            if {$item == $value} { return found }
+        }
+        return missing
+    }
+} -body {
+    list [findit {1 2 3} 2] [findit {1 2 3} 4]
+} -cleanup {
+    rename findit ""
+} -ok {found missing}
+
+test foreach-6.2 {return with multiple lists} -setup {
+    proc firstpair {a b} {
+        foreach x $a y $b {
+            return $x$y
+        }
+    }
+} -body {
+    firstpair {1 2} {x y}
+} -cleanup {
+    rename firstpair ""
+} -ok {1x}
@@ -0,0 +1,119 @@
+# Test Suite: format command
+
+test format-1.1 {format: syntax} {
+    format
+} -error {wrong # args: should be "format formatString ?arg arg ...?"}
+
+test format-1.2 {format: plain text} {
+    format "abc"
+} -ok {abc}
+
+test format-1.3 {format: percent} {
+    format "100%%"
+} -ok {100%}
+
+test format-1.4 {format: not enough args} {
+    format "%d %d" 1
+} -error {not enough arguments for all format specifiers}
+
+test format-1.5 {format: bad specifier} {
+    format "%q" 1
+} -error {bad field specifier "q"}
+
+test format-1.6 {format: incomplete specifier} {
+    format "%5"
+} -error {format string ended in middle of field specifier}
+
+test format-1.7 {format: non-integer} {
+    format "%d" abc
+} -error {expected integer but got "abc"}
+
+test format-2.1 {format: integers} {
+    list [format %d 42] [format %i -42] [format %u 42]
+} -ok {42 -42 42}
+
+test format-2.2 {format: radixes} {
+    list [format %o 8] [format %x 255] [format %X 255] [format %b 5]
+} -ok {10 ff FF 101}
+
+test format-2.3 {format: alternate forms} {
+    list [format %#o 8] [format %#x 255] [format %#X 255] [format %#b 5] [format %#x 0]
+} -ok {010 0xff 0XFF 0b101 0}
+
+test format-2.4 {format: negative unsigned} {
+    list [format %x -1] [format %b -1]
+} -ok {ffffffffffffffff 1111111111111111111111111111111111111111111111111111111111111111}
+
+test format-2.5 {format: integer width and flags} {
+    list [format {[%5d]} 42] [format {[%-5d]} 42] [format {[%05d]} -42] \
+        [format {[%+d]} 42] [format {[% d]} 42] [format {[%+05d]} 42]
+} -ok {{[   42]} {[42   ]} {[-0042]} {[+42]} {[ 42]} {[+0042]}}
+
+test format-2.6 {format: integer precision} {
+    list [format {[%.3d]} 7] [format {[%6.3d]} -7] [format {[%06.3d]} 7] [format {[%.0d]} 0]
+} -ok {{[007]} {[  -007]} {[   007]} {[]}}
+
+test format-2.7 {format: zero-padded binary with prefix} {
+    format %#010b 5
+} -ok {0b00000101}
+
+test format-2.8 {format: size modifiers ignored} {
+    list [format %ld 5] [format %lld 5] [format %hx 255]
+} -ok {5 5 ff}
+
+test format-3.1 {format: width from argument} {
+    list [format {[%*d]} 5 42] [format {[%*d]} -5 42] [format {[%-*s]} 4 ab]
+} -ok {{[   42]} {[42   ]} {[ab  ]}}
+
+test format-3.2 {format: precision from argument} {
+    list [format {[%.*f]} 2 3.14159] [format {[%*.*f]} 8 3 3.14159]
+} -ok {{[3.14]} {[   3.142]}}
+
+test format-3.3 {format: field width overflow} {
+    format %99999999999999999999d 1
+} -error {field width too large}
+
+test format-3.4 {format: field width too large} {
+    list [catch {format %999999999d x} msg] $msg [catch {format %*d 999999999 1} msg] $msg
+} -ok {1 {field width too large} 1 {field width too large}}
+
+test format-3.5 {format: precision too large} {
+    list [catch {format %.99999999999999999999f 1} msg] $msg \
+        [catch {format %.*s 999999999 x} msg] $msg
+} -ok {1 {precision too large} 1 {precision too large}}
+
+test format-4.1 {format: strings} {
+    list [format {[%s]} abc] [format {[%5s]} abc] [format {[%-5s]} abc] [format {[%.2s]} abc]
+} -ok {{[abc]} {[  abc]} {[abc  ]} {[ab]}}
+
+test format-4.2 {format: characters} {
+    list [format %c 65] [format {[%3c]} 97]
+} -ok {A {[  a]}}
+
+test format-4.3 {format: character code out of range} {
+    list [catch {format %c 4294967361} msg] $msg [catch {format %c 0x110000} msg] $msg \
+        [catch {format %c -1} msg] $msg [format %c 0x10FFFF]
+} -ok [list 1 {invalid character code "4294967361"} 1 {invalid character code "1114112"} \
+    1 {invalid character code "-1"} \U10FFFF]
+
+test format-5.1 {format: fixed point} {
+    list [format %f 1.5] [format %.2f 2.346] [format %.0f 2.5] [format %#.0f 3] \
+        [format {[%08.3f]} -3.14159] [format %+.1f 2]
+} -ok {1.500000 2.35 2 3. {[-003.142]} +2.0}
+
+test format-5.2 {format: exponential} {
+    list [format %e 150] [format %.2E 0.000123] [format %.0e 5] [format %e 0]
+} -ok {1.500000e+02 1.23E-04 5e+00 0.000000e+00}
+
+test format-5.3 {format: general} {
+    list [format %g 100000] [format %g 1000000] [format %g 0.0001] [format %g 0.00001] \
+        [format %g 1.5] [format %G 1e-10] [format %#g 1.5] [format %.3g 3.14159]
+} -ok {100000 1e+06 0.0001 1e-05 1.5 1E-10 1.50000 3.14}
+
+test format-5.4 {format: infinities} {
+    list [format %f Inf] [format %f -Inf] [format {[%5f]} inf]
+} -ok {Inf -Inf {[  Inf]}}
+
+test format-6.1 {format: mixed} {
+    format "%s has %d items costing %.2f" cart 3 9.5
+} -ok {cart has 3 items costing 9.50}
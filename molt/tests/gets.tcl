@@ -0,0 +1,12 @@
+# Test Suite: gets command
+#
+# Reading from stdin can't be tested in the test suite; see the unit tests in
+# channel.rs.
+
+test gets-1.1 {gets: syntax} {
+    gets
+} -error {wrong # args: should be "gets channelId ?varName?"}
+
+test gets-1.2 {gets: unknown channel} {
+    gets nonesuch
+} -error {can not find channel named "nonesuch"}
@@ -0,0 +1,55 @@
+# Test Script: glob
+#
+# Paths are relative to the tests directory.
+
+test glob-1.1 {glob errors} {
+    glob
+} -error {wrong # args: should be "glob ?switches? name ?name ...?"}
+
+test glob-1.2 {glob errors} {
+    glob -nonesuch *.tcl
+} -error {bad option "-nonesuch": must be -directory, -nocomplain, or --}
+
+test glob-1.3 {glob errors} {
+    glob -directory
+} -error {missing argument to "-directory"}
+
+test glob-1.4 {glob errors} {
+    glob -nocomplain
+} -error {wrong # args: should be "glob ?switches? name ?name ...?"}
+
+test glob-1.5 {no match} {
+    glob *.nonesuch
+} -error {no files matched glob pattern "*.nonesuch"}
+
+test glob-1.6 {no match, several patterns} {
+    glob *.nonesuch *.other
+} -error {no files matched glob patterns "*.nonesuch *.other"}
+
+test glob-2.1 {-nocomplain} {
+    glob -nocomplain *.nonesuch
+} -ok {}
+
+test glob-2.2 {literal name} {
+    glob all.tcl
+} -ok {all.tcl}
+
+test glob-2.3 {wildcards} {
+    glob al?.tcl all*.tcl
+} -ok {all.tcl all.tcl}
+
+test glob-2.4 {bracketed sets} {
+    glob ../src/\[mp\]a*.rs
+} -ok {../src/macros.rs ../src/parser.rs}
+
+test glob-2.5 {-directory} {
+    glob -directory ../src li?.rs
+} -ok {../src/lib.rs}
+
+test glob-2.6 {wildcards in directory names} {
+    glob ../sr?/lib.rs
+} -ok {../src/lib.rs}
+
+test glob-2.7 {--} {
+    glob -nocomplain -- -nonesuch
+} -ok {}
@@ -99,3 +99,15 @@ test if-4.1 {if command} {
 
     list [doit 1] [doit 0]
 } -ok {then else}
+
+test if-5.1 {if with boolean string conditions} {
+    list [if {"true"} {set a yes} {set a no}] [if {"off"} {set a yes} {set a no}]
+} -ok {yes no}
+
+test if-5.2 {if with numeric conditions} {
+    list [if {2 * 3} {set a yes} {set a no}] [if {0.0} {set a yes} {set a no}]
+} -ok {yes no}
+
+test if-5.3 {if with non-boolean string condition} {
+    if {"abc"} {set a yes}
+} -error {expected boolean but got "abc"}
@@ -36,3 +36,40 @@ test incr-3.1 {incr scalar as array} -body {
     set x ""
     incr x(0)
 } -error {can't set "x(0)": variable isn't array}
+
+test incr-3.2 {incr non-integer var} -body {
+    set a "hello"
+    incr a
+} -cleanup {
+    unset a
+} -error {expected integer but got "hello"}
+
+test incr-3.3 {non-integer var is unchanged} -body {
+    set a "hello"
+    catch {incr a}
+    set a
+} -cleanup {
+    unset a
+} -ok {hello}
+
+test incr-3.4 {incr non-integer array element} -body {
+    set a(1) "hello"
+    incr a(1)
+} -cleanup {
+    unset a
+} -error {expected integer but got "hello"}
+
+test incr-3.5 {incr new array element} -body {
+    set a(1) 5
+    incr a(2) 3
+    list $a(1) $a(2)
+} -cleanup {
+    unset a
+} -ok {5 3}
+
+test incr-3.6 {incr array variable} -body {
+    set a(1) 5
+    incr a
+} -cleanup {
+    unset a
+} -error {can't read "a": variable is array}
@@ -7,7 +7,7 @@ test info-1.1 {info errors} {
 # TODO: Really need glob matching.
 test info-1.2 {info errors} {
     info nonesuch
-} -error {unknown or ambiguous subcommand "nonesuch": must be args, body, cmdtype, commands, complete, default, exists, globals, locals, procs, or vars}
+} -error {unknown or ambiguous subcommand "nonesuch": must be args, body, cmdtype, commands, complete, default, exists, frame, globals, locals, nameofexecutable, patchlevel, proc, procs, script, tclversion, or vars}
 
 test info-2.1 {info complete errors} {
     info complete
@@ -180,6 +180,48 @@ test info-9.4 {info exists command, array set} {
     info exists b
 } -ok {1}
 
+test info-9.5 {info exists command, empty array vs. nonexistent array} {
+    array set ::emptyArray {}
+    set result [list \
+        [info exists ::emptyArray] [array exists ::emptyArray] \
+        [info exists ::emptyArray(1)] \
+        [info exists ::noArray] [array exists ::noArray]]
+    unset ::emptyArray
+    set result
+} -ok {1 1 0 0 0}
+
+test info-9.6 {info exists command, array with its last element unset} {
+    set ::lastElem(1) x
+    unset ::lastElem(1)
+    set result [list [info exists ::lastElem] [array exists ::lastElem] \
+        [info exists ::lastElem(1)]]
+    unset ::lastElem
+    set result
+} -ok {1 1 0}
+
+test info-9.7 {info exists command, element of scalar} {
+    set s 1
+    info exists s(1)
+} -ok {0}
+
+test info-9.8 {info exists command, linked array} -setup {
+    array set ::linkedArray {1 x}
+    proc checkLinked {} {
+        global linkedArray
+        list [info exists linkedArray] [info exists linkedArray(1)] \
+            [info exists linkedArray(2)]
+    }
+} -body {
+    checkLinked
+} -cleanup {
+    rename checkLinked ""
+    unset ::linkedArray
+} -ok {1 1 0}
+
+test info-9.9 {info exists command, environment array} {
+    list [info exists ::env] [array exists ::env] [info exists ::env(MOLT_NO_SUCH_VAR)]
+} -ok {1 1 0}
+
 test info-10.1 {info globals command: some defined; toplevel} -body {
     # Note: we don't test for emptiness before we define globals.  Eventually there will be
     # standard variables defined, which would break the test.
@@ -242,3 +284,171 @@ test info-11.2 {info globals command: some defined; in proc} -setup {
     global a b
     unset a b
 } -ok {0 0 1 1 1}
+
+test info-12.1 {info commands is sorted} {
+    set names [info commands]
+    set sorted 1
+    set prev ""
+    foreach name $names {
+        if {[string compare $prev $name] >= 0} {
+            set sorted 0
+        }
+        set prev $name
+    }
+    list $sorted [expr {$names eq [info commands]}]
+} -ok {1 1}
+
+test info-12.2 {info procs is sorted} -setup {
+    proc zed {} {}
+    proc alpha {} {}
+    proc middle {} {}
+} -body {
+    set result {}
+    foreach name [info procs] {
+        if {$name in {zed alpha middle}} {
+            lappend result $name
+        }
+    }
+    set result
+} -cleanup {
+    rename zed ""
+    rename alpha ""
+    rename middle ""
+} -ok {alpha middle zed}
+
+test info-12.3 {info commands -ensemble: errors} {
+    info commands -ensemble
+} -error {wrong # args: should be "info commands ?-ensemble command?"}
+
+test info-12.4 {info commands -ensemble: errors} {
+    info commands -nonesuch info
+} -error {wrong # args: should be "info commands ?-ensemble command?"}
+
+test info-12.5 {info commands -ensemble: not an ensemble} {
+    info commands -ensemble info
+} -error {"info" isn't an ensemble}
+
+test info-13.1 {info frame errors} {
+    info frame 1 2
+} -error {wrong # args: should be "info frame ?number?"}
+
+test info-13.2 {info frame errors} {
+    info frame nonesuch
+} -error {expected integer but got "nonesuch"}
+
+test info-13.3 {info frame errors} {
+    info frame 1000
+} -error {bad level "1000"}
+
+test info-13.4 {info frame: current frame} {
+    set info [info frame 0]
+    list [dict get $info type] [dict get $info cmd] [dict get $info line]
+} -ok {eval {info frame 0} 2}
+
+test info-13.5 {info frame: frame count} -setup {
+    proc myproc {} { info frame }
+} -body {
+    expr {[myproc] - [info frame]}
+} -cleanup {
+    rename myproc ""
+} -ok {1}
+
+test info-13.6 {info frame: in proc} -setup {
+    proc myproc {a} {
+        info frame 0
+    }
+} -body {
+    set info [myproc 1]
+    list [dict get $info type] [dict get $info proc] [dict get $info cmd] \
+        [dict get $info line]
+} -cleanup {
+    rename myproc ""
+} -ok {proc myproc {info frame 0} 2}
+
+test info-13.7 {info frame: caller, with substitutions} -setup {
+    proc myproc {a} { info frame -1 }
+} -body {
+    set x xyz
+    dict get [myproc $x] cmd
+} -cleanup {
+    rename myproc ""
+} -ok {myproc xyz}
+
+test info-13.8 {info frame: absolute frame numbers} -setup {
+    proc myproc {} {
+        set n [info frame]
+        list $n [dict get [info frame $n] cmd]
+    }
+} -body {
+    set result [myproc]
+    expr {[lindex $result 1] eq "info frame [lindex $result 0]"}
+} -cleanup {
+    rename myproc ""
+} -ok {1}
+
+test info-14.1 {info script errors} {
+    info script nonesuch
+} -error {wrong # args: should be "info script"}
+
+test info-14.2 {info script: the test script} {
+    info script
+} -ok {info.tcl}
+
+test info-15.1 {info proc errors} {
+    info proc
+} -error {wrong # args: should be "info proc procname"}
+
+test info-15.2 {info proc errors} {
+    info proc set
+} -error {"set" isn't a procedure}
+
+test info-15.3 {info proc: required arguments only} -setup {
+    proc myproc {a b} {list $a $b}
+} -body {
+    info proc myproc
+} -cleanup {
+    rename myproc ""
+} -ok {args {a b} body {list $a $b} numArgs 2 numOptionalArgs 0 hasArgs 0}
+
+test info-15.4 {info proc: optional and variadic arguments} -setup {
+    proc myproc {a {b 1} {c 2} args} {}
+} -body {
+    set info [info proc myproc]
+    list [dict get $info args] [dict get $info numArgs] \
+        [dict get $info numOptionalArgs] [dict get $info hasArgs]
+} -cleanup {
+    rename myproc ""
+} -ok {{a b c args} 1 2 1}
+
+test info-15.5 {info proc: no arguments} -setup {
+    proc myproc {} {}
+} -body {
+    info proc myproc
+} -cleanup {
+    rename myproc ""
+} -ok {args {} body {} numArgs 0 numOptionalArgs 0 hasArgs 0}
+
+test info-16.1 {info tclversion errors} {
+    info tclversion 1
+} -error {wrong # args: should be "info tclversion"}
+
+test info-16.2 {info patchlevel errors} {
+    info patchlevel 1
+} -error {wrong # args: should be "info patchlevel"}
+
+test info-16.3 {info tclversion is a prefix of info patchlevel} {
+    set version [info tclversion]
+    expr {[string first "$version." [info patchlevel]] == 0}
+} -ok {1}
+
+test info-16.4 {info tclversion is a number} {
+    string is double -strict [info tclversion]
+} -ok {1}
+
+test info-17.1 {info nameofexecutable errors} {
+    info nameofexecutable 1
+} -error {wrong # args: should be "info nameofexecutable"}
+
+test info-17.2 {info nameofexecutable} {
+    expr {[info nameofexecutable] ne ""}
+} -ok {1}
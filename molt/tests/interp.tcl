@@ -7,3 +7,258 @@ test interp-1.1 {stack level checking} -setup {
 } -cleanup {
     rename bad_recursion {}
 } -error {too many nested calls to Interp::eval (infinite loop?)}
+
+test interp-1.2 {stack level checking, nested children} -setup {
+    set script {
+        interp create c
+        interp eval c [list set script $script]
+        interp eval c $script
+    }
+} -body {
+    eval $script
+} -cleanup {
+    interp delete c
+    unset script
+} -error {too many nested calls to Interp::eval (infinite loop?)}
+
+test interp-2.1 {interp errors} {
+    interp
+} -error {wrong # args: should be "interp subcommand ?arg ...?"}
+
+test interp-2.2 {interp create errors} {
+    interp create -nonesuch
+} -error {bad option "-nonesuch": must be -safe or --}
+
+test interp-2.3 {interp create errors} -setup {
+    interp create child
+} -body {
+    interp create child
+} -cleanup {
+    interp delete child
+} -error {interpreter named "child" already exists, cannot create}
+
+test interp-2.4 {interp eval errors} {
+    interp eval nonesuch {set a 1}
+} -error {could not find interpreter "nonesuch"}
+
+test interp-2.5 {interp delete errors} {
+    interp delete nonesuch
+} -error {could not find interpreter "nonesuch"}
+
+test interp-3.1 {interp create returns name} -body {
+    interp create child
+} -cleanup {
+    interp delete child
+} -ok {child}
+
+test interp-3.2 {interp create generates names} -body {
+    set a [interp create]
+    set b [interp create]
+    list [string equal $a $b] [interp exists $a] [interp exists $b]
+} -cleanup {
+    interp delete $a $b
+} -ok {0 1 1}
+
+test interp-3.3 {interp exists} -setup {
+    interp create child
+} -body {
+    set a [interp exists child]
+    interp delete child
+    list $a [interp exists child]
+} -ok {1 0}
+
+test interp-4.1 {interp eval returns result} -setup {
+    interp create child
+} -body {
+    interp eval child {expr {1 + 2}}
+} -cleanup {
+    interp delete child
+} -ok {3}
+
+test interp-4.2 {child variables are invisible in parent} -setup {
+    interp create child
+} -body {
+    interp eval child {set ::childvar 1}
+    list [info exists ::childvar] [interp eval child {set ::childvar}]
+} -cleanup {
+    interp delete child
+} -ok {0 1}
+
+test interp-4.3 {parent variables are invisible in child} -setup {
+    set ::parentvar 1
+    interp create child
+} -body {
+    interp eval child {info exists ::parentvar}
+} -cleanup {
+    interp delete child
+    unset ::parentvar
+} -ok {0}
+
+test interp-4.4 {child errors propagate} -setup {
+    interp create child
+    interp eval child {proc fail {} { error "child failure" }}
+} -body {
+    catch {interp eval child {fail}} result opts
+    list $result [expr {[string first {"fail"} [dict get $opts -errorinfo]] >= 0}]
+} -cleanup {
+    interp delete child
+} -ok {{child failure} 1}
+
+test interp-4.5 {deleting a child frees its commands} -setup {
+    interp create child
+    interp eval child {proc hello {} { return hello }}
+} -body {
+    interp delete child
+    interp create child
+    interp eval child {expr {"hello" in [info commands]}}
+} -cleanup {
+    interp delete child
+} -ok {0}
+
+test interp-2.6 {interp issafe errors} {
+    interp issafe nonesuch
+} -error {could not find interpreter "nonesuch"}
+
+test interp-2.7 {interp issafe errors} {
+    interp issafe a b
+} -error {wrong # args: should be "interp issafe ?path?"}
+
+test interp-5.1 {safe child cannot exit} -setup {
+    interp create -safe child
+} -body {
+    interp eval child {exit}
+} -cleanup {
+    interp delete child
+} -error {invalid command name "exit"}
+
+test interp-5.2 {safe child cannot source} -setup {
+    interp create -safe child
+} -body {
+    interp eval child {source all.tcl}
+} -cleanup {
+    interp delete child
+} -error {invalid command name "source"}
+
+test interp-5.3 {safe child has no env} -setup {
+    interp create -safe child
+} -body {
+    interp eval child {info exists ::env}
+} -cleanup {
+    interp delete child
+} -ok {0}
+
+test interp-5.4 {children of safe children are safe} -setup {
+    interp create -safe child
+} -body {
+    interp eval child {
+        interp create grandchild
+        interp eval grandchild {expr {"exit" in [info commands]}}
+    }
+} -cleanup {
+    interp delete child
+} -ok {0}
+
+test interp-5.5 {safe child cannot read stdin} -setup {
+    interp create -safe child
+} -body {
+    interp eval child {gets stdin}
+} -cleanup {
+    interp delete child
+} -error {invalid command name "gets"}
+
+test interp-5.6 {safe child can write to stdout} -setup {
+    interp create -safe child
+} -body {
+    interp eval child {expr {"puts" in [info commands]}}
+} -cleanup {
+    interp delete child
+} -ok {1}
+
+test interp-6.1 {interp issafe} -setup {
+    interp create -safe safechild
+    interp create child
+} -body {
+    list [interp issafe] [interp issafe safechild] [interp issafe child] \
+        [interp eval safechild {interp issafe}]
+} -cleanup {
+    interp delete safechild child
+} -ok {0 1 0 1}
+
+test interp-2.8 {interp alias errors} {
+    interp alias nonesuch
+} -error {wrong # args: should be "interp alias path aliasName ?targetCmd ?arg ...??"}
+
+test interp-2.9 {interp alias errors} {
+    interp alias nonesuch hello list
+} -error {could not find interpreter "nonesuch"}
+
+test interp-2.10 {interp alias errors} -setup {
+    interp create child
+} -body {
+    interp alias child nonesuch
+} -cleanup {
+    interp delete child
+} -error {"nonesuch" isn't an alias}
+
+test interp-7.1 {interp alias calls the parent} -setup {
+    proc greet {greeting name} { return "$greeting, $name" }
+    interp create -safe child
+} -body {
+    list [interp alias child hello greet Hello] [interp eval child {hello World}]
+} -cleanup {
+    interp delete child
+    rename greet ""
+} -ok {hello {Hello, World}}
+
+test interp-7.2 {interp alias target runs in the parent} -setup {
+    set ::counter 0
+    interp create child
+    interp alias child count incr ::counter
+} -body {
+    interp eval child {
+        set counter 10
+        count
+        count
+    }
+    list $::counter [interp eval child {set counter}]
+} -cleanup {
+    interp delete child
+    unset ::counter
+} -ok {2 10}
+
+test interp-7.3 {interp alias errors propagate} -setup {
+    interp create child
+    interp alias child oops error "parent error"
+} -body {
+    interp eval child {oops}
+} -cleanup {
+    interp delete child
+} -error {parent error}
+
+test interp-7.4 {interp alias query and delete} -setup {
+    interp create child
+    interp alias child hello list Hello
+} -body {
+    set target [interp alias child hello]
+    interp alias child hello {}
+    list $target [interp eval child {expr {"hello" in [info commands]}}]
+} -cleanup {
+    interp delete child
+} -ok {{list Hello} 0}
+
+test interp-7.5 {interp alias in the current interpreter} -setup {
+    interp alias {} hello list Hello
+} -body {
+    list [hello World] [interp alias {} hello]
+} -cleanup {
+    rename hello ""
+} -ok {{Hello World} {list Hello}}
+
+test interp-7.6 {the child is unavailable to its own aliases} -setup {
+    interp create child
+    interp alias child reenter interp eval child {set a 1}
+} -body {
+    interp eval child {reenter}
+} -cleanup {
+    interp delete child
+} -error {could not find interpreter "child"}
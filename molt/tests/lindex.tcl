@@ -36,6 +36,22 @@ test list-2.2 {index list} {
     lindex {a {b c d} e} {1 1}
 } -ok {c}
 
+test list-2.3 {end index} {
+    list [lindex {a b c} end] [lindex {a b c} end-1] [lindex {a b c} end+1]
+} -ok {c b {}}
+
+test list-2.4 {end index, nested} {
+    lindex {a {b c d} e} {1 end-2}
+} -ok {b}
+
+test list-2.5 {index arithmetic} {
+    list [lindex {a b c} 0+1] [lindex {a b c} 2-2]
+} -ok {b a}
+
+test list-2.6 {bad index} {
+    lindex {a b c} foo
+} -error {bad index "foo": must be integer?[+-]integer? or end?[+-]integer?}
+
 test list-3.1 {no arguments} {
     lindex
 } -error {wrong # args: should be "lindex list ?index ...?"}
@@ -22,3 +22,27 @@ test list-1.4 {list command} {
 test list-1.5 {list command} {
     list a {} c
 } -ok {a {} c}
+
+test list-2.1 {list quoting, leading brace} {
+    list {{a}} b
+} -ok {{{a}} b}
+
+test list-2.2 {list quoting, trailing backslash} {
+    set x [list "a\\" b]
+    list [llength $x] [lindex $x 0]
+} -ok {2 a\\}
+
+test list-2.3 {list quoting, unbalanced braces} {
+    set x [list "\}\{" b]
+    list [llength $x] [lindex $x 0]
+} -ok {2 \}\{}
+
+test list-2.4 {list quoting, leading quote} {
+    set x [list {"a} b]
+    list [llength $x] [lindex $x 0]
+} -ok {2 {"a}}
+
+test list-2.5 {list quoting, newline in escaped element} {
+    set x [list "\{\n" b]
+    list $x [expr {[lindex $x 0] eq "\{\n"}]
+} -ok {{\{\n b} 1}
@@ -0,0 +1,227 @@
+# Test Script: namespace and variable
+
+test namespace-1.1 {namespace errors} {
+    namespace
+} -error {wrong # args: should be "namespace subcommand ?arg ...?"}
+
+test namespace-1.2 {namespace errors} {
+    namespace nonesuch
+} -error {unknown or ambiguous subcommand "nonesuch": must be current, eval, or exists}
+
+test namespace-2.1 {namespace current} {
+    namespace current
+} -ok {::}
+
+test namespace-2.2 {namespace current} {
+    namespace current extra
+} -error {wrong # args: should be "namespace current"}
+
+test namespace-3.1 {namespace eval errors} {
+    namespace eval ns1
+} -error {wrong # args: should be "namespace eval name script"}
+
+test namespace-3.2 {namespace eval, current namespace} {
+    namespace eval ns1 { namespace current }
+} -ok {::ns1}
+
+test namespace-3.3 {namespace eval, nested} {
+    namespace eval ns1 {
+        namespace eval inner { namespace current }
+    }
+} -ok {::ns1::inner}
+
+test namespace-3.4 {namespace eval, qualified name} {
+    namespace eval ::ns1::inner2 { namespace current }
+} -ok {::ns1::inner2}
+
+test namespace-3.5 {namespace eval restores current namespace on error} {
+    catch {namespace eval ns1 { error "oops" }} result
+    list $result [namespace current]
+} -ok {oops ::}
+
+test namespace-4.1 {namespace exists} {
+    namespace eval ns2 {}
+    list [namespace exists ns2] [namespace exists ::ns2] [namespace exists nonesuch]
+} -ok {1 1 0}
+
+test namespace-4.2 {namespace exists, parents are created} {
+    namespace eval ns3::child {}
+    list [namespace exists ns3] [namespace exists ns3::child]
+} -ok {1 1}
+
+test namespace-4.3 {namespace exists, global namespace} {
+    namespace exists ::
+} -ok {1}
+
+test namespace-5.1 {same-named procs don't collide} -setup {
+    namespace eval alpha {
+        proc name {} { return alpha }
+    }
+    namespace eval beta {
+        proc name {} { return beta }
+    }
+} -body {
+    list [alpha::name] [::beta::name] [namespace eval alpha { name }]
+} -cleanup {
+    rename alpha::name ""
+    rename beta::name ""
+} -ok {alpha beta alpha}
+
+test namespace-5.2 {procs call procs in their own namespace} -setup {
+    proc helper {} { return global }
+    namespace eval alpha {
+        proc helper {} { return alpha }
+        proc run {} { helper }
+    }
+    namespace eval beta {
+        proc run {} { helper }
+    }
+} -body {
+    list [alpha::run] [beta::run] [helper]
+} -cleanup {
+    rename helper ""
+    rename alpha::helper ""
+    rename alpha::run ""
+    rename beta::run ""
+} -ok {alpha global global}
+
+test namespace-5.3 {procs run in their namespace} -setup {
+    namespace eval alpha {
+        proc where {} { namespace current }
+    }
+} -body {
+    alpha::where
+} -cleanup {
+    rename alpha::where ""
+} -ok {::alpha}
+
+test namespace-5.4 {qualified proc names} -setup {
+    proc ::alpha::qualified {} { return ok }
+} -body {
+    namespace eval beta { ::alpha::qualified }
+} -cleanup {
+    rename ::alpha::qualified ""
+} -ok {ok}
+
+test namespace-5.5 {proc in unknown namespace} {
+    proc nonesuch::frob {} {}
+} -error {can't create procedure "nonesuch::frob": unknown namespace}
+
+test namespace-5.6 {info commands shows qualified names} -setup {
+    namespace eval alpha { proc listed {} {} }
+} -body {
+    expr {"alpha::listed" in [info commands]}
+} -cleanup {
+    rename alpha::listed ""
+} -ok {1}
+
+test variable-1.1 {variable errors} {
+    variable
+} -error {wrong # args: should be "variable ?name value ...? name ?value?"}
+
+test variable-2.1 {namespace variables} -setup {
+    namespace eval alpha {
+        variable count 1
+    }
+    namespace eval beta {
+        variable count 2
+    }
+} -body {
+    list $alpha::count $::beta::count
+} -cleanup {
+    unset alpha::count beta::count
+} -ok {1 2}
+
+test variable-2.2 {variable links procedure locals} -setup {
+    namespace eval alpha {
+        variable count 0
+        proc next {} {
+            variable count
+            incr count
+        }
+    }
+} -body {
+    alpha::next
+    alpha::next
+    set alpha::count
+} -cleanup {
+    rename alpha::next ""
+    unset alpha::count
+} -ok {2}
+
+test variable-2.3 {variable sets several variables} -setup {
+    namespace eval alpha {
+        variable a 1 b 2 c
+    }
+} -body {
+    list $alpha::a $alpha::b [info exists alpha::c]
+} -cleanup {
+    unset alpha::a alpha::b
+} -ok {1 2 0}
+
+test variable-2.4 {variable in global namespace} -setup {
+    set ::gvar 5
+    proc getgvar {} {
+        variable gvar
+        return $gvar
+    }
+} -body {
+    getgvar
+} -cleanup {
+    rename getgvar ""
+    unset ::gvar
+} -ok {5}
+
+test variable-2.5 {namespace eval variables are namespace variables} -body {
+    namespace eval alpha {
+        set temp 1
+    }
+    list [info exists temp] $alpha::temp [namespace eval alpha { set temp }]
+} -cleanup {
+    unset alpha::temp
+} -ok {0 1 1}
+
+test variable-2.6 {namespace eval sees existing globals} -setup {
+    set ::gvar 1
+} -body {
+    namespace eval alpha {
+        incr gvar
+    }
+    list $::gvar [info exists alpha::gvar]
+} -cleanup {
+    unset ::gvar
+} -ok {2 0}
+
+test variable-2.7 {namespace eval in the global namespace} -body {
+    namespace eval :: {
+        set nsglobal 1
+    }
+    set ::nsglobal
+} -cleanup {
+    unset ::nsglobal
+} -ok {1}
+
+test variable-2.8 {namespace eval variables are visible to the namespace's procs} -setup {
+    namespace eval alpha {
+        set counter 0
+        proc bump {} {
+            variable counter
+            incr counter
+        }
+    }
+} -body {
+    alpha::bump
+    namespace eval alpha { set counter }
+} -cleanup {
+    rename alpha::bump ""
+    unset alpha::counter
+} -ok {1}
+
+test variable-3.1 {qualified variable names} {
+    set ::qvar 1
+    proc getqvar {} { set ::qvar }
+    set result [getqvar]
+    rename getqvar ""
+    unset ::qvar
+    set result
+} -ok {1}
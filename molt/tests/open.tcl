@@ -0,0 +1,69 @@
+# Test Suite: open, close, and eof commands
+#
+# Reading and writing files is tested by the unit tests in channel.rs.
+
+test open-1.1 {open: syntax} {
+    open
+} -error {wrong # args: should be "open fileName ?access?"}
+
+test open-1.2 {open: bad access mode} {
+    open all.tcl q
+} -error {illegal access mode "q"}
+
+test open-1.3 {open: no such file} {
+    open nonesuch.txt
+} -error {couldn't open "nonesuch.txt": no such file or directory}
+
+test open-2.1 {open and read a file} -body {
+    set f [open all.tcl]
+    set line [gets $f]
+    close $f
+    set line
+} -ok {# Runs all tests.}
+
+test open-2.2 {read a file to the end} -body {
+    set f [open all.tcl r]
+    set before [eof $f]
+    read $f
+    set after [eof $f]
+    close $f
+    list $before $after
+} -ok {0 1}
+
+test open-2.3 {gets into a variable at end of file} -body {
+    set f [open all.tcl]
+    read $f
+    set count [gets $f line]
+    close $f
+    list $count $line
+} -ok {-1 {}}
+
+test open-2.4 {can't write a read-only channel} -body {
+    set f [open all.tcl]
+    catch {puts $f "Howdy"} result
+    close $f
+    string equal $result "channel \"$f\" wasn't opened for writing"
+} -ok {1}
+
+test close-1.1 {close: syntax} {
+    close
+} -error {wrong # args: should be "close channelId"}
+
+test close-1.2 {close: unknown channel} {
+    close nonesuch
+} -error {can not find channel named "nonesuch"}
+
+test close-1.3 {close: closed channel} -body {
+    set f [open all.tcl]
+    close $f
+    catch {close $f} result
+    string equal $result "can not find channel named \"$f\""
+} -ok {1}
+
+test eof-1.1 {eof: syntax} {
+    eof
+} -error {wrong # args: should be "eof channelId"}
+
+test eof-1.2 {eof: unknown channel} {
+    eof nonesuch
+} -error {can not find channel named "nonesuch"}
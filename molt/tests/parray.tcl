@@ -0,0 +1,98 @@
+# Test Suite: parray command
+
+# Redirects calls to puts into ::output, until end_capture is called.
+proc begin_capture {} {
+    rename puts ::_puts
+    proc ::puts {text} { append ::output $text\n }
+    set ::output ""
+}
+
+# Restores puts, returning the captured output.
+proc end_capture {} {
+    rename ::puts ""
+    rename ::_puts puts
+    return $::output
+}
+
+test parray-1.1 {parray: syntax} {
+    parray
+} -error {wrong # args: should be "parray arrayName ?pattern?"}
+
+test parray-1.2 {parray: not an array} -setup {
+    set ::scalar 1
+} -body {
+    parray ::scalar
+} -cleanup {
+    unset ::scalar
+} -error {"::scalar" isn't an array}
+
+test parray-1.3 {parray: no such variable} {
+    parray nonesuch
+} -error {"nonesuch" isn't an array}
+
+test parray-2.1 {parray: sorted and aligned} -setup {
+    array set ::arr {b 2 a 1 ccc {x y}}
+} -body {
+    begin_capture
+    parray ::arr
+    end_capture
+} -cleanup {
+    unset ::arr
+} -ok {::arr(a)   = 1
+::arr(b)   = 2
+::arr(ccc) = x y
+}
+
+test parray-2.2 {parray: local array} -setup {
+    proc myproc {} {
+        set a(1) one
+        begin_capture
+        parray a
+        end_capture
+    }
+} -body {
+    myproc
+} -cleanup {
+    rename myproc ""
+} -ok {a(1) = one
+}
+
+test parray-2.3 {parray: empty array} -setup {
+    array set ::arr {}
+} -body {
+    begin_capture
+    parray ::arr
+    end_capture
+} -cleanup {
+    unset ::arr
+} -ok {}
+
+test parray-2.4 {parray: pattern} -setup {
+    array set ::arr {long 1 l 2 other 3 lx {x y}}
+} -body {
+    begin_capture
+    parray ::arr l*
+    end_capture
+} -cleanup {
+    unset ::arr
+} -ok {::arr(l)    = 2
+::arr(long) = 1
+::arr(lx)   = x y
+}
+
+test parray-2.5 {parray: pattern matches nothing} -setup {
+    array set ::arr {a 1}
+} -body {
+    begin_capture
+    parray ::arr z*
+    end_capture
+} -cleanup {
+    unset ::arr
+} -ok {}
+
+test parray-2.6 {parray: too many args} {
+    parray a b c
+} -error {wrong # args: should be "parray arrayName ?pattern?"}
+
+rename begin_capture ""
+rename end_capture ""
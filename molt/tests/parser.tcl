@@ -34,3 +34,67 @@ test parser-2.5 {Splat expands quoted word} {
 test parser-2.6 {Splat expands interpolated script} {
     list - {*}[list a b c] -
 } -ok {- a b c -}
+
+# Backslash substitution
+
+test parser-3.1 {Single-character escapes} {
+    string length "\a\b\f\n\r\t\v"
+} -ok 7
+
+test parser-3.2 {Newline escape} {
+    list "line1\nline2"
+} -ok "{line1
+line2}"
+
+test parser-3.3 {Hex and Unicode escapes} {
+    list "\x41" "\u0042" "\U00000043"
+} -ok {A B C}
+
+test parser-3.4 {Octal escapes} {
+    list "\101" \102 "\7" == "\x07"
+} -ok [list A B \x07 == \x07]
+
+test parser-3.5 {Line continuation between words} {
+    list a \
+        b \
+        c
+} -ok {a b c}
+
+test parser-3.6 {Line continuation in quoted word} {
+    set a "x\
+        y"
+} -ok {x y}
+
+test parser-3.7 {Line continuation in braced word} {
+    set a {x\
+        y}
+} -ok {x y}
+
+#-------------------------------------------------------------------------
+# Nesting limits: deeply nested input is an error, not a stack overflow.
+
+proc nest {depth prefix suffix core} {
+    set s $core
+    for {set i 0} {$i < $depth} {incr i} {
+        set s "$prefix$s$suffix"
+    }
+    return $s
+}
+
+test parser-4.1 {Deeply nested brackets} -body {
+    eval [nest 10000 {[list } {]} 1]
+} -error {too many nested substitutions}
+
+test parser-4.2 {Deeply nested array indices} -body {
+    eval "set x [nest 10000 {$a(} {)} 1]"
+} -error {too many nested substitutions}
+
+test parser-4.3 {Deeply nested expansion} -body {
+    eval "list [nest 10000 {{*}} {} a]"
+} -error {too many nested substitutions}
+
+test parser-4.4 {Moderately nested brackets} -body {
+    eval "set x [nest 50 {[list } {]} 1]"
+} -ok {1}
+
+rename nest ""
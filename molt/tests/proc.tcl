@@ -71,12 +71,59 @@ test proc-3.2 {defined proc errors} -body {
 } -error {wrong # args: should be "myproc a ?b? ?arg ...?"}
 
 test proc-3.3 {defined proc errors} -body {
-    # Weird but allowed
     proc myproc {args {b 1} a} {}
+} -error {procedure "myproc": formal parameter "args" is not last}
+
+test proc-3.4 {defined proc errors} -body {
+    proc myproc {a {b 1} c} {}
+} -error {required argument "c" follows optional argument "b"}
+
+test proc-3.5 {defined proc errors} -body {
+    proc myproc {{a 1} b {c 2}} {}
+} -error {required argument "b" follows optional argument "a"}
+
+test proc-3.6 {defined proc errors} -body {
+    proc myproc {a {b 1}} {}
+    proc myproc {a args b} {}
+} -cleanup {
+    rename myproc ""
+} -error {procedure "myproc": formal parameter "args" is not last}
+
+test proc-3.7 {defined proc errors} -body {
+    proc myproc {a b a} {}
+} -error {procedure "myproc": formal parameter "a" is not unique}
+
+test proc-3.8 {defined proc errors} -body {
+    proc myproc {a {b 1} {a 2}} {}
+} -error {procedure "myproc": formal parameter "a" is not unique}
+
+test proc-3.9 {defined proc errors} -body {
+    proc myproc {a {args 3}} {}
+} -error {procedure "myproc": formal parameter "args" can't have a default value}
+
+test proc-3.10 {defined proc errors} -body {
+    proc myproc {{args 3}} {}
+} -error {procedure "myproc": formal parameter "args" can't have a default value}
+
+test proc-3.11 {defined proc errors} -body {
+    proc myproc {args args} {}
+} -error {procedure "myproc": formal parameter "args" is not last}
+
+test proc-3.12 {failed definition leaves existing proc} -body {
+    proc myproc {a} {return $a}
+    catch {proc myproc {a a} {}}
+    myproc 1
+} -cleanup {
+    rename myproc ""
+} -ok {1}
+
+test proc-3.13 {defined proc errors} -body {
+    proc myproc {a {b 1} {c 2} args} {}
+    myproc 1 2 3 4
     myproc
 } -cleanup {
     rename myproc ""
-} -error {wrong # args: should be "myproc args ?b? a"}
+} -error {wrong # args: should be "myproc a ?b? ?c? ?arg ...?"}
 
 # Normal argument
 test proc-4.1 {defined proc} -body {
@@ -112,9 +159,60 @@ test proc-4.3 {defined proc} -body {
 } -ok {A {1 {}} B {1 2} C {1 {2 3}}}
 
 test proc-4.4 {defined proc} -body {
-    # Weird but allowed
-    proc myproc {args {b 1} a} {list args $args b $b a $a}
-    myproc 1 2 3
+    proc myproc {a {b 1} {c 2} args} {list a $a b $b c $c args $args}
+    list [myproc 1] [myproc 1 2 3 4 5]
+} -cleanup {
+    rename myproc ""
+} -ok {{a 1 b 1 c 2 args {}} {a 1 b 2 c 3 args {4 5}}}
+
+test proc-5.1 {info args: defaults and args} -setup {
+    proc myproc {a {b {x y}} {c ""} args} {}
+} -body {
+    info args myproc
+} -cleanup {
+    rename myproc ""
+} -ok {a b c args}
+
+test proc-5.2 {info default: multi-word list default} -setup {
+    proc myproc {a {b {x {y z}}}} { return $b }
+} -body {
+    list [info default myproc b val] $val [llength $val] [myproc 1]
+} -cleanup {
+    rename myproc ""
+} -ok {1 {x {y z}} 2 {x {y z}}}
+
+test proc-5.3 {info default: empty-string default} -setup {
+    proc myproc {a {b ""}} {}
+} -body {
+    set val dummy
+    list [info default myproc b val] $val
+} -cleanup {
+    rename myproc ""
+} -ok {1 {}}
+
+test proc-5.4 {info default: args has no default} -setup {
+    proc myproc {a args} {}
+} -body {
+    set val dummy
+    list [info default myproc args val] $val
+} -cleanup {
+    rename myproc ""
+} -ok {0 {}}
+
+test proc-5.5 {info default: no such argument} -setup {
+    proc myproc {a args} {}
+} -body {
+    info default myproc b val
+} -cleanup {
+    rename myproc ""
+} -error {procedure "myproc" doesn't have an argument "b"}
+
+test proc-5.6 {info default: variable is an array} -setup {
+    proc myproc {{a 1}} {}
+    array set arr {}
+} -body {
+    info default myproc a arr
 } -cleanup {
     rename myproc ""
-} -ok {args 1 b 2 a 3}
+    unset arr
+} -error {can't set "arr": variable is array}
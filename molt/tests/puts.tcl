@@ -0,0 +1,29 @@
+# Test Suite: puts command
+
+test puts-1.1 {puts: syntax} {
+    puts
+} -error {wrong # args: should be "puts ?-nonewline? ?channelId? string"}
+
+test puts-1.2 {puts: too many args} {
+    puts a b c
+} -error {wrong # args: should be "puts ?-nonewline? ?channelId? string"}
+
+test puts-1.3 {puts: unknown channel} {
+    puts nonesuch "Howdy"
+} -error {can not find channel named "nonesuch"}
+
+test puts-2.1 {puts: explicit channel} -body {
+    puts stdout "puts-2.1"
+} -ok {} -output "puts-2.1\n"
+
+test puts-2.2 {puts: -nonewline} -body {
+    puts -nonewline stdout "puts-2.2"
+} -ok {} -output "puts-2.2"
+
+test puts-2.3 {puts: can't write stdin} {
+    puts stdin "Howdy"
+} -error {channel "stdin" wasn't opened for writing}
+
+test puts-2.4 {puts: default channel} -body {
+    puts "puts-2.4"
+} -ok {} -output "puts-2.4\n"
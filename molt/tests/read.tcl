@@ -0,0 +1,16 @@
+# Test Suite: read command
+#
+# Reading from stdin can't be tested in the test suite; see the unit tests in
+# channel.rs.
+
+test read-1.1 {read: syntax} {
+    read
+} -error {wrong # args: should be "read channelId ?numBytes?" or "read ?-nonewline? channelId"}
+
+test read-1.2 {read: unknown channel} {
+    read nonesuch
+} -error {can not find channel named "nonesuch"}
+
+test read-1.3 {read: bad count} {
+    read stdin -1
+} -error {expected non-negative integer but got "-1"}
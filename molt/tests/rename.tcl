@@ -23,3 +23,42 @@ test rename-2.2 {rename command} -setup {
 } -cleanup {
     rename howdy ""
 } -ok hello
+
+test rename-3.1 {wrong # args messages name the command as called} -setup {
+    rename string str
+    rename foreach fe
+} -body {
+    list [catch {str compare -length} msg1] $msg1 \
+        [catch {str is integer -failindex -strict} msg2] $msg2 \
+        [catch {fe a b c d} msg3] $msg3
+} -cleanup {
+    rename str string
+    rename fe foreach
+} -ok {1 {wrong # args: should be "str compare ?-nocase? ?-length length? string1 string2"} 1 {wrong # args: should be "str is class ?-strict? ?-failindex var? str"} 1 {wrong # args: should be "fe varList list ?varList list ...? body"}}
+
+test rename-4.1 {rename onto existing command} -setup {
+    proc hello {} { return "hello" }
+    proc howdy {} { return "howdy" }
+} -body {
+    list [catch {rename hello howdy} msg] $msg [hello] [howdy]
+} -cleanup {
+    rename hello ""
+    rename howdy ""
+} -ok {1 {can't rename to "howdy": command already exists} hello howdy}
+
+test rename-4.2 {rename onto existing native command} -setup {
+    proc hello {} { return "hello" }
+} -body {
+    rename hello set
+} -cleanup {
+    rename hello ""
+} -error {can't rename to "set": command already exists}
+
+test rename-4.3 {rename in namespace shadowing global command} -setup {
+    namespace eval ::rn { proc hello {} { return "hello" } }
+} -body {
+    namespace eval ::rn { rename hello set }
+    rn::set
+} -cleanup {
+    rename ::rn::set ""
+} -ok hello
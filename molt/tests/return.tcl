@@ -2,11 +2,11 @@
 
 # NOTE: The semantics of return are a subset of those of standard TCL.
 
-# Test syntax.  Note: TCL doesn't work this way, but until I implement
-# the full return syntax, it doesn't matter.
-test return-1.1 {return errors} {
-    return foo bar
-} -error {invalid return option: "foo"}
+# Test syntax.  As in standard TCL, any option name is retained.
+test return-1.1 {return, unknown options are retained} {
+    set code [catch {return foo bar} result opts]
+    list $code $result $opts
+} -ok {2 {} {foo bar -code 0 -level 1}}
 
 # return the empty string
 test return-2.1 {return command} -setup {
@@ -68,3 +68,46 @@ test return-3.7 {return, -error* applied} {
     set code [catch {return -code error -errorcode A -errorinfo B -level 0 x} result opts]
     list $code $result $errorCode [expr {$errorInfo eq [dict get $opts -errorinfo]}]
 } -ok {1 x A 1}
+
+test return-4.1 {return, extra options included in catch options} {
+    set code [catch {return -code break -level 0 -mine 1 -yours 2 x} result opts]
+    list $code $result $opts
+} -ok {3 x {-mine 1 -yours 2 -code 3 -level 0}}
+
+test return-4.2 {return -options} {
+    set code [catch {return -options {-code error -errorcode A -mine 1} x} result opts]
+    list $code $result [dict get $opts -code] [dict get $opts -errorcode] \
+        [dict get $opts -mine]
+} -ok {2 x 1 A 1}
+
+test return-4.3 {return -options, later options override} {
+    set code [catch {return -options {-code break -level 2} -level 0 x} result opts]
+    list $code $result $opts
+} -ok {3 x {-code 3 -level 0}}
+
+test return-4.4 {return -options, round trip} -setup {
+    proc roundTrip {} {
+        catch {return -code error -errorcode {MY CODE} -mine yes "failed"} result opts
+        return -options $opts $result
+    }
+} -body {
+    set code [catch {roundTrip} result opts]
+    list $code $result [dict get $opts -errorcode] [dict get $opts -mine]
+} -cleanup {
+    rename roundTrip ""
+} -ok {1 failed {MY CODE} yes}
+
+test return-4.5 {return -options, invalid dictionary} {
+    return -options {a b c} x
+} -error {missing value to go with key}
+
+test return-4.6 {return, extra options pass through procs} -setup {
+    proc passThrough {} {
+        return -level 2 -mine yes x
+    }
+} -body {
+    set code [catch {passThrough} result opts]
+    list $code $result $opts
+} -cleanup {
+    rename passThrough ""
+} -ok {2 x {-mine yes -code 0 -level 1}}
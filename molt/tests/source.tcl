@@ -0,0 +1,49 @@
+# Test Script: source command
+
+test source-1.1 {source: syntax} {
+    source
+} -error {wrong # args: should be "source filename"}
+
+test source-1.2 {source: no such file} -body {
+    catch {source nonesuch.tcl} result
+    string first "couldn't read file \"nonesuch.tcl\": " $result
+} -ok {0}
+
+test source-2.1 {source returns the value of the script} -setup {
+    set ::sourcedValue 1
+    set ::sourcedFail 0
+} -body {
+    list [source sourced.tcl] $::sourcedValue
+} -cleanup {
+    unset ::sourcedValue ::sourcedFail ::sourcedScript
+} -ok {2 2}
+
+test source-2.2 {source adds a file frame to errorInfo} -setup {
+    set ::sourcedValue 1
+    set ::sourcedFail 1
+} -body {
+    catch {source sourced.tcl} result
+    list $result [expr {[string first {(file "sourced.tcl" line 6)} $::errorInfo] > 0}]
+} -cleanup {
+    unset ::sourcedValue ::sourcedFail ::sourcedScript
+} -ok {{sourced failure} 1}
+
+test source-3.1 {source: info script} -setup {
+    set ::sourcedValue 1
+    set ::sourcedFail 0
+} -body {
+    source sourced.tcl
+    list $::sourcedScript [info script]
+} -cleanup {
+    unset ::sourcedValue ::sourcedFail ::sourcedScript
+} -ok {sourced.tcl source.tcl}
+
+test source-3.2 {source: info script is restored after an error} -setup {
+    set ::sourcedValue 1
+    set ::sourcedFail 1
+} -body {
+    catch {source sourced.tcl}
+    list $::sourcedScript [info script]
+} -cleanup {
+    unset ::sourcedValue ::sourcedFail ::sourcedScript
+} -ok {sourced.tcl source.tcl}
@@ -0,0 +1,10 @@
+# Used by source.tcl; not a test script.
+
+set ::sourcedScript [info script]
+set ::sourcedValue [expr {$::sourcedValue + 1}]
+
+if {$::sourcedFail} {
+    error "sourced failure"
+}
+
+set ::sourcedValue
@@ -0,0 +1,42 @@
+# Test Script: split command.
+
+test split-1.1 {split errors} {
+    split
+} -error {wrong # args: should be "split string ?splitChars?"}
+
+test split-2.1 {split on whitespace} {
+    split "a b\tc\nd"
+} -ok {a b c d}
+
+test split-2.2 {split on split chars} {
+    split "a,b;c" ",;"
+} -ok {a b c}
+
+test split-2.3 {split keeps empty elements} {
+    split ",a,,b," ,
+} -ok {{} a {} b {}}
+
+test split-2.4 {split empty string} {
+    llength [split "" ,]
+} -ok {0}
+
+test split-2.5 {split into characters} {
+    split abc ""
+} -ok {a b c}
+
+test split-2.6 {split Unicode} {
+    list [split "аб,вг" ,] [split カタ ""]
+} -ok {{аб вг} {カ タ}}
+
+test split-2.7 {split elements are ordinary values} {
+    set total 0
+    foreach n [split 1,2,3 ,] {
+        incr total $n
+    }
+    set total
+} -ok {6}
+
+test split-3.1 {split and join are inverses} {
+    set string "one,two,,three"
+    expr {[join [split $string ,] ,] eq $string}
+} -ok {1}
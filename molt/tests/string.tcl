@@ -1,5 +1,19 @@
 # Test Script: string command
 
+# string bytelength
+test string-1.1 {string bytelength: syntax} {
+    string bytelength
+} -error {wrong # args: should be "string bytelength string"}
+
+test string-1.2 {string bytelength} {
+    list \
+        [string bytelength {}] \
+        [string bytelength abc] \
+        [string bytelength "a\u00e9"] \
+        [string bytelength "\u4e2d\u6587"] \
+        [string length "\u4e2d\u6587"]
+} -ok {0 3 3 6 2}
+
 # string cat
 test string-2.1 {string cat} {
     list \
@@ -357,3 +371,272 @@ test string-15.10 {string range: Unicode 1} {
 test string-15.11 {string range: Unicode 2} {
     string range カタカナ 2 3
 } -ok カナ
+
+test string-15.12 {string range: first > last, both positive} {
+    string range 012345 3 1
+} -ok {}
+
+# string is
+test string-16.1 {string is: syntax} {
+    string is
+} -error {wrong # args: should be "string is class ?-strict? ?-failindex var? str"}
+
+test string-16.2 {string is: bad class} {
+    string is nonesuch abc
+} -error {bad class "nonesuch": must be alnum, alpha, ascii, boolean, control, dict, digit, double, entier, false, graph, integer, list, lower, print, punct, space, true, upper, wideinteger, wordchar, or xdigit}
+
+test string-16.3 {string is: bad option} {
+    string is integer -nonesuch abc
+} -error {bad option "-nonesuch": must be -strict or -failindex}
+
+test string-16.4 {string is: missing failindex var} {
+    string is integer -failindex abc
+} -error {wrong # args: should be "string is class ?-strict? ?-failindex var? str"}
+
+test string-16.5 {string is: empty string} {
+    list [string is integer ""] [string is integer -strict ""] \
+        [string is alpha ""] [string is alpha -strict ""]
+} -ok {1 0 1 0}
+
+test string-16.6 {string is integer} {
+    list [string is integer 123] [string is integer -45] [string is integer +6] \
+        [string is integer 0x1F] [string is integer " 12 "] [string is integer 12a] \
+        [string is integer 1.5] [string is integer abc]
+} -ok {1 1 1 1 1 0 0 0}
+
+test string-16.7 {string is double} {
+    list [string is double 1.5] [string is double 12] [string is double 1e10] \
+        [string is double -0.5] [string is double 0x10] [string is double 1.5x]
+} -ok {1 1 1 1 1 0}
+
+test string-16.8 {string is: boolean classes} {
+    list [string is boolean yes] [string is boolean off] [string is boolean maybe] \
+        [string is true on] [string is true no] [string is false 0] [string is false 1]
+} -ok {1 1 0 1 0 1 0}
+
+test string-16.9 {string is: character classes} {
+    list [string is alpha abc] [string is alpha ab1] [string is alnum ab1] \
+        [string is digit 123] [string is digit 12a] [string is lower abc] \
+        [string is upper ABC] [string is upper AbC] [string is space " \t\n"] \
+        [string is xdigit 09afAF] [string is xdigit 0g] [string is wordchar a_1] \
+        [string is wordchar a-1] [string is ascii abc] [string is punct "!?."] \
+        [string is control "\x01"] [string is print "a b"] [string is graph "a b"]
+} -ok {1 0 1 1 0 1 1 0 1 1 0 1 0 1 1 1 1 0}
+
+test string-16.10 {string is: list and dict} {
+    list [string is list {a b c}] [string is list "a \{b"] \
+        [string is dict {a 1 b 2}] [string is dict {a 1 b}]
+} -ok {1 0 1 0}
+
+test string-16.11 {string is: -failindex, character class} -body {
+    set result [string is alpha -failindex idx abc1def]
+    list $result $idx
+} -ok {0 3}
+
+test string-16.12 {string is: -failindex, integer} -body {
+    set result [string is integer -failindex idx 123abc]
+    list $result $idx
+} -ok {0 3}
+
+test string-16.13 {string is: -failindex, not set on success} -body {
+    set idx unchanged
+    string is integer -failindex idx 123
+    set idx
+} -ok {unchanged}
+
+test string-16.14 {string is: -strict and -failindex} -body {
+    set result [string is integer -strict -failindex idx ""]
+    list $result $idx
+} -ok {0 0}
+
+test string-16.15 {string is: -failindex, whole-string class} -body {
+    set result [string is boolean -failindex idx maybe]
+    list $result $idx
+} -ok {0 0}
+
+# Multibyte characters: indices are character indices, not byte offsets.
+test string-17.1 {string first: startIndex after multibyte characters} {
+    string first b "ééab" 1
+} -ok {3}
+
+test string-17.2 {string first: CJK} {
+    list [string first 語 "日本語の日本語"] [string first 語 "日本語の日本語" 3]
+} -ok {2 6}
+
+test string-17.3 {string first: emoji} {
+    string first x "😀😀x😀x" 3
+} -ok {4}
+
+test string-17.4 {string last: emoji and CJK} {
+    list [string last 😀 "a😀b😀c"] [string last 日 "日本語の日本語" 3] \
+        [string last 日 "日本語の日本語" 4]
+} -ok {3 0 4}
+
+test string-17.5 {string map: multibyte keys and values} {
+    string map {😀 smile 語 go ab 日} "日本語😀ab"
+} -ok {日本gosmile日}
+
+test string-17.6 {string map -nocase: multibyte} {
+    string map -nocase {ÉCOLE school} "école Ecole ÉCOLE"
+} -ok {school Ecole school}
+
+test string-17.7 {string map -nocase: lowercase changes byte length} {
+    string map -nocase {x y} "İx"
+} -ok {İy}
+
+test string-17.8 {string length and range agree with first} {
+    set s "a😀日b"
+    set i [string first b $s]
+    list [string length $s] $i [string range $s $i $i]
+} -ok {4 3 b}
+
+test string-17.9 {string last: lastIndex after multibyte characters} {
+    list [string last a "éaéa" 2] [string last a "éaéa" 3] [string last é "éaéa" 1]
+} -ok {1 3 0}
+
+test string-18.1 {string match: syntax} {
+    string match a
+} -error {wrong # args: should be "string match ?-nocase? pattern string"}
+
+test string-18.2 {string match: bad option} {
+    string match -exact a a
+} -error {bad option "-exact": must be -nocase}
+
+test string-18.3 {string match: literal and wildcards} {
+    list [string match abc abc] [string match abc abcd] [string match a*c abbbc] \
+        [string match a?c abc] [string match a?c ac] [string match * ""]
+} -ok {1 0 1 1 0 1}
+
+test string-18.4 {string match: bracket expressions} {
+    list [string match {[aeiou]*} apple] [string match {[aeiou]*} banana] \
+        [string match {[a-c]x} bx] [string match {[a-c]x} dx] \
+        [string match {[0-9a-f]*} f00]
+} -ok {1 0 1 0 1}
+
+test string-18.5 {string match: negated bracket expressions} {
+    list [string match {[^aeiou]*} apple] [string match {[^aeiou]*} banana] \
+        [string match {[^a-z]} Q] [string match {[^a-z]} q]
+} -ok {0 1 1 0}
+
+test string-18.6 {string match: escapes} {
+    list [string match {a\*} a*] [string match {a\*} ab] [string match {[\]]} \]] \
+        [string match {\[x\]} {[x]}]
+} -ok {1 0 1 1}
+
+test string-18.7 {string match: -nocase} {
+    list [string match -nocase {*.TXT} readme.txt] [string match {*.TXT} readme.txt] \
+        [string match -nocase {[A-C]} b]
+} -ok {1 0 1}
+
+test string-18.8 {string match: multibyte characters} {
+    list [string match {日?語} 日本語] [string match {[α-ω]*} λόγος] [string match {*😀} a😀]
+} -ok {1 1 1}
+
+test string-19.1 {string insert: syntax} {
+    string insert a b
+} -error {wrong # args: should be "string insert string index insertString"}
+
+test string-19.2 {string insert: bad index} {
+    string insert abc x Z
+} -error {bad index "x": must be integer?[+-]integer? or end?[+-]integer?}
+
+test string-19.3 {string insert: start-relative index} {
+    list [string insert abc 0 Z] [string insert abc 1 Z] [string insert abc 3 Z] \
+        [string insert abc 1+1 Z]
+} -ok {Zabc aZbc abcZ abZc}
+
+test string-19.4 {string insert: end-relative index} {
+    list [string insert abc end Z] [string insert abc end-1 Z] [string insert abc end-3 Z]
+} -ok {abcZ abZc Zabc}
+
+test string-19.5 {string insert: out of range indices} {
+    list [string insert abc -5 Z] [string insert abc 10 Z] [string insert abc end-10 Z] \
+        [string insert "" 0 Z]
+} -ok {Zabc abcZ Zabc Z}
+
+test string-19.6 {string insert: multibyte characters} {
+    list [string insert 日本語 1 Z] [string insert 日本語 end-1 é] [string insert a😀b 2 日]
+} -ok {日Z本語 日本é語 a😀日b}
+
+test string-20.1 {string wordend: syntax} {
+    string wordend a
+} -error {wrong # args: should be "string wordend string charIndex"}
+
+test string-20.2 {string wordend: bad index} {
+    string wordend abc x
+} -error {bad index "x": must be integer?[+-]integer? or end?[+-]integer?}
+
+test string-20.3 {string wordend: word characters} {
+    set s "foo_bar1 baz"
+    list [string wordend $s 0] [string wordend $s 3] [string wordend $s 7] \
+        [string wordend $s 9] [string wordend $s end]
+} -ok {8 8 8 12 12}
+
+test string-20.4 {string wordend: non-word characters} {
+    list [string wordend "a  b" 1] [string wordend "a.b" 1] [string wordend "a.b" 2]
+} -ok {2 2 3}
+
+test string-20.5 {string wordend: out of range indices} {
+    list [string wordend abc -5] [string wordend abc 10] [string wordend "" 0]
+} -ok {3 3 0}
+
+test string-20.6 {string wordend: multibyte characters} {
+    list [string wordend "日本語 abc" 1] [string wordend "λόγος!" 0]
+} -ok {3 5}
+
+test string-21.1 {string wordstart: syntax} {
+    string wordstart a
+} -error {wrong # args: should be "string wordstart string charIndex"}
+
+test string-21.2 {string wordstart: bad index} {
+    string wordstart abc x
+} -error {bad index "x": must be integer?[+-]integer? or end?[+-]integer?}
+
+test string-21.3 {string wordstart: word characters} {
+    set s "foo bar_baz1"
+    list [string wordstart $s 0] [string wordstart $s 2] [string wordstart $s 4] \
+        [string wordstart $s 8] [string wordstart $s end]
+} -ok {0 0 4 4 4}
+
+test string-21.4 {string wordstart: non-word characters} {
+    list [string wordstart "a  b" 2] [string wordstart "a.b" 1] [string wordstart "ab.c" 3]
+} -ok {2 1 3}
+
+test string-21.5 {string wordstart: out of range indices} {
+    list [string wordstart abc -5] [string wordstart "ab cd" 10] [string wordstart "" 0]
+} -ok {0 3 0}
+
+test string-21.6 {string wordstart: multibyte characters} {
+    list [string wordstart "abc 日本語" 5] [string wordstart "!λόγος" 3]
+} -ok {4 1}
+
+test string-22.1 {string compare -nocase: ordering} {
+    list [string compare -nocase ABC abd] [string compare -nocase abd ABC] \
+        [string compare -nocase AB abc] [string compare -nocase -length 2 abX ABy]
+} -ok {-1 1 -1 0}
+
+test string-22.2 {string compare/equal -nocase: multibyte} {
+    list [string compare -nocase ÉCOLE école] [string equal -nocase ÉCOLE école] \
+        [string equal -nocase -length 1 Σx σy] [string equal -nocase Straße STRASSE]
+} -ok {0 1 1 0}
+
+test string-23.1 {string compare: -length counts characters} {
+    list [string compare -length 2 日本語 日本人] [string compare -length 3 日本語 日本人] \
+        [string compare -length 1 éa éb] [string compare -length 2 éa éb]
+} -ok {0 1 0 -1}
+
+test string-23.2 {string equal: -length counts characters} {
+    list [string equal -length 2 日本語 日本人] [string equal -length 3 日本語 日本人] \
+        [string equal -length 1 😀a 😀b] [string equal -length -1 ab ac]
+} -ok {1 0 1 0}
+
+test string-23.3 {string compare: ordered by character} {
+    list [string compare z é] [string compare é z] [string compare ￿ 😀] \
+        [string compare 日 日本]
+} -ok {-1 1 -1 -1}
+
+test string-23.4 {string compare: -length consistent with string first} {
+    set s "αβγ-δ"
+    set i [string first - $s]
+    string equal -length $i $s αβγ
+} -ok {1}
@@ -0,0 +1,153 @@
+# Test Script: tailcall
+
+test tailcall-1.1 {tailcall signature} {
+    tailcall
+} -error {wrong # args: should be "tailcall command ?arg ...?"}
+
+test tailcall-1.2 {tailcall outside a proc} {
+    tailcall list a
+} -error {tailcall can only be called from a proc or lambda}
+
+test tailcall-1.3 {tailcall in namespace eval} -body {
+    namespace eval ::tc { tailcall list a }
+} -error {tailcall can only be called from a proc or lambda}
+
+test tailcall-2.1 {tailcall result is the proc's result} -setup {
+    proc myproc {} {
+        tailcall list a b
+        return "not reached"
+    }
+} -body {
+    myproc
+} -cleanup {
+    rename myproc ""
+} -ok {a b}
+
+test tailcall-2.2 {tailcall runs after the proc's scope is popped} -setup {
+    proc myproc {} {
+        set x local
+        tailcall info exists x
+    }
+} -body {
+    myproc
+} -cleanup {
+    rename myproc ""
+} -ok {0}
+
+test tailcall-2.3 {tailcall arguments are substituted in the proc} -setup {
+    proc myproc {x} {
+        tailcall list $x [expr {$x * 2}]
+    }
+} -body {
+    myproc 3
+} -cleanup {
+    rename myproc ""
+} -ok {3 6}
+
+test tailcall-2.4 {tailcall to a native command with an error} -setup {
+    proc myproc {} {
+        tailcall error "simulated error"
+    }
+} -body {
+    myproc
+} -cleanup {
+    rename myproc ""
+} -error {simulated error}
+
+test tailcall-2.5 {tailcall to an undefined command} -setup {
+    proc myproc {} {
+        tailcall nonesuch a
+    }
+} -body {
+    myproc
+} -cleanup {
+    rename myproc ""
+} -error {invalid command name "nonesuch"}
+
+test tailcall-2.6 {tailcall resolves the command in the proc's namespace} -setup {
+    namespace eval ::tc {
+        proc helper {} { return "tc helper" }
+        proc myproc {} { tailcall helper }
+    }
+    proc helper {} { return "global helper" }
+} -body {
+    tc::myproc
+} -cleanup {
+    rename ::tc::helper ""
+    rename ::tc::myproc ""
+    rename helper ""
+} -ok {tc helper}
+
+test tailcall-3.1 {deep tail recursion doesn't grow the stack} -setup {
+    proc count {n acc} {
+        if {$n == 0} {
+            return $acc
+        }
+        tailcall count [expr {$n - 1}] [expr {$acc + $n}]
+    }
+} -body {
+    count 5000 0
+} -cleanup {
+    rename count ""
+} -ok {12502500}
+
+test tailcall-3.2 {mutual tail recursion} -setup {
+    proc even {n} {
+        if {$n == 0} { return 1 }
+        tailcall odd [expr {$n - 1}]
+    }
+    proc odd {n} {
+        if {$n == 0} { return 0 }
+        tailcall even [expr {$n - 1}]
+    }
+} -body {
+    list [even 3001] [odd 3001]
+} -cleanup {
+    rename even ""
+    rename odd ""
+} -ok {0 1}
+
+test tailcall-3.3 {tail calls from nested procs} -setup {
+    proc inner {} { tailcall list inner }
+    proc outer {} {
+        set x [inner]
+        tailcall list outer $x
+    }
+} -body {
+    outer
+} -cleanup {
+    rename inner ""
+    rename outer ""
+} -ok {outer inner}
+
+test tailcall-4.1 {tailcall is discarded if the proc throws an error} -setup {
+    proc myproc {} {
+        catch {tailcall set ::tc_flag 1}
+        error "simulated error"
+    }
+} -body {
+    list [catch myproc msg] $msg [info exists ::tc_flag]
+} -cleanup {
+    rename myproc ""
+} -ok {1 {simulated error} 0}
+
+test tailcall-4.2 {caught tailcall still replaces the proc's result} -setup {
+    proc myproc {} {
+        catch {tailcall list a}
+        return b
+    }
+} -body {
+    myproc
+} -cleanup {
+    rename myproc ""
+} -ok {a}
+
+test tailcall-4.3 {proc argument errors don't leak scopes} -setup {
+    proc myproc {a} {}
+} -body {
+    set before 1
+    catch myproc
+    info exists before
+} -cleanup {
+    rename myproc ""
+} -ok {1}
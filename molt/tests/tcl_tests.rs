@@ -1,3 +1,6 @@
+// The test suite loads its files with the `source` command, which requires the `os` feature.
+#![cfg(feature = "os")]
+
 extern crate molt;
 use molt::Interp;
 
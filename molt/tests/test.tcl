@@ -11,3 +11,56 @@ test test-2.1 {a fancy test} -setup {
     set a 5
 } -cleanup {
 } -ok 5
+
+test testConstraint-1.1 {testConstraint errors} {
+    testConstraint
+} -error {wrong # args: should be "testConstraint name ?value?"}
+
+test testConstraint-1.2 {testConstraint errors} {
+    testConstraint myConstraint nonesuch
+} -error {expected boolean but got "nonesuch"}
+
+test testConstraint-2.1 {undefined constraints aren't satisfied} {
+    testConstraint nonesuch
+} -ok {0}
+
+test testConstraint-2.2 {set and clear constraints} {
+    list [testConstraint myConstraint 1] [testConstraint myConstraint] \
+        [testConstraint myConstraint 0] [testConstraint myConstraint]
+} -ok {1 1 0 0}
+
+test testConstraint-2.3 {platform constraints} {
+    expr {[testConstraint unix] || [testConstraint windows]}
+} -ok {1}
+
+testConstraint harnessTest 1
+
+test test-3.1 {tests with satisfied constraints are run} -constraints {
+    harnessTest
+} -body {
+    set a 1
+} -ok {1}
+
+test test-3.2 {tests with unsatisfied constraints are skipped} -constraints {
+    harnessTest knownBug
+} -body {
+    error "should have been skipped"
+} -ok {}
+
+testConstraint harnessTest 0
+
+set ::hookLog {}
+proc testSetup {} { lappend ::hookLog setup }
+proc testTeardown {} { lappend ::hookLog teardown }
+
+test test-4.1 {the setup hook runs before the test} {
+    set ::hookLog
+} -ok {setup}
+
+test test-4.2 {the teardown hook runs after the test} {
+    set ::hookLog
+} -ok {setup teardown setup}
+
+rename testSetup ""
+rename testTeardown ""
+unset ::hookLog
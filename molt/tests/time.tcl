@@ -0,0 +1,62 @@
+# Test Suite: time command
+
+test time-1.1 {time: syntax} {
+    time
+} -error {wrong # args: should be "time ?-result format? command ?count?"}
+
+test time-1.2 {time: syntax} {
+    time a b c d e
+} -error {wrong # args: should be "time ?-result format? command ?count?"}
+
+test time-1.3 {time: syntax} {
+    time -result dict
+} -error {wrong # args: should be "time ?-result format? command ?count?"}
+
+test time-1.4 {time: syntax} {
+    time -result dict {set a 1} 2 3
+} -error {wrong # args: should be "time ?-result format? command ?count?"}
+
+test time-1.5 {time: bad format} {
+    time -result list {set a 1}
+} -error {bad format "list": must be dict or string}
+
+test time-1.6 {time: bad count} {
+    time {set a 1} x
+} -error {expected integer but got "x"}
+
+test time-1.7 {time: command error} {
+    time {error oops} 5
+} -error {oops}
+
+test time-2.1 {time: string result} {
+    set result [time {set a 1} 10]
+    list [string is integer -strict [lindex $result 0]] [lindex $result 1] [lindex $result 2] [lindex $result 3]
+} -ok {1 nanoseconds per iteration}
+
+test time-2.2 {time: -result string} {
+    set result [time -result string {set a 1}]
+    list [string is integer -strict [lindex $result 0]] [lindex $result 1] [lindex $result 2] [lindex $result 3]
+} -ok {1 nanoseconds per iteration}
+
+test time-2.3 {time: evaluates the command count times} {
+    set ::count 0
+    time {incr ::count} 5
+    set ::count
+} -ok {5}
+
+test time-3.1 {time: -result dict} {
+    set result [time -result dict {set a 1} 10]
+    dict keys $result
+} -ok {nanoseconds microseconds milliseconds iterations total_nanoseconds}
+
+test time-3.2 {time: -result dict values} {
+    set result [time -result dict {set a 1} 10]
+    list [dict get $result iterations] \
+        [expr {[dict get $result total_nanoseconds] >= [dict get $result nanoseconds]}] \
+        [expr {[dict get $result microseconds] == [dict get $result nanoseconds] / 1000.0}]
+} -ok {10 1 1}
+
+test time-3.3 {time: -result dict, zero iterations} {
+    set result [time -result dict {error oops} 0]
+    list [dict get $result iterations] [dict get $result nanoseconds]
+} -ok {0 0}
@@ -0,0 +1,162 @@
+# Test Suite: trace command
+
+test trace-1.1 {trace: syntax} {
+    trace
+} -error {wrong # args: should be "trace subcommand ?arg ...?"}
+
+test trace-1.2 {trace add: syntax} {
+    trace add execution set enter
+} -error {wrong # args: should be "trace add type name opList command"}
+
+test trace-1.3 {trace add: bad type} {
+    trace add variable x write cmd
+} -error {bad option "variable": must be execution}
+
+test trace-1.4 {trace add: bad operation} {
+    trace add execution set enterstep cmd
+} -error {bad operation "enterstep": must be enter or leave}
+
+test trace-1.5 {trace add: empty operation list} {
+    trace add execution set {} cmd
+} -error {bad operation list "": must be one or more of enter or leave}
+
+test trace-1.6 {trace add: unknown command} {
+    trace add execution nonesuch enter cmd
+} -error {unknown command "nonesuch"}
+
+test trace-1.7 {trace info: syntax} {
+    trace info execution
+} -error {wrong # args: should be "trace info type name"}
+
+test trace-1.8 {trace remove: syntax} {
+    trace remove execution set
+} -error {wrong # args: should be "trace remove type name opList command"}
+
+test trace-2.1 {trace add execution: enter} -setup {
+    proc myproc {a} { return "<$a>" }
+    proc logger {args} { lappend ::log $args }
+    set ::log {}
+} -body {
+    trace add execution myproc enter logger
+    set x 1
+    myproc [expr {$x + 1}]
+    set ::log
+} -cleanup {
+    rename myproc ""
+    rename logger ""
+} -ok {{{myproc 2} enter}}
+
+test trace-2.2 {trace add execution: leave} -setup {
+    proc myproc {a} { return "<$a>" }
+    proc logger {args} { lappend ::log $args }
+    set ::log {}
+} -body {
+    trace add execution myproc leave logger
+    myproc x
+    catch {myproc}
+    set ::log
+} -cleanup {
+    rename myproc ""
+    rename logger ""
+} -ok {{{myproc x} 0 <x> leave} {myproc 1 {wrong # args: should be "myproc a"} leave}}
+
+test trace-2.3 {trace add execution: enter and leave, in order} -setup {
+    proc myproc {a} { lappend ::log body }
+    proc logger {tag args} { lappend ::log "$tag [lindex $args end]" }
+    set ::log {}
+} -body {
+    trace add execution myproc {enter leave} {logger one}
+    trace add execution myproc enter {logger two}
+    myproc x
+    set ::log
+} -cleanup {
+    rename myproc ""
+    rename logger ""
+} -ok {{one enter} {two enter} body {one leave}}
+
+test trace-2.4 {trace add execution: error in enter trace} -setup {
+    proc myproc {} { set ::called 1 }
+    proc logger {args} { error "trace failed" }
+    set ::called 0
+} -body {
+    trace add execution myproc enter logger
+    list [catch {myproc} result] $result $::called
+} -cleanup {
+    rename myproc ""
+    rename logger ""
+} -ok {1 {trace failed} 0}
+
+test trace-2.5 {trace add execution: traces are disabled in trace scripts} -setup {
+    proc myproc {} { return }
+    proc logger {args} { lappend ::log $args; myproc }
+    set ::log {}
+} -body {
+    trace add execution myproc enter logger
+    myproc
+    set ::log
+} -cleanup {
+    rename myproc ""
+    rename logger ""
+} -ok {{myproc enter}}
+
+test trace-2.6 {trace add execution: native command} -setup {
+    proc logger {args} { lappend ::log [lindex $args 0] }
+    set ::log {}
+} -body {
+    trace add execution incr enter logger
+    set a 1
+    incr a 5
+    trace remove execution incr enter logger
+    incr a
+    set ::log
+} -cleanup {
+    rename logger ""
+} -ok {{incr a 5}}
+
+test trace-3.1 {trace info execution} -setup {
+    proc myproc {} {}
+} -body {
+    trace add execution myproc enter {logger one}
+    trace add execution myproc {leave enter} {logger two}
+    trace info execution myproc
+} -cleanup {
+    rename myproc ""
+} -ok {{enter {logger one}} {{enter leave} {logger two}}}
+
+test trace-3.2 {trace info execution: no traces} {
+    trace info execution set
+} -ok {}
+
+test trace-4.1 {trace remove execution} -setup {
+    proc myproc {} {}
+} -body {
+    trace add execution myproc enter {logger one}
+    trace add execution myproc {enter leave} {logger two}
+    trace remove execution myproc {leave enter} {logger two}
+    trace remove execution myproc leave {logger one}
+    trace remove execution myproc enter nonesuch
+    trace info execution myproc
+} -cleanup {
+    rename myproc ""
+} -ok {{enter {logger one}}}
+
+test trace-5.1 {traces follow renamed commands} -setup {
+    proc myproc {} {}
+} -body {
+    trace add execution myproc enter logger
+    rename myproc yourproc
+    trace info execution yourproc
+} -cleanup {
+    rename yourproc ""
+} -ok {{enter logger}}
+
+test trace-5.2 {traces are removed with their commands} -setup {
+    proc myproc {} {}
+} -body {
+    trace add execution myproc enter logger
+    rename myproc ""
+    proc myproc {} {}
+    trace info execution myproc
+} -cleanup {
+    rename myproc ""
+} -ok {}
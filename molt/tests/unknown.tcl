@@ -0,0 +1,87 @@
+# Test Script: unknown
+
+test unknown-1.1 {no unknown command} {
+    nonesuch 1 2
+} -error {invalid command name "nonesuch"}
+
+test unknown-2.1 {unknown receives the original words} -setup {
+    proc unknown {args} { return "unknown: $args" }
+} -body {
+    nonesuch 1 {2 3}
+} -cleanup {
+    rename unknown ""
+} -ok {unknown: nonesuch 1 {2 3}}
+
+test unknown-2.2 {unknown fires once per miss} -setup {
+    set ::unknown_log {}
+    proc unknown {name args} {
+        lappend ::unknown_log $name
+        return [llength $args]
+    }
+} -body {
+    set a [nonesuch 1 2]
+    set b [other]
+    list $a $b $::unknown_log
+} -cleanup {
+    rename unknown ""
+    unset ::unknown_log
+} -ok {2 0 {nonesuch other}}
+
+test unknown-2.3 {unknown isn't called for defined commands} -setup {
+    set ::unknown_log {}
+    proc unknown {args} { lappend ::unknown_log $args }
+} -body {
+    set a 1
+    list $a $::unknown_log
+} -cleanup {
+    rename unknown ""
+    unset ::unknown_log
+} -ok {1 {}}
+
+test unknown-2.4 {unknown can define the missing command} -setup {
+    proc unknown {name args} {
+        proc $name {args} { return "loaded: $args" }
+        $name {*}$args
+    }
+} -body {
+    list [autoloaded a b] [info cmdtype autoloaded]
+} -cleanup {
+    rename unknown ""
+    rename autoloaded ""
+} -ok {{loaded: a b} proc}
+
+test unknown-3.1 {errors in unknown propagate} -setup {
+    proc unknown {name args} { error "can't handle \"$name\"" }
+} -body {
+    nonesuch
+} -cleanup {
+    rename unknown ""
+} -error {can't handle "nonesuch"}
+
+test unknown-3.2 {unknown calling a missing command doesn't recurse} -setup {
+    set ::unknown_count 0
+    proc unknown {args} {
+        incr ::unknown_count
+        alsomissing
+    }
+} -body {
+    list [catch {nonesuch} result] $result $::unknown_count
+} -cleanup {
+    rename unknown ""
+    unset ::unknown_count
+} -ok {1 {invalid command name "alsomissing"} 1}
+
+test unknown-3.3 {unknown is called again after an error} -setup {
+    set ::unknown_count 0
+    proc unknown {args} {
+        incr ::unknown_count
+        error "failed"
+    }
+} -body {
+    catch {nonesuch}
+    catch {nonesuch}
+    set ::unknown_count
+} -cleanup {
+    rename unknown ""
+    unset ::unknown_count
+} -ok {2}
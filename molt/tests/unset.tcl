@@ -2,9 +2,10 @@
 
 test unset-1.1 {unset, various no-ops} {
     unset
-    unset a
+    unset -nocomplain
     unset -nocomplain a
-    unset -ncomplain -- a
+    unset -nocomplain -- a
+    unset -nocomplain a(1)
 } -ok {}
 
 test unset-1.2 {unset, one variable} {
@@ -35,3 +36,49 @@ test unset-1.5 {unset, array element} {
     unset a(2)
     set a(2)
 } -error {can't read "a(2)": no such element in array}
+
+test unset-2.1 {unset, no such variable} {
+    unset a
+} -error {can't unset "a": no such variable}
+
+test unset-2.2 {unset, no such element} {
+    set a(1) one
+    unset a(2)
+} -error {can't unset "a(2)": no such element in array}
+
+test unset-2.3 {unset, element of scalar} {
+    set a 1
+    unset a(1)
+} -error {can't unset "a(1)": variable isn't array}
+
+test unset-2.4 {unset, element of no such variable} {
+    unset a(1)
+} -error {can't unset "a(1)": no such variable}
+
+test unset-2.5 {unset, stops at first missing variable} {
+    set a 1
+    set c 3
+    catch {unset a b c} result
+    list $result [info exists a] [info exists c]
+} -ok {{can't unset "b": no such variable} 0 1}
+
+test unset-2.6 {unset, options} {
+    set -nocomplain 1
+    set -- 2
+    unset -- -nocomplain
+    unset -nocomplain -- --
+    list [info exists -nocomplain] [info exists --]
+} -ok {0 0}
+
+test unset-2.7 {unset, linked variable} -setup {
+    proc unsetGlobal {} {
+        global gvar
+        unset gvar
+    }
+} -body {
+    set ::gvar 1
+    unsetGlobal
+    list [info exists ::gvar] [catch {unsetGlobal} result] $result
+} -cleanup {
+    rename unsetGlobal ""
+} -ok {0 1 {can't unset "gvar": no such variable}}
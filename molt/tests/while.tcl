@@ -60,3 +60,15 @@ test while-5.1 {while with continue} {
     }
     set list
 } -ok {1 3 5 7 9}
+
+test while-6.1 {while with boolean string condition} {
+    set i 0
+    while {"0"} {
+        incr i
+    }
+    set i
+} -ok {0}
+
+test while-6.2 {while with non-boolean string condition} {
+    while {"abc"} {}
+} -error {expected boolean but got "abc"}